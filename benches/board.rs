@@ -0,0 +1,90 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ggez::graphics::Color;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tetris::bitboard::BitBoard;
+use tetris::board::{GameBoard, Playfield};
+use tetris::constants::{GARBAGE_COLOR, GRID_HEIGHT, GRID_WIDTH};
+use tetris::{Tetromino, TetrominoType};
+
+/// A half-filled board with ragged columns, like a board in the middle of a game
+fn midgame_board() -> GameBoard {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut board = GameBoard::new();
+    for y in GRID_HEIGHT as usize / 2..GRID_HEIGHT as usize {
+        let hole = rng.gen_range(0..GRID_WIDTH as usize);
+        for x in (0..GRID_WIDTH as usize).filter(|&x| x != hole && rng.gen_bool(0.8)) {
+            board[y][x] = GARBAGE_COLOR;
+        }
+    }
+    board
+}
+
+/// A board whose bottom four rows are complete
+fn clearable_board() -> GameBoard {
+    let mut board = midgame_board();
+    for y in GRID_HEIGHT as usize - 4..GRID_HEIGHT as usize {
+        board[y].fill(Color::RED);
+    }
+    board
+}
+
+/// Every piece in every orientation at every column of the top half
+fn probes() -> Vec<Tetromino> {
+    let mut probes = Vec::new();
+    for kind in TetrominoType::ALL {
+        let mut piece = Tetromino::new(kind);
+        for _ in 0..4 {
+            for x in -1..GRID_WIDTH {
+                for y in 0..GRID_HEIGHT / 2 {
+                    piece.position = glam::Vec2::new(x as f32, y as f32);
+                    probes.push(piece.clone());
+                }
+            }
+            piece.rotate();
+        }
+    }
+    probes
+}
+
+/// Collision checks, drops, clones and line clears on the color grid and on the bitboard
+fn bench_boards(c: &mut Criterion) {
+    let board = midgame_board();
+    let bits = BitBoard::from(&board);
+    let probes = probes();
+
+    let mut group = c.benchmark_group("collides");
+    group.bench_function(BenchmarkId::new("GameBoard", probes.len()), |b| {
+        b.iter(|| probes.iter().filter(|piece| board.collides(piece)).count())
+    });
+    group.bench_function(BenchmarkId::new("BitBoard", probes.len()), |b| {
+        b.iter(|| probes.iter().filter(|piece| bits.collides(piece)).count())
+    });
+    group.finish();
+
+    // Each probe falls to the stack or the floor, as the ghost piece and the bot do every move
+    let mut group = c.benchmark_group("drop_position");
+    let free: Vec<&Tetromino> = probes.iter().filter(|piece| !board.collides(piece)).collect();
+    group.bench_function(BenchmarkId::new("GameBoard", free.len()), |b| {
+        b.iter(|| free.iter().map(|piece| board.drop_position(piece).position.y).sum::<f32>())
+    });
+    group.bench_function(BenchmarkId::new("BitBoard", free.len()), |b| {
+        b.iter(|| free.iter().map(|piece| bits.drop_position(piece).position.y).sum::<f32>())
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("clone");
+    group.bench_function("GameBoard", |b| b.iter(|| black_box(&board).clone()));
+    group.bench_function("BitBoard", |b| b.iter(|| black_box(&bits).clone()));
+    group.finish();
+
+    let clearable = clearable_board();
+    let clearable_bits = BitBoard::from(&clearable);
+    let mut group = c.benchmark_group("clear_lines");
+    group.bench_function("GameBoard", |b| b.iter(|| clearable.clone().clear_lines()));
+    group.bench_function("BitBoard", |b| b.iter(|| clearable_bits.clone().clear_lines()));
+    group.finish();
+}
+
+criterion_group!(benches, bench_boards);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use tetris::ai::{beam_search, AiController, Weights, DEFAULT_BEAM_WIDTH};
+use tetris::game::Game;
+
+/// Pieces placed in each playout
+const PLAYOUT_PIECES: u32 = 100;
+
+/// Full games played by the bot, reported in pieces per second
+fn bench_playouts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("playout");
+    group.throughput(Throughput::Elements(PLAYOUT_PIECES as u64));
+    group.sample_size(20);
+
+    // The headless core driven by the controller, one step per input as `game::run` does
+    group.bench_function("AiController", |b| {
+        b.iter(|| {
+            let mut game = Game::with_seed(1);
+            let mut controller = AiController::default();
+            while game.pieces() < PLAYOUT_PIECES && !game.is_game_over() {
+                game.step(&mut controller);
+            }
+            game.lines_cleared()
+        })
+    });
+    group.finish();
+}
+
+/// Planning one piece with the deepest search the bot uses, which has to fit in a frame
+fn bench_search(c: &mut Criterion) {
+    let mut game = Game::with_seed(1);
+    let mut controller = AiController::default();
+    while game.pieces() < 20 {
+        game.step(&mut controller);
+    }
+    let snapshot = game.snapshot().unwrap();
+    c.bench_function("beam_search depth 4", |b| b.iter(|| beam_search(&snapshot, 4, DEFAULT_BEAM_WIDTH, &Weights::default())));
+}
+
+criterion_group!(benches, bench_playouts, bench_search);
+criterion_main!(benches);
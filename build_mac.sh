@@ -64,9 +64,14 @@ cp target/release/tetris TetrisApp.app/Contents/MacOS/
 echo "Generating sound files..."
 python3 generate_sounds.py
 
-# Copy sound files to Resources
-echo "Copying sound files..."
+# Copy sound files, themes, openers and scripts to Resources
+echo "Copying sound files, themes, openers and scripts..."
 cp -r sounds/ TetrisApp.app/Contents/Resources/
+cp -r themes/ TetrisApp.app/Contents/Resources/
+cp -r openers/ TetrisApp.app/Contents/Resources/
+if [ -d scripts ]; then
+    cp -r scripts/ TetrisApp.app/Contents/Resources/
+fi
 
 # Generate icons
 echo "Generating icons..."
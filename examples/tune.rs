@@ -0,0 +1,30 @@
+//! Tunes the bot's heuristic weights by self-play and writes the best ones where the game loads them
+//!
+//! `cargo run --release --example tune -- [generations] [output file]`
+
+use std::path::PathBuf;
+use tetris::ai::{self, Weights};
+use tetris::tuning::{self, Tuner};
+
+/// Generations run when none are given
+const DEFAULT_GENERATIONS: u32 = 20;
+
+fn main() -> std::io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let generations = match args.next() {
+        Some(arg) => arg.parse().map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("bad generation count {:?}", arg)))?,
+        None => DEFAULT_GENERATIONS,
+    };
+    let path = args.next().map_or_else(ai::weights_path, PathBuf::from);
+
+    // Tuning starts from the weights in use, so a run starts out no worse than them
+    let current = Weights::load(&path);
+    let mut tuner = Tuner::new(rand::random(), tuning::POPULATION, tuning::GAMES, tuning::PIECES, current);
+    while tuner.generation() < generations {
+        let best = tuner.step();
+        println!("generation {:>3}: {:.0} points, {:?}", tuner.generation(), best.fitness, best.weights);
+        best.weights.save(&path)?;
+    }
+    println!("weights written to {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,471 @@
+import os
+import struct
+import sys
+
+# Every glyph is drawn on a 5x7 pixel grid sitting on the baseline.
+# Lowercase letters with descenders get an eighth row below the baseline.
+GLYPHS = {
+    ' ': [],
+    '!': ["..#..", "..#..", "..#..", "..#..", "..#..", ".....", "..#.."],
+    '"': [".#.#.", ".#.#.", ".#.#.", ".....", ".....", ".....", "....."],
+    '#': [".#.#.", ".#.#.", "#####", ".#.#.", "#####", ".#.#.", ".#.#."],
+    '$': ["..#..", ".####", "#.#..", ".###.", "..#.#", "####.", "..#.."],
+    '%': ["##...", "##..#", "...#.", "..#..", ".#...", "#..##", "...##"],
+    '&': [".##..", "#..#.", "#.#..", ".#...", "#.#.#", "#..#.", ".##.#"],
+    "'": ["..#..", "..#..", "..#..", ".....", ".....", ".....", "....."],
+    '(': ["...#.", "..#..", ".#...", ".#...", ".#...", "..#..", "...#."],
+    ')': [".#...", "..#..", "...#.", "...#.", "...#.", "..#..", ".#..."],
+    '*': [".....", "..#..", "#.#.#", ".###.", "#.#.#", "..#..", "....."],
+    '+': [".....", "..#..", "..#..", "#####", "..#..", "..#..", "....."],
+    ',': [".....", ".....", ".....", ".....", "..##.", "..#..", ".#..."],
+    '-': [".....", ".....", ".....", "#####", ".....", ".....", "....."],
+    '.': [".....", ".....", ".....", ".....", ".....", ".##..", ".##.."],
+    '/': [".....", "....#", "...#.", "..#..", ".#...", "#....", "....."],
+    '0': [".###.", "#...#", "#..##", "#.#.#", "##..#", "#...#", ".###."],
+    '1': ["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."],
+    '2': [".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####"],
+    '3': ["#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###."],
+    '4': ["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."],
+    '5': ["#####", "#....", "####.", "....#", "....#", "#...#", ".###."],
+    '6': ["..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###."],
+    '7': ["#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#..."],
+    '8': [".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###."],
+    '9': [".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##.."],
+    ':': [".....", ".##..", ".##..", ".....", ".##..", ".##..", "....."],
+    ';': [".....", ".##..", ".##..", ".....", ".##..", "..#..", ".#..."],
+    '<': ["...#.", "..#..", ".#...", "#....", ".#...", "..#..", "...#."],
+    '=': [".....", ".....", "#####", ".....", "#####", ".....", "....."],
+    '>': [".#...", "..#..", "...#.", "....#", "...#.", "..#..", ".#..."],
+    '?': [".###.", "#...#", "....#", "...#.", "..#..", ".....", "..#.."],
+    '@': [".###.", "#...#", "....#", ".##.#", "#.#.#", "#.#.#", ".###."],
+    'A': [".###.", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"],
+    'B': ["####.", "#...#", "#...#", "####.", "#...#", "#...#", "####."],
+    'C': [".###.", "#...#", "#....", "#....", "#....", "#...#", ".###."],
+    'D': ["###..", "#..#.", "#...#", "#...#", "#...#", "#..#.", "###.."],
+    'E': ["#####", "#....", "#....", "####.", "#....", "#....", "#####"],
+    'F': ["#####", "#....", "#....", "####.", "#....", "#....", "#...."],
+    'G': [".###.", "#...#", "#....", "#.###", "#...#", "#...#", ".####"],
+    'H': ["#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"],
+    'I': [".###.", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."],
+    'J': ["..###", "...#.", "...#.", "...#.", "...#.", "#..#.", ".##.."],
+    'K': ["#...#", "#..#.", "#.#..", "##...", "#.#..", "#..#.", "#...#"],
+    'L': ["#....", "#....", "#....", "#....", "#....", "#....", "#####"],
+    'M': ["#...#", "##.##", "#.#.#", "#.#.#", "#...#", "#...#", "#...#"],
+    'N': ["#...#", "#...#", "##..#", "#.#.#", "#..##", "#...#", "#...#"],
+    'O': [".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."],
+    'P': ["####.", "#...#", "#...#", "####.", "#....", "#....", "#...."],
+    'Q': [".###.", "#...#", "#...#", "#...#", "#.#.#", "#..#.", ".##.#"],
+    'R': ["####.", "#...#", "#...#", "####.", "#.#..", "#..#.", "#...#"],
+    'S': [".####", "#....", "#....", ".###.", "....#", "....#", "####."],
+    'T': ["#####", "..#..", "..#..", "..#..", "..#..", "..#..", "..#.."],
+    'U': ["#...#", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."],
+    'V': ["#...#", "#...#", "#...#", "#...#", "#...#", ".#.#.", "..#.."],
+    'W': ["#...#", "#...#", "#...#", "#.#.#", "#.#.#", "#.#.#", ".#.#."],
+    'X': ["#...#", "#...#", ".#.#.", "..#..", ".#.#.", "#...#", "#...#"],
+    'Y': ["#...#", "#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."],
+    'Z': ["#####", "....#", "...#.", "..#..", ".#...", "#....", "#####"],
+    '[': [".###.", ".#...", ".#...", ".#...", ".#...", ".#...", ".###."],
+    '\\': [".....", "#....", ".#...", "..#..", "...#.", "....#", "....."],
+    ']': [".###.", "...#.", "...#.", "...#.", "...#.", "...#.", ".###."],
+    '^': ["..#..", ".#.#.", "#...#", ".....", ".....", ".....", "....."],
+    '_': [".....", ".....", ".....", ".....", ".....", ".....", "#####"],
+    '`': [".#...", "..#..", "...#.", ".....", ".....", ".....", "....."],
+    'a': [".....", ".....", ".###.", "....#", ".####", "#...#", ".####"],
+    'b': ["#....", "#....", "#.##.", "##..#", "#...#", "#...#", "####."],
+    'c': [".....", ".....", ".###.", "#....", "#....", "#...#", ".###."],
+    'd': ["....#", "....#", ".##.#", "#..##", "#...#", "#...#", ".####"],
+    'e': [".....", ".....", ".###.", "#...#", "#####", "#....", ".###."],
+    'f': ["..##.", ".#..#", ".#...", "###..", ".#...", ".#...", ".#..."],
+    'g': [".....", ".....", ".####", "#...#", "#...#", ".####", "....#", ".###."],
+    'h': ["#....", "#....", "#.##.", "##..#", "#...#", "#...#", "#...#"],
+    'i': ["..#..", ".....", ".##..", "..#..", "..#..", "..#..", ".###."],
+    'j': ["...#.", ".....", "..##.", "...#.", "...#.", "...#.", "#..#.", ".##.."],
+    'k': ["#....", "#....", "#..#.", "#.#..", "##...", "#.#..", "#..#."],
+    'l': [".##..", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."],
+    'm': [".....", ".....", "##.#.", "#.#.#", "#.#.#", "#...#", "#...#"],
+    'n': [".....", ".....", "#.##.", "##..#", "#...#", "#...#", "#...#"],
+    'o': [".....", ".....", ".###.", "#...#", "#...#", "#...#", ".###."],
+    'p': [".....", ".....", "####.", "#...#", "#...#", "####.", "#....", "#...."],
+    'q': [".....", ".....", ".####", "#...#", "#...#", ".####", "....#", "....#"],
+    'r': [".....", ".....", "#.##.", "##..#", "#....", "#....", "#...."],
+    's': [".....", ".....", ".####", "#....", ".###.", "....#", "####."],
+    't': [".#...", ".#...", "###..", ".#...", ".#...", ".#..#", "..##."],
+    'u': [".....", ".....", "#...#", "#...#", "#...#", "#..##", ".##.#"],
+    'v': [".....", ".....", "#...#", "#...#", "#...#", ".#.#.", "..#.."],
+    'w': [".....", ".....", "#...#", "#...#", "#.#.#", "#.#.#", ".#.#."],
+    'x': [".....", ".....", "#...#", ".#.#.", "..#..", ".#.#.", "#...#"],
+    'y': [".....", ".....", "#...#", "#...#", "#...#", ".####", "....#", ".###."],
+    'z': [".....", ".....", "#####", "...#.", "..#..", ".#...", "#####"],
+    '{': ["...#.", "..#..", "..#..", ".#...", "..#..", "..#..", "...#."],
+    '|': ["..#..", "..#..", "..#..", "..#..", "..#..", "..#..", "..#.."],
+    '}': [".#...", "..#..", "..#..", "...#.", "..#..", "..#..", ".#..."],
+    '~': [".....", ".....", ".#...", "#.#.#", "...#.", ".....", "....."],
+    '©': [".###.", "#...#", "#.###", "#.#.#", "#.###", "#...#", ".###."],
+}
+
+UNIT = 128              # Font units per pixel
+UNITS_PER_EM = 8 * UNIT  # Seven rows above the baseline plus one below
+ADVANCE = 6 * UNIT       # Five pixels of glyph and one of spacing
+ASCENT = 7 * UNIT
+DESCENT = -UNIT
+LINE_GAP = UNIT
+
+
+def glyph_rects(rows):
+    """Merge each row's runs of set pixels into rectangles (x0, y0, x1, y1) in font units."""
+    rects = []
+    for row_index, row in enumerate(rows):
+        top = (7 - row_index) * UNIT
+        x = 0
+        while x < len(row):
+            if row[x] == '#':
+                start = x
+                while x < len(row) and row[x] == '#':
+                    x += 1
+                rects.append((start * UNIT, top - UNIT, x * UNIT, top))
+            else:
+                x += 1
+    return rects
+
+
+def encode_glyph(rects):
+    """Encode rectangles as a simple TrueType glyph with one clockwise contour each."""
+    if not rects:
+        return b''
+    points = []
+    end_points = []
+    for x0, y0, x1, y1 in rects:
+        points.extend([(x0, y0), (x0, y1), (x1, y1), (x1, y0)])
+        end_points.append(len(points) - 1)
+    xs = [p[0] for p in points]
+    ys = [p[1] for p in points]
+    data = struct.pack('>hhhhh', len(rects), min(xs), min(ys), max(xs), max(ys))
+    data += struct.pack('>%dH' % len(end_points), *end_points)
+    data += struct.pack('>H', 0)  # No hinting instructions
+    data += bytes([0x01] * len(points))  # Every point is on the curve, with 16-bit coordinates
+    previous = 0
+    for x in xs:
+        data += struct.pack('>h', x - previous)
+        previous = x
+    previous = 0
+    for y in ys:
+        data += struct.pack('>h', y - previous)
+        previous = y
+    # Glyphs are padded to four bytes to keep the long loca offsets aligned
+    return data + b'\0' * (-len(data) % 4)
+
+
+# The fallback font draws the characters the pixel font lacks, for names typed in other scripts.
+# Its glyphs come from GNU Unifont, whose hex files give each character of the Basic Multilingual
+# Plane as 16 rows of 8 or 16 pixels; rows 0 to 13 sit above the baseline and the last two below.
+FALLBACK_UNIT = 16                     # Font units per Unifont pixel, small enough for one-byte deltas
+FALLBACK_ASCENT = 14 * FALLBACK_UNIT   # Sixteen rows fill the same height as the pixel font's eight
+FALLBACK_DESCENT = -2 * FALLBACK_UNIT
+
+# Only the scripts names are typed in are kept, since all of Unifont would add 10 MB to the game
+FALLBACK_RANGES = [
+    (0x0020, 0x007E),  # Basic Latin
+    (0x00A0, 0x024F),  # Latin-1 Supplement and Latin Extended-A and B
+    (0x0300, 0x036F),  # Combining accents
+    (0x0370, 0x052F),  # Greek and Cyrillic
+    (0x1E00, 0x1EFF),  # Latin Extended Additional, for Vietnamese
+    (0x3000, 0x30FF),  # CJK punctuation, hiragana and katakana
+    (0xFFFD, 0xFFFD),  # The replacement character
+]
+
+
+def double_byte_characters(encoding, leads):
+    """The characters of a double-byte encoding's rows `leads`, each of 94 cells from 0xA1."""
+    characters = set()
+    for lead in leads:
+        for cell in range(0xA1, 0xFF):
+            try:
+                characters.update(bytes([lead, cell]).decode(encoding))
+            except UnicodeDecodeError:
+                pass
+    return characters
+
+
+def fallback_codes():
+    codes = {code for first, last in FALLBACK_RANGES for code in range(first, last + 1)}
+    # The ideographs in both the first level of JIS X 0208 and of GB 2312, the ones most used in
+    # Japanese and in Chinese, and the Hangul syllables of KS X 1001, the ones used in Korean
+    ideographs = double_byte_characters('euc_jp', range(0xB0, 0xD0)) & double_byte_characters('gb2312', range(0xB0, 0xD8))
+    syllables = double_byte_characters('euc_kr', range(0xB0, 0xC9))
+    return codes | {ord(ch) for ch in ideographs | syllables}
+
+
+def read_hex(path):
+    codes = fallback_codes()
+    glyphs = {}
+    with open(path) as hex_file:
+        for line in hex_file:
+            code, bits = line.strip().split(':')
+            code = int(code, 16)
+            if code in codes:
+                width = len(bits) // 4
+                rows = [int(bits[row * width // 4:(row + 1) * width // 4], 16) for row in range(16)]
+                glyphs[code] = (width, rows)
+    return glyphs
+
+
+def bitmap_contours(width, rows):
+    # Every pixel edge with an empty pixel on its other side, running clockwise around the filled area
+    pixels = {(x, 13 - row) for row, bits in enumerate(rows) for x in range(width) if bits >> (width - 1 - x) & 1}
+    edges = {}
+    for x, y in pixels:
+        if (x - 1, y) not in pixels:
+            edges.setdefault((x, y), []).append((x, y + 1))
+        if (x, y + 1) not in pixels:
+            edges.setdefault((x, y + 1), []).append((x + 1, y + 1))
+        if (x + 1, y) not in pixels:
+            edges.setdefault((x + 1, y + 1), []).append((x + 1, y))
+        if (x, y - 1) not in pixels:
+            edges.setdefault((x + 1, y), []).append((x, y))
+    # Edges are chained into closed loops, keeping only the corners where a loop turns
+    contours = []
+    while edges:
+        start = min(edges)
+        loop = [start]
+        point = start
+        while True:
+            following = edges[point].pop()
+            if not edges[point]:
+                del edges[point]
+            if following == start:
+                break
+            loop.append(following)
+            point = following
+        corners = [
+            p for i, p in enumerate(loop)
+            if (loop[i - 1][0] != loop[(i + 1) % len(loop)][0]) and (loop[i - 1][1] != loop[(i + 1) % len(loop)][1])
+        ]
+        contours.append([(x * FALLBACK_UNIT, y * FALLBACK_UNIT) for x, y in corners])
+    return contours
+
+
+def encode_coordinates(deltas, short_flag, same_flag):
+    # One-byte deltas keep their sign in the flag; zero deltas take no bytes at all
+    flags = []
+    data = b''
+    for delta in deltas:
+        if delta == 0:
+            flags.append(same_flag)
+        elif -255 <= delta <= 255:
+            flags.append(short_flag | (same_flag if delta > 0 else 0))
+            data += struct.pack('>B', abs(delta))
+        else:
+            flags.append(0)
+            data += struct.pack('>h', delta)
+    return flags, data
+
+
+def encode_contours(contours):
+    if not contours:
+        return b''
+    points = [p for contour in contours for p in contour]
+    end_points = []
+    for contour in contours:
+        end_points.append((end_points[-1] if end_points else -1) + len(contour))
+    xs = [p[0] for p in points]
+    ys = [p[1] for p in points]
+    data = struct.pack('>hhhhh', len(contours), min(xs), min(ys), max(xs), max(ys))
+    data += struct.pack('>%dH' % len(end_points), *end_points)
+    data += struct.pack('>H', 0)  # No hinting instructions
+    x_flags, x_data = encode_coordinates([x - p for x, p in zip(xs, [0] + xs)], 0x02, 0x10)
+    y_flags, y_data = encode_coordinates([y - p for y, p in zip(ys, [0] + ys)], 0x04, 0x20)
+    data += bytes(0x01 | x | y for x, y in zip(x_flags, y_flags))
+    data += x_data + y_data
+    return data + b'\0' * (-len(data) % 4)
+
+
+def checksum(data):
+    data += b'\0' * (-len(data) % 4)
+    return sum(struct.unpack('>%dI' % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def build_font():
+    chars = sorted(GLYPHS)
+    # Glyph 0 is the hollow box shown for missing characters
+    notdef = ["#####", "#...#", "#...#", "#...#", "#...#", "#...#", "#####"]
+    glyphs = [glyph_rects(notdef)] + [glyph_rects(GLYPHS[c]) for c in chars]
+
+    glyf = b''
+    loca = []
+    for rects in glyphs:
+        loca.append(len(glyf))
+        glyf += encode_glyph(rects)
+    loca.append(len(glyf))
+
+    num_glyphs = len(glyphs)
+    max_points = max(len(rects) * 4 for rects in glyphs)
+    max_contours = max(len(rects) for rects in glyphs)
+
+    head = struct.pack(
+        '>IIIIHHqqhhhhHHhhh',
+        0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0b1011, UNITS_PER_EM,
+        0, 0, 0, DESCENT, 5 * UNIT, ASCENT, 0, 8, 2, 1, 0,
+    )
+    hhea = struct.pack(
+        '>IhhhHhhhhhhhhhhhH',
+        0x00010000, ASCENT, DESCENT, LINE_GAP, ADVANCE, 0, UNIT, 5 * UNIT,
+        1, 0, 0, 0, 0, 0, 0, 0, num_glyphs,
+    )
+    maxp = struct.pack(
+        '>IHHHHHHHHHHHHHH',
+        0x00010000, num_glyphs, max_points, max_contours, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0,
+    )
+    hmtx = b''.join(
+        struct.pack('>Hh', ADVANCE, min((r[0] for r in rects), default=0)) for rects in glyphs
+    )
+
+    # One cmap segment per character, then the required closing segment
+    codes = [ord(c) for c in chars] + [0xFFFF]
+    seg_count = len(codes)
+    search_range = 2 * 2 ** (seg_count.bit_length() - 1)
+    subtable = struct.pack(
+        '>HHHHHHH', 4, 0, 0, seg_count * 2, search_range,
+        seg_count.bit_length() - 1, seg_count * 2 - search_range,
+    )
+    subtable += struct.pack('>%dH' % seg_count, *codes) + struct.pack('>H', 0)
+    subtable += struct.pack('>%dH' % seg_count, *codes)
+    deltas = [(i + 1 - code) & 0xFFFF for i, code in enumerate(codes[:-1])] + [1]
+    subtable += struct.pack('>%dH' % seg_count, *deltas)
+    subtable += struct.pack('>%dH' % seg_count, *([0] * seg_count))
+    subtable = subtable[:2] + struct.pack('>H', len(subtable)) + subtable[4:]
+    cmap = struct.pack('>HHHHI', 0, 1, 3, 1, 12) + subtable
+
+    post = struct.pack('>IIhhIIIII', 0x00030000, 0, -UNIT, UNIT // 2, 1, 0, 0, 0, 0)
+
+    return font_file({
+        b'cmap': cmap,
+        b'glyf': glyf,
+        b'head': head,
+        b'hhea': hhea,
+        b'hmtx': hmtx,
+        b'loca': struct.pack('>%dI' % len(loca), *loca),
+        b'maxp': maxp,
+        b'name': name_table("Tetris Pixel"),
+        b'post': post,
+    })
+
+
+def build_fallback_font(hex_glyphs):
+    codes = sorted(hex_glyphs)
+    # Glyph 0 is Unifont's own replacement character
+    notdef = hex_glyphs.get(0xFFFD, (8, [0] * 16))
+    bitmaps = [notdef] + [hex_glyphs[code] for code in codes]
+
+    # Glyphs are collected in lists, since adding to one long byte string would copy it every time
+    glyf = []
+    loca = [0]
+    hmtx = []
+    max_points = 0
+    max_contours = 0
+    for width, rows in bitmaps:
+        contours = bitmap_contours(width, rows)
+        glyf.append(encode_contours(contours))
+        loca.append(loca[-1] + len(glyf[-1]))
+        hmtx.append(struct.pack('>Hh', width * FALLBACK_UNIT, min((x for c in contours for x, _ in c), default=0)))
+        max_points = max(max_points, sum(len(c) for c in contours))
+        max_contours = max(max_contours, len(contours))
+
+    num_glyphs = len(bitmaps)
+    units_per_em = FALLBACK_ASCENT - FALLBACK_DESCENT
+    head = struct.pack(
+        '>IIIIHHqqhhhhHHhhh',
+        0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0b1011, units_per_em,
+        0, 0, 0, FALLBACK_DESCENT, 16 * FALLBACK_UNIT, FALLBACK_ASCENT, 0, 8, 2, 1, 0,
+    )
+    hhea = struct.pack(
+        '>IhhhHhhhhhhhhhhhH',
+        0x00010000, FALLBACK_ASCENT, FALLBACK_DESCENT, 2 * FALLBACK_UNIT, 16 * FALLBACK_UNIT, 0, 0,
+        16 * FALLBACK_UNIT, 1, 0, 0, 0, 0, 0, 0, 0, num_glyphs,
+    )
+    maxp = struct.pack(
+        '>IHHHHHHHHHHHHHH',
+        0x00010000, num_glyphs, max_points, max_contours, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0,
+    )
+
+    # Runs of consecutive characters share a segment, since one per character would overflow the table
+    segments = []
+    for glyph_id, code in enumerate(codes, start=1):
+        if segments and segments[-1][1] == code - 1 and segments[-1][2] == (glyph_id - code) & 0xFFFF:
+            segments[-1][1] = code
+        else:
+            segments.append([code, code, (glyph_id - code) & 0xFFFF])
+    segments.append([0xFFFF, 0xFFFF, 1])
+    seg_count = len(segments)
+    search_range = 2 * 2 ** (seg_count.bit_length() - 1)
+    subtable = struct.pack(
+        '>HHHHHHH', 4, 0, 0, seg_count * 2, search_range,
+        seg_count.bit_length() - 1, seg_count * 2 - search_range,
+    )
+    subtable += struct.pack('>%dH' % seg_count, *(end for _, end, _ in segments)) + struct.pack('>H', 0)
+    subtable += struct.pack('>%dH' % seg_count, *(start for start, _, _ in segments))
+    subtable += struct.pack('>%dH' % seg_count, *(delta for _, _, delta in segments))
+    subtable += struct.pack('>%dH' % seg_count, *([0] * seg_count))
+    subtable = subtable[:2] + struct.pack('>H', len(subtable)) + subtable[4:]
+    cmap = struct.pack('>HHHHI', 0, 1, 3, 1, 12) + subtable
+
+    post = struct.pack('>IIhhIIIII', 0x00030000, 0, FALLBACK_DESCENT, FALLBACK_UNIT, 1, 0, 0, 0, 0)
+
+    return font_file({
+        b'cmap': cmap,
+        b'glyf': b''.join(glyf),
+        b'head': head,
+        b'hhea': hhea,
+        b'hmtx': b''.join(hmtx),
+        b'loca': struct.pack('>%dI' % len(loca), *loca),
+        b'maxp': maxp,
+        b'name': name_table("Tetris Fallback"),
+        b'post': post,
+    })
+
+
+def name_table(family):
+    names = {1: family, 2: "Regular", 4: family, 6: family.replace(' ', '')}
+    strings = b''
+    records = b''
+    for name_id, value in names.items():
+        encoded = value.encode('utf-16-be')
+        records += struct.pack('>HHHHHH', 3, 1, 0x409, name_id, len(encoded), len(strings))
+        strings += encoded
+    return struct.pack('>HHH', 0, len(names), 6 + len(records)) + records + strings
+
+
+def font_file(tables):
+    num_tables = len(tables)
+    entry_selector = num_tables.bit_length() - 1
+    search_range = 16 * 2 ** entry_selector
+    font = struct.pack('>IHHHH', 0x00010000, num_tables, search_range, entry_selector,
+                       num_tables * 16 - search_range)
+    offset = 12 + 16 * num_tables
+    directory = b''
+    body = []
+    for tag, data in tables.items():
+        directory += struct.pack('>4sIII', tag, checksum(data), offset + sum(map(len, body)), len(data))
+        body.append(data + b'\0' * (-len(data) % 4))
+    font += directory + b''.join(body)
+
+    # The head table stores a checksum that makes the whole file sum to a magic number
+    adjustment = (0xB1B0AFBA - checksum(font)) & 0xFFFFFFFF
+    head_offset = font.index(tables[b'head'])
+    return font[:head_offset + 8] + struct.pack('>I', adjustment) + font[head_offset + 12:]
+
+
+def generate_main():
+    """Generate the bundled pixel font"""
+    os.makedirs(os.path.join("assets", "fonts"), exist_ok=True)
+    with open(os.path.join("assets", "fonts", "pixel.ttf"), 'wb') as font_file:
+        font_file.write(build_font())
+    # The fallback font is only rebuilt when given a Unifont hex file, e.g. unifont.hex from unifoundry.com
+    if len(sys.argv) > 1:
+        with open(os.path.join("assets", "fonts", "fallback.ttf"), 'wb') as font_file:
+            font_file.write(build_fallback_font(read_hex(sys.argv[1])))
+
+    print("Font generated successfully!")
+
+
+if __name__ == "__main__":
+    generate_main()
@@ -1,4 +1,6 @@
 import os
+import shutil
+import subprocess
 import wave
 import struct
 import math
@@ -87,6 +89,14 @@ def create_game_over_sound():
         samples.extend(generate_square_wave(freq, 0.1, 0.4))
     return samples
 
+def create_level_up_sound():
+    """Create a rising fanfare for reaching the next level."""
+    # GameBoy-style major arpeggio ending on a held octave
+    samples = []
+    for freq, duration in [(523.25, 0.07), (659.25, 0.07), (783.99, 0.07), (1046.50, 0.2)]:  # C5, E5, G5, C6
+        samples.extend(generate_square_wave(freq, duration, 0.3))
+    return samples
+
 def create_tetris_sound():
     """Creates a special sound for clearing 4 rows at once (Tetris)"""
     samples = []
@@ -365,6 +375,30 @@ def create_background_music():
     # Loop the sequence 2 times (it's twice as long now)
     return mixed_samples * 2
 
+def compress_sounds():
+    """Convert the WAV files to Ogg Vorbis if oggenc or ffmpeg is installed.
+
+    The game loads .ogg before .flac and .wav, so the uncompressed files are removed
+    once converted; without an encoder the WAV files are kept as they are.
+    """
+    if shutil.which("oggenc"):
+        encode = lambda wav, ogg: ["oggenc", "--quiet", "--quality", "4", "--output", ogg, wav]
+    elif shutil.which("ffmpeg"):
+        encode = lambda wav, ogg: ["ffmpeg", "-loglevel", "error", "-y", "-i", wav, "-c:a", "libvorbis", "-q:a", "4", ogg]
+    else:
+        print("No oggenc or ffmpeg found, keeping WAV files")
+        return
+
+    for filename in sorted(os.listdir("sounds")):
+        if not filename.endswith(".wav"):
+            continue
+        wav = os.path.join("sounds", filename)
+        ogg = wav[:-len(".wav")] + ".ogg"
+        if subprocess.run(encode(wav, ogg)).returncode == 0:
+            os.remove(wav)
+        else:
+            print(f"Could not compress {filename}, keeping the WAV file")
+
 def generate_main():
     """Generate all sound effects"""
     # Generate and save all sound effects
@@ -374,7 +408,9 @@ def generate_main():
     save_wave_file('clear.wav', create_clear_sound())
     save_wave_file('tetris.wav', create_tetris_sound())
     save_wave_file('game_over.wav', create_game_over_sound())
+    save_wave_file('level_up.wav', create_level_up_sound())
     save_wave_file('background.wav', create_background_music())
+    compress_sounds()
     
     print("Sound effects generated successfully!")
 
@@ -0,0 +1,680 @@
+use crate::bitboard::BitBoard;
+use crate::board::{GameBoard, Playfield};
+use crate::config;
+use crate::constants::{AI_WEIGHTS_FILE, GARBAGE_COLOR, NEXT_QUEUE_SIZE};
+use crate::game::{Controller, Move, Snapshot};
+use crate::garbage::{self, AttackTracker, GarbageHoles, GarbageQueue};
+use crate::rotation::{RotationSystem, SRS};
+use crate::tetromino::Tetromino;
+use ggez::graphics::Color;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::iter;
+use std::path::{Path, PathBuf};
+
+/// Seconds between the bot's inputs; lower values make the bot play faster
+pub const DEFAULT_MOVE_DELAY: f32 = 0.08;
+
+/// Positions the bot's search keeps at each depth; wider beams find more but cost more
+pub const DEFAULT_BEAM_WIDTH: usize = 8;
+
+/// How strong the computer opponent plays: how fast it places pieces, how often it slips and how far it looks ahead
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Insane];
+
+    /// Name shown in the settings screen
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Insane => "insane",
+        }
+    }
+
+    /// Moves `steps` places through `ALL`, wrapping around
+    pub fn cycle(self, steps: i32) -> Self {
+        let index = Self::ALL.iter().position(|&difficulty| difficulty == self).unwrap_or(0) as i32;
+        Self::ALL[(index + steps).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+
+    /// Seconds between the bot's inputs
+    pub fn move_delay(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.2,
+            Difficulty::Normal => 0.12,
+            Difficulty::Hard => DEFAULT_MOVE_DELAY,
+            Difficulty::Insane => 0.03,
+        }
+    }
+
+    /// Most pieces the bot places per second, like a human's speed; `None` plays as fast as its inputs allow
+    pub fn max_pps(self) -> Option<f32> {
+        match self {
+            Difficulty::Easy => Some(0.8),
+            Difficulty::Normal => Some(1.5),
+            Difficulty::Hard => Some(2.5),
+            Difficulty::Insane => None,
+        }
+    }
+
+    /// Chance that a piece is dropped one column off from where the bot meant it to go
+    pub fn misdrop_chance(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.15,
+            Difficulty::Normal => 0.05,
+            Difficulty::Hard => 0.01,
+            Difficulty::Insane => 0.0,
+        }
+    }
+
+    /// Pieces the bot plans ahead for, counting the falling one
+    pub fn search_depth(self) -> usize {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 3,
+            Difficulty::Insane => 4,
+        }
+    }
+}
+
+/// Full path of the tuned weights file, next to the settings file
+pub fn weights_path() -> PathBuf {
+    config::config_dir().join(AI_WEIGHTS_FILE)
+}
+
+/// Weights for the board evaluation heuristic
+/// Positive weights reward a feature, negative weights penalise it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Weights {
+    pub aggregate_height: f64,  // Sum of all column heights
+    pub lines: f64,             // Lines cleared by the placement
+    pub holes: f64,             // Empty cells covered by filled ones
+    pub bumpiness: f64,         // Sum of height differences between neighbouring columns
+}
+
+impl Default for Weights {
+    /// Weights that are known to play well for single-piece lookahead
+    fn default() -> Self {
+        Self {
+            aggregate_height: -0.510066,
+            lines: 0.760666,
+            holes: -0.35663,
+            bumpiness: -0.184483,
+        }
+    }
+}
+
+impl Weights {
+    /// Loads weights written by the tuner, falling back to the defaults if the file is missing or invalid
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the weights, creating the config directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string_pretty(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+}
+
+/// Scores a board after a placement that cleared `lines` lines
+pub fn evaluate(board: &BitBoard, lines: u32, weights: &Weights) -> f64 {
+    let heights = board.column_heights();
+    let aggregate_height: u32 = heights.iter().sum();
+    let bumpiness: u32 = heights.windows(2).map(|pair| pair[0].abs_diff(pair[1])).sum();
+
+    weights.aggregate_height * aggregate_height as f64
+        + weights.lines * lines as f64
+        + weights.holes * board.count_holes() as f64
+        + weights.bumpiness * bumpiness as f64
+}
+
+/// Where the bot wants to put a piece
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub hold: bool,        // Whether to hold first and place the piece that comes out instead
+    pub rotations: usize,  // Clockwise rotations from the spawn orientation
+    pub x: i32,            // Target column of the piece's left edge after rotating
+    pub score: f64,        // Heuristic value of the resulting board
+}
+
+/// Every placement of a piece reachable by rotating at the spawn position, sliding and dropping
+/// Returns the rotation count, target column, resulting board and lines cleared for each
+fn placements(board: &BitBoard, piece: &Tetromino, rotation_system: &dyn RotationSystem) -> Vec<(usize, i32, BitBoard, u32)> {
+    let mut results = Vec::new();
+    let mut rotated = Some(piece.clone());
+
+    for rotations in 0..4 {
+        let Some(oriented) = rotated else {
+            break;
+        };
+
+        // Slide to the left wall, then try every column on the way to the right wall
+        let mut shifted = oriented.clone();
+        while let Some(left) = board.try_move(&shifted, -1, 0) {
+            shifted = left;
+        }
+        loop {
+            let mut result = board.clone();
+            result.lock(&board.drop_position(&shifted));
+            let lines = result.clear_lines();
+            results.push((rotations, shifted.position.x as i32, result, lines));
+
+            match board.try_move(&shifted, 1, 0) {
+                Some(right) => shifted = right,
+                None => break,
+            }
+        }
+
+        rotated = rotation_system.rotate(board, &oriented);
+    }
+    results
+}
+
+/// Finds the best reachable placement for a piece by trying every rotation and column
+/// When the next piece is known, each placement is scored by the best follow-up for it
+/// Pieces are rotated with the game's rotation system and shifted with the board's rules, so kicks and walls apply;
+/// the search runs on a `BitBoard` copy, which is much cheaper to clone for every candidate
+pub fn best_placement(
+    board: &GameBoard,
+    piece: &Tetromino,
+    next: Option<&Tetromino>,
+    weights: &Weights,
+    rotation_system: &dyn RotationSystem,
+) -> Option<Placement> {
+    let board = BitBoard::from(board);
+    let mut best: Option<Placement> = None;
+    for (rotations, x, result, lines) in placements(&board, piece, rotation_system) {
+        let follow_up = next.and_then(|next| {
+            placements(&result, next, rotation_system)
+                .iter()
+                .map(|(_, _, after, more)| evaluate(after, lines + more, weights))
+                .max_by(f64::total_cmp)
+        });
+        let score = follow_up.unwrap_or_else(|| evaluate(&result, lines, weights));
+
+        if best.is_none_or(|b| score > b.score) {
+            best = Some(Placement { hold: false, rotations, x, score });
+        }
+    }
+    best
+}
+
+/// A position reached by the beam search: the board after some placements, and what is left to place
+#[derive(Clone)]
+struct Node<'a> {
+    board: BitBoard,               // Board after the placements so far
+    lines: u32,                    // Lines cleared by them
+    next: usize,                   // Index of the piece to place next in the falling piece and queue
+    hold: Option<&'a Tetromino>,   // Piece in the hold box
+    first: Option<Placement>,      // The placement this line of play started with
+}
+
+/// Plans the falling piece by searching `depth` pieces ahead through the queue and the hold box
+/// Each depth places one more piece on every kept position and keeps the `beam` best boards; the placement
+/// that leads to the best board at the end is returned. Holding is tried wherever the game allows it
+pub fn beam_search(snapshot: &Snapshot, depth: usize, beam: usize, weights: &Weights) -> Option<Placement> {
+    let pieces: Vec<&Tetromino> = iter::once(snapshot.current).chain(snapshot.queue).collect();
+    let start = Node { board: BitBoard::from(snapshot.board), lines: 0, next: 0, hold: snapshot.hold, first: None };
+    let mut frontier = vec![start];
+
+    for _ in 0..depth.max(1) {
+        let mut children: Vec<(f64, Node)> = Vec::new();
+        for node in &frontier {
+            let can_hold = if node.first.is_none() { snapshot.can_hold } else { snapshot.hold_enabled };
+            // Each choice is the piece placed, what's left in hold, the next piece index and whether hold was used
+            let mut choices = vec![(pieces.get(node.next).copied(), node.hold, node.next + 1, false)];
+            if can_hold {
+                choices.push(match node.hold {
+                    Some(held) => (Some(held), pieces.get(node.next).copied(), node.next + 1, true),
+                    None => (pieces.get(node.next + 1).copied(), pieces.get(node.next).copied(), node.next + 2, true),
+                });
+            }
+
+            for (piece, hold, next, held) in choices {
+                let Some(piece) = piece else {
+                    continue;
+                };
+                for (rotations, x, board, lines) in placements(&node.board, piece, snapshot.rotation_system) {
+                    let lines = node.lines + lines;
+                    let score = evaluate(&board, lines, weights);
+                    let first = node.first.unwrap_or(Placement { hold: held, rotations, x, score });
+                    children.push((score, Node { board, lines, next, hold, first: Some(first) }));
+                }
+            }
+        }
+        if children.is_empty() {
+            break;
+        }
+        children.sort_by(|a, b| b.0.total_cmp(&a.0));
+        children.truncate(beam.max(1));
+        frontier = children.into_iter().map(|(score, node)| Node { first: node.first.map(|first| Placement { score, ..first }), ..node }).collect();
+    }
+    frontier.first().and_then(|best| best.first)
+}
+
+/// The heuristic bot as a `Controller`
+/// Plans a placement whenever a new piece appears, then holds, rotates, shifts and hard drops towards it
+#[derive(Debug, Clone)]
+pub struct AiController {
+    pub weights: Weights,               // Heuristic used to choose placements
+    pub depth: usize,                   // Pieces planned ahead, counting the falling one
+    pub beam: usize,                    // Positions the search keeps at each depth
+    plan: Option<Placement>,            // Target for the current piece
+    planned_for: Option<u32>,           // Piece count the plan was made for
+    rotations_done: usize,              // Rotations already requested towards the plan
+}
+
+impl AiController {
+    pub fn new(weights: Weights) -> Self {
+        Self {
+            weights,
+            depth: 2,
+            beam: DEFAULT_BEAM_WIDTH,
+            plan: None,
+            planned_for: None,
+            rotations_done: 0,
+        }
+    }
+}
+
+impl Default for AiController {
+    fn default() -> Self {
+        Self::new(Weights::default())
+    }
+}
+
+impl Controller for AiController {
+    fn next_move(&mut self, snapshot: &Snapshot) -> Move {
+        if self.planned_for != Some(snapshot.pieces) {
+            self.plan = beam_search(snapshot, self.depth, self.beam, &self.weights);
+            self.planned_for = Some(snapshot.pieces);
+            self.rotations_done = 0;
+        }
+        let Some(plan) = self.plan else {
+            return Move::HardDrop;
+        };
+
+        if plan.hold {
+            // The piece coming out of hold gets a plan of its own
+            self.planned_for = None;
+            return Move::Hold;
+        }
+
+        if self.rotations_done < plan.rotations {
+            self.rotations_done += 1;
+            return Move::RotateCw;
+        }
+        match (plan.x - snapshot.current.position.x as i32).signum() {
+            -1 => Move::Left,
+            1 => Move::Right,
+            _ => Move::HardDrop,
+        }
+    }
+}
+
+/// A computer-controlled board for single-player versus
+/// The bot plays one input at a time through the same board rules as the player
+pub struct AiOpponent {
+    pub board: GameBoard,               // The bot's playfield
+    current_piece: Option<Tetromino>,   // Piece being placed
+    queue: VecDeque<Tetromino>,         // Upcoming pieces, the next one first
+    hold_piece: Option<Tetromino>,      // Piece set aside with hold
+    hold_used: bool,                    // Whether hold was used on the current piece
+    pieces: u32,                        // Number of pieces spawned so far
+    lines_cleared: u32,                 // Total lines cleared
+    pub controller: AiController,       // Chooses the bot's inputs
+    move_timer: f32,                    // Time since the last input
+    pub move_delay: f32,                // Seconds between inputs
+    pub max_pps: Option<f32>,           // Most pieces placed per second; `None` places them as fast as inputs allow
+    pub misdrop_chance: f64,            // Chance that a piece is dropped one column off its planned spot
+    piece_time: f32,                    // Seconds spent on the current piece
+    pub attack: AttackTracker,          // Combo and back-to-back state for outgoing garbage
+    pub garbage: GarbageQueue,          // Garbage sent by the player, waiting to rise
+    pub garbage_holes: GarbageHoles,    // Where the holes of rising garbage go
+    rng: StdRng,                        // Source of pieces and garbage holes
+    last_move_was_rotation: bool,       // Needed for T-spin detection
+    pub topped_out: bool,               // Whether the bot has lost
+}
+
+impl AiOpponent {
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// Creates a bot whose pieces and garbage holes are determined by `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut bot = Self {
+            board: GameBoard::new(),
+            current_piece: None,
+            queue: (0..NEXT_QUEUE_SIZE).map(|_| Tetromino::random_from(&mut rng)).collect(),
+            hold_piece: None,
+            hold_used: false,
+            pieces: 0,
+            lines_cleared: 0,
+            controller: AiController::default(),
+            move_timer: 0.0,
+            move_delay: DEFAULT_MOVE_DELAY,
+            max_pps: None,
+            misdrop_chance: 0.0,
+            piece_time: 0.0,
+            attack: AttackTracker::new(),
+            garbage: GarbageQueue::new(),
+            garbage_holes: GarbageHoles::default(),
+            rng,
+            last_move_was_rotation: false,
+            topped_out: false,
+        };
+        bot.spawn_piece();
+        bot
+    }
+
+    /// Plays at `difficulty`: its input speed, piece rate, misdrops and search depth
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.move_delay = difficulty.move_delay();
+        self.max_pps = difficulty.max_pps();
+        self.misdrop_chance = difficulty.misdrop_chance();
+        self.controller.depth = difficulty.search_depth();
+    }
+
+    /// Queues garbage sent by the player
+    pub fn receive_garbage(&mut self, lines: u32) {
+        self.garbage.receive(lines);
+    }
+
+    /// Advances the bot by `dt` seconds
+    /// Returns the number of garbage lines the bot sends to the player
+    pub fn update(&mut self, dt: f32) -> u32 {
+        if self.topped_out {
+            return 0;
+        }
+
+        self.move_timer += dt;
+        let mut outgoing = 0;
+        while self.move_timer >= self.move_delay && !self.topped_out {
+            self.move_timer -= self.move_delay;
+            outgoing += self.step();
+        }
+        outgoing
+    }
+
+    /// The bot's board with the falling piece drawn in, for display
+    pub fn board_with_piece(&self) -> Vec<Vec<Color>> {
+        let mut rows = self.board.rows().to_vec();
+        if let Some(piece) = &self.current_piece {
+            for (x, y) in GameBoard::piece_cells(piece) {
+                if let Some(cell) = rows
+                    .get_mut(y as usize)
+                    .and_then(|row| row.get_mut(x as usize))
+                {
+                    *cell = piece.color;
+                }
+            }
+        }
+        rows
+    }
+
+    /// Makes a single input chosen by the controller
+    /// Returns the garbage sent if the input locked the piece
+    fn step(&mut self) -> u32 {
+        let Some(piece) = self.current_piece.clone() else {
+            return 0;
+        };
+        let snapshot = Snapshot {
+            board: &self.board,
+            current: &piece,
+            next: &self.queue[0],
+            queue: &self.queue,
+            hold: self.hold_piece.as_ref(),
+            can_hold: !self.hold_used,
+            hold_enabled: true,
+            pieces: self.pieces,
+            score: 0, // Versus games are not scored
+            level: 1,
+            lines_cleared: self.lines_cleared,
+            rotation_system: &SRS,
+        };
+        let mv = self.controller.next_move(&snapshot);
+        self.piece_time += self.move_delay;
+        if mv == Move::HardDrop && self.misdrop_chance > 0.0 && self.rng.gen_bool(self.misdrop_chance) {
+            // A slip of the finger: one column to either side, then down; against a wall it drops in place
+            let side = if self.rng.gen_bool(0.5) { -1 } else { 1 };
+            if let Some(slipped) = self.board.try_move(&piece, side, 0) {
+                self.last_move_was_rotation = false;
+                return self.lock(self.board.drop_position(&slipped));
+            }
+        }
+
+        let moved = match mv {
+            Move::Left => self.board.try_move(&piece, -1, 0),
+            Move::Right => self.board.try_move(&piece, 1, 0),
+            Move::RotateCw => self.board.try_rotate(&piece),
+            Move::SoftDrop => self.board.try_move(&piece, 0, 1),
+            Move::Hold => {
+                self.hold();
+                return 0;
+            }
+            Move::None | Move::HardDrop => None,
+        };
+        if let Some(moved) = moved {
+            self.current_piece = Some(moved);
+            self.last_move_was_rotation = mv == Move::RotateCw;
+            return 0;
+        }
+
+        // Hard drop, or the path is blocked: drop where we are
+        let dropped = self.board.drop_position(&piece);
+        if dropped.position.y != piece.position.y {
+            self.last_move_was_rotation = false;
+        }
+        self.lock(dropped)
+    }
+
+    /// Locks a piece, handles clears and garbage, and spawns the next piece
+    /// Under a piece rate cap the bot then waits until the piece has taken its share of a second
+    fn lock(&mut self, piece: Tetromino) -> u32 {
+        if let Some(pps) = self.max_pps {
+            self.move_timer -= (1.0 / pps - self.piece_time).max(0.0);
+        }
+        self.piece_time = 0.0;
+
+        let t_spin = garbage::detect_t_spin(&self.board, &piece, self.last_move_was_rotation);
+        self.board.lock(&piece);
+        let lines = self.board.clear_lines();
+        self.lines_cleared += lines;
+
+        let attack = self.attack.on_lock(lines, t_spin);
+        let outgoing = self.garbage.cancel(attack);
+        if lines == 0 {
+            for chunk in self.garbage.take(garbage::GARBAGE_CAP_PER_LOCK) {
+                self.garbage_holes.add_chunk(&mut self.board, chunk, GARBAGE_COLOR, &mut self.rng);
+            }
+        }
+
+        self.spawn_piece();
+        outgoing
+    }
+
+    /// Swaps the falling piece with the held one, or sets it aside and spawns the next piece
+    fn hold(&mut self) {
+        if self.hold_used {
+            return;
+        }
+        let Some(current) = self.current_piece.take() else {
+            return;
+        };
+        match self.hold_piece.replace(Tetromino::new(current.kind)) {
+            Some(held) => {
+                let piece = Tetromino::new(held.kind);
+                if self.board.collides(&piece) {
+                    self.topped_out = true;
+                }
+                self.last_move_was_rotation = false;
+                self.current_piece = Some(piece);
+            }
+            None => self.spawn_piece(),
+        }
+        self.hold_used = true;
+    }
+
+    /// Spawns the next piece; the controller plans for it on its next input
+    fn spawn_piece(&mut self) {
+        let next = Tetromino::random_from(&mut self.rng);
+        self.queue.push_back(next);
+        let piece = self.queue.pop_front().unwrap();
+        if self.board.collides(&piece) {
+            self.topped_out = true;
+        }
+        self.pieces += 1;
+        self.hold_used = false;
+        self.last_move_was_rotation = false;
+        self.current_piece = Some(piece);
+    }
+}
+
+impl Default for AiOpponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{GRID_HEIGHT, GRID_WIDTH};
+    use crate::game::{run, Game};
+    use crate::tetromino::TetrominoType;
+
+    #[test]
+    fn test_evaluate_prefers_flat_boards() {
+        let weights = Weights::default();
+        let flat = BitBoard::new();
+        let mut bumpy = BitBoard::new();
+        bumpy.set(0, GRID_HEIGHT as usize - 1, Color::RED);
+        bumpy.set(0, GRID_HEIGHT as usize - 2, Color::RED);
+        assert!(evaluate(&flat, 0, &weights) > evaluate(&bumpy, 0, &weights));
+    }
+
+    #[test]
+    fn test_best_placement_fills_the_well() {
+        // A bottom row with a single gap at the right edge is perfect for a vertical I
+        let mut board = GameBoard::new();
+        let bottom = GRID_HEIGHT as usize - 1;
+        for x in 0..GRID_WIDTH as usize - 1 {
+            board[bottom][x] = Color::RED;
+        }
+
+        let piece = Tetromino::new(TetrominoType::I);
+        let placement = best_placement(&board, &piece, None, &Weights::default(), &SRS).unwrap();
+        assert_eq!(placement.rotations % 2, 1);
+        assert_eq!(placement.x, GRID_WIDTH - 1);
+    }
+
+    #[test]
+    fn test_search_holds_for_the_well() {
+        // Four rows with a gap at the right edge only take an I, which waits in the hold box
+        let mut board = GameBoard::new();
+        for y in GRID_HEIGHT as usize - 4..GRID_HEIGHT as usize {
+            for x in 0..GRID_WIDTH as usize - 1 {
+                board[y][x] = Color::RED;
+            }
+        }
+        let current = Tetromino::new(TetrominoType::S);
+        let held = Tetromino::new(TetrominoType::I);
+        let queue: VecDeque<_> = [TetrominoType::Z, TetrominoType::S, TetrominoType::Z].map(Tetromino::new).into();
+        let mut snapshot = Snapshot {
+            board: &board,
+            current: &current,
+            next: &queue[0],
+            queue: &queue,
+            hold: Some(&held),
+            can_hold: true,
+            hold_enabled: true,
+            pieces: 1,
+            score: 0,
+            level: 1,
+            lines_cleared: 0,
+            rotation_system: &SRS,
+        };
+        let placement = beam_search(&snapshot, 3, DEFAULT_BEAM_WIDTH, &Weights::default()).unwrap();
+        assert!(placement.hold);
+        assert_eq!(placement.x, GRID_WIDTH - 1);
+
+        // Once hold is used the falling piece has to go somewhere else
+        snapshot.can_hold = false;
+        assert!(!beam_search(&snapshot, 3, DEFAULT_BEAM_WIDTH, &Weights::default()).unwrap().hold);
+    }
+
+    #[test]
+    fn test_bot_plays_and_survives() {
+        let mut bot = AiOpponent::new();
+        // Feed the bot enough time for a few hundred inputs, around a hundred pieces
+        for _ in 0..600 {
+            bot.update(DEFAULT_MOVE_DELAY);
+        }
+        assert!(!bot.topped_out);
+        assert!(bot.board.column_heights().iter().all(|&h| h < GRID_HEIGHT as u32));
+    }
+
+    #[test]
+    fn test_controller_plays_headless_game() {
+        let mut game = Game::new();
+        let mut controller = AiController::default();
+        run(&mut game, &mut controller, 400);
+        assert!(!game.is_game_over());
+        assert!(game.lines_cleared() > 0);
+    }
+
+    #[test]
+    fn test_difficulty_caps_piece_rate() {
+        let pieces_in = |difficulty: Difficulty, seconds: f32| {
+            let mut bot = AiOpponent::with_seed(3);
+            bot.set_difficulty(difficulty);
+            let mut time = 0.0;
+            while time < seconds {
+                bot.update(0.01);
+                time += 0.01;
+            }
+            assert!(!bot.topped_out, "{:?} topped out", difficulty);
+            bot.pieces
+        };
+        let easy = pieces_in(Difficulty::Easy, 20.0);
+        assert!(easy <= (20.0 * Difficulty::Easy.max_pps().unwrap()) as u32 + 1, "{} pieces on easy", easy);
+        let hard = pieces_in(Difficulty::Hard, 20.0);
+        assert!(hard > easy);
+        assert!(pieces_in(Difficulty::Insane, 20.0) > hard);
+    }
+
+    #[test]
+    fn test_bot_receives_garbage() {
+        let mut bot = AiOpponent::new();
+        bot.receive_garbage(3);
+        assert_eq!(bot.garbage.pending(), 3);
+        assert_eq!(bot.board_with_piece().len(), GRID_HEIGHT as usize);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::constants::{
+    ATTACK_METER_DRAIN, LEVEL_UP_POP_TIME, LEVEL_UP_TIME, LEVEL_UP_TINT_TIME, LINE_CLEAR_COLLAPSE_TIME, LINE_CLEAR_FLASH_TIME,
+    LOCK_FLASH_TIME,
+};
+
+/// Animation of completed rows: they flash white, then the rows above fall into their place
+/// The board keeps the completed rows until the animation finishes
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineClearAnimation {
+    rows: Vec<usize>,  // Completed rows, from top to bottom
+    elapsed: f64,      // Seconds since the animation started
+}
+
+impl LineClearAnimation {
+    /// Starts the animation for the given completed rows
+    pub fn new(mut rows: Vec<usize>) -> Self {
+        rows.sort_unstable();
+        Self { rows, elapsed: 0.0 }
+    }
+
+    /// The completed rows, from top to bottom
+    pub fn rows(&self) -> &[usize] {
+        &self.rows
+    }
+
+    pub fn is_cleared_row(&self, row: usize) -> bool {
+        self.rows.contains(&row)
+    }
+
+    /// Advances the animation; returns true once it has finished
+    pub fn update(&mut self, dt: f64) -> bool {
+        self.elapsed += dt;
+        self.is_finished()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= LINE_CLEAR_FLASH_TIME + LINE_CLEAR_COLLAPSE_TIME
+    }
+
+    /// Opacity of the white flash over the completed rows, fading out as they collapse
+    pub fn flash_alpha(&self) -> f32 {
+        1.0 - self.collapse_progress()
+    }
+
+    /// Opacity of the completed rows when flashing is reduced: they fade out instead of flashing white
+    pub fn fade_alpha(&self) -> f32 {
+        (1.0 - self.elapsed / LINE_CLEAR_FLASH_TIME).clamp(0.0, 1.0) as f32
+    }
+
+    /// How far the collapse has gone, from 0.0 (still flashing) to 1.0 (done)
+    pub fn collapse_progress(&self) -> f32 {
+        ((self.elapsed - LINE_CLEAR_FLASH_TIME) / LINE_CLEAR_COLLAPSE_TIME).clamp(0.0, 1.0) as f32
+    }
+
+    /// How many rows down a remaining row is currently drawn, as a fraction of its final drop
+    pub fn row_offset(&self, row: usize) -> f32 {
+        let cleared_below = self.rows.iter().filter(|&&cleared| cleared > row).count();
+        cleared_below as f32 * self.collapse_progress()
+    }
+}
+
+/// Flash of a piece that just locked: its cells show white for a few frames, so placements are easy to follow
+/// The cells themselves come from `Game::last_locked`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LockFlash {
+    elapsed: f64,  // Seconds since the piece locked
+}
+
+impl LockFlash {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the flash; returns true once it has finished
+    pub fn update(&mut self, dt: f64) -> bool {
+        self.elapsed += dt;
+        self.is_finished()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= LOCK_FLASH_TIME
+    }
+}
+
+/// Fanfare on reaching a new level: the board is tinted for a moment and the level number pops up over it
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelUpFanfare {
+    level: u32,    // Level just reached
+    elapsed: f64,  // Seconds since the level went up
+}
+
+impl LevelUpFanfare {
+    pub fn new(level: u32) -> Self {
+        Self { level, elapsed: 0.0 }
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Advances the fanfare; returns true once it has finished
+    pub fn update(&mut self, dt: f64) -> bool {
+        self.elapsed += dt;
+        self.is_finished()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= LEVEL_UP_TIME
+    }
+
+    /// Opacity of the tint over the board, fading from full to nothing
+    pub fn tint_alpha(&self) -> f32 {
+        (1.0 - self.elapsed / LEVEL_UP_TINT_TIME).max(0.0) as f32
+    }
+
+    /// Size of the level number against its resting size; it starts half as big again and shrinks into place
+    pub fn text_scale(&self) -> f32 {
+        1.0 + 0.5 * (1.0 - self.elapsed / LEVEL_UP_POP_TIME).max(0.0) as f32
+    }
+
+    /// Opacity of the level number, which fades out over the last third of the fanfare
+    pub fn text_alpha(&self) -> f32 {
+        ((LEVEL_UP_TIME - self.elapsed) / (LEVEL_UP_TIME / 3.0)).clamp(0.0, 1.0) as f32
+    }
+}
+
+/// Attack just sent to the versus opponent, shown in the garbage meter and draining away so each attack can be seen
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AttackMeter {
+    lines: f64,  // Lines still shown
+}
+
+impl AttackMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an attack that was sent
+    pub fn send(&mut self, lines: u32) {
+        self.lines += lines as f64;
+    }
+
+    /// Drains the meter at `ATTACK_METER_DRAIN` lines a second
+    pub fn update(&mut self, dt: f64) {
+        self.lines = (self.lines - dt * ATTACK_METER_DRAIN).max(0.0);
+    }
+
+    /// Lines still shown, part of a line as the last one drains
+    pub fn lines(&self) -> f32 {
+        self.lines as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases() {
+        let mut animation = LineClearAnimation::new(vec![19, 17]);
+        assert_eq!(animation.rows(), &[17, 19]);
+        assert_eq!(animation.flash_alpha(), 1.0);
+        assert_eq!(animation.fade_alpha(), 1.0);
+        assert_eq!(animation.row_offset(5), 0.0);
+
+        // Halfway through the collapse
+        assert!(!animation.update(LINE_CLEAR_FLASH_TIME + LINE_CLEAR_COLLAPSE_TIME / 2.0));
+        assert!((animation.collapse_progress() - 0.5).abs() < 1e-6);
+        assert_eq!(animation.fade_alpha(), 0.0);
+        assert!((animation.row_offset(5) - 1.0).abs() < 1e-6);  // Two cleared rows below
+        assert!((animation.row_offset(18) - 0.5).abs() < 1e-6); // One cleared row below
+        assert_eq!(animation.row_offset(19), 0.0);
+
+        assert!(animation.update(LINE_CLEAR_COLLAPSE_TIME));
+        assert_eq!(animation.flash_alpha(), 0.0);
+    }
+
+    #[test]
+    fn test_lock_flash_ends() {
+        let mut flash = LockFlash::new();
+        assert!(!flash.update(LOCK_FLASH_TIME / 2.0));
+        assert!(flash.update(LOCK_FLASH_TIME / 2.0));
+    }
+
+    #[test]
+    fn test_level_up_fanfare() {
+        let mut fanfare = LevelUpFanfare::new(4);
+        assert_eq!(fanfare.level(), 4);
+        assert_eq!((fanfare.tint_alpha(), fanfare.text_scale(), fanfare.text_alpha()), (1.0, 1.5, 1.0));
+
+        // The tint is gone and the number has settled long before the number fades
+        assert!(!fanfare.update(LEVEL_UP_TINT_TIME));
+        assert_eq!((fanfare.tint_alpha(), fanfare.text_scale(), fanfare.text_alpha()), (0.0, 1.0, 1.0));
+
+        assert!(fanfare.update(LEVEL_UP_TIME));
+        assert_eq!(fanfare.text_alpha(), 0.0);
+    }
+
+    #[test]
+    fn test_attack_meter_drains() {
+        let mut meter = AttackMeter::new();
+        meter.send(4);
+        meter.update(1.0 / ATTACK_METER_DRAIN);
+        assert_eq!(meter.lines(), 3.0);
+        meter.send(2);
+        meter.update(10.0);
+        assert_eq!(meter.lines(), 0.0);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::constants::{CONFIG_DIR_NAME, RESOURCES_VAR, RESOURCE_FOLDERS};
+use std::path::{Path, PathBuf};
+
+/// Default sounds, themes, openers and fallback font built into the game by resource path, so it runs from any directory
+/// Files in the resource folders take their place; the pixel font is always built in by `text`
+#[cfg(feature = "embedded-assets")]
+static EMBEDDED: &[(&str, &[u8])] = &[
+    ("/sounds/background.wav", include_bytes!("../assets/sounds/background.wav")),
+    ("/sounds/clear.wav", include_bytes!("../assets/sounds/clear.wav")),
+    ("/sounds/drop.wav", include_bytes!("../assets/sounds/drop.wav")),
+    ("/sounds/game_over.wav", include_bytes!("../assets/sounds/game_over.wav")),
+    ("/sounds/level_up.wav", include_bytes!("../assets/sounds/level_up.wav")),
+    ("/sounds/move.wav", include_bytes!("../assets/sounds/move.wav")),
+    ("/sounds/rotate.wav", include_bytes!("../assets/sounds/rotate.wav")),
+    ("/sounds/tetris.wav", include_bytes!("../assets/sounds/tetris.wav")),
+    ("/themes/gameboy.toml", include_bytes!("../themes/gameboy.toml")),
+    ("/themes/nes.toml", include_bytes!("../themes/nes.toml")),
+    ("/openers/tsd_left.toml", include_bytes!("../openers/tsd_left.toml")),
+    ("/openers/tsd_right.toml", include_bytes!("../openers/tsd_right.toml")),
+    ("/fonts/fallback.ttf", include_bytes!("../assets/fonts/fallback.ttf")),
+];
+
+/// Without the `embedded-assets` feature every resource comes from the resource folders
+#[cfg(not(feature = "embedded-assets"))]
+static EMBEDDED: &[(&str, &[u8])] = &[];
+
+/// The built-in copy of a resource, e.g. `/sounds/move.wav`
+pub fn embedded(path: &str) -> Option<&'static [u8]> {
+    EMBEDDED.iter().find(|(name, _)| *name == path).map(|(_, data)| *data)
+}
+
+/// Resource paths of the built-in files directly in `dir`, e.g. `/themes`
+pub fn embedded_files(dir: &str) -> Vec<PathBuf> {
+    let dir = format!("{}/", dir.trim_end_matches('/'));
+    EMBEDDED
+        .iter()
+        .filter(|(name, _)| name.strip_prefix(&dir).is_some_and(|file| !file.contains('/')))
+        .map(|(name, _)| PathBuf::from(name))
+        .collect()
+}
+
+/// Where a resource folder was found, for the log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceSource {
+    Override,    // Named by `TETRIS_RESOURCES`
+    Bundle,      // `Resources` beside the `MacOS` folder of a macOS app bundle
+    Executable,  // `resources` beside the executable, or the executable's own folder
+    Project,     // The crate's folder, for builds run from cargo
+    DataDir,     // `tetris` in the user's or the system's data directories, e.g. `/usr/share/tetris`
+}
+
+impl ResourceSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResourceSource::Override => RESOURCES_VAR,
+            ResourceSource::Bundle => "app bundle",
+            ResourceSource::Executable => "beside the executable",
+            ResourceSource::Project => "project folder",
+            ResourceSource::DataDir => "data directory",
+        }
+    }
+}
+
+/// The places resources may be, most specific first
+/// `exe` is the path of the running executable, `data_dirs` the data directories to look in for a `tetris` folder
+pub fn resource_candidates(exe: Option<&Path>, override_dir: Option<PathBuf>, data_dirs: &[PathBuf]) -> Vec<(PathBuf, ResourceSource)> {
+    let mut candidates = Vec::new();
+    candidates.extend(override_dir.map(|dir| (dir, ResourceSource::Override)));
+    if let Some(exe_dir) = exe.and_then(Path::parent) {
+        if let Some(contents) = exe_dir.parent().filter(|_| exe_dir.ends_with("MacOS")) {
+            candidates.push((contents.join("Resources"), ResourceSource::Bundle));
+        }
+        candidates.push((exe_dir.join("resources"), ResourceSource::Executable));
+        candidates.push((exe_dir.to_path_buf(), ResourceSource::Executable));
+    }
+    if cfg!(debug_assertions) {
+        candidates.push((PathBuf::from(env!("CARGO_MANIFEST_DIR")), ResourceSource::Project));
+    }
+    candidates.extend(data_dirs.iter().map(|dir| (dir.join(CONFIG_DIR_NAME), ResourceSource::DataDir)));
+    candidates
+}
+
+/// The first candidate holding any of the `RESOURCE_FOLDERS`; a folder named by the override is taken as it is
+pub fn locate_resources(candidates: Vec<(PathBuf, ResourceSource)>) -> Option<(PathBuf, ResourceSource)> {
+    candidates.into_iter().find(|(dir, source)| {
+        if *source == ResourceSource::Override {
+            if !dir.is_dir() {
+                log::warn!(target: "assets", "{} names {}, which is not a folder", RESOURCES_VAR, dir.display());
+            }
+            return dir.is_dir();
+        }
+        RESOURCE_FOLDERS.iter().any(|folder| dir.join(folder).is_dir())
+    })
+}
+
+/// The user's data directory and, on Linux and other XDG systems, the system's (`$XDG_DATA_DIRS`)
+fn data_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = dirs::data_dir().into_iter().collect();
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        let system = std::env::var("XDG_DATA_DIRS").ok().filter(|dirs| !dirs.is_empty());
+        let system = system.as_deref().unwrap_or("/usr/local/share:/usr/share");
+        dirs.extend(system.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from));
+    }
+    dirs
+}
+
+/// The folder resources are read from, logging where it was found
+/// Without one the game runs on its built-in assets, read from the executable's folder in case some are added later
+pub fn resource_dir() -> PathBuf {
+    let exe = std::env::current_exe().ok();
+    let override_dir = std::env::var_os(RESOURCES_VAR).filter(|dir| !dir.is_empty()).map(PathBuf::from);
+    match locate_resources(resource_candidates(exe.as_deref(), override_dir, &data_dirs())) {
+        Some((dir, source)) => {
+            log::info!(target: "assets", "Resources from {} ({})", dir.display(), source.label());
+            dir
+        }
+        None => {
+            let dir = exe.as_deref().and_then(Path::parent).map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+            log::info!(target: "assets", "No resource folder found, using the built-in assets");
+            dir
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_locates_resources() {
+        let root = std::env::temp_dir().join(format!("tetris-assets-{}", std::process::id()));
+        let macos = root.join("Tetris.app").join("Contents").join("MacOS");
+        fs::create_dir_all(&macos).unwrap();
+        let exe = macos.join("tetris");
+        let data_dirs = [root.join("share")];
+        let data = &data_dirs[0];
+        fs::create_dir_all(data.join(CONFIG_DIR_NAME).join("themes")).unwrap();
+        let found = |override_dir: Option<PathBuf>| {
+            let mut candidates = resource_candidates(Some(&exe), override_dir, &data_dirs);
+            candidates.retain(|(_, source)| *source != ResourceSource::Project);
+            locate_resources(candidates)
+        };
+
+        // Empty folders don't count, so the search goes on to the data directory
+        assert_eq!(found(None), Some((data.join(CONFIG_DIR_NAME), ResourceSource::DataDir)));
+        fs::create_dir_all(macos.join("sounds")).unwrap();
+        assert_eq!(found(None), Some((macos.clone(), ResourceSource::Executable)));
+        let bundle = root.join("Tetris.app").join("Contents").join("Resources");
+        fs::create_dir_all(bundle.join("openers")).unwrap();
+        assert_eq!(found(None), Some((bundle, ResourceSource::Bundle)));
+
+        // The override wins whatever it holds, unless it isn't there at all
+        assert_eq!(found(Some(root.clone())), Some((root.clone(), ResourceSource::Override)));
+        assert_eq!(found(Some(root.join("missing"))).map(|(_, source)| source), Some(ResourceSource::Bundle));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-assets")]
+    fn test_embedded_defaults_load() {
+        use crate::opener::Opener;
+        use crate::theme::Theme;
+
+        assert_eq!(embedded_files("/themes"), vec![PathBuf::from("/themes/gameboy.toml"), PathBuf::from("/themes/nes.toml")]);
+        assert_eq!(embedded_files("/sounds/").len(), 8);
+        assert!(embedded_files("/").is_empty());
+        assert!(embedded("/sounds/move.wav").is_some_and(|wav| wav.starts_with(b"RIFF")));
+        assert!(embedded("/sounds/hurry.wav").is_none());
+
+        for path in embedded_files("/themes").into_iter().chain(embedded_files("/openers")) {
+            let text = std::str::from_utf8(embedded(path.to_str().unwrap()).unwrap()).unwrap();
+            if path.starts_with("/themes") {
+                assert!(Theme::from_toml(text).is_ok(), "{}", path.display());
+            } else {
+                assert!(Opener::from_toml(text).is_ok(), "{}", path.display());
+            }
+        }
+    }
+}
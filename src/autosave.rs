@@ -0,0 +1,136 @@
+use crate::config;
+use crate::constants::{AUTOSAVE_FILE, AUTOSAVE_INTERVAL};
+use crate::events::GameEvent;
+use crate::garbage::TSpin;
+use crate::high_scores::HighScoreEntry;
+use crate::stats::Stats;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Full path of the autosave of the game in progress
+pub fn autosave_path() -> PathBuf {
+    config::config_dir().join(AUTOSAVE_FILE)
+}
+
+/// The score and statistics of a game in progress, saved now and then so a crash or power cut
+/// in the middle of a long game doesn't lose it
+/// The file is removed whenever a game ends or is left on purpose, so one found at startup is a game that was cut short
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Autosave {
+    pub entry: HighScoreEntry,  // Score, level, lines, time and mode so far, without a name
+    pub stats: Stats,           // Live statistics so far, for the lifetime totals
+}
+
+impl Autosave {
+    /// Reads the autosave left behind by a game, or `None` if there is none or it can't be read
+    pub fn load(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&json)
+            .map_err(|e| log::warn!(target: "save", "Autosave in {} is unreadable: {}", path.display(), e))
+            .ok()
+    }
+
+    /// Writes the autosave to a temporary file that then replaces the last one, so a crash while
+    /// saving leaves the previous autosave intact
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let temp = path.with_extension("tmp");
+        let mut file = File::create(&temp)?;
+        file.write_all(serde_json::to_string(self)?.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    }
+
+    /// Removes the autosave once its game has ended or been dealt with
+    pub fn clear(path: impl AsRef<Path>) -> io::Result<()> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Decides when the game in progress is saved: every `AUTOSAVE_INTERVAL` seconds of play,
+/// and straight after a level up, a tetris or a T-spin
+#[derive(Debug, Clone, Default)]
+pub struct AutosaveTimer {
+    elapsed: f64,  // Seconds of play since the last save
+    due: bool,     // Whether something worth keeping happened since
+}
+
+impl AutosaveTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts `dt` seconds of play
+    pub fn update(&mut self, dt: f64) {
+        self.elapsed += dt;
+        self.due |= self.elapsed >= AUTOSAVE_INTERVAL;
+    }
+
+    /// Saves early after the events a player would hate to lose
+    pub fn on_event(&mut self, event: &GameEvent) {
+        self.due |= match event {
+            GameEvent::LevelUp(_) => true,
+            GameEvent::LinesCleared { lines, t_spin } => *lines >= 4 || *t_spin != TSpin::None,
+            _ => false,
+        };
+    }
+
+    /// Whether a save is due, starting the count again if so
+    pub fn take_due(&mut self) -> bool {
+        let due = self.due;
+        if due {
+            *self = Self::new();
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saves_on_a_timer_and_after_big_moments() {
+        let mut timer = AutosaveTimer::new();
+        timer.update(AUTOSAVE_INTERVAL / 2.0);
+        timer.on_event(&GameEvent::LinesCleared { lines: 2, t_spin: TSpin::None });
+        assert!(!timer.take_due());
+        timer.update(AUTOSAVE_INTERVAL / 2.0);
+        assert!(timer.take_due());
+        assert!(!timer.take_due(), "the count starts again after a save");
+
+        timer.on_event(&GameEvent::LinesCleared { lines: 4, t_spin: TSpin::None });
+        assert!(timer.take_due());
+        timer.on_event(&GameEvent::LevelUp(3));
+        assert!(timer.take_due());
+    }
+
+    #[test]
+    fn test_autosave_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("tetris-autosave-{}", std::process::id()));
+        let path = dir.join(AUTOSAVE_FILE);
+        let autosave = Autosave {
+            entry: HighScoreEntry { level: 12, lines: 118, mode: "MARATHON".to_string(), ..HighScoreEntry::new(String::new(), 98_400) },
+            stats: Stats::new(),
+        };
+        autosave.save(&path).unwrap();
+        assert_eq!(Autosave::load(&path), Some(autosave));
+
+        Autosave::clear(&path).unwrap();
+        assert_eq!(Autosave::load(&path), None);
+        Autosave::clear(&path).unwrap();
+
+        fs::write(&path, "{\"entry\": {\"sco").unwrap();
+        assert_eq!(Autosave::load(&path), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,331 @@
+use crate::constants::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::ui::{Easing, Timeline};
+use ggez::graphics::{self, Color, DrawParam, Image, Rect};
+use ggez::{Context, GameResult};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Levels played before the background moves on to its next scene
+pub const LEVELS_PER_SCENE: u32 = 5;
+
+const STAR_COUNT: usize = 160;     // Stars in the starfield
+const STAR_SPEED: f32 = 60.0;      // Pixels per second the nearest stars fall
+const SKYLINE_LAYERS: usize = 3;   // Rows of buildings, from the farthest to the nearest
+const SKY_BANDS: usize = 24;       // Horizontal bands the sky gradient is drawn with
+const SCENE_FADE_TIME: f64 = 2.0;  // Seconds one scene takes to fade into the next
+
+/// Sky of each scene, top color then horizon color; scenes past the last start over
+const SKIES: [([u8; 3], [u8; 3]); 4] = [
+    ([8, 10, 40], [40, 50, 110]),   // Night
+    ([40, 20, 70], [200, 90, 80]),  // Dusk
+    ([5, 40, 50], [30, 130, 130]),  // Aurora
+    ([40, 5, 15], [150, 40, 30]),   // Ember
+];
+
+/// What is drawn behind the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundStyle {
+    Plain,      // Only the theme's background color, for competitive play
+    Starfield,  // Stars drifting down at different depths
+    City,       // Skyline layers scrolling past at different speeds
+    Images,     // Pictures from the backgrounds folder, one per scene
+}
+
+impl BackgroundStyle {
+    pub const ALL: [BackgroundStyle; 4] = [
+        BackgroundStyle::Plain,
+        BackgroundStyle::Starfield,
+        BackgroundStyle::City,
+        BackgroundStyle::Images,
+    ];
+
+    /// Name shown in the settings screen
+    pub fn name(self) -> &'static str {
+        match self {
+            BackgroundStyle::Plain => "plain",
+            BackgroundStyle::Starfield => "starfield",
+            BackgroundStyle::City => "city",
+            BackgroundStyle::Images => "images",
+        }
+    }
+
+    /// Moves `steps` places through `ALL`, wrapping around
+    pub fn cycle(self, steps: i32) -> Self {
+        let index = Self::ALL.iter().position(|&style| style == self).unwrap_or(0) as i32;
+        Self::ALL[(index + steps).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+}
+
+/// Scene shown at a level: 0 for levels 1 to 5, 1 for levels 6 to 10, and so on
+pub fn scene_for_level(level: u32) -> usize {
+    (level.max(1) - 1) as usize / LEVELS_PER_SCENE as usize
+}
+
+/// Top and horizon colors of a scene's sky
+fn sky(scene: usize) -> (Color, Color) {
+    let (top, horizon) = SKIES[scene % SKIES.len()];
+    (Color::from_rgb(top[0], top[1], top[2]), Color::from_rgb(horizon[0], horizon[1], horizon[2]))
+}
+
+fn lerp(a: Color, b: Color, t: f32) -> Color {
+    Color::new(a.r + (b.r - a.r) * t, a.g + (b.g - a.g) * t, a.b + (b.b - a.b) * t, a.a + (b.a - a.a) * t)
+}
+
+/// A star of the starfield
+#[derive(Clone, Debug, PartialEq)]
+struct Star {
+    x: f32,      // Horizontal position in logical pixels
+    y: f32,      // Vertical position in logical pixels
+    depth: f32,  // From 0.2 (far, slow and dim) to 1.0 (near, fast and bright)
+}
+
+/// One row of buildings, repeating every `period` pixels as it scrolls
+#[derive(Clone, Debug, PartialEq)]
+struct Skyline {
+    buildings: Vec<Rect>,  // Outlines of the buildings, standing on the bottom of the screen
+    period: f32,           // Width after which the row repeats
+    speed: f32,            // Pixels per second the row scrolls left
+    shade: f32,            // How far the buildings are tinted from black towards the horizon color
+}
+
+/// Animated scenery behind the board that moves on to a new scene every few levels
+/// Like the particles, it uses its own random numbers so it never changes the piece sequence
+pub struct Background {
+    stars: Vec<Star>,         // The starfield
+    skylines: Vec<Skyline>,   // The city, from the farthest row to the nearest
+    images: Vec<Image>,       // Pictures from the backgrounds folder, in file name order
+    scene: usize,             // Scene being shown
+    previous: usize,          // Scene fading out while `fade` runs
+    fade: Timeline,           // Fade from the previous scene to the current one
+    elapsed: f32,             // Seconds the background has been animating
+}
+
+impl Background {
+    pub fn new(images: Vec<Image>) -> Self {
+        let mut rng = StdRng::from_entropy();
+        let stars = (0..STAR_COUNT)
+            .map(|_| Star {
+                x: rng.gen_range(0.0..SCREEN_WIDTH),
+                y: rng.gen_range(0.0..SCREEN_HEIGHT),
+                depth: rng.gen_range(0.2..1.0),
+            })
+            .collect();
+        let skylines = (0..SKYLINE_LAYERS)
+            .map(|layer| {
+                let near = (layer + 1) as f32 / SKYLINE_LAYERS as f32;
+                let mut buildings = Vec::new();
+                let mut x = 0.0;
+                while x < SCREEN_WIDTH {
+                    let w = rng.gen_range(60.0..160.0) * near;
+                    let h = rng.gen_range(0.15..0.45) * SCREEN_HEIGHT * (0.5 + near / 2.0);
+                    buildings.push(Rect::new(x, SCREEN_HEIGHT - h, w, h));
+                    x += w + rng.gen_range(0.0..20.0);
+                }
+                Skyline { buildings, period: x, speed: 8.0 + 30.0 * near, shade: 0.1 + 0.25 * (1.0 - near) }
+            })
+            .collect();
+        Self {
+            stars,
+            skylines,
+            images,
+            scene: 0,
+            previous: 0,
+            fade: Timeline::new(SCENE_FADE_TIME, Easing::EaseInOut),
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn scene(&self) -> usize {
+        self.scene
+    }
+
+    /// Shows the scene for `level`, fading over from the old one when it changes
+    pub fn set_level(&mut self, level: u32) {
+        let scene = scene_for_level(level);
+        if scene != self.scene {
+            self.previous = self.scene;
+            self.scene = scene;
+            self.fade.start();
+        }
+    }
+
+    /// Shows the scene for `level` straight away, for a new game
+    pub fn reset(&mut self, level: u32) {
+        self.scene = scene_for_level(level);
+        self.previous = self.scene;
+        self.fade.finish();
+    }
+
+    /// Moves the stars and skylines and advances the fade between scenes
+    pub fn update(&mut self, dt: f64) {
+        self.fade.update(dt);
+        let dt = dt as f32;
+        self.elapsed += dt;
+        for star in &mut self.stars {
+            star.y = (star.y + STAR_SPEED * star.depth * dt).rem_euclid(SCREEN_HEIGHT);
+        }
+    }
+
+    /// Sky colors between the previous scene and the current one as the fade goes
+    fn sky_colors(&self) -> (Color, Color) {
+        let (old_top, old_horizon) = sky(self.previous);
+        let (top, horizon) = sky(self.scene);
+        let t = self.fade.value();
+        (lerp(old_top, top, t), lerp(old_horizon, horizon, t))
+    }
+
+    /// Draws the background over the whole logical screen in the given style
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, style: BackgroundStyle) -> GameResult {
+        match style {
+            BackgroundStyle::Plain => Ok(()),
+            BackgroundStyle::Starfield => self.draw_starfield(ctx, canvas),
+            BackgroundStyle::City => self.draw_city(ctx, canvas),
+            BackgroundStyle::Images => {
+                self.draw_images(canvas);
+                Ok(())
+            }
+        }
+    }
+
+    /// Vertical gradient from `top` to `horizon`, drawn as bands
+    fn draw_sky(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, top: Color, horizon: Color) -> GameResult {
+        let mut builder = graphics::MeshBuilder::new();
+        let band_height = SCREEN_HEIGHT / SKY_BANDS as f32;
+        for band in 0..SKY_BANDS {
+            let color = lerp(top, horizon, band as f32 / (SKY_BANDS - 1) as f32);
+            builder.rectangle(
+                graphics::DrawMode::fill(),
+                Rect::new(0.0, band as f32 * band_height, SCREEN_WIDTH, band_height + 1.0),
+                color,
+            )?;
+        }
+        canvas.draw(&graphics::Mesh::from_data(ctx, builder.build()), DrawParam::default());
+        Ok(())
+    }
+
+    fn draw_starfield(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        // A darker sky than the city's, so the stars stand out
+        let (top, horizon) = self.sky_colors();
+        let dim = |color: Color| lerp(Color::BLACK, color, 0.5);
+        self.draw_sky(ctx, canvas, dim(top), dim(horizon))?;
+
+        let mut builder = graphics::MeshBuilder::new();
+        for star in &self.stars {
+            // Near stars are bigger and twinkle less
+            let twinkle = 0.75 + 0.25 * (self.elapsed * 3.0 + star.x).sin() * (1.0 - star.depth);
+            let size = 1.0 + 3.0 * star.depth;
+            let color = Color::new(1.0, 1.0, 1.0, star.depth * twinkle);
+            builder.rectangle(graphics::DrawMode::fill(), Rect::new(star.x, star.y, size, size), color)?;
+        }
+        canvas.draw(&graphics::Mesh::from_data(ctx, builder.build()), DrawParam::default());
+        Ok(())
+    }
+
+    fn draw_city(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        let (top, horizon) = self.sky_colors();
+        self.draw_sky(ctx, canvas, top, horizon)?;
+
+        let mut builder = graphics::MeshBuilder::new();
+        for skyline in &self.skylines {
+            let color = lerp(Color::BLACK, horizon, skyline.shade);
+            let scroll = (self.elapsed * skyline.speed) % skyline.period;
+            // Each row is drawn twice side by side so it wraps around without a gap
+            for copy in [0.0, skyline.period] {
+                for building in &skyline.buildings {
+                    let x = building.x - scroll + copy;
+                    if x < SCREEN_WIDTH && x + building.w > 0.0 {
+                        builder.rectangle(graphics::DrawMode::fill(), Rect::new(x, building.y, building.w, building.h), color)?;
+                    }
+                }
+            }
+        }
+        canvas.draw(&graphics::Mesh::from_data(ctx, builder.build()), DrawParam::default());
+        Ok(())
+    }
+
+    /// Draws the picture of each scene scaled to cover the screen, the new one fading in over the old
+    fn draw_images(&self, canvas: &mut graphics::Canvas) {
+        if self.images.is_empty() {
+            return;
+        }
+        let mut draw = |scene: usize, alpha: f32| {
+            let image = &self.images[scene % self.images.len()];
+            let scale = (SCREEN_WIDTH / image.width() as f32).max(SCREEN_HEIGHT / image.height() as f32);
+            let x = (SCREEN_WIDTH - image.width() as f32 * scale) / 2.0;
+            let y = (SCREEN_HEIGHT - image.height() as f32 * scale) / 2.0;
+            canvas.draw(image, DrawParam::new().dest([x, y]).scale([scale, scale]).color(Color::new(1.0, 1.0, 1.0, alpha)));
+        };
+        if self.fade.is_running() {
+            draw(self.previous, 1.0);
+        }
+        draw(self.scene, self.fade.value());
+    }
+}
+
+/// Loads the `.png` and `.jpg` pictures in the `/backgrounds` resource folder, in file name order
+/// Pictures that fail to load are skipped
+pub fn load_images(ctx: &Context) -> Vec<Image> {
+    let mut paths: Vec<_> = ctx
+        .fs
+        .read_dir("/backgrounds")
+        .map(|entries| {
+            entries
+                .filter(|path| path.extension().is_some_and(|ext| ext == "png" || ext == "jpg" || ext == "jpeg"))
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths.into_iter().filter_map(|path| Image::from_path(ctx, path).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scenes_change_every_few_levels() {
+        assert_eq!(scene_for_level(0), 0);
+        assert_eq!(scene_for_level(1), 0);
+        assert_eq!(scene_for_level(LEVELS_PER_SCENE), 0);
+        assert_eq!(scene_for_level(LEVELS_PER_SCENE + 1), 1);
+        assert_eq!(sky(SKIES.len()), sky(0));
+
+        let mut background = Background::new(Vec::new());
+        background.set_level(3);
+        assert!(!background.fade.is_running());
+        background.set_level(LEVELS_PER_SCENE * 2 + 1);
+        assert_eq!(background.scene(), 2);
+        assert!(background.fade.is_running());
+        let rgb = |(top, horizon): (Color, Color)| (top.to_rgb(), horizon.to_rgb());
+        assert_eq!(rgb(background.sky_colors()), rgb(sky(0)));
+
+        background.update(SCENE_FADE_TIME);
+        assert_eq!(rgb(background.sky_colors()), rgb(sky(2)));
+        background.reset(1);
+        assert_eq!(background.scene(), 0);
+        assert!(!background.fade.is_running());
+    }
+
+    #[test]
+    fn test_stars_wrap_around() {
+        let mut background = Background::new(Vec::new());
+        background.update(60.0);
+        assert!(background.stars.iter().all(|star| (0.0..SCREEN_HEIGHT).contains(&star.y)));
+        for skyline in &background.skylines {
+            assert!(skyline.period >= SCREEN_WIDTH);
+            assert!(skyline.buildings.iter().all(|building| building.bottom() == SCREEN_HEIGHT));
+        }
+    }
+
+    #[test]
+    fn test_style_names() {
+        assert_eq!(BackgroundStyle::Plain.cycle(-1), BackgroundStyle::Images);
+        assert_eq!(BackgroundStyle::Images.cycle(1), BackgroundStyle::Plain);
+        for style in BackgroundStyle::ALL {
+            let text = toml::to_string(&[("background", style)].into_iter().collect::<std::collections::BTreeMap<_, _>>()).unwrap();
+            assert_eq!(text.trim(), format!("background = \"{}\"", style.name()));
+        }
+    }
+}
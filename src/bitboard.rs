@@ -0,0 +1,267 @@
+use crate::board::{GameBoard, Playfield};
+use crate::tetromino::Tetromino;
+use ggez::graphics::Color;
+
+/// Widest board a row of bits can hold
+pub const MAX_WIDTH: usize = 16;
+
+/// Packed color of an empty cell
+const EMPTY: u32 = 0x0000_00FF;
+
+/// A compact board for search and headless simulation: one `u16` of filled bits per row, plus a color layer
+/// Collision checks test whole piece rows against board rows at once, and a clone is two small
+/// allocations instead of one per row; colors are packed to 8 bits per channel, which keeps every
+/// piece and garbage color exact
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitBoard {
+    width: usize,      // Columns in use, at most `MAX_WIDTH`
+    rows: Vec<u16>,    // Filled cells, bit x for column x; rows are indexed top to bottom
+    colors: Vec<u32>,  // Packed RGBA of every cell, row by row
+}
+
+impl Default for BitBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BitBoard {
+    /// Creates an empty board of the standard size
+    pub fn new() -> Self {
+        Self::from(&GameBoard::new())
+    }
+
+    /// Creates an empty board with the given dimensions
+    /// Panics if the board is wider than `MAX_WIDTH`
+    pub fn with_size(width: usize, height: usize) -> Self {
+        assert!(width <= MAX_WIDTH, "a bitboard holds at most {} columns", MAX_WIDTH);
+        Self {
+            width,
+            rows: vec![0; height],
+            colors: vec![EMPTY; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Bits of a row with every column filled
+    fn full_row(&self) -> u16 {
+        ((1u32 << self.width) - 1) as u16
+    }
+
+    /// Color of a cell, `Color::BLACK` when it is empty
+    pub fn color(&self, x: usize, y: usize) -> Color {
+        Color::from_rgba_u32(self.colors[y * self.width + x])
+    }
+
+    /// Fills a cell with a color, or empties it with `Color::BLACK`
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        if color == Color::BLACK {
+            self.rows[y] &= !(1 << x);
+        } else {
+            self.rows[y] |= 1 << x;
+        }
+        self.colors[y * self.width + x] = color.to_rgba_u32();
+    }
+
+    /// Copies the piece onto the board and returns the cells it filled
+    /// Cells above the top edge are dropped
+    pub fn lock(&mut self, piece: &Tetromino) -> Vec<(i32, i32)> {
+        let mut placed = Vec::with_capacity(4);
+        for (x, y) in GameBoard::piece_cells(piece) {
+            if y >= 0 && (y as usize) < self.height() && x >= 0 && (x as usize) < self.width {
+                self.set(x as usize, y as usize, piece.color);
+                placed.push((x, y));
+            }
+        }
+        placed
+    }
+
+    /// Indices of the complete lines, from top to bottom
+    pub fn full_rows(&self) -> Vec<usize> {
+        let full = self.full_row();
+        (0..self.height()).filter(|&y| self.rows[y] == full).collect()
+    }
+
+    /// Removes all complete lines, shifting everything above them down
+    /// Returns the number of lines cleared
+    pub fn clear_lines(&mut self) -> u32 {
+        let full = self.full_row();
+        let width = self.width;
+        let mut kept = self.height();
+        for y in (0..self.height()).rev() {
+            if self.rows[y] != full {
+                kept -= 1;
+                if kept != y {
+                    self.rows[kept] = self.rows[y];
+                    self.colors.copy_within(y * width..(y + 1) * width, kept * width);
+                }
+            }
+        }
+        self.rows[..kept].fill(0);
+        self.colors[..kept * width].fill(EMPTY);
+        kept as u32
+    }
+
+    /// Pushes the board up and adds garbage lines at the bottom, all with a hole at `hole`
+    pub fn add_garbage(&mut self, lines: u32, hole: usize, color: Color) {
+        let lines = (lines as usize).min(self.height());
+        let (width, height) = (self.width, self.height());
+        self.rows.copy_within(lines.., 0);
+        self.colors.copy_within(lines * width.., 0);
+        for y in height - lines..height {
+            for x in 0..width {
+                self.set(x, y, if x == hole { Color::BLACK } else { color });
+            }
+        }
+    }
+
+    /// Height of each column, measured from the floor to its highest filled cell
+    pub fn column_heights(&self) -> Vec<u32> {
+        let height = self.height();
+        (0..self.width)
+            .map(|x| {
+                self.rows
+                    .iter()
+                    .position(|row| row & (1 << x) != 0)
+                    .map_or(0, |top| (height - top) as u32)
+            })
+            .collect()
+    }
+
+    /// Number of empty cells that have a filled cell somewhere above them
+    pub fn count_holes(&self) -> u32 {
+        let mut covered = 0u16;
+        let mut holes = 0;
+        for &row in &self.rows {
+            holes += (covered & !row).count_ones();
+            covered |= row;
+        }
+        holes
+    }
+}
+
+impl Playfield for BitBoard {
+    fn is_filled(&self, x: i32, y: i32) -> bool {
+        if x < 0 || x >= self.width as i32 || y >= self.height() as i32 {
+            return true;
+        }
+        y >= 0 && self.rows[y as usize] & (1 << x) != 0
+    }
+
+    /// Tests each row of the piece as a whole against the board row it covers
+    fn collides(&self, piece: &Tetromino) -> bool {
+        let (left, top) = (piece.position.x as i32, piece.position.y as i32);
+        piece.shape.iter().enumerate().any(|(dy, cells)| {
+            let bits = cells.iter().rev().fold(0u32, |bits, &cell| bits << 1 | cell as u32);
+            if bits == 0 {
+                return false;
+            }
+            // Anything shifted past the left or right edge is a wall hit
+            let mask = if left < 0 {
+                let cut = 1u32.checked_shl(left.unsigned_abs()).map_or(u32::MAX, |bit| bit - 1);
+                if bits & cut != 0 {
+                    return true;
+                }
+                bits >> left.unsigned_abs()
+            } else {
+                bits.checked_shl(left as u32).unwrap_or(u32::MAX)
+            };
+            if mask >> self.width != 0 {
+                return true;
+            }
+            let y = top + dy as i32;
+            y >= self.height() as i32 || (y >= 0 && self.rows[y as usize] as u32 & mask != 0)
+        })
+    }
+}
+
+impl From<&GameBoard> for BitBoard {
+    fn from(board: &GameBoard) -> Self {
+        let mut bits = Self::with_size(board.width(), board.height());
+        for (y, row) in board.rows().iter().enumerate() {
+            for (x, &color) in row.iter().enumerate() {
+                bits.set(x, y, color);
+            }
+        }
+        bits
+    }
+}
+
+impl From<&BitBoard> for GameBoard {
+    fn from(bits: &BitBoard) -> Self {
+        let mut board = GameBoard::with_size(bits.width(), bits.height());
+        for y in 0..bits.height() {
+            for x in 0..bits.width() {
+                board[y][x] = bits.color(x, y);
+            }
+        }
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{GARBAGE_COLOR, GRID_HEIGHT, GRID_WIDTH};
+    use crate::tetromino::TetrominoType;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_collision_matches_game_board() {
+        let mut board = GameBoard::new();
+        board[15][4] = Color::RED;
+        board[19][0] = Color::GREEN;
+        let bits = BitBoard::from(&board);
+
+        for kind in TetrominoType::ALL {
+            let mut piece = Tetromino::new(kind);
+            for _ in 0..4 {
+                for x in -4..GRID_WIDTH + 4 {
+                    for y in -3..GRID_HEIGHT + 3 {
+                        piece.position = glam::Vec2::new(x as f32, y as f32);
+                        assert_eq!(bits.collides(&piece), board.collides(&piece), "{:?} at ({}, {})", kind, x, y);
+                    }
+                }
+                piece.rotate();
+            }
+        }
+    }
+
+    #[test]
+    fn test_lock_clear_and_garbage_match_game_board() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut board = GameBoard::new();
+        let mut bits = BitBoard::new();
+        for _ in 0..200 {
+            let mut piece = Tetromino::random_from(&mut rng);
+            for _ in 0..rng.gen_range(0..4) {
+                piece.rotate();
+            }
+            piece.position.x = rng.gen_range(-1..GRID_WIDTH) as f32;
+            if board.collides(&piece) {
+                continue;
+            }
+            let piece = board.drop_position(&piece);
+            assert_eq!(bits.drop_position(&piece), piece);
+            assert_eq!(bits.lock(&piece), board.lock(&piece));
+            assert_eq!(bits.full_rows(), board.full_rows());
+            assert_eq!(bits.clear_lines(), board.clear_lines());
+            if rng.gen_bool(0.2) {
+                let hole = rng.gen_range(0..GRID_WIDTH as usize);
+                board.add_garbage(2, hole, GARBAGE_COLOR);
+                bits.add_garbage(2, hole, GARBAGE_COLOR);
+            }
+            assert_eq!(bits.column_heights(), board.column_heights());
+            assert_eq!(bits.count_holes(), board.count_holes());
+            assert_eq!(GameBoard::from(&bits), board);
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use crate::constants::{GRID_HEIGHT, GRID_WIDTH, WALL_COLOR};
+use crate::rotation::{RotationSystem, SRS};
+use crate::tetromino::{Tetromino, TetrominoType};
+use ggez::graphics::Color;
+use std::fmt;
+use std::ops::{Index, IndexMut, Range};
+
+/// What moving and rotating pieces needs from a board: which cells are filled
+/// Implemented by the full-color `GameBoard` and the compact `BitBoard` used for search
+pub trait Playfield {
+    /// Whether the cell is filled; anything outside the left, right or bottom edge counts as filled
+    /// Cells above the top edge are empty so pieces can spawn partially hidden
+    fn is_filled(&self, x: i32, y: i32) -> bool;
+
+    /// Checks if a piece collides with the board boundaries or existing blocks
+    fn collides(&self, piece: &Tetromino) -> bool {
+        GameBoard::piece_cells(piece)
+            .into_iter()
+            .any(|(x, y)| self.is_filled(x, y))
+    }
+
+    /// Returns the piece moved by the given offset, or `None` if it would collide
+    fn try_move(&self, piece: &Tetromino, dx: i32, dy: i32) -> Option<Tetromino> {
+        let mut moved = piece.clone();
+        moved.position.x += dx as f32;
+        moved.position.y += dy as f32;
+        (!self.collides(&moved)).then_some(moved)
+    }
+
+    /// Returns the piece moved down as far as it can go
+    fn drop_position(&self, piece: &Tetromino) -> Tetromino {
+        let mut dropped = piece.clone();
+        while let Some(lower) = self.try_move(&dropped, 0, 1) {
+            dropped = lower;
+        }
+        dropped
+    }
+}
+
+/// The playfield and the rules for moving, rotating and locking pieces on it
+/// Empty cells are `Color::BLACK`; rows are indexed top to bottom
+#[derive(Clone, PartialEq)]
+pub struct GameBoard {
+    cells: Vec<Vec<Color>>,
+    walls: (usize, usize),  // Columns walled off on the left and right, filled in every row
+}
+
+impl Default for GameBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameBoard {
+    /// Creates an empty board of the standard size
+    pub fn new() -> Self {
+        Self::with_size(GRID_WIDTH as usize, GRID_HEIGHT as usize)
+    }
+
+    /// Creates an empty board with the given dimensions
+    pub fn with_size(width: usize, height: usize) -> Self {
+        Self {
+            cells: vec![vec![Color::BLACK; width]; height],
+            walls: (0, 0),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.first().map_or(0, |row| row.len())
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Walls off `left` and `right` columns at the edges, narrowing the board
+    /// Wall cells are filled with `WALL_COLOR` and come back in every row that enters the board
+    pub fn set_walls(&mut self, left: usize, right: usize) {
+        let width = self.width();
+        let left = left.min(width);
+        self.walls = (left, right.min(width - left));
+        for row in &mut self.cells {
+            Self::build_walls(row, self.walls);
+        }
+    }
+
+    /// Columns between the walls, where pieces and garbage holes can go
+    pub fn open_columns(&self) -> Range<usize> {
+        self.walls.0..self.width() - self.walls.1
+    }
+
+    fn build_walls(row: &mut [Color], (left, right): (usize, usize)) {
+        let width = row.len();
+        row[..left].fill(WALL_COLOR);
+        row[width - right..].fill(WALL_COLOR);
+    }
+
+    /// A row with nothing in it but the walls
+    fn empty_row(&self) -> Vec<Color> {
+        let mut row = vec![Color::BLACK; self.width()];
+        Self::build_walls(&mut row, self.walls);
+        row
+    }
+
+    /// The rows of the board, top to bottom
+    pub fn rows(&self) -> &[Vec<Color>] {
+        &self.cells
+    }
+
+    /// The character a cell is shown with in `to_ascii`
+    pub fn cell_char(color: Color) -> char {
+        if color == Color::BLACK {
+            '.'
+        } else if color == WALL_COLOR {
+            '|'
+        } else {
+            TetrominoType::from_color(color).map_or('#', TetrominoType::letter)
+        }
+    }
+
+    /// The board as text, one line per row top first: `.` for empty cells, the piece's letter for a block in
+    /// a piece's standard color, `|` for walls and `#` for garbage or any other color
+    pub fn to_ascii(&self) -> String {
+        self.cells
+            .iter()
+            .map(|row| row.iter().map(|&color| Self::cell_char(color)).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The board coordinates covered by a piece
+    pub fn piece_cells(piece: &Tetromino) -> Vec<(i32, i32)> {
+        let mut cells = Vec::with_capacity(4);
+        for (y, row) in piece.shape.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if cell {
+                    cells.push((piece.position.x as i32 + x as i32, piece.position.y as i32 + y as i32));
+                }
+            }
+        }
+        cells
+    }
+
+    /// Returns the piece rotated clockwise with SRS kicks, or `None` if no kick works
+    /// Games can pick another system; see `rotation`
+    pub fn try_rotate(&self, piece: &Tetromino) -> Option<Tetromino> {
+        SRS.rotate(self, piece)
+    }
+
+    /// Copies the piece onto the board and returns the cells it filled
+    /// Cells above the top edge are dropped
+    pub fn lock(&mut self, piece: &Tetromino) -> Vec<(i32, i32)> {
+        self.fill(Self::piece_cells(piece), piece.color)
+    }
+
+    /// Fills cells with a color and returns the ones that were on the board
+    pub fn fill(&mut self, cells: impl IntoIterator<Item = (i32, i32)>, color: Color) -> Vec<(i32, i32)> {
+        let mut placed = Vec::with_capacity(4);
+        for (x, y) in cells {
+            if y >= 0 && (y as usize) < self.height() && x >= 0 && (x as usize) < self.width() {
+                self.cells[y as usize][x as usize] = color;
+                placed.push((x, y));
+            }
+        }
+        placed
+    }
+
+    /// Indices of the complete lines, from top to bottom
+    pub fn full_rows(&self) -> Vec<usize> {
+        (0..self.height())
+            .filter(|&y| !self.cells[y].contains(&Color::BLACK))
+            .collect()
+    }
+
+    /// Removes all complete lines, shifting everything above them down
+    /// Returns the number of lines cleared
+    pub fn clear_lines(&mut self) -> u32 {
+        let before = self.cells.len();
+        self.cells.retain(|row| row.contains(&Color::BLACK));
+        let cleared = before - self.cells.len();
+        for _ in 0..cleared {
+            self.cells.insert(0, self.empty_row());
+        }
+        cleared as u32
+    }
+
+    /// Pushes the board up and adds garbage lines at the bottom, all with a hole at `hole`
+    /// A hole in a wall is moved to the nearest open column
+    pub fn add_garbage(&mut self, lines: u32, hole: usize, color: Color) {
+        let width = self.width();
+        let open = self.open_columns();
+        let hole = hole.clamp(open.start, open.end.max(open.start + 1) - 1);
+        for _ in 0..(lines as usize).min(self.height()) {
+            let mut row = vec![color; width];
+            Self::build_walls(&mut row, self.walls);
+            if let Some(cell) = row.get_mut(hole) {
+                *cell = Color::BLACK;
+            }
+            self.cells.remove(0);
+            self.cells.push(row);
+        }
+    }
+
+    /// Height of each column, measured from the floor to its highest filled cell
+    pub fn column_heights(&self) -> Vec<u32> {
+        let height = self.height();
+        (0..self.width())
+            .map(|x| {
+                self.cells
+                    .iter()
+                    .position(|row| row[x] != Color::BLACK)
+                    .map_or(0, |top| (height - top) as u32)
+            })
+            .collect()
+    }
+
+    /// Number of empty cells that have a filled cell somewhere above them
+    pub fn count_holes(&self) -> u32 {
+        let mut holes = 0;
+        for x in 0..self.width() {
+            let mut covered = false;
+            for row in &self.cells {
+                if row[x] != Color::BLACK {
+                    covered = true;
+                } else if covered {
+                    holes += 1;
+                }
+            }
+        }
+        holes
+    }
+}
+
+impl Playfield for GameBoard {
+    fn is_filled(&self, x: i32, y: i32) -> bool {
+        if x < 0 || x >= self.width() as i32 || y >= self.height() as i32 {
+            return true;
+        }
+        y >= 0 && self.cells[y as usize][x as usize] != Color::BLACK
+    }
+}
+
+impl Index<usize> for GameBoard {
+    type Output = Vec<Color>;
+
+    fn index(&self, row: usize) -> &Self::Output {
+        &self.cells[row]
+    }
+}
+
+impl IndexMut<usize> for GameBoard {
+    fn index_mut(&mut self, row: usize) -> &mut Self::Output {
+        &mut self.cells[row]
+    }
+}
+
+/// Prints the grid as `to_ascii` does
+impl fmt::Display for GameBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_ascii())
+    }
+}
+
+/// Prints the grid rather than every cell's color, so a failing test shows the board it failed on
+impl fmt::Debug for GameBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "GameBoard {}x{}, walls {:?}", self.width(), self.height(), self.walls)?;
+        f.write_str(&self.to_ascii())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::GARBAGE_COLOR;
+
+    #[test]
+    fn test_collision_and_drop() {
+        let board = GameBoard::new();
+        let piece = Tetromino::new(TetrominoType::O);
+        assert!(!board.collides(&piece));
+        assert!(board.try_move(&piece, -10, 0).is_none());
+
+        let dropped = board.drop_position(&piece);
+        assert_eq!(dropped.position.y, (GRID_HEIGHT - 2) as f32);
+        assert!(board.try_move(&dropped, 0, 1).is_none());
+    }
+
+    #[test]
+    fn test_ascii_names_the_blocks() {
+        let mut board = GameBoard::with_size(4, 3);
+        board.set_walls(1, 0);
+        board[1][1] = TetrominoType::T.color();
+        board[2][2] = GARBAGE_COLOR;
+        assert_eq!(board.to_ascii(), "|...\n|T..\n|.#.");
+        assert_eq!(board.to_string(), board.to_ascii());
+        assert!(format!("{:?}", board).ends_with("|T..\n|.#."));
+    }
+
+    #[test]
+    fn test_rotation_kicks_off_wall() {
+        let board = GameBoard::new();
+        // A vertical I piece next to the right wall has to shift left to rotate
+        let mut piece = Tetromino::new(TetrominoType::I);
+        piece.rotate();
+        piece.position.x = (GRID_WIDTH - 2) as f32;
+        piece.position.y = 5.0;
+        let rotated = board.try_rotate(&piece).expect("rotation should kick off the wall");
+        assert_eq!(rotated.shape.len(), 1);
+        assert!(!board.collides(&rotated));
+    }
+
+    #[test]
+    fn test_lock_and_clear() {
+        let mut board = GameBoard::new();
+        let bottom = GRID_HEIGHT as usize - 1;
+        for x in 0..GRID_WIDTH as usize - 1 {
+            board[bottom][x] = Color::RED;
+        }
+        board[bottom - 1][0] = Color::GREEN;
+
+        // Fill the last gap with a vertical I piece
+        let mut piece = Tetromino::new(TetrominoType::I);
+        piece.rotate();
+        piece.position.x = (GRID_WIDTH - 1) as f32;
+        let piece = board.drop_position(&piece);
+        assert_eq!(board.lock(&piece).len(), 4);
+
+        assert_eq!(board.full_rows(), vec![bottom]);
+        assert_eq!(board.clear_lines(), 1);
+        assert!(board.full_rows().is_empty());
+        assert_eq!(board[bottom][0], Color::GREEN);
+        assert_eq!(board.height(), GRID_HEIGHT as usize);
+    }
+
+    #[test]
+    fn test_heights_holes_and_garbage() {
+        let mut board = GameBoard::new();
+        board.add_garbage(2, 3, Color::WHITE);
+        assert_eq!(board.column_heights()[0], 2);
+        assert_eq!(board.column_heights()[3], 0);
+        assert_eq!(board.count_holes(), 0);
+
+        board[GRID_HEIGHT as usize - 3][3] = Color::RED;
+        assert_eq!(board.count_holes(), 2);
+    }
+
+    #[test]
+    fn test_walls_narrow_the_board() {
+        let mut board = GameBoard::new();
+        board.set_walls(1, 2);
+        assert_eq!(board.open_columns(), 1..GRID_WIDTH as usize - 2);
+        assert!(board.is_filled(0, 0) && board.is_filled(GRID_WIDTH - 1, 0));
+        assert!(board.full_rows().is_empty());
+
+        // Garbage keeps the walls and its hole out of them; cleared rows come back walled
+        board.add_garbage(1, 0, Color::WHITE);
+        let bottom = GRID_HEIGHT as usize - 1;
+        assert_eq!(board[bottom][0], WALL_COLOR);
+        assert_eq!(board[bottom][1], Color::BLACK);
+        board[bottom][1] = Color::RED;
+        assert_eq!(board.clear_lines(), 1);
+        assert_eq!(board[bottom][0], WALL_COLOR);
+        assert_eq!(board[bottom][1], Color::BLACK);
+        assert_eq!(board[bottom][GRID_WIDTH as usize - 2], WALL_COLOR);
+    }
+}
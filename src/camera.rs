@@ -0,0 +1,93 @@
+use crate::constants::{GRID_HEIGHT, GRID_SIZE, GRID_WIDTH};
+use ggez::graphics::Rect;
+
+/// Where a board is drawn and how big, mapping board cells to logical pixels
+/// All board drawing goes through a camera, so the same code draws the main board, mini boards and the title demo
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub origin: [f32; 2],  // Top-left corner of the board in logical pixels
+    pub cell_size: f32,    // Width and height of a cell in logical pixels
+}
+
+impl Camera {
+    pub fn new(origin: [f32; 2], cell_size: f32) -> Self {
+        Self { origin, cell_size }
+    }
+
+    /// The largest camera that shows a whole board inside `area`, centered in it
+    pub fn fit(area: Rect) -> Self {
+        let cell_size = (area.w / GRID_WIDTH as f32).min(area.h / GRID_HEIGHT as f32).max(0.0);
+        let w = cell_size * GRID_WIDTH as f32;
+        let h = cell_size * GRID_HEIGHT as f32;
+        Self::new([area.x + (area.w - w) / 2.0, area.y + (area.h - h) / 2.0], cell_size)
+    }
+
+    /// Scale of the camera's cells against full-size ones; line widths and padding are multiplied by it
+    pub fn zoom(&self) -> f32 {
+        self.cell_size / GRID_SIZE
+    }
+
+    /// The same board scaled by `factor` around its center
+    pub fn zoomed(&self, factor: f32) -> Self {
+        let center = self.bounds().center();
+        let cell_size = self.cell_size * factor;
+        Self::new(
+            [
+                center.x - cell_size * GRID_WIDTH as f32 / 2.0,
+                center.y - cell_size * GRID_HEIGHT as f32 / 2.0,
+            ],
+            cell_size,
+        )
+    }
+
+    /// Top-left corner of a board cell; fractional cells are allowed for animations
+    pub fn cell(&self, x: f32, y: f32) -> [f32; 2] {
+        [self.origin[0] + x * self.cell_size, self.origin[1] + y * self.cell_size]
+    }
+
+    /// Area covered by `w` x `h` cells starting at cell (`x`, `y`)
+    pub fn cells(&self, x: f32, y: f32, w: f32, h: f32) -> Rect {
+        let [left, top] = self.cell(x, y);
+        Rect::new(left, top, w * self.cell_size, h * self.cell_size)
+    }
+
+    /// The board cell under a point in logical pixels, which may be off the board
+    pub fn cell_at(&self, x: f32, y: f32) -> (i32, i32) {
+        (((x - self.origin[0]) / self.cell_size).floor() as i32, ((y - self.origin[1]) / self.cell_size).floor() as i32)
+    }
+
+    /// Area covered by the whole board
+    pub fn bounds(&self) -> Rect {
+        self.cells(0.0, 0.0, GRID_WIDTH as f32, GRID_HEIGHT as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_centers_the_board() {
+        // Twice as tall as needed: cells fill the width and the board sits in the middle
+        let area = Rect::new(100.0, 0.0, GRID_WIDTH as f32 * 10.0, GRID_HEIGHT as f32 * 20.0);
+        let camera = Camera::fit(area);
+        assert_eq!(camera.cell_size, 10.0);
+        assert_eq!(camera.zoom(), 10.0 / GRID_SIZE);
+        assert_eq!(camera.bounds().center(), area.center());
+        assert_eq!(camera.cell(1.0, 0.5), [camera.origin[0] + 10.0, camera.origin[1] + 5.0]);
+        assert_eq!(camera.cells(2.0, 3.0, 1.0, 2.0).h, 20.0);
+        let [left, top] = camera.cell(4.0, 7.0);
+        assert_eq!(camera.cell_at(left + 9.0, top), (4, 7));
+        assert_eq!(camera.cell_at(left - 1.0, top), (3, 7));
+        assert_eq!(camera.cell_at(camera.origin[0] - 1.0, camera.origin[1] - 1.0), (-1, -1));
+    }
+
+    #[test]
+    fn test_zoom_keeps_the_center() {
+        let camera = Camera::new([50.0, 40.0], GRID_SIZE);
+        let zoomed = camera.zoomed(0.5);
+        assert_eq!(zoomed.cell_size, GRID_SIZE / 2.0);
+        assert_eq!(zoomed.bounds().center(), camera.bounds().center());
+        assert_eq!(camera.zoomed(1.0), camera);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::constants::{CHART_MAX_SAMPLES, CHART_PPS_WINDOW, CHART_SAMPLE_INTERVAL};
+use ggez::graphics::Rect;
+
+/// Score and pieces placed at one moment of a game
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    pub time: f64,    // Seconds of play
+    pub score: u32,   // Score at that moment
+    pub pieces: u32,  // Pieces locked so far
+}
+
+/// Samples taken while a game is played, charted on the results screen
+/// Long games keep a bounded number of samples by dropping every other one and sampling half as often
+#[derive(Clone, Debug)]
+pub struct GameSamples {
+    samples: Vec<Sample>,
+    interval: f64,  // Seconds between samples, doubled whenever the samples are thinned out
+}
+
+impl Default for GameSamples {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameSamples {
+    pub fn new() -> Self {
+        Self { samples: vec![Sample { time: 0.0, score: 0, pieces: 0 }], interval: CHART_SAMPLE_INTERVAL }
+    }
+
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    /// Takes a sample if the interval has passed since the last one
+    pub fn record(&mut self, time: f64, score: u32, pieces: u32) {
+        let last = self.samples.last().map_or(0.0, |s| s.time);
+        if time - last < self.interval {
+            return;
+        }
+        self.samples.push(Sample { time, score, pieces });
+        if self.samples.len() > CHART_MAX_SAMPLES {
+            let mut index = 0;
+            self.samples.retain(|_| {
+                index += 1;
+                index % 2 == 1
+            });
+            self.interval *= 2.0;
+        }
+    }
+
+    /// Takes the final sample when the game ends, whenever that is
+    pub fn finish(&mut self, time: f64, score: u32, pieces: u32) {
+        if self.samples.last().is_some_and(|s| s.time >= time) {
+            return;
+        }
+        self.samples.push(Sample { time, score, pieces });
+    }
+
+    /// Score against time
+    pub fn score_series(&self) -> Vec<(f64, f64)> {
+        self.samples.iter().map(|s| (s.time, s.score as f64)).collect()
+    }
+
+    /// Pieces per second against time, averaged over the preceding window
+    pub fn pps_series(&self) -> Vec<(f64, f64)> {
+        self.samples
+            .iter()
+            .skip(1)
+            .map(|s| {
+                let start = (s.time - CHART_PPS_WINDOW).max(0.0);
+                // The latest sample at or before the window's start, or the first one
+                let base = self.samples.iter().rev().find(|b| b.time <= start).unwrap_or(&self.samples[0]);
+                let span = s.time - base.time;
+                let pps = if span > 0.0 { s.pieces.saturating_sub(base.pieces) as f64 / span } else { 0.0 };
+                (s.time, pps)
+            })
+            .collect()
+    }
+}
+
+/// The largest value in a series, at least a small positive number so a flat chart still has a scale
+pub fn series_max(series: &[(f64, f64)]) -> f64 {
+    series.iter().map(|&(_, v)| v).fold(f64::EPSILON, f64::max)
+}
+
+/// A series scaled into `area`, time from the left edge and values from zero at the bottom edge
+/// Returns None with fewer than two distinct points, which can't be drawn as a line
+pub fn chart_points(series: &[(f64, f64)], area: Rect) -> Option<Vec<[f32; 2]>> {
+    let end = series.last()?.0;
+    let start = series.first()?.0;
+    if series.len() < 2 || end <= start {
+        return None;
+    }
+    let max = series_max(series);
+    let mut points: Vec<[f32; 2]> = Vec::with_capacity(series.len());
+    for &(time, value) in series {
+        let x = area.x + ((time - start) / (end - start)) as f32 * area.w;
+        let y = area.y + area.h - (value / max) as f32 * area.h;
+        if points.last() != Some(&[x, y]) {
+            points.push([x, y]);
+        }
+    }
+    (points.len() >= 2).then_some(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_are_thinned_and_pps_is_windowed() {
+        let mut samples = GameSamples::new();
+        for tick in 1..=20 {
+            let time = tick as f64 * 0.5;
+            samples.record(time, tick * 100, tick);
+        }
+        assert_eq!(samples.samples().len(), 11);
+        let pps = samples.pps_series();
+        assert!(pps.iter().all(|&(_, v)| (v - 2.0).abs() < 1e-9));
+
+        for tick in 21..=(CHART_MAX_SAMPLES as u32 * 2) {
+            samples.record(tick as f64 * 0.5, tick * 100, tick);
+        }
+        assert!(samples.samples().len() <= CHART_MAX_SAMPLES);
+        samples.finish(10_000.0, 5, 5);
+        assert_eq!(samples.samples().last().unwrap().time, 10_000.0);
+    }
+
+    #[test]
+    fn test_chart_points_fill_the_area() {
+        let area = Rect::new(10.0, 20.0, 100.0, 50.0);
+        let points = chart_points(&[(0.0, 0.0), (5.0, 2.0), (10.0, 4.0)], area).unwrap();
+        assert_eq!(points, vec![[10.0, 70.0], [60.0, 45.0], [110.0, 20.0]]);
+        assert_eq!(chart_points(&[(0.0, 1.0)], area), None);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::constants::{CHAT_FADE_TIME, CHAT_HISTORY, CHAT_MAX_LENGTH, CHAT_SHOW_TIME};
+use std::collections::VecDeque;
+
+/// Cleans a chat message before it is shown; the chat log runs every message through one
+pub trait ChatFilter {
+    fn clean(&self, text: &str) -> String;
+}
+
+/// Masks the listed words with asterisks wherever they appear, ignoring case
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WordFilter {
+    words: Vec<String>,  // Lowercase words to mask
+}
+
+impl WordFilter {
+    pub fn new(words: &[String]) -> Self {
+        let words = words.iter().map(|word| word.trim().to_lowercase()).filter(|word| !word.is_empty()).collect();
+        Self { words }
+    }
+}
+
+impl ChatFilter for WordFilter {
+    fn clean(&self, text: &str) -> String {
+        let mut cleaned = text.to_string();
+        for word in &self.words {
+            // Masking keeps the length, so a match's position in the lowercase copy is its position in the text
+            while let Some(start) = cleaned.to_lowercase().find(word.as_str()) {
+                cleaned.replace_range(start..start + word.len(), &"*".repeat(word.len()));
+            }
+        }
+        cleaned
+    }
+}
+
+/// A message in the chat overlay
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatLine {
+    pub from: String,  // Name of the player who sent it
+    pub text: String,  // The message, cleaned by the filter
+    pub age: f64,      // Seconds since it arrived
+}
+
+impl ChatLine {
+    /// How visible the line is in the overlay: fully for a while, then fading out over `CHAT_FADE_TIME`
+    pub fn opacity(&self) -> f32 {
+        ((CHAT_SHOW_TIME - self.age) / CHAT_FADE_TIME).clamp(0.0, 1.0) as f32
+    }
+}
+
+/// The last few chat messages, oldest first
+pub struct ChatLog {
+    lines: VecDeque<ChatLine>,     // At most `CHAT_HISTORY` lines
+    filter: Box<dyn ChatFilter>,   // Cleans each message as it arrives
+}
+
+impl ChatLog {
+    pub fn new(filter: Box<dyn ChatFilter>) -> Self {
+        Self { lines: VecDeque::new(), filter }
+    }
+
+    /// Swaps the filter for messages that arrive from now on
+    pub fn set_filter(&mut self, filter: Box<dyn ChatFilter>) {
+        self.filter = filter;
+    }
+
+    /// Adds a message, keeping only printable characters up to `CHAT_MAX_LENGTH`, and forgets the oldest beyond `CHAT_HISTORY`
+    pub fn push(&mut self, from: &str, text: &str) {
+        let printable = |text: &str, length: usize| -> String { text.chars().filter(|ch| ch.is_ascii_graphic() || *ch == ' ').take(length).collect() };
+        let text = printable(text, CHAT_MAX_LENGTH);
+        if text.trim().is_empty() {
+            return;
+        }
+        let text = self.filter.clean(&text);
+        self.lines.push_back(ChatLine { from: printable(from, CHAT_MAX_LENGTH), text, age: 0.0 });
+        while self.lines.len() > CHAT_HISTORY {
+            self.lines.pop_front();
+        }
+    }
+
+    /// Ages the messages
+    pub fn update(&mut self, dt: f64) {
+        for line in &mut self.lines {
+            line.age += dt;
+        }
+    }
+
+    /// Every message kept, oldest first, as shown while typing
+    pub fn lines(&self) -> impl Iterator<Item = &ChatLine> {
+        self.lines.iter()
+    }
+
+    /// The messages that haven't faded out yet, oldest first
+    pub fn visible(&self) -> impl Iterator<Item = &ChatLine> {
+        self.lines.iter().filter(|line| line.opacity() > 0.0)
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_filter_masks_listed_words() {
+        let filter = WordFilter::new(&["darn".to_string(), " ".to_string()]);
+        assert_eq!(filter.clean("Darn it, DARN"), "**** it, ****");
+        assert_eq!(filter.clean("nice"), "nice");
+        assert_eq!(WordFilter::default().clean("darn"), "darn");
+    }
+
+    #[test]
+    fn test_log_keeps_recent_clean_lines() {
+        let mut log = ChatLog::new(Box::new(WordFilter::new(&["darn".to_string()])));
+        log.push("ALEX", "gg darn");
+        log.push("ALEX", "   ");
+        log.push("ALEX", "\u{7}bell");
+        let texts: Vec<_> = log.lines().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts, ["gg ****", "bell"], "blank lines are dropped and unprintable characters removed");
+
+        for i in 0..CHAT_HISTORY {
+            log.push("SAM", &i.to_string());
+        }
+        assert_eq!(log.lines().count(), CHAT_HISTORY);
+        assert_eq!(log.lines().next().unwrap().text, "0");
+
+        // Lines fade out of the overlay but stay in the history
+        log.update(CHAT_SHOW_TIME - CHAT_FADE_TIME / 2.0);
+        assert_eq!(log.visible().next().unwrap().opacity(), 0.5);
+        log.update(CHAT_FADE_TIME);
+        assert_eq!(log.visible().count(), 0);
+        assert_eq!(log.lines().count(), CHAT_HISTORY);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::config;
+use crate::constants::{CLIPS_DIR, REPLAY_HOLD_TIME};
+use crate::replay::Frame;
+use ggez::graphics::Color;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Pixels per board cell in a clip, one of them the gap between cells
+const CELL_PIXELS: usize = 12;
+
+/// Folder clips are saved to, next to the settings file
+pub fn clips_dir() -> PathBuf {
+    config::config_dir().join(CLIPS_DIR)
+}
+
+/// A new file in the clips folder, named after the time it's saved
+pub fn new_clip_path() -> PathBuf {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    clips_dir().join(format!("replay_{}.gif", seconds))
+}
+
+/// Colors of a clip, the empty cell black first; boards use a handful, but a palette holds 256 at most,
+/// so any beyond that are drawn with the closest one kept
+struct Palette {
+    colors: Vec<[u8; 3]>,
+}
+
+impl Palette {
+    fn new(frames: &[Frame]) -> Self {
+        let mut colors = vec![[0, 0, 0]];
+        for color in frames.iter().flat_map(|frame| frame.cells.iter().flatten()) {
+            let rgb = rgb(*color);
+            if colors.len() < 256 && !colors.contains(&rgb) {
+                colors.push(rgb);
+            }
+        }
+        Self { colors }
+    }
+
+    fn index(&self, color: Color) -> u8 {
+        let rgb = rgb(color);
+        let distance = |other: &[u8; 3]| (0..3).map(|i| (rgb[i] as i32 - other[i] as i32).pow(2)).sum::<i32>();
+        self.colors.iter().enumerate().min_by_key(|(_, other)| distance(other)).map_or(0, |(i, _)| i as u8)
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        self.colors.iter().flatten().copied().collect()
+    }
+}
+
+fn rgb(color: Color) -> [u8; 3] {
+    let (r, g, b) = color.to_rgb();
+    [r, g, b]
+}
+
+/// A frame's board as palette indices, each cell a square with a black gap on its right and bottom
+fn pixels(frame: &Frame, palette: &Palette, width: usize, height: usize) -> Vec<u8> {
+    let mut pixels = vec![0; width * height];
+    for (y, row) in frame.cells.iter().enumerate() {
+        for (x, &color) in row.iter().enumerate() {
+            let index = palette.index(color);
+            for py in y * CELL_PIXELS..(y + 1) * CELL_PIXELS - 1 {
+                let start = py * width + x * CELL_PIXELS;
+                pixels[start..start + CELL_PIXELS - 1].fill(index);
+            }
+        }
+    }
+    pixels
+}
+
+/// Encodes frames as a looping animated GIF at the speed they were played, holding the last one a moment
+/// before starting over. Frames that show the same board are merged into one shown for longer
+pub fn write_gif(frames: &[Frame], writer: impl Write) -> Result<(), gif::EncodingError> {
+    let Some(first) = frames.first() else {
+        return Ok(());
+    };
+    let columns = first.cells.first().map_or(0, Vec::len);
+    let (width, height) = (columns * CELL_PIXELS, first.cells.len() * CELL_PIXELS);
+    let palette = Palette::new(frames);
+    let mut encoder = gif::Encoder::new(writer, width as u16, height as u16, &palette.bytes())?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    // GIF delays are whole hundredths of a second, so each frame ends on the hundredth nearest its true end
+    let mut shown = 0;
+    let mut start = 0;
+    while start < frames.len() {
+        let end = (start + 1..frames.len()).find(|&i| frames[i].cells != frames[start].cells).unwrap_or(frames.len());
+        let until = match frames.get(end) {
+            Some(next) => next.time - first.time,
+            None => frames[end - 1].time - first.time + REPLAY_HOLD_TIME,
+        };
+        let hundredths = (until * 100.0).round() as u64;
+        let mut frame = gif::Frame::from_indexed_pixels(width as u16, height as u16, pixels(&frames[start], &palette, width, height), None);
+        frame.delay = hundredths.saturating_sub(shown).min(u16::MAX as u64) as u16;
+        encoder.write_frame(&frame)?;
+        shown = hundredths;
+        start = end;
+    }
+    Ok(())
+}
+
+/// Saves frames as a GIF file, creating its folder if needed
+pub fn save_gif(frames: &[Frame], path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = BufWriter::new(File::create(path)?);
+    write_gif(frames, file).map_err(|e| match e {
+        gif::EncodingError::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, Move};
+
+    #[test]
+    fn test_gif_merges_repeated_frames() {
+        let mut game = Game::with_seed(1);
+        let mut frames = vec![Frame::capture(0.0, &game), Frame::capture(0.5, &game)];
+        game.apply(Move::HardDrop);
+        frames.push(Frame::capture(1.0, &game));
+
+        let mut gif = Vec::new();
+        write_gif(&frames, &mut gif).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(gif.as_slice()).unwrap();
+        assert_eq!(decoder.width() as usize, game.board().width() * CELL_PIXELS);
+        let mut delays = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+        // The two frames of the same board are one, and the last is held
+        assert_eq!(delays, [100, (REPLAY_HOLD_TIME * 100.0) as u16]);
+    }
+}
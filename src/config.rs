@@ -0,0 +1,335 @@
+use crate::ai::Difficulty;
+use crate::background::BackgroundStyle;
+use crate::constants::{CONFIG_DIR_NAME, CONFIG_FILE, MATCH_LENGTHS, MAX_START_LEVEL};
+use crate::grid::GridStyle;
+use crate::garbage::GarbageStyle;
+use crate::high_scores::CharClass;
+use crate::keybindings::KeyBindings;
+use crate::mode::Handicap;
+use crate::sfx::PitchVariation;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory the config file lives in, e.g. `~/.config/tetris` on Linux
+/// Falls back to the working directory if the platform has no config directory
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .map(|dir| dir.join(CONFIG_DIR_NAME))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Full path of the config file
+pub fn config_path() -> PathBuf {
+    config_dir().join(CONFIG_FILE)
+}
+
+/// Sound levels, each from 0.0 (muted) to 1.0 (full volume)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub music_volume: f32,     // Volume of the background music
+    pub sfx_volume: f32,       // Volume of the sound effects
+    pub muted: bool,           // Whether all sound is silenced; the volumes are kept for unmuting
+    pub shuffle_music: bool,   // Whether the game music playlist plays in random order
+    pub pitch_spread: f32,     // Random pitch change of move, rotate and drop sounds, as a fraction either way
+    pub combo_pitch: f32,      // Semitones the clear sound rises with each lock of a combo
+    pub stereo_pan: f32,       // How far piece sounds pan toward the piece's side, from 0.0 (centered) to 1.0
+    pub mute_unfocused: bool,  // Whether the music goes quiet while the game window is in the background
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        let pitch = PitchVariation::default();
+        Self {
+            music_volume: 0.7,
+            sfx_volume: 1.0,
+            muted: false,
+            shuffle_music: false,
+            pitch_spread: pitch.spread,
+            combo_pitch: pitch.combo_step,
+            stereo_pan: 1.0,
+            mute_unfocused: true,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// The pitch settings as the sound manager takes them
+    pub fn pitch_variation(&self) -> PitchVariation {
+        PitchVariation { spread: self.pitch_spread, combo_step: self.combo_pitch }
+    }
+}
+
+/// How the window is set up and what the game looks like
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub fullscreen: bool,             // Whether the game starts in fullscreen
+    pub vsync: bool,                  // Whether to sync frames to the display refresh rate; applied at startup
+    pub frame_cap: u32,               // Most frames drawn per second, or 0 for no cap
+    pub show_fps: bool,               // Whether the frame rate is shown in the corner of the screen
+    pub theme: String,                // Name of the theme to draw with
+    pub particles: bool,              // Whether line clears and hard drops throw particles
+    pub block_patterns: bool,         // Whether blocks show a glyph per piece, for telling pieces apart without color
+    pub background: BackgroundStyle,  // Scenery behind the board, changing every few levels
+    pub grid: GridStyle,              // How the grid over the board is drawn
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            fullscreen: false,
+            vsync: true,
+            frame_cap: 0,
+            show_fps: false,
+            theme: "classic".to_string(),
+            particles: true,
+            block_patterns: false,
+            background: BackgroundStyle::Starfield,
+            grid: GridStyle::Lines,
+        }
+    }
+}
+
+/// How the game handles held keys, how pieces turn and what it draws to help the player
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameplaySettings {
+    pub das_ms: u32,        // Delayed auto shift: how long a direction is held before it repeats
+    pub arr_ms: u32,        // Auto repeat rate: time between repeated moves (0 = instantly to the wall)
+    pub ghost_piece: bool,  // Whether to show where the piece will land
+    pub rotation: String,   // Name of the rotation system pieces spawn and turn with
+    pub touch_overlay: bool, // Whether to show the touch gestures over the board during games
+    pub record_play_data: bool, // Whether every placement is logged to the play data file for machine learning
+    pub start_level: u32,   // Level single-player games start at, picked in the title menu
+}
+
+impl Default for GameplaySettings {
+    fn default() -> Self {
+        Self {
+            das_ms: 170,
+            arr_ms: 50,
+            ghost_piece: true,
+            rotation: "srs".to_string(),
+            touch_overlay: false,
+            record_play_data: false,
+            start_level: 1,
+        }
+    }
+}
+
+/// Options that make the game comfortable for more players
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    pub reduce_flashing: bool,  // Replace blinking text, line clear flashes, spark bursts and level up fanfares with calmer effects
+}
+
+/// Learning aids shown in practice games, and the pieces they deal; ranked games never use them
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PracticeSettings {
+    pub heatmap: bool,          // Shade the board by how often pieces locked on each cell this session
+    pub t_spin_hints: bool,     // Highlight the spots where a T could spin in for a single or double
+    pub gravity: bool,          // Whether pieces fall on their own; off so they only move by input
+    pub sequence: String,       // Piece letters dealt over and over instead of random pieces, like "IJLOSTZ"
+    pub sequence_file: String,  // File of piece letters used instead of `sequence`, relative to the settings file
+}
+
+/// Rules of versus games on this machine
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VersusSettings {
+    pub garbage_style: GarbageStyle,  // Where the holes of garbage received go
+    pub handicap: Handicap,           // Harder rules a guest plays under; each profile keeps its own instead
+    pub first_to: u32,                // Round wins that take a match; a LAN host's choice is used by both players
+    pub difficulty: Difficulty,       // How strong the computer opponent plays
+}
+
+impl Default for VersusSettings {
+    fn default() -> Self {
+        Self {
+            garbage_style: GarbageStyle::default(),
+            handicap: Handicap::default(),
+            first_to: MATCH_LENGTHS[0],
+            difficulty: Difficulty::default(),
+        }
+    }
+}
+
+/// How names are entered for the high score table
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NameEntrySettings {
+    pub arcade_initials: bool,               // Whether names are three capital initials, as on arcade machines
+    pub last_name: String,                   // Name entered last, offered first next time
+    pub blocked_words: Vec<String>,          // Words a name may not contain, ignoring case, spacing and look-alike digits
+    pub allowed_characters: Vec<CharClass>,  // Kinds of characters names may use; empty allows any
+}
+
+/// Where scores are shared beyond this machine, and how chat with other players works
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OnlineSettings {
+    pub leaderboard_url: String,     // `http://` address of the online leaderboard; empty keeps scores offline
+    pub lobby_address: String,       // Lobby server listing rooms for online versus, as `host[:port]`; empty hides the rooms
+    pub chat_muted: bool,            // Hide chat messages from other players
+    pub blocked_words: Vec<String>,  // Words masked with asterisks in chat messages
+    pub overlay_port: u16,           // Local port stream overlays read the live game from; 0 keeps the overlay server off
+}
+
+/// Player preferences that persist between runs
+/// Missing fields in the settings file fall back to their defaults
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub audio: AudioSettings,
+    pub video: VideoSettings,
+    pub gameplay: GameplaySettings,
+    pub accessibility: AccessibilitySettings,
+    pub practice: PracticeSettings,
+    pub versus: VersusSettings,
+    pub name_entry: NameEntrySettings,
+    pub online: OnlineSettings,
+    pub controls: KeyBindings,
+}
+
+impl Settings {
+    /// Allowed range and step for the DAS and ARR settings, in milliseconds
+    pub const DAS_RANGE: (u32, u32) = (50, 300);
+    pub const ARR_RANGE: (u32, u32) = (0, 100);
+    pub const TIMING_STEP: u32 = 10;
+    /// Step used when adjusting a volume
+    pub const VOLUME_STEP: f32 = 0.1;
+    /// Frame caps to choose from, where 0 means uncapped
+    pub const FRAME_CAPS: [u32; 3] = [60, 120, 0];
+
+    /// Loads settings from a file, falling back to the defaults if it is missing or invalid
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// Saves settings to a file, creating its directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string_pretty(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+
+    /// Changes a volume by `steps` volume steps, staying within 0.0..=1.0
+    pub fn adjust_volume(volume: f32, steps: i32) -> f32 {
+        // Round to whole steps so repeated adjustments don't drift
+        let step_count = (volume / Self::VOLUME_STEP).round() as i32 + steps;
+        (step_count as f32 * Self::VOLUME_STEP).clamp(0.0, 1.0)
+    }
+
+    /// Changes a timing value by `steps` timing steps, staying within `range`
+    pub fn adjust_timing(value: u32, steps: i32, range: (u32, u32)) -> u32 {
+        let adjusted = value as i64 + steps as i64 * Self::TIMING_STEP as i64;
+        adjusted.clamp(range.0 as i64, range.1 as i64) as u32
+    }
+
+    /// Changes a start level by `steps` levels, staying within 1..=MAX_START_LEVEL
+    pub fn adjust_start_level(level: u32, steps: i32) -> u32 {
+        (level as i64 + steps as i64).clamp(1, MAX_START_LEVEL as i64) as u32
+    }
+
+    /// Moves `steps` places through `FRAME_CAPS`, wrapping around; a cap not in the list starts from the first
+    pub fn cycle_frame_cap(cap: u32, steps: i32) -> u32 {
+        let count = Self::FRAME_CAPS.len() as i32;
+        let index = Self::FRAME_CAPS.iter().position(|&other| other == cap).unwrap_or(0) as i32;
+        Self::FRAME_CAPS[(index + steps).rem_euclid(count) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keybindings::Action;
+    use ggez::input::keyboard::KeyCode;
+
+    #[test]
+    fn test_toml_roundtrip_and_defaults() {
+        let mut settings = Settings::default();
+        settings.audio.music_volume = 0.3;
+        settings.audio.muted = true;
+        settings.audio.shuffle_music = true;
+        settings.audio.combo_pitch = 2.0;
+        settings.video.fullscreen = true;
+        settings.gameplay.ghost_piece = false;
+        settings.gameplay.touch_overlay = true;
+        settings.gameplay.record_play_data = true;
+        settings.gameplay.start_level = 7;
+        settings.accessibility.reduce_flashing = true;
+        settings.practice.heatmap = true;
+        settings.practice.t_spin_hints = true;
+        settings.practice.gravity = true;
+        settings.practice.sequence = "TSZ".to_string();
+        settings.versus.handicap.garbage_rows = 4;
+        settings.versus.handicap.no_hold = true;
+        settings.versus.garbage_style = GarbageStyle::Shifting;
+        settings.versus.first_to = 7;
+        settings.versus.difficulty = Difficulty::Insane;
+        settings.name_entry.arcade_initials = true;
+        settings.name_entry.last_name = "Alex".to_string();
+        settings.name_entry.blocked_words = vec!["darn".to_string()];
+        settings.name_entry.allowed_characters = vec![CharClass::Letters, CharClass::Digits];
+        settings.online.leaderboard_url = "http://scores.example.org/tetris".to_string();
+        settings.online.lobby_address = "lobby.example.org".to_string();
+        settings.online.chat_muted = true;
+        settings.online.blocked_words = vec!["darn".to_string()];
+        settings.online.overlay_port = 7880;
+        settings.controls.bind(Action::HardDrop, KeyCode::X);
+        let text = toml::to_string_pretty(&settings).unwrap();
+        assert_eq!(toml::from_str::<Settings>(&text).unwrap(), settings);
+
+        // Missing sections and fields use the defaults
+        let partial: Settings = toml::from_str("[gameplay]\ndas_ms = 100\n").unwrap();
+        assert_eq!(partial.gameplay.das_ms, 100);
+        assert_eq!(partial.gameplay.arr_ms, GameplaySettings::default().arr_ms);
+        assert_eq!(partial.audio, AudioSettings::default());
+        assert_eq!(partial.controls, KeyBindings::default());
+    }
+
+    #[test]
+    fn test_save_creates_directory() {
+        let dir = std::env::temp_dir().join(format!("tetris-config-test-{}", std::process::id()));
+        let path = dir.join("nested").join(CONFIG_FILE);
+        let mut settings = Settings::default();
+        settings.gameplay.das_ms = 120;
+        settings.save(&path).unwrap();
+        assert_eq!(Settings::load(&path), settings);
+        let _ = fs::remove_dir_all(&dir);
+
+        // A missing file falls back to the defaults
+        assert_eq!(Settings::load(&path), Settings::default());
+    }
+
+    #[test]
+    fn test_adjustments_stay_in_range() {
+        assert!((Settings::adjust_volume(0.7, 1) - 0.8).abs() < 1e-6);
+        assert_eq!(Settings::adjust_volume(1.0, 1), 1.0);
+        assert_eq!(Settings::adjust_volume(0.0, -1), 0.0);
+
+        assert_eq!(Settings::adjust_timing(170, 1, Settings::DAS_RANGE), 180);
+        assert_eq!(Settings::adjust_timing(300, 1, Settings::DAS_RANGE), 300);
+        assert_eq!(Settings::adjust_timing(0, -1, Settings::ARR_RANGE), 0);
+
+        assert_eq!(Settings::adjust_start_level(1, -1), 1);
+        assert_eq!(Settings::adjust_start_level(19, 3), MAX_START_LEVEL);
+        assert_eq!(Settings::adjust_start_level(40, -1), MAX_START_LEVEL);
+
+        assert_eq!(Settings::cycle_frame_cap(60, 1), 120);
+        assert_eq!(Settings::cycle_frame_cap(0, 1), 60);
+        assert_eq!(Settings::cycle_frame_cap(60, -1), 0);
+        assert_eq!(Settings::cycle_frame_cap(75, 1), 120);
+    }
+}
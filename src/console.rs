@@ -0,0 +1,127 @@
+use crate::constants::{CONSOLE_LINES, LOG_LEVEL_VAR};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// A warning or error kept for the in-game console
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    pub level: Level,    // Warn or Error
+    pub target: String,  // Part of the game it came from, e.g. `sound` or `net`
+    pub message: String,
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.level, self.target, self.message)
+    }
+}
+
+/// The recent warnings and errors, shared between the logger and the game that shows them
+#[derive(Debug, Clone, Default)]
+pub struct Console {
+    recent: Arc<Mutex<(VecDeque<LogLine>, usize)>>, // Newest last, and how many were ever logged
+}
+
+impl Console {
+    /// The warnings and errors still kept, oldest first
+    pub fn lines(&self) -> Vec<LogLine> {
+        self.recent.lock().map(|recent| recent.0.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// How many warnings and errors were logged since the game started, including those no longer kept
+    pub fn total(&self) -> usize {
+        self.recent.lock().map(|recent| recent.1).unwrap_or_default()
+    }
+
+    fn push(&self, line: LogLine) {
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.0.len() == CONSOLE_LINES {
+                recent.0.pop_front();
+            }
+            recent.0.push_back(line);
+            recent.1 += 1;
+        }
+    }
+}
+
+/// Prints every record of the game at or above its level to stderr, and keeps the warnings and errors for the console
+/// Libraries the game uses are only heard from when they warn, and never in the console
+pub struct Logger {
+    level: LevelFilter,
+    console: Console,
+}
+
+impl Logger {
+    pub fn new(level: LevelFilter, console: Console) -> Self {
+        Self { level, console }
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        let ours = record.module_path().is_some_and(|path| path.starts_with("tetris"));
+        if !self.enabled(record.metadata()) || (!ours && record.level() > Level::Warn) {
+            return;
+        }
+        eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+        if ours && record.level() <= Level::Warn {
+            self.console.push(LogLine {
+                level: record.level(),
+                target: record.target().to_string(),
+                message: record.args().to_string(),
+            });
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// The lowest level to print: the one named by `TETRIS_LOG`, or info
+pub fn level_from_env() -> LevelFilter {
+    std::env::var(LOG_LEVEL_VAR).ok().and_then(|level| level.trim().parse().ok()).unwrap_or(LevelFilter::Info)
+}
+
+/// Installs the logger for the whole program and returns the console it fills
+/// Records logged before this, or when another logger is already installed, only reach that logger
+pub fn init() -> Console {
+    let console = Console::default();
+    let level = level_from_env();
+    if log::set_boxed_logger(Box::new(Logger::new(level, console.clone()))).is_ok() {
+        log::set_max_level(level);
+    }
+    console
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(logger: &Logger, level: Level, module: &str, message: &str) {
+        let args = format_args!("{}", message);
+        logger.log(&Record::builder().level(level).target("sound").module_path(Some(module)).args(args).build());
+    }
+
+    #[test]
+    fn test_console_keeps_recent_warnings() {
+        let console = Console::default();
+        let logger = Logger::new(LevelFilter::Info, console.clone());
+        record(&logger, Level::Info, "tetris::sfx", "loaded");
+        record(&logger, Level::Debug, "tetris::sfx", "too detailed");
+        record(&logger, Level::Warn, "wgpu_core", "not the game's");
+        assert!(console.lines().is_empty());
+
+        for n in 0..CONSOLE_LINES + 2 {
+            record(&logger, Level::Warn, "tetris::sfx", &format!("missing {}", n));
+        }
+        let lines = console.lines();
+        assert_eq!(lines.len(), CONSOLE_LINES);
+        assert_eq!(lines[0].to_string(), "WARN sound: missing 2");
+        assert_eq!(console.total(), CONSOLE_LINES + 2);
+    }
+}
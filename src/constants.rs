@@ -1,15 +1,21 @@
+use ggez::graphics::Color;
+
 // Game constants
 pub const GRID_SIZE: f32 = 60.0;      // Size of each grid cell in pixels (doubled from 30.0)
 pub const GRID_WIDTH: i32 = 10;       // Width of the game board in cells
 pub const GRID_HEIGHT: i32 = 20;      // Height of the game board in cells
 pub const MARGIN: f32 = 40.0;         // Margin between game field and window borders (doubled from 20.0)
 pub const BORDER_WIDTH: f32 = 4.0;    // Width of the game field border (doubled from 2.0)
-pub const PREVIEW_BOX_SIZE: f32 = 6.0;  // Size of the preview box in grid cells
-pub const SCREEN_WIDTH: f32 = GRID_SIZE * (GRID_WIDTH as f32 + PREVIEW_BOX_SIZE + 3.0) + 2.0 * MARGIN;   // Total screen width including preview and margins
+pub const PREVIEW_BOX_SIZE: f32 = 6.0;  // Width of the right-hand panels in grid cells
+pub const HOLD_BOX_SIZE: f32 = 5.0;   // Width of the hold box in grid cells
+pub const PANEL_SPACING: f32 = 20.0;  // Space between stacked panels
+pub const NEXT_QUEUE_SIZE: usize = 3; // Number of upcoming pieces shown in the queue
+pub const SCREEN_WIDTH: f32 = GRID_SIZE * (HOLD_BOX_SIZE + GRID_WIDTH as f32 + PREVIEW_BOX_SIZE + 2.0) + 2.0 * MARGIN;   // Hold box, board and panels, one cell apart, plus margins
 pub const SCREEN_HEIGHT: f32 = GRID_SIZE * GRID_HEIGHT as f32 + 2.0 * MARGIN; // Total screen height including margins
+pub const INITIAL_WINDOW_SCALE: f32 = 0.6; // Window size at startup relative to the logical screen size
+pub const MIN_WINDOW_SCALE: f32 = 0.3;     // Smallest window size the user can resize to
 pub const DROP_TIME: f64 = 1.0;       // Time in seconds between automatic piece movements
-pub const PREVIEW_X: f32 = GRID_SIZE * (GRID_WIDTH as f32 + 3.0) + MARGIN; // X position of preview box, with extra spacing
-pub const PREVIEW_Y: f32 = GRID_SIZE * 2.0 + MARGIN;  // Y position of preview box
+pub const LOCK_DELAY: f64 = 0.5;      // Seconds a piece soft dropped into the stack waits before it locks
 
 // 8-bit aesthetic constants
 #[allow(dead_code)]
@@ -18,12 +24,119 @@ pub const PIXEL_SIZE: f32 = 6.0;      // Size of a "pixel" in our 8-bit style
 pub const BLOCK_PIXELS: i32 = 8;      // Number of "pixels" per tetris block (squared)
 pub const GRID_LINE_WIDTH: f32 = 2.0; // Width of grid lines
 pub const BLOCK_PADDING: f32 = 4.0;   // Padding inside blocks to create a pixelated effect
+pub const GARBAGE_COLOR: Color = Color { r: 0.4, g: 0.4, b: 0.4, a: 1.0 }; // Color of garbage lines sent by an opponent
+pub const WALL_COLOR: Color = Color { r: 0.2, g: 0.2, b: 0.25, a: 1.0 }; // Color of the columns walled off by a narrow board handicap
+
+// Versus mini view constants
+pub const OPPONENT_CELL_SIZE: f32 = 16.0; // Size of a cell in the opponent's mini board
+pub const GARBAGE_METER_WIDTH: f32 = 16.0; // Width of the garbage meter next to the board
+pub const ATTACK_METER_DRAIN: f64 = 4.0;   // Lines a second a sent attack drains from the garbage meter
+
+// Versus match constants
+pub const MATCH_LENGTHS: [u32; 3] = [3, 5, 7]; // Round wins a versus match can be played to
+
+// Chat constants
+pub const CHAT_MAX_LENGTH: usize = 60; // Longest chat message, in characters
+pub const CHAT_HISTORY: usize = 8;     // Chat messages kept, shown while typing
+pub const CHAT_SHOW_TIME: f64 = 8.0;   // Seconds a chat message stays in the overlay
+pub const CHAT_FADE_TIME: f64 = 1.0;   // Seconds at the end of that it takes to fade out
+
+// Hot reload constants
+pub const HOT_RELOAD_INTERVAL: f64 = 0.5; // Seconds the resource folders must be quiet before changes are reloaded in debug builds
+pub const HOT_RELOAD_DIRS: [&str; 4] = ["themes", "skins", "fonts", "sounds"]; // Resource folders watched for changes
+
+// Step mode constants
+pub const STEP_TICK: f64 = 1.0 / 60.0; // Seconds of game time each press of the step key runs in step mode
+
+// Log console constants
+pub const CONSOLE_LINES: usize = 12;       // Warnings and errors kept for the in-game console
+pub const LOG_LEVEL_VAR: &str = "TETRIS_LOG"; // Environment variable naming the lowest level printed, e.g. `debug`
+
+// Attract mode constants
+pub const ATTRACT_DELAY: f64 = 10.0;  // Seconds idle on the title screen before the demo starts
+pub const ATTRACT_MOVE_DELAY: f32 = 0.15; // Seconds between the demo bot's inputs
+pub const ATTRACT_ALPHA: f32 = 0.35;  // Opacity of the demo board behind the title text
+
+// Kiosk mode constants
+pub const KIOSK_IDLE_TIMEOUT: f64 = 60.0;  // Seconds without input before a kiosk goes back to the title screen
+pub const KIOSK_DEMO_TIME: f64 = 45.0;     // Seconds the demo plays in a kiosk before the high scores show
+pub const KIOSK_SCORES_TIME: f64 = 12.0;   // Seconds the high scores show before the demo plays again
+
+// Line clear animation constants
+pub const LINE_CLEAR_FLASH_TIME: f64 = 0.12;    // Seconds completed rows flash white
+pub const LINE_CLEAR_COLLAPSE_TIME: f64 = 0.18; // Seconds for the rows above to fall into place
+pub const LOCK_FLASH_TIME: f64 = 0.05;          // Seconds a locked piece shows white before taking its color
+pub const QUEUE_SHIFT_TIME: f64 = 0.15;         // Seconds the next queue takes to slide up when a piece spawns
+pub const LEVEL_UP_TIME: f64 = 1.2;             // Seconds the new level number shows over the board
+pub const LEVEL_UP_TINT_TIME: f64 = 0.4;        // Seconds the board's tint takes to fade after a level up
+pub const LEVEL_UP_POP_TIME: f64 = 0.15;        // Seconds the level number takes to shrink to its size
+pub const OPENER_NOTICE_TIME: f64 = 2.0;       // Seconds the opener trainer says it starts over after a mistake
+
+// Particle constants
+pub const MAX_PARTICLES: usize = 600;       // Most particles alive at once; extra spawns are dropped
+pub const SPARKS_PER_CELL: usize = 4;       // Sparks from each block of a cleared row
+pub const DUST_PER_CELL: usize = 5;         // Dust specks under each bottom block of a hard-dropped piece
+pub const PARTICLE_GRAVITY: f32 = 1200.0;   // Downward acceleration of sparks in pixels per second squared
+
+// Game mode constants
+pub const SPRINT_LINES: u32 = 40;   // Lines to clear to finish a sprint
+pub const ULTRA_TIME: f64 = 120.0;   // Length of an ultra game in seconds
+pub const DIG_ROWS: u32 = 10;       // Garbage rows to clear to finish a dig game
+pub const MAX_START_LEVEL: u32 = 20; // Highest level a game can be started at from the title menu
+
+// Touch constants
+pub const TOUCH_STEP: f32 = GRID_SIZE;              // Logical pixels a finger drags for each move or soft drop step
+pub const TOUCH_TAP_DISTANCE: f32 = 20.0;           // Farthest a finger can move and still tap
+pub const TOUCH_FLICK_DISTANCE: f32 = GRID_SIZE * 2.0; // Shortest stroke down that hard drops when made quickly
+pub const TOUCH_FLICK_TIME: f64 = 0.25;             // Longest a stroke down can take to count as a flick
+
+// Instant replay constants
+pub const REPLAY_LENGTH: f64 = 10.0;       // Seconds at the end of a game kept for the instant replay
+pub const REPLAY_FRAME_TIME: f64 = 1.0 / 30.0; // Seconds between recorded frames
+pub const REPLAY_SPEED: f64 = 0.5;         // Playback speed against the game's
+pub const REPLAY_HOLD_TIME: f64 = 1.5;     // Seconds the last frame stays up before the replay starts over
+
+// Results chart constants
+pub const CHART_SAMPLE_INTERVAL: f64 = 1.0; // Seconds between score samples taken during a game
+pub const CHART_MAX_SAMPLES: usize = 600;   // Samples kept before they are thinned out and the interval doubled
+pub const CHART_PPS_WINDOW: f64 = 5.0;      // Seconds of play averaged into each point of the PPS chart
+
+// Garbage constants
+pub const GARBAGE_SHIFT_CHANCE: f64 = 0.2; // Chance each line of shifting garbage moves its hole a column over
+
+// Handicap constants
+pub const HANDICAP_MAX_GARBAGE_ROWS: u32 = 10; // Most garbage rows a handicapped board can start with
+pub const HANDICAP_MAX_WALLS: u32 = 4;         // Most columns a handicap can wall off
+pub const HANDICAP_MAX_LEVELS: u32 = 9;        // Most levels a handicapped game can start above the first
+
+// Pause constants
+pub const RESUME_COUNTDOWN: f64 = 3.0; // Seconds counted down before play resumes after a pause
 
 // Scoring constants
 pub const SCORE_SINGLE: u32 = 100;    // Points for clearing 1 line
 pub const SCORE_DOUBLE: u32 = 300;    // Points for clearing 2 lines
 pub const SCORE_TRIPLE: u32 = 500;    // Points for clearing 3 lines
 pub const SCORE_TETRIS: u32 = 800;    // Points for clearing 4 lines
-pub const SCORE_DROP: u32 = 1;        // Points per cell for dropping a piece
+pub const SCORE_SOFT_DROP: u32 = 1;   // Points per cell for soft dropping a piece
+pub const SCORE_HARD_DROP: u32 = 2;   // Points per cell for hard dropping a piece
+pub const SCORE_COMBO: u32 = 50;      // Points per lock in a combo, before this one
 pub const MAX_HIGH_SCORES: usize = 10; // Maximum number of high scores to store
-pub const HIGH_SCORES_FILE: &str = "high_scores.json";
\ No newline at end of file
+pub const INITIALS_LENGTH: usize = 3;  // Length of a name in arcade initials mode
+pub const HIGH_SCORES_FILE: &str = "high_scores.json";
+pub const CONFIG_DIR_NAME: &str = "tetris"; // Folder inside the platform config directory
+pub const RESOURCES_VAR: &str = "TETRIS_RESOURCES"; // Environment variable naming the resource folder, overriding the search
+pub const RESOURCE_FOLDERS: [&str; 7] = ["sounds", "themes", "openers", "scripts", "fonts", "skins", "backgrounds"]; // A folder holding any of these is a resource folder
+pub const CONFIG_FILE: &str = "settings.toml"; // Settings file with audio, video, gameplay and control preferences
+pub const PROFILES_FILE: &str = "profiles.json"; // Player profiles, next to the settings file
+pub const LIFETIME_STATS_FILE: &str = "lifetime_stats.json"; // Totals over every game played on this machine
+pub const PLAY_DATA_FILE: &str = "play_data.jsonl"; // Placements logged for machine learning, next to the settings file
+pub const AI_WEIGHTS_FILE: &str = "ai_weights.toml"; // Bot heuristic weights found by the tuner, next to the settings file
+pub const SCENARIOS_DIR: &str = "scenarios"; // Board editor scenarios, next to the settings file
+pub const CLIPS_DIR: &str = "clips"; // Animated GIFs saved from the results screen, next to the settings file
+pub const HISTORY_FILE: &str = "history.jsonl"; // One summary line per game played, next to the settings file
+pub const HISTORY_PAGE_SIZE: usize = 10; // Games on each page of the history screen
+pub const GHOST_FILE: &str = "sprint_ghost.json"; // Every lock of the fastest sprint, raced as a ghost in later sprints
+pub const AUTOSAVE_FILE: &str = "autosave.json"; // Score and statistics of the game in progress, next to the settings file
+pub const AUTOSAVE_INTERVAL: f64 = 10.0; // Seconds of play between autosaves, besides those after level ups and big clears
+pub const SCENARIO_SLOTS: usize = 9;  // Numbered scenario files the board editor saves to and loads from
+pub const SOUND_FORMATS: [&str; 3] = ["ogg", "flac", "wav"]; // Sound file extensions tried in order, compressed first
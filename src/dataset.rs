@@ -0,0 +1,154 @@
+use crate::board::GameBoard;
+use crate::config;
+use crate::constants::PLAY_DATA_FILE;
+use crate::game::LockedPiece;
+use crate::tetromino::Tetromino;
+use ggez::graphics::Color;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Version of the record layout, written at the start of every game; bumped whenever a field changes
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Full path of the play data log, next to the settings file
+pub fn play_data_path() -> PathBuf {
+    config::config_dir().join(PLAY_DATA_FILE)
+}
+
+/// One line of the play data log, for training models that imitate human players
+/// Pieces are named by their letters, and boards are rows top first, each a bit mask with bit x set for a filled column x
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DataRecord {
+    /// Starts a game; the placements after it belong to that game
+    Game {
+        schema: u32,       // `SCHEMA_VERSION` the records were written with
+        mode: String,      // Name of the game mode
+        rotation: String,  // Name of the rotation system
+        width: usize,      // Board columns
+        height: usize,     // Board rows
+    },
+    /// A piece the player locked
+    Placement {
+        board: Vec<u32>,          // Locked blocks the piece landed on, without the piece
+        piece: char,              // The piece placed
+        hold: Option<char>,       // Piece left in the hold box
+        next: Vec<char>,          // Upcoming pieces, the next one first
+        rotation: u8,             // Clockwise turns from the spawn orientation
+        x: i32,                   // Column of the piece's bounding box
+        y: i32,                   // Row of the piece's bounding box
+        cells: Vec<(i32, i32)>,   // Board cells it filled, as (column, row)
+        lines: u32,               // Rows it completed
+    },
+}
+
+impl DataRecord {
+    /// The record of a game starting
+    pub fn game(mode: &str, rotation: &str, board: &GameBoard) -> Self {
+        DataRecord::Game {
+            schema: SCHEMA_VERSION,
+            mode: mode.to_string(),
+            rotation: rotation.to_string(),
+            width: board.width(),
+            height: board.height(),
+        }
+    }
+
+    /// The record of a lock; `board` is the board right after it, with the piece and its completed rows still on it,
+    /// and `next` the queue the player saw while placing it
+    pub fn placement<'a>(board: &GameBoard, locked: &LockedPiece, hold: Option<&Tetromino>, next: impl IntoIterator<Item = &'a Tetromino>) -> Self {
+        let board = board
+            .rows()
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter().enumerate().fold(0, |mask, (x, &color)| {
+                    let own = locked.cells.contains(&(x as i32, y as i32));
+                    if color != Color::BLACK && !own {
+                        mask | 1 << x
+                    } else {
+                        mask
+                    }
+                })
+            })
+            .collect();
+        DataRecord::Placement {
+            board,
+            piece: locked.piece.kind.letter(),
+            hold: hold.map(|piece| piece.kind.letter()),
+            next: next.into_iter().map(|piece| piece.kind.letter()).collect(),
+            rotation: locked.piece.rotation,
+            x: locked.piece.position.x as i32,
+            y: locked.piece.position.y as i32,
+            cells: locked.cells.clone(),
+            lines: locked.full_rows.len() as u32,
+        }
+    }
+}
+
+/// Appends records to the play data log, one JSON line each
+pub struct DataLog {
+    file: BufWriter<File>,
+}
+
+impl DataLog {
+    /// Opens the log for appending, creating it and its directory if needed
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file: BufWriter::new(file) })
+    }
+
+    /// Writes a record as one line, straight through to the file so a closed window loses nothing
+    pub fn write(&mut self, record: &DataRecord) -> io::Result<()> {
+        serde_json::to_writer(&mut self.file, record)?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, Move, StepResult};
+    use crate::events::GameEvent;
+
+    #[test]
+    fn test_placement_leaves_the_piece_off_the_board() {
+        let mut game = Game::with_seed(4);
+        let kind = game.current_piece().unwrap().kind;
+        let queue: Vec<char> = game.next_queue().iter().map(|piece| piece.kind.letter()).collect();
+        let StepResult { events, .. } = game.apply(Move::HardDrop);
+        let locked = events.iter().find_map(|event| match event {
+            GameEvent::PieceLocked(locked) => Some(locked.clone()),
+            _ => None,
+        });
+        // The next piece has spawned, so the queue the player saw starts with it
+        let seen = game.current_piece().into_iter().chain(game.next_queue()).take(queue.len());
+        let record = DataRecord::placement(game.board(), &locked.unwrap(), game.hold_piece(), seen);
+
+        let DataRecord::Placement { board, piece, hold, next, cells, .. } = &record else {
+            panic!("not a placement: {:?}", record);
+        };
+        assert!(board.iter().all(|&row| row == 0), "the first piece lands on an empty board");
+        assert_eq!(*piece, kind.letter());
+        assert_eq!(*hold, None);
+        assert_eq!(*next, queue);
+        assert_eq!(cells.len(), 4);
+
+        // Records survive a round trip through a JSON line
+        let path = std::env::temp_dir().join(format!("tetris_play_data_{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut log = DataLog::open(&path).unwrap();
+        log.write(&DataRecord::game("MARATHON", "srs", game.board())).unwrap();
+        log.write(&record).unwrap();
+        let lines: Vec<DataRecord> = fs::read_to_string(&path).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert!(matches!(lines[0], DataRecord::Game { schema: SCHEMA_VERSION, .. }));
+        assert_eq!(lines[1], record);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,333 @@
+use crate::config;
+use crate::constants::{GARBAGE_COLOR, GRID_HEIGHT, GRID_WIDTH, SCENARIOS_DIR};
+use crate::game::Game;
+use crate::tetromino::TetrominoType;
+use ggez::graphics::Color;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Most pieces a scenario deals before the random bag takes over
+pub const MAX_QUEUE: usize = 14;
+
+/// Folder the board editor keeps its scenario files in
+pub fn scenarios_dir() -> PathBuf {
+    config::config_dir().join(SCENARIOS_DIR)
+}
+
+/// File of a numbered editor slot, counting from 1
+pub fn slot_path(slot: usize) -> PathBuf {
+    scenarios_dir().join(format!("scenario{}.toml", slot))
+}
+
+/// What fills a cell of a scenario's board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Empty,
+    Piece(TetrominoType),  // A block in the color of a piece
+    Garbage,               // A gray block, as risen garbage
+}
+
+impl Cell {
+    /// Everything the editor paints with, in the order the brush cycles through
+    pub const BRUSHES: [Cell; 8] = [
+        Cell::Piece(TetrominoType::I),
+        Cell::Piece(TetrominoType::O),
+        Cell::Piece(TetrominoType::T),
+        Cell::Piece(TetrominoType::S),
+        Cell::Piece(TetrominoType::Z),
+        Cell::Piece(TetrominoType::J),
+        Cell::Piece(TetrominoType::L),
+        Cell::Garbage,
+    ];
+
+    /// Color the cell takes on the game board
+    pub fn color(self) -> Color {
+        match self {
+            Cell::Empty => Color::BLACK,
+            Cell::Piece(kind) => kind.color(),
+            Cell::Garbage => GARBAGE_COLOR,
+        }
+    }
+
+    /// Character the cell is written as in a scenario file
+    fn letter(self) -> char {
+        match self {
+            Cell::Empty => '.',
+            Cell::Piece(kind) => kind.letter(),
+            Cell::Garbage => 'G',
+        }
+    }
+
+    fn from_letter(letter: char) -> Option<Cell> {
+        match letter.to_ascii_uppercase() {
+            '.' | ' ' => Some(Cell::Empty),
+            'G' | '#' => Some(Cell::Garbage),
+            letter => TetrominoType::from_letter(letter).map(Cell::Piece),
+        }
+    }
+}
+
+/// A board position and the pieces dealt first, built in the board editor and played from in practice
+/// Also the format of puzzle content: a start position and a set sequence of pieces
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "ScenarioFile", into = "ScenarioFile")]
+pub struct Scenario {
+    cells: Vec<Vec<Cell>>,          // Board cells by row then column, rows top to bottom
+    pub queue: Vec<TetrominoType>,  // Pieces dealt before the random bag, the first one falling first
+}
+
+/// A scenario as written to a file: the rows from the highest one with blocks down to the floor, one character
+/// per cell (`.` empty, a piece letter, or `G` for garbage), and the queue as piece letters
+#[derive(Serialize, Deserialize)]
+struct ScenarioFile {
+    #[serde(default)]
+    board: Vec<String>,
+    #[serde(default)]
+    queue: String,
+}
+
+impl TryFrom<ScenarioFile> for Scenario {
+    type Error = String;
+
+    fn try_from(file: ScenarioFile) -> Result<Self, Self::Error> {
+        if file.board.len() > GRID_HEIGHT as usize {
+            return Err(format!("the board has {} rows, more than {}", file.board.len(), GRID_HEIGHT));
+        }
+        let mut scenario = Scenario::new();
+        let top = GRID_HEIGHT as usize - file.board.len();
+        for (y, row) in file.board.iter().enumerate() {
+            if row.chars().count() > GRID_WIDTH as usize {
+                return Err(format!("row {:?} is wider than {} cells", row, GRID_WIDTH));
+            }
+            for (x, letter) in row.chars().enumerate() {
+                scenario.cells[top + y][x] = Cell::from_letter(letter).ok_or_else(|| format!("unknown cell {:?} in row {:?}", letter, row))?;
+            }
+        }
+        for letter in file.queue.chars().filter(|letter| !letter.is_whitespace()) {
+            let kind = TetrominoType::from_letter(letter).ok_or_else(|| format!("unknown piece {:?} in the queue", letter))?;
+            scenario.queue.push(kind);
+        }
+        scenario.queue.truncate(MAX_QUEUE);
+        Ok(scenario)
+    }
+}
+
+impl From<Scenario> for ScenarioFile {
+    fn from(scenario: Scenario) -> Self {
+        let top = scenario.cells.iter().position(|row| row.iter().any(|&cell| cell != Cell::Empty)).unwrap_or(scenario.cells.len());
+        Self {
+            board: scenario.cells[top..].iter().map(|row| row.iter().map(|cell| cell.letter()).collect()).collect(),
+            queue: scenario.queue.iter().map(|kind| kind.letter()).collect(),
+        }
+    }
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            cells: vec![vec![Cell::Empty; GRID_WIDTH as usize]; GRID_HEIGHT as usize],
+            queue: Vec::new(),
+        }
+    }
+}
+
+impl Scenario {
+    /// An empty board with no pieces queued
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> Cell {
+        self.cells[y][x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        self.cells[y][x] = cell;
+    }
+
+    /// Empties the board, keeping the queue
+    pub fn clear_board(&mut self) {
+        *self = Self { queue: std::mem::take(&mut self.queue), ..Self::default() };
+    }
+
+    /// Sets up a game to start from the scenario: its board, then its queue dealt first if it has one
+    pub fn apply(&self, game: &mut Game) {
+        for (y, row) in self.cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                game.board_mut()[y][x] = cell.color();
+            }
+        }
+        if !self.queue.is_empty() {
+            game.deal_sequence(&self.queue);
+        }
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Reads a scenario file; one that can't be parsed is an `InvalidData` error saying why
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::from_toml(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.message().to_string()))
+    }
+
+    /// Saves the scenario to a file, creating its directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+}
+
+/// The board editor's scenario with its cursor, brush and file slot
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoardEditor {
+    pub scenario: Scenario,  // Position being built
+    cursor: (usize, usize),  // Cell the keyboard paints, as column and row
+    brush: usize,            // Index into `Cell::BRUSHES` of what painting fills cells with
+    slot: usize,             // Numbered file saved to and loaded from, counting from 1
+}
+
+impl Default for BoardEditor {
+    fn default() -> Self {
+        Self {
+            scenario: Scenario::new(),
+            cursor: (GRID_WIDTH as usize / 2, GRID_HEIGHT as usize - 1),
+            brush: 0,
+            slot: 1,
+        }
+    }
+}
+
+impl BoardEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// Moves the cursor by whole cells, stopping at the edges of the board
+    pub fn move_cursor(&mut self, dx: i32, dy: i32) {
+        let x = (self.cursor.0 as i32 + dx).clamp(0, GRID_WIDTH - 1);
+        let y = (self.cursor.1 as i32 + dy).clamp(0, GRID_HEIGHT - 1);
+        self.cursor = (x as usize, y as usize);
+    }
+
+    /// Puts the cursor on a cell, as when it's clicked; cells off the board are ignored
+    pub fn set_cursor(&mut self, x: i32, y: i32) {
+        if (0..GRID_WIDTH).contains(&x) && (0..GRID_HEIGHT).contains(&y) {
+            self.cursor = (x as usize, y as usize);
+        }
+    }
+
+    pub fn brush(&self) -> Cell {
+        Cell::BRUSHES[self.brush]
+    }
+
+    /// Moves `steps` places through the brushes, wrapping around
+    pub fn cycle_brush(&mut self, steps: i32) {
+        self.brush = (self.brush as i32 + steps).rem_euclid(Cell::BRUSHES.len() as i32) as usize;
+    }
+
+    /// Fills the cell under the cursor with the brush
+    pub fn paint(&mut self) {
+        let (x, y) = self.cursor;
+        self.scenario.set(x, y, self.brush());
+    }
+
+    /// Empties the cell under the cursor
+    pub fn erase(&mut self) {
+        let (x, y) = self.cursor;
+        self.scenario.set(x, y, Cell::Empty);
+    }
+
+    /// Adds a piece to the end of the queue, unless it's full
+    pub fn push_piece(&mut self, kind: TetrominoType) {
+        if self.scenario.queue.len() < MAX_QUEUE {
+            self.scenario.queue.push(kind);
+        }
+    }
+
+    /// Takes the last piece off the queue
+    pub fn pop_piece(&mut self) {
+        self.scenario.queue.pop();
+    }
+
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+    /// Moves `steps` places through the numbered slots, wrapping around
+    pub fn cycle_slot(&mut self, steps: i32, slots: usize) {
+        self.slot = (self.slot as i32 - 1 + steps).rem_euclid(slots as i32) as usize + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::GameBoard;
+    use crate::constants::SCENARIO_SLOTS;
+
+    #[test]
+    fn test_scenario_files_roundtrip() {
+        let mut editor = BoardEditor::new();
+        editor.paint();
+        editor.move_cursor(-20, 0);
+        editor.cycle_brush(-1);
+        editor.paint();
+        editor.move_cursor(0, -1);
+        editor.cycle_brush(3);
+        editor.paint();
+        editor.erase();
+        editor.push_piece(TetrominoType::T);
+        editor.push_piece(TetrominoType::S);
+        editor.pop_piece();
+        editor.push_piece(TetrominoType::I);
+
+        let text = editor.scenario.to_toml();
+        assert!(text.contains("\"G....I....\""), "only the rows with blocks are written:\n{}", text);
+        assert!(text.contains("queue = \"TI\""));
+        assert_eq!(Scenario::from_toml(&text).unwrap(), editor.scenario);
+
+        // Rows are counted up from the floor, and letters may be lowercase
+        let scenario = Scenario::from_toml("board = [\"t\", \"##\"]\nqueue = \"o z\"\n").unwrap();
+        assert_eq!(scenario.cell(0, GRID_HEIGHT as usize - 2), Cell::Piece(TetrominoType::T));
+        assert_eq!(scenario.cell(1, GRID_HEIGHT as usize - 1), Cell::Garbage);
+        assert_eq!(scenario.queue, [TetrominoType::O, TetrominoType::Z]);
+
+        assert!(Scenario::from_toml("board = [\"...........\"]").is_err(), "too wide");
+        assert!(Scenario::from_toml("board = [\"x\"]").is_err());
+        assert!(Scenario::from_toml("queue = \"TQ\"").is_err());
+
+        editor.cycle_slot(-1, SCENARIO_SLOTS);
+        assert_eq!(editor.slot(), SCENARIO_SLOTS);
+    }
+
+    #[test]
+    fn test_scenario_starts_a_game() {
+        let mut scenario = Scenario::new();
+        scenario.set(0, GRID_HEIGHT as usize - 1, Cell::Garbage);
+        scenario.queue = vec![TetrominoType::O, TetrominoType::I];
+        let mut game = Game::with_seed(3);
+        game.board_mut()[0][0] = Color::WHITE;
+        scenario.apply(&mut game);
+
+        let mut expected = GameBoard::new();
+        expected[GRID_HEIGHT as usize - 1][0] = GARBAGE_COLOR;
+        assert_eq!(*game.board(), expected);
+        assert_eq!(game.current_piece().unwrap().kind, TetrominoType::O);
+        assert_eq!(game.next_queue()[0].kind, TetrominoType::I);
+    }
+}
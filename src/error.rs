@@ -0,0 +1,53 @@
+use ggez::GameError;
+use std::fmt;
+
+/// Things that can go wrong while presenting the game, as opposed to in its rules
+/// Frontends log these and carry on, so a missing sound or a font that can't measure never ends a game
+#[derive(Debug)]
+pub enum Error {
+    Sound { name: String, source: GameError },     // A sound that failed to load, play or stop
+    Text { content: String, source: GameError },   // Text whose size the font couldn't give
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Sound { name, source } => write!(f, "sound {}: {}", name, source),
+            Error::Text { content, source } => write!(f, "text {:?}: {}", content, source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Sound { source, .. } | Error::Text { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Lets ggez callbacks pass errors on with `?` where failing is the right call
+impl From<Error> for GameError {
+    fn from(e: Error) -> Self {
+        GameError::CustomError(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_messages_name_what_failed() {
+        let error = Error::Sound { name: "move".to_string(), source: GameError::AudioError("no device".to_string()) };
+        let message = error.to_string();
+        assert!(message.starts_with("sound move:") && message.contains("no device"), "{}", message);
+        assert!(error.source().is_some());
+
+        let error = Error::Text { content: "SCORE".to_string(), source: GameError::RenderError("lost".to_string()) };
+        assert!(GameError::from(error).to_string().contains("\"SCORE\""));
+    }
+}
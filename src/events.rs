@@ -0,0 +1,39 @@
+use crate::garbage::TSpin;
+use crate::game::LockedPiece;
+use crate::tetromino::TetrominoType;
+
+/// Something that happened in a game, reported by the game core in the order it happened
+/// Sounds, effects, statistics and the versus link all react to these instead of to the inputs that caused them
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    PieceSpawned(TetrominoType),                  // A piece from the queue entered the board
+    PieceMoved,                                   // An input shifted or soft dropped the piece
+    PieceRotated,                                 // An input rotated the piece
+    PieceHeld,                                    // The piece was swapped into the hold box
+    PieceLocked(LockedPiece),                     // The piece locked; its completed rows are still on the board
+    LinesCleared { lines: u32, t_spin: TSpin },   // Completed rows were removed
+    LevelUp(u32),                                 // The level went up to this one
+    AttackSent(u32),                              // Garbage lines sent to the opponent, after cancelling our own
+    GarbageRose(u32),                             // Garbage lines pushed up into the board
+    GameOver,                                     // A piece had no room to enter the board
+}
+
+impl GameEvent {
+    /// Whether the locked blocks changed, so an opponent's copy of the board needs updating
+    pub fn changes_board(&self) -> bool {
+        matches!(self, GameEvent::LinesCleared { .. } | GameEvent::GarbageRose(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_board() {
+        assert!(GameEvent::LinesCleared { lines: 1, t_spin: TSpin::None }.changes_board());
+        assert!(GameEvent::GarbageRose(2).changes_board());
+        assert!(!GameEvent::AttackSent(2).changes_board());
+        assert!(!GameEvent::PieceMoved.changes_board());
+    }
+}
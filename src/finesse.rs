@@ -0,0 +1,72 @@
+use crate::board::GameBoard;
+use crate::constants::GRID_WIDTH;
+use crate::rotation::RotationSystem;
+use crate::tetromino::Tetromino;
+
+/// Leftmost and rightmost board columns covered by a piece
+fn column_span(piece: &Tetromino) -> (i32, i32) {
+    let columns = piece
+        .shape
+        .iter()
+        .flat_map(|row| row.iter().enumerate().filter(|(_, &cell)| cell).map(|(x, _)| x as i32));
+    let (min, max) = columns.fold((i32::MAX, i32::MIN), |(min, max), x| (min.min(x), max.max(x)));
+    (piece.position.x as i32 + min, piece.position.x as i32 + max)
+}
+
+/// Fewest inputs that bring a freshly spawned piece into the rotation and column of `placed`
+/// Counts rotations plus left/right presses, where holding a direction into a wall counts as one press;
+/// the path is assumed to be clear, so tucks and spins are not covered
+pub fn minimal_inputs(placed: &Tetromino, rotation_system: &dyn RotationSystem) -> u32 {
+    let board = GameBoard::new();
+    let (target_left, target_right) = column_span(placed);
+    let mut spawn = Some(rotation_system.spawn(placed.kind));
+    let mut best = u32::MAX;
+    for rotations in 0..4 {
+        let Some(turned) = spawn else {
+            break;
+        };
+        if turned.shape == placed.shape {
+            let (left, _) = column_span(&turned);
+            let tapped = (target_left - left).unsigned_abs();
+            let via_left_wall = 1 + target_left as u32;
+            let via_right_wall = 1 + (GRID_WIDTH - 1 - target_right) as u32;
+            best = best.min(rotations + tapped.min(via_left_wall).min(via_right_wall));
+        }
+        spawn = rotation_system.rotate(&board, &turned);
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rotation::{ARS, SRS};
+    use crate::tetromino::TetrominoType;
+
+    #[test]
+    fn test_minimal_inputs() {
+        // Dropping straight down needs nothing
+        let piece = Tetromino::new(TetrominoType::T);
+        assert_eq!(minimal_inputs(&piece, &SRS), 0);
+
+        // Two taps right
+        let mut piece = Tetromino::new(TetrominoType::T);
+        piece.position.x += 2.0;
+        assert_eq!(minimal_inputs(&piece, &SRS), 2);
+
+        // Against the left wall is one held press, one column out of it is a tap back
+        let mut piece = Tetromino::new(TetrominoType::O);
+        piece.position.x = 0.0;
+        assert_eq!(minimal_inputs(&piece, &SRS), 1);
+        piece.position.x = 1.0;
+        assert_eq!(minimal_inputs(&piece, &SRS), 2);
+
+        // A vertical I takes one rotation; turning it twice more would look the same but cost more
+        let piece = SRS.rotate(&GameBoard::new(), &Tetromino::new(TetrominoType::I)).unwrap();
+        assert_eq!(minimal_inputs(&piece, &SRS), 1);
+
+        // ARS spawns the T pointing down, so pointing up takes two rotations
+        let piece = Tetromino::new(TetrominoType::T);
+        assert_eq!(minimal_inputs(&piece, &ARS), 2);
+    }
+}
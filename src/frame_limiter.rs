@@ -0,0 +1,50 @@
+use std::time::{Duration, Instant};
+
+/// Keeps frames from being drawn faster than a cap by sleeping out the rest of each frame
+/// Without it, and with vsync off, the game redraws as fast as the GPU allows
+pub struct FrameLimiter {
+    frame_start: Instant,  // When the current frame began
+}
+
+impl Default for FrameLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameLimiter {
+    pub fn new() -> Self {
+        Self {
+            frame_start: Instant::now(),
+        }
+    }
+
+    /// Time left in a frame at `cap` frames per second after `elapsed` has passed; a cap of 0 never waits
+    pub fn remaining(elapsed: Duration, cap: u32) -> Duration {
+        if cap == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(1.0 / cap as f64).saturating_sub(elapsed)
+    }
+
+    /// Sleeps until the frame has lasted its share of a second, then starts timing the next one
+    pub fn wait(&mut self, cap: u32) {
+        let remaining = Self::remaining(self.frame_start.elapsed(), cap);
+        if !remaining.is_zero() {
+            std::thread::sleep(remaining);
+        }
+        self.frame_start = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining() {
+        assert_eq!(FrameLimiter::remaining(Duration::from_millis(5), 0), Duration::ZERO);
+        assert_eq!(FrameLimiter::remaining(Duration::from_millis(10), 50), Duration::from_millis(10));
+        assert_eq!(FrameLimiter::remaining(Duration::from_millis(30), 50), Duration::ZERO);
+    }
+}
@@ -0,0 +1,1191 @@
+use crate::board::{GameBoard, Playfield};
+use crate::constants::*;
+use crate::events::GameEvent;
+use crate::garbage::{self, AttackTracker, GarbageHoles, GarbageQueue, GarbageStyle, TSpin};
+use crate::generator::{self, PieceGenerator};
+use crate::rotation::{RotationSystem, SRS};
+use crate::scoring::{Clear, ScoringSystem, GUIDELINE};
+use crate::tetromino::{Tetromino, TetrominoType};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::VecDeque;
+
+/// A single input a controller can make on one step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Move {
+    None,      // Let gravity act without any input
+    Left,      // Shift the piece one column left
+    Right,     // Shift the piece one column right
+    RotateCw,  // Rotate the piece clockwise, with wall kicks
+    SoftDrop,  // Move the piece down one row
+    HardDrop,  // Drop the piece to the floor and lock it
+    Hold,      // Swap the piece with the held one, once per piece
+}
+
+/// Read-only view of a game handed to controllers
+#[derive(Clone, Copy, Debug)]
+pub struct Snapshot<'a> {
+    pub board: &'a GameBoard,                     // Locked blocks, without the falling piece
+    pub current: &'a Tetromino,                   // The falling piece
+    pub next: &'a Tetromino,                      // The piece that spawns after the current one
+    pub queue: &'a VecDeque<Tetromino>,           // Every upcoming piece, the next one first
+    pub hold: Option<&'a Tetromino>,              // The piece in the hold box
+    pub can_hold: bool,                           // Whether hold can be used on the falling piece
+    pub hold_enabled: bool,                       // Whether hold can be used at all, as on the pieces to come
+    pub pieces: u32,                              // Number of pieces spawned so far; changes when a new piece appears
+    pub score: u32,                               // Current score
+    pub level: u32,                               // Current level
+    pub lines_cleared: u32,                       // Total lines cleared
+    pub rotation_system: &'a dyn RotationSystem,  // How the pieces turn and kick
+}
+
+/// Anything that can play the game: built-in bots, scripts, or external agents
+pub trait Controller {
+    /// Chooses the next move for the given game state
+    fn next_move(&mut self, snapshot: &Snapshot) -> Move;
+}
+
+/// A piece that has just been locked onto the board
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockedPiece {
+    pub piece: Tetromino,          // The piece where it locked
+    pub cells: Vec<(i32, i32)>,    // Board cells it filled
+    pub t_spin: TSpin,             // T-spin detected before it locked
+    pub full_rows: Vec<usize>,     // Rows it completed, top to bottom
+    pub dropped: u32,              // Rows it fell on the hard drop that locked it, 0 if gravity locked it
+}
+
+/// What happened during a call to `Game::apply`, `Game::gravity`, `Game::update` or `Game::step`
+/// Frontends turn its events into sounds, effects and network messages
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StepResult {
+    pub moved: bool,                     // Whether the input changed the piece
+    pub dropped: u32,                    // Rows the piece fell on a hard drop
+    pub locked: Option<LockedPiece>,     // The piece that locked, if one did
+    pub lock_finished: bool,             // Whether a lock was completed: rows cleared, garbage exchanged, next piece spawned
+    pub lines_cleared: u32,              // Lines cleared by the finished lock
+    pub attack: u32,                     // Garbage lines the finished lock sends to an opponent
+    pub garbage_added: u32,              // Garbage lines that rose into the board after the finished lock
+    pub spawned: Option<TetrominoType>,  // Kind of the piece that entered the board
+    pub game_over: bool,                 // Whether the game has ended
+    pub events: Vec<GameEvent>,          // Everything above, in the order it happened
+}
+
+impl StepResult {
+    fn merge(mut self, other: StepResult) -> StepResult {
+        self.events.extend(other.events);
+        StepResult {
+            moved: self.moved || other.moved,
+            dropped: self.dropped + other.dropped,
+            locked: self.locked.or(other.locked),
+            lock_finished: self.lock_finished || other.lock_finished,
+            lines_cleared: self.lines_cleared + other.lines_cleared,
+            attack: self.attack + other.attack,
+            garbage_added: self.garbage_added + other.garbage_added,
+            spawned: other.spawned.or(self.spawned),
+            game_over: self.game_over || other.game_over,
+            events: self.events,
+        }
+    }
+}
+
+/// Multipliers on the standard rules, changed at runtime by scripts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tuning {
+    pub gravity: f64,  // Speed of gravity; 2.0 pulls pieces down twice as fast
+    pub score: f64,    // Points scored for line clears
+    pub garbage: f64,  // Garbage lines sent for each attack
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            gravity: 1.0,
+            score: 1.0,
+            garbage: 1.0,
+        }
+    }
+}
+
+/// A lock waiting for its completed rows to be cleared
+#[derive(Clone, Debug)]
+struct PendingClear {
+    t_spin: TSpin,  // T-spin of the locked piece, needed for its attack
+    elapsed: f64,   // Seconds since the piece locked
+}
+
+/// A complete game of Tetris without any rendering, audio or event loop
+/// Inputs go in through `apply` and time through `update`; what happened comes back as a `StepResult`.
+/// The windowed game is a frontend over this, and bots and tests play it directly
+#[derive(Clone, Debug)]
+pub struct Game {
+    board: GameBoard,                              // Locked blocks
+    current_piece: Option<Tetromino>,              // Falling piece; none while completed rows wait to be cleared
+    next_queue: VecDeque<Tetromino>,               // Upcoming pieces, the next one first
+    hold_piece: Option<Tetromino>,                 // Piece set aside with hold
+    hold_used: bool,                               // Whether hold was used since the current piece spawned
+    hold_enabled: bool,                            // Whether hold can be used at all
+    pieces: u32,                                   // Number of pieces spawned so far
+    score: u32,                                    // Current game score
+    level: u32,                                    // Current game level
+    start_level: u32,                              // Level the game started at, before any lines
+    lines_cleared: u32,                            // Total number of lines cleared
+    game_over: bool,                               // Whether a spawn collided
+    drop_timer: f64,                               // Time since gravity last pulled the piece down
+    gravity_frozen: bool,                          // Whether gravity is off, so pieces only fall by input
+    grounded: bool,                                // Whether the falling piece rests on blocks or the floor
+    lock_delay: f64,                               // Seconds a piece soft dropped into the stack waits before locking
+    lock_timer: Option<f64>,                       // Time since a soft drop pushed the grounded piece, while the lock delay runs
+    last_locked: Vec<(i32, i32)>,                  // Cells filled by the latest lock, until rows clear or garbage rises
+    line_clear_delay: f64,                         // Seconds completed rows stay on the board before they're cleared
+    pending_clear: Option<PendingClear>,           // Lock waiting on its completed rows
+    last_move_was_rotation: bool,                  // Whether the last successful action was a rotation (for T-spins)
+    garbage: GarbageQueue,                         // Incoming garbage waiting to rise
+    garbage_holes: GarbageHoles,                   // Where the holes of rising garbage go
+    attack: AttackTracker,                         // Combo and back-to-back state for outgoing attacks
+    tuning: Tuning,                                // Multipliers on gravity, scoring and garbage
+    seed: u64,                                     // Seed the piece sequence was generated from
+    rng: StdRng,                                   // Source of pieces and garbage holes
+    sequence: VecDeque<TetrominoType>,             // Pieces dealt before the generator's again, for set openings
+    generator: Box<dyn PieceGenerator>,            // Where pieces come from after the dealt sequence
+    rotation_system: &'static dyn RotationSystem,  // How pieces spawn, turn and kick
+    scoring_system: &'static dyn ScoringSystem,    // Points for locks and drops
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    /// Creates a new game with an empty board and a random seed
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// Creates a new game whose piece sequence is fully determined by `seed`
+    /// Two games with the same seed and the same moves play out identically
+    pub fn with_seed(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let current_piece = Tetromino::random_from(&mut rng);
+        let next_queue = (0..NEXT_QUEUE_SIZE).map(|_| Tetromino::random_from(&mut rng)).collect();
+        Self {
+            board: GameBoard::new(),
+            current_piece: Some(current_piece),
+            next_queue,
+            hold_piece: None,
+            hold_used: false,
+            hold_enabled: true,
+            pieces: 1,
+            score: 0,
+            level: 1,
+            start_level: 1,
+            lines_cleared: 0,
+            game_over: false,
+            drop_timer: 0.0,
+            gravity_frozen: false,
+            grounded: false,
+            lock_delay: LOCK_DELAY,
+            lock_timer: None,
+            last_locked: Vec::new(),
+            line_clear_delay: 0.0,
+            pending_clear: None,
+            last_move_was_rotation: false,
+            garbage: GarbageQueue::new(),
+            garbage_holes: GarbageHoles::default(),
+            attack: AttackTracker::new(),
+            tuning: Tuning::default(),
+            seed,
+            rng,
+            sequence: VecDeque::new(),
+            generator: Box::new(generator::Random),
+            rotation_system: &SRS,
+            scoring_system: &GUIDELINE,
+        }
+    }
+
+    pub fn rotation_system(&self) -> &'static dyn RotationSystem {
+        self.rotation_system
+    }
+
+    pub fn scoring_system(&self) -> &'static dyn ScoringSystem {
+        self.scoring_system
+    }
+
+    /// Changes how locks and drops are scored from now on
+    pub fn set_scoring_system(&mut self, system: &'static dyn ScoringSystem) {
+        self.scoring_system = system;
+    }
+
+    /// Locks in a row that have cleared lines, up to the last one
+    pub fn combo(&self) -> u32 {
+        self.attack.combo
+    }
+
+    /// Switches to another rotation system; pieces already dealt are turned back to its spawn orientations
+    pub fn set_rotation_system(&mut self, system: &'static dyn RotationSystem) {
+        self.rotation_system = system;
+        let pieces = self.current_piece.iter_mut().chain(&mut self.next_queue).chain(&mut self.hold_piece);
+        for piece in pieces {
+            *piece = system.spawn(piece.kind);
+        }
+    }
+
+    /// Sets how long a piece soft dropped into the stack waits before it locks
+    /// Zero locks it at once, as the NES did
+    pub fn set_lock_delay(&mut self, seconds: f64) {
+        self.lock_delay = seconds.max(0.0);
+    }
+
+    pub fn garbage_style(&self) -> GarbageStyle {
+        self.garbage_holes.style()
+    }
+
+    /// Changes where the holes of garbage rising from now on go
+    pub fn set_garbage_style(&mut self, style: GarbageStyle) {
+        self.garbage_holes = GarbageHoles::new(style);
+    }
+
+    /// Starts the game at a later level, which every ten lines then count up from
+    pub fn set_start_level(&mut self, level: u32) {
+        self.start_level = level.max(1);
+        self.level = self.lines_cleared / 10 + self.start_level;
+    }
+
+    /// Turns gravity off or back on; a frozen piece only moves by input, and only locks when dropped
+    pub fn set_gravity_frozen(&mut self, frozen: bool) {
+        self.gravity_frozen = frozen;
+        self.drop_timer = 0.0;
+    }
+
+    pub fn is_gravity_frozen(&self) -> bool {
+        self.gravity_frozen
+    }
+
+    /// Turns hold on or off; a piece already held stays in the hold box
+    pub fn set_hold_enabled(&mut self, enabled: bool) {
+        self.hold_enabled = enabled;
+    }
+
+    pub fn hold_enabled(&self) -> bool {
+        self.hold_enabled
+    }
+
+    /// Whether the falling piece rests on blocks or the floor, so it can't fall any further
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// Keeps completed rows on the board for `seconds` before clearing them, so a frontend can animate them
+    /// With no delay, the default, rows are cleared as soon as the piece locks
+    pub fn set_line_clear_delay(&mut self, seconds: f64) {
+        self.line_clear_delay = seconds;
+    }
+
+    pub fn tuning(&self) -> Tuning {
+        self.tuning
+    }
+
+    /// Changes the multipliers on gravity, scoring and garbage; negative values count as zero
+    pub fn set_tuning(&mut self, tuning: Tuning) {
+        self.tuning = Tuning {
+            gravity: tuning.gravity.max(0.0),
+            score: tuning.score.max(0.0),
+            garbage: tuning.garbage.max(0.0),
+        };
+    }
+
+    /// The seed this game was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn board(&self) -> &GameBoard {
+        &self.board
+    }
+
+    /// Board cells filled by the latest lock; empty once clearing rows or rising garbage have moved them
+    pub fn last_locked(&self) -> &[(i32, i32)] {
+        &self.last_locked
+    }
+
+    /// The board, for setting up positions in tests and puzzles
+    pub fn board_mut(&mut self) -> &mut GameBoard {
+        &mut self.board
+    }
+
+    /// The falling piece; `None` while completed rows are waiting to be cleared
+    pub fn current_piece(&self) -> Option<&Tetromino> {
+        self.current_piece.as_ref()
+    }
+
+    /// Where the falling piece would land after no, one, two and three turns from where it is, each dropped straight down
+    /// Turns kick as they would in play; the list stops at the first turn the stack blocks
+    pub fn rotation_previews(&self) -> Vec<Tetromino> {
+        let mut previews = Vec::new();
+        let mut turned = self.current_piece.clone();
+        while let Some(piece) = turned.filter(|_| previews.len() < 4) {
+            previews.push(self.board.drop_position(&piece));
+            turned = self.rotation_system.rotate(&self.board, &piece);
+        }
+        previews
+    }
+
+    /// Replaces the falling piece, for setting up positions in tests and puzzles
+    pub fn set_current_piece(&mut self, piece: Tetromino) {
+        self.lock_timer = None;
+        self.place(piece);
+    }
+
+    /// Makes `piece` the falling piece and notes whether it is grounded
+    /// A piece moved off the ledge it rested on stops counting toward its lock
+    fn place(&mut self, piece: Tetromino) {
+        self.grounded = self.board.try_move(&piece, 0, 1).is_none();
+        if !self.grounded {
+            self.lock_timer = None;
+        }
+        self.current_piece = Some(piece);
+    }
+
+    /// Deals `kinds` in order, the first one falling now, before going back to the generator's pieces
+    /// The held piece is dropped, so a set opening starts the same every time
+    pub fn deal_sequence(&mut self, kinds: &[TetrominoType]) {
+        self.sequence = kinds.iter().copied().collect();
+        self.redeal();
+    }
+
+    /// Takes pieces from `generator` from now on, dealing the falling piece and the queue again from it
+    pub fn set_generator(&mut self, generator: Box<dyn PieceGenerator>) {
+        self.generator = generator;
+        self.redeal();
+    }
+
+    /// Replaces the falling piece and the queue with fresh ones and empties the hold box
+    fn redeal(&mut self) {
+        let current = self.rotation_system.spawn(self.next_kind());
+        self.next_queue = (0..NEXT_QUEUE_SIZE).map(|_| self.rotation_system.spawn(self.next_kind())).collect();
+        self.hold_piece = None;
+        self.hold_used = false;
+        self.set_current_piece(current);
+    }
+
+    /// Type of the next piece to join the queue
+    fn next_kind(&mut self) -> TetrominoType {
+        self.sequence.pop_front().unwrap_or_else(|| self.generator.next(&mut self.rng))
+    }
+
+    /// The upcoming pieces, the next one first
+    pub fn next_queue(&self) -> &VecDeque<Tetromino> {
+        &self.next_queue
+    }
+
+    pub fn next_piece(&self) -> &Tetromino {
+        &self.next_queue[0]
+    }
+
+    pub fn hold_piece(&self) -> Option<&Tetromino> {
+        self.hold_piece.as_ref()
+    }
+
+    /// Whether hold can be used; it can only be used once per piece
+    pub fn can_hold(&self) -> bool {
+        !self.hold_used
+    }
+
+    /// Number of pieces spawned so far
+    pub fn pieces(&self) -> u32 {
+        self.pieces
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    pub fn lines_cleared(&self) -> u32 {
+        self.lines_cleared
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    /// Whether completed rows are waiting out the line clear delay
+    pub fn is_clearing(&self) -> bool {
+        self.pending_clear.is_some()
+    }
+
+    /// Garbage lines queued to rise after the next lock that clears nothing
+    pub fn pending_garbage(&self) -> u32 {
+        self.garbage.pending()
+    }
+
+    /// Attack the lock waiting on its completed rows will make once they clear, before it cancels queued garbage
+    /// Zero when no rows are waiting
+    pub fn pending_attack(&self) -> u32 {
+        let Some(pending) = &self.pending_clear else {
+            return 0;
+        };
+        let lines = self.board.full_rows().len() as u32;
+        self.scaled_attack(self.attack.clone().on_lock(lines, pending.t_spin))
+    }
+
+    /// Queues garbage sent by an opponent
+    pub fn receive_garbage(&mut self, lines: u32) {
+        self.garbage.receive(lines);
+    }
+
+    /// Seconds between gravity steps at the current level
+    pub fn drop_speed(&self) -> f64 {
+        // Decrease drop time as level increases (higher levels = faster speed)
+        // Zero gravity gives an infinite interval, so pieces only fall by input
+        DROP_TIME / (1.0 + 0.1 * self.level as f64) / self.tuning.gravity
+    }
+
+    /// The board as `GameBoard::to_ascii` prints it, with the falling piece in lowercase letters
+    pub fn to_ascii(&self) -> String {
+        let mut rows: Vec<Vec<char>> = self.board.to_ascii().lines().map(|row| row.chars().collect()).collect();
+        if let Some(piece) = &self.current_piece {
+            for (x, y) in GameBoard::piece_cells(piece) {
+                if let Some(cell) = rows.get_mut(y as usize).and_then(|row| row.get_mut(x as usize)) {
+                    *cell = piece.kind.letter().to_ascii_lowercase();
+                }
+            }
+        }
+        rows.into_iter().map(String::from_iter).collect::<Vec<_>>().join("\n")
+    }
+
+    /// The state behind the board, one `name: value` line each, for the debug overlay and bug reports
+    pub fn debug_state(&self) -> Vec<String> {
+        let timer = |timer: Option<f64>| timer.map_or("-".to_string(), |seconds| format!("{:.2}s", seconds));
+        let piece = self.current_piece.as_ref().map_or("-".to_string(), |piece| {
+            format!("{} at ({}, {}), rotation {}", piece.kind.letter(), piece.position.x, piece.position.y, piece.rotation)
+        });
+        vec![
+            format!("seed: {}", self.seed),
+            format!("rules: {} rotation, {} scoring", self.rotation_system.name(), self.scoring_system.name()),
+            format!("piece: {}", piece),
+            format!("hold: {}{}", self.hold_piece.as_ref().map_or('-', |piece| piece.kind.letter()), if self.hold_used { " (used)" } else { "" }),
+            format!("next: {}", self.next_queue.iter().map(|piece| piece.kind.letter()).collect::<String>()),
+            format!("pieces: {}, level: {}, lines: {}, score: {}", self.pieces, self.level, self.lines_cleared, self.score),
+            format!("gravity: {:.2}s of {:.2}s, grounded: {}", self.drop_timer, self.drop_speed(), self.grounded),
+            format!("lock delay: {} of {:.2}s", timer(self.lock_timer), self.lock_delay),
+            format!("line clear: {} of {:.2}s", timer(self.pending_clear.as_ref().map(|pending| pending.elapsed)), self.line_clear_delay),
+            format!("combo: {}, garbage pending: {}, last move rotated: {}", self.combo(), self.garbage.pending(), self.last_move_was_rotation),
+            format!("game over: {}", self.game_over),
+        ]
+    }
+
+    /// A read-only view of the game for controllers, or `None` while there is no falling piece
+    pub fn snapshot(&self) -> Option<Snapshot<'_>> {
+        Some(Snapshot {
+            board: &self.board,
+            current: self.current_piece.as_ref()?,
+            next: self.next_piece(),
+            queue: &self.next_queue,
+            hold: self.hold_piece.as_ref(),
+            can_hold: self.hold_enabled && !self.hold_used,
+            hold_enabled: self.hold_enabled,
+            pieces: self.pieces,
+            score: self.score,
+            level: self.level,
+            lines_cleared: self.lines_cleared,
+            rotation_system: self.rotation_system,
+        })
+    }
+
+    /// Applies a single input to the falling piece
+    pub fn apply(&mut self, mv: Move) -> StepResult {
+        if self.game_over {
+            return StepResult { game_over: true, ..StepResult::default() };
+        }
+        let Some(current) = &self.current_piece else {
+            return StepResult::default();
+        };
+
+        let moved = match mv {
+            Move::None => None,
+            Move::Left => self.board.try_move(current, -1, 0),
+            Move::Right => self.board.try_move(current, 1, 0),
+            Move::RotateCw => self.rotation_system.rotate(&self.board, current),
+            Move::SoftDrop => self.board.try_move(current, 0, 1),
+            Move::HardDrop => {
+                let dropped = self.board.drop_position(current);
+                let cells_dropped = (dropped.position.y - current.position.y) as u32;
+                self.score = self.score.saturating_add(self.scoring_system.hard_drop(cells_dropped));
+                if cells_dropped > 0 {
+                    self.last_move_was_rotation = false;
+                }
+                self.current_piece = Some(dropped);
+                let result = StepResult { dropped: cells_dropped, ..StepResult::default() };
+                return result.merge(self.lock(cells_dropped));
+            }
+            Move::Hold => return self.hold(),
+        };
+
+        match moved {
+            Some(piece) => {
+                self.place(piece);
+                self.last_move_was_rotation = mv == Move::RotateCw;
+                if mv == Move::SoftDrop {
+                    self.score = self.score.saturating_add(self.scoring_system.soft_drop(1));
+                }
+                let event = if mv == Move::RotateCw { GameEvent::PieceRotated } else { GameEvent::PieceMoved };
+                StepResult { moved: true, events: vec![event], ..StepResult::default() }
+            }
+            // Pushing down on a grounded piece starts its lock rather than waiting on gravity
+            None if mv == Move::SoftDrop => {
+                if self.lock_delay == 0.0 {
+                    return self.lock(0);
+                }
+                self.lock_timer.get_or_insert(0.0);
+                StepResult::default()
+            }
+            None => StepResult::default(),
+        }
+    }
+
+    /// Moves the falling piece down one row, locking it if it can't move
+    pub fn gravity(&mut self) -> StepResult {
+        if self.game_over {
+            return StepResult { game_over: true, ..StepResult::default() };
+        }
+        let Some(current) = &self.current_piece else {
+            return StepResult::default();
+        };
+
+        match self.board.try_move(current, 0, 1) {
+            Some(piece) => {
+                self.place(piece);
+                self.last_move_was_rotation = false;
+                StepResult::default()
+            }
+            None => self.lock(0),
+        }
+    }
+
+    /// Advances the game clock by `dt` seconds: gravity pulls the piece down at the level's speed
+    /// unless it is frozen, a piece soft dropped into the stack locks once the lock delay is over,
+    /// and a lock waiting on its completed rows finishes once the line clear delay is over
+    pub fn update(&mut self, dt: f64) -> StepResult {
+        if self.game_over {
+            return StepResult { game_over: true, ..StepResult::default() };
+        }
+
+        if let Some(pending) = &mut self.pending_clear {
+            pending.elapsed += dt;
+            if pending.elapsed >= self.line_clear_delay {
+                let t_spin = pending.t_spin;
+                return self.finish_lock(t_spin);
+            }
+            return StepResult::default();
+        }
+
+        if let Some(timer) = &mut self.lock_timer {
+            *timer += dt;
+            if *timer >= self.lock_delay {
+                return self.lock(0);
+            }
+        }
+
+        if self.gravity_frozen {
+            return StepResult::default();
+        }
+        self.drop_timer += dt;
+        if self.drop_timer >= self.drop_speed() {
+            self.drop_timer = 0.0;
+            return self.gravity();
+        }
+        StepResult::default()
+    }
+
+    /// Advances the game by one step: the controller makes one move, then gravity pulls
+    /// the piece down one row unless the move already locked it
+    pub fn step(&mut self, controller: &mut dyn Controller) -> StepResult {
+        if self.game_over {
+            return StepResult { game_over: true, ..StepResult::default() };
+        }
+        let Some(snapshot) = self.snapshot() else {
+            return StepResult::default();
+        };
+
+        let mv = controller.next_move(&snapshot);
+        let result = self.apply(mv);
+        if result.locked.is_some() {
+            result
+        } else {
+            result.merge(self.gravity())
+        }
+    }
+
+    /// Swaps the falling piece with the held one, or sets it aside and spawns the next piece
+    /// The piece coming out of hold starts again from the top
+    fn hold(&mut self) -> StepResult {
+        if self.hold_used || !self.hold_enabled {
+            return StepResult::default();
+        }
+        let Some(current) = self.current_piece.take() else {
+            return StepResult::default();
+        };
+
+        let mut result = match self.hold_piece.replace(self.rotation_system.spawn(current.kind)) {
+            Some(held) => {
+                let piece = self.rotation_system.spawn(held.kind);
+                if self.board.collides(&piece) {
+                    self.game_over = true;
+                }
+                self.lock_timer = None;
+                self.place(piece);
+                let events = if self.game_over { vec![GameEvent::GameOver] } else { Vec::new() };
+                StepResult { game_over: self.game_over, events, ..StepResult::default() }
+            }
+            None => self.spawn_next(),
+        };
+        result.events.insert(0, GameEvent::PieceHeld);
+        self.hold_used = true;
+        self.drop_timer = 0.0;
+        self.last_move_was_rotation = false;
+        result.moved = true;
+        result
+    }
+
+    /// Locks the falling piece onto the board
+    /// Completed rows stay until the line clear delay is over; otherwise the lock finishes at once
+    fn lock(&mut self, dropped: u32) -> StepResult {
+        let Some(piece) = self.current_piece.take() else {
+            return StepResult::default();
+        };
+        self.lock_timer = None;
+
+        // Check for a T-spin before the piece becomes part of the board
+        let t_spin = garbage::detect_t_spin(&self.board, &piece, self.last_move_was_rotation);
+        let cells = self.board.lock(&piece);
+        self.last_locked = cells.clone();
+        let full_rows = self.board.full_rows();
+        let wait = !full_rows.is_empty() && self.line_clear_delay > 0.0;
+        let locked = LockedPiece { piece, cells, t_spin, full_rows, dropped };
+        let result = StepResult {
+            events: vec![GameEvent::PieceLocked(locked.clone())],
+            locked: Some(locked),
+            ..StepResult::default()
+        };
+
+        if wait {
+            self.pending_clear = Some(PendingClear { t_spin, elapsed: 0.0 });
+            result
+        } else {
+            result.merge(self.finish_lock(t_spin))
+        }
+    }
+
+    /// Clears completed rows, updates the score, exchanges garbage and spawns the next piece
+    fn finish_lock(&mut self, t_spin: TSpin) -> StepResult {
+        self.pending_clear = None;
+        let lines = self.board.clear_lines();
+        let level = self.level;
+        self.update_score(lines, t_spin);
+        let mut events = Vec::new();
+        if lines > 0 {
+            events.push(GameEvent::LinesCleared { lines, t_spin });
+        }
+        if self.level > level {
+            events.push(GameEvent::LevelUp(self.level));
+        }
+
+        // Our attack cancels queued garbage first; garbage only rises when the lock cleared nothing
+        let attack = self.attack.on_lock(lines, t_spin);
+        let attack = self.scaled_attack(attack);
+        let outgoing = self.garbage.cancel(attack);
+        let mut garbage_added = 0;
+        if lines == 0 {
+            for chunk in self.garbage.take(garbage::GARBAGE_CAP_PER_LOCK) {
+                self.add_garbage(chunk);
+                garbage_added += chunk;
+            }
+        }
+        if outgoing > 0 {
+            events.push(GameEvent::AttackSent(outgoing));
+        }
+        if garbage_added > 0 {
+            events.push(GameEvent::GarbageRose(garbage_added));
+        }
+        if lines > 0 || garbage_added > 0 {
+            self.last_locked.clear();
+        }
+
+        let result = StepResult {
+            lock_finished: true,
+            lines_cleared: lines,
+            attack: outgoing,
+            garbage_added,
+            events,
+            ..StepResult::default()
+        };
+        result.merge(self.spawn_next())
+    }
+
+    /// An attack with the garbage tuning applied
+    fn scaled_attack(&self, attack: u32) -> u32 {
+        (attack as f64 * self.tuning.garbage).round() as u32
+    }
+
+    /// Pushes the board up and fills the bottom with one chunk of garbage lines, with holes in the garbage style
+    fn add_garbage(&mut self, lines: u32) {
+        self.garbage_holes.add_chunk(&mut self.board, lines, GARBAGE_COLOR, &mut self.rng);
+    }
+
+    /// Spawns the next piece from the queue; the game ends if it collides immediately
+    fn spawn_next(&mut self) -> StepResult {
+        let piece = self.next_queue.pop_front().unwrap();
+        let kind = self.next_kind();
+        self.next_queue.push_back(self.rotation_system.spawn(kind));
+        self.pieces += 1;
+        self.hold_used = false;
+        self.last_move_was_rotation = false;
+        if self.board.collides(&piece) {
+            self.game_over = true;
+        }
+        let kind = piece.kind;
+        self.lock_timer = None;
+        self.place(piece);
+        let mut events = vec![GameEvent::PieceSpawned(kind)];
+        if self.game_over {
+            events.push(GameEvent::GameOver);
+        }
+        StepResult {
+            spawned: Some(kind),
+            game_over: self.game_over,
+            events,
+            ..StepResult::default()
+        }
+    }
+
+    /// Scores a lock at the level it was made on, then counts its lines
+    /// Must run before the attack tracker sees the lock, which holds the combo and back-to-back state
+    fn update_score(&mut self, lines: u32, t_spin: TSpin) {
+        let clear = Clear {
+            lines,
+            t_spin,
+            combo: self.attack.combo,
+            back_to_back: self.attack.back_to_back && Clear::is_difficult(lines, t_spin),
+        };
+        let points = self.scoring_system.clear(&clear, self.level);
+        self.score = self.score.saturating_add((points as f64 * self.tuning.score).round() as u32);
+        self.lines_cleared += lines;
+        self.level = (self.lines_cleared / 10) + self.start_level;
+    }
+}
+
+/// Plays a game with the given controller until it ends or `max_steps` steps have run
+/// Returns the number of steps taken
+pub fn run(game: &mut Game, controller: &mut dyn Controller, max_steps: u32) -> u32 {
+    let mut steps = 0;
+    while steps < max_steps && !game.is_game_over() {
+        game.step(controller);
+        steps += 1;
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Always hard drops, so every step locks a piece
+    struct DropController;
+
+    impl Controller for DropController {
+        fn next_move(&mut self, _snapshot: &Snapshot) -> Move {
+            Move::HardDrop
+        }
+    }
+
+    #[test]
+    fn test_ascii_shows_the_falling_piece() {
+        let mut game = Game::with_seed(1);
+        let kind = game.current_piece().unwrap().kind;
+        let falling = kind.letter().to_ascii_lowercase();
+        assert_eq!(game.to_ascii().matches(falling).count(), 4);
+        game.apply(Move::HardDrop);
+        assert_eq!(game.board().to_ascii().matches(kind.letter()).count(), 4);
+        assert!(game.debug_state().iter().any(|line| line == "seed: 1"));
+    }
+
+    #[test]
+    fn test_apply_moves() {
+        let mut game = Game::new();
+        let start_x = game.current_piece().unwrap().position.x;
+        assert!(game.apply(Move::Left).moved);
+        assert_eq!(game.current_piece().unwrap().position.x, start_x - 1.0);
+        assert!(game.apply(Move::SoftDrop).moved);
+        assert_eq!(game.current_piece().unwrap().position.y, 1.0);
+
+        let result = game.apply(Move::HardDrop);
+        assert!(result.locked.is_some());
+        assert!(result.dropped > 0);
+        assert_eq!(result.spawned, Some(game.current_piece().unwrap().kind));
+        assert_eq!(game.pieces(), 2);
+        assert!(game.score() > 0);
+    }
+
+    #[test]
+    fn test_gravity_locks_on_the_floor() {
+        let mut game = Game::new();
+        let mut steps = 0;
+        while game.gravity().locked.is_none() {
+            steps += 1;
+            assert!(steps <= GRID_HEIGHT, "piece never locked");
+        }
+        assert!(game.board().column_heights().iter().any(|&h| h > 0));
+    }
+
+    #[test]
+    fn test_same_seed_same_game() {
+        let mut first = Game::with_seed(7);
+        let mut second = Game::with_seed(7);
+        run(&mut first, &mut DropController, 50);
+        run(&mut second, &mut DropController, 50);
+        assert_eq!(first.board(), second.board());
+        assert_eq!(first.score(), second.score());
+        assert_eq!(first.current_piece(), second.current_piece());
+        assert_eq!(first.seed(), 7);
+    }
+
+    #[test]
+    fn test_run_until_game_over() {
+        let mut game = Game::new();
+        let steps = run(&mut game, &mut DropController, 1000);
+        // Dropping every piece in the middle tops out quickly
+        assert!(game.is_game_over());
+        assert!(steps < 1000);
+        assert_eq!(game.step(&mut DropController), StepResult { game_over: true, ..StepResult::default() });
+    }
+
+    /// Puts a vertical I piece above the empty first column of rows that are otherwise full
+    fn prepare_clear(game: &mut Game, rows: std::ops::Range<usize>) {
+        for y in rows {
+            for x in 1..GRID_WIDTH as usize {
+                game.board_mut()[y][x] = GARBAGE_COLOR;
+            }
+        }
+        let mut piece = Tetromino::new(TetrominoType::I);
+        piece.rotate();
+        piece.position.x = 0.0;
+        piece.position.y = 0.0;
+        game.set_current_piece(piece);
+    }
+
+    #[test]
+    fn test_hold_swaps_once_per_piece() {
+        let mut game = Game::with_seed(3);
+        let first = game.current_piece().unwrap().kind;
+        let next = game.next_piece().kind;
+
+        // The first hold sets the piece aside and spawns the next one
+        let result = game.apply(Move::Hold);
+        assert!(result.moved);
+        assert_eq!(result.spawned, Some(next));
+        assert_eq!(game.hold_piece().unwrap().kind, first);
+        assert!(!game.can_hold());
+        assert_eq!(game.apply(Move::Hold), StepResult::default());
+
+        // After the next lock, hold swaps the held piece back in at the top
+        game.apply(Move::HardDrop);
+        let current = game.current_piece().unwrap().kind;
+        assert!(game.apply(Move::Hold).spawned.is_none());
+        assert_eq!(game.current_piece().unwrap().kind, first);
+        assert_eq!(game.current_piece().unwrap().position.y, 0.0);
+        assert_eq!(game.hold_piece().unwrap().kind, current);
+    }
+
+    #[test]
+    fn test_tetris_scores_and_clears() {
+        let mut game = Game::with_seed(1);
+        prepare_clear(&mut game, 16..20);
+        let result = game.apply(Move::HardDrop);
+        assert_eq!(result.locked.unwrap().full_rows, vec![16, 17, 18, 19]);
+        assert!(result.lock_finished);
+        assert_eq!(result.lines_cleared, 4);
+        assert_eq!(game.score(), SCORE_TETRIS + 16 * SCORE_HARD_DROP);
+        assert_eq!(game.board(), &GameBoard::new());
+    }
+
+    #[test]
+    fn test_back_to_back_combo_and_scoring_presets() {
+        let mut game = Game::with_seed(1);
+        prepare_clear(&mut game, 16..20);
+        game.apply(Move::HardDrop);
+        assert_eq!(game.combo(), 1);
+
+        // A second tetris in a row is back-to-back and the second lock of a combo
+        prepare_clear(&mut game, 16..20);
+        let before = game.score();
+        let dropped = game.apply(Move::HardDrop).dropped;
+        assert_eq!(game.score() - before, SCORE_TETRIS * 3 / 2 + SCORE_COMBO + dropped * SCORE_HARD_DROP);
+
+        // The NES table ignores both, and soft drops score a point per row
+        let mut game = Game::with_seed(1);
+        game.set_scoring_system(&crate::scoring::NES);
+        prepare_clear(&mut game, 16..20);
+        game.apply(Move::SoftDrop);
+        let dropped = game.apply(Move::HardDrop).dropped;
+        assert_eq!(game.score(), 1200 + 1 + dropped);
+    }
+
+    #[test]
+    fn test_soft_drop_into_the_stack_starts_the_lock() {
+        let mut game = Game::with_seed(1);
+        while game.apply(Move::SoftDrop).moved {}
+        assert!(game.is_grounded());
+
+        // The lock delay runs from the push into the floor, well before gravity would lock the piece
+        game.update(LOCK_DELAY / 2.0);
+        assert!(game.update(LOCK_DELAY / 2.0).locked.is_some());
+        assert!(!game.is_grounded());
+
+        // Sliding off the ledge stops the count
+        let mut game = Game::with_seed(1);
+        game.board_mut()[GRID_HEIGHT as usize - 1][5] = GARBAGE_COLOR;
+        game.set_current_piece(Tetromino::new(TetrominoType::T));
+        while game.apply(Move::SoftDrop).moved {}
+        game.update(LOCK_DELAY / 2.0);
+        game.apply(Move::Left);
+        game.apply(Move::Left);
+        assert!(!game.is_grounded());
+        assert!(game.update(LOCK_DELAY).locked.is_none());
+
+        // Without a lock delay the push locks at once
+        let mut game = Game::with_seed(1);
+        game.set_lock_delay(0.0);
+        let mut result = game.apply(Move::SoftDrop);
+        while result.moved {
+            result = game.apply(Move::SoftDrop);
+        }
+        assert!(result.locked.is_some());
+    }
+
+    #[test]
+    fn test_line_clear_delay() {
+        let mut game = Game::with_seed(1);
+        game.set_line_clear_delay(0.3);
+        prepare_clear(&mut game, 19..20);
+
+        // The completed row stays on the board and nothing falls until the delay is over
+        let result = game.apply(Move::HardDrop);
+        assert!(result.locked.is_some());
+        assert!(!result.lock_finished);
+        assert!(game.is_clearing());
+        assert!(game.current_piece().is_none());
+        assert_eq!(game.board().full_rows(), vec![19]);
+        assert_eq!(game.apply(Move::Left), StepResult::default());
+        assert_eq!(game.update(0.2), StepResult::default());
+
+        let result = game.update(0.2);
+        assert!(result.lock_finished);
+        assert_eq!(result.lines_cleared, 1);
+        assert!(result.spawned.is_some());
+        assert!(!game.is_clearing());
+        assert_eq!(game.lines_cleared(), 1);
+    }
+
+    #[test]
+    fn test_dealt_sequence_comes_first() {
+        use TetrominoType::*;
+        let mut game = Game::with_seed(3);
+        game.deal_sequence(&[T, I, O, L, J, S, Z, T]);
+        assert_eq!(game.current_piece().unwrap().kind, T);
+        let queued: Vec<_> = game.next_queue().iter().map(|piece| piece.kind).collect();
+        assert_eq!(queued, [I, O, L, J, S, Z, T][..NEXT_QUEUE_SIZE]);
+
+        // The rest of the sequence follows as pieces lock
+        for expected in [I, O, L, J, S, Z, T] {
+            game.apply(Move::HardDrop);
+            assert_eq!(game.current_piece().unwrap().kind, expected);
+        }
+    }
+
+    #[test]
+    fn test_generator_deals_after_the_sequence() {
+        use TetrominoType::*;
+        let mut game = Game::with_seed(3);
+        game.set_generator(Box::new(generator::Sequence::parse("SZ").unwrap()));
+        game.deal_sequence(&[T]);
+        let mut dealt = vec![game.current_piece().unwrap().kind];
+        for _ in 0..4 {
+            game.apply(Move::HardDrop);
+            dealt.push(game.current_piece().unwrap().kind);
+        }
+        assert_eq!(dealt, [T, S, Z, S, Z]);
+    }
+
+    #[test]
+    fn test_last_locked_cells() {
+        let mut game = Game::with_seed(1);
+        assert!(game.last_locked().is_empty());
+        let locked = game.apply(Move::HardDrop).locked.unwrap();
+        assert_eq!(game.last_locked(), locked.cells.as_slice());
+
+        // Clearing rows moves the cells, so the record goes with them
+        game.set_line_clear_delay(0.3);
+        prepare_clear(&mut game, 19..20);
+        let locked = game.apply(Move::HardDrop).locked.unwrap();
+        assert_eq!(game.last_locked(), locked.cells.as_slice());
+        assert!(game.update(0.3).lock_finished);
+        assert!(game.last_locked().is_empty());
+    }
+
+    #[test]
+    fn test_update_applies_gravity_at_level_speed() {
+        let mut game = Game::new();
+        let y = game.current_piece().unwrap().position.y;
+        game.update(game.drop_speed() / 2.0);
+        assert_eq!(game.current_piece().unwrap().position.y, y);
+        game.update(game.drop_speed() / 2.0);
+        assert_eq!(game.current_piece().unwrap().position.y, y + 1.0);
+    }
+
+    #[test]
+    fn test_garbage_rises_after_a_lock_without_clears() {
+        let mut game = Game::with_seed(5);
+        game.receive_garbage(2);
+        assert_eq!(game.pending_garbage(), 2);
+
+        let result = game.apply(Move::HardDrop);
+        assert_eq!(result.garbage_added, 2);
+        assert_eq!(game.pending_garbage(), 0);
+        let bottom = &game.board()[GRID_HEIGHT as usize - 1];
+        assert_eq!(bottom.iter().filter(|&&cell| cell == GARBAGE_COLOR).count(), GRID_WIDTH as usize - 1);
+    }
+
+    #[test]
+    fn test_clears_cancel_incoming_garbage() {
+        let mut game = Game::with_seed(1);
+        game.receive_garbage(3);
+        prepare_clear(&mut game, 16..20);
+        // A tetris sends four lines; three of them cancel the queued garbage
+        let result = game.apply(Move::HardDrop);
+        assert_eq!(result.attack, 1);
+        assert_eq!(result.garbage_added, 0);
+        assert_eq!(game.pending_garbage(), 0);
+    }
+
+    #[test]
+    fn test_pending_attack_waits_on_the_clear() {
+        let mut game = Game::with_seed(1);
+        game.set_line_clear_delay(0.3);
+        game.receive_garbage(3);
+        prepare_clear(&mut game, 16..20);
+        assert_eq!(game.pending_attack(), 0);
+
+        // The whole tetris is shown while its rows flash, before any of it cancels the queued garbage
+        game.apply(Move::HardDrop);
+        assert_eq!(game.pending_attack(), 4);
+        assert_eq!(game.pending_garbage(), 3);
+        let result = game.update(0.4);
+        assert_eq!(result.attack, 1);
+        assert_eq!(game.pending_attack(), 0);
+    }
+
+    #[test]
+    fn test_events_in_order() {
+        let mut game = Game::with_seed(1);
+        assert_eq!(game.apply(Move::Left).events, vec![GameEvent::PieceMoved]);
+        assert_eq!(game.apply(Move::RotateCw).events, vec![GameEvent::PieceRotated]);
+        assert!(game.apply(Move::None).events.is_empty());
+
+        // Ten lines cleared by one lock level up and send an attack
+        game.receive_garbage(1);
+        game.lines_cleared = 6;
+        prepare_clear(&mut game, 16..20);
+        let result = game.apply(Move::HardDrop);
+        let locked = result.locked.clone().unwrap();
+        assert_eq!(locked.dropped, result.dropped);
+        assert_eq!(
+            result.events,
+            vec![
+                GameEvent::PieceLocked(locked),
+                GameEvent::LinesCleared { lines: 4, t_spin: TSpin::None },
+                GameEvent::LevelUp(2),
+                GameEvent::AttackSent(3),
+                GameEvent::PieceSpawned(result.spawned.unwrap()),
+            ]
+        );
+
+        let events = game.apply(Move::Hold).events;
+        assert_eq!(events[0], GameEvent::PieceHeld);
+        assert!(matches!(events[1], GameEvent::PieceSpawned(_)));
+    }
+
+    #[test]
+    fn test_rotation_system_spawns_and_turns_pieces() {
+        let mut game = Game::with_seed(3);
+        game.apply(Move::Hold);
+        game.set_rotation_system(&crate::rotation::ARS);
+        assert_eq!(game.rotation_system().name(), "ars");
+        let dealt = game.current_piece().into_iter().chain(game.next_queue()).chain(game.hold_piece());
+        for piece in dealt {
+            assert_eq!(piece, &crate::rotation::ARS.spawn(piece.kind));
+        }
+
+        let mut piece = crate::rotation::ARS.spawn(TetrominoType::T);
+        piece.position.y = 5.0;
+        game.set_current_piece(piece.clone());
+        game.apply(Move::RotateCw);
+        assert_eq!(game.current_piece(), crate::rotation::ARS.rotate(game.board(), &piece).as_ref());
+        assert_eq!(game.snapshot().unwrap().rotation_system.name(), "ars");
+    }
+
+    #[test]
+    fn test_rotation_previews_land_each_turn() {
+        let mut game = Game::with_seed(1);
+        game.set_current_piece(Tetromino::new(TetrominoType::T));
+        let previews = game.rotation_previews();
+        assert_eq!(previews.len(), 4);
+        // Each preview is where the piece lands after turning that many times in play
+        for preview in &previews {
+            assert_eq!(preview, &game.board().drop_position(game.current_piece().unwrap()));
+            assert!(game.board().try_move(preview, 0, 1).is_none(), "previews rest on the floor");
+            assert!(game.apply(Move::RotateCw).moved);
+        }
+    }
+
+    #[test]
+    fn test_tuning_scales_gravity_score_and_garbage() {
+        let mut game = Game::with_seed(1);
+        let speed = game.drop_speed();
+        game.set_tuning(Tuning { gravity: 2.0, score: 0.5, garbage: 0.0 });
+        assert_eq!(game.drop_speed(), speed / 2.0);
+
+        prepare_clear(&mut game, 16..20);
+        let result = game.apply(Move::HardDrop);
+        assert_eq!(game.score(), SCORE_TETRIS / 2 + result.dropped * SCORE_HARD_DROP);
+        assert_eq!(result.attack, 0);
+
+        // Negative multipliers would run the clock backwards
+        game.set_tuning(Tuning { gravity: -1.0, ..Tuning::default() });
+        assert_eq!(game.tuning().gravity, 0.0);
+        assert!(game.drop_speed().is_infinite());
+    }
+
+    #[test]
+    fn test_frozen_gravity_waits_on_input() {
+        let mut game = Game::with_seed(1);
+        game.set_gravity_frozen(true);
+        let start = game.current_piece().unwrap().position;
+        game.update(60.0);
+        assert_eq!(game.current_piece().unwrap().position, start);
+
+        // Pushing into the floor still locks the piece after the lock delay
+        while game.apply(Move::SoftDrop).moved {}
+        assert!(game.update(LOCK_DELAY).locked.is_some());
+
+        game.set_gravity_frozen(false);
+        let start = game.current_piece().unwrap().position;
+        game.update(game.drop_speed());
+        assert_ne!(game.current_piece().unwrap().position, start);
+    }
+}
@@ -0,0 +1,384 @@
+use crate::board::{GameBoard, Playfield};
+use crate::constants::GARBAGE_SHIFT_CHANCE;
+use crate::tetromino::{Tetromino, TetrominoType};
+use ggez::graphics::Color;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// Extra lines sent for consecutive clears, indexed by combo count (first clear = 0)
+const COMBO_TABLE: [u32; 12] = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+
+/// Maximum garbage lines that rise after a single piece locks
+pub const GARBAGE_CAP_PER_LOCK: u32 = 8;
+
+/// Kind of T-spin performed by the last lock
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TSpin {
+    None,
+    Mini,
+    Full,
+}
+
+/// Detects a T-spin using the three-corner rule
+/// `last_move_was_rotation` must be true for a T-spin to count
+pub fn detect_t_spin(board: &GameBoard, piece: &Tetromino, last_move_was_rotation: bool) -> TSpin {
+    if piece.kind != TetrominoType::T || !last_move_was_rotation {
+        return TSpin::None;
+    }
+
+    let filled = |x: i32, y: i32| -> bool {
+        y >= 0
+            && (y as usize) < piece.shape.len()
+            && x >= 0
+            && (x as usize) < piece.shape[0].len()
+            && piece.shape[y as usize][x as usize]
+    };
+
+    // The center of a T is the only cell with three neighbours inside the piece
+    let mut center = None;
+    for (y, row) in piece.shape.iter().enumerate() {
+        for (x, &cell) in row.iter().enumerate() {
+            let (x, y) = (x as i32, y as i32);
+            if cell {
+                let neighbours = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                    .iter()
+                    .filter(|(dx, dy)| filled(x + dx, y + dy))
+                    .count();
+                if neighbours == 3 {
+                    center = Some((x, y));
+                }
+            }
+        }
+    }
+    let Some((cx, cy)) = center else {
+        return TSpin::None;
+    };
+
+    // The T points away from the side with no neighbour
+    let (fx, fy) = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+        .into_iter()
+        .find(|(dx, dy)| !filled(cx - dx, cy - dy))
+        .unwrap_or((0, -1));
+
+    let origin_x = piece.position.x as i32 + cx;
+    let origin_y = piece.position.y as i32 + cy;
+    // Walls and floor count as filled corners
+    let occupied = |dx: i32, dy: i32| board.is_filled(origin_x + dx, origin_y + dy);
+
+    let corners = [(-1, -1), (1, -1), (-1, 1), (1, 1)]
+        .iter()
+        .filter(|(dx, dy)| occupied(*dx, *dy))
+        .count();
+    if corners < 3 {
+        return TSpin::None;
+    }
+
+    // Both corners on the pointing side filled means a full T-spin, otherwise a mini
+    let (px, py) = (fy, fx); // Perpendicular to the pointing direction
+    let front_left = occupied(fx + px, fy + py);
+    let front_right = occupied(fx - px, fy - py);
+    if front_left && front_right {
+        TSpin::Full
+    } else {
+        TSpin::Mini
+    }
+}
+
+/// Base attack for a clear, before combo and back-to-back bonuses
+pub fn base_attack(lines: u32, t_spin: TSpin) -> u32 {
+    match (t_spin, lines) {
+        (TSpin::Full, 1) => 2,
+        (TSpin::Full, 2) => 4,
+        (TSpin::Full, 3) => 6,
+        (TSpin::Mini, 2) => 1,
+        (TSpin::Mini, _) => 0,
+        (_, 2) => 1,
+        (_, 3) => 2,
+        (_, 4) => 4,
+        _ => 0,
+    }
+}
+
+/// Tracks combo and back-to-back state across locks and converts clears into attack lines
+#[derive(Debug, Clone, Default)]
+pub struct AttackTracker {
+    pub combo: u32,          // Number of consecutive locks that cleared lines
+    pub back_to_back: bool,  // Whether the last clear was a tetris or T-spin
+}
+
+impl AttackTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a lock and returns the number of garbage lines it sends
+    pub fn on_lock(&mut self, lines: u32, t_spin: TSpin) -> u32 {
+        if lines == 0 {
+            self.combo = 0;
+            return 0;
+        }
+
+        let difficult = lines == 4 || t_spin != TSpin::None;
+        let mut attack = base_attack(lines, t_spin);
+        if difficult && self.back_to_back {
+            attack += 1;
+        }
+        attack += COMBO_TABLE[(self.combo as usize).min(COMBO_TABLE.len() - 1)];
+
+        self.combo += 1;
+        self.back_to_back = difficult;
+        attack
+    }
+}
+
+/// Incoming garbage waiting to rise into the board
+/// Each entry is one attack chunk; all lines of a chunk share the same hole column
+#[derive(Debug, Clone, Default)]
+pub struct GarbageQueue {
+    chunks: VecDeque<u32>,
+}
+
+impl GarbageQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues an incoming attack
+    pub fn receive(&mut self, lines: u32) {
+        if lines > 0 {
+            self.chunks.push_back(lines);
+        }
+    }
+
+    /// Total queued lines, stopping at `u32::MAX` however much was received
+    pub fn pending(&self) -> u32 {
+        self.chunks.iter().fold(0, |total, &lines| total.saturating_add(lines))
+    }
+
+    /// Uses an outgoing attack to cancel queued garbage, oldest first
+    /// Returns the part of the attack that is left over to send to the opponent
+    pub fn cancel(&mut self, mut attack: u32) -> u32 {
+        while attack > 0 {
+            let Some(front) = self.chunks.front_mut() else {
+                break;
+            };
+            let cancelled = attack.min(*front);
+            *front -= cancelled;
+            attack -= cancelled;
+            if *front == 0 {
+                self.chunks.pop_front();
+            }
+        }
+        attack
+    }
+
+    /// Removes up to `cap` lines from the queue, returned as chunks ready to be inserted
+    pub fn take(&mut self, cap: u32) -> Vec<u32> {
+        let mut taken = Vec::new();
+        let mut remaining = cap;
+        while remaining > 0 {
+            let Some(front) = self.chunks.front_mut() else {
+                break;
+            };
+            let lines = remaining.min(*front);
+            *front -= lines;
+            remaining -= lines;
+            taken.push(lines);
+            if *front == 0 {
+                self.chunks.pop_front();
+            }
+        }
+        taken
+    }
+
+    /// Drops all queued garbage
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+}
+
+/// Where the holes of rising garbage go; players of different communities are used to different styles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GarbageStyle {
+    #[default]
+    Clean,     // The lines of an attack chunk share one hole, picked at random for each chunk
+    Cheesy,    // Every line has a hole of its own, anywhere
+    Shifting,  // One hole column for all garbage, now and then moving a column to either side
+}
+
+impl GarbageStyle {
+    pub const ALL: [GarbageStyle; 3] = [GarbageStyle::Clean, GarbageStyle::Cheesy, GarbageStyle::Shifting];
+
+    /// Name shown in the settings screen
+    pub fn name(self) -> &'static str {
+        match self {
+            GarbageStyle::Clean => "clean",
+            GarbageStyle::Cheesy => "cheesy",
+            GarbageStyle::Shifting => "shifting",
+        }
+    }
+
+    /// Moves `steps` places through `ALL`, wrapping around
+    pub fn cycle(self, steps: i32) -> Self {
+        let index = Self::ALL.iter().position(|&style| style == self).unwrap_or(0) as i32;
+        Self::ALL[(index + steps).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+}
+
+/// Picks the hole columns of rising garbage in a `GarbageStyle`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GarbageHoles {
+    style: GarbageStyle,    // How holes are placed
+    column: Option<usize>,  // Hole column of shifting garbage, once the first line has risen
+}
+
+impl GarbageHoles {
+    pub fn new(style: GarbageStyle) -> Self {
+        Self { style, column: None }
+    }
+
+    pub fn style(&self) -> GarbageStyle {
+        self.style
+    }
+
+    /// The hole of each line of an attack chunk, bottom line last, all within `columns`
+    pub fn chunk(&mut self, lines: u32, columns: Range<usize>, rng: &mut impl Rng) -> Vec<usize> {
+        match self.style {
+            GarbageStyle::Clean => vec![rng.gen_range(columns); lines as usize],
+            GarbageStyle::Cheesy => (0..lines).map(|_| rng.gen_range(columns.clone())).collect(),
+            GarbageStyle::Shifting => (0..lines)
+                .map(|_| {
+                    let column = match self.column {
+                        None => rng.gen_range(columns.clone()),
+                        Some(column) if rng.gen_bool(GARBAGE_SHIFT_CHANCE) => {
+                            let shifted = if rng.gen_bool(0.5) { column + 1 } else { column.saturating_sub(1) };
+                            shifted.clamp(columns.start, columns.end - 1)
+                        }
+                        Some(column) => column.clamp(columns.start, columns.end - 1),
+                    };
+                    self.column = Some(column);
+                    column
+                })
+                .collect(),
+        }
+    }
+
+    /// Pushes the board up and fills the bottom with a chunk of garbage lines, with holes in this style
+    pub fn add_chunk(&mut self, board: &mut GameBoard, lines: u32, color: Color, rng: &mut impl Rng) {
+        for hole in self.chunk(lines, board.open_columns(), rng) {
+            board.add_garbage(1, hole, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attack_table() {
+        let mut tracker = AttackTracker::new();
+        assert_eq!(tracker.on_lock(1, TSpin::None), 0);
+        // Second consecutive clear, still no combo bonus
+        assert_eq!(tracker.on_lock(2, TSpin::None), 1);
+        // Third consecutive clear gets +1 combo
+        assert_eq!(tracker.on_lock(4, TSpin::None), 5);
+        // Back-to-back tetris gets +1, plus the combo bonus
+        assert_eq!(tracker.on_lock(4, TSpin::None), 4 + 1 + 1);
+        // A lock without clears resets the combo but keeps back-to-back
+        assert_eq!(tracker.on_lock(0, TSpin::None), 0);
+        assert_eq!(tracker.combo, 0);
+        assert!(tracker.back_to_back);
+        assert_eq!(tracker.on_lock(2, TSpin::Full), 4 + 1);
+        // A plain single breaks back-to-back
+        tracker.on_lock(1, TSpin::None);
+        assert!(!tracker.back_to_back);
+    }
+
+    #[test]
+    fn test_garbage_styles_place_holes() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let clean = GarbageHoles::new(GarbageStyle::Clean).chunk(6, 0..10, &mut rng);
+        assert!(clean.iter().all(|&hole| hole == clean[0]));
+
+        // Over enough lines, cheesy holes move around and shifting ones only ever step to a neighbour
+        let cheesy = GarbageHoles::new(GarbageStyle::Cheesy).chunk(40, 2..8, &mut rng);
+        assert!(cheesy.iter().all(|hole| (2..8).contains(hole)));
+        assert!(cheesy.iter().any(|&hole| hole != cheesy[0]));
+        let mut shifting = GarbageHoles::new(GarbageStyle::Shifting);
+        let mut holes = shifting.chunk(40, 0..10, &mut rng);
+        holes.extend(shifting.chunk(40, 0..10, &mut rng));
+        assert!(holes.windows(2).all(|pair| pair[0].abs_diff(pair[1]) <= 1));
+        assert!(holes.windows(2).any(|pair| pair[0] != pair[1]));
+
+        assert_eq!(GarbageStyle::Clean.cycle(-1), GarbageStyle::Shifting);
+    }
+
+    #[test]
+    fn test_cancel_and_take() {
+        let mut queue = GarbageQueue::new();
+        queue.receive(3);
+        queue.receive(2);
+        assert_eq!(queue.pending(), 5);
+
+        // Cancel the first chunk completely and part of the second
+        assert_eq!(queue.cancel(4), 0);
+        assert_eq!(queue.pending(), 1);
+
+        // Attack larger than the queue sends the rest
+        assert_eq!(queue.cancel(3), 2);
+        assert_eq!(queue.pending(), 0);
+
+        queue.receive(6);
+        queue.receive(4);
+        assert_eq!(queue.take(8), vec![6, 2]);
+        assert_eq!(queue.pending(), 2);
+
+        queue.receive(u32::MAX);
+        assert_eq!(queue.pending(), u32::MAX);
+    }
+
+    #[test]
+    fn test_t_spin_detection() {
+        let mut board = GameBoard::new();
+        // Build a T-slot at the bottom: floor with a one-cell gap, row above with a three-cell gap
+        for (x, cell) in board[19].iter_mut().enumerate() {
+            if x != 4 {
+                *cell = Color::RED;
+            }
+        }
+        for (x, cell) in board[18].iter_mut().enumerate() {
+            if !(3..=5).contains(&x) {
+                *cell = Color::RED;
+            }
+        }
+        // Overhang covering one top corner
+        board[17][3] = Color::RED;
+
+        // T pointing down into the slot
+        let mut piece = Tetromino::new(TetrominoType::T);
+        piece.rotate();
+        piece.rotate();
+        piece.position.x = 3.0;
+        piece.position.y = 18.0;
+
+        assert_eq!(detect_t_spin(&board, &piece, true), TSpin::Full);
+        assert_eq!(detect_t_spin(&board, &piece, false), TSpin::None);
+
+        // Non-T pieces never T-spin
+        let mut other = Tetromino::new(TetrominoType::L);
+        other.position = piece.position;
+        assert_eq!(detect_t_spin(&board, &other, true), TSpin::None);
+
+        // In open space there are not enough filled corners
+        let mut open = Tetromino::new(TetrominoType::T);
+        open.position.y = 5.0;
+        assert_eq!(detect_t_spin(&GameBoard::new(), &open, true), TSpin::None);
+    }
+}
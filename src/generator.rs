@@ -0,0 +1,102 @@
+use crate::tetromino::TetrominoType;
+use rand::rngs::StdRng;
+use std::fmt::Debug;
+use std::fs;
+use std::path::Path;
+
+/// Where a game's pieces come from, once any sequence dealt with `Game::deal_sequence` has run out
+/// Generators draw on the game's seeded random number generator, so a seed still decides every piece
+pub trait PieceGenerator: Debug + Send {
+    /// Type of the next piece to join the queue
+    fn next(&mut self, rng: &mut StdRng) -> TetrominoType;
+
+    /// A copy of the generator at the same point, for cloned games
+    fn clone_box(&self) -> Box<dyn PieceGenerator>;
+}
+
+impl Clone for Box<dyn PieceGenerator> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Every piece drawn independently of the ones before it, the standard generator
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Random;
+
+impl PieceGenerator for Random {
+    fn next(&mut self, rng: &mut StdRng) -> TetrominoType {
+        TetrominoType::random_from(rng)
+    }
+
+    fn clone_box(&self) -> Box<dyn PieceGenerator> {
+        Box::new(*self)
+    }
+}
+
+/// A set list of pieces dealt over and over, for drilling a pattern in practice games
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sequence {
+    kinds: Vec<TetrominoType>,  // Never empty
+    next: usize,                // Index of the piece dealt next
+}
+
+impl Sequence {
+    /// Reads piece letters like `IJLOSTZ`, ignoring case, whitespace and anything after a `#` on a line
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut kinds = Vec::new();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            for letter in line.chars().filter(|letter| !letter.is_whitespace()) {
+                let kind = TetrominoType::from_letter(letter).ok_or_else(|| format!("unknown piece {:?}", letter))?;
+                kinds.push(kind);
+            }
+        }
+        if kinds.is_empty() {
+            return Err("no pieces in the sequence".to_string());
+        }
+        Ok(Self { kinds, next: 0 })
+    }
+
+    /// Reads a sequence file, written the same way as for `parse`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// The pieces of one pass through the sequence
+    pub fn kinds(&self) -> &[TetrominoType] {
+        &self.kinds
+    }
+}
+
+impl PieceGenerator for Sequence {
+    fn next(&mut self, _rng: &mut StdRng) -> TetrominoType {
+        let kind = self.kinds[self.next];
+        self.next = (self.next + 1) % self.kinds.len();
+        kind
+    }
+
+    fn clone_box(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_sequence_repeats() {
+        use TetrominoType::*;
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut sequence = Sequence::parse("i t\n# comment\nsz # trailing").unwrap();
+        let dealt: Vec<_> = (0..6).map(|_| sequence.next(&mut rng)).collect();
+        assert_eq!(dealt, vec![I, T, S, Z, I, T]);
+
+        assert!(Sequence::parse("IJX").unwrap_err().contains("'X'"));
+        assert!(Sequence::parse("  # nothing\n").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::board::GameBoard;
+use crate::config;
+use crate::constants::{GARBAGE_COLOR, GHOST_FILE};
+use crate::game::LockedPiece;
+use crate::tetromino::TetrominoType;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Full path of the best sprint's recording, next to the settings file
+pub fn ghost_path() -> PathBuf {
+    config::config_dir().join(GHOST_FILE)
+}
+
+/// A piece of a recorded run: when it locked and where
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GhostLock {
+    pub time: f64,               // Seconds of play when the piece locked
+    pub piece: char,             // Letter of the piece
+    pub cells: Vec<(i32, i32)>,  // Board cells it filled, before the rows it completed were cleared
+}
+
+/// Every lock of a game, enough to play its board back without the inputs or the piece sequence
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GhostRun {
+    pub time: f64,               // Seconds the run took, set when it finishes
+    pub locks: Vec<GhostLock>,   // In the order they happened
+}
+
+impl GhostRun {
+    /// Reads a recorded run, or `None` if there is none or it can't be read
+    pub fn load(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&json) {
+            Ok(run) => Some(run),
+            Err(e) => {
+                log::warn!(target: "save", "Could not read the sprint ghost {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Saves the run to a file, creating its directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    /// Adds a lock that happened `time` seconds into the game
+    pub fn record(&mut self, time: f64, locked: &LockedPiece) {
+        self.locks.push(GhostLock { time, piece: locked.piece.kind.letter(), cells: locked.cells.clone() });
+    }
+
+    /// Whether a run finished in `time` seconds would beat this one
+    pub fn is_beaten_by(&self, time: f64) -> bool {
+        self.time <= 0.0 || time < self.time
+    }
+}
+
+/// Plays a recorded run back on a board of its own, in step with the game being played
+#[derive(Debug, Clone)]
+pub struct GhostPlayer {
+    run: GhostRun,
+    board: GameBoard,  // The run's board at the time last shown
+    next: usize,       // Index of the next lock to place
+    lines: u32,        // Lines the run has cleared so far
+}
+
+impl GhostPlayer {
+    pub fn new(run: GhostRun) -> Self {
+        Self { run, board: GameBoard::new(), next: 0, lines: 0 }
+    }
+
+    /// Places every lock that happened by `time` seconds into the run, clearing the rows they complete
+    pub fn update(&mut self, time: f64) {
+        while let Some(lock) = self.run.locks.get(self.next).filter(|lock| lock.time <= time) {
+            let color = TetrominoType::from_letter(lock.piece).map_or(GARBAGE_COLOR, TetrominoType::color);
+            self.board.fill(lock.cells.iter().copied(), color);
+            self.lines += self.board.clear_lines();
+            self.next += 1;
+        }
+    }
+
+    pub fn board(&self) -> &GameBoard {
+        &self.board
+    }
+
+    pub fn lines(&self) -> u32 {
+        self.lines
+    }
+
+    pub fn run(&self) -> &GhostRun {
+        &self.run
+    }
+
+    /// Whether the run had finished by `time` seconds
+    pub fn is_finished(&self, time: f64) -> bool {
+        time >= self.run.time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, Move};
+
+    #[test]
+    fn test_ghost_plays_back_the_board() {
+        let mut game = Game::with_seed(7);
+        let mut run = GhostRun::default();
+        for i in 0..6 {
+            if let Some(locked) = game.apply(Move::HardDrop).locked {
+                run.record(i as f64, &locked);
+            }
+        }
+        run.time = 6.0;
+
+        let mut ghost = GhostPlayer::new(run.clone());
+        ghost.update(2.5);
+        assert_eq!(ghost.next, 3);
+        ghost.update(10.0);
+        assert_eq!(ghost.board().rows(), game.board().rows());
+        assert!(ghost.is_finished(6.0));
+        assert!(run.is_beaten_by(5.9));
+        assert!(!run.is_beaten_by(6.0));
+
+        let dir = std::env::temp_dir().join(format!("tetris-ghost-{}", std::process::id()));
+        let path = dir.join(GHOST_FILE);
+        run.save(&path).unwrap();
+        assert_eq!(GhostRun::load(&path), Some(run));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_ghost_clears_lines() {
+        let mut run = GhostRun { time: 2.0, locks: Vec::new() };
+        for (i, left) in [0, 4].into_iter().enumerate() {
+            let cells = (left..left + 4).map(|x| (x, 19)).collect();
+            run.locks.push(GhostLock { time: i as f64, piece: 'I', cells });
+        }
+        run.locks.push(GhostLock { time: 2.0, piece: 'O', cells: vec![(8, 18), (9, 18), (8, 19), (9, 19)] });
+
+        let mut ghost = GhostPlayer::new(run);
+        ghost.update(2.0);
+        assert_eq!(ghost.lines(), 1);
+        assert_eq!(ghost.board().rows()[19][8], TetrominoType::O.color());
+        assert_eq!(ghost.board().rows()[19][0], ggez::graphics::Color::BLACK);
+    }
+}
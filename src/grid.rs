@@ -0,0 +1,122 @@
+use crate::camera::Camera;
+use crate::constants::{GRID_HEIGHT, GRID_LINE_WIDTH, GRID_WIDTH};
+use ggez::graphics::{self, Color, Rect};
+use ggez::{Context, GameResult};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+/// How the grid over the board is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GridStyle {
+    Off,      // No grid at all
+    Dots,     // A dot where the lines would cross
+    Lines,    // Lines between every row and column
+    Outline,  // Only the edge of the playfield
+}
+
+impl GridStyle {
+    pub const ALL: [GridStyle; 4] = [GridStyle::Off, GridStyle::Dots, GridStyle::Lines, GridStyle::Outline];
+
+    /// Name shown in the settings screen
+    pub fn name(self) -> &'static str {
+        match self {
+            GridStyle::Off => "off",
+            GridStyle::Dots => "dots",
+            GridStyle::Lines => "lines",
+            GridStyle::Outline => "outline",
+        }
+    }
+
+    /// Moves `steps` places through `ALL`, wrapping around
+    pub fn cycle(self, steps: i32) -> Self {
+        let index = Self::ALL.iter().position(|&style| style == self).unwrap_or(0) as i32;
+        Self::ALL[(index + steps).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+}
+
+/// Rectangles that make up the grid over the board `camera` shows
+pub fn grid_rects(style: GridStyle, camera: &Camera) -> Vec<Rect> {
+    let board = camera.bounds();
+    let width = GRID_LINE_WIDTH * camera.zoom();
+    let column = |x: i32| Rect::new(board.x + x as f32 * camera.cell_size - width / 2.0, board.y - width / 2.0, width, board.h + width);
+    let row = |y: i32| Rect::new(board.x - width / 2.0, board.y + y as f32 * camera.cell_size - width / 2.0, board.w + width, width);
+    match style {
+        GridStyle::Off => Vec::new(),
+        GridStyle::Lines => (0..=GRID_WIDTH).map(column).chain((0..=GRID_HEIGHT).map(row)).collect(),
+        GridStyle::Outline => vec![column(0), column(GRID_WIDTH), row(0), row(GRID_HEIGHT)],
+        GridStyle::Dots => {
+            // Only the crossings inside the board; its edge is already framed by the border
+            let size = width * 2.0;
+            (1..GRID_HEIGHT)
+                .flat_map(|y| (1..GRID_WIDTH).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    let [left, top] = camera.cell(x as f32, y as f32);
+                    Rect::new(left - size / 2.0, top - size / 2.0, size, size)
+                })
+                .collect()
+        }
+    }
+}
+
+/// What a cached grid mesh was built for: the style, the camera as bits, and the color
+type GridKey = (GridStyle, [u32; 3], [u8; 4]);
+
+/// Draws the grid from a mesh that is built once and reused until the style, camera or color changes
+#[derive(Default)]
+pub struct GridRenderer {
+    cache: RefCell<Option<(GridKey, graphics::Mesh)>>,  // The last mesh built and what it was built for
+}
+
+impl GridRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, style: GridStyle, camera: &Camera, color: Color) -> GameResult {
+        let rects = match style {
+            GridStyle::Off => return Ok(()),
+            _ => grid_rects(style, camera),
+        };
+        let (r, g, b, a) = color.to_rgba();
+        let key = (style, [camera.origin[0].to_bits(), camera.origin[1].to_bits(), camera.cell_size.to_bits()], [r, g, b, a]);
+        let mut cache = self.cache.borrow_mut();
+        if cache.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let mut builder = graphics::MeshBuilder::new();
+            for rect in rects {
+                builder.rectangle(graphics::DrawMode::fill(), rect, color)?;
+            }
+            *cache = Some((key, graphics::Mesh::from_data(ctx, builder.build())));
+        }
+        if let Some((_, mesh)) = cache.as_ref() {
+            canvas.draw(mesh, graphics::DrawParam::default());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::GRID_SIZE;
+
+    #[test]
+    fn test_grid_shapes() {
+        let camera = Camera::new([10.0, 20.0], GRID_SIZE);
+        assert!(grid_rects(GridStyle::Off, &camera).is_empty());
+        assert_eq!(grid_rects(GridStyle::Lines, &camera).len(), (GRID_WIDTH + 1 + GRID_HEIGHT + 1) as usize);
+        assert_eq!(grid_rects(GridStyle::Dots, &camera).len(), ((GRID_WIDTH - 1) * (GRID_HEIGHT - 1)) as usize);
+
+        // The outline is the outermost of the full lines
+        let lines = grid_rects(GridStyle::Lines, &camera);
+        let outline = grid_rects(GridStyle::Outline, &camera);
+        assert_eq!(outline, [lines[0], lines[GRID_WIDTH as usize], lines[GRID_WIDTH as usize + 1], lines[lines.len() - 1]]);
+
+        // Lines keep their width relative to the cells when the board is zoomed
+        let small = camera.zoomed(0.5);
+        assert_eq!(grid_rects(GridStyle::Lines, &small)[0].w, GRID_LINE_WIDTH / 2.0);
+
+        assert_eq!(GridStyle::Off.cycle(-1), GridStyle::Outline);
+        assert_eq!(GridStyle::Lines.name(), "lines");
+    }
+}
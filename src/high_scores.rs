@@ -0,0 +1,455 @@
+use crate::config;
+use crate::constants::{HIGH_SCORES_FILE, MAX_HIGH_SCORES};
+use crate::mode::format_time;
+use crate::ui;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the high score file; files from before versioning held only names and scores and count as 1
+pub const HIGH_SCORES_VERSION: u32 = 2;
+
+/// High score entry with player name and score, and how the game went
+/// Entries from version 1 files have only a name and score; the rest is zero or empty
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u32,
+    #[serde(default)]
+    pub timestamp: u64,  // Seconds since the Unix epoch when the game ended, 0 if unknown
+    #[serde(default)]
+    pub level: u32,      // Level the game ended on, 0 if unknown
+    #[serde(default)]
+    pub lines: u32,      // Lines cleared
+    #[serde(default)]
+    pub duration: f64,   // Seconds of play, 0.0 if unknown
+    #[serde(default)]
+    pub mode: String,    // Name of the game mode, empty if unknown
+}
+
+impl HighScoreEntry {
+    /// An entry with only a name and score, dated now
+    pub fn new(name: String, score: u32) -> Self {
+        Self {
+            name,
+            score,
+            timestamp: now(),
+            level: 0,
+            lines: 0,
+            duration: 0.0,
+            mode: String::new(),
+        }
+    }
+
+    /// Day the game ended on as `YYYY-MM-DD` in UTC, or `-` if unknown
+    pub fn date(&self) -> String {
+        if self.timestamp == 0 {
+            return "-".to_string();
+        }
+        let (year, month, day) = civil_date(self.timestamp / 86_400);
+        format!("{}-{:02}-{:02}", year, month, day)
+    }
+
+    /// Play time as minutes and seconds, or `-` if unknown
+    pub fn time(&self) -> String {
+        if self.duration > 0.0 { format_time(self.duration) } else { "-".to_string() }
+    }
+}
+
+/// Seconds since the Unix epoch
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Year, month and day of the `days`th day after 1970-01-01, in the proleptic Gregorian calendar
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    // Counts in 400-year eras starting on March 1st, so the leap day ends each year
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// `path` with `extension` added after its own, e.g. `high_scores.json.tmp`
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+/// Full path of the high score file, in the config directory so every working directory shares it
+pub fn high_scores_path() -> PathBuf {
+    config::config_dir().join(HIGH_SCORES_FILE)
+}
+
+/// Keeps a copy of a high score file the game can't use as it is, next to the original
+fn backup(path: &Path, contents: &str) {
+    let backup = sibling(path, &format!("{}.corrupt", now()));
+    match fs::write(&backup, contents) {
+        Ok(()) => log::info!(target: "save", "Kept a copy of the high scores in {}", backup.display()),
+        Err(e) => log::error!(target: "save", "Could not back up the high scores to {}: {}", backup.display(), e),
+    }
+}
+
+/// Collection of high scores that can be loaded/saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScores {
+    #[serde(default = "first_version")]
+    pub version: u32,  // Format of the file the scores were read from; saving writes the current one
+    pub entries: Vec<HighScoreEntry>,
+}
+
+/// Version of files written before the format was versioned
+fn first_version() -> u32 {
+    1
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HighScores {
+    /// Create a new empty high score list
+    pub fn new() -> Self {
+        Self {
+            version: HIGH_SCORES_VERSION,
+            entries: Vec::new(),
+        }
+    }
+    
+    /// Load high scores from file
+    pub fn load() -> Self {
+        Self::load_from(&high_scores_path())
+    }
+
+    /// Load high scores from the given file; a missing file gives an empty list
+    /// A file that can't be read as scores is copied to a `.corrupt` backup first, since the next
+    /// save overwrites the original, and so is one from a newer version of the game, whose extra
+    /// details would otherwise be lost
+    pub fn load_from(path: &Path) -> Self {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) => return Self::new(),
+        };
+        match Self::from_json(&contents) {
+            Ok((scores, stored_version)) => {
+                if stored_version > HIGH_SCORES_VERSION {
+                    backup(path, &contents);
+                }
+                scores
+            }
+            Err(e) => {
+                log::warn!(target: "save", "High scores in {} are unreadable: {}", path.display(), e);
+                backup(path, &contents);
+                Self::new()
+            }
+        }
+    }
+
+    /// Reads a high score file of any version, bringing it up to the current one
+    /// Returns the scores and the version the file was written in
+    pub fn from_json(json: &str) -> serde_json::Result<(Self, u32)> {
+        let mut scores: Self = serde_json::from_str(json)?;
+        let stored_version = scores.version;
+        // Version 1 entries have only a name and score, so their details read as zero and empty
+        scores.version = HIGH_SCORES_VERSION;
+
+        // Edited files may be out of order or too long
+        scores.entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        scores.entries.truncate(MAX_HIGH_SCORES);
+        Ok((scores, stored_version))
+    }
+    
+    /// Save high scores to file
+    pub fn save(&self) -> io::Result<()> {
+        self.save_to(&high_scores_path())
+    }
+
+    /// Save high scores to the given file
+    /// The scores are written to a temporary file that then replaces the old one, so a crash
+    /// halfway through a save leaves the previous scores intact
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string(self)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let temp = sibling(path, "tmp");
+        let mut file = File::create(&temp)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    }
+    
+    /// Add a new high score if it qualifies, return true if it was added
+    /// The table is only changed in memory; `save` writes it out
+    pub fn add_score(&mut self, name: String, score: u32) -> bool {
+        self.add_entry(HighScoreEntry::new(name, score))
+    }
+
+    /// Add a new high score with the details of its game if it qualifies, return true if it was added
+    pub fn add_entry(&mut self, entry: HighScoreEntry) -> bool {
+        // Check if the score qualifies (greater than the lowest score or fewer than MAX_HIGH_SCORES entries)
+        let qualifies = self.would_qualify(entry.score);
+        
+        if qualifies {
+            // Add the new entry
+            self.entries.push(entry);
+            
+            // Sort entries by score (descending)
+            self.entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+            
+            // Truncate to max number of entries
+            if self.entries.len() > MAX_HIGH_SCORES {
+                self.entries.truncate(MAX_HIGH_SCORES);
+            }
+        }
+        
+        qualifies
+    }
+    
+    /// Adds scores from another table, such as one exported on another machine
+    /// Entries already in the table are skipped: an entry is the same game as one with the same
+    /// timestamp, or for undated entries the same name and score; returns how many made the table
+    /// Like `add_entry`, this only changes the table in memory
+    pub fn merge(&mut self, entries: &[HighScoreEntry]) -> usize {
+        let mut added = 0;
+        for entry in entries {
+            let known = self.entries.iter().any(|other| {
+                other.timestamp == entry.timestamp
+                    && (entry.timestamp != 0 || (other.name == entry.name && other.score == entry.score))
+            });
+            if !known && self.would_qualify(entry.score) {
+                self.entries.push(entry.clone());
+                self.entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+                self.entries.truncate(MAX_HIGH_SCORES);
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Check if a score would qualify for the high score list
+    pub fn would_qualify(&self, score: u32) -> bool {
+        self.entries.len() < MAX_HIGH_SCORES || 
+        self.entries.iter().any(|entry| entry.score < score)
+    }
+}
+
+/// Why a name can't go on the high score table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameRejection {
+    Empty,            // Nothing is left once the name is trimmed
+    Blocked,          // The name contains a blocked word
+    Character(char),  // The name uses a character of a kind that isn't allowed
+}
+
+/// Checks a name before it goes on the high score table; returns the name to record, or why it can't be
+/// The table runs every submitted name through one, so installs can swap in rules of their own
+pub trait NameValidator {
+    fn validate(&self, name: &str) -> Result<String, NameRejection>;
+}
+
+/// Kinds of characters a name may be limited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CharClass {
+    Letters,  // Letters of any script, with their accents
+    Digits,   // Numbers
+    Spaces,   // Spaces between words
+    Symbols,  // Punctuation, symbols and emoji
+}
+
+impl CharClass {
+    /// The kind of a grapheme cluster, decided by its first character
+    fn of(cluster: &str) -> Option<CharClass> {
+        let ch = cluster.chars().next()?;
+        Some(if ch.is_alphabetic() {
+            CharClass::Letters
+        } else if ch.is_numeric() {
+            CharClass::Digits
+        } else if ch.is_whitespace() {
+            CharClass::Spaces
+        } else {
+            CharClass::Symbols
+        })
+    }
+}
+
+/// Letters a blocked word is often spelled around with, as digits and symbols
+const LOOKALIKES: [(char, char); 8] = [('0', 'o'), ('1', 'i'), ('3', 'e'), ('4', 'a'), ('5', 's'), ('7', 't'), ('@', 'a'), ('$', 's')];
+
+/// A name folded for comparing with blocked words: lowercase, look-alikes replaced and everything but letters dropped,
+/// so spacing or dots between the letters don't hide a word
+fn fold(text: &str) -> String {
+    text.chars()
+        .map(|ch| LOOKALIKES.iter().find(|&&(alike, _)| alike == ch).map_or(ch, |&(_, letter)| letter))
+        .filter(|ch| ch.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Validates names by the settings: trims them, turns down blocked words and keeps them to the allowed kinds of characters
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NameRules {
+    blocked: Vec<String>,     // Folded words a name may not contain
+    allowed: Vec<CharClass>,  // Kinds of characters allowed; empty allows any
+}
+
+impl NameRules {
+    pub fn new(blocked: &[String], allowed: &[CharClass]) -> Self {
+        let blocked = blocked.iter().map(|word| fold(word)).filter(|word| !word.is_empty()).collect();
+        Self { blocked, allowed: allowed.to_vec() }
+    }
+}
+
+impl NameValidator for NameRules {
+    fn validate(&self, name: &str) -> Result<String, NameRejection> {
+        // Runs of spaces inside the name become one, as they do at its ends
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if name.is_empty() {
+            return Err(NameRejection::Empty);
+        }
+        if !self.allowed.is_empty() {
+            if let Some(cluster) = ui::graphemes(&name).find(|cluster| CharClass::of(cluster).is_some_and(|class| !self.allowed.contains(&class))) {
+                return Err(NameRejection::Character(cluster.chars().next().unwrap_or_default()));
+            }
+        }
+        let folded = fold(&name);
+        if self.blocked.iter().any(|word| folded.contains(word.as_str())) {
+            return Err(NameRejection::Blocked);
+        }
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_high_scores() {
+        let mut high_scores = HighScores::new();
+        
+        // Test adding scores when list is not full
+        assert!(high_scores.add_score("Player1".to_string(), 1000));
+        assert!(high_scores.add_score("Player2".to_string(), 500));
+        assert!(high_scores.add_score("Player3".to_string(), 750));
+        
+        // Test scores are sorted correctly
+        assert_eq!(high_scores.entries[0].score, 1000);
+        assert_eq!(high_scores.entries[1].score, 750);
+        assert_eq!(high_scores.entries[2].score, 500);
+        
+        // Test would_qualify function with non-full list
+        assert!(high_scores.would_qualify(400)); // Should qualify when list isn't full
+        
+        // Fill up the high scores list
+        for i in 0..MAX_HIGH_SCORES {
+            high_scores.add_score(format!("Player{}", i), (1000 + i) as u32);
+        }
+        
+        // Test would_qualify function with full list
+        assert!(high_scores.would_qualify(1500)); // Should qualify (better than some scores)
+        assert!(!high_scores.would_qualify(500)); // Shouldn't qualify (worse than all scores)
+        
+        // Test maximum number of scores
+        assert_eq!(high_scores.entries.len(), MAX_HIGH_SCORES);
+        
+        // Test adding a qualifying score to full list
+        assert!(high_scores.add_score("NewPlayer".to_string(), 1500));
+        assert_eq!(high_scores.entries.len(), MAX_HIGH_SCORES); // List should stay at max size
+    }
+
+    #[test]
+    fn test_name_rules() {
+        let rules = NameRules::new(&["Darn".to_string()], &[]);
+        assert_eq!(rules.validate("  Ann   Lee "), Ok("Ann Lee".to_string()));
+        assert_eq!(rules.validate("   "), Err(NameRejection::Empty));
+        assert_eq!(rules.validate("xDARNx"), Err(NameRejection::Blocked));
+        assert_eq!(rules.validate("d.4.r.n"), Err(NameRejection::Blocked));
+        assert_eq!(rules.validate("Zoë 😀"), Ok("Zoë 😀".to_string()));
+
+        let rules = NameRules::new(&[], &[CharClass::Letters, CharClass::Spaces]);
+        assert_eq!(rules.validate("Rene\u{0301} Roy"), Ok("Rene\u{0301} Roy".to_string()));
+        assert_eq!(rules.validate("Ann2"), Err(NameRejection::Character('2')));
+        assert_eq!(rules.validate("Ann!"), Err(NameRejection::Character('!')));
+    }
+
+    #[test]
+    fn test_migrates_version_1_files() {
+        let old = r#"{"entries":[{"name":"AAA","score":900},{"name":"BBB","score":400}]}"#;
+        let (scores, stored_version) = HighScores::from_json(old).unwrap();
+        assert_eq!(stored_version, 1);
+        assert_eq!(scores.version, HIGH_SCORES_VERSION);
+        assert_eq!(scores.entries.len(), 2);
+        assert_eq!(scores.entries[0].name, "AAA");
+        assert_eq!(scores.entries[0].level, 0);
+        assert_eq!(scores.entries[0].date(), "-");
+        assert_eq!(scores.entries[0].time(), "-");
+
+        // The details survive a save and load
+        let entry = HighScoreEntry {
+            level: 7,
+            lines: 64,
+            duration: 312.5,
+            mode: "MARATHON".to_string(),
+            timestamp: 1_700_000_000,
+            ..HighScoreEntry::new("CCC".to_string(), 12000)
+        };
+        let mut scores = HighScores::new();
+        scores.entries.push(entry.clone());
+        let json = serde_json::to_string(&scores).unwrap();
+        assert!(json.contains(&format!("\"version\":{}", HIGH_SCORES_VERSION)));
+        assert_eq!(HighScores::from_json(&json).unwrap().0.entries, [entry]);
+        assert!(HighScores::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_entry_date_and_time() {
+        let mut entry = HighScoreEntry::new("DDD".to_string(), 1);
+        entry.timestamp = 0;
+        assert_eq!(entry.date(), "-");
+        entry.timestamp = 951_782_400;  // The leap day of 2000
+        assert_eq!(entry.date(), "2000-02-29");
+        entry.timestamp = 1_700_000_000;
+        assert_eq!(entry.date(), "2023-11-14");
+        entry.duration = 65.2;
+        assert_eq!(entry.time(), "1:05.20");
+    }
+
+    #[test]
+    fn test_saves_atomically_and_backs_up_corrupt_files() {
+        let dir = std::env::temp_dir().join(format!("tetris-high-scores-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("high_scores.json");
+
+        let mut scores = HighScores::new();
+        scores.entries.push(HighScoreEntry::new("AAA".to_string(), 500));
+        scores.save_to(&path).unwrap();
+        assert!(!sibling(&path, "tmp").exists(), "the temporary file is renamed into place");
+        assert_eq!(HighScores::load_from(&path).entries, scores.entries);
+
+        // A damaged file is kept aside instead of being lost to the next save
+        fs::write(&path, "{\"entries\": [{\"name\": \"AAA\", \"sco").unwrap();
+        assert!(HighScores::load_from(&path).entries.is_empty());
+        let backups: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".corrupt"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert!(fs::read_to_string(backups[0].path()).unwrap().contains("\"sco"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+use crate::config;
+use crate::constants::{HISTORY_FILE, HISTORY_PAGE_SIZE};
+use crate::high_scores::HighScoreEntry;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Full path of the game history, next to the settings file
+pub fn history_path() -> PathBuf {
+    config::config_dir().join(HISTORY_FILE)
+}
+
+/// Every game played on this machine, oldest first, each summed up like a high score entry
+/// The file holds one JSON line per game, so adding a game never rewrites the ones before it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    pub games: Vec<HighScoreEntry>,
+}
+
+impl History {
+    /// Reads the history, skipping lines that can't be read, or an empty one if there is no file
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let Ok(text) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+        let games: Vec<HighScoreEntry> = lines.iter().filter_map(|line| serde_json::from_str(line).ok()).collect();
+        if games.len() < lines.len() {
+            log::warn!(target: "save", "Skipped {} unreadable games in {}", lines.len() - games.len(), path.display());
+        }
+        Self { games }
+    }
+
+    /// Adds a game to the end of the history file, creating it and its directory if needed
+    pub fn append(path: impl AsRef<Path>, game: &HighScoreEntry) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut line = serde_json::to_vec(game)?;
+        line.push(b'\n');
+        OpenOptions::new().create(true).append(true).open(path)?.write_all(&line)
+    }
+
+    /// Number of pages on the history screen; an empty history still has its one empty page
+    pub fn pages(&self) -> usize {
+        self.games.len().div_ceil(HISTORY_PAGE_SIZE).max(1)
+    }
+
+    /// The games on a page of the history screen, newest first; page 0 holds the latest games
+    pub fn page(&self, page: usize) -> Vec<&HighScoreEntry> {
+        self.games.iter().rev().skip(page * HISTORY_PAGE_SIZE).take(HISTORY_PAGE_SIZE).collect()
+    }
+
+    /// Average score of the latest `count` games, and of the `count` games before those if there were that many
+    pub fn trend(&self, count: usize) -> Option<(f64, Option<f64>)> {
+        let average = |games: &[HighScoreEntry]| games.iter().map(|game| game.score as f64).sum::<f64>() / games.len() as f64;
+        let (before, latest) = self.games.split_at(self.games.len().saturating_sub(count));
+        if latest.is_empty() {
+            return None;
+        }
+        let previous = before.len().checked_sub(count).map(|start| average(&before[start..]));
+        Some((average(latest), previous))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(score: u32) -> HighScoreEntry {
+        HighScoreEntry { mode: "MARATHON".to_string(), ..HighScoreEntry::new("ANNA".to_string(), score) }
+    }
+
+    #[test]
+    fn test_history_appends_and_pages() {
+        let dir = std::env::temp_dir().join(format!("tetris-history-{}", std::process::id()));
+        let path = dir.join(HISTORY_FILE);
+        assert_eq!(History::load(&path).pages(), 1);
+
+        for score in 1..=HISTORY_PAGE_SIZE as u32 + 3 {
+            History::append(&path, &game(score * 100)).unwrap();
+        }
+        let mut text = fs::read_to_string(&path).unwrap();
+        text.push_str("{\"name\": \"cut off\n");
+        fs::write(&path, text).unwrap();
+
+        let history = History::load(&path);
+        assert_eq!(history.games.len(), HISTORY_PAGE_SIZE + 3);
+        assert_eq!(history.pages(), 2);
+        assert_eq!(history.page(0)[0].score, (HISTORY_PAGE_SIZE as u32 + 3) * 100, "the newest game comes first");
+        assert_eq!(history.page(1).iter().map(|game| game.score).collect::<Vec<_>>(), vec![300, 200, 100]);
+        assert!(history.page(2).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_trend_compares_the_latest_games_with_those_before() {
+        let mut history = History::default();
+        assert_eq!(history.trend(2), None);
+        history.games = [100, 200, 300, 500].into_iter().map(game).collect();
+        assert_eq!(history.trend(2), Some((400.0, Some(150.0))));
+        assert_eq!(history.trend(3), Some((1000.0 / 3.0, None)));
+        assert_eq!(history.trend(10), Some((275.0, None)));
+    }
+}
@@ -0,0 +1,350 @@
+use ggez::input::keyboard::KeyCode;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Logical actions a player can trigger during a game
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    RotateCw,
+    HardDrop,
+    Hold,
+    Pause,
+    ToggleMusic,
+    VolumeDown,
+    VolumeUp,
+    Mute,
+    NextTrack,
+    PreviewRotations,
+}
+
+impl Action {
+    /// Every action, in the order shown on the controls screen
+    pub const ALL: [Action; 13] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::SoftDrop,
+        Action::RotateCw,
+        Action::HardDrop,
+        Action::Hold,
+        Action::Pause,
+        Action::ToggleMusic,
+        Action::VolumeDown,
+        Action::VolumeUp,
+        Action::Mute,
+        Action::NextTrack,
+        Action::PreviewRotations,
+    ];
+
+    /// Name shown on the controls screen
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "MOVE LEFT",
+            Action::MoveRight => "MOVE RIGHT",
+            Action::SoftDrop => "SOFT DROP",
+            Action::RotateCw => "ROTATE",
+            Action::HardDrop => "HARD DROP",
+            Action::Hold => "HOLD",
+            Action::Pause => "PAUSE",
+            Action::ToggleMusic => "TOGGLE MUSIC",
+            Action::VolumeDown => "VOLUME DOWN",
+            Action::VolumeUp => "VOLUME UP",
+            Action::Mute => "MUTE",
+            Action::NextTrack => "NEXT TRACK",
+            Action::PreviewRotations => "PREVIEW TURNS",
+        }
+    }
+
+    /// Whether the action changes the sound rather than the game
+    pub fn is_audio(self) -> bool {
+        matches!(
+            self,
+            Action::ToggleMusic | Action::VolumeDown | Action::VolumeUp | Action::Mute | Action::NextTrack
+        )
+    }
+
+    /// Keys bound to the action when the config file doesn't override them
+    fn default_keys(self) -> Vec<KeyCode> {
+        match self {
+            Action::MoveLeft => vec![KeyCode::Left],
+            Action::MoveRight => vec![KeyCode::Right],
+            Action::SoftDrop => vec![KeyCode::Down],
+            Action::RotateCw => vec![KeyCode::Up],
+            Action::HardDrop => vec![KeyCode::Space],
+            Action::Hold => vec![KeyCode::C, KeyCode::LShift],
+            Action::Pause => vec![KeyCode::P],
+            Action::ToggleMusic => vec![KeyCode::M],
+            Action::VolumeDown => vec![KeyCode::Minus],
+            Action::VolumeUp => vec![KeyCode::Equals],
+            Action::Mute => vec![KeyCode::N],
+            Action::NextTrack => vec![KeyCode::Period],
+            Action::PreviewRotations => vec![KeyCode::LControl],
+        }
+    }
+}
+
+/// Which hand a player steers with, picking the side of the keyboard the movement keys start on
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Handedness {
+    #[default]
+    Right,  // Arrow keys move and rotate; space, C and left shift drop and hold
+    Left,   // W, A, S and D move and rotate; space, comma and right shift drop and hold
+}
+
+impl Handedness {
+    pub const ALL: [Handedness; 2] = [Handedness::Right, Handedness::Left];
+
+    /// Name shown on the profile screens
+    pub fn label(self) -> &'static str {
+        match self {
+            Handedness::Right => "RIGHT-HANDED",
+            Handedness::Left => "LEFT-HANDED",
+        }
+    }
+}
+
+/// Keys that can be bound, with the names used in the config file
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Return, "Return"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Back, "Backspace"),
+    (KeyCode::LShift, "LShift"),
+    (KeyCode::RShift, "RShift"),
+    (KeyCode::LControl, "LControl"),
+    (KeyCode::RControl, "RControl"),
+    (KeyCode::LAlt, "LAlt"),
+    (KeyCode::RAlt, "RAlt"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Semicolon, "Semicolon"),
+    (KeyCode::Minus, "Minus"),
+    (KeyCode::Equals, "Equals"),
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::Numpad0, "Numpad0"),
+    (KeyCode::Numpad1, "Numpad1"),
+    (KeyCode::Numpad2, "Numpad2"),
+    (KeyCode::Numpad3, "Numpad3"),
+    (KeyCode::Numpad4, "Numpad4"),
+    (KeyCode::Numpad5, "Numpad5"),
+    (KeyCode::Numpad6, "Numpad6"),
+    (KeyCode::Numpad7, "Numpad7"),
+    (KeyCode::Numpad8, "Numpad8"),
+    (KeyCode::Numpad9, "Numpad9"),
+];
+
+/// Name of a key as written in the config file, or `None` if it can't be bound
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(code, _)| *code == key).map(|(_, name)| *name)
+}
+
+/// Parses a key name from the config file (case-insensitive)
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, known)| known.eq_ignore_ascii_case(name.trim()))
+        .map(|(code, _)| *code)
+}
+
+/// Maps logical actions to the keys that trigger them
+/// Stored in the config file as a table mapping each action to a list of key names
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<Action, Vec<String>>", into = "BTreeMap<Action, Vec<String>>")]
+pub struct KeyBindings {
+    bindings: BTreeMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .iter()
+                .map(|&action| (action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+/// Actions missing from the file keep their default keys
+/// Unknown key names are ignored, and an action with only unknown keys keeps its defaults
+/// An empty list leaves the action unbound
+impl From<BTreeMap<Action, Vec<String>>> for KeyBindings {
+    fn from(names: BTreeMap<Action, Vec<String>>) -> Self {
+        let mut bindings = Self::default();
+        for (action, names) in names {
+            let keys: Vec<KeyCode> = names.iter().filter_map(|name| key_from_name(name)).collect();
+            if names.is_empty() || !keys.is_empty() {
+                bindings.bindings.insert(action, keys);
+            }
+        }
+        bindings
+    }
+}
+
+impl From<KeyBindings> for BTreeMap<Action, Vec<String>> {
+    fn from(bindings: KeyBindings) -> Self {
+        bindings
+            .bindings
+            .into_iter()
+            .map(|(action, keys)| {
+                (action, keys.iter().filter_map(|&key| key_name(key)).map(String::from).collect())
+            })
+            .collect()
+    }
+}
+
+impl KeyBindings {
+    /// The default keys, with the piece controls moved to suit the hand
+    pub fn for_hand(hand: Handedness) -> Self {
+        let mut bindings = Self::default();
+        if hand == Handedness::Left {
+            bindings.bindings.insert(Action::MoveLeft, vec![KeyCode::A]);
+            bindings.bindings.insert(Action::MoveRight, vec![KeyCode::D]);
+            bindings.bindings.insert(Action::SoftDrop, vec![KeyCode::S]);
+            bindings.bindings.insert(Action::RotateCw, vec![KeyCode::W]);
+            bindings.bindings.insert(Action::Hold, vec![KeyCode::Comma, KeyCode::RShift]);
+            bindings.bindings.insert(Action::PreviewRotations, vec![KeyCode::RControl]);
+        }
+        bindings
+    }
+
+    /// Returns the action bound to a key, if any
+    pub fn action_for(&self, key: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(&action, _)| action)
+    }
+
+    /// Keys bound to an action
+    pub fn keys_for(&self, action: Action) -> &[KeyCode] {
+        self.bindings.get(&action).map_or(&[], |keys| keys.as_slice())
+    }
+
+    /// Binds a key to an action, replacing the action's previous keys
+    /// The key is removed from any other action so one key never triggers two actions
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        for keys in self.bindings.values_mut() {
+            keys.retain(|&bound| bound != key);
+        }
+        self.bindings.insert(action, vec![key]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_bindings() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.action_for(KeyCode::Left), Some(Action::MoveLeft));
+        assert_eq!(bindings.action_for(KeyCode::Space), Some(Action::HardDrop));
+        assert_eq!(bindings.action_for(KeyCode::Q), None);
+        for action in Action::ALL {
+            assert!(!bindings.keys_for(action).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_audio_actions() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.action_for(KeyCode::Minus), Some(Action::VolumeDown));
+        assert_eq!(bindings.action_for(KeyCode::Equals), Some(Action::VolumeUp));
+        let audio: Vec<Action> = Action::ALL.into_iter().filter(|action| action.is_audio()).collect();
+        assert_eq!(
+            audio,
+            [Action::ToggleMusic, Action::VolumeDown, Action::VolumeUp, Action::Mute, Action::NextTrack]
+        );
+    }
+
+    #[test]
+    fn test_bind_moves_key_between_actions() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Action::HardDrop, KeyCode::Up);
+        assert_eq!(bindings.action_for(KeyCode::Up), Some(Action::HardDrop));
+        assert_eq!(bindings.action_for(KeyCode::Space), None);
+        assert!(bindings.keys_for(Action::RotateCw).is_empty());
+
+        // The unbound action stays unbound after saving and loading
+        let parsed: KeyBindings = toml::from_str(&toml::to_string(&bindings).unwrap()).unwrap();
+        assert_eq!(parsed.action_for(KeyCode::Up), Some(Action::HardDrop));
+    }
+
+    #[test]
+    fn test_left_handed_bindings() {
+        let bindings = KeyBindings::for_hand(Handedness::Left);
+        assert_eq!(bindings.action_for(KeyCode::A), Some(Action::MoveLeft));
+        assert_eq!(bindings.action_for(KeyCode::W), Some(Action::RotateCw));
+        assert_eq!(bindings.action_for(KeyCode::Left), None);
+        assert_eq!(bindings.action_for(KeyCode::Space), Some(Action::HardDrop));
+
+        // Every key still triggers at most one action
+        let mut keys: Vec<KeyCode> = Action::ALL.iter().flat_map(|&action| bindings.keys_for(action).to_vec()).collect();
+        let count = keys.len();
+        keys.sort_by_key(|&key| key_name(key));
+        keys.dedup();
+        assert_eq!(keys.len(), count);
+        assert_eq!(KeyBindings::for_hand(Handedness::Right), KeyBindings::default());
+    }
+
+    #[test]
+    fn test_toml_roundtrip_and_fallback() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Action::RotateCw, KeyCode::X);
+        let parsed: KeyBindings = toml::from_str(&toml::to_string(&bindings).unwrap()).unwrap();
+        assert_eq!(parsed, bindings);
+
+        // Missing actions and unknown keys fall back to the defaults
+        let partial: KeyBindings = toml::from_str("move_left = [\"a\"]\npause = [\"NoSuchKey\"]\n").unwrap();
+        assert_eq!(partial.keys_for(Action::MoveLeft), &[KeyCode::A]);
+        assert_eq!(partial.keys_for(Action::Pause), &[KeyCode::P]);
+        assert_eq!(partial.keys_for(Action::HardDrop), &[KeyCode::Space]);
+
+        assert!(toml::from_str::<KeyBindings>("move_left = 3").is_err());
+    }
+}
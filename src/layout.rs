@@ -0,0 +1,124 @@
+use crate::constants::{
+    BORDER_WIDTH, GARBAGE_METER_WIDTH, GRID_HEIGHT, GRID_SIZE, GRID_WIDTH, HOLD_BOX_SIZE, MARGIN,
+    OPPONENT_CELL_SIZE, PANEL_SPACING, PREVIEW_BOX_SIZE, SCREEN_HEIGHT,
+};
+use crate::camera::Camera;
+use ggez::graphics::Rect;
+
+/// Where each part of the game screen is drawn, in logical pixels
+/// Widgets are placed relative to each other here, so adding one means adding a rect instead of new offsets in the draw code
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Layout {
+    pub hold: Rect,           // Hold box, left of the top of the board
+    pub board: Rect,          // Playfield
+    pub garbage_meter: Rect,  // Incoming garbage meter, right of the board
+    pub queue: Rect,          // Next pieces, at the top of the right-hand column
+    pub score: Rect,          // Score, level, lines and the mode's goal
+    pub stats: Rect,          // Statistics, filling the rest of the right-hand column
+    pub opponent: Rect,       // Opponent's mini board in versus games, at the left of the statistics
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Layout {
+    /// Height reserved at the top of a panel for its label
+    pub const LABEL_HEIGHT: f32 = 48.0;
+    /// Height of the label above the opponent's board
+    pub const OPPONENT_LABEL_HEIGHT: f32 = 24.0;
+
+    pub fn new() -> Self {
+        let hold = Rect::new(MARGIN, MARGIN, GRID_SIZE * HOLD_BOX_SIZE, GRID_SIZE * 4.0);
+        let board = Rect::new(
+            hold.right() + GRID_SIZE,
+            MARGIN,
+            GRID_SIZE * GRID_WIDTH as f32,
+            GRID_SIZE * GRID_HEIGHT as f32,
+        );
+        let garbage_meter = Rect::new(board.right() + BORDER_WIDTH + 12.0, board.y, GARBAGE_METER_WIDTH, board.h);
+
+        // The right-hand column stacks the queue, score and statistics panels
+        let column_x = board.right() + GRID_SIZE;
+        let column_w = GRID_SIZE * PREVIEW_BOX_SIZE;
+        let queue = Rect::new(column_x, MARGIN, column_w, GRID_SIZE * 8.0);
+        let score = Rect::new(column_x, queue.bottom() + PANEL_SPACING, column_w, GRID_SIZE * 5.0);
+        let stats_y = score.bottom() + PANEL_SPACING;
+        let stats = Rect::new(column_x, stats_y, column_w, SCREEN_HEIGHT - MARGIN - stats_y);
+        let opponent = Rect::new(
+            column_x,
+            stats.y + Self::OPPONENT_LABEL_HEIGHT,
+            OPPONENT_CELL_SIZE * GRID_WIDTH as f32,
+            OPPONENT_CELL_SIZE * GRID_HEIGHT as f32,
+        );
+
+        Self {
+            hold,
+            board,
+            garbage_meter,
+            queue,
+            score,
+            stats,
+            opponent,
+        }
+    }
+
+    /// Camera showing the player's board at full size
+    pub fn board_camera(&self) -> Camera {
+        Camera::new([self.board.x, self.board.y], GRID_SIZE)
+    }
+
+    /// Camera showing the opponent's mini board
+    pub fn opponent_camera(&self) -> Camera {
+        Camera::fit(self.opponent)
+    }
+
+    /// Part of the statistics panel not covered by the opponent's board
+    pub fn stats_beside_opponent(&self) -> Rect {
+        let left = self.opponent.right() + PANEL_SPACING;
+        Rect::new(left, self.stats.y, self.stats.right() - left, self.stats.h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::SCREEN_WIDTH;
+
+    #[test]
+    fn test_widgets_do_not_overlap() {
+        let layout = Layout::new();
+        let widgets = [
+            layout.hold,
+            layout.board,
+            layout.garbage_meter,
+            layout.queue,
+            layout.score,
+            layout.stats,
+        ];
+        for (i, a) in widgets.iter().enumerate() {
+            for b in &widgets[i + 1..] {
+                assert!(!a.overlaps(b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+
+        // The opponent's board sits inside the statistics panel, beside the narrowed statistics
+        assert!(layout.opponent.bottom() <= layout.stats.bottom());
+        assert!(!layout.opponent.overlaps(&layout.stats_beside_opponent()));
+    }
+
+    #[test]
+    fn test_widgets_fit_on_screen() {
+        let layout = Layout::new();
+        let screen = Rect::new(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT);
+        for widget in [layout.hold, layout.board, layout.queue, layout.score, layout.stats, layout.opponent] {
+            assert!(screen.contains(widget.point()));
+            assert!(widget.right() <= SCREEN_WIDTH - MARGIN);
+            assert!(widget.bottom() <= SCREEN_HEIGHT - MARGIN);
+        }
+        assert_eq!(layout.board_camera().bounds(), layout.board);
+        assert_eq!(layout.opponent_camera().bounds(), layout.opponent);
+    }
+}
@@ -0,0 +1,322 @@
+use crate::board::GameBoard;
+use crate::tetromino::Tetromino;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// Entries fetched for the global table
+pub const GLOBAL_TOP: usize = 10;
+
+/// A finished game as sent to the leaderboard server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Submission {
+    pub name: String,
+    pub mode: String,
+    pub score: u32,
+    pub replay_hash: String,  // `ReplayHash` of the game, so the server can tell games apart and spot tampering
+}
+
+/// One line of the global table as the server sends it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub score: u32,
+    #[serde(default)]
+    pub mode: String,
+}
+
+/// Fingerprint of a game: 64-bit FNV-1a over the seed and the cells of every piece locked, in order
+/// Two games hash the same only if they dealt the same pieces and placed them the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayHash(u64);
+
+impl ReplayHash {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub fn new(seed: u64) -> Self {
+        let mut hash = Self(Self::OFFSET);
+        hash.write(&seed.to_le_bytes());
+        hash
+    }
+
+    /// Adds a locked piece
+    pub fn add_lock(&mut self, piece: &Tetromino) {
+        for (x, y) in GameBoard::piece_cells(piece) {
+            self.write(&x.to_le_bytes());
+            self.write(&y.to_le_bytes());
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// The hash as 16 hex digits
+    pub fn hex(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+/// Where the leaderboard server is: a plain `http://host[:port]/path` URL
+/// Scores are posted as JSON to `<path>/scores`, and `<path>/scores?limit=10` returns the best as a JSON array
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub host: String,
+    pub port: u16,
+    pub path: String,  // Path prefix without a trailing slash, empty for the server root
+}
+
+impl Endpoint {
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let rest = url
+            .trim()
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("{:?} is not an http:// URL", url))?;
+        let (authority, path) = rest.split_once('/').map_or((rest, ""), |(authority, path)| (authority, path));
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| format!("bad port in {:?}", url))?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(format!("no host in {:?}", url));
+        }
+        let path = path.trim_end_matches('/');
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: if path.is_empty() { String::new() } else { format!("/{}", path) },
+        })
+    }
+}
+
+/// Whether the server could be reached
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Waiting,          // A request is on its way
+    Online,           // The last request worked
+    Offline(String),  // The last request failed, and why
+}
+
+/// What a request thread reports back
+enum Reply {
+    Submitted,
+    SubmitFailed(Submission, String),  // The submission is kept and sent again with the next refresh
+    Top(Vec<RemoteEntry>),
+    FetchFailed(String),
+}
+
+/// Talks to the leaderboard server on background threads, so a slow or missing server never stalls the game
+/// Scores that can't be sent are held and retried whenever the table is refreshed
+pub struct Leaderboard {
+    endpoint: Result<Endpoint, String>,  // The server, or why its URL is unusable
+    sender: Sender<Reply>,               // Handed to each request thread
+    replies: Receiver<Reply>,            // Results of finished requests
+    pending: Vec<Submission>,            // Scores that failed to send
+    top: Vec<RemoteEntry>,               // The global table as last fetched
+    status: Status,                      // How the last request went
+}
+
+impl Leaderboard {
+    /// A client for the server at `url`, or `None` when no URL is configured
+    pub fn new(url: &str) -> Option<Self> {
+        if url.trim().is_empty() {
+            return None;
+        }
+        let endpoint = Endpoint::parse(url);
+        let status = match &endpoint {
+            Ok(_) => Status::Online,
+            Err(e) => Status::Offline(e.clone()),
+        };
+        let (sender, replies) = mpsc::channel();
+        Some(Self { endpoint, sender, replies, pending: Vec::new(), top: Vec::new(), status })
+    }
+
+    /// The global table as last fetched
+    pub fn top(&self) -> &[RemoteEntry] {
+        &self.top
+    }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    /// Sends a score in the background
+    pub fn submit(&mut self, submission: Submission) {
+        let Ok(endpoint) = self.endpoint.clone() else {
+            return;
+        };
+        self.status = Status::Waiting;
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let reply = match post_score(&endpoint, &submission) {
+                Ok(()) => Reply::Submitted,
+                Err(e) => Reply::SubmitFailed(submission, e.to_string()),
+            };
+            let _ = sender.send(reply);
+        });
+    }
+
+    /// Fetches the global table in the background, first resending any scores that failed before
+    pub fn refresh(&mut self) {
+        let Ok(endpoint) = self.endpoint.clone() else {
+            return;
+        };
+        for submission in std::mem::take(&mut self.pending) {
+            self.submit(submission);
+        }
+        self.status = Status::Waiting;
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let reply = match fetch_top(&endpoint) {
+                Ok(top) => Reply::Top(top),
+                Err(e) => Reply::FetchFailed(e.to_string()),
+            };
+            let _ = sender.send(reply);
+        });
+    }
+
+    /// Takes in the results of finished requests; call once a frame
+    pub fn poll(&mut self) {
+        while let Ok(reply) = self.replies.try_recv() {
+            match reply {
+                // Fetch the table again so the new score shows up in it
+                Reply::Submitted => self.refresh(),
+                Reply::SubmitFailed(submission, e) => {
+                    self.pending.push(submission);
+                    self.status = Status::Offline(e);
+                }
+                Reply::Top(mut top) => {
+                    top.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+                    top.truncate(GLOBAL_TOP);
+                    self.top = top;
+                    self.status = Status::Online;
+                }
+                Reply::FetchFailed(e) => self.status = Status::Offline(e),
+            }
+        }
+    }
+}
+
+fn post_score(endpoint: &Endpoint, submission: &Submission) -> io::Result<()> {
+    let body = serde_json::to_string(submission)?;
+    request(endpoint, "POST", &format!("{}/scores", endpoint.path), Some(&body)).map(|_| ())
+}
+
+fn fetch_top(endpoint: &Endpoint) -> io::Result<Vec<RemoteEntry>> {
+    let body = request(endpoint, "GET", &format!("{}/scores?limit={}", endpoint.path, GLOBAL_TOP), None)?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Sends one HTTP/1.0 request and returns the body of a successful response
+/// HTTP/1.0 keeps the response unchunked and closes the connection once it is sent
+#[cfg(feature = "leaderboard")]
+fn request(endpoint: &Endpoint, method: &str, path: &str, body: Option<&str>) -> io::Result<String> {
+    use std::io::{Read, Write};
+    use std::net::{TcpStream, ToSocketAddrs};
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    let address = (endpoint.host.as_str(), endpoint.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the server's address didn't resolve"))?;
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let body = body.unwrap_or("");
+    let head = format!(
+        "{} {} HTTP/1.0\r\nHost: {}\r\nAccept: application/json\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+        method,
+        if path.is_empty() { "/" } else { path },
+        endpoint.host,
+        body.len()
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body.as_bytes())?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    response_body(&response)
+}
+
+#[cfg(not(feature = "leaderboard"))]
+fn request(_endpoint: &Endpoint, _method: &str, _path: &str, _body: Option<&str>) -> io::Result<String> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "built without the leaderboard feature"))
+}
+
+/// Body of an HTTP response, or an error naming the status if it wasn't a success
+pub fn response_body(response: &str) -> io::Result<String> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let (head, body) = response.split_once("\r\n\r\n").ok_or_else(|| invalid("the response has no body"))?;
+    let status_line = head.lines().next().unwrap_or_default();
+    let code: u16 = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| invalid("the response has no status"))?;
+    if (200..300).contains(&code) {
+        Ok(body.to_string())
+    } else {
+        Err(invalid(&format!("the server answered {}", status_line.trim())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tetromino::TetrominoType;
+
+    #[test]
+    fn test_endpoint_parsing() {
+        let endpoint = Endpoint::parse("http://scores.example.org:8080/tetris/").unwrap();
+        assert_eq!(endpoint, Endpoint { host: "scores.example.org".to_string(), port: 8080, path: "/tetris".to_string() });
+        let endpoint = Endpoint::parse("http://localhost").unwrap();
+        assert_eq!((endpoint.port, endpoint.path.as_str()), (80, ""));
+        assert!(Endpoint::parse("https://scores.example.org").is_err());
+        assert!(Endpoint::parse("http://:80/").is_err());
+        assert!(Endpoint::parse("http://host:port").is_err());
+    }
+
+    #[test]
+    fn test_response_body() {
+        let ok = "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n[{\"name\":\"Alex\",\"score\":5}]";
+        let top: Vec<RemoteEntry> = serde_json::from_str(&response_body(ok).unwrap()).unwrap();
+        assert_eq!(top, [RemoteEntry { name: "Alex".to_string(), score: 5, mode: String::new() }]);
+        let error = response_body("HTTP/1.1 503 Service Unavailable\r\n\r\n").unwrap_err();
+        assert!(error.to_string().contains("503"));
+        assert!(response_body("garbage").is_err());
+    }
+
+    #[test]
+    fn test_replay_hash_follows_placements() {
+        let mut piece = Tetromino::new(TetrominoType::T);
+        let mut a = ReplayHash::new(7);
+        let mut b = ReplayHash::new(7);
+        a.add_lock(&piece);
+        b.add_lock(&piece);
+        assert_eq!(a, b);
+        assert_eq!(a.hex().len(), 16);
+
+        piece.position.x += 1.0;
+        b.add_lock(&piece);
+        a.add_lock(&Tetromino::new(TetrominoType::T));
+        assert_ne!(a, b);
+        assert_ne!(ReplayHash::new(7), ReplayHash::new(8));
+    }
+
+    #[test]
+    fn test_unconfigured_or_unreachable_server() {
+        assert!(Leaderboard::new("  ").is_none());
+        let mut leaderboard = Leaderboard::new("ftp://nowhere").unwrap();
+        assert!(matches!(leaderboard.status(), Status::Offline(_)));
+        leaderboard.refresh();
+        leaderboard.poll();
+        assert!(leaderboard.top().is_empty());
+    }
+}
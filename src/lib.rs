@@ -2,15 +2,69 @@ pub mod tetromino;
 pub mod sound_tests;
 pub mod test_event;
 pub mod constants;
+pub mod ai;
+pub mod animation;
+pub mod assets;
+pub mod autosave;
+pub mod background;
+pub mod bitboard;
+pub mod board;
+pub mod camera;
+pub mod chart;
+pub mod chat;
+pub mod clip;
+pub mod console;
+pub mod finesse;
+pub mod frame_limiter;
+pub mod stepper;
+pub mod error;
+pub mod events;
+pub mod game;
+pub mod grid;
+pub mod high_scores;
+pub mod history;
+pub mod mode;
+pub mod opener;
+pub mod overlay;
+pub mod music;
+pub mod particles;
+pub mod rotation;
+pub mod scoring;
+pub mod scripting;
+pub mod sfx;
+pub mod simulate;
+pub mod skin;
+pub mod pattern;
+pub mod practice;
+pub mod profiles;
+pub mod replay;
+pub mod stats;
+pub mod synth;
+pub mod keybindings;
+pub mod layout;
+pub mod leaderboard;
+pub mod lobby;
+pub mod net;
+pub mod config;
+pub mod dataset;
+pub mod editor;
+pub mod text;
+pub mod theme;
+pub mod touch;
+pub mod transfer;
+pub mod tutorial;
+pub mod tuning;
+pub mod ui;
+pub mod viewport;
+#[cfg(debug_assertions)]
+pub mod watch;
+pub mod garbage;
+pub mod generator;
+pub mod ghost;
+pub mod versus;
 
 // Export main types from tetromino module
 pub use crate::tetromino::{Tetromino, TetrominoType};
 
 // Export TestState for tests
 pub use crate::test_event::TestState;
-
-// Re-export functionality from main.rs for testing
-mod tests_reexport;
-
-// Re-export functionality for integration tests
-pub use tests_reexport::{keycode_to_char, GameState, GameScreen, HighScores, HighScoreEntry}; 
\ No newline at end of file
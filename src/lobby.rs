@@ -0,0 +1,359 @@
+use crate::net::{connect_stream, read_lines, write_line};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// Default TCP port of the lobby server
+pub const LOBBY_PORT: u16 = 7879;
+
+/// Seconds between asking the lobby for its rooms while the room browser is open
+pub const REFRESH_INTERVAL: f64 = 2.0;
+
+/// How often the lobby server looks for new connections and messages
+const SERVER_POLL: Duration = Duration::from_millis(10);
+
+/// How long to wait for a host's echo before pinging it again
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// An open room, waiting for someone to join its host for a versus match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Room {
+    pub name: String,     // Name shown in the room browser
+    pub host: String,     // Name of the player waiting in the room
+    pub mode: String,     // Rules of the match, as the host describes them
+    pub address: String,  // Where the host's game listens, filled in by the lobby from the host's connection
+}
+
+/// Messages between a game and the lobby server, each sent as one line of JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LobbyMessage {
+    /// Asks for the rooms open right now
+    List,
+    /// Opens a room for the sender, whose versus host listens on `port`, replacing any room it had open
+    Open { name: String, host: String, mode: String, port: u16 },
+    /// Closes the sender's room, as when someone joined it or the host gave up
+    Close,
+    /// The rooms open right now, in the order they were opened
+    Rooms { rooms: Vec<Room> },
+    /// A chat message, passed on by the lobby to every other game connected to it
+    Chat { from: String, text: String },
+}
+
+/// A game connected to the lobby server
+struct Visitor {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    address: SocketAddr,  // Where the game connected from; its room is reached at this IP
+    room: Option<Room>,   // The room it has open
+    opened: u64,          // When the room was opened, counted in rooms, to list rooms oldest first
+}
+
+/// Keeps the list of open rooms for every game connected to it
+/// A room closes when its host says so or disconnects, so the list never offers a host that's gone
+pub struct LobbyServer {
+    listener: TcpListener,
+    visitors: Vec<Visitor>,
+    opened: u64,  // Rooms opened so far
+}
+
+impl LobbyServer {
+    /// Starts listening on all interfaces at the given port
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self { listener, visitors: Vec::new(), opened: 0 })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// The rooms open right now, oldest first
+    pub fn rooms(&self) -> Vec<Room> {
+        let mut open: Vec<&Visitor> = self.visitors.iter().filter(|visitor| visitor.room.is_some()).collect();
+        open.sort_by_key(|visitor| visitor.opened);
+        open.iter().filter_map(|visitor| visitor.room.clone()).collect()
+    }
+
+    /// Takes in new games and answers their messages without blocking, dropping games that left
+    pub fn update(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, address)) => {
+                    if stream.set_nonblocking(true).is_ok() {
+                        log::debug!(target: "net", "Game at {} joined the lobby", address);
+                        self.visitors.push(Visitor { stream, buffer: Vec::new(), address, room: None, opened: 0 });
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    log::warn!(target: "net", "Lobby could not accept a connection: {}", e);
+                    break;
+                }
+            }
+        }
+
+        let mut index = 0;
+        while index < self.visitors.len() {
+            match self.answer(index) {
+                Ok(()) => index += 1,
+                Err(e) => {
+                    log::debug!(target: "net", "Game at {} left the lobby: {}", self.visitors[index].address, e);
+                    self.visitors.swap_remove(index);
+                }
+            }
+        }
+    }
+
+    /// Answers the messages of one game
+    fn answer(&mut self, index: usize) -> io::Result<()> {
+        let visitor = &mut self.visitors[index];
+        let messages: Vec<LobbyMessage> = read_lines(&mut visitor.stream, &mut visitor.buffer)?;
+        for message in messages {
+            match message {
+                LobbyMessage::List => {
+                    let rooms = self.rooms();
+                    write_line(&mut self.visitors[index].stream, &LobbyMessage::Rooms { rooms })?;
+                }
+                LobbyMessage::Open { name, host, mode, port } => {
+                    self.opened += 1;
+                    let visitor = &mut self.visitors[index];
+                    let address = SocketAddr::new(visitor.address.ip(), port).to_string();
+                    visitor.room = Some(Room { name, host, mode, address });
+                    visitor.opened = self.opened;
+                }
+                LobbyMessage::Close => self.visitors[index].room = None,
+                LobbyMessage::Chat { from, text } => {
+                    let chat = LobbyMessage::Chat { from, text };
+                    for (other, visitor) in self.visitors.iter_mut().enumerate() {
+                        // A game that can't take the message is dropped with its next read
+                        if other != index {
+                            let _ = write_line(&mut visitor.stream, &chat);
+                        }
+                    }
+                }
+                // Only the lobby sends rooms
+                LobbyMessage::Rooms { .. } => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs a lobby server on the given port until the process is stopped
+pub fn serve(port: u16) -> io::Result<()> {
+    let mut server = LobbyServer::bind(port)?;
+    log::info!(target: "net", "Lobby listening on {}", server.local_addr()?);
+    loop {
+        server.update();
+        std::thread::sleep(SERVER_POLL);
+    }
+}
+
+/// Times round trips to room hosts: each is sent a datagram naming its address, which its `NetHost` echoes back
+struct Pinger {
+    socket: Option<UdpSocket>,        // Socket the pings go out on; without one rooms show no ping
+    sent: HashMap<String, Instant>,   // When the ping still on its way to each host address went out
+    pings: HashMap<String, Duration>, // Last round trip to each host address
+}
+
+impl Pinger {
+    fn new() -> Self {
+        let socket = UdpSocket::bind(("0.0.0.0", 0)).and_then(|socket| socket.set_nonblocking(true).map(|_| socket));
+        Self { socket: socket.ok(), sent: HashMap::new(), pings: HashMap::new() }
+    }
+
+    /// Pings a host, unless a ping to it is still on its way
+    fn send(&mut self, address: &str) {
+        let Some(socket) = &self.socket else {
+            return;
+        };
+        if self.sent.get(address).is_some_and(|sent| sent.elapsed() < PING_TIMEOUT) {
+            return;
+        }
+        if socket.send_to(address.as_bytes(), address).is_ok() {
+            self.sent.insert(address.to_string(), Instant::now());
+        }
+    }
+
+    /// Takes in the echoes that have come back
+    fn poll(&mut self) {
+        let Some(socket) = &self.socket else {
+            return;
+        };
+        let mut echo = [0u8; 256];
+        while let Ok((length, _)) = socket.recv_from(&mut echo) {
+            let Ok(address) = std::str::from_utf8(&echo[..length]) else {
+                continue;
+            };
+            if let Some(sent) = self.sent.remove(address) {
+                self.pings.insert(address.to_string(), sent.elapsed());
+            }
+        }
+    }
+
+    fn ping(&self, address: &str) -> Option<Duration> {
+        self.pings.get(address).copied()
+    }
+}
+
+/// The game's connection to the lobby server: the rooms it lists and the room this game has open
+pub struct LobbyClient {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    rooms: Vec<Room>,         // Rooms as last listed
+    open_room: Option<Room>,  // The room this game has open, with no address
+    chat: Vec<(String, String)>, // Chat messages received but not yet shown, as sender and text
+    pinger: Pinger,           // Times the trip to each listed room's host
+}
+
+impl LobbyClient {
+    /// Connects to a lobby server, e.g. "lobby.example.org" or "192.168.1.20:7879"
+    /// The default lobby port is used when the address doesn't specify one
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let stream = connect_stream(address, LOBBY_PORT)?;
+        stream.set_nodelay(true)?;
+        stream.set_nonblocking(true)?;
+        Ok(Self { stream, buffer: Vec::new(), rooms: Vec::new(), open_room: None, chat: Vec::new(), pinger: Pinger::new() })
+    }
+
+    /// Asks the lobby for its rooms; they arrive with a later `update`
+    pub fn refresh(&mut self) -> io::Result<()> {
+        write_line(&mut self.stream, &LobbyMessage::List)
+    }
+
+    /// Lists a room for this game's versus host, listening on `port`
+    pub fn open_room(&mut self, name: &str, host: &str, mode: &str, port: u16) -> io::Result<()> {
+        let open = LobbyMessage::Open { name: name.to_string(), host: host.to_string(), mode: mode.to_string(), port };
+        write_line(&mut self.stream, &open)?;
+        self.open_room = Some(Room { name: name.to_string(), host: host.to_string(), mode: mode.to_string(), address: String::new() });
+        Ok(())
+    }
+
+    /// Takes this game's room off the list
+    pub fn close_room(&mut self) -> io::Result<()> {
+        if self.open_room.take().is_none() {
+            return Ok(());
+        }
+        write_line(&mut self.stream, &LobbyMessage::Close)
+    }
+
+    /// Sends a chat message signed with our name to everyone else in the lobby
+    pub fn send_chat(&mut self, from: &str, text: &str) -> io::Result<()> {
+        write_line(&mut self.stream, &LobbyMessage::Chat { from: from.to_string(), text: text.to_string() })
+    }
+
+    /// Takes the chat messages received so far, as sender and text
+    pub fn take_chat(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.chat)
+    }
+
+    /// The room this game has open
+    pub fn open(&self) -> Option<&Room> {
+        self.open_room.as_ref()
+    }
+
+    /// Rooms as last listed by the lobby
+    pub fn rooms(&self) -> &[Room] {
+        &self.rooms
+    }
+
+    /// Round trip to a room's host, once an echo has come back
+    pub fn ping(&self, room: &Room) -> Option<Duration> {
+        self.pinger.ping(&room.address)
+    }
+
+    /// Takes in the lobby's answers and the hosts' echoes without blocking, pinging each newly listed room
+    /// Returns an error once the lobby has disconnected
+    pub fn update(&mut self) -> io::Result<()> {
+        let messages: Vec<LobbyMessage> = read_lines(&mut self.stream, &mut self.buffer)?;
+        for message in messages {
+            match message {
+                LobbyMessage::Rooms { rooms } => {
+                    self.rooms = rooms;
+                    for room in &self.rooms {
+                        self.pinger.send(&room.address);
+                    }
+                }
+                LobbyMessage::Chat { from, text } => self.chat.push((from, text)),
+                // Only games send these
+                LobbyMessage::List | LobbyMessage::Open { .. } | LobbyMessage::Close => {}
+            }
+        }
+        self.pinger.poll();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::NetHost;
+
+    /// Runs the server and updates the client until `done` or a second has passed
+    fn exchange(server: &mut LobbyServer, client: &mut LobbyClient, done: impl Fn(&LobbyClient) -> bool) {
+        let start = Instant::now();
+        while !done(client) && start.elapsed() < Duration::from_secs(1) {
+            server.update();
+            client.update().unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_rooms_open_and_close() {
+        let mut server = LobbyServer::bind(0).unwrap();
+        let address = format!("127.0.0.1:{}", server.local_addr().unwrap().port());
+        let mut host = LobbyClient::connect(&address).unwrap();
+        let mut guest = LobbyClient::connect(&address).unwrap();
+
+        host.open_room("ALEX'S ROOM", "ALEX", "FIRST TO 3", 7000).unwrap();
+        assert_eq!(host.open().unwrap().name, "ALEX'S ROOM");
+        exchange(&mut server, &mut host, |_| false);
+        guest.refresh().unwrap();
+        exchange(&mut server, &mut guest, |guest| !guest.rooms().is_empty());
+        let room = &guest.rooms()[0];
+        assert_eq!((room.host.as_str(), room.mode.as_str()), ("ALEX", "FIRST TO 3"));
+        assert_eq!(room.address, "127.0.0.1:7000", "the room is reached where its host connected from");
+
+        // Chat goes to everyone but the sender
+        guest.send_chat("SAM", "hi").unwrap();
+        exchange(&mut server, &mut host, |host| !host.chat.is_empty());
+        assert_eq!(host.take_chat(), [("SAM".to_string(), "hi".to_string())]);
+        assert!(guest.take_chat().is_empty());
+
+        // A room goes away when it's closed, and when its host leaves
+        host.close_room().unwrap();
+        assert!(host.open().is_none());
+        exchange(&mut server, &mut host, |_| false);
+        assert!(server.rooms().is_empty());
+        host.open_room("AGAIN", "ALEX", "FIRST TO 5", 7000).unwrap();
+        exchange(&mut server, &mut host, |_| false);
+        assert_eq!(server.rooms().len(), 1);
+        drop(host);
+        let start = Instant::now();
+        while !server.rooms().is_empty() && start.elapsed() < Duration::from_secs(1) {
+            server.update();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(server.rooms().is_empty());
+    }
+
+    #[test]
+    fn test_hosts_answer_pings() {
+        let host = NetHost::bind(0).unwrap();
+        let address = format!("127.0.0.1:{}", host.local_addr().unwrap().port());
+        let mut pinger = Pinger::new();
+        pinger.send(&address);
+        let start = Instant::now();
+        while pinger.ping(&address).is_none() && start.elapsed() < Duration::from_secs(1) {
+            host.accept().unwrap();
+            pinger.poll();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(pinger.ping(&address).is_some());
+        assert!(pinger.ping("127.0.0.1:1").is_none());
+    }
+}
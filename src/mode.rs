@@ -0,0 +1,432 @@
+use crate::constants::{
+    DIG_ROWS, GARBAGE_COLOR, HANDICAP_MAX_GARBAGE_ROWS, HANDICAP_MAX_LEVELS, HANDICAP_MAX_WALLS, SPRINT_LINES, ULTRA_TIME,
+};
+use crate::game::{Game, Tuning};
+use crate::scoring::{ScoringSystem, GUIDELINE, NES};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Rules of a single-player game: how it starts, how fast and how it's scored, and when it's won
+/// Every mode ends when a piece can't enter the board; a mode with a goal can also end by reaching it
+pub trait GameMode {
+    /// Name shown in menus
+    fn name(&self) -> &'static str;
+
+    /// Multipliers on gravity and scoring, set when a game starts
+    fn tuning(&self) -> Tuning {
+        Tuning::default()
+    }
+
+    /// Points for locks and drops, set when a game starts
+    fn scoring(&self) -> &'static dyn ScoringSystem {
+        &GUIDELINE
+    }
+
+    /// Prepares a new game, before its first piece can move
+    fn setup(&self, _game: &mut Game) {}
+
+    /// Whether the mode's goal has been reached after `elapsed` seconds of play
+    fn is_complete(&self, _game: &Game, _elapsed: f64) -> bool {
+        false
+    }
+
+    /// Label and value of the mode's goal for the score panel, if it has one
+    fn goal(&self, _game: &Game, _elapsed: f64) -> Option<(&'static str, String)> {
+        None
+    }
+
+    /// Result shown on the game over screen once the goal is reached
+    fn result(&self, _game: &Game, _elapsed: f64) -> String {
+        "GOAL REACHED!".to_string()
+    }
+
+    /// Whether games count toward the high scores, the leaderboard and the statistics
+    fn is_ranked(&self) -> bool {
+        true
+    }
+
+    /// Whether the mode is for practice, with its learning aids like the placement heatmap
+    fn is_practice(&self) -> bool {
+        false
+    }
+
+    /// Whether games can start at a later level, picked in the title menu
+    /// Off for timed and goal modes, whose records and ghosts would otherwise mix games from different levels
+    fn has_level_select(&self) -> bool {
+        false
+    }
+
+    /// Whether gravity can be frozen during games, from the pause menu
+    fn can_freeze_gravity(&self) -> bool {
+        false
+    }
+
+    /// Whether games race a ghost of the fastest one played before
+    fn has_ghost(&self) -> bool {
+        false
+    }
+}
+
+/// Play until topping out
+pub struct Marathon;
+
+impl GameMode for Marathon {
+    fn name(&self) -> &'static str {
+        "MARATHON"
+    }
+
+    fn has_level_select(&self) -> bool {
+        true
+    }
+}
+
+/// Play until topping out, scored like the NES game, where a piece pushed down onto the stack locks at once
+pub struct Classic;
+
+impl GameMode for Classic {
+    fn name(&self) -> &'static str {
+        "CLASSIC"
+    }
+
+    fn has_level_select(&self) -> bool {
+        true
+    }
+
+    fn scoring(&self) -> &'static dyn ScoringSystem {
+        &NES
+    }
+
+    fn setup(&self, game: &mut Game) {
+        game.set_lock_delay(0.0);
+    }
+}
+
+/// Clear SPRINT_LINES lines as fast as possible
+pub struct Sprint;
+
+impl GameMode for Sprint {
+    fn name(&self) -> &'static str {
+        "SPRINT"
+    }
+
+    fn is_complete(&self, game: &Game, _elapsed: f64) -> bool {
+        game.lines_cleared() >= SPRINT_LINES
+    }
+
+    fn goal(&self, game: &Game, _elapsed: f64) -> Option<(&'static str, String)> {
+        Some(("LEFT", SPRINT_LINES.saturating_sub(game.lines_cleared()).to_string()))
+    }
+
+    fn result(&self, _game: &Game, elapsed: f64) -> String {
+        format!("{} LINES IN {}", SPRINT_LINES, format_time(elapsed))
+    }
+
+    fn has_ghost(&self) -> bool {
+        true
+    }
+}
+
+/// Score as much as possible in ULTRA_TIME seconds
+pub struct Ultra;
+
+impl GameMode for Ultra {
+    fn name(&self) -> &'static str {
+        "ULTRA"
+    }
+
+    fn is_complete(&self, _game: &Game, elapsed: f64) -> bool {
+        elapsed >= ULTRA_TIME
+    }
+
+    fn goal(&self, _game: &Game, elapsed: f64) -> Option<(&'static str, String)> {
+        Some(("LEFT", format_time((ULTRA_TIME - elapsed).max(0.0))))
+    }
+
+    fn result(&self, _game: &Game, _elapsed: f64) -> String {
+        "TIME UP!".to_string()
+    }
+}
+
+/// Clear DIG_ROWS rows of garbage from the bottom of the board as fast as possible
+pub struct Dig;
+
+impl Dig {
+    /// Rows still holding garbage
+    fn rows_left(game: &Game) -> usize {
+        game.board().rows().iter().filter(|row| row.contains(&GARBAGE_COLOR)).count()
+    }
+}
+
+impl GameMode for Dig {
+    fn name(&self) -> &'static str {
+        "DIG"
+    }
+
+    fn setup(&self, game: &mut Game) {
+        // Holes come from the game's seed, so a seeded dig game always starts from the same board
+        let mut rng = StdRng::seed_from_u64(game.seed());
+        for _ in 0..DIG_ROWS {
+            let hole = rng.gen_range(game.board().open_columns());
+            game.board_mut().add_garbage(1, hole, GARBAGE_COLOR);
+        }
+    }
+
+    fn is_complete(&self, game: &Game, _elapsed: f64) -> bool {
+        Self::rows_left(game) == 0
+    }
+
+    fn goal(&self, game: &Game, _elapsed: f64) -> Option<(&'static str, String)> {
+        Some(("LEFT", Self::rows_left(game).to_string()))
+    }
+
+    fn result(&self, _game: &Game, elapsed: f64) -> String {
+        format!("DUG OUT IN {}", format_time(elapsed))
+    }
+}
+
+/// Unranked play for working on stacking, starting without gravity so pieces only lock when dropped
+pub struct Practice;
+
+impl GameMode for Practice {
+    fn name(&self) -> &'static str {
+        "PRACTICE"
+    }
+
+    fn setup(&self, game: &mut Game) {
+        game.set_gravity_frozen(true);
+    }
+
+    fn is_ranked(&self) -> bool {
+        false
+    }
+
+    fn is_practice(&self) -> bool {
+        true
+    }
+
+    fn can_freeze_gravity(&self) -> bool {
+        true
+    }
+}
+
+/// Harder rules a player takes on in versus games, to even out a gap in skill between two players
+/// Applied on top of the mode's rules when a game starts; the default is no handicap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Handicap {
+    pub garbage_rows: u32,  // Rows of garbage the board starts with
+    pub walls: u32,         // Columns walled off to narrow the board, split between the two sides
+    pub extra_levels: u32,  // Levels the game starts above the first, for faster gravity from the start
+    pub no_hold: bool,      // Whether hold is taken away
+}
+
+impl Handicap {
+    /// Whether the handicap changes nothing
+    pub fn is_none(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The handicap with every option within its allowed range
+    pub fn clamped(self) -> Self {
+        Self {
+            garbage_rows: self.garbage_rows.min(HANDICAP_MAX_GARBAGE_ROWS),
+            walls: self.walls.min(HANDICAP_MAX_WALLS),
+            extra_levels: self.extra_levels.min(HANDICAP_MAX_LEVELS),
+            no_hold: self.no_hold,
+        }
+    }
+
+    /// Sets up a new game with the handicap, before the mode's own setup
+    pub fn apply(&self, game: &mut Game) {
+        let handicap = self.clamped();
+        let right = handicap.walls as usize / 2;
+        game.board_mut().set_walls(handicap.walls as usize - right, right);
+        game.set_start_level(1 + handicap.extra_levels);
+        game.set_hold_enabled(!handicap.no_hold);
+
+        // Holes come from the game's seed, offset so they don't repeat a dig game's
+        let mut rng = StdRng::seed_from_u64(game.seed().wrapping_add(1));
+        for _ in 0..handicap.garbage_rows {
+            let hole = rng.gen_range(game.board().open_columns());
+            game.board_mut().add_garbage(1, hole, GARBAGE_COLOR);
+        }
+    }
+}
+
+/// The single-player modes offered on the title screen, in menu order
+/// Adding a mode means writing a `GameMode` and registering it here; nothing else needs to know about it
+pub struct ModeRegistry {
+    modes: Vec<Box<dyn GameMode>>,  // Registered modes; the first is the default, also used for versus games
+}
+
+impl Default for ModeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModeRegistry {
+    /// A registry with the built-in modes
+    pub fn new() -> Self {
+        let mut registry = Self { modes: Vec::new() };
+        registry.register(Box::new(Marathon));
+        registry.register(Box::new(Sprint));
+        registry.register(Box::new(Ultra));
+        registry.register(Box::new(Dig));
+        registry.register(Box::new(Classic));
+        registry.register(Box::new(Practice));
+        registry
+    }
+
+    /// Adds a mode after the ones already registered
+    pub fn register(&mut self, mode: Box<dyn GameMode>) {
+        self.modes.push(mode);
+    }
+
+    /// The mode at a menu position; out of range positions give the default mode
+    pub fn get(&self, index: usize) -> &dyn GameMode {
+        self.modes.get(index).unwrap_or(&self.modes[0]).as_ref()
+    }
+
+    /// Menu position of the mode with the given name
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.modes.iter().position(|mode| mode.name() == name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.modes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modes.is_empty()
+    }
+
+    /// Names of the modes, in menu order
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.modes.iter().map(|mode| mode.name())
+    }
+}
+
+/// Formats seconds as minutes, seconds and hundredths, e.g. `1:05.20`
+pub fn format_time(seconds: f64) -> String {
+    let hundredths = (seconds * 100.0).floor() as u64;
+    format!("{}:{:02}.{:02}", hundredths / 6000, hundredths / 100 % 60, hundredths % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{GRID_HEIGHT, GRID_WIDTH, WALL_COLOR};
+    use crate::game::Move;
+    use crate::tetromino::{Tetromino, TetrominoType};
+
+    /// A game that has cleared the given number of lines, one at a time
+    fn game_with_lines(lines: u32) -> Game {
+        let mut game = Game::with_seed(1);
+        for _ in 0..lines {
+            for x in 1..GRID_WIDTH as usize {
+                game.board_mut()[GRID_HEIGHT as usize - 1][x] = GARBAGE_COLOR;
+            }
+            let mut piece = Tetromino::new(TetrominoType::I);
+            piece.rotate();
+            piece.position.x = 0.0;
+            game.set_current_piece(piece);
+            game.apply(Move::HardDrop);
+        }
+        assert_eq!(game.lines_cleared(), lines);
+        game
+    }
+
+    #[test]
+    fn test_goals() {
+        assert!(!Marathon.is_complete(&game_with_lines(0), 1000.0));
+        assert!(!Sprint.is_complete(&game_with_lines(SPRINT_LINES - 1), 0.0));
+        assert!(Sprint.is_complete(&game_with_lines(SPRINT_LINES), 0.0));
+        assert!(Ultra.is_complete(&game_with_lines(0), ULTRA_TIME));
+
+        assert_eq!(Sprint.goal(&game_with_lines(SPRINT_LINES + 3), 0.0), Some(("LEFT", "0".to_string())));
+        assert_eq!(Marathon.goal(&game_with_lines(0), 0.0), None);
+        assert_eq!(Ultra.goal(&game_with_lines(0), ULTRA_TIME - 30.5), Some(("LEFT", "0:30.50".to_string())));
+    }
+
+    #[test]
+    fn test_dig_starts_with_garbage_and_ends_when_it_is_gone() {
+        let mut game = Game::with_seed(1);
+        Dig.setup(&mut game);
+        assert_eq!(Dig.goal(&game, 0.0), Some(("LEFT", DIG_ROWS.to_string())));
+        assert!(!Dig.is_complete(&game, 0.0));
+
+        // Each garbage row has exactly one hole
+        let bottom = &game.board()[GRID_HEIGHT as usize - 1];
+        assert_eq!(bottom.iter().filter(|&&cell| cell == GARBAGE_COLOR).count(), GRID_WIDTH as usize - 1);
+
+        *game.board_mut() = crate::board::GameBoard::new();
+        assert!(Dig.is_complete(&game, 0.0));
+    }
+
+    #[test]
+    fn test_handicaps() {
+        let mut game = Game::with_seed(1);
+        Handicap::default().apply(&mut game);
+        assert_eq!(game.board(), &crate::board::GameBoard::new());
+        assert!(Handicap::default().is_none());
+
+        let handicap = Handicap { garbage_rows: 3, walls: 3, extra_levels: 4, no_hold: true };
+        let mut game = Game::with_seed(1);
+        handicap.apply(&mut game);
+        assert_eq!(game.level(), 5);
+        assert_eq!(game.board().open_columns(), 2..GRID_WIDTH as usize - 1);
+        assert_eq!(game.board()[0][1], WALL_COLOR);
+        let garbage = game.board().rows().iter().filter(|row| row.contains(&GARBAGE_COLOR)).count();
+        assert_eq!(garbage, 3);
+
+        // Hold does nothing
+        let current = game.current_piece().cloned();
+        assert!(!game.apply(Move::Hold).moved);
+        assert_eq!(game.current_piece().cloned(), current);
+        assert_eq!(Handicap { walls: 20, ..handicap }.clamped().walls, HANDICAP_MAX_WALLS);
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = ModeRegistry::new();
+        assert_eq!(registry.names().collect::<Vec<_>>(), ["MARATHON", "SPRINT", "ULTRA", "DIG", "CLASSIC", "PRACTICE"]);
+        assert_eq!(registry.find("ULTRA"), Some(2));
+        assert_eq!(registry.get(99).name(), "MARATHON");
+
+        struct Zen;
+        impl GameMode for Zen {
+            fn name(&self) -> &'static str {
+                "ZEN"
+            }
+        }
+        registry.register(Box::new(Zen));
+        assert_eq!(registry.find("ZEN"), Some(6));
+        assert_eq!(registry.len(), 7);
+        assert_eq!(registry.get(6).scoring().name(), "GUIDELINE");
+        assert_eq!(registry.get(4).scoring().name(), "NES");
+    }
+
+    #[test]
+    fn test_practice_has_no_gravity_and_is_unranked() {
+        let mut game = Game::with_seed(1);
+        Practice.setup(&mut game);
+        let start = game.current_piece().unwrap().position;
+        game.update(60.0);
+        assert_eq!(game.current_piece().unwrap().position, start);
+
+        assert!(!Practice.is_ranked() && Practice.is_practice());
+        assert!(Marathon.is_ranked() && !Marathon.is_practice());
+        assert!(Marathon.has_level_select() && Classic.has_level_select());
+        assert!(!Sprint.has_level_select() && !Ultra.has_level_select() && !Dig.has_level_select() && !Practice.has_level_select());
+        assert!(Practice.can_freeze_gravity() && !Marathon.can_freeze_gravity());
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0.0), "0:00.00");
+        assert_eq!(format_time(65.2), "1:05.20");
+        assert_eq!(format_time(120.0), "2:00.00");
+    }
+}
@@ -0,0 +1,436 @@
+use crate::constants::SOUND_FORMATS;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::path::{Path, PathBuf};
+
+/// Seconds a crossfade between two tracks takes
+pub const CROSSFADE_TIME: f32 = 2.0;
+
+/// File names of playlist tracks start with this
+const PLAYLIST_PREFIX: &str = "theme_";
+
+/// Playback speed of the game music while in danger, when there is no hurry track
+pub const HURRY_PITCH: f32 = 1.15;
+
+/// The music hurries once the stack is this few rows from the top
+const DANGER_ROWS: u32 = 5;
+
+/// and calms down again once this many rows are free
+const SAFE_ROWS: u32 = 8;
+
+/// The music hurries from this level on, however low the stack
+const HURRY_LEVEL: u32 = 15;
+
+/// Music level while a jingle plays, as a fraction of the music volume
+const DUCK_LEVEL: f32 = 0.3;
+
+/// Seconds the music takes to dip under a jingle
+const DUCK_ATTACK: f32 = 0.1;
+
+/// Seconds the music takes to come back once the jingle is over
+const DUCK_RELEASE: f32 = 0.6;
+
+/// Which music fits the current screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MusicContext {
+    Menu,  // Title, results and settings screens: the menu track on a loop
+    Game,  // During a game: the playlist, one track after another
+}
+
+/// A track that is sounding, fading in or fading out
+#[derive(Clone, Debug, PartialEq)]
+pub struct Voice {
+    pub id: u64,        // Tells voices apart, including two of the same track at different speeds
+    pub track: String,  // Path of the track's file
+    pub level: f32,     // Loudness from 0.0 to 1.0, before the music volume is applied
+    pub pitch: f32,     // Playback speed, 1.0 for normal
+    pub looping: bool,  // Whether the track repeats instead of ending
+    fading_out: bool,   // Whether the voice is on its way out
+}
+
+/// Picks the playlist tracks out of the files in the sounds directory
+/// A track in several formats is listed once, in the first of `SOUND_FORMATS` found
+pub fn playlist_tracks(files: impl IntoIterator<Item = PathBuf>) -> Vec<String> {
+    let mut files: Vec<PathBuf> = files
+        .into_iter()
+        .filter(|path| path.file_stem().and_then(|stem| stem.to_str()).is_some_and(|stem| stem.starts_with(PLAYLIST_PREFIX)))
+        .filter(|path| format_rank(path).is_some())
+        .collect();
+    files.sort_by_key(|path| (path.file_stem().map(|stem| stem.to_owned()), format_rank(path)));
+    files.dedup_by_key(|path| path.file_stem().map(|stem| stem.to_owned()));
+    files.iter().map(|path| path.to_string_lossy().replace('\\', "/")).collect()
+}
+
+/// Position of the file's extension in `SOUND_FORMATS`, or `None` if the game can't play it
+fn format_rank(path: &Path) -> Option<usize> {
+    let extension = path.extension()?.to_str()?;
+    SOUND_FORMATS.iter().position(|format| format.eq_ignore_ascii_case(extension))
+}
+
+/// Tracks played in turn during games, in name order or shuffled
+#[derive(Debug)]
+pub struct Playlist {
+    tracks: Vec<String>,  // Paths of the tracks, sorted by name
+    order: Vec<usize>,    // Indices into `tracks` in the order they play
+    position: usize,      // Index into `order` of the current track
+    shuffle: bool,        // Whether the order is reshuffled on every pass
+    rng: StdRng,          // Source of the shuffles
+}
+
+impl Playlist {
+    pub fn new(mut tracks: Vec<String>, shuffle: bool, seed: u64) -> Self {
+        tracks.sort();
+        let mut playlist = Self {
+            order: (0..tracks.len()).collect(),
+            tracks,
+            position: 0,
+            shuffle,
+            rng: StdRng::seed_from_u64(seed),
+        };
+        if shuffle {
+            playlist.order.shuffle(&mut playlist.rng);
+        }
+        playlist
+    }
+
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// The track playing now, or `None` for an empty playlist
+    pub fn current(&self) -> Option<&str> {
+        self.order.get(self.position).map(|&index| self.tracks[index].as_str())
+    }
+
+    /// Moves to the next track, starting over after the last one
+    /// A shuffled playlist is shuffled again for each pass, without playing the same track twice in a row
+    pub fn advance(&mut self) -> Option<&str> {
+        self.position += 1;
+        if self.position >= self.order.len() {
+            self.position = 0;
+            if let (true, Some(&last)) = (self.shuffle, self.order.last()) {
+                self.order.shuffle(&mut self.rng);
+                if self.order[0] == last {
+                    let end = self.order.len() - 1;
+                    self.order.swap(0, end);
+                }
+            }
+        }
+        self.current()
+    }
+}
+
+/// Decides which music tracks sound and how loud; the frontend plays its voices and calls `update` every frame
+/// Menus loop one track and games play the playlist; switching between them, or skipping to the next track,
+/// fades the old track out while the new one fades in over `CROSSFADE_TIME`
+/// When a playlist track ends on its own the next one follows it
+/// In danger the game crossfades to the hurry track, or without one to the same track played faster
+#[derive(Debug)]
+pub struct MusicDirector {
+    menu_track: Option<String>,   // Track looped on the menus
+    hurry_track: Option<String>,  // Track looped in danger, if the game has one
+    playlist: Playlist,           // Tracks played during games
+    context: MusicContext,        // Which music is wanted
+    hurry: bool,                  // Whether the game is in danger
+    voices: Vec<Voice>,           // Tracks sounding now, the newest last
+    next_id: u64,                 // Id of the next voice to start
+}
+
+impl MusicDirector {
+    /// Starts on the menu track at full level
+    pub fn new(menu_track: Option<String>, hurry_track: Option<String>, playlist: Playlist) -> Self {
+        let mut music = Self {
+            menu_track,
+            hurry_track,
+            playlist,
+            context: MusicContext::Menu,
+            hurry: false,
+            voices: Vec::new(),
+            next_id: 0,
+        };
+        if let Some(track) = music.menu_track.clone() {
+            music.start_voice(track, 1.0, 1.0, true);
+        }
+        music
+    }
+
+    pub fn context(&self) -> MusicContext {
+        self.context
+    }
+
+    /// Tracks to play and their levels
+    pub fn voices(&self) -> &[Voice] {
+        &self.voices
+    }
+
+    /// Whether the game music is hurrying
+    pub fn hurry(&self) -> bool {
+        self.hurry
+    }
+
+    /// Switches between menu and game music, crossfading if the track changes
+    /// Every game starts out calm
+    pub fn set_context(&mut self, context: MusicContext) {
+        if context != self.context {
+            self.context = context;
+            self.hurry = false;
+            self.crossfade_to_wanted();
+        }
+    }
+
+    /// Hurries the game music while the stack is near the top or the level is high, and calms it
+    /// once the danger passes; the stack has to fall a few rows further than it rose, so the music
+    /// doesn't flip back and forth with every line
+    pub fn watch_danger(&mut self, free_rows: u32, level: u32) {
+        let hurry = if level >= HURRY_LEVEL || free_rows <= DANGER_ROWS {
+            true
+        } else if free_rows >= SAFE_ROWS {
+            false
+        } else {
+            self.hurry
+        };
+        if hurry != self.hurry && self.context == MusicContext::Game {
+            self.hurry = hurry;
+            self.crossfade_to_wanted();
+        }
+    }
+
+    /// Crossfades to the next playlist track; does nothing on the menus
+    pub fn next_track(&mut self) {
+        if self.context == MusicContext::Game && self.playlist.len() > 1 {
+            self.playlist.advance();
+            self.crossfade_to_wanted();
+        }
+    }
+
+    /// Moves the fades along by `dt` seconds
+    /// `ended` are the tracks that finished playing on their own; if that was the game's track, the next one starts
+    pub fn update(&mut self, dt: f32, ended: &[u64]) {
+        let current_ended = self
+            .voices
+            .iter()
+            .any(|voice| !voice.fading_out && !voice.looping && ended.contains(&voice.id));
+        self.voices.retain(|voice| !ended.contains(&voice.id));
+        if current_ended && self.context == MusicContext::Game {
+            // Nothing is left to fade from, so the next track starts at full level
+            self.playlist.advance();
+            if let Some((track, pitch, looping)) = self.wanted() {
+                self.start_voice(track, 1.0, pitch, looping);
+            }
+        }
+
+        let step = dt / CROSSFADE_TIME;
+        for voice in &mut self.voices {
+            if voice.fading_out {
+                voice.level = (voice.level - step).max(0.0);
+            } else {
+                voice.level = (voice.level + step).min(1.0);
+            }
+        }
+        self.voices.retain(|voice| !voice.fading_out || voice.level > 0.0);
+    }
+
+    /// The track the context calls for, its speed, and whether it loops
+    /// Games fall back to the menu track when there is no playlist
+    fn wanted(&self) -> Option<(String, f32, bool)> {
+        match (self.context, self.playlist.current()) {
+            (MusicContext::Game, _) if self.hurry && self.hurry_track.is_some() => {
+                self.hurry_track.clone().map(|track| (track, 1.0, true))
+            }
+            (MusicContext::Game, Some(track)) => {
+                let pitch = if self.hurry { HURRY_PITCH } else { 1.0 };
+                Some((track.to_string(), pitch, self.playlist.len() <= 1))
+            }
+            _ => self.menu_track.clone().map(|track| (track, 1.0, true)),
+        }
+    }
+
+    /// Fades every voice out except the wanted track, which fades in, or back in if it was on its way out
+    fn crossfade_to_wanted(&mut self) {
+        let wanted = self.wanted();
+        for voice in &mut self.voices {
+            voice.fading_out = wanted
+                .as_ref()
+                .is_none_or(|(track, pitch, _)| *track != voice.track || *pitch != voice.pitch);
+        }
+        if let Some((track, pitch, looping)) = wanted {
+            if !self.voices.iter().any(|voice| voice.track == track && voice.pitch == pitch) {
+                self.start_voice(track, 0.0, pitch, looping);
+            }
+        }
+    }
+
+    fn start_voice(&mut self, track: String, level: f32, pitch: f32, looping: bool) {
+        self.voices.push(Voice { id: self.next_id, track, level, pitch, looping, fading_out: false });
+        self.next_id += 1;
+    }
+}
+
+/// Dips the music under jingles like the tetris and game over sounds, and brings it back once they finish
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ducking {
+    gain: f32,  // Multiplier on the music volume, from `DUCK_LEVEL` to 1.0
+}
+
+impl Default for Ducking {
+    fn default() -> Self {
+        Self { gain: 1.0 }
+    }
+}
+
+impl Ducking {
+    /// Multiplier on the music volume right now
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Moves the gain along by `dt` seconds, down while a jingle is playing and back up after
+    pub fn update(&mut self, dt: f32, jingle_playing: bool) {
+        let range = 1.0 - DUCK_LEVEL;
+        self.gain = if jingle_playing {
+            (self.gain - range * dt / DUCK_ATTACK).max(DUCK_LEVEL)
+        } else {
+            (self.gain + range * dt / DUCK_RELEASE).min(1.0)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracks(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_playlist_tracks_prefer_compressed_files() {
+        let files = ["/sounds/theme_b.wav", "/sounds/theme_a.ogg", "/sounds/theme_b.flac", "/sounds/move.ogg", "/sounds/theme_c.txt"];
+        let found = playlist_tracks(files.iter().map(PathBuf::from));
+        assert_eq!(found, tracks(&["/sounds/theme_a.ogg", "/sounds/theme_b.flac"]));
+    }
+
+    #[test]
+    fn test_shuffle_plays_every_track_once_per_pass() {
+        let mut playlist = Playlist::new(tracks(&["a", "b", "c", "d"]), true, 3);
+        let mut first = playlist.current().unwrap().to_string();
+        for _ in 0..5 {
+            let mut pass = vec![first];
+            for _ in 1..4 {
+                pass.push(playlist.advance().unwrap().to_string());
+            }
+            let last = pass[3].clone();
+            pass.sort();
+            assert_eq!(pass, tracks(&["a", "b", "c", "d"]));
+
+            first = playlist.advance().unwrap().to_string();
+            assert_ne!(first, last, "a pass never starts with the track that ended the last one");
+        }
+
+        // Without shuffle the tracks play in name order
+        let mut playlist = Playlist::new(tracks(&["b", "a"]), false, 3);
+        assert_eq!(playlist.current(), Some("a"));
+        assert_eq!(playlist.advance(), Some("b"));
+        assert_eq!(playlist.advance(), Some("a"));
+    }
+
+    #[test]
+    fn test_crossfades_between_menu_and_game() {
+        let playlist = Playlist::new(tracks(&["theme_1", "theme_2"]), false, 0);
+        let mut music = MusicDirector::new(Some("menu".to_string()), None, playlist);
+        assert_eq!(music.voices().len(), 1);
+
+        music.set_context(MusicContext::Game);
+        music.update(CROSSFADE_TIME / 2.0, &[]);
+        let levels: Vec<(&str, f32)> = music.voices().iter().map(|voice| (voice.track.as_str(), voice.level)).collect();
+        assert_eq!(levels, [("menu", 0.5), ("theme_1", 0.5)]);
+
+        // The menu track drops out once it is silent
+        music.update(CROSSFADE_TIME, &[]);
+        assert_eq!(music.voices().len(), 1);
+        assert_eq!(music.voices()[0].level, 1.0);
+
+        // Going back mid-fade turns the menu track around instead of restarting it
+        music.set_context(MusicContext::Menu);
+        music.update(CROSSFADE_TIME / 4.0, &[]);
+        music.set_context(MusicContext::Game);
+        music.update(CROSSFADE_TIME / 4.0, &[]);
+        assert_eq!(music.voices().iter().find(|voice| voice.track == "theme_1").unwrap().level, 1.0);
+    }
+
+    #[test]
+    fn test_ended_track_moves_the_playlist_on() {
+        let playlist = Playlist::new(tracks(&["theme_1", "theme_2"]), false, 0);
+        let mut music = MusicDirector::new(None, None, playlist);
+        assert!(music.voices().is_empty());
+
+        music.set_context(MusicContext::Game);
+        music.update(CROSSFADE_TIME, &[]);
+        music.update(0.1, &[music.voices()[0].id]);
+        assert_eq!(music.voices().len(), 1);
+        assert_eq!(music.voices()[0].track, "theme_2");
+        assert_eq!(music.voices()[0].level, 1.0);
+
+        // Skipping crossfades, and the menus have no next track
+        music.next_track();
+        music.update(CROSSFADE_TIME / 2.0, &[]);
+        assert_eq!(music.voices().len(), 2);
+        music.set_context(MusicContext::Menu);
+        music.update(CROSSFADE_TIME, &[]);
+        assert!(music.voices().is_empty(), "without a menu track the menus are silent");
+    }
+
+    #[test]
+    fn test_danger_hurries_the_game_music() {
+        let playlist = Playlist::new(tracks(&["theme_1"]), false, 0);
+        let mut music = MusicDirector::new(None, None, playlist);
+        music.watch_danger(0, 1);
+        assert!(!music.hurry(), "the menus never hurry");
+
+        music.set_context(MusicContext::Game);
+        music.update(CROSSFADE_TIME, &[]);
+        music.watch_danger(DANGER_ROWS, 1);
+        music.update(CROSSFADE_TIME, &[]);
+        let voices: Vec<(&str, f32)> = music.voices().iter().map(|voice| (voice.track.as_str(), voice.pitch)).collect();
+        assert_eq!(voices, [("theme_1", HURRY_PITCH)]);
+
+        // A line or two of relief isn't enough to calm down
+        music.watch_danger(SAFE_ROWS - 1, 1);
+        assert!(music.hurry());
+        music.watch_danger(SAFE_ROWS, 1);
+        assert!(!music.hurry());
+        music.watch_danger(SAFE_ROWS, HURRY_LEVEL);
+        assert!(music.hurry());
+
+        // With a hurry track the game switches to it, and a new game starts calm
+        let playlist = Playlist::new(tracks(&["theme_1"]), false, 0);
+        let mut music = MusicDirector::new(None, Some("hurry".to_string()), playlist);
+        music.set_context(MusicContext::Game);
+        music.watch_danger(0, 1);
+        music.update(CROSSFADE_TIME, &[]);
+        assert_eq!(music.voices()[0].track, "hurry");
+        music.set_context(MusicContext::Menu);
+        music.set_context(MusicContext::Game);
+        assert!(!music.hurry());
+    }
+
+    #[test]
+    fn test_ducking_dips_under_jingles() {
+        let mut ducking = Ducking::default();
+        ducking.update(DUCK_ATTACK / 2.0, true);
+        assert!(ducking.gain() < 1.0 && ducking.gain() > DUCK_LEVEL);
+        ducking.update(DUCK_ATTACK, true);
+        assert_eq!(ducking.gain(), DUCK_LEVEL);
+
+        // The music comes back more slowly than it dipped
+        ducking.update(DUCK_ATTACK, false);
+        assert!(ducking.gain() < 1.0);
+        ducking.update(DUCK_RELEASE, false);
+        assert_eq!(ducking.gain(), 1.0);
+    }
+}
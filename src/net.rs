@@ -0,0 +1,517 @@
+use ggez::graphics::Color;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+/// Default TCP port used for hosting and joining versus games
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Protocol version exchanged in the handshake; peers with a different version are rejected
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// How long to wait when connecting to a host before giving up
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Longest line a peer may send; one that goes on past it without a newline is dropped
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// A single board cell as sent over the wire: `None` for empty, otherwise the RGB color
+pub type WireCell = Option<[u8; 3]>;
+
+/// Messages exchanged between two instances during a versus game
+/// Each message is sent as one line of JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NetMessage {
+    /// Handshake sent by both sides right after connecting
+    Hello { version: u32 },
+    /// A piece was locked at the given board cells
+    PiecePlaced { cells: Vec<(i32, i32)>, color: [u8; 3] },
+    /// Garbage lines sent to the opponent
+    Attack { lines: u32 },
+    /// Full copy of the sender's board, used to resync after clears and garbage
+    BoardSnapshot { rows: Vec<Vec<WireCell>> },
+    /// The sender topped out
+    GameOver,
+    /// The host's match length: rounds a player needs to win the match
+    Match { first_to: u32 },
+    /// The sender is ready for the next round of the match
+    Ready,
+    /// A chat message from the sender, signed with their name
+    Chat { from: String, text: String },
+}
+
+/// Converts a board color to its wire representation
+pub fn color_to_wire(color: Color) -> WireCell {
+    if color == Color::BLACK {
+        None
+    } else {
+        let (r, g, b) = color.to_rgb();
+        Some([r, g, b])
+    }
+}
+
+/// Converts a wire cell back to a board color
+pub fn wire_to_color(cell: WireCell) -> Color {
+    match cell {
+        Some([r, g, b]) => Color::from_rgb(r, g, b),
+        None => Color::BLACK,
+    }
+}
+
+/// Converts a whole board to a snapshot message
+pub fn snapshot_from_board(board: &[Vec<Color>]) -> NetMessage {
+    NetMessage::BoardSnapshot {
+        rows: board
+            .iter()
+            .map(|row| row.iter().map(|&cell| color_to_wire(cell)).collect())
+            .collect(),
+    }
+}
+
+/// A connected peer
+/// Messages are newline-delimited JSON over a non-blocking TCP stream
+pub struct NetSession {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+}
+
+impl NetSession {
+    /// Wraps an already connected stream and sends the handshake
+    pub fn from_stream(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        stream.set_nonblocking(true)?;
+        let mut session = Self {
+            stream,
+            buffer: Vec::new(),
+        };
+        session.send(&NetMessage::Hello { version: PROTOCOL_VERSION })?;
+        Ok(session)
+    }
+
+    /// Connects to a host, e.g. "192.168.1.20" or "192.168.1.20:7878"
+    /// The default port is used when the address doesn't specify one
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let session = Self::from_stream(connect_stream(address, DEFAULT_PORT)?)?;
+        log::info!(target: "net", "Connected to {}", address.trim());
+        Ok(session)
+    }
+
+    /// Sends a message to the peer
+    pub fn send(&mut self, message: &NetMessage) -> io::Result<()> {
+        write_line(&mut self.stream, message)
+    }
+
+    /// Reads all messages that have arrived since the last poll without blocking
+    /// Returns an error once the peer has disconnected
+    pub fn poll(&mut self) -> io::Result<Vec<NetMessage>> {
+        read_lines(&mut self.stream, &mut self.buffer)
+    }
+}
+
+/// Connects to `address`, using `default_port` when it doesn't name one
+pub(crate) fn connect_stream(address: &str, default_port: u16) -> io::Result<TcpStream> {
+    let address = address.trim();
+    let with_port = if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{}:{}", address, default_port)
+    };
+    let socket_addr = with_port
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "invalid address"))?;
+    TcpStream::connect_timeout(&socket_addr, CONNECT_TIMEOUT)
+}
+
+/// Writes a message as one line of JSON to a non-blocking stream
+pub(crate) fn write_line(stream: &mut TcpStream, message: &impl Serialize) -> io::Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    // The stream is non-blocking, so retry until the whole line is written
+    let mut written = 0;
+    while written < line.len() {
+        match stream.write(&line[written..]) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => written += n,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::yield_now(),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Reads the lines of JSON that have arrived on a non-blocking stream, keeping a partial line in `buffer`
+/// Lines that aren't a message are logged and skipped
+/// Returns an error once the peer has disconnected, or has sent more than `MAX_LINE_LENGTH` without a newline
+pub(crate) fn read_lines<T: DeserializeOwned>(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> io::Result<Vec<T>> {
+    let mut chunk = [0u8; 4096];
+    let mut messages = Vec::new();
+    loop {
+        match stream.read(&mut chunk) {
+            Ok(0) => return Err(ErrorKind::ConnectionAborted.into()),
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+            Err(e) => return Err(e),
+        }
+        while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            match serde_json::from_slice(&line[..end]) {
+                Ok(message) => messages.push(message),
+                Err(e) => log::warn!(target: "net", "Skipped a line that isn't a message: {}", e),
+            }
+        }
+        if buffer.len() > MAX_LINE_LENGTH {
+            return Err(io::Error::new(ErrorKind::InvalidData, "line too long"));
+        }
+    }
+    Ok(messages)
+}
+
+/// Listens for a single opponent to join
+/// Pings from the room browser are echoed on the UDP port of the same number, so players can see how far away the host is
+pub struct NetHost {
+    listener: TcpListener,
+    pings: UdpSocket,
+}
+
+impl NetHost {
+    /// Starts listening on all interfaces at the given port
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        let pings = UdpSocket::bind(("0.0.0.0", listener.local_addr()?.port()))?;
+        pings.set_nonblocking(true)?;
+        log::info!(target: "net", "Hosting on {}", listener.local_addr()?);
+        Ok(Self { listener, pings })
+    }
+
+    /// The address the host is listening on
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts a waiting opponent, if any, without blocking, and answers any pings
+    pub fn accept(&self) -> io::Result<Option<NetSession>> {
+        let mut ping = [0u8; 256];
+        while let Ok((length, from)) = self.pings.recv_from(&mut ping) {
+            let _ = self.pings.send_to(&ping[..length], from);
+        }
+        match self.listener.accept() {
+            Ok((stream, address)) => {
+                log::info!(target: "net", "Opponent joined from {}", address);
+                NetSession::from_stream(stream).map(Some)
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// The local view of a versus match: the connection plus what we know about the opponent
+pub struct VersusLink {
+    session: NetSession,
+    pub opponent_board: Vec<Vec<Color>>,  // Mirror of the opponent's board for the mini view
+    pub incoming_attacks: Vec<u32>,       // Attacks received but not yet queued
+    pub opponent_topped_out: bool,        // Whether the opponent has lost
+    pub match_length: Option<u32>,        // Match length the host sent, until the frontend takes it
+    chat: Vec<(String, String)>,          // Chat messages received but not yet shown, as sender and text
+    ready: bool,                          // Whether we're ready for the next round
+    opponent_ready: bool,                 // Whether the opponent is ready for the next round
+    round_started: bool,                  // Whether a round started since the frontend last asked
+}
+
+impl VersusLink {
+    /// Creates a link for a board of the given size
+    pub fn new(session: NetSession, width: usize, height: usize) -> Self {
+        Self {
+            session,
+            opponent_board: vec![vec![Color::BLACK; width]; height],
+            incoming_attacks: Vec::new(),
+            opponent_topped_out: false,
+            match_length: None,
+            chat: Vec::new(),
+            ready: false,
+            opponent_ready: false,
+            round_started: false,
+        }
+    }
+
+    /// Tells the opponent that a piece was locked
+    pub fn send_placement(&mut self, cells: Vec<(i32, i32)>, color: Color) -> io::Result<()> {
+        let (r, g, b) = color.to_rgb();
+        self.session.send(&NetMessage::PiecePlaced { cells, color: [r, g, b] })
+    }
+
+    /// Sends garbage lines to the opponent
+    pub fn send_attack(&mut self, lines: u32) -> io::Result<()> {
+        if lines == 0 {
+            return Ok(());
+        }
+        self.session.send(&NetMessage::Attack { lines })
+    }
+
+    /// Sends a full copy of our board
+    pub fn send_snapshot(&mut self, board: &[Vec<Color>]) -> io::Result<()> {
+        self.session.send(&snapshot_from_board(board))
+    }
+
+    /// Tells the opponent that we topped out
+    pub fn send_game_over(&mut self) -> io::Result<()> {
+        self.session.send(&NetMessage::GameOver)
+    }
+
+    /// Tells the opponent how many round wins take the match
+    pub fn send_match(&mut self, first_to: u32) -> io::Result<()> {
+        self.session.send(&NetMessage::Match { first_to })
+    }
+
+    /// Tells the opponent we're ready for the next round, which starts once both players are
+    pub fn send_ready(&mut self) -> io::Result<()> {
+        if self.ready {
+            return Ok(());
+        }
+        self.ready = true;
+        self.session.send(&NetMessage::Ready)?;
+        if self.opponent_ready {
+            self.start_round();
+        }
+        Ok(())
+    }
+
+    /// Whether we're waiting on the opponent to be ready for the next round
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    /// Whether the opponent is waiting on us to be ready for the next round
+    pub fn opponent_is_ready(&self) -> bool {
+        self.opponent_ready
+    }
+
+    /// Whether both players became ready and a new round started since the last call
+    pub fn take_round_start(&mut self) -> bool {
+        std::mem::take(&mut self.round_started)
+    }
+
+    /// Forgets the last round's view of the opponent
+    /// Called as soon as both players are ready, so messages of the new round that arrive with
+    /// the opponent's ready aren't lost
+    fn start_round(&mut self) {
+        for row in &mut self.opponent_board {
+            row.fill(Color::BLACK);
+        }
+        self.incoming_attacks.clear();
+        self.opponent_topped_out = false;
+        self.ready = false;
+        self.opponent_ready = false;
+        self.round_started = true;
+    }
+
+    /// Sends a chat message signed with our name
+    pub fn send_chat(&mut self, from: &str, text: &str) -> io::Result<()> {
+        self.session.send(&NetMessage::Chat { from: from.to_string(), text: text.to_string() })
+    }
+
+    /// Takes the chat messages received so far, as sender and text
+    pub fn take_chat(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.chat)
+    }
+
+    /// Takes all attacks received so far, one entry per attack
+    pub fn take_attacks(&mut self) -> Vec<u32> {
+        std::mem::take(&mut self.incoming_attacks)
+    }
+
+    /// Processes incoming messages
+    /// Returns an error if the connection dropped or the peer speaks another protocol version
+    pub fn update(&mut self) -> io::Result<()> {
+        for message in self.session.poll()? {
+            log::trace!(target: "net", "Received {:?}", message);
+            match message {
+                NetMessage::Hello { version } => {
+                    if version != PROTOCOL_VERSION {
+                        return Err(io::Error::new(
+                            ErrorKind::InvalidData,
+                            format!("opponent uses protocol version {}", version),
+                        ));
+                    }
+                }
+                NetMessage::PiecePlaced { cells, color } => {
+                    let color = wire_to_color(Some(color));
+                    for (x, y) in cells {
+                        if let Some(cell) = self
+                            .opponent_board
+                            .get_mut(y as usize)
+                            .and_then(|row| row.get_mut(x as usize))
+                        {
+                            *cell = color;
+                        }
+                    }
+                }
+                NetMessage::Attack { lines } => {
+                    self.incoming_attacks.push(lines);
+                }
+                NetMessage::BoardSnapshot { rows } => {
+                    self.opponent_board = rows
+                        .into_iter()
+                        .map(|row| row.into_iter().map(wire_to_color).collect())
+                        .collect();
+                }
+                NetMessage::GameOver => {
+                    self.opponent_topped_out = true;
+                }
+                NetMessage::Match { first_to } => {
+                    self.match_length = Some(first_to);
+                }
+                NetMessage::Chat { from, text } => {
+                    self.chat.push((from, text));
+                }
+                NetMessage::Ready => {
+                    self.opponent_ready = true;
+                    if self.ready {
+                        self.start_round();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// Polls until at least one message arrives or a second has passed
+    fn poll_until_some(session: &mut NetSession) -> Vec<NetMessage> {
+        let start = Instant::now();
+        loop {
+            let messages = session.poll().unwrap();
+            if !messages.is_empty() || start.elapsed() > Duration::from_secs(1) {
+                return messages;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn connected_pair() -> (NetSession, NetSession) {
+        let host = NetHost::bind(0).unwrap();
+        let port = host.local_addr().unwrap().port();
+        let client = NetSession::connect(&format!("127.0.0.1:{}", port)).unwrap();
+        let start = Instant::now();
+        loop {
+            if let Some(server) = host.accept().unwrap() {
+                return (server, client);
+            }
+            assert!(start.elapsed() < Duration::from_secs(1), "host never accepted");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_wire_color_roundtrip() {
+        assert_eq!(color_to_wire(Color::BLACK), None);
+        let cyan = Color::from_rgb(0, 240, 240);
+        assert_eq!(wire_to_color(color_to_wire(cyan)), cyan);
+    }
+
+    #[test]
+    fn test_handshake_and_messages() {
+        let (mut server, mut client) = connected_pair();
+
+        // Both sides greet each other first
+        assert_eq!(poll_until_some(&mut server), vec![NetMessage::Hello { version: PROTOCOL_VERSION }]);
+        assert_eq!(poll_until_some(&mut client), vec![NetMessage::Hello { version: PROTOCOL_VERSION }]);
+
+        client.send(&NetMessage::Attack { lines: 4 }).unwrap();
+        client.send(&NetMessage::GameOver).unwrap();
+        let mut received = Vec::new();
+        while received.len() < 2 {
+            received.extend(poll_until_some(&mut server));
+        }
+        assert_eq!(received, vec![NetMessage::Attack { lines: 4 }, NetMessage::GameOver]);
+    }
+
+    #[test]
+    fn test_bad_lines_are_skipped_and_endless_ones_dropped() {
+        let (mut server, mut client) = connected_pair();
+        poll_until_some(&mut server);
+
+        client.stream.write_all(b"not json\n").unwrap();
+        client.send(&NetMessage::GameOver).unwrap();
+        assert_eq!(poll_until_some(&mut server), vec![NetMessage::GameOver]);
+
+        // A line that never ends is cut off before it can use up memory
+        let junk = [b'x'; 4096];
+        let start = Instant::now();
+        let error = loop {
+            let _ = client.stream.write(&junk);
+            if let Err(e) = server.poll() {
+                break e;
+            }
+            assert!(start.elapsed() < Duration::from_secs(1), "endless line was never dropped");
+        };
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(server.buffer.len() <= MAX_LINE_LENGTH + junk.len());
+    }
+
+    #[test]
+    fn test_versus_link_applies_messages() {
+        let (server, mut client) = connected_pair();
+        let mut link = VersusLink::new(server, 10, 20);
+
+        let mut board = vec![vec![Color::BLACK; 10]; 20];
+        board[19][0] = Color::RED;
+        client.send(&snapshot_from_board(&board)).unwrap();
+        client.send(&NetMessage::PiecePlaced { cells: vec![(5, 18)], color: [0, 240, 240] }).unwrap();
+        client.send(&NetMessage::Attack { lines: 2 }).unwrap();
+        client.send(&NetMessage::Chat { from: "ALEX".to_string(), text: "gg".to_string() }).unwrap();
+        client.send(&NetMessage::GameOver).unwrap();
+
+        let start = Instant::now();
+        while !link.opponent_topped_out && start.elapsed() < Duration::from_secs(1) {
+            link.update().unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        assert!(link.opponent_topped_out);
+        assert_eq!(link.opponent_board[19][0], Color::RED);
+        assert_eq!(link.opponent_board[18][5], Color::from_rgb(0, 240, 240));
+        assert_eq!(link.take_attacks(), vec![2]);
+        assert!(link.take_attacks().is_empty());
+        assert_eq!(link.take_chat(), [("ALEX".to_string(), "gg".to_string())]);
+    }
+
+    #[test]
+    fn test_rounds_start_when_both_are_ready() {
+        let (server, mut client) = connected_pair();
+        let mut link = VersusLink::new(server, 10, 20);
+        client.send(&NetMessage::Match { first_to: 5 }).unwrap();
+        client.send(&NetMessage::GameOver).unwrap();
+        client.send(&NetMessage::Ready).unwrap();
+
+        let start = Instant::now();
+        while !link.opponent_ready && start.elapsed() < Duration::from_secs(1) {
+            link.update().unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(link.match_length, Some(5));
+        assert!(link.opponent_topped_out);
+        assert!(!link.take_round_start(), "we aren't ready yet");
+
+        // Our ready starts the round, clearing what was left of the last one
+        link.send_ready().unwrap();
+        assert!(link.take_round_start());
+        assert!(!link.take_round_start());
+        assert!(!link.opponent_topped_out);
+        assert!(!link.is_ready());
+        let mut received = Vec::new();
+        while received.len() < 2 {
+            received.extend(poll_until_some(&mut client));
+        }
+        assert_eq!(received, vec![NetMessage::Hello { version: PROTOCOL_VERSION }, NetMessage::Ready]);
+    }
+}
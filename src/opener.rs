@@ -0,0 +1,236 @@
+use crate::constants::{GRID_HEIGHT, GRID_WIDTH};
+use crate::tetromino::{Tetromino, TetrominoType};
+use serde::Deserialize;
+
+/// A piece of an opener and where it goes
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "StepFile")]
+pub struct Step {
+    pub piece: TetrominoType,   // Piece to place
+    pub cells: Vec<(i32, i32)>, // Board cells it fills when it locks, sorted
+}
+
+/// A step as written in an opener file: the piece's letter, and cells as `[column, row]` counted from the bottom left
+#[derive(Deserialize)]
+struct StepFile {
+    piece: String,
+    cells: Vec<[i32; 2]>,
+}
+
+impl TryFrom<StepFile> for Step {
+    type Error = String;
+
+    fn try_from(file: StepFile) -> Result<Self, Self::Error> {
+        let mut letters = file.piece.chars();
+        let piece = match (letters.next(), letters.next()) {
+            (Some(letter), None) => TetrominoType::from_letter(letter),
+            _ => None,
+        }
+        .ok_or_else(|| format!("unknown piece {:?}", file.piece))?;
+        let mut cells: Vec<(i32, i32)> = file.cells.iter().map(|&[column, row]| (column, GRID_HEIGHT - 1 - row)).collect();
+        cells.sort_unstable();
+        if cells.iter().any(|&(x, y)| !(0..GRID_WIDTH).contains(&x) || !(0..GRID_HEIGHT).contains(&y)) {
+            return Err(format!("{:?} piece has cells outside the board", piece));
+        }
+        if !fits_shape(piece, &cells) {
+            return Err(format!("{:?} piece cells {:?} don't make its shape", piece, file.cells));
+        }
+        Ok(Self { piece, cells })
+    }
+}
+
+/// Whether sorted board cells are the shape of a piece in one of its rotations
+fn fits_shape(kind: TetrominoType, cells: &[(i32, i32)]) -> bool {
+    let mut piece = Tetromino::new(kind);
+    (0..4).any(|_| {
+        piece.rotate();
+        normalized(&shape_cells(&piece)) == normalized(cells)
+    })
+}
+
+fn shape_cells(piece: &Tetromino) -> Vec<(i32, i32)> {
+    let mut cells = Vec::new();
+    for (y, row) in piece.shape.iter().enumerate() {
+        for (x, &cell) in row.iter().enumerate() {
+            if cell {
+                cells.push((x as i32, y as i32));
+            }
+        }
+    }
+    cells
+}
+
+/// Cells moved so the leftmost column and top row are 0, sorted
+fn normalized(cells: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let left = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let top = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    let mut moved: Vec<_> = cells.iter().map(|&(x, y)| (x - left, y - top)).collect();
+    moved.sort_unstable();
+    moved
+}
+
+/// A set sequence of placements for the start of a game, like a T-spin setup
+/// Openers are TOML files listing each step's piece and cells; a step's cells are where the piece sits
+/// when it locks, after the rows cleared by earlier steps have gone
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Opener {
+    pub name: String,     // Name shown in the title menu
+    pub steps: Vec<Step>, // Placements in the order the pieces are dealt
+}
+
+impl Opener {
+    /// Parses an opener file, checking that every step's cells make its piece
+    pub fn from_toml(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// The pieces of the opener in the order they're dealt
+    pub fn sequence(&self) -> Vec<TetrominoType> {
+        self.steps.iter().map(|step| step.piece).collect()
+    }
+}
+
+/// What a lock did to the opener being practiced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    Correct,   // The piece went where a step wanted it
+    Mistake,   // The piece went somewhere else; the opener starts over
+    Complete,  // The last step was placed
+}
+
+/// Guides a player through an opener, one step at a time
+/// Held pieces may be placed out of order, so a step counts as soon as a piece of its type fills its cells
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenerTrainer {
+    opener: Opener,    // Opener being practiced
+    placed: Vec<bool>, // Which steps are done
+}
+
+impl OpenerTrainer {
+    pub fn new(opener: Opener) -> Self {
+        let placed = vec![false; opener.steps.len()];
+        Self { opener, placed }
+    }
+
+    pub fn opener(&self) -> &Opener {
+        &self.opener
+    }
+
+    /// Steps placed so far
+    pub fn progress(&self) -> usize {
+        self.placed.iter().filter(|&&placed| placed).count()
+    }
+
+    /// The first step still waiting for a piece of type `kind`, whose cells are outlined for the falling piece
+    pub fn target(&self, kind: TetrominoType) -> Option<&Step> {
+        self.opener.steps.iter().zip(&self.placed).find(|(step, &placed)| !placed && step.piece == kind).map(|(step, _)| step)
+    }
+
+    /// Checks a locked piece against the steps still to place
+    pub fn on_lock(&mut self, kind: TetrominoType, cells: &[(i32, i32)]) -> Placement {
+        let mut cells = cells.to_vec();
+        cells.sort_unstable();
+        let found = self.opener.steps.iter().zip(&self.placed).position(|(step, &placed)| !placed && step.piece == kind && step.cells == cells);
+        let Some(index) = found else {
+            return Placement::Mistake;
+        };
+        self.placed[index] = true;
+        if self.progress() == self.placed.len() {
+            Placement::Complete
+        } else {
+            Placement::Correct
+        }
+    }
+
+    /// Forgets the placed steps, to try the opener again
+    pub fn restart(&mut self) {
+        self.placed.fill(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::GameBoard;
+    use crate::game::{Game, Move};
+    use crate::garbage::TSpin;
+    use crate::events::GameEvent;
+
+    fn bundled() -> Vec<Opener> {
+        [include_str!("../openers/tsd_left.toml"), include_str!("../openers/tsd_right.toml")]
+            .iter()
+            .map(|text| Opener::from_toml(text).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_steps_are_checked() {
+        let step = |piece: &str, cells: &str| Opener::from_toml(&format!("name = \"x\"\n[[steps]]\npiece = \"{}\"\ncells = {}\n", piece, cells));
+        let opener = step("o", "[[0, 0], [1, 0], [0, 1], [1, 1]]").unwrap();
+        assert_eq!(opener.steps[0].piece, TetrominoType::O);
+        assert_eq!(opener.steps[0].cells, [(0, 18), (0, 19), (1, 18), (1, 19)]);
+
+        assert!(step("Q", "[[0, 0], [1, 0], [0, 1], [1, 1]]").is_err());
+        assert!(step("O", "[[0, 0], [1, 0], [2, 0], [3, 0]]").is_err(), "an I shape isn't an O");
+        assert!(step("I", "[[7, 0], [8, 0], [9, 0], [10, 0]]").is_err(), "off the board");
+        assert!(step("S", "[[1, 2], [1, 3], [0, 3], [0, 4]]").is_ok(), "a standing S");
+    }
+
+    #[test]
+    fn test_trainer_follows_steps() {
+        let opener = bundled().remove(0);
+        let mut trainer = OpenerTrainer::new(opener.clone());
+        let first = &opener.steps[0];
+        assert_eq!(trainer.target(first.piece), Some(first));
+
+        // Any cell order is accepted, and a placed step isn't offered again
+        let reversed: Vec<_> = first.cells.iter().rev().copied().collect();
+        assert_eq!(trainer.on_lock(first.piece, &reversed), Placement::Correct);
+        assert_eq!(trainer.progress(), 1);
+        assert_ne!(trainer.target(first.piece), Some(first));
+
+        assert_eq!(trainer.on_lock(TetrominoType::O, &[(0, 0), (1, 0), (0, 1), (1, 1)]), Placement::Mistake);
+        trainer.restart();
+        assert_eq!(trainer.progress(), 0);
+    }
+
+    #[test]
+    fn test_bundled_openers_end_in_a_t_spin_double() {
+        for opener in bundled() {
+            let mut game = Game::with_seed(1);
+            game.deal_sequence(&opener.sequence());
+            let mut trainer = OpenerTrainer::new(opener.clone());
+
+            // Every piece but the T is dropped straight into its place
+            let (setup, last) = opener.steps.split_at(opener.steps.len() - 1);
+            for step in setup {
+                for &(x, y) in &step.cells {
+                    game.board_mut()[y as usize][x as usize] = step.piece.color();
+                }
+                assert_ne!(trainer.on_lock(step.piece, &step.cells), Placement::Mistake);
+            }
+
+            // The T stands up beside the slot, drops and turns clockwise into it
+            let spin = &last[0];
+            assert_eq!(spin.piece, TetrominoType::T);
+            let center = spin.cells.iter().map(|&(x, _)| x).min().unwrap() + 1;
+            let left = |game: &Game| GameBoard::piece_cells(game.current_piece().unwrap()).iter().map(|&(x, _)| x).min().unwrap();
+            game.set_current_piece(Tetromino::new(TetrominoType::T));
+            game.apply(Move::RotateCw);
+            while left(&game) != center {
+                assert!(game.apply(if left(&game) < center { Move::Right } else { Move::Left }).moved);
+            }
+            while game.apply(Move::SoftDrop).moved {}
+            assert!(game.apply(Move::RotateCw).moved, "{}: the T turns into the slot", opener.name);
+            let result = game.apply(Move::HardDrop);
+            let locked = result.events.iter().find_map(|event| match event {
+                GameEvent::PieceLocked(locked) => Some(locked.clone()),
+                _ => None,
+            });
+            let locked = locked.unwrap();
+            assert_eq!(locked.t_spin, TSpin::Full, "{}", opener.name);
+            assert_eq!(locked.full_rows.len(), 2, "{}", opener.name);
+            assert_eq!(trainer.on_lock(TetrominoType::T, &locked.cells), Placement::Complete, "{}", opener.name);
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use crate::board::GameBoard;
+use crate::events::GameEvent;
+use crate::game::Game;
+use ggez::graphics::Color;
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long a slow overlay client may hold up a write before it is dropped
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The falling piece, for overlays that draw it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PieceState {
+    pub kind: char,              // Letter of the piece
+    pub color: String,           // `#rrggbb`
+    pub cells: Vec<(i32, i32)>,  // Board cells it covers, as (column, row)
+}
+
+/// What stream overlays are sent whenever something happens in the game, as one JSON object
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OverlayState {
+    pub mode: String,                      // Name of the game mode
+    pub score: u32,
+    pub level: u32,
+    pub lines: u32,
+    pub time: f64,                         // Seconds played
+    pub board: Vec<Vec<Option<String>>>,   // Locked cells as `#rrggbb`, rows top first, null for empty
+    pub piece: Option<PieceState>,         // The falling piece
+    pub hold: Option<char>,                // Piece in the hold box
+    pub next: Vec<char>,                   // Upcoming pieces, the next one first
+    pub events: Vec<&'static str>,         // What just happened, for overlays that animate it
+    pub game_over: bool,
+}
+
+fn hex(color: Color) -> String {
+    let (r, g, b) = color.to_rgb();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Name an overlay sees for an event
+fn event_name(event: &GameEvent) -> &'static str {
+    match event {
+        GameEvent::PieceSpawned(_) => "piece_spawned",
+        GameEvent::PieceMoved => "piece_moved",
+        GameEvent::PieceRotated => "piece_rotated",
+        GameEvent::PieceHeld => "piece_held",
+        GameEvent::PieceLocked(_) => "piece_locked",
+        GameEvent::LinesCleared { lines: 4, .. } => "tetris",
+        GameEvent::LinesCleared { .. } => "lines_cleared",
+        GameEvent::LevelUp(_) => "level_up",
+        GameEvent::AttackSent(_) => "attack_sent",
+        GameEvent::GarbageRose(_) => "garbage_rose",
+        GameEvent::GameOver => "game_over",
+    }
+}
+
+impl OverlayState {
+    /// The game as it stands after `events`
+    pub fn capture(game: &Game, mode: &str, time: f64, events: &[GameEvent]) -> Self {
+        let board = game
+            .board()
+            .rows()
+            .iter()
+            .map(|row| row.iter().map(|&color| (color != Color::BLACK).then(|| hex(color))).collect())
+            .collect();
+        let piece = game.current_piece().map(|piece| PieceState {
+            kind: piece.kind.letter(),
+            color: hex(piece.color),
+            cells: GameBoard::piece_cells(piece),
+        });
+        Self {
+            mode: mode.to_string(),
+            score: game.score(),
+            level: game.level(),
+            lines: game.lines_cleared(),
+            time,
+            board,
+            piece,
+            hold: game.hold_piece().map(|piece| piece.kind.letter()),
+            next: game.next_queue().iter().map(|piece| piece.kind.letter()).collect(),
+            events: events.iter().map(event_name).collect(),
+            game_over: game.is_game_over(),
+        }
+    }
+}
+
+/// The latest state and the overlays listening for the next one
+#[derive(Default)]
+struct Shared {
+    latest: String,                  // The last state published, as JSON
+    version: u64,                    // How many states were published
+    clients: Vec<(TcpStream, u64)>,  // Open event streams, with the version each has been sent
+}
+
+/// A local HTTP server for stream overlays: `GET /state` answers with the latest state as JSON, and
+/// `GET /events` is a Server-Sent Events stream of every state published after it, for `EventSource` in a browser source
+/// Requests are taken and states sent out on background threads, so a slow overlay never stalls the game
+pub struct OverlayServer {
+    address: SocketAddr,            // Where the server listens
+    shared: Arc<Mutex<Shared>>,     // The latest state and the open event streams, shared with the threads
+    sender: Sender<(u64, String)>,  // States for the broadcast thread to send out, with their versions
+    last: String,                   // The last state published, to skip sending it again
+}
+
+impl OverlayServer {
+    /// Listens on `port` of this machine only; port 0 picks a free one
+    pub fn start(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let address = listener.local_addr()?;
+        let shared = Arc::new(Mutex::new(Shared::default()));
+
+        let accepting = Arc::clone(&shared);
+        thread::spawn(move || {
+            // Each request is answered on its own thread, so an overlay that is slow to send its
+            // headers can't hold up the others
+            for stream in listener.incoming().flatten() {
+                let shared = Arc::clone(&accepting);
+                thread::spawn(move || serve(stream, &shared));
+            }
+        });
+
+        let (sender, states) = mpsc::channel::<(u64, String)>();
+        let broadcasting = Arc::clone(&shared);
+        thread::spawn(move || {
+            for (version, json) in states {
+                // Writing happens outside the lock, so new overlays can connect meanwhile; one that joined
+                // after this state was published was sent it on joining
+                let clients = std::mem::take(&mut broadcasting.lock().unwrap().clients);
+                let event = format!("data: {}\n\n", json);
+                let alive: Vec<(TcpStream, u64)> = clients
+                    .into_iter()
+                    .filter_map(|(mut client, seen)| {
+                        if seen >= version {
+                            return Some((client, seen));
+                        }
+                        client.write_all(event.as_bytes()).ok().map(|_| (client, version))
+                    })
+                    .collect();
+                broadcasting.lock().unwrap().clients.extend(alive);
+            }
+        });
+
+        Ok(Self { address, shared, sender, last: String::new() })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Sends the state to every overlay, unless it's the same as the last one
+    pub fn publish(&mut self, state: &OverlayState) {
+        let Ok(json) = serde_json::to_string(state) else {
+            return;
+        };
+        if json == self.last {
+            return;
+        }
+        let version = {
+            let mut shared = self.shared.lock().unwrap();
+            shared.latest = json.clone();
+            shared.version += 1;
+            shared.version
+        };
+        self.last = json.clone();
+        let _ = self.sender.send((version, json));
+    }
+}
+
+/// Answers one request: the state, the start of an event stream, or not found
+fn serve(stream: TcpStream, shared: &Mutex<Shared>) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // The headers are read to the end so closing the connection doesn't reset it under the response
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let mut stream = reader.into_inner();
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+    match path.split('?').next() {
+        Some("/state") => {
+            let json = shared.lock().unwrap().latest.clone();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nAccess-Control-Allow-Origin: *\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                json.len(),
+                json
+            )
+        }
+        Some("/events") => {
+            // The stream starts with the latest state; it's written outside the lock, so a slow overlay can't
+            // hold up publishing, and sent again if a newer one was published meanwhile, so none is missed
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nAccess-Control-Allow-Origin: *\r\n\r\n")?;
+            let mut sent = 0;
+            loop {
+                let (latest, version) = {
+                    let mut shared = shared.lock().unwrap();
+                    if shared.version == sent {
+                        shared.clients.push((stream, sent));
+                        return Ok(());
+                    }
+                    (shared.latest.clone(), shared.version)
+                };
+                write!(stream, "data: {}\n\n", latest)?;
+                sent = version;
+            }
+        }
+        _ => stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Move;
+    use std::io::Read;
+
+    #[test]
+    fn test_overlays_get_the_state_and_its_updates() {
+        let mut game = Game::with_seed(1);
+        let mut server = OverlayServer::start(0).unwrap();
+        server.publish(&OverlayState::capture(&game, "MARATHON", 0.0, &[]));
+
+        let mut events = TcpStream::connect(server.address()).unwrap();
+        events.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        events.write_all(b"GET /events HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut events = BufReader::new(events);
+        let mut next_state = || -> serde_json::Value {
+            let mut line = String::new();
+            while !line.starts_with("data: ") {
+                line.clear();
+                events.read_line(&mut line).unwrap();
+            }
+            serde_json::from_str(&line["data: ".len()..]).unwrap()
+        };
+        let first = next_state();
+        assert_eq!(first["mode"], "MARATHON");
+        assert_eq!(first["next"].as_array().unwrap().len(), game.next_queue().len());
+
+        // A lock is pushed to the stream, and the state answers with it too
+        let result = game.apply(Move::HardDrop);
+        server.publish(&OverlayState::capture(&game, "MARATHON", 1.0, &result.events));
+        let update = next_state();
+        assert_eq!(update["time"], 1.0, "the first state isn't sent twice");
+        assert!(update["events"].as_array().unwrap().contains(&"piece_locked".into()));
+        assert_eq!(update["board"].as_array().unwrap().iter().flat_map(|row| row.as_array().unwrap()).filter(|cell| !cell.is_null()).count(), 4);
+
+        // An overlay that connects and never sends its request doesn't hold up the others
+        let _silent = TcpStream::connect(server.address()).unwrap();
+        let asked = std::time::Instant::now();
+        let mut state = TcpStream::connect(server.address()).unwrap();
+        state.write_all(b"GET /state HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        state.read_to_string(&mut response).unwrap();
+        assert!(asked.elapsed() < CLIENT_TIMEOUT / 2);
+        let body = response.split_once("\r\n\r\n").unwrap().1;
+        assert_eq!(serde_json::from_str::<serde_json::Value>(body).unwrap(), update);
+    }
+}
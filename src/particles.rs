@@ -0,0 +1,184 @@
+use crate::camera::Camera;
+use crate::constants::{BLOCK_PADDING, DUST_PER_CELL, MAX_PARTICLES, PARTICLE_GRAVITY, SPARKS_PER_CELL};
+use ggez::graphics::{self, Color};
+use ggez::{Context, GameResult};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// A single square particle, positioned in logical screen pixels
+#[derive(Clone, Debug, PartialEq)]
+pub struct Particle {
+    pub x: f32,         // Horizontal position of the center
+    pub y: f32,         // Vertical position of the center
+    pub vx: f32,        // Horizontal speed in pixels per second
+    pub vy: f32,        // Vertical speed in pixels per second
+    pub gravity: f32,   // Downward acceleration in pixels per second squared
+    pub size: f32,      // Width and height in pixels
+    pub life: f32,      // Seconds left before the particle disappears
+    pub max_life: f32,  // Lifetime the particle started with
+    pub color: Color,   // Color at full opacity
+}
+
+impl Particle {
+    /// Opacity, fading out over the particle's lifetime
+    pub fn alpha(&self) -> f32 {
+        (self.life / self.max_life).clamp(0.0, 1.0)
+    }
+}
+
+/// Short-lived decorative particles drawn over the board
+/// Particles use their own random numbers so they never change the piece sequence
+pub struct ParticleSystem {
+    particles: Vec<Particle>,  // Live particles
+    rng: StdRng,               // Source of particle spread
+}
+
+impl Default for ParticleSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        Self {
+            particles: Vec::new(),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    /// Adds a particle, dropping it if the system is already full
+    pub fn spawn(&mut self, particle: Particle) {
+        if self.particles.len() < MAX_PARTICLES {
+            self.particles.push(particle);
+        }
+    }
+
+    /// Sparks bursting out of a cleared row, in the colors of the blocks that were in it
+    pub fn spawn_sparks(&mut self, camera: &Camera, row: usize, colors: &[Color]) {
+        for (x, &color) in colors.iter().enumerate() {
+            let [center_x, center_y] = camera.cell(x as f32 + 0.5, row as f32 + 0.5);
+            for _ in 0..SPARKS_PER_CELL {
+                let angle = self.rng.gen_range(0.0..std::f32::consts::TAU);
+                let speed = self.rng.gen_range(150.0..450.0);
+                let life = self.rng.gen_range(0.3..0.6);
+                let particle = Particle {
+                    x: center_x + self.rng.gen_range(-0.4..0.4) * camera.cell_size,
+                    y: center_y,
+                    vx: angle.cos() * speed,
+                    vy: angle.sin() * speed - 150.0,
+                    gravity: PARTICLE_GRAVITY,
+                    size: self.rng.gen_range(4.0..10.0),
+                    life,
+                    max_life: life,
+                    color,
+                };
+                self.spawn(particle);
+            }
+        }
+    }
+
+    /// A puff of dust under the bottom of a hard-dropped piece, given the board cells it landed on
+    pub fn spawn_dust(&mut self, camera: &Camera, cells: &[(i32, i32)]) {
+        // Only the lowest cell of each column touches what the piece landed on
+        for &(x, y) in cells {
+            if cells.iter().any(|&(other_x, other_y)| other_x == x && other_y > y) {
+                continue;
+            }
+            let [center_x, bottom] = camera.cell(x as f32 + 0.5, (y + 1) as f32);
+            let bottom = bottom - BLOCK_PADDING * camera.zoom();
+            for _ in 0..DUST_PER_CELL {
+                let life = self.rng.gen_range(0.25..0.45);
+                let particle = Particle {
+                    x: center_x + self.rng.gen_range(-0.5..0.5) * camera.cell_size,
+                    y: bottom,
+                    vx: self.rng.gen_range(-120.0..120.0),
+                    vy: self.rng.gen_range(-90.0..-20.0),
+                    gravity: PARTICLE_GRAVITY / 4.0,
+                    size: self.rng.gen_range(5.0..9.0),
+                    life,
+                    max_life: life,
+                    color: Color::new(0.8, 0.8, 0.8, 0.7),
+                };
+                self.spawn(particle);
+            }
+        }
+    }
+
+    /// Moves the particles and removes the ones that have faded out
+    pub fn update(&mut self, dt: f32) {
+        for particle in &mut self.particles {
+            particle.vy += particle.gravity * dt;
+            particle.x += particle.vx * dt;
+            particle.y += particle.vy * dt;
+            particle.life -= dt;
+        }
+        self.particles.retain(|particle| particle.life > 0.0);
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        if self.particles.is_empty() {
+            return Ok(());
+        }
+
+        // Build every particle into one mesh so they cost a single draw call
+        let mut builder = graphics::MeshBuilder::new();
+        for particle in &self.particles {
+            let half = particle.size / 2.0;
+            let mut color = particle.color;
+            color.a *= particle.alpha();
+            builder.rectangle(
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(particle.x - half, particle.y - half, particle.size, particle.size),
+                color,
+            )?;
+        }
+        let mesh = graphics::Mesh::from_data(ctx, builder.build());
+        canvas.draw(&mesh, graphics::DrawParam::default());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Layout;
+
+    #[test]
+    fn test_particles_fade_and_expire() {
+        let mut system = ParticleSystem::new();
+        system.spawn_sparks(&Layout::new().board_camera(), 19, &[Color::RED, Color::BLUE]);
+        assert_eq!(system.particles().len(), 2 * SPARKS_PER_CELL);
+
+        system.update(0.1);
+        assert!(system.particles().iter().all(|particle| particle.alpha() < 1.0));
+
+        system.update(1.0);
+        assert!(system.particles().is_empty());
+    }
+
+    #[test]
+    fn test_dust_only_under_lowest_cells() {
+        let mut system = ParticleSystem::new();
+        // A vertical I piece only touches the ground with its bottom cell
+        system.spawn_dust(&Layout::new().board_camera(), &[(4, 16), (4, 17), (4, 18), (4, 19)]);
+        assert_eq!(system.particles().len(), DUST_PER_CELL);
+    }
+
+    #[test]
+    fn test_spawn_is_capped() {
+        let mut system = ParticleSystem::new();
+        for _ in 0..100 {
+            system.spawn_sparks(&Layout::new().board_camera(), 0, &[Color::WHITE; 10]);
+        }
+        assert_eq!(system.particles().len(), MAX_PARTICLES);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::tetromino::TetrominoType;
+
+/// Line segments `[x1, y1, x2, y2]` of the glyph drawn on a piece's blocks when patterns are on
+/// Coordinates are fractions of the block, so the same glyph works at any block size;
+/// every piece gets a different shape so pieces can be told apart without color
+pub fn glyph(kind: TetrominoType) -> &'static [[f32; 4]] {
+    match kind {
+        // Horizontal bar
+        TetrominoType::I => &[[0.25, 0.5, 0.75, 0.5]],
+        // Hollow square
+        TetrominoType::O => &[
+            [0.3, 0.3, 0.7, 0.3],
+            [0.7, 0.3, 0.7, 0.7],
+            [0.7, 0.7, 0.3, 0.7],
+            [0.3, 0.7, 0.3, 0.3],
+        ],
+        // Upside-down T
+        TetrominoType::T => &[[0.25, 0.7, 0.75, 0.7], [0.5, 0.3, 0.5, 0.7]],
+        // Rising diagonal
+        TetrominoType::S => &[[0.28, 0.72, 0.72, 0.28]],
+        // Falling diagonal
+        TetrominoType::Z => &[[0.28, 0.28, 0.72, 0.72]],
+        // Vertical bar
+        TetrominoType::J => &[[0.5, 0.25, 0.5, 0.75]],
+        // Cross
+        TetrominoType::L => &[[0.28, 0.28, 0.72, 0.72], [0.28, 0.72, 0.72, 0.28]],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyphs_are_distinct_and_inside_the_block() {
+        for (i, &kind) in TetrominoType::ALL.iter().enumerate() {
+            let segments = glyph(kind);
+            assert!(!segments.is_empty());
+            assert!(segments.iter().flatten().all(|&v| (0.0..=1.0).contains(&v)));
+            for &other in &TetrominoType::ALL[i + 1..] {
+                assert_ne!(segments, glyph(other), "{:?} and {:?} share a glyph", kind, other);
+            }
+        }
+    }
+}
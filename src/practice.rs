@@ -0,0 +1,187 @@
+use crate::board::{GameBoard, Playfield};
+use crate::constants::{GRID_HEIGHT, GRID_WIDTH};
+use crate::garbage::{self, TSpin};
+use crate::rotation::RotationSystem;
+use crate::tetromino::{Tetromino, TetrominoType};
+use ggez::graphics::Color;
+use std::collections::{HashSet, VecDeque};
+
+/// How often pieces locked on each cell of the board over a session of practice games
+/// Players look at it to find habits in their stacking, like always building up one side
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heatmap {
+    counts: [[u32; GRID_WIDTH as usize]; GRID_HEIGHT as usize],  // Locks on each cell, by row then column
+    pieces: u32,                                                  // Pieces recorded
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self {
+            counts: [[0; GRID_WIDTH as usize]; GRID_HEIGHT as usize],
+            pieces: 0,
+        }
+    }
+}
+
+impl Heatmap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts the cells a piece locked on; cells above the board are left out
+    pub fn record(&mut self, cells: &[(i32, i32)]) {
+        for &(x, y) in cells {
+            if (0..GRID_WIDTH).contains(&x) && (0..GRID_HEIGHT).contains(&y) {
+                self.counts[y as usize][x as usize] += 1;
+            }
+        }
+        self.pieces += 1;
+    }
+
+    /// Times a piece locked on a cell
+    pub fn count(&self, x: usize, y: usize) -> u32 {
+        self.counts[y][x]
+    }
+
+    pub fn pieces(&self) -> u32 {
+        self.pieces
+    }
+
+    /// A cell's count against the busiest cell's, from 0.0 (never used) to 1.0
+    pub fn heat(&self, x: usize, y: usize) -> f32 {
+        let busiest = self.counts.iter().flatten().copied().max().unwrap_or(0);
+        if busiest == 0 {
+            0.0
+        } else {
+            self.counts[y][x] as f32 / busiest as f32
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Color a cell with `heat` is shaded in: clear for unused cells, then from blue through yellow to red
+/// `opacity` is the opacity of the hottest cells
+pub fn heat_color(heat: f32, opacity: f32) -> Color {
+    let heat = heat.clamp(0.0, 1.0);
+    if heat == 0.0 {
+        return Color::new(0.0, 0.0, 0.0, 0.0);
+    }
+    let (r, g, b) = if heat < 0.5 {
+        let t = heat * 2.0;
+        (t, t, 1.0 - t)
+    } else {
+        let t = (heat - 0.5) * 2.0;
+        (1.0, 1.0 - t, 0.0)
+    };
+    Color::new(r, g, b, opacity * (0.3 + 0.7 * heat))
+}
+
+/// A spot where a T turned in as its last move would be a full T-spin clearing lines
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TSlot {
+    pub cells: Vec<(i32, i32)>,  // Board cells the T would fill, sorted
+    pub lines: usize,            // Rows it would clear: 1 for a T-spin single, 2 for a double
+}
+
+/// Finds the T-spin singles and doubles on the board, by searching every place a T can get to from its spawn
+/// with `system`'s moves and turns, and keeping the resting spots it can turn into that pass the three-corner rule
+/// Doubles come before singles
+pub fn t_slots(board: &GameBoard, system: &dyn RotationSystem) -> Vec<TSlot> {
+    let key = |piece: &Tetromino| (piece.position.x as i32, piece.position.y as i32, piece.rotation % 4);
+    let start = system.spawn(TetrominoType::T);
+    if board.collides(&start) {
+        return Vec::new();
+    }
+    let mut seen = HashSet::from([key(&start)]);
+    let mut queue = VecDeque::from([start]);
+    let mut slots: Vec<TSlot> = Vec::new();
+    while let Some(piece) = queue.pop_front() {
+        let moves = [board.try_move(&piece, -1, 0), board.try_move(&piece, 1, 0), board.try_move(&piece, 0, 1)];
+        for next in moves.into_iter().flatten() {
+            if seen.insert(key(&next)) {
+                queue.push_back(next);
+            }
+        }
+        let Some(turned) = system.rotate(board, &piece) else {
+            continue;
+        };
+        if board.try_move(&turned, 0, 1).is_none() && garbage::detect_t_spin(board, &turned, true) == TSpin::Full {
+            let mut after = board.clone();
+            after.lock(&turned);
+            let mut cells = GameBoard::piece_cells(&turned);
+            let lines = after.full_rows().into_iter().filter(|&row| cells.iter().any(|&(_, y)| y == row as i32)).count();
+            cells.sort_unstable();
+            if (1..=2).contains(&lines) && !slots.iter().any(|slot| slot.cells == cells) {
+                slots.push(TSlot { cells, lines });
+            }
+        }
+        if seen.insert(key(&turned)) {
+            queue.push_back(turned);
+        }
+    }
+    slots.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.cells.cmp(&b.cells)));
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rotation::SRS;
+
+    #[test]
+    fn test_heatmap_counts_locked_cells() {
+        let mut heatmap = Heatmap::new();
+        assert_eq!(heatmap.heat(0, 0), 0.0);
+
+        heatmap.record(&[(0, 19), (1, 19), (2, 19), (3, 19)]);
+        heatmap.record(&[(0, 19), (0, 18), (0, -1), (0, 17)]);
+        assert_eq!(heatmap.pieces(), 2);
+        assert_eq!(heatmap.count(0, 19), 2);
+        assert_eq!(heatmap.count(3, 19), 1);
+        assert_eq!(heatmap.heat(0, 19), 1.0);
+        assert_eq!(heatmap.heat(1, 19), 0.5);
+        assert_eq!(heatmap.heat(9, 0), 0.0);
+
+        heatmap.clear();
+        assert_eq!(heatmap, Heatmap::new());
+    }
+
+    #[test]
+    fn test_t_slots_are_found_under_overhangs() {
+        let mut board = GameBoard::new();
+        assert!(t_slots(&board, &SRS).is_empty());
+
+        // Two full rows but for a T-shaped gap in columns 3 to 5, roofed over on the left
+        let fill = |board: &mut GameBoard, cells: &[(usize, usize)]| {
+            for &(x, y) in cells {
+                board[y][x] = Color::WHITE;
+            }
+        };
+        for x in (0..GRID_WIDTH as usize).filter(|&x| x != 4) {
+            fill(&mut board, &[(x, 19)]);
+        }
+        for x in (0..GRID_WIDTH as usize).filter(|x| !(3..=5).contains(x)) {
+            fill(&mut board, &[(x, 18)]);
+        }
+        assert!(t_slots(&board, &SRS).is_empty(), "an open slot is filled by a plain drop");
+
+        fill(&mut board, &[(0, 17), (1, 17), (2, 17), (3, 17)]);
+        // The T fits flat for a double, or standing against the roof for a single
+        let slots = t_slots(&board, &SRS);
+        assert_eq!(slots, [
+            TSlot { cells: vec![(3, 18), (4, 18), (4, 19), (5, 18)], lines: 2 },
+            TSlot { cells: vec![(3, 18), (4, 17), (4, 18), (4, 19)], lines: 1 },
+        ]);
+    }
+
+    #[test]
+    fn test_heat_colors() {
+        assert_eq!(heat_color(0.0, 1.0).a, 0.0);
+        assert_eq!(heat_color(1.0, 0.5), Color::new(1.0, 0.0, 0.0, 0.5));
+        assert_eq!(heat_color(0.5, 1.0).to_rgb(), Color::new(1.0, 1.0, 0.0, 1.0).to_rgb());
+        assert!(heat_color(0.1, 1.0).b > heat_color(0.1, 1.0).r);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::config;
+use crate::constants::PROFILES_FILE;
+use crate::high_scores::HighScoreEntry;
+use crate::keybindings::{Handedness, KeyBindings};
+use crate::mode::Handicap;
+use crate::stats::{LifetimeStats, Stats};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Longest profile name, the same as a high score name
+pub const MAX_NAME_LENGTH: usize = 15;
+
+/// Most recent games kept in a profile's score history
+pub const HISTORY_LENGTH: usize = 20;
+
+/// Full path of the profiles file
+pub fn profiles_path() -> PathBuf {
+    config::config_dir().join(PROFILES_FILE)
+}
+
+/// A player with their own controls, totals and recent games
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub handedness: Handedness,        // Hand the controls were laid out for
+    #[serde(default)]
+    pub controls: KeyBindings,         // Keys this player plays with
+    #[serde(default)]
+    pub handicap: Handicap,            // Harder rules this player takes on in versus games
+    #[serde(default)]
+    pub stats: LifetimeStats,          // Totals over all the player's games
+    #[serde(default)]
+    pub history: Vec<HighScoreEntry>,  // Most recent games first, at most `HISTORY_LENGTH`
+}
+
+impl Profile {
+    /// A new profile with the controls laid out for the hand
+    pub fn new(name: String, handedness: Handedness) -> Self {
+        Self {
+            name,
+            handedness,
+            controls: KeyBindings::for_hand(handedness),
+            handicap: Handicap::default(),
+            stats: LifetimeStats::default(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Adds a finished game to the totals and the history
+    pub fn record_game(&mut self, entry: HighScoreEntry, stats: &Stats, completed: bool) {
+        self.stats.record(&entry, stats, completed);
+        self.history.insert(0, entry);
+        self.history.truncate(HISTORY_LENGTH);
+    }
+}
+
+/// Why a profile couldn't be created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileError {
+    EmptyName,  // The name is blank
+    NameTaken,  // Another profile has the name, ignoring case
+}
+
+/// Every profile on this machine, and which one is playing
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileStore {
+    pub profiles: Vec<Profile>,  // In the order they were created
+    pub last: Option<String>,    // Name of the profile chosen last, offered first at startup
+    #[serde(skip)]
+    active: Option<usize>,       // Index of the profile playing now, `None` for a guest
+}
+
+impl ProfileStore {
+    /// Loads the profiles from a file, starting with none if it is missing or invalid
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Saves the profiles to a file, creating its directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Index of the profile chosen last, if it still exists
+    pub fn last_index(&self) -> Option<usize> {
+        let last = self.last.as_ref()?;
+        self.profiles.iter().position(|profile| &profile.name == last)
+    }
+
+    /// Adds a profile and returns its index
+    pub fn create(&mut self, name: &str, handedness: Handedness) -> Result<usize, ProfileError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ProfileError::EmptyName);
+        }
+        if self.profiles.iter().any(|profile| profile.name.eq_ignore_ascii_case(name)) {
+            return Err(ProfileError::NameTaken);
+        }
+        self.profiles.push(Profile::new(name.to_string(), handedness));
+        Ok(self.profiles.len() - 1)
+    }
+
+    /// Makes a profile the one playing, or nobody with `None`; it is offered first next time
+    pub fn select(&mut self, index: Option<usize>) {
+        self.active = index.filter(|&index| index < self.profiles.len());
+        if let Some(profile) = self.active() {
+            self.last = Some(profile.name.clone());
+        }
+    }
+
+    /// The profile playing now, `None` for a guest
+    pub fn active(&self) -> Option<&Profile> {
+        self.active.map(|index| &self.profiles[index])
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut Profile> {
+        self.active.map(|index| &mut self.profiles[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garbage::TSpin;
+    use crate::keybindings::Action;
+    use ggez::input::keyboard::KeyCode;
+
+    #[test]
+    fn test_create_and_select() {
+        let mut store = ProfileStore::default();
+        assert_eq!(store.create("  ", Handedness::Right), Err(ProfileError::EmptyName));
+        assert_eq!(store.create("Alex", Handedness::Left), Ok(0));
+        assert_eq!(store.create("ALEX", Handedness::Right), Err(ProfileError::NameTaken));
+        assert_eq!(store.create(" Sam ", Handedness::Right), Ok(1));
+        assert_eq!(store.profiles[1].name, "Sam");
+        assert!(store.active().is_none());
+
+        store.select(Some(0));
+        assert_eq!(store.active().unwrap().controls.action_for(KeyCode::A), Some(Action::MoveLeft));
+        assert_eq!(store.last_index(), Some(0));
+
+        // Playing as a guest keeps the last profile for next time
+        store.select(None);
+        assert!(store.active().is_none());
+        assert_eq!(store.last_index(), Some(0));
+    }
+
+    #[test]
+    fn test_record_game_keeps_recent_history() {
+        let mut profile = Profile::new("Alex".to_string(), Handedness::Right);
+        let mut stats = Stats::new();
+        for _ in 0..7 {
+            stats.on_lock(0, TSpin::None, 0);
+        }
+        for score in 0..HISTORY_LENGTH as u32 + 5 {
+            let entry = HighScoreEntry { lines: 2, duration: 10.0, ..HighScoreEntry::new("Alex".to_string(), score) };
+            profile.record_game(entry, &stats, false);
+        }
+        let games = HISTORY_LENGTH as u32 + 5;
+        assert_eq!(profile.stats.games, games);
+        assert_eq!(profile.stats.lines, games * 2);
+        assert_eq!(profile.stats.pieces, games * 7);
+        assert_eq!(profile.stats.best_score, games - 1);
+        assert_eq!(profile.history.len(), HISTORY_LENGTH);
+        assert_eq!(profile.history[0].score, games - 1, "the newest game comes first");
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("tetris-profiles-test-{}", std::process::id()));
+        let path = dir.join(PROFILES_FILE);
+        let mut store = ProfileStore::default();
+        store.create("Alex", Handedness::Left).unwrap();
+        store.select(Some(0));
+        store.active_mut().unwrap().controls.bind(Action::HardDrop, KeyCode::X);
+        store.save(&path).unwrap();
+
+        // The active profile isn't saved, only the one to offer first
+        let loaded = ProfileStore::load(&path);
+        assert!(loaded.active().is_none());
+        assert_eq!(loaded.last_index(), Some(0));
+        assert_eq!(loaded.profiles, store.profiles);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(ProfileStore::load(&path), ProfileStore::default());
+    }
+}
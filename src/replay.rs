@@ -0,0 +1,230 @@
+use crate::board::GameBoard;
+use crate::constants::{REPLAY_FRAME_TIME, REPLAY_HOLD_TIME, REPLAY_LENGTH, REPLAY_SPEED};
+use crate::game::Game;
+use ggez::graphics::Color;
+use std::collections::VecDeque;
+
+/// The board at one moment of a game, with the falling piece drawn in
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub time: f64,                // Seconds into the game
+    pub cells: Vec<Vec<Color>>,   // Board rows, top first, black for empty cells
+}
+
+impl Frame {
+    pub fn capture(time: f64, game: &Game) -> Self {
+        let mut cells = game.board().rows().to_vec();
+        if let Some(piece) = game.current_piece() {
+            for (x, y) in GameBoard::piece_cells(piece) {
+                if let Some(cell) = cells.get_mut(y as usize).and_then(|row| row.get_mut(x as usize)) {
+                    *cell = piece.color;
+                }
+            }
+        }
+        Self { time, cells }
+    }
+}
+
+/// The last `REPLAY_LENGTH` seconds of a game, sampled every `REPLAY_FRAME_TIME` seconds
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReplayBuffer {
+    frames: VecDeque<Frame>,  // Oldest first
+    due: f64,                 // Game time the next frame is recorded at
+}
+
+impl ReplayBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps the game's board if a frame is due, forgetting frames that have fallen out of the window
+    pub fn record(&mut self, time: f64, game: &Game) {
+        if time < self.due {
+            return;
+        }
+        // Frames stay on a steady beat unless the game went a whole frame without recording
+        self.due = if time - self.due > REPLAY_FRAME_TIME { time } else { self.due } + REPLAY_FRAME_TIME;
+        self.capture(time, game);
+    }
+
+    /// Keeps the game's board whether or not a frame is due, as for the moment a game ends
+    pub fn capture(&mut self, time: f64, game: &Game) {
+        self.frames.push_back(Frame::capture(time, game));
+        while self.frames.front().is_some_and(|first| time - first.time > REPLAY_LENGTH) {
+            self.frames.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// A player for the frames kept so far, or `None` before anything was recorded
+    pub fn play(&self) -> Option<ReplayPlayer> {
+        (!self.frames.is_empty()).then(|| ReplayPlayer::new(self.frames.iter().cloned().collect()))
+    }
+}
+
+/// Plays recorded frames back at `REPLAY_SPEED`, holding the last one for `REPLAY_HOLD_TIME` before starting over
+/// A segment of the frames can be marked while they play, to be saved as a clip
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayPlayer {
+    frames: Vec<Frame>,  // Oldest first, never empty
+    time: f64,           // Game seconds played since the first frame
+    start: usize,        // First frame of the marked segment
+    end: usize,          // Last frame of the marked segment
+}
+
+impl ReplayPlayer {
+    fn new(frames: Vec<Frame>) -> Self {
+        let end = frames.len() - 1;
+        Self { frames, time: 0.0, start: 0, end }
+    }
+
+    /// Game seconds from the first frame to the last
+    pub fn length(&self) -> f64 {
+        self.frames[self.frames.len() - 1].time - self.frames[0].time
+    }
+
+    /// Moves the playback on by `dt` real seconds
+    pub fn update(&mut self, dt: f64) {
+        self.time += dt * REPLAY_SPEED;
+        if self.time > self.length() + REPLAY_HOLD_TIME * REPLAY_SPEED {
+            self.time = 0.0;
+        }
+    }
+
+    /// Index of the frame on screen: the last one recorded at or before the playback time
+    fn shown(&self) -> usize {
+        let now = self.frames[0].time + self.time;
+        self.frames.partition_point(|frame| frame.time <= now).max(1) - 1
+    }
+
+    pub fn frame(&self) -> &Frame {
+        &self.frames[self.shown()]
+    }
+
+    /// Starts the marked segment at the frame on screen, moving its end along if it was earlier
+    pub fn mark_start(&mut self) {
+        self.start = self.shown();
+        self.end = self.end.max(self.start);
+    }
+
+    /// Ends the marked segment at the frame on screen, moving its start back if it was later
+    pub fn mark_end(&mut self) {
+        self.end = self.shown();
+        self.start = self.start.min(self.end);
+    }
+
+    /// The frames of the marked segment, all of them until a segment is marked
+    pub fn segment(&self) -> &[Frame] {
+        &self.frames[self.start..=self.end]
+    }
+
+    /// Where the marked segment starts and ends in the recording, each from 0.0 to 1.0
+    pub fn segment_bounds(&self) -> (f32, f32) {
+        let length = self.length();
+        if length <= 0.0 {
+            return (0.0, 1.0);
+        }
+        let at = |i: usize| ((self.frames[i].time - self.frames[0].time) / length) as f32;
+        (at(self.start), at(self.end))
+    }
+
+    /// How far through the recording the playback is, from 0.0 to 1.0
+    pub fn progress(&self) -> f32 {
+        let length = self.length();
+        if length <= 0.0 {
+            1.0
+        } else {
+            (self.time / length).min(1.0) as f32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Move;
+
+    #[test]
+    fn test_buffer_keeps_the_last_seconds() {
+        let game = Game::with_seed(1);
+        let mut buffer = ReplayBuffer::new();
+        assert!(buffer.play().is_none());
+
+        let mut time = 0.0;
+        while time < REPLAY_LENGTH * 3.0 {
+            buffer.record(time, &game);
+            time += REPLAY_FRAME_TIME / 4.0;
+        }
+        let frames = (REPLAY_LENGTH / REPLAY_FRAME_TIME).round() as usize;
+        assert!(buffer.len().abs_diff(frames) <= 1, "{} frames kept, {} expected", buffer.len(), frames);
+        assert!(buffer.frames.front().unwrap().time >= time - REPLAY_LENGTH - REPLAY_FRAME_TIME);
+
+        // The falling piece is drawn into the frame
+        let piece = game.current_piece().unwrap();
+        let (x, y) = GameBoard::piece_cells(piece)[0];
+        assert_eq!(buffer.frames.back().unwrap().cells[y as usize][x as usize], piece.color);
+
+        buffer.clear();
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_player_plays_slowly_and_loops() {
+        let mut game = Game::with_seed(1);
+        let mut buffer = ReplayBuffer::new();
+        buffer.capture(0.0, &game);
+        game.apply(Move::HardDrop);
+        buffer.capture(1.0, &game);
+
+        let mut player = buffer.play().unwrap();
+        assert_eq!(player.length(), 1.0);
+        assert_eq!(player.frame().time, 0.0);
+
+        // A second of game time takes longer than a second to play
+        player.update(1.0);
+        assert_eq!(player.frame().time, 0.0);
+        player.update(1.0 / REPLAY_SPEED - 1.0);
+        assert_eq!(player.frame().time, 1.0);
+        assert_eq!(player.progress(), 1.0);
+
+        // The last frame is held, then the replay starts over
+        player.update(REPLAY_HOLD_TIME + 0.1);
+        assert_eq!(player.frame().time, 0.0);
+    }
+
+    #[test]
+    fn test_segment_is_marked_at_the_frame_on_screen() {
+        let game = Game::with_seed(1);
+        let mut buffer = ReplayBuffer::new();
+        for time in [0.0, 1.0, 2.0, 3.0] {
+            buffer.capture(time, &game);
+        }
+        let mut player = buffer.play().unwrap();
+        assert_eq!(player.segment().len(), 4);
+        assert_eq!(player.segment_bounds(), (0.0, 1.0));
+
+        player.update(1.0 / REPLAY_SPEED);
+        player.mark_start();
+        player.update(1.0 / REPLAY_SPEED);
+        player.mark_end();
+        let times: Vec<f64> = player.segment().iter().map(|frame| frame.time).collect();
+        assert_eq!(times, [1.0, 2.0]);
+
+        // A start after the end takes the end with it
+        player.update(1.0 / REPLAY_SPEED);
+        player.mark_start();
+        assert_eq!(player.segment().len(), 1);
+        assert_eq!(player.segment_bounds(), (1.0, 1.0));
+    }
+}
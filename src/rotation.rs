@@ -0,0 +1,381 @@
+use crate::board::Playfield;
+use crate::tetromino::{Tetromino, TetrominoType};
+use glam::Vec2;
+use std::fmt::Debug;
+
+/// How pieces spawn, turn and kick off walls and blocks
+/// Every orientation is a square box with the piece drawn in it; a piece on the board only keeps
+/// the cells it covers, so rotating moves it by the difference between the two boxes' margins
+pub trait RotationSystem: Debug + Sync {
+    /// Lowercase name stored in the settings file
+    fn name(&self) -> &'static str;
+
+    /// Box of a piece in one orientation: 0 spawn, then one more for each clockwise turn
+    fn state(&self, kind: TetrominoType, rotation: u8) -> Vec<Vec<bool>>;
+
+    /// Offsets tried in order when a clockwise turn from `from` doesn't fit in place; y grows downwards
+    fn kicks(&self, kind: TetrominoType, from: u8) -> &'static [(i32, i32)];
+
+    /// Whether kicks may be tried for a turn into `to` whose box has its top left corner at `origin`
+    fn can_kick(&self, _board: &dyn Playfield, _kind: TetrominoType, _to: u8, _origin: (i32, i32)) -> bool {
+        true
+    }
+
+    /// A new piece in its spawn orientation at the top of the board
+    /// The orientation's whole box is centered, so the piece turns about the middle of the board
+    fn spawn(&self, kind: TetrominoType) -> Tetromino {
+        let state = self.state(kind, 0);
+        let (shape, (left, _)) = trim(&state);
+        let mut piece = Tetromino::new(kind);
+        piece.shape = shape;
+        piece.position.x = Tetromino::spawn_column(state[0].len()) + left as f32;
+        piece
+    }
+
+    /// Returns the piece turned clockwise, kicked if needed, or `None` if no kick fits
+    fn rotate(&self, board: &dyn Playfield, piece: &Tetromino) -> Option<Tetromino> {
+        let from = piece.rotation % 4;
+        let to = (from + 1) % 4;
+        let (_, (old_x, old_y)) = trim(&self.state(piece.kind, from));
+        let (shape, (new_x, new_y)) = trim(&self.state(piece.kind, to));
+
+        let mut rotated = piece.clone();
+        rotated.shape = shape;
+        rotated.rotation = to;
+        rotated.position += Vec2::new((new_x - old_x) as f32, (new_y - old_y) as f32);
+        if !board.collides(&rotated) {
+            return Some(rotated);
+        }
+
+        let origin = (rotated.position.x as i32 - new_x, rotated.position.y as i32 - new_y);
+        if !self.can_kick(board, piece.kind, to, origin) {
+            return None;
+        }
+        self.kicks(piece.kind, from)
+            .iter()
+            .find_map(|&(dx, dy)| board.try_move(&rotated, dx, dy))
+    }
+}
+
+/// The Super Rotation System of current games: pieces spawn flat side down and kick up to five ways
+#[derive(Debug)]
+pub struct Srs;
+
+/// Arika's rotation from the TGM games: pieces spawn flat side up and only kick one column sideways,
+/// except that a lying I kicks up off the floor or stack to stand, as in TGM3
+#[derive(Debug)]
+pub struct Ars;
+
+/// Nintendo-style rotation with no kicks at all; a turn that doesn't fit in place fails
+#[derive(Debug)]
+pub struct Classic;
+
+pub static SRS: Srs = Srs;
+pub static ARS: Ars = Ars;
+pub static CLASSIC: Classic = Classic;
+
+/// Every rotation system, in the order the settings screen cycles through them
+pub static SYSTEMS: [&dyn RotationSystem; 3] = [&SRS, &ARS, &CLASSIC];
+
+/// The rotation system with the given name, or SRS if there is none
+pub fn by_name(name: &str) -> &'static dyn RotationSystem {
+    SYSTEMS
+        .iter()
+        .copied()
+        .find(|system| system.name() == name)
+        .unwrap_or(&SRS)
+}
+
+/// SRS kicks for J, L, S, T and Z pieces, by starting orientation
+const SRS_KICKS: [[(i32, i32); 4]; 4] = [
+    [(-1, 0), (-1, -1), (0, 2), (-1, 2)],
+    [(1, 0), (1, 1), (0, -2), (1, -2)],
+    [(1, 0), (1, -1), (0, 2), (1, 2)],
+    [(-1, 0), (-1, 1), (0, -2), (-1, -2)],
+];
+
+/// SRS kicks for the I piece, by starting orientation
+const SRS_I_KICKS: [[(i32, i32); 4]; 4] = [
+    [(-2, 0), (1, 0), (-2, 1), (1, -2)],
+    [(-1, 0), (2, 0), (-1, -2), (2, 1)],
+    [(2, 0), (-1, 0), (2, -1), (-1, 2)],
+    [(1, 0), (-2, 0), (1, 2), (-2, -1)],
+];
+
+/// ARS kicks: one column right, then one column left
+const ARS_KICKS: [(i32, i32); 2] = [(1, 0), (-1, 0)];
+
+/// ARS floor kicks for an I standing up: one row up, then two
+const ARS_I_FLOOR_KICKS: [(i32, i32); 2] = [(0, -1), (0, -2)];
+
+impl RotationSystem for Srs {
+    fn name(&self) -> &'static str {
+        "srs"
+    }
+
+    fn state(&self, kind: TetrominoType, rotation: u8) -> Vec<Vec<bool>> {
+        let spawn = match kind {
+            TetrominoType::I => ["....", "####", "....", "...."].as_slice(),
+            TetrominoType::O => &["##", "##"],
+            TetrominoType::T => &[".#.", "###", "..."],
+            TetrominoType::S => &[".##", "##.", "..."],
+            TetrominoType::Z => &["##.", ".##", "..."],
+            TetrominoType::J => &["#..", "###", "..."],
+            TetrominoType::L => &["..#", "###", "..."],
+        };
+        let mut state = parse(spawn);
+        for _ in 0..rotation % 4 {
+            state = turn(&state);
+        }
+        state
+    }
+
+    fn kicks(&self, kind: TetrominoType, from: u8) -> &'static [(i32, i32)] {
+        match kind {
+            TetrominoType::O => &[],
+            TetrominoType::I => &SRS_I_KICKS[from as usize % 4],
+            _ => &SRS_KICKS[from as usize % 4],
+        }
+    }
+}
+
+impl RotationSystem for Ars {
+    fn name(&self) -> &'static str {
+        "ars"
+    }
+
+    fn state(&self, kind: TetrominoType, rotation: u8) -> Vec<Vec<bool>> {
+        let states: [&[&str]; 4] = match kind {
+            TetrominoType::I => [
+                &["....", "####", "....", "...."],
+                &["..#.", "..#.", "..#.", "..#."],
+                &["....", "####", "....", "...."],
+                &["..#.", "..#.", "..#.", "..#."],
+            ],
+            TetrominoType::O => [&["##", "##"]; 4],
+            TetrominoType::T => [
+                &["...", "###", ".#."],
+                &[".#.", "##.", ".#."],
+                &["...", ".#.", "###"],
+                &[".#.", ".##", ".#."],
+            ],
+            TetrominoType::S => [
+                &["...", ".##", "##."],
+                &["#..", "##.", ".#."],
+                &["...", ".##", "##."],
+                &["#..", "##.", ".#."],
+            ],
+            TetrominoType::Z => [
+                &["...", "##.", ".##"],
+                &["..#", ".##", ".#."],
+                &["...", "##.", ".##"],
+                &["..#", ".##", ".#."],
+            ],
+            TetrominoType::J => [
+                &["...", "###", "..#"],
+                &[".#.", ".#.", "##."],
+                &["...", "#..", "###"],
+                &[".##", ".#.", ".#."],
+            ],
+            TetrominoType::L => [
+                &["...", "###", "#.."],
+                &["##.", ".#.", ".#."],
+                &["...", "..#", "###"],
+                &[".#.", ".#.", ".##"],
+            ],
+        };
+        parse(states[rotation as usize % 4])
+    }
+
+    fn kicks(&self, kind: TetrominoType, from: u8) -> &'static [(i32, i32)] {
+        match kind {
+            // Standing up takes two rows below the lying I, which the floor or stack may not have
+            TetrominoType::I if from.is_multiple_of(2) => &ARS_I_FLOOR_KICKS,
+            TetrominoType::I | TetrominoType::O => &[],
+            _ => &ARS_KICKS,
+        }
+    }
+
+    /// The center column rule: J, L and T don't kick when the first blocked cell, in reading order, is in the middle column
+    fn can_kick(&self, board: &dyn Playfield, kind: TetrominoType, to: u8, origin: (i32, i32)) -> bool {
+        if !matches!(kind, TetrominoType::J | TetrominoType::L | TetrominoType::T) {
+            return true;
+        }
+        let state = self.state(kind, to);
+        let first_blocked = state.iter().enumerate().find_map(|(y, row)| {
+            (0..row.len()).find(|&x| row[x] && board.is_filled(origin.0 + x as i32, origin.1 + y as i32))
+        });
+        first_blocked != Some(1)
+    }
+}
+
+impl RotationSystem for Classic {
+    fn name(&self) -> &'static str {
+        "classic"
+    }
+
+    fn state(&self, kind: TetrominoType, rotation: u8) -> Vec<Vec<bool>> {
+        let states: [&[&str]; 4] = match kind {
+            TetrominoType::I => [
+                &["....", "....", "####", "...."],
+                &["..#.", "..#.", "..#.", "..#."],
+                &["....", "....", "####", "...."],
+                &["..#.", "..#.", "..#.", "..#."],
+            ],
+            TetrominoType::O => [&["##", "##"]; 4],
+            TetrominoType::T => [
+                &["...", "###", ".#."],
+                &[".#.", "##.", ".#."],
+                &[".#.", "###", "..."],
+                &[".#.", ".##", ".#."],
+            ],
+            TetrominoType::S => [
+                &["...", ".##", "##."],
+                &[".#.", ".##", "..#"],
+                &["...", ".##", "##."],
+                &[".#.", ".##", "..#"],
+            ],
+            TetrominoType::Z => [
+                &["...", "##.", ".##"],
+                &["..#", ".##", ".#."],
+                &["...", "##.", ".##"],
+                &["..#", ".##", ".#."],
+            ],
+            TetrominoType::J => [
+                &["...", "###", "..#"],
+                &[".#.", ".#.", "##."],
+                &["#..", "###", "..."],
+                &[".##", ".#.", ".#."],
+            ],
+            TetrominoType::L => [
+                &["...", "###", "#.."],
+                &["##.", ".#.", ".#."],
+                &["..#", "###", "..."],
+                &[".#.", ".#.", ".##"],
+            ],
+        };
+        parse(states[rotation as usize % 4])
+    }
+
+    fn kicks(&self, _kind: TetrominoType, _from: u8) -> &'static [(i32, i32)] {
+        &[]
+    }
+}
+
+/// Turns rows of `#` and `.` into a box of cells
+fn parse(rows: &[&str]) -> Vec<Vec<bool>> {
+    rows.iter().map(|row| row.chars().map(|c| c == '#').collect()).collect()
+}
+
+/// The box turned 90 degrees clockwise
+fn turn(state: &[Vec<bool>]) -> Vec<Vec<bool>> {
+    let size = state.len();
+    (0..size).map(|y| (0..size).map(|x| state[size - 1 - x][y]).collect()).collect()
+}
+
+/// The filled part of a box, and the column and row it starts at within the box
+fn trim(state: &[Vec<bool>]) -> (Vec<Vec<bool>>, (i32, i32)) {
+    let filled_rows: Vec<usize> = (0..state.len()).filter(|&y| state[y].contains(&true)).collect();
+    let filled_columns: Vec<usize> = (0..state[0].len()).filter(|&x| state.iter().any(|row| row[x])).collect();
+    let (top, bottom) = (filled_rows[0], filled_rows[filled_rows.len() - 1]);
+    let (left, right) = (filled_columns[0], filled_columns[filled_columns.len() - 1]);
+    let shape = state[top..=bottom].iter().map(|row| row[left..=right].to_vec()).collect();
+    (shape, (left as i32, top as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::GameBoard;
+    use ggez::graphics::Color;
+
+    #[test]
+    fn test_states_hold_four_cells_and_cycle() {
+        for system in SYSTEMS {
+            for kind in TetrominoType::ALL {
+                for rotation in 0..4 {
+                    let cells = system.state(kind, rotation).iter().flatten().filter(|&&cell| cell).count();
+                    assert_eq!(cells, 4, "{} {:?} state {}", system.name(), kind, rotation);
+                }
+                assert_eq!(system.state(kind, 4), system.state(kind, 0));
+            }
+        }
+        assert_eq!(by_name("ars").name(), "ars");
+        assert_eq!(by_name("unknown").name(), "srs");
+    }
+
+    #[test]
+    fn test_spawn_orientations() {
+        // SRS matches the pieces' own shapes; ARS and classic spawn T, J and L flat side up
+        for kind in TetrominoType::ALL {
+            assert_eq!(SRS.spawn(kind), Tetromino::new(kind));
+        }
+        assert_eq!(ARS.spawn(TetrominoType::T).shape, parse(&["###", ".#."]));
+        assert_eq!(CLASSIC.spawn(TetrominoType::J).shape, parse(&["###", "..#"]));
+
+        // Every system spawns O in the middle two columns and the others starting at the fourth
+        for system in SYSTEMS {
+            for kind in TetrominoType::ALL {
+                let column = if kind == TetrominoType::O { 4.0 } else { 3.0 };
+                assert_eq!(system.spawn(kind).position.x, column, "{} {:?}", system.name(), kind);
+            }
+        }
+    }
+
+    #[test]
+    fn test_turns_in_place_around_the_box() {
+        let board = GameBoard::new();
+        for system in SYSTEMS {
+            for kind in TetrominoType::ALL {
+                let mut piece = system.spawn(kind);
+                piece.position.y = 5.0;
+                let start = piece.clone();
+                for _ in 0..4 {
+                    piece = system.rotate(&board, &piece).expect("open space should never need a kick");
+                }
+                assert_eq!(piece, start, "{} {:?} should come back after four turns", system.name(), kind);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kicks_differ_between_systems() {
+        // A vertical I against the left wall: SRS kicks it out, ARS and classic can't turn it
+        let board = GameBoard::new();
+        for system in SYSTEMS {
+            let vertical = system.rotate(&board, &system.spawn(TetrominoType::I)).unwrap();
+            let mut against_wall = vertical.clone();
+            against_wall.position = Vec2::new(0.0, 5.0);
+            let turned = system.rotate(&board, &against_wall);
+            assert_eq!(turned.is_some(), system.name() == "srs", "{}", system.name());
+        }
+
+        // A T blocked in the middle column doesn't kick under ARS, but does when blocked at the side
+        let mut board = GameBoard::new();
+        let t = ARS.spawn(TetrominoType::T);
+        board[0][4] = Color::RED;
+        assert!(ARS.rotate(&board, &t).is_none());
+        let mut board = GameBoard::new();
+        board[0][3] = Color::RED;
+        let turned = ARS.rotate(&board, &t).expect("a side block allows the kick");
+        assert!(!board.collides(&turned));
+        assert!(CLASSIC.rotate(&board, &CLASSIC.spawn(TetrominoType::T)).is_none());
+    }
+
+    #[test]
+    fn test_floor_kicks() {
+        // An I lying on the floor kicks up to stand under SRS and ARS, but classic has no kicks
+        let board = GameBoard::new();
+        let bottom = |piece: &Tetromino| GameBoard::piece_cells(piece).into_iter().map(|(_, y)| y).max().unwrap();
+        for system in SYSTEMS {
+            let mut lying = system.spawn(TetrominoType::I);
+            lying.position.y += (board.height() as i32 - 1 - bottom(&lying)) as f32;
+            let turned = system.rotate(&board, &lying);
+            assert_eq!(turned.is_some(), system.name() != "classic", "{}", system.name());
+            if let Some(turned) = turned {
+                assert!(!board.collides(&turned));
+                assert_eq!(bottom(&turned), board.height() as i32 - 1, "{} should stand on the floor", system.name());
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::constants::{
+    SCORE_COMBO, SCORE_DOUBLE, SCORE_HARD_DROP, SCORE_SINGLE, SCORE_SOFT_DROP, SCORE_TETRIS, SCORE_TRIPLE,
+};
+use crate::garbage::TSpin;
+use std::fmt::Debug;
+
+/// Points for T-spins clearing 0 to 3 lines, before the level multiplier
+const T_SPIN_POINTS: [u32; 4] = [400, 800, 1200, 1600];
+/// Points for mini T-spins clearing 0 to 2 lines, before the level multiplier
+const MINI_T_SPIN_POINTS: [u32; 3] = [100, 200, 400];
+/// Points for 1 to 4 lines in the NES game, before the level multiplier
+const NES_LINE_POINTS: [u32; 4] = [40, 100, 300, 1200];
+
+/// A locked piece as the scoring sees it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Clear {
+    pub lines: u32,          // Lines the lock cleared; spins score even without any
+    pub t_spin: TSpin,       // T-spin of the locked piece
+    pub combo: u32,          // Locks in a row that cleared lines before this one
+    pub back_to_back: bool,  // Whether this is a tetris or T-spin clear right after another
+}
+
+impl Clear {
+    /// Whether the clear is a tetris or a T-spin that cleared lines, which can chain back-to-back
+    pub fn is_difficult(lines: u32, t_spin: TSpin) -> bool {
+        lines == 4 || (lines > 0 && t_spin != TSpin::None)
+    }
+}
+
+/// How many points locks and drops are worth
+/// Points for locks are multiplied by the level here, so each system can use its own progression
+pub trait ScoringSystem: Debug + Sync {
+    /// Name of the preset
+    fn name(&self) -> &'static str;
+
+    /// Points for a lock at the given level, including spin, combo and back-to-back bonuses
+    fn clear(&self, clear: &Clear, level: u32) -> u32;
+
+    /// Points for soft dropping the piece by `cells` rows
+    fn soft_drop(&self, cells: u32) -> u32;
+
+    /// Points for hard dropping the piece by `cells` rows
+    fn hard_drop(&self, cells: u32) -> u32;
+}
+
+/// The scoring of current games: T-spins, combos, and half again for back-to-back tetrises and spins
+#[derive(Debug)]
+pub struct Guideline;
+
+/// The NES table of 40, 100, 300 and 1200 points, with nothing for spins, combos or back-to-back;
+/// the NES has no hard drop, so dropped rows all count as soft drop
+#[derive(Debug)]
+pub struct Nes;
+
+pub static GUIDELINE: Guideline = Guideline;
+pub static NES: Nes = Nes;
+
+impl ScoringSystem for Guideline {
+    fn name(&self) -> &'static str {
+        "GUIDELINE"
+    }
+
+    fn clear(&self, clear: &Clear, level: u32) -> u32 {
+        let lines = clear.lines as usize;
+        let points = match clear.t_spin {
+            TSpin::Full => T_SPIN_POINTS[lines.min(T_SPIN_POINTS.len() - 1)],
+            TSpin::Mini => MINI_T_SPIN_POINTS[lines.min(MINI_T_SPIN_POINTS.len() - 1)],
+            TSpin::None => match clear.lines {
+                1 => SCORE_SINGLE,
+                2 => SCORE_DOUBLE,
+                3 => SCORE_TRIPLE,
+                4 => SCORE_TETRIS,
+                _ => 0,
+            },
+        };
+        let points = if clear.back_to_back { points * 3 / 2 } else { points };
+        let combo = if clear.lines > 0 { SCORE_COMBO * clear.combo } else { 0 };
+        (points + combo) * level
+    }
+
+    fn soft_drop(&self, cells: u32) -> u32 {
+        cells * SCORE_SOFT_DROP
+    }
+
+    fn hard_drop(&self, cells: u32) -> u32 {
+        cells * SCORE_HARD_DROP
+    }
+}
+
+impl ScoringSystem for Nes {
+    fn name(&self) -> &'static str {
+        "NES"
+    }
+
+    fn clear(&self, clear: &Clear, level: u32) -> u32 {
+        match clear.lines {
+            0 => 0,
+            lines => NES_LINE_POINTS[(lines as usize).min(NES_LINE_POINTS.len()) - 1] * level,
+        }
+    }
+
+    fn soft_drop(&self, cells: u32) -> u32 {
+        cells
+    }
+
+    fn hard_drop(&self, cells: u32) -> u32 {
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear(lines: u32, t_spin: TSpin) -> Clear {
+        Clear { lines, t_spin, combo: 0, back_to_back: false }
+    }
+
+    #[test]
+    fn test_guideline() {
+        assert_eq!(GUIDELINE.clear(&clear(4, TSpin::None), 1), SCORE_TETRIS);
+        assert_eq!(GUIDELINE.clear(&clear(2, TSpin::None), 3), SCORE_DOUBLE * 3);
+        assert_eq!(GUIDELINE.clear(&clear(0, TSpin::None), 5), 0);
+
+        // Spins score even without lines, and minis score less
+        assert_eq!(GUIDELINE.clear(&clear(0, TSpin::Full), 1), 400);
+        assert_eq!(GUIDELINE.clear(&clear(2, TSpin::Full), 2), 2400);
+        assert_eq!(GUIDELINE.clear(&clear(1, TSpin::Mini), 1), 200);
+
+        // Back-to-back adds half, combos add per lock in the chain
+        let chained = Clear { back_to_back: true, ..clear(4, TSpin::None) };
+        assert_eq!(GUIDELINE.clear(&chained, 1), SCORE_TETRIS * 3 / 2);
+        let combo = Clear { combo: 3, ..clear(1, TSpin::None) };
+        assert_eq!(GUIDELINE.clear(&combo, 2), (SCORE_SINGLE + 3 * SCORE_COMBO) * 2);
+        let broken = Clear { combo: 3, ..clear(0, TSpin::None) };
+        assert_eq!(GUIDELINE.clear(&broken, 1), 0);
+
+        assert_eq!(GUIDELINE.soft_drop(5), 5 * SCORE_SOFT_DROP);
+        assert_eq!(GUIDELINE.hard_drop(5), 5 * SCORE_HARD_DROP);
+    }
+
+    #[test]
+    fn test_nes() {
+        let expected = [0, 40, 100, 300, 1200];
+        for (lines, &points) in expected.iter().enumerate() {
+            assert_eq!(NES.clear(&clear(lines as u32, TSpin::None), 1), points);
+        }
+        assert_eq!(NES.clear(&clear(4, TSpin::None), 10), 12000);
+
+        // Spins, combos and back-to-back are worth nothing extra
+        let fancy = Clear { lines: 2, t_spin: TSpin::Full, combo: 5, back_to_back: true };
+        assert_eq!(NES.clear(&fancy, 1), 100);
+        assert_eq!(NES.hard_drop(10), NES.soft_drop(10));
+    }
+}
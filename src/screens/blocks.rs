@@ -0,0 +1,232 @@
+use ggez::{
+    graphics::{self, Color},
+    Context, GameResult,
+};
+use tetris::tetromino::{Tetromino, TetrominoType};
+use tetris::pattern;
+use tetris::constants::*;
+use tetris::camera::Camera;
+use tetris::layout::Layout;
+use tetris::text::{TextStyle, TEXT_SIZE};
+use tetris::skin::Tile;
+use crate::GameState;
+
+impl GameState {
+    /// Draws a side panel background with pixelated frame layers (8-bit style)
+    pub(crate) fn draw_panel(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, rect: graphics::Rect) -> GameResult {
+        // Outer frame (darker), inner frame (lighter), then the main background (darkest)
+        for (inset, color) in [
+            (0.0, self.theme().panel_frame),
+            (GRID_LINE_WIDTH * 2.0, self.theme().panel_inner),
+            (GRID_LINE_WIDTH * 4.0, self.theme().panel),
+        ] {
+            let mesh = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(rect.x + inset, rect.y + inset, rect.w - inset * 2.0, rect.h - inset * 2.0),
+                color,
+            )?;
+            canvas.draw(&mesh, graphics::DrawParam::default());
+        }
+        Ok(())
+    }
+
+    /// Draws a panel's label at the top of its rect with a block-like shadow for 8-bit effect
+    pub(crate) fn draw_panel_label(&self, canvas: &mut graphics::Canvas, rect: graphics::Rect, label: &str) {
+        let x = rect.x + GRID_SIZE * 0.5;
+        let y = rect.y + (Layout::LABEL_HEIGHT - TEXT_SIZE) / 2.0 + GRID_LINE_WIDTH * 2.0;
+        self.text_renderer.draw_text(canvas, label, [x, y], TextStyle::new(self.theme().text));
+    }
+
+    /// Draws a piece centered in `area`, e.g. in the hold box or queue
+    pub(crate) fn draw_piece_preview(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut graphics::Canvas,
+        piece: &Tetromino,
+        area: graphics::Rect,
+        alpha: f32,
+    ) -> GameResult {
+        let piece_width = piece.shape[0].len() as f32 * GRID_SIZE;
+        let piece_height = piece.shape.len() as f32 * GRID_SIZE;
+        let origin_x = area.x + (area.w - piece_width) / 2.0;  // Center horizontally
+        let origin_y = area.y + (area.h - piece_height) / 2.0;  // Center vertically
+
+        let color = Color::new(piece.color.r, piece.color.g, piece.color.b, alpha);
+        for (y, row) in piece.shape.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if cell {
+                    self.draw_block_at(ctx, canvas, origin_x + x as f32 * GRID_SIZE, origin_y + y as f32 * GRID_SIZE, GRID_SIZE, color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws a board's cells flat, without the 8-bit shading, to keep small views of it readable
+    pub(crate) fn draw_mini_cells(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, camera: &Camera, board: &[Vec<Color>]) -> GameResult {
+        for (y, row) in board.iter().enumerate() {
+            for (x, &color) in row.iter().enumerate() {
+                if color != Color::BLACK {
+                    let area = camera.cells(x as f32, y as f32, 1.0, 1.0);
+                    let cell = graphics::Mesh::new_rectangle(
+                        ctx,
+                        graphics::DrawMode::fill(),
+                        graphics::Rect::new(area.x + 1.0, area.y + 1.0, area.w - 2.0, area.h - 2.0),
+                        self.theme().block_color(color),
+                    )?;
+                    canvas.draw(&cell, graphics::DrawParam::default());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws a block in 8-bit style at a board cell, as seen by `camera`
+    pub(crate) fn draw_block(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, camera: &Camera, x: f32, y: f32, color: Color) -> GameResult {
+        let [block_x, block_y] = camera.cell(x, y);
+        self.draw_block_at(ctx, canvas, block_x, block_y, camera.cell_size, color)
+    }
+
+    /// Draws a block in 8-bit style with its top-left corner at a screen position
+    /// `color` is the piece's standard color, with the opacity to draw at; the theme picks the actual color
+    /// The shading keeps its proportions at any block `size`
+    pub(crate) fn draw_block_at(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, block_x: f32, block_y: f32, size: f32, color: Color) -> GameResult {
+        let kind = TetrominoType::from_color(color);
+        if let (Some(skin), Some(tile)) = (self.skin(), Tile::for_color(color)) {
+            skin.draw(canvas, tile, block_x, block_y, size, Color::new(1.0, 1.0, 1.0, color.a));
+            return self.draw_block_pattern(ctx, canvas, kind, [block_x, block_y], size, self.block_color(color));
+        }
+        let color = self.block_color(color);
+        let line_width = GRID_LINE_WIDTH * size / GRID_SIZE;
+        let padding = BLOCK_PADDING * size / GRID_SIZE;
+        
+        // Main block (slightly smaller to create grid effect)
+        let block_rect = graphics::Rect::new(
+            block_x + line_width, 
+            block_y + line_width,
+            size - 2.0 * line_width, 
+            size - 2.0 * line_width,
+        );
+        
+        // Create the block mesh
+                            let mesh = graphics::Mesh::new_rectangle(
+                                ctx,
+                                graphics::DrawMode::fill(),
+            block_rect,
+                                color,
+                            )?;
+                            canvas.draw(&mesh, graphics::DrawParam::default());
+        
+        // Add a lighter highlight on top and left (8-bit style shading)
+        let highlight_color = Color::new(
+            f32::min(color.r + 0.2, 1.0),
+            f32::min(color.g + 0.2, 1.0),
+            f32::min(color.b + 0.2, 1.0),
+            color.a,
+        );
+        
+        // Top highlight
+        let top_highlight = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(
+                block_x + line_width,
+                block_y + line_width,
+                size - 2.0 * line_width,
+                padding,
+            ),
+            highlight_color,
+        )?;
+        canvas.draw(&top_highlight, graphics::DrawParam::default());
+        
+        // Left highlight
+        let left_highlight = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(
+                block_x + line_width,
+                block_y + line_width,
+                padding,
+                size - 2.0 * line_width,
+            ),
+            highlight_color,
+        )?;
+        canvas.draw(&left_highlight, graphics::DrawParam::default());
+        
+        // Add a darker shadow on bottom and right
+        let shadow_color = Color::new(
+            f32::max(color.r - 0.3, 0.0),
+            f32::max(color.g - 0.3, 0.0),
+            f32::max(color.b - 0.3, 0.0),
+            color.a,
+        );
+        
+        // Bottom shadow
+        let bottom_shadow = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(
+                block_x + line_width,
+                block_y + size - line_width - padding,
+                size - 2.0 * line_width,
+                padding,
+            ),
+            shadow_color,
+        )?;
+        canvas.draw(&bottom_shadow, graphics::DrawParam::default());
+        
+        // Right shadow
+        let right_shadow = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(
+                block_x + size - line_width - padding,
+                block_y + line_width,
+                padding,
+                size - 2.0 * line_width,
+            ),
+            shadow_color,
+        )?;
+        canvas.draw(&right_shadow, graphics::DrawParam::default());
+
+        self.draw_block_pattern(ctx, canvas, kind, [block_x, block_y], size, color)
+    }
+
+    /// Draws the per-piece glyph over a block when block patterns are on, dark on light blocks and light on dark ones
+    /// `color` is the block's color as drawn, which picks the glyph's ink
+    fn draw_block_pattern(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut graphics::Canvas,
+        kind: Option<TetrominoType>,
+        [block_x, block_y]: [f32; 2],
+        size: f32,
+        color: Color,
+    ) -> GameResult {
+        let line_width = GRID_LINE_WIDTH * size / GRID_SIZE;
+        let padding = BLOCK_PADDING * size / GRID_SIZE;
+        if let (true, Some(kind)) = (self.settings.video.block_patterns, kind) {
+            let luminance = 0.299 * color.r + 0.587 * color.g + 0.114 * color.b;
+            let ink = if luminance > 0.5 {
+                Color::new(0.0, 0.0, 0.0, 0.6 * color.a)
+            } else {
+                Color::new(1.0, 1.0, 1.0, 0.7 * color.a)
+            };
+            let glyph_size = size - 2.0 * line_width;
+            let origin_x = block_x + line_width;
+            let origin_y = block_y + line_width;
+            let mut glyph = graphics::MeshBuilder::new();
+            for &[x1, y1, x2, y2] in pattern::glyph(kind) {
+                glyph.line(
+                    &[[origin_x + x1 * glyph_size, origin_y + y1 * glyph_size], [origin_x + x2 * glyph_size, origin_y + y2 * glyph_size]],
+                    padding,
+                    ink,
+                )?;
+            }
+            canvas.draw(&graphics::Mesh::from_data(ctx, glyph.build()), graphics::DrawParam::default());
+        }
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use ggez::{graphics, input::keyboard::KeyCode};
+use tetris::constants::*;
+use tetris::keybindings::{self, Action, Handedness, KeyBindings};
+use tetris::text::TextStyle;
+use crate::{GameScreen, GameState};
+
+impl GameState {
+    /// Changes the keys with `change` and saves them where they came from
+    fn change_controls(&mut self, change: impl FnOnce(&mut KeyBindings)) {
+        match self.profiles.active_mut() {
+            Some(profile) => {
+                change(&mut profile.controls);
+                self.save_profiles();
+            }
+            None => {
+                change(&mut self.settings.controls);
+                self.save_settings();
+            }
+        }
+    }
+
+    /// Keys a restore on the controls screen goes back to: the layout for the profile's hand
+    fn default_controls(&self) -> KeyBindings {
+        KeyBindings::for_hand(self.profiles.active().map_or(Handedness::Right, |profile| profile.handedness))
+    }
+
+    /// Names of the keys bound to an action, for display
+    pub(crate) fn binding_label(&self, action: Action) -> String {
+        let names: Vec<String> = self
+            .controls()
+            .keys_for(action)
+            .iter()
+            .filter_map(|&key| keybindings::key_name(key))
+            .map(|name| name.to_uppercase())
+            .collect();
+        if names.is_empty() {
+            "---".to_string()
+        } else {
+            names.join(" / ")
+        }
+    }
+
+    /// Handles a key press on the controls screen; while rebinding, the key becomes the selected action's binding
+    pub(crate) fn handle_controls_key(&mut self, key: KeyCode) {
+        if self.rebinding {
+            // The next key pressed becomes the binding; Esc cancels
+            if key != KeyCode::Escape && keybindings::key_name(key).is_some() {
+                let action = Action::ALL[self.controls_cursor];
+                self.change_controls(|controls| controls.bind(action, key));
+            }
+            self.rebinding = false;
+            return;
+        }
+        match key {
+            KeyCode::Up => {
+                self.controls_cursor = (self.controls_cursor + Action::ALL.len() - 1) % Action::ALL.len();
+            }
+            KeyCode::Down => {
+                self.controls_cursor = (self.controls_cursor + 1) % Action::ALL.len();
+            }
+            KeyCode::Return => {
+                self.rebinding = true;
+            }
+            KeyCode::Back => {
+                // Restore the default bindings
+                let defaults = self.default_controls();
+                self.change_controls(|controls| *controls = defaults);
+            }
+            KeyCode::Escape => {
+                self.screen = GameScreen::Settings;
+            }
+            _ => {}
+        }
+    }
+
+    /// Draws the controls screen listing each action and its keys
+    pub(crate) fn draw_controls_screen(&self, canvas: &mut graphics::Canvas) {
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "CONTROLS", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 6.0], title_style);
+
+        let list_y = SCREEN_HEIGHT / 6.0 + 120.0;
+        for (i, action) in Action::ALL.iter().enumerate() {
+            let selected = i == self.controls_cursor;
+            let keys = if selected && self.rebinding {
+                if self.show_cursor { "PRESS A KEY".to_string() } else { String::new() }
+            } else {
+                self.binding_label(*action)
+            };
+            let marker = if selected { ">" } else { " " };
+            let color = if selected { self.theme().accent } else { self.theme().text };
+            let y = list_y + i as f32 * 60.0;
+            let style = TextStyle::new(color).scale(2.0).shadow(0.0);
+
+            let label = format!("{} {}", marker, action.label());
+            self.text_renderer.draw_text(canvas, &label, [SCREEN_WIDTH / 2.0 - 400.0, y], style);
+            self.text_renderer.draw_text(canvas, &keys, [SCREEN_WIDTH / 2.0 + 100.0, y], style);
+        }
+
+        let help_lines = [
+            "UP/DOWN: SELECT   ENTER: REBIND",
+            "BACKSPACE: RESTORE DEFAULTS   ESC: BACK",
+        ];
+        let help_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+        for (i, line) in help_lines.iter().enumerate() {
+            self.text_renderer.draw_text(canvas, line, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 200.0 + i as f32 * 40.0], help_style);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use ggez::{
+    graphics::{self, Color},
+    Context, GameResult,
+};
+use tetris::constants::*;
+use tetris::keybindings::Action;
+use tetris::text::{Align, TextStyle, TEXT_SIZE};
+use crate::GameState;
+
+impl GameState {
+    /// Draws the game's internal state in the top left corner while the debug overlay is on
+    pub(crate) fn draw_debug_overlay(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        let mut lines = self.game.debug_state();
+        let held = match self.held_direction {
+            Some(Action::MoveLeft) => "left",
+            Some(_) => "right",
+            None => "-",
+        };
+        let gameplay = &self.settings.gameplay;
+        lines.push(format!(
+            "das: {:.3}s of {:.3}s, arr: {:.3}s of {:.3}s, held: {}",
+            self.das_timer,
+            gameplay.das_ms as f64 / 1000.0,
+            self.arr_timer,
+            gameplay.arr_ms as f64 / 1000.0,
+            held
+        ));
+        lines.push(format!("time: {:.3}s", self.stats.elapsed()));
+        if self.stepper.is_enabled() {
+            lines.push(format!("step mode: tick {} of {:.4}s, f6 steps, f10 runs again", self.stepper.ticks(), STEP_TICK));
+        }
+        let line_height = TEXT_SIZE * 0.6 * 1.5;
+        let area = graphics::Rect::new(MARGIN / 2.0, MARGIN / 2.0, SCREEN_WIDTH * 0.5, lines.len() as f32 * line_height + 8.0);
+        let panel = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), area, Color::new(0.0, 0.0, 0.0, 0.75))?;
+        canvas.draw(&panel, graphics::DrawParam::default());
+
+        let style = TextStyle::new(self.theme().text).scale(0.6).shadow(0.0);
+        for (row, line) in lines.iter().enumerate() {
+            let y = area.y + 4.0 + row as f32 * line_height;
+            self.text_renderer.draw_text(canvas, &line.to_uppercase(), [area.x + 8.0, y], style);
+        }
+        Ok(())
+    }
+
+    /// Draws the recent warnings and errors at the bottom of the screen while the console is open,
+    /// or how many arrived since it was last closed
+    pub(crate) fn draw_log_console(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        let style = TextStyle::new(self.theme().hint).scale(0.6).shadow(0.0);
+        if !self.show_console {
+            let unseen = self.log_console.total() - self.console_seen;
+            if unseen > 0 {
+                let hint = format!("{} NEW WARNING{} - PRESS ` TO SHOW", unseen, if unseen == 1 { "" } else { "S" });
+                self.text_renderer.draw_text(canvas, &hint, [SCREEN_WIDTH - 8.0, SCREEN_HEIGHT - 20.0], style.align(Align::Right));
+            }
+            return Ok(());
+        }
+
+        let lines = self.log_console.lines();
+        let line_height = TEXT_SIZE * 0.6 * 1.5;
+        let rows = lines.len().max(1);
+        let height = rows as f32 * line_height + 8.0;
+        let area = graphics::Rect::new(0.0, SCREEN_HEIGHT - height, SCREEN_WIDTH, height);
+        let panel = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), area, Color::new(0.0, 0.0, 0.0, 0.8))?;
+        canvas.draw(&panel, graphics::DrawParam::default());
+
+        if lines.is_empty() {
+            self.text_renderer.draw_text(canvas, "NO WARNINGS", [8.0, area.y + 4.0], style);
+        }
+        for (row, line) in lines.iter().enumerate() {
+            let color = if line.level == log::Level::Error { Color::RED } else { self.theme().text };
+            let y = area.y + 4.0 + row as f32 * line_height;
+            self.text_renderer.draw_text(canvas, &line.to_string().to_uppercase(), [8.0, y], TextStyle { color, ..style });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use ggez::{
+    graphics,
+    input::keyboard::KeyCode,
+    input::mouse::MouseButton,
+    Context, GameResult,
+};
+use tetris::tetromino::{Tetromino, TetrominoType};
+use tetris::constants::*;
+use tetris::layout::Layout;
+use tetris::editor::{self, Cell, Scenario};
+use tetris::text::TextStyle;
+use tetris::ui::keycode_to_char;
+use crate::{GameScreen, GameState};
+
+impl GameState {
+    /// Handles a key press in the board editor
+    /// Arrows move the cursor, Space paints and X erases, Tab changes the brush, piece letters queue pieces and
+    /// Backspace takes the last one off, C clears the board, Page Up/Down pick the file slot, F5 saves, F9 loads,
+    /// Enter plays and Esc goes back to the title
+    pub(crate) fn handle_editor_key(&mut self, ctx: &mut Context, key: KeyCode, shift: bool) -> GameResult {
+        match key {
+            KeyCode::Left => self.editor.move_cursor(-1, 0),
+            KeyCode::Right => self.editor.move_cursor(1, 0),
+            KeyCode::Up => self.editor.move_cursor(0, -1),
+            KeyCode::Down => self.editor.move_cursor(0, 1),
+            KeyCode::Space => self.editor.paint(),
+            KeyCode::X | KeyCode::Delete => self.editor.erase(),
+            KeyCode::Tab => self.editor.cycle_brush(if shift { -1 } else { 1 }),
+            KeyCode::Back => self.editor.pop_piece(),
+            KeyCode::C => self.editor.scenario.clear_board(),
+            KeyCode::PageUp => self.editor.cycle_slot(-1, SCENARIO_SLOTS),
+            KeyCode::PageDown => self.editor.cycle_slot(1, SCENARIO_SLOTS),
+            KeyCode::F5 => {
+                let path = editor::slot_path(self.editor.slot());
+                self.editor_status = match self.editor.scenario.save(&path) {
+                    Ok(()) => format!("SAVED SLOT {}", self.editor.slot()),
+                    Err(e) => format!("SAVE FAILED: {}", e).to_uppercase(),
+                };
+            }
+            KeyCode::F9 => {
+                let path = editor::slot_path(self.editor.slot());
+                self.editor_status = match Scenario::load(&path) {
+                    Ok(scenario) => {
+                        self.editor.scenario = scenario;
+                        format!("LOADED SLOT {}", self.editor.slot())
+                    }
+                    Err(e) => format!("LOAD FAILED: {}", e).to_uppercase(),
+                };
+            }
+            KeyCode::Return => self.start_scenario(ctx)?,
+            KeyCode::Escape => self.screen = GameScreen::Title,
+            key => {
+                if let Some(kind) = keycode_to_char(key, false).and_then(TetrominoType::from_letter) {
+                    self.editor.push_piece(kind);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the board editor's cursor to the cell at a point in logical pixels, then paints it (`Some(true)`),
+    /// erases it (`Some(false)`) or leaves it
+    pub(crate) fn handle_editor_pointer(&mut self, x: f32, y: f32, paint: Option<bool>) {
+        let (column, row) = self.layout.board_camera().cell_at(x, y);
+        if !(0..GRID_WIDTH).contains(&column) || !(0..GRID_HEIGHT).contains(&row) {
+            return;
+        }
+        self.editor.set_cursor(column, row);
+        match paint {
+            Some(true) => self.editor.paint(),
+            Some(false) => self.editor.erase(),
+            None => {}
+        }
+    }
+
+    /// Paints or erases the board editor's cell under the mouse, whichever button is down
+    pub(crate) fn handle_editor_mouse(&mut self, ctx: &mut Context, x: f32, y: f32) {
+        let (x, y) = Self::to_logical(ctx, x, y);
+        let paint = if ctx.mouse.button_pressed(MouseButton::Left) {
+            Some(true)
+        } else if ctx.mouse.button_pressed(MouseButton::Right) {
+            Some(false)
+        } else {
+            None
+        };
+        self.handle_editor_pointer(x, y, paint);
+    }
+
+    /// Draws the board editor: the board with its cursor, the brush, the queue, the file slot and the keys
+    pub(crate) fn draw_editor_screen(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        let camera = self.layout.board_camera();
+        let board = camera.bounds();
+        let border = graphics::Rect::new(board.x - BORDER_WIDTH, board.y - BORDER_WIDTH, board.w + 2.0 * BORDER_WIDTH, board.h + 2.0 * BORDER_WIDTH);
+        let frame = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(BORDER_WIDTH), border, self.theme().border)?;
+        canvas.draw(&frame, graphics::DrawParam::default());
+        self.grid.draw(ctx, canvas, self.settings.video.grid, &camera, self.theme().grid)?;
+        for y in 0..GRID_HEIGHT as usize {
+            for x in 0..GRID_WIDTH as usize {
+                let cell = self.editor.scenario.cell(x, y);
+                if cell != Cell::Empty {
+                    self.draw_block(ctx, canvas, &camera, x as f32, y as f32, cell.color())?;
+                }
+            }
+        }
+        let (x, y) = self.editor.cursor();
+        let cursor = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::stroke(BLOCK_PADDING),
+            camera.cells(x as f32, y as f32, 1.0, 1.0),
+            self.theme().accent,
+        )?;
+        canvas.draw(&cursor, graphics::DrawParam::default());
+
+        // The brush in the hold box's place
+        let rect = self.layout.hold;
+        self.draw_panel(ctx, canvas, rect)?;
+        self.draw_panel_label(canvas, rect, "BRUSH");
+        let area = graphics::Rect::new(rect.x, rect.y + Layout::LABEL_HEIGHT, rect.w, rect.h - Layout::LABEL_HEIGHT);
+        let center = area.center();
+        self.draw_block_at(ctx, canvas, center.x - GRID_SIZE / 2.0, center.y - GRID_SIZE / 2.0, GRID_SIZE, self.editor.brush().color())?;
+
+        // The first pieces of the queue in the next queue's place, and how many more follow
+        let rect = self.layout.queue;
+        self.draw_panel(ctx, canvas, rect)?;
+        self.draw_panel_label(canvas, rect, "QUEUE");
+        let slot_height = (rect.h - Layout::LABEL_HEIGHT) / NEXT_QUEUE_SIZE as f32;
+        let queue = &self.editor.scenario.queue;
+        for (i, &kind) in queue.iter().take(NEXT_QUEUE_SIZE).enumerate() {
+            let slot = graphics::Rect::new(rect.x, rect.y + Layout::LABEL_HEIGHT + i as f32 * slot_height, rect.w, slot_height);
+            self.draw_piece_preview(ctx, canvas, &Tetromino::new(kind), slot, 1.0)?;
+        }
+        let text_style = TextStyle::new(self.theme().text).shadow(0.0);
+        let x = rect.x + GRID_SIZE * 0.5;
+        let more = match queue.len() {
+            0 => "RANDOM".to_string(),
+            len if len > NEXT_QUEUE_SIZE => format!("+{} MORE", len - NEXT_QUEUE_SIZE),
+            _ => String::new(),
+        };
+        self.text_renderer.draw_text(canvas, &more, [x, rect.bottom() + 16.0], text_style);
+
+        // The file slot, the last save or load and the keys, down the rest of the right-hand column
+        let top = self.layout.score.y;
+        let title_style = TextStyle::new(self.theme().accent).scale(2.0).shadow(2.0);
+        self.text_renderer.draw_text(canvas, "EDITOR", [x, top], title_style);
+        let slot = format!("SLOT {} OF {}", self.editor.slot(), SCENARIO_SLOTS);
+        self.text_renderer.draw_text(canvas, &slot, [x, top + 50.0], text_style);
+        self.text_renderer.draw_text(canvas, &self.editor_status, [x, top + 80.0], TextStyle::new(self.theme().hint).shadow(0.0));
+        let help = [
+            "ARROWS/MOUSE: CURSOR",
+            "SPACE/LEFT CLICK: PAINT",
+            "X/RIGHT CLICK: ERASE",
+            "TAB: BRUSH   C: CLEAR",
+            "I O T S Z J L: QUEUE",
+            "BACKSPACE: UNQUEUE",
+            "PAGE UP/DOWN: SLOT",
+            "F5: SAVE   F9: LOAD",
+            "ENTER: PLAY   ESC: BACK",
+        ];
+        let help_style = TextStyle::new(self.theme().hint).shadow(0.0);
+        for (i, line) in help.iter().enumerate() {
+            self.text_renderer.draw_text(canvas, line, [x, top + 140.0 + i as f32 * 30.0], help_style);
+        }
+        Ok(())
+    }
+}
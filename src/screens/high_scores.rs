@@ -0,0 +1,209 @@
+use ggez::{
+    graphics::{self, Color},
+    Context, GameResult,
+};
+use tetris::constants::*;
+use tetris::leaderboard::{self, Leaderboard};
+use tetris::text::{Align, TextStyle};
+use crate::{GameScreen, GameState};
+
+/// Header, position as a fraction of the screen width, and alignment of each high score column
+const HIGH_SCORE_COLUMNS: [(&str, f32, Align); 8] = [
+    ("RANK", 0.06, Align::Center),
+    ("NAME", 0.11, Align::Left),
+    ("SCORE", 0.35, Align::Right),
+    ("LEVEL", 0.43, Align::Right),
+    ("LINES", 0.51, Align::Right),
+    ("TIME", 0.62, Align::Right),
+    ("MODE", 0.65, Align::Left),
+    ("DATE", 0.88, Align::Right),
+];
+
+impl GameState {
+    /// Shows the high score table, asking the online leaderboard for its latest table
+    pub(crate) fn open_high_scores(&mut self) {
+        self.screen = GameScreen::HighScores;
+        if let Some(leaderboard) = &mut self.leaderboard {
+            leaderboard.refresh();
+        }
+    }
+
+    /// Draws the online leaderboard's table in two columns starting at `y`, or why it can't be shown
+    fn draw_global_scores(&self, canvas: &mut graphics::Canvas, leaderboard: &Leaderboard, y: f32) {
+        let heading_style = TextStyle::new(self.theme().accent).scale(1.5).centered();
+        self.text_renderer.draw_text(canvas, "GLOBAL TOP 10", [SCREEN_WIDTH / 2.0, y], heading_style);
+
+        let y = y + 60.0;
+        let top = leaderboard.top();
+        // A table fetched earlier stays up while a new one loads or when the server goes away
+        if top.is_empty() {
+            let message = match leaderboard.status() {
+                leaderboard::Status::Waiting => "CONNECTING...",
+                leaderboard::Status::Online => "NO SCORES YET",
+                leaderboard::Status::Offline(_) => "OFFLINE",
+            };
+            let style = TextStyle::new(self.theme().text).scale(1.2).centered();
+            self.text_renderer.draw_text(canvas, message, [SCREEN_WIDTH / 2.0, y], style);
+            return;
+        }
+
+        let style = TextStyle::new(self.theme().text).scale(1.2);
+        let rows = leaderboard::GLOBAL_TOP.div_ceil(2);
+        for (i, entry) in top.iter().enumerate() {
+            let (left, right) = if i < rows { (0.11, 0.45) } else { (0.55, 0.89) };
+            let row_y = y + (i % rows) as f32 * 45.0;
+            let name = format!("{:>2}. {}", i + 1, entry.name);
+            self.text_renderer.draw_text(canvas, &name, [SCREEN_WIDTH * left, row_y], style.align(Align::Left));
+            self.text_renderer.draw_text(canvas, &entry.score.to_string(), [SCREEN_WIDTH * right, row_y], style.align(Align::Right));
+        }
+    }
+
+    /// Draws the high scores screen
+    pub(crate) fn draw_high_scores(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        // Draw background with solid color
+        let bg_rect = graphics::Rect::new(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT);
+        let bg_mesh = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            bg_rect,
+            self.theme().background,
+        )?;
+        canvas.draw(&bg_mesh, graphics::DrawParam::default());
+        
+        // Draw title text with shadow
+        let title_scale = 3.0;
+        let (title_width, title_height) = self.text_renderer.measure(ctx, "HIGH SCORES", title_scale);
+        let title_style = TextStyle::new(self.theme().accent).scale(title_scale).centered().shadow(4.0);
+        self.text_renderer.draw_text(canvas, "HIGH SCORES", [SCREEN_WIDTH / 2.0, 50.0], title_style);
+        
+        // Draw decorative line
+        let line_width = title_width + 100.0;
+        let line_y = 50.0 + title_height + 20.0;
+        let line_segments = 20;
+        let segment_width = line_width / line_segments as f32;
+        
+        let colors = [
+            Color::from_rgb(50, 220, 240),   // Cyan
+            Color::from_rgb(60, 210, 250),   // Light blue
+            Color::from_rgb(80, 190, 255),   // Blue
+            Color::from_rgb(100, 170, 255),  // Darker blue
+            Color::from_rgb(120, 150, 255),  // Light purple
+        ];
+        
+        for i in 0..line_segments {
+            let color_idx = i % colors.len();
+            let line_rect = graphics::Rect::new(
+                (SCREEN_WIDTH - line_width) / 2.0 + i as f32 * segment_width,
+                line_y,
+                segment_width,
+                4.0,
+            );
+            let line_mesh = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                line_rect,
+                colors[color_idx],
+            )?;
+            canvas.draw(&line_mesh, graphics::DrawParam::default());
+        }
+        
+        // Draw scores with larger text and better alignment
+        let mut y_pos = line_y + 60.0;  // Increased initial spacing
+        let line_height = 50.0;  // Increased line height
+        let text_scale = 1.2;    // Small enough for every column to fit
+        
+        // Draw headers with the alignment of their column
+        let header_style = TextStyle::new(self.theme().text).scale(text_scale);
+        for (header, x, align) in HIGH_SCORE_COLUMNS {
+            self.text_renderer.draw_text(canvas, header, [SCREEN_WIDTH * x, y_pos], header_style.align(align));
+        }
+        
+        y_pos += line_height + 20.0;  // Add extra spacing after header
+        
+        // Draw each score entry with matching alignment
+        for (i, entry) in self.high_scores.entries.iter().enumerate() {
+            let rank = i + 1;
+            let color = if rank <= 3 {
+                match rank {
+                    1 => Color::from_rgb(255, 215, 0),  // Gold
+                    2 => Color::from_rgb(192, 192, 192), // Silver
+                    3 => Color::from_rgb(205, 127, 50),  // Bronze
+                    _ => self.theme().text,
+                }
+            } else {
+                self.theme().text
+            };
+            
+            // Scores from old files have no details, which show as dashes
+            let unknown = |value: u32| if value == 0 { "-".to_string() } else { value.to_string() };
+            let mode = if entry.mode.is_empty() { "-".to_string() } else { entry.mode.clone() };
+            let lines = if entry.level == 0 { "-".to_string() } else { entry.lines.to_string() };
+            let cells = [
+                rank.to_string(),
+                entry.name.clone(),
+                entry.score.to_string(),
+                unknown(entry.level),
+                lines,
+                entry.time(),
+                mode,
+                entry.date(),
+            ];
+            let style = TextStyle::new(color).scale(text_scale);
+            for (cell, (_, x, align)) in cells.iter().zip(HIGH_SCORE_COLUMNS) {
+                self.text_renderer.draw_text(canvas, cell, [SCREEN_WIDTH * x, y_pos], style.align(align));
+            }
+            
+            y_pos += line_height;
+        }
+
+        // The online table sits below a full local table, so it doesn't jump as the local one fills
+        if let Some(leaderboard) = &self.leaderboard {
+            let global_y = line_y + 60.0 + line_height + 20.0 + MAX_HIGH_SCORES as f32 * line_height + 30.0;
+            self.draw_global_scores(canvas, leaderboard, global_y);
+        }
+        
+        // Draw "Press any key to continue" if blinking
+        if self.show_text {
+            let continue_style = TextStyle::new(self.theme().accent).scale(1.5).centered();
+            self.text_renderer.draw_text(canvas, "PRESS ANY KEY TO CONTINUE", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 100.0], continue_style);
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_high_score_column_positions() {
+        // Each column's widest text ends before the next one starts, with a little room between
+        let widest = ["10", "WWWWWWWWWWWWWWW", "9999999", "99", "999", "99:59.99", "MARATHON", "2000-01-01"];
+        let extent = |(_, x, align): (&str, f32, Align), text: &str| {
+            let width = tetris::text::estimate(text, 1.2).0;
+            let x = SCREEN_WIDTH * x;
+            match align {
+                Align::Left => (x, x + width),
+                Align::Center => (x - width / 2.0, x + width / 2.0),
+                Align::Right => (x - width, x),
+            }
+        };
+        let spans: Vec<(f32, f32)> = HIGH_SCORE_COLUMNS
+            .iter()
+            .zip(widest)
+            .map(|(&column, text)| {
+                let (start, end) = extent(column, text);
+                let (header_start, header_end) = extent(column, column.0);
+                (start.min(header_start), end.max(header_end))
+            })
+            .collect();
+        for pair in spans.windows(2) {
+            assert!(pair[0].1 + GRID_SIZE / 2.0 <= pair[1].0, "{:?} overlaps {:?}", pair[0], pair[1]);
+        }
+        
+        // Verify columns are within screen bounds
+        assert!(spans[0].0 >= SCREEN_WIDTH * 0.03); // Not too close to left edge
+        assert!(spans[spans.len() - 1].1 <= SCREEN_WIDTH * 0.97); // Not too close to right edge
+    }
+}
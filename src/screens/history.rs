@@ -0,0 +1,101 @@
+use ggez::{
+    graphics,
+    input::keyboard::KeyCode,
+};
+use tetris::constants::*;
+use tetris::history::{self, History};
+use tetris::high_scores::HighScoreEntry;
+use tetris::text::{Align, TextStyle};
+use crate::{GameScreen, GameState};
+
+/// Headers of the history table with where their column sits, as a fraction of the screen width, and its alignment
+const HISTORY_COLUMNS: [(&str, f32, Align); 7] = [
+    ("DATE", 0.06, Align::Left),
+    ("NAME", 0.22, Align::Left),
+    ("SCORE", 0.48, Align::Right),
+    ("LEVEL", 0.56, Align::Right),
+    ("LINES", 0.64, Align::Right),
+    ("TIME", 0.75, Align::Right),
+    ("MODE", 0.78, Align::Left),
+];
+
+impl GameState {
+    /// Adds a game to the history file
+    pub(crate) fn add_to_history(&self, game: &HighScoreEntry) {
+        if let Err(e) = History::append(history::history_path(), game) {
+            log::error!(target: "save", "Could not add the game to the history: {}", e);
+        }
+    }
+
+    /// Shows the history screen at its latest games
+    pub(crate) fn open_history(&mut self) {
+        self.history = History::load(history::history_path());
+        self.history_page = 0;
+        self.screen = GameScreen::History;
+    }
+
+    /// Pages through the history: right and down go back in time, left and up come forward, Esc leaves
+    pub(crate) fn handle_history_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Right | KeyCode::Down | KeyCode::PageDown => {
+                self.history_page = (self.history_page + 1).min(self.history.pages() - 1);
+            }
+            KeyCode::Left | KeyCode::Up | KeyCode::PageUp => self.history_page = self.history_page.saturating_sub(1),
+            KeyCode::Home => self.history_page = 0,
+            KeyCode::End => self.history_page = self.history.pages() - 1,
+            KeyCode::Escape | KeyCode::Return | KeyCode::Back => {
+                self.history = History::default();
+                self.screen = GameScreen::Title;
+            }
+            _ => {}
+        }
+    }
+
+    /// Draws a page of the history, newest game first, with how the latest games compare to those before
+    pub(crate) fn draw_history_screen(&self, canvas: &mut graphics::Canvas) {
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "HISTORY", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 10.0], title_style);
+
+        let top = SCREEN_HEIGHT / 10.0 + 100.0;
+        let header_style = TextStyle::new(self.theme().hint).scale(1.2).shadow(0.0);
+        for (header, x, align) in HISTORY_COLUMNS {
+            self.text_renderer.draw_text(canvas, header, [SCREEN_WIDTH * x, top], header_style.align(align));
+        }
+        let games = self.history.page(self.history_page);
+        if games.is_empty() {
+            let style = TextStyle::new(self.theme().text).scale(1.5).centered().shadow(0.0);
+            self.text_renderer.draw_text(canvas, "NO GAMES YET", [SCREEN_WIDTH / 2.0, top + 120.0], style);
+        }
+        let row_style = TextStyle::new(self.theme().text).scale(1.2).shadow(0.0);
+        for (row, game) in games.iter().enumerate() {
+            let name = if game.name.is_empty() { "GUEST".to_string() } else { game.name.to_uppercase() };
+            let cells = [
+                game.date(),
+                name,
+                game.score.to_string(),
+                game.level.to_string(),
+                game.lines.to_string(),
+                game.time(),
+                game.mode.clone(),
+            ];
+            let y = top + 60.0 + row as f32 * 42.0;
+            for (cell, (_, x, align)) in cells.iter().zip(HISTORY_COLUMNS) {
+                self.text_renderer.draw_text(canvas, cell, [SCREEN_WIDTH * x, y], row_style.align(align));
+            }
+        }
+
+        let summary_y = top + 60.0 + HISTORY_PAGE_SIZE as f32 * 42.0 + 30.0;
+        if let Some((latest, previous)) = self.history.trend(HISTORY_PAGE_SIZE) {
+            let mut summary = format!("{} GAMES   LAST {} AVERAGE {:.0}", self.history.games.len(), HISTORY_PAGE_SIZE.min(self.history.games.len()), latest);
+            if let Some(previous) = previous.filter(|previous| *previous > 0.0) {
+                summary.push_str(&format!(" ({:+.0}% ON THE {} BEFORE)", (latest / previous - 1.0) * 100.0, HISTORY_PAGE_SIZE));
+            }
+            let style = TextStyle::new(self.theme().accent).scale(1.2).centered().shadow(0.0);
+            self.text_renderer.draw_text(canvas, &summary, [SCREEN_WIDTH / 2.0, summary_y], style);
+        }
+
+        let help_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+        let help = format!("PAGE {}/{}   LEFT/RIGHT: PAGE   ESC: BACK", self.history_page + 1, self.history.pages());
+        self.text_renderer.draw_text(canvas, &help, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 100.0], help_style);
+    }
+}
@@ -0,0 +1,374 @@
+use ggez::{
+    graphics::{self, Color},
+    input::keyboard::KeyCode,
+    Context, GameResult,
+};
+use tetris::constants::*;
+use tetris::net::{self, NetHost, NetSession};
+use tetris::lobby::{self, LobbyClient};
+use tetris::text::{Align, TextStyle, TEXT_SIZE};
+use tetris::ui::keycode_to_char;
+use std::fmt;
+use crate::{GameScreen, GameState};
+
+impl GameState {
+    /// Shows a network error on the screen the player is on and logs it
+    pub(crate) fn net_error(&mut self, status: &str, error: impl fmt::Display) {
+        let mut message = status.to_lowercase();
+        message[..1].make_ascii_uppercase();
+        log::warn!(target: "net", "{}: {}", message, error);
+        self.net_status = format!("{}: {}", status, error);
+    }
+
+    /// Opens the room browser, connecting to the lobby server set in the settings
+    pub(crate) fn open_rooms(&mut self) {
+        self.screen = GameScreen::Rooms;
+        self.room_choice = 0;
+        self.lobby_refresh = 0.0;
+        self.net_status.clear();
+        if self.lobby.is_some() {
+            return;
+        }
+        let address = self.settings.online.lobby_address.clone();
+        if address.trim().is_empty() {
+            self.net_status = "NO LOBBY SERVER SET".to_string();
+            return;
+        }
+        match LobbyClient::connect(&address) {
+            Ok(lobby) => self.lobby = Some(lobby),
+            Err(e) => self.net_error("LOBBY OFFLINE", e),
+        }
+    }
+
+    /// Hosts a versus game and lists it in the lobby as a room named after the player
+    fn create_room(&mut self) {
+        let player = self.player_name();
+        let name = format!("{}'S ROOM", player);
+        let mode = format!("FIRST TO {}", self.settings.versus.first_to);
+        let Some(lobby) = &mut self.lobby else {
+            return;
+        };
+        let host = match NetHost::bind(net::DEFAULT_PORT) {
+            Ok(host) => host,
+            Err(e) => {
+                self.net_error("CANNOT HOST", e);
+                return;
+            }
+        };
+        match lobby.open_room(&name, &player, &mode, net::DEFAULT_PORT) {
+            Ok(()) => {
+                self.net_host = Some(host);
+                self.net_status.clear();
+                self.screen = GameScreen::Hosting;
+            }
+            Err(e) => self.net_error("LOBBY ERROR", e),
+        }
+    }
+
+    /// Name other players know this player by: the profile's, or GUEST
+    fn player_name(&self) -> String {
+        self.profiles.active().map_or("GUEST".to_string(), |profile| profile.name.to_uppercase())
+    }
+
+    /// Whether there's anyone to chat with: a versus opponent over the network, or the lobby
+    pub(crate) fn chat_available(&self) -> bool {
+        self.versus.is_some() || self.lobby.is_some()
+    }
+
+    /// Handles a key press while a chat message is typed: Enter sends it, Esc drops it
+    pub(crate) fn handle_chat_key(&mut self, ctx: &mut Context, key: KeyCode) {
+        let Some(input) = &mut self.chat_input else {
+            return;
+        };
+        match key {
+            KeyCode::Return => {
+                let text = std::mem::take(input);
+                self.chat_input = None;
+                self.send_chat(text);
+            }
+            KeyCode::Escape => self.chat_input = None,
+            KeyCode::Back => {
+                input.pop();
+            }
+            key if input.chars().count() < CHAT_MAX_LENGTH => {
+                let shift = ctx.keyboard.is_key_pressed(KeyCode::LShift) || ctx.keyboard.is_key_pressed(KeyCode::RShift);
+                if let Some(ch) = keycode_to_char(key, shift) {
+                    input.push(ch);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Sends a chat message to the versus opponent, or else to the lobby, and shows it in our own chat
+    fn send_chat(&mut self, text: String) {
+        if text.trim().is_empty() {
+            return;
+        }
+        let from = self.player_name();
+        if self.versus.is_some() {
+            self.send_versus(|link, _| link.send_chat(&from, &text));
+        } else if let Some(lobby) = &mut self.lobby {
+            if let Err(e) = lobby.send_chat(&from, &text) {
+                self.net_error("LOBBY DISCONNECTED", e);
+                self.lobby = None;
+            }
+        }
+        self.chat.push(&from, &text);
+    }
+
+    /// Adds chat messages from other players, unless chat is muted
+    pub(crate) fn receive_chat(&mut self, messages: Vec<(String, String)>) {
+        if self.settings.online.chat_muted {
+            return;
+        }
+        for (from, text) in messages {
+            self.chat.push(&from, &text);
+        }
+    }
+
+    /// Keeps the room browser's list fresh, dropping the lobby connection if it fails
+    pub(crate) fn update_lobby(&mut self, dt: f64) {
+        let Some(lobby) = &mut self.lobby else {
+            return;
+        };
+        let mut result = Ok(());
+        if self.screen == GameScreen::Rooms {
+            self.lobby_refresh -= dt;
+            if self.lobby_refresh <= 0.0 {
+                self.lobby_refresh = lobby::REFRESH_INTERVAL;
+                result = lobby.refresh();
+            }
+        }
+        if let Err(e) = result.and_then(|_| lobby.update()) {
+            self.net_error("LOBBY DISCONNECTED", e);
+            self.lobby = None;
+        }
+        let messages = self.lobby.as_mut().map(LobbyClient::take_chat).unwrap_or_default();
+        self.receive_chat(messages);
+        let rooms = self.lobby.as_ref().map_or(0, |lobby| lobby.rooms().len());
+        self.room_choice = self.room_choice.min(rooms);
+    }
+
+    /// Handles a key press in the room browser: Enter joins the selected room or creates one, R lists the rooms again
+    pub(crate) fn handle_rooms_key(&mut self, key: KeyCode) {
+        let rooms = self.lobby.as_ref().map_or(0, |lobby| lobby.rooms().len());
+        match key {
+            KeyCode::Up => self.room_choice = self.room_choice.saturating_sub(1),
+            KeyCode::Down => self.room_choice = (self.room_choice + 1).min(rooms),
+            KeyCode::R => self.lobby_refresh = 0.0,
+            KeyCode::Return if self.room_choice == rooms => self.create_room(),
+            KeyCode::Return => {
+                let Some(room) = self.lobby.as_ref().and_then(|lobby| lobby.rooms().get(self.room_choice)) else {
+                    return;
+                };
+                match NetSession::connect(&room.address) {
+                    Ok(session) => {
+                        self.lobby = None;
+                        self.start_versus(session);
+                    }
+                    Err(e) => self.net_error("CONNECTION FAILED", e),
+                }
+            }
+            KeyCode::Escape => {
+                self.lobby = None;
+                self.screen = GameScreen::Title;
+            }
+            _ => {}
+        }
+    }
+
+    /// Handles a key press in a versus room: Enter readies up, Esc leaves
+    pub(crate) fn handle_room_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Return | KeyCode::Space => self.send_versus(|link, _| link.send_ready()),
+            KeyCode::Escape => {
+                self.leave_versus();
+                self.screen = GameScreen::Title;
+            }
+            _ => {}
+        }
+    }
+
+    /// Handles a key press while waiting for an opponent; Esc stops hosting
+    pub(crate) fn handle_hosting_key(&mut self, key: KeyCode) {
+        if key == KeyCode::Escape {
+            self.net_host = None;
+            // A room listed in the lobby is taken down, back in the room browser
+            self.screen = match &mut self.lobby {
+                Some(lobby) => {
+                    let _ = lobby.close_room();
+                    GameScreen::Rooms
+                }
+                None => GameScreen::Title,
+            };
+        }
+    }
+
+    /// Handles a key press on the join screen, where the host's address is typed
+    pub(crate) fn handle_joining_key(&mut self, ctx: &mut Context, key: KeyCode) {
+        match key {
+            KeyCode::Escape => {
+                self.screen = GameScreen::Title;
+            }
+            KeyCode::Return => {
+                match NetSession::connect(&self.join_address) {
+                    Ok(session) => self.start_versus(session),
+                    Err(e) => self.net_error("CONNECTION FAILED", e),
+                }
+            }
+            KeyCode::Back => {
+                self.join_address.pop();
+            }
+            keycode if self.join_address.len() < 40 => {
+                if let Some(ch) = keycode_to_char(keycode, ctx.keyboard.is_key_pressed(KeyCode::LShift) || ctx.keyboard.is_key_pressed(KeyCode::RShift)) {
+                    self.join_address.push(ch);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Draws the versus lobby, either waiting for an opponent or typing the host address
+    pub(crate) fn draw_versus_lobby(&self, canvas: &mut graphics::Canvas) {
+        let hosting = self.screen == GameScreen::Hosting;
+        let title = if hosting { "HOST VERSUS" } else { "JOIN VERSUS" };
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, title, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 4.0], title_style);
+
+        let mut lines = Vec::new();
+        if hosting {
+            if self.net_host.is_some() {
+                lines.push(format!("WAITING FOR OPPONENT ON PORT {}", net::DEFAULT_PORT));
+            }
+            if let Some(room) = self.lobby.as_ref().and_then(LobbyClient::open) {
+                lines.push(format!("{} IS LISTED IN THE LOBBY", room.name));
+            }
+        } else {
+            let cursor = if self.show_cursor { "_" } else { " " };
+            lines.push("ENTER HOST ADDRESS:".to_string());
+            lines.push(format!("{}{}", self.join_address, cursor));
+            lines.push("PRESS ENTER TO CONNECT".to_string());
+        }
+        if !self.net_status.is_empty() {
+            lines.push(self.net_status.to_uppercase());
+        }
+        lines.push("PRESS ESC TO CANCEL".to_string());
+
+        let line_style = TextStyle::new(self.theme().text).scale(2.0).centered().shadow(0.0);
+        for (i, line) in lines.iter().enumerate() {
+            self.text_renderer.draw_text(canvas, line, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0 - 60.0 + i as f32 * 60.0], line_style);
+        }
+    }
+
+    /// Draws the chat along the bottom of the screen: recent messages fading out, or every message kept and
+    /// the line being typed while the chat line is open
+    pub(crate) fn draw_chat(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        let typing = self.chat_input.is_some();
+        let lines: Vec<_> = if typing { self.chat.lines().collect() } else { self.chat.visible().collect() };
+        if lines.is_empty() && !typing {
+            return Ok(());
+        }
+
+        let line_height = TEXT_SIZE * 1.5;
+        let rows = lines.len() + typing as usize;
+        let bottom = SCREEN_HEIGHT - MARGIN / 2.0;
+        let area = graphics::Rect::new(MARGIN / 2.0, bottom - rows as f32 * line_height - 8.0, SCREEN_WIDTH * 0.6, rows as f32 * line_height + 8.0);
+        let panel = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), area, Color::new(0.0, 0.0, 0.0, 0.6))?;
+        canvas.draw(&panel, graphics::DrawParam::default());
+
+        let x = area.x + 8.0;
+        let mut y = area.y + 4.0;
+        for line in lines {
+            let alpha = if typing { 1.0 } else { line.opacity() };
+            let accent = self.theme().accent;
+            let text = self.theme().text;
+            let from = format!("{}: ", line.from.to_uppercase());
+            let (from_width, _) = self.text_renderer.measure(ctx, &from, 1.0);
+            self.text_renderer.draw_text(canvas, &from, [x, y], TextStyle::new(Color { a: alpha, ..accent }).shadow(0.0));
+            self.text_renderer.draw_text(canvas, &line.text.to_uppercase(), [x + from_width, y], TextStyle::new(Color { a: alpha, ..text }).shadow(0.0));
+            y += line_height;
+        }
+        if let Some(input) = &self.chat_input {
+            let cursor = if self.show_cursor { "_" } else { " " };
+            let line = format!("SAY: {}{}", input.to_uppercase(), cursor);
+            self.text_renderer.draw_text(canvas, &line, [x, y], TextStyle::new(self.theme().text).shadow(0.0));
+        }
+        Ok(())
+    }
+
+    /// Draws the room browser: the lobby's open rooms with their host, rules and ping, then the row that creates a room
+    pub(crate) fn draw_rooms_screen(&self, canvas: &mut graphics::Canvas) {
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "ONLINE ROOMS", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 8.0], title_style);
+
+        let columns = [0.08, 0.45, 0.65, 0.92];
+        let top = SCREEN_HEIGHT / 4.0;
+        if let Some(lobby) = &self.lobby {
+            let heading_style = TextStyle::new(self.theme().hint).scale(1.5).shadow(0.0);
+            for (heading, x) in ["ROOM", "HOST", "MODE"].iter().zip(columns) {
+                self.text_renderer.draw_text(canvas, heading, [SCREEN_WIDTH * x, top], heading_style);
+            }
+            self.text_renderer.draw_text(canvas, "PING", [SCREEN_WIDTH * columns[3], top], heading_style.align(Align::Right));
+
+            let rooms = lobby.rooms();
+            for (i, room) in rooms.iter().enumerate() {
+                let color = if i == self.room_choice { self.theme().accent } else { self.theme().text };
+                let style = TextStyle::new(color).scale(1.5).shadow(0.0);
+                let y = top + 60.0 + i as f32 * 45.0;
+                let ping = lobby.ping(room).map_or("--".to_string(), |ping| format!("{} MS", ping.as_millis()));
+                let name: String = room.name.chars().take(18).collect();
+                let host: String = room.host.chars().take(10).collect();
+                self.text_renderer.draw_text(canvas, &name.to_uppercase(), [SCREEN_WIDTH * columns[0], y], style);
+                self.text_renderer.draw_text(canvas, &host.to_uppercase(), [SCREEN_WIDTH * columns[1], y], style);
+                self.text_renderer.draw_text(canvas, &room.mode.to_uppercase(), [SCREEN_WIDTH * columns[2], y], style);
+                self.text_renderer.draw_text(canvas, &ping, [SCREEN_WIDTH * columns[3], y], style.align(Align::Right));
+            }
+            if rooms.is_empty() {
+                let empty_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+                self.text_renderer.draw_text(canvas, "NO OPEN ROOMS", [SCREEN_WIDTH / 2.0, top + 60.0], empty_style);
+            }
+            let create = rooms.len();
+            let color = if self.room_choice == create { self.theme().accent } else { self.theme().text };
+            let create_style = TextStyle::new(color).scale(2.0).centered().shadow(0.0);
+            let y = top + 60.0 + create.max(1) as f32 * 45.0 + 40.0;
+            self.text_renderer.draw_text(canvas, "CREATE ROOM", [SCREEN_WIDTH / 2.0, y], create_style);
+        }
+
+        if !self.net_status.is_empty() {
+            let status_style = TextStyle::new(self.theme().text).scale(1.5).centered().shadow(0.0);
+            self.text_renderer.draw_text(canvas, &self.net_status.to_uppercase(), [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 260.0], status_style);
+        }
+        let help_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+        let help = if self.lobby.is_some() { "UP/DOWN: SELECT   ENTER: JOIN   R: REFRESH   ESC: BACK" } else { "ESC: BACK" };
+        self.text_renderer.draw_text(canvas, help, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 200.0], help_style);
+    }
+
+    /// Draws a versus room: the match's length and whether each player is ready to start
+    pub(crate) fn draw_room_screen(&self, canvas: &mut graphics::Canvas) {
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "VERSUS ROOM", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 4.0], title_style);
+
+        let mut lines = Vec::new();
+        if let Some(link) = &self.versus {
+            lines.push("OPPONENT CONNECTED".to_string());
+            if let Some(versus_match) = &self.versus_match {
+                lines.push(format!("FIRST TO {}", versus_match.first_to()));
+            }
+            let ready = |ready: bool| if ready { "READY" } else { "NOT READY" };
+            lines.push(format!("YOU: {}", ready(link.is_ready())));
+            lines.push(format!("OPPONENT: {}", ready(link.opponent_is_ready())));
+        }
+        if !self.net_status.is_empty() {
+            lines.push(self.net_status.to_uppercase());
+        }
+        let line_style = TextStyle::new(self.theme().text).scale(2.0).centered().shadow(0.0);
+        for (i, line) in lines.iter().enumerate() {
+            self.text_renderer.draw_text(canvas, line, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0 - 120.0 + i as f32 * 60.0], line_style);
+        }
+
+        let help_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+        let help = if self.versus.is_some() { "ENTER: READY   ESC: LEAVE" } else { "ESC: BACK" };
+        self.text_renderer.draw_text(canvas, help, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 200.0], help_style);
+    }
+}
@@ -0,0 +1,19 @@
+pub mod blocks;
+pub mod controls;
+pub mod debug;
+pub mod editor;
+pub mod high_scores;
+pub mod history;
+pub mod lobby;
+pub mod name_entry;
+pub mod pause;
+pub mod playing;
+pub mod profiles;
+pub mod recovered;
+pub mod results;
+pub mod settings;
+pub mod statistics;
+pub mod title;
+pub mod transfer;
+pub mod versus;
+pub mod versus_options;
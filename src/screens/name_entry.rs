@@ -0,0 +1,235 @@
+use ggez::{
+    graphics::{self, Color},
+    input::keyboard::KeyCode,
+    Context, GameResult,
+};
+use tetris::constants::*;
+use tetris::high_scores::{HighScoreEntry, NameRejection};
+use tetris::leaderboard::Submission;
+use tetris::profiles;
+use tetris::text::TextStyle;
+use tetris::ui::{self, CharGrid, GridKey, MenuEvent};
+use crate::GameState;
+
+impl GameState {
+    /// Checks if the current score qualifies for the high score list
+    pub(crate) fn check_high_score(&self) -> bool {
+        self.modes.get(self.mode).is_ranked() && self.high_scores.would_qualify(self.game.score())
+    }
+
+    /// Offers a name for the high score table: the profile playing, or else the name entered last
+    /// The on-screen keyboard starts on END when there is a name, so Enter takes it as it is
+    pub(crate) fn begin_name_entry(&mut self) {
+        let name = match self.profiles.active() {
+            Some(profile) => profile.name.clone(),
+            None => self.settings.name_entry.last_name.clone(),
+        };
+        self.current_name.clear();
+        self.name_error = None;
+        for ch in name.chars() {
+            self.push_name_char(ch);
+        }
+        self.name_grid = CharGrid::new();
+        if !self.current_name.is_empty() {
+            self.name_grid.select(GridKey::Done);
+        }
+    }
+
+    /// Handles a key press on the name entry screen
+    /// The arrows and Enter work the on-screen keyboard and Backspace deletes; typed characters arrive as text input
+    pub(crate) fn handle_name_key(&mut self, ctx: &mut Context, key: KeyCode) {
+        match key {
+            KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right => {
+                self.name_grid.handle_key(key);
+                self.play_menu_sound(ctx, MenuEvent::Moved);
+            }
+            KeyCode::Return => match self.name_grid.handle_key(key) {
+                Some(GridKey::Char(ch)) => self.push_name_char(ch),
+                Some(GridKey::Delete) => {
+                    ui::pop_grapheme(&mut self.current_name);
+                    self.name_error = None;
+                }
+                Some(GridKey::Done) if !self.current_name.is_empty() => self.finish_name_entry(),
+                Some(GridKey::Done) | None => {}
+            },
+            KeyCode::Back => {
+                ui::pop_grapheme(&mut self.current_name);
+                self.name_error = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds a typed character to the name, whatever the keyboard layout or input method produced it
+    pub(crate) fn handle_name_text(&mut self, ch: char) {
+        if ch.is_control() {
+            return;
+        }
+        self.push_name_char(ch);
+        // Someone typing their name finishes it with Enter, as before the keyboard was added
+        self.name_grid.select(GridKey::Done);
+    }
+
+    /// Adds a character to the name being entered, up to the longest name allowed in grapheme clusters,
+    /// so an accent typed as a combining mark still fits on the last letter
+    /// Arcade initials are capitals, and the cursor moves to END once all three are in
+    fn push_name_char(&mut self, ch: char) {
+        let arcade = self.settings.name_entry.arcade_initials;
+        let length = if arcade { INITIALS_LENGTH } else { profiles::MAX_NAME_LENGTH };
+        let mut name = self.current_name.clone();
+        name.push(if arcade { ch.to_ascii_uppercase() } else { ch });
+        if ui::grapheme_count(&name) > length {
+            return;
+        }
+        self.current_name = name;
+        self.name_error = None;
+        if arcade && ui::grapheme_count(&self.current_name) == length {
+            self.name_grid.select(GridKey::Done);
+        }
+    }
+
+    /// Adds the entered name and score to the table, sends it online and remembers the name for next time
+    /// A name the rules turn down stays on screen with the reason, to be changed
+    fn finish_name_entry(&mut self) {
+        match self.name_rules.validate(&self.current_name) {
+            Ok(name) => self.current_name = name,
+            Err(rejection) => {
+                self.name_error = Some(rejection);
+                return;
+            }
+        }
+        let recovered = self.recovered.is_some();
+        self.add_high_score();
+        // A recovered game has no replay to back its score online
+        if !recovered {
+            self.submit_score(self.current_name.clone());
+        }
+        self.settings.name_entry.last_name = std::mem::take(&mut self.current_name);
+        self.save_settings();
+        self.open_high_scores();
+    }
+
+    /// Sends the current score to the online leaderboard under `name`, if one is configured
+    /// Versus games aren't sent, since their scores depend on the opponent
+    pub(crate) fn submit_score(&mut self, name: String) {
+        if self.versus_result.is_some() || self.game.score() == 0 || !self.modes.get(self.mode).is_ranked() {
+            return;
+        }
+        let submission = Submission {
+            name,
+            mode: self.modes.get(self.mode).name().to_string(),
+            score: self.game.score(),
+            replay_hash: self.replay.hex(),
+        };
+        if let Some(leaderboard) = &mut self.leaderboard {
+            leaderboard.submit(submission);
+        }
+    }
+
+    /// Adds the current score to the high scores, with the level, lines, time and mode of the game
+    /// A recovered game's score is added instead while one is being named
+    pub(crate) fn add_high_score(&mut self) -> bool {
+        let name = self.current_name.clone();
+        let entry = match self.recovered.take() {
+            Some(recovered) => HighScoreEntry { name, ..recovered.entry },
+            None => self.score_entry(name),
+        };
+        let added = self.high_scores.add_entry(entry);
+        if added {
+            self.save_high_scores();
+        }
+        added
+    }
+
+    /// The score of the current game under `name`, with its level, lines, time and mode
+    pub(crate) fn score_entry(&self, name: String) -> HighScoreEntry {
+        HighScoreEntry {
+            level: self.game.level(),
+            lines: self.game.lines_cleared(),
+            duration: self.stats.elapsed(),
+            mode: self.modes.get(self.mode).name().to_string(),
+            ..HighScoreEntry::new(name, self.game.score())
+        }
+    }
+
+    /// Draws the name entry screen
+    pub(crate) fn draw_name_entry(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        // Draw background with solid color
+        // Fill with background color instead of using clear
+        let bg_rect = graphics::Rect::new(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT);
+        let bg_mesh = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            bg_rect,
+            self.theme().background,
+        )?;
+        canvas.draw(&bg_mesh, graphics::DrawParam::default());
+        
+        // Draw title text with shadow
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "HIGH SCORE!", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 4.0], title_style);
+        
+        // Draw score text
+        let score_style = TextStyle::new(self.theme().text).scale(2.0).centered().shadow(0.0);
+        let score = format!("YOUR SCORE: {}", self.recovered.as_ref().map_or(self.game.score(), |recovered| recovered.entry.score));
+        self.text_renderer.draw_text(canvas, &score, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 3.0], score_style);
+        
+        // Draw name entry prompt
+        let prompt_style = TextStyle::new(self.theme().text).scale(1.5).centered().shadow(0.0);
+        let prompt = if self.settings.name_entry.arcade_initials { "ENTER YOUR INITIALS:" } else { "ENTER YOUR NAME:" };
+        self.text_renderer.draw_text(canvas, prompt, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0 - 30.0], prompt_style);
+        
+        // Draw the current name
+        let display_name = if self.show_cursor {
+            format!("{}_", self.current_name)
+        } else {
+            format!("{}  ", self.current_name) // Two spaces to maintain consistent width
+        };
+        
+        let name_scale = 2.0;
+        
+        // Calculate fixed box width based on maximum name length (15 chars) plus cursor
+        let (max_name_width, _) = self.text_renderer.measure(ctx, &("A".repeat(15) + " "), name_scale);
+        let fixed_box_width = max_name_width + 60.0; // Add more padding
+        
+        // Draw with fixed-width background box
+        let name_bg_rect = graphics::Rect::new(
+            (SCREEN_WIDTH - fixed_box_width) / 2.0,
+            SCREEN_HEIGHT / 2.0 + 10.0,
+            fixed_box_width,
+            50.0,
+        );
+        
+        let name_bg = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            name_bg_rect,
+            Color::new(0.1, 0.1, 0.2, 1.0),
+        )?;
+        canvas.draw(&name_bg, graphics::DrawParam::default());
+        
+        // Center the text within the fixed box
+        let name_style = TextStyle::new(Color::from_rgb(100, 255, 100)).scale(name_scale).centered().shadow(0.0);
+        self.text_renderer.draw_text(canvas, &display_name, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0 + 20.0], name_style);
+        
+        // Draw the on-screen keyboard below the name
+        self.name_grid.draw(canvas, &self.text_renderer, self.theme(), [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0 + 130.0], 80.0, 1.75);
+        
+        if let Some(rejection) = self.name_error {
+            let message = match rejection {
+                NameRejection::Empty => "TYPE A NAME FIRST".to_string(),
+                NameRejection::Blocked => "PLEASE PICK ANOTHER NAME".to_string(),
+                NameRejection::Character(ch) => format!("{} CAN'T BE USED IN NAMES", ch),
+            };
+            let error_style = TextStyle::new(Color::RED).scale(1.5).centered().shadow(0.0);
+            self.text_renderer.draw_text(canvas, &message, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 190.0], error_style);
+        }
+
+        // Draw instructions
+        let instructions_style = TextStyle::new(self.theme().hint).centered().shadow(0.0);
+        let instructions = "TYPE, OR PICK WITH THE ARROWS AND ENTER; END WHEN DONE";
+        self.text_renderer.draw_text(canvas, instructions, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 140.0], instructions_style);
+        
+        Ok(())
+    }
+}
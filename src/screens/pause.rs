@@ -0,0 +1,162 @@
+use ggez::{
+    graphics::{self, Color},
+    input::keyboard::KeyCode,
+    Context, GameResult,
+};
+use tetris::constants::*;
+use tetris::keybindings::Action;
+use tetris::text::TextStyle;
+use tetris::ui::{Menu, MenuEvent};
+use crate::{GameScreen, GameState};
+
+/// Entries of the pause menu, in display order
+#[derive(PartialEq, Clone, Copy)]
+pub(crate) enum PauseItem {
+    Resume,
+    Restart,
+    Gravity,  // Freezes or unfreezes gravity, in modes that allow it
+    Settings,
+    QuitToTitle,
+}
+
+impl PauseItem {
+    /// The entries of the pause menu; the gravity toggle only shows in modes that can freeze gravity,
+    /// and restarting is left out of LAN games, which can't be restarted without the opponent
+    pub(crate) fn all(gravity_toggle: bool, restart: bool) -> Vec<PauseItem> {
+        let mut items = vec![PauseItem::Resume];
+        if restart {
+            items.push(PauseItem::Restart);
+        }
+        if gravity_toggle {
+            items.push(PauseItem::Gravity);
+        }
+        items.extend([PauseItem::Settings, PauseItem::QuitToTitle]);
+        items
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            PauseItem::Resume => "RESUME",
+            PauseItem::Restart => "RESTART",
+            PauseItem::Gravity => "GRAVITY",
+            PauseItem::Settings => "SETTINGS",
+            PauseItem::QuitToTitle => "QUIT TO TITLE",
+        }
+    }
+}
+
+impl GameState {
+    /// Handles a key press while asking whether to quit: Y or Enter closes the game, N or Esc keeps playing
+    pub(crate) fn handle_quit_key(&mut self, ctx: &mut Context, key: KeyCode) {
+        match key {
+            KeyCode::Y | KeyCode::Return => ctx.request_quit(),
+            KeyCode::N | KeyCode::Escape => self.quit_requested = false,
+            _ => {}
+        }
+    }
+
+    /// Pauses the game and opens the pause menu on its first item
+    pub(crate) fn pause(&mut self) {
+        self.paused = true;
+        self.resume_countdown = 0.0;
+        self.held_direction = None;
+        let labels: Vec<&str> = self.pause_items().into_iter().map(PauseItem::label).collect();
+        self.pause_menu = Menu::new(&labels);
+        self.show_gravity();
+    }
+
+    /// The entries of the pause menu for the game being played
+    fn pause_items(&self) -> Vec<PauseItem> {
+        PauseItem::all(self.modes.get(self.mode).can_freeze_gravity(), self.versus.is_none())
+    }
+
+    /// Shows whether gravity is on beside the pause menu's gravity toggle
+    fn show_gravity(&mut self) {
+        if let Some(index) = self.pause_items().iter().position(|&item| item == PauseItem::Gravity) {
+            self.pause_menu.set_value(index, if self.game.is_gravity_frozen() { "OFF" } else { "ON" });
+        }
+    }
+
+    /// Freezes or unfreezes gravity and keeps the choice for the mode's next games
+    fn toggle_gravity(&mut self) {
+        let frozen = !self.game.is_gravity_frozen();
+        self.game.set_gravity_frozen(frozen);
+        self.settings.practice.gravity = !frozen;
+        self.save_settings();
+        self.show_gravity();
+    }
+
+    /// Closes the pause menu and starts the countdown before play resumes
+    fn resume(&mut self) {
+        self.paused = false;
+        self.resume_countdown = RESUME_COUNTDOWN;
+    }
+
+    /// Handles a key press on the pause menu
+    pub(crate) fn handle_pause_key(&mut self, ctx: &mut Context, key: KeyCode) -> GameResult {
+        // The pause key closes the menu as well
+        if self.controls().action_for(key) == Some(Action::Pause) {
+            self.resume();
+            return Ok(());
+        }
+
+        let event = self.pause_menu.handle_key(key);
+        self.play_menu_sound(ctx, event);
+        match event {
+            MenuEvent::Adjusted(index, _) if self.pause_items()[index] == PauseItem::Gravity => self.toggle_gravity(),
+            MenuEvent::Activated(index) => match self.pause_items()[index] {
+                PauseItem::Resume => self.resume(),
+                PauseItem::Gravity => self.toggle_gravity(),
+                PauseItem::Restart => {
+                    if self.bot.is_some() {
+                        self.start_bot_versus(ctx)?;
+                    } else {
+                        self.reset_game(ctx)?;
+                    }
+                }
+                PauseItem::Settings => self.open_settings(GameScreen::Playing),
+                PauseItem::QuitToTitle => {
+                    self.discard_autosave();
+                    self.end_versus(false);
+                    self.leave_versus();
+                    self.paused = false;
+                    self.screen = self.exit_screen();
+                }
+            },
+            MenuEvent::Back => self.resume(),
+            MenuEvent::Moved | MenuEvent::Adjusted(..) | MenuEvent::None => {}
+        }
+        Ok(())
+    }
+
+    /// Draws the pause screen overlay
+    pub(crate) fn draw_pause_screen(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        // First draw the game in the background
+        self.draw_game(ctx, canvas)?;
+        
+        // Draw semi-transparent overlay
+        let overlay_rect = graphics::Rect::new(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT);
+        let overlay = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            overlay_rect,
+            Color::new(0.0, 0.0, 0.0, 0.7),
+        )?;
+        canvas.draw(&overlay, graphics::DrawParam::default());
+        
+        // Draw "PAUSED" text with pixelated effect
+        let pause_style = TextStyle::new(self.theme().accent).scale(4.0).centered().shadow(4.0);
+        self.text_renderer.draw_text(canvas, "PAUSED", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 3.0], pause_style);
+        
+        if self.quit_requested {
+            let question_style = TextStyle::new(self.theme().text).scale(2.0).centered();
+            self.text_renderer.draw_text(canvas, "QUIT THE GAME?", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0], question_style);
+            let answer_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+            self.text_renderer.draw_text(canvas, "Y: QUIT   N: KEEP PLAYING", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0 + 70.0], answer_style);
+        } else {
+            self.pause_menu.draw(canvas, &self.text_renderer, self.theme(), [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0], 60.0, 2.0);
+        }
+
+        Ok(())
+    }
+}
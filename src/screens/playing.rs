@@ -0,0 +1,925 @@
+use ggez::{
+    event,
+    graphics::{self, Color},
+    input::keyboard::{KeyCode, KeyInput, KeyMods},
+    Context, GameResult,
+};
+use tetris::tetromino::TetrominoType;
+use tetris::ghost::GhostPlayer;
+use tetris::animation::{LevelUpFanfare, LineClearAnimation, LockFlash};
+use tetris::background::BackgroundStyle;
+use tetris::practice;
+use tetris::events::GameEvent;
+use tetris::game::{LockedPiece, Move};
+use tetris::board::{GameBoard, Playfield};
+use tetris::constants::*;
+use tetris::keybindings::{self, Action};
+use tetris::camera::Camera;
+use tetris::layout::Layout;
+use tetris::mode;
+use tetris::opener::Placement;
+use tetris::overlay::{OverlayServer, OverlayState};
+use tetris::dataset::{self, DataLog, DataRecord};
+use tetris::text::{Align, TextStyle, TEXT_SIZE};
+use tetris::theme::GhostStyle;
+use tetris::touch::Gesture;
+use tetris::tutorial::Lesson;
+use tetris::skin::Tile;
+use std::collections::HashSet;
+use crate::{GameScreen, GameState};
+
+impl GameState {
+    /// Handles a key press during play: pausing, and moving the piece once the resume countdown is over
+    pub(crate) fn handle_game_key(&mut self, ctx: &mut Context, key: KeyCode, repeat: bool) {
+        let action = self.controls().action_for(key);
+        // Pieces can't be moved until the resume countdown ends
+        let counting_down = self.resume_countdown > 0.0;
+        match action {
+            Some(Action::Pause) => {
+                self.pause();
+            }
+            // Held directions repeat through auto shift instead of OS key repeat
+            Some(Action::MoveLeft) if !counting_down && !repeat => {
+                self.stats.on_input();
+                self.play(ctx, Move::Left);
+                self.start_auto_shift(Action::MoveLeft);
+            }
+            Some(Action::MoveRight) if !counting_down && !repeat => {
+                self.stats.on_input();
+                self.play(ctx, Move::Right);
+                self.start_auto_shift(Action::MoveRight);
+            }
+            Some(Action::SoftDrop) if !counting_down => {
+                self.stats.on_soft_drop();
+                self.play(ctx, Move::SoftDrop);
+            }
+            Some(Action::RotateCw) if !counting_down => {
+                self.stats.on_input();
+                self.play(ctx, Move::RotateCw);
+            }
+            Some(Action::HardDrop) if !counting_down => {
+                self.play(ctx, Move::HardDrop);
+            }
+            Some(Action::Hold) if !counting_down && !repeat => {
+                self.play(ctx, Move::Hold);
+            }
+            _ => {}
+        }
+    }
+
+    /// Runs the game in progress for `dt` seconds: the resume countdown, then, unless paused, the game
+    /// core, its animations and the opponent
+    pub(crate) fn update_game(&mut self, ctx: &mut Context, dt: f64) -> GameResult {
+        // Count down before play resumes after a pause
+        if !self.paused && self.resume_countdown > 0.0 {
+            self.resume_countdown = (self.resume_countdown - dt).max(0.0);
+        }
+
+        // Only update game logic if not paused and not counting down,
+        // and in step mode only by a tick for each step asked for
+        let playing = !self.paused && self.resume_countdown <= 0.0;
+        if let Some(dt) = playing.then(|| self.stepper.tick(dt)).flatten() {
+            if let Some(animation) = &mut self.line_clear {
+                animation.update(dt);
+            }
+            if self.lock_flash.as_mut().is_some_and(|flash| flash.update(dt)) {
+                self.lock_flash = None;
+            }
+            if self.level_up.as_mut().is_some_and(|fanfare| fanfare.update(dt)) {
+                self.level_up = None;
+            }
+            self.trainer_notice = (self.trainer_notice - dt).max(0.0);
+            self.attack_meter.update(dt);
+            self.queue_shift.update(dt);
+            self.background.set_level(self.game.level());
+            self.background.update(dt);
+
+            self.particles.update(dt as f32);
+            self.update_auto_shift(ctx, dt)?;
+
+            // Gravity and the wait on cleared rows run in the game core
+            let result = self.game.update(dt);
+            self.handle_events(ctx, &result.events);
+
+            // The computer opponent plays at its own pace
+            self.update_bot(dt);
+
+            // Sprint and ultra games end once their goal is reached
+            self.stats.update(dt);
+            self.recording.record(self.stats.elapsed(), &self.game);
+            self.samples.record(self.stats.elapsed(), self.game.score(), self.stats.locked());
+            if let Some(ghost) = &mut self.ghost {
+                ghost.update(self.stats.elapsed());
+            }
+            if self.screen == GameScreen::Playing && self.modes.get(self.mode).is_complete(&self.game, self.stats.elapsed()) {
+                self.mode_complete = true;
+                self.show_results();
+            }
+
+            self.autosave_timer.update(dt);
+            if self.screen == GameScreen::Playing && self.autosave_timer.take_due() {
+                self.autosave();
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends an input to the game core and reacts to what it did
+    /// Returns true if the input moved the piece
+    fn play(&mut self, ctx: &mut Context, mv: Move) -> bool {
+        let result = self.game.apply(mv);
+        self.handle_events(ctx, &result.events);
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.on_move(&mut self.game, mv, &result);
+        }
+        result.moved
+    }
+
+    /// Hands each event from the game core to the sounds, effects, statistics and the opponent
+    fn handle_events(&mut self, ctx: &mut Context, events: &[GameEvent]) {
+        let mut missed_step = false;
+        let mut board_changed = false;
+        for event in events {
+            board_changed |= event.changes_board() || matches!(event, GameEvent::PieceLocked(_));
+            self.sounds.on_event(ctx, event, self.game.current_piece());
+            self.stats.on_event(event, self.game.rotation_system());
+            self.autosave_timer.on_event(event);
+            if let GameEvent::PieceLocked(locked) = event {
+                self.record_placement(locked);
+                self.replay.add_lock(&locked.piece);
+                if self.races_ghost() {
+                    self.ghost_run.record(self.stats.elapsed(), locked);
+                }
+                if self.modes.get(self.mode).is_practice() {
+                    self.heatmap.record(&locked.cells);
+                }
+                if let Some(trainer) = &mut self.trainer {
+                    missed_step |= trainer.on_lock(locked.piece.kind, &locked.cells) == Placement::Mistake;
+                }
+            }
+            self.spawn_effects(event);
+            self.send_event(event);
+            self.scripts.on_event(&mut self.game, event);
+            if *event == GameEvent::GameOver && self.screen == GameScreen::Playing {
+                self.end_versus(false);
+                self.show_results();
+            }
+        }
+        self.report_script_errors();
+        if !events.is_empty() {
+            self.publish_overlay(events);
+        }
+        if board_changed {
+            self.find_t_slots();
+        }
+
+        // A piece placed off the opener clears the board and deals the opener again
+        if missed_step && self.screen == GameScreen::Playing {
+            self.trainer_notice = OPENER_NOTICE_TIME;
+            let _ = self.reset_game(ctx);
+        }
+    }
+
+    /// Starts the stream overlay server on `port`, or none for port 0 or a port that is taken
+    pub(crate) fn start_overlay(port: u16) -> Option<OverlayServer> {
+        if port == 0 {
+            return None;
+        }
+        OverlayServer::start(port).map_err(|e| log::error!(target: "net", "Could not start the stream overlay server: {}", e)).ok()
+    }
+
+    /// Sends the game as it stands after `events` to the stream overlays, if the server is running
+    pub(crate) fn publish_overlay(&mut self, events: &[GameEvent]) {
+        let Some(overlay) = &mut self.overlay else {
+            return;
+        };
+        let state = OverlayState::capture(&self.game, self.modes.get(self.mode).name(), self.stats.elapsed(), events);
+        overlay.publish(&state);
+    }
+
+    /// Opens the play data log if recording is on, and marks the start of a game in it
+    pub(crate) fn start_play_data(&mut self) {
+        if !self.settings.gameplay.record_play_data {
+            self.play_data = None;
+            return;
+        }
+        if self.play_data.is_none() {
+            match DataLog::open(dataset::play_data_path()) {
+                Ok(log) => self.play_data = Some(log),
+                Err(e) => log::error!(target: "save", "Could not open the play data log: {}", e),
+            }
+        }
+        let record = DataRecord::game(self.modes.get(self.mode).name(), self.game.rotation_system().name(), self.game.board());
+        self.write_play_data(&record);
+    }
+
+    /// Logs a lock with the board it landed on and the queue the player saw, if recording play data
+    fn record_placement(&mut self, locked: &LockedPiece) {
+        if self.play_data.is_none() {
+            return;
+        }
+        // When the lock spawned the next piece, the queue the player saw starts with it
+        let queue = self.game.next_queue();
+        let seen = self.game.current_piece().into_iter().chain(queue).take(queue.len());
+        let record = DataRecord::placement(self.game.board(), locked, self.game.hold_piece(), seen);
+        self.write_play_data(&record);
+    }
+
+    /// Appends a record to the play data log, closing it if writing fails
+    fn write_play_data(&mut self, record: &DataRecord) {
+        let Some(log) = &mut self.play_data else {
+            return;
+        };
+        if let Err(e) = log.write(record) {
+            log::error!(target: "save", "Could not write play data: {}", e);
+            self.play_data = None;
+        }
+    }
+
+    /// Looks for T-spin spots on the board during practice games; other games never show them
+    pub(crate) fn find_t_slots(&mut self) {
+        self.t_slots = if self.modes.get(self.mode).is_practice() {
+            practice::t_slots(self.game.board(), self.game.rotation_system())
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Logs script errors; the failed scripts have already been unloaded
+    pub(crate) fn report_script_errors(&mut self) {
+        for error in self.scripts.take_errors() {
+            log::warn!(target: "script", "Script error in {}", error);
+        }
+    }
+
+    /// Starts the line clear animation and particle effects for a game event
+    fn spawn_effects(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PieceLocked(locked) => {
+                if !self.settings.accessibility.reduce_flashing {
+                    self.lock_flash = Some(LockFlash::new());
+                }
+                if self.settings.video.particles && locked.dropped > 0 {
+                    self.particles.spawn_dust(&self.layout.board_camera(), &locked.cells);
+                }
+                if locked.full_rows.is_empty() {
+                    return;
+                }
+                // Spark bursts count as flashing; the dust puff on hard drops is gentle enough to keep
+                if self.settings.video.particles && !self.settings.accessibility.reduce_flashing {
+                    for &row in &locked.full_rows {
+                        let colors: Vec<Color> = self.game.board()[row].iter().map(|&color| self.block_color(color)).collect();
+                        self.particles.spawn_sparks(&self.layout.board_camera(), row, &colors);
+                    }
+                }
+                // Completed rows flash and collapse while the game core waits to clear them
+                if self.game.is_clearing() {
+                    self.line_clear = Some(LineClearAnimation::new(locked.full_rows.clone()));
+                }
+            }
+            // The animation ends together with the wait for the rows to clear
+            GameEvent::LinesCleared { .. } => self.line_clear = None,
+            // The queue slides up to fill the slot of the piece that just left it
+            GameEvent::PieceSpawned(_) => self.queue_shift.start(),
+            // The jingle plays either way; the tint and the popping number count as flashing
+            GameEvent::LevelUp(level) if !self.settings.accessibility.reduce_flashing => {
+                self.level_up = Some(LevelUpFanfare::new(*level));
+            }
+            _ => {}
+        }
+    }
+
+    /// Draws the hold box; the held piece is dimmed while hold can't be used again
+    fn draw_hold(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        let rect = self.layout.hold;
+        self.draw_panel(ctx, canvas, rect)?;
+        self.draw_panel_label(canvas, rect, "HOLD");
+
+        if let Some(piece) = self.game.hold_piece() {
+            let area = graphics::Rect::new(rect.x, rect.y + Layout::LABEL_HEIGHT, rect.w, rect.h - Layout::LABEL_HEIGHT);
+            let alpha = if self.game.can_hold() { 1.0 } else { 0.35 };
+            self.draw_piece_preview(ctx, canvas, piece, area, alpha)?;
+        }
+
+        // The profile playing is named under the box
+        if let Some(profile) = self.profiles.active() {
+            let x = rect.x + rect.w / 2.0;
+            let label_style = TextStyle::new(self.theme().hint).centered().shadow(0.0);
+            self.text_renderer.draw_text(canvas, "PLAYER", [x, rect.bottom() + 30.0], label_style);
+            let name_style = TextStyle::new(self.theme().text).scale(1.2).centered();
+            self.text_renderer.draw_text(canvas, &profile.name, [x, rect.bottom() + 56.0], name_style);
+        }
+        Ok(())
+    }
+
+    /// Draws the queue of upcoming pieces, the next one at the top
+    fn draw_queue(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        let rect = self.layout.queue;
+        self.draw_panel(ctx, canvas, rect)?;
+        self.draw_panel_label(canvas, rect, "NEXT");
+
+        // After a spawn the pieces rise from one slot lower, and the newly dealt piece fades in at the bottom
+        let slot_height = (rect.h - Layout::LABEL_HEIGHT) / NEXT_QUEUE_SIZE as f32;
+        let shift = 1.0 - self.queue_shift.value();
+        let queue = self.game.next_queue();
+        for (i, piece) in queue.iter().enumerate() {
+            let dealt = i + 1 == queue.len();
+            let offset = if dealt { 0.0 } else { shift };
+            let slot = graphics::Rect::new(
+                rect.x,
+                rect.y + Layout::LABEL_HEIGHT + (i as f32 + offset) * slot_height,
+                rect.w,
+                slot_height,
+            );
+            self.draw_piece_preview(ctx, canvas, piece, slot, if dealt { 1.0 - shift } else { 1.0 })?;
+        }
+        Ok(())
+    }
+
+    /// Draws the main game screen
+    pub(crate) fn draw_game(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+                // Draw the scenery, then cover the board with the theme's background so it stays readable
+                let camera = self.layout.board_camera();
+                let board = camera.bounds();
+                self.background.draw(ctx, canvas, self.settings.video.background)?;
+                if self.settings.video.background != BackgroundStyle::Plain {
+                    let backdrop = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), board, self.theme().background)?;
+                    canvas.draw(&backdrop, graphics::DrawParam::default());
+                }
+
+                // Draw game field border
+                let border_rect = graphics::Rect::new(
+                    board.x - BORDER_WIDTH,
+                    board.y - BORDER_WIDTH,
+                    board.w + 2.0 * BORDER_WIDTH,
+                    board.h + 2.0 * BORDER_WIDTH,
+                );
+                let border_mesh = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::stroke(BORDER_WIDTH),
+                    border_rect,
+                    self.theme().border,
+                )?;
+                canvas.draw(&border_mesh, graphics::DrawParam::default());
+
+        // Draw the grid lines
+        self.grid.draw(ctx, canvas, self.settings.video.grid, &camera, self.theme().grid)?;
+
+                // Draw the game board; while rows are being cleared, the rows above slide down into their place
+                let animation = self.line_clear.as_ref();
+                let reduce_flashing = self.settings.accessibility.reduce_flashing;
+                for y in 0..GRID_HEIGHT {
+                    let row = y as usize;
+                    let offset = animation.map_or(0.0, |animation| animation.row_offset(row));
+                    // With reduced flashing the completed rows fade out instead of flashing white
+                    let mut alpha = 1.0;
+                    if let Some(animation) = animation.filter(|animation| animation.is_cleared_row(row)) {
+                        if animation.collapse_progress() > 0.0 {
+                            continue;
+                        }
+                        if reduce_flashing {
+                            alpha = animation.fade_alpha();
+                        }
+                    }
+                    for x in 0..GRID_WIDTH {
+                        let color = self.game.board()[row][x as usize];
+                        if color != Color::BLACK {
+                            let color = Color::new(color.r, color.g, color.b, alpha);
+                            self.draw_block(ctx, canvas, &camera, x as f32, y as f32 + offset, color)?;
+                        }
+                    }
+                }
+
+                // Shade the cells pieces have locked on during practice
+                if self.settings.practice.heatmap && self.modes.get(self.mode).is_practice() {
+                    self.draw_heatmap(ctx, canvas, &camera, 0.5)?;
+                }
+
+                // Highlight the spots a T could spin into
+                if self.settings.practice.t_spin_hints && !self.t_slots.is_empty() {
+                    self.draw_t_slots(ctx, canvas, &camera)?;
+                }
+
+                // Flash the piece that just locked
+                if self.lock_flash.is_some() {
+                    let inset = GRID_LINE_WIDTH * camera.zoom();
+                    for &(x, y) in self.game.last_locked().iter().filter(|&&(_, y)| y >= 0) {
+                        let cell = camera.cells(x as f32, y as f32, 1.0, 1.0);
+                        let flash = graphics::Mesh::new_rectangle(
+                            ctx,
+                            graphics::DrawMode::fill(),
+                            graphics::Rect::new(cell.x + inset, cell.y + inset, cell.w - 2.0 * inset, cell.h - 2.0 * inset),
+                            Color::WHITE,
+                        )?;
+                        canvas.draw(&flash, graphics::DrawParam::default());
+                    }
+                }
+
+                // Flash the completed rows
+                if let Some(animation) = animation.filter(|_| !reduce_flashing) {
+                    for &row in animation.rows() {
+                        let flash = graphics::Mesh::new_rectangle(
+                            ctx,
+                            graphics::DrawMode::fill(),
+                            camera.cells(0.0, row as f32, GRID_WIDTH as f32, 1.0),
+                            Color::new(1.0, 1.0, 1.0, animation.flash_alpha()),
+                        )?;
+                        canvas.draw(&flash, graphics::DrawParam::default());
+                    }
+                }
+
+                // Draw the ghost piece where the current piece would land
+                if let (true, Some(piece)) = (self.settings.gameplay.ghost_piece, self.game.current_piece()) {
+                    let ghost = self.game.board().drop_position(piece);
+                    let theme = self.theme();
+                    let faded = Color::new(piece.color.r, piece.color.g, piece.color.b, theme.ghost_alpha);
+                    let ghost_color = self.block_color(faded);
+                    for (x, y) in GameBoard::piece_cells(&ghost) {
+                        if y < 0 {
+                            continue;
+                        }
+                        // Skins have a tile of their own for the ghost, tinted with the piece's color
+                        if let Some(skin) = self.skin() {
+                            let [cell_x, cell_y] = camera.cell(x as f32, y as f32);
+                            skin.draw(canvas, Tile::Ghost, cell_x, cell_y, camera.cell_size, ghost_color);
+                            continue;
+                        }
+                        match theme.ghost {
+                            GhostStyle::Filled => self.draw_block(ctx, canvas, &camera, x as f32, y as f32, faded)?,
+                            GhostStyle::Outline => {
+                                let [cell_x, cell_y] = camera.cell(x as f32, y as f32);
+                                let line_width = GRID_LINE_WIDTH * camera.zoom();
+                                let padding = BLOCK_PADDING * camera.zoom();
+                                let outline = graphics::Mesh::new_rectangle(
+                                    ctx,
+                                    graphics::DrawMode::stroke(padding),
+                                    graphics::Rect::new(
+                                        cell_x + line_width + padding / 2.0,
+                                        cell_y + line_width + padding / 2.0,
+                                        camera.cell_size - 2.0 * line_width - padding,
+                                        camera.cell_size - 2.0 * line_width - padding,
+                                    ),
+                                    ghost_color,
+                                )?;
+                                canvas.draw(&outline, graphics::DrawParam::default());
+                            }
+                        }
+                    }
+                }
+
+                // Holding the preview key outlines where each turn of the piece would land
+                let previewing = self.controls().keys_for(Action::PreviewRotations).iter().any(|&key| ctx.keyboard.is_key_pressed(key));
+                if previewing {
+                    self.draw_rotation_previews(ctx, canvas, &camera)?;
+                }
+
+                // Outline where the opener wants the falling piece
+                if self.trainer.is_some() {
+                    self.draw_opener_guide(ctx, canvas, &camera)?;
+                }
+                self.draw_tutorial_prompt(canvas, &camera);
+
+                // Draw the current piece
+                if let Some(piece) = self.game.current_piece() {
+                    for (y, row) in piece.shape.iter().enumerate() {
+                        for (x, &cell) in row.iter().enumerate() {
+                            if cell {
+                        self.draw_block(
+                            ctx, 
+                            canvas, 
+                            &camera,
+                            (piece.position.x as i32 + x as i32) as f32, 
+                            (piece.position.y as i32 + y as i32) as f32, 
+                            piece.color
+                        )?;
+                            }
+                        }
+                    }
+                }
+
+                // Draw sparks and dust over the board
+                self.particles.draw(ctx, canvas)?;
+
+                // Tint the board and show the new level number after a level up
+                if let Some(fanfare) = &self.level_up {
+                    let accent = self.theme().accent;
+                    let tint = graphics::Mesh::new_rectangle(
+                        ctx,
+                        graphics::DrawMode::fill(),
+                        board,
+                        Color::new(accent.r, accent.g, accent.b, 0.35 * fanfare.tint_alpha()),
+                    )?;
+                    canvas.draw(&tint, graphics::DrawParam::default());
+                    let label = format!("LEVEL {}", fanfare.level());
+                    let scale = 4.0 * fanfare.text_scale();
+                    let (_, height) = self.text_renderer.measure(ctx, &label, scale);
+                    let style = TextStyle::new(Color::new(accent.r, accent.g, accent.b, fanfare.text_alpha())).scale(scale).centered().shadow(4.0);
+                    self.text_renderer.draw_text(canvas, &label, [board.center().x, board.y + (board.h - height) / 2.0], style);
+                }
+
+                // Show the touch gestures over the board
+                if self.settings.gameplay.touch_overlay {
+                    self.draw_touch_overlay(ctx, canvas, board)?;
+                }
+
+                // Draw the hold box and the queue of next pieces
+        self.draw_hold(ctx, canvas)?;
+        self.draw_queue(ctx, canvas)?;
+
+        // Draw the score panel
+        self.draw_score_panel(ctx, canvas)?;
+
+        // Draw the statistics panel
+        self.draw_stats_panel(ctx, canvas)?;
+
+        // Draw the opponent's board and our garbage meter during versus games
+        if let Some(link) = &self.versus {
+            self.draw_opponent_board(ctx, canvas, "OPPONENT", &link.opponent_board)?;
+        }
+        if let Some(bot) = &self.bot {
+            self.draw_opponent_board(ctx, canvas, "OPPONENT", &bot.board_with_piece())?;
+        }
+        if let Some(ghost) = &self.ghost {
+            self.draw_ghost_board(ctx, canvas, ghost)?;
+        }
+        if self.versus.is_some() || self.bot.is_some() || self.game.pending_garbage() > 0 {
+            self.draw_garbage_meter(ctx, canvas)?;
+        }
+        
+        Ok(())
+    }
+
+    /// Labels the board with the touch gestures: dragging sideways and down near the edges, tapping in the middle,
+    /// flicking and holding along the bottom, and tapping beside the board to pause
+    fn draw_touch_overlay(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, board: graphics::Rect) -> GameResult {
+        let hint = self.theme().hint;
+        let band = graphics::Rect::new(board.x, board.bottom() - GRID_SIZE * 3.0, board.w, GRID_SIZE * 3.0);
+        let shade = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), band, Color::new(0.0, 0.0, 0.0, 0.35))?;
+        canvas.draw(&shade, graphics::DrawParam::default());
+
+        let style = TextStyle::new(Color::new(hint.r, hint.g, hint.b, 0.8)).centered().shadow(1.0);
+        let middle = board.center();
+        self.text_renderer.draw_text(canvas, "TAP: ROTATE", [middle.x, middle.y], style);
+        self.text_renderer.draw_text(canvas, "< SWIPE", [board.x + GRID_SIZE * 1.5, middle.y - GRID_SIZE * 2.0], style);
+        self.text_renderer.draw_text(canvas, "SWIPE >", [board.right() - GRID_SIZE * 1.5, middle.y - GRID_SIZE * 2.0], style);
+        let lines = ["SWIPE UP: HOLD", "SWIPE DOWN: SOFT DROP", "FLICK DOWN: HARD DROP", "TAP OUTSIDE: PAUSE"];
+        for (i, line) in lines.iter().enumerate() {
+            self.text_renderer.draw_text(canvas, line, [middle.x, band.y + 20.0 + i as f32 * 38.0], style);
+        }
+        Ok(())
+    }
+
+    /// Outlines the cells of the opener step the falling piece is for, and shows how far the opener has got above the board
+    fn draw_opener_guide(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, camera: &Camera) -> GameResult {
+        let Some(trainer) = &self.trainer else {
+            return Ok(());
+        };
+        let accent = self.theme().accent;
+        let target = self.game.current_piece().and_then(|piece| trainer.target(piece.kind));
+        if let Some(step) = target {
+            let inset = (GRID_LINE_WIDTH + BLOCK_PADDING) * camera.zoom();
+            let mut outlines = graphics::MeshBuilder::new();
+            for &(x, y) in &step.cells {
+                let cell = camera.cells(x as f32, y as f32, 1.0, 1.0);
+                let rect = graphics::Rect::new(cell.x + inset, cell.y + inset, cell.w - 2.0 * inset, cell.h - 2.0 * inset);
+                outlines.rectangle(graphics::DrawMode::stroke(BLOCK_PADDING * camera.zoom()), rect, accent)?;
+            }
+            let mesh = graphics::Mesh::from_data(ctx, outlines.build());
+            canvas.draw(&mesh, graphics::DrawParam::default());
+        }
+
+        let opener = trainer.opener();
+        let status = if trainer.progress() == opener.steps.len() {
+            format!("{} COMPLETE", opener.name)
+        } else if self.trainer_notice > 0.0 {
+            "MISSED - TRY AGAIN".to_string()
+        } else {
+            format!("{} {}/{}", opener.name, trainer.progress(), opener.steps.len())
+        };
+        let board = camera.bounds();
+        let style = TextStyle::new(accent).centered().shadow(2.0);
+        self.text_renderer.draw_text(canvas, &status, [board.center().x, board.y - 40.0], style);
+        Ok(())
+    }
+
+    /// Shows the tutorial's lesson above the board, with the keys that do it
+    fn draw_tutorial_prompt(&self, canvas: &mut graphics::Canvas, camera: &Camera) {
+        let Some(tutorial) = &self.tutorial else {
+            return;
+        };
+        let text = match tutorial.lesson() {
+            Some(lesson) => {
+                let keys: Vec<&str> = lesson
+                    .actions()
+                    .iter()
+                    .filter_map(|&action| self.controls().keys_for(action).first().and_then(|&key| keybindings::key_name(key)))
+                    .collect();
+                format!("{}/{} {} ({})", tutorial.progress() + 1, Lesson::ALL.len(), lesson.prompt(), keys.join(" / "))
+            }
+            None => "TUTORIAL COMPLETE - ESC TO LEAVE".to_string(),
+        };
+        let board = camera.bounds();
+        let style = TextStyle::new(self.theme().accent).centered().shadow(2.0);
+        self.text_renderer.draw_text(canvas, &text, [board.center().x, board.y - 40.0], style);
+    }
+
+    /// Outlines the landing spot of each rotation state of the falling piece in its color, numbered by the turns it takes
+    fn draw_rotation_previews(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, camera: &Camera) -> GameResult {
+        let previews = self.game.rotation_previews();
+        let Some(piece) = previews.first() else {
+            return Ok(());
+        };
+        let color = self.block_color(piece.color);
+        let faded = Color::new(color.r, color.g, color.b, self.theme().ghost_alpha);
+        let inset = (GRID_LINE_WIDTH + BLOCK_PADDING) * camera.zoom();
+        let mut outlines = graphics::MeshBuilder::new();
+        for preview in &previews {
+            for (x, y) in GameBoard::piece_cells(preview).into_iter().filter(|&(_, y)| y >= 0) {
+                let cell = camera.cells(x as f32, y as f32, 1.0, 1.0);
+                let rect = graphics::Rect::new(cell.x + inset, cell.y + inset, cell.w - 2.0 * inset, cell.h - 2.0 * inset);
+                outlines.rectangle(graphics::DrawMode::stroke(BLOCK_PADDING * camera.zoom()), rect, faded)?;
+            }
+        }
+        let mesh = graphics::Mesh::from_data(ctx, outlines.build());
+        canvas.draw(&mesh, graphics::DrawParam::default());
+
+        // The number sits on the middle of each spot, so overlapping spots can still be told apart
+        let style = TextStyle::new(color).centered().shadow(2.0);
+        for (turns, preview) in previews.iter().enumerate() {
+            let cells = GameBoard::piece_cells(preview);
+            let x = cells.iter().map(|&(x, _)| x as f32).sum::<f32>() / cells.len() as f32;
+            let y = cells.iter().map(|&(_, y)| y as f32).sum::<f32>() / cells.len() as f32;
+            let [label_x, label_y] = camera.cell(x + 0.5, y + 0.5);
+            self.text_renderer.draw_text(canvas, &turns.to_string(), [label_x, label_y - TEXT_SIZE / 2.0], style);
+        }
+        Ok(())
+    }
+
+    /// Fills the cells of each T-spin spot in the T's color, more strongly for doubles, and labels it
+    fn draw_t_slots(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, camera: &Camera) -> GameResult {
+        let color = self.block_color(TetrominoType::T.color());
+        let mut cells = graphics::MeshBuilder::new();
+        for slot in &self.t_slots {
+            let alpha = if slot.lines == 2 { 0.45 } else { 0.25 };
+            for &(x, y) in slot.cells.iter().filter(|&&(_, y)| y >= 0) {
+                cells.rectangle(graphics::DrawMode::fill(), camera.cells(x as f32, y as f32, 1.0, 1.0), Color::new(color.r, color.g, color.b, alpha))?;
+            }
+        }
+        let mesh = graphics::Mesh::from_data(ctx, cells.build());
+        canvas.draw(&mesh, graphics::DrawParam::default());
+
+        // A single often shares cells with a double in the same slot; only the double, listed first, is labeled
+        let style = TextStyle::new(self.theme().text).centered().shadow(1.0);
+        let mut labeled: HashSet<(i32, i32)> = HashSet::new();
+        for slot in &self.t_slots {
+            if slot.cells.iter().any(|cell| labeled.contains(cell)) {
+                continue;
+            }
+            labeled.extend(&slot.cells);
+            let (x, y) = slot.cells.iter().copied().min_by_key(|&(x, y)| (y, x)).unwrap_or_default();
+            if y >= 0 {
+                let label = if slot.lines == 2 { "TSD" } else { "TSS" };
+                let cell = camera.cells(x as f32, y as f32, 1.0, 1.0);
+                self.text_renderer.draw_text(canvas, label, [cell.center().x, cell.y - 24.0 * camera.zoom()], style);
+            }
+        }
+        Ok(())
+    }
+
+    /// Shades each cell of the board `camera` shows by how often practice pieces locked on it
+    /// `opacity` is that of the busiest cells
+    pub(crate) fn draw_heatmap(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, camera: &Camera, opacity: f32) -> GameResult {
+        if self.heatmap.pieces() == 0 {
+            return Ok(());
+        }
+        let mut cells = graphics::MeshBuilder::new();
+        for y in 0..GRID_HEIGHT as usize {
+            for x in 0..GRID_WIDTH as usize {
+                let heat = self.heatmap.heat(x, y);
+                if heat > 0.0 {
+                    cells.rectangle(graphics::DrawMode::fill(), camera.cells(x as f32, y as f32, 1.0, 1.0), practice::heat_color(heat, opacity))?;
+                }
+            }
+        }
+        let mesh = graphics::Mesh::from_data(ctx, cells.build());
+        canvas.draw(&mesh, graphics::DrawParam::default());
+        Ok(())
+    }
+
+    /// Draws the ghost's board where an opponent's would be, with how far ahead of it the player is
+    fn draw_ghost_board(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, ghost: &GhostPlayer) -> GameResult {
+        self.draw_opponent_board(ctx, canvas, "GHOST", ghost.board().rows())?;
+        let board = self.layout.opponent_camera().bounds();
+        let elapsed = self.stats.elapsed();
+        let status = if ghost.is_finished(elapsed) {
+            format!("DONE {}", mode::format_time(ghost.run().time))
+        } else {
+            format!("{} LEFT", SPRINT_LINES.saturating_sub(ghost.lines()))
+        };
+        let status_style = TextStyle::new(self.theme().hint).shadow(0.0);
+        self.text_renderer.draw_text(canvas, &status, [board.x, board.bottom() + 4.0], status_style);
+        let lead = self.game.lines_cleared() as i64 - ghost.lines() as i64;
+        let (lead, color) = match lead {
+            0 => ("EVEN".to_string(), self.theme().text),
+            lead if lead > 0 => (format!("AHEAD {}", lead), Color::GREEN),
+            lead => (format!("BEHIND {}", -lead), Color::RED),
+        };
+        let lead_style = TextStyle::new(color).shadow(0.0);
+        self.text_renderer.draw_text(canvas, &lead, [board.x, board.bottom() + 4.0 + TEXT_SIZE], lead_style);
+        Ok(())
+    }
+
+    /// Starts delayed auto shift for a direction that was just pressed
+    fn start_auto_shift(&mut self, direction: Action) {
+        self.held_direction = Some(direction);
+        self.das_timer = 0.0;
+        self.arr_timer = 0.0;
+    }
+
+    /// Repeats the held direction once it has been held longer than the DAS delay
+    fn update_auto_shift(&mut self, ctx: &mut Context, dt: f64) -> GameResult {
+        let Some(direction) = self.held_direction else {
+            return Ok(());
+        };
+        let held = self
+            .controls()
+            .keys_for(direction)
+            .iter()
+            .any(|&key| ctx.keyboard.is_key_pressed(key));
+        if !held {
+            self.held_direction = None;
+            return Ok(());
+        }
+
+        self.das_timer += dt;
+        if self.das_timer < self.settings.gameplay.das_ms as f64 / 1000.0 {
+            return Ok(());
+        }
+
+        let movement = if direction == Action::MoveLeft { Move::Left } else { Move::Right };
+        let arr = self.settings.gameplay.arr_ms as f64 / 1000.0;
+        if arr <= 0.0 {
+            // Zero ARR moves the piece straight to the wall
+            while self.play(ctx, movement) {}
+            return Ok(());
+        }
+        self.arr_timer += dt;
+        while self.arr_timer >= arr {
+            self.arr_timer -= arr;
+            if !self.play(ctx, movement) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Acts on a touchscreen gesture: during a game it moves the piece; on other screens it works the menus
+    /// like the arrow keys and Enter
+    pub(crate) fn handle_gesture(&mut self, ctx: &mut Context, gesture: Gesture) -> GameResult {
+        if self.screen == GameScreen::Playing && !self.paused && !self.quit_requested {
+            if self.resume_countdown > 0.0 {
+                return Ok(());
+            }
+            match gesture {
+                // Tapping beside the board is the only way to pause without a keyboard
+                Gesture::Tap(x, y) if !self.layout.board.contains([x, y]) => self.pause(),
+                Gesture::Tap(..) => {
+                    self.stats.on_input();
+                    self.play(ctx, Move::RotateCw);
+                }
+                Gesture::Left | Gesture::Right => {
+                    self.stats.on_input();
+                    self.play(ctx, if gesture == Gesture::Left { Move::Left } else { Move::Right });
+                }
+                Gesture::Down => {
+                    self.stats.on_soft_drop();
+                    self.play(ctx, Move::SoftDrop);
+                }
+                Gesture::Up => {
+                    self.play(ctx, Move::Hold);
+                }
+                Gesture::Flick => {
+                    self.play(ctx, Move::HardDrop);
+                }
+            }
+            return Ok(());
+        }
+        let key = match gesture {
+            Gesture::Tap(..) => KeyCode::Return,
+            Gesture::Left => KeyCode::Left,
+            Gesture::Right => KeyCode::Right,
+            Gesture::Up => KeyCode::Up,
+            Gesture::Down => KeyCode::Down,
+            Gesture::Flick => return Ok(()),
+        };
+        let input = KeyInput { scancode: 0, keycode: Some(key), mods: KeyMods::empty() };
+        event::EventHandler::key_down_event(self, ctx, input, false)
+    }
+
+    /// Draws the seconds left before play resumes over the game
+    pub(crate) fn draw_resume_countdown(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        let count = format!("{}", self.resume_countdown.ceil() as u32);
+        let count_scale = 6.0;
+        let (_, count_height) = self.text_renderer.measure(ctx, &count, count_scale);
+        let board = self.layout.board;
+        let count_style = TextStyle::new(self.theme().accent).scale(count_scale).centered().shadow(4.0);
+        self.text_renderer.draw_text(canvas, &count, [board.center().x, board.y + (board.h - count_height) / 2.0], count_style);
+
+        Ok(())
+    }
+
+    /// Draws the UI panel with score information
+    fn draw_score_panel(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        // Draw score panel background with pixelated corners
+        let panel = self.layout.score;
+        self.draw_panel(ctx, canvas, panel)?;
+        
+        // Label and value rows; sprint, ultra and dig games show their goal as a fifth row
+        let mut rows = vec![
+            ("SCORE", self.game.score().to_string()),
+            ("LEVEL", self.game.level().to_string()),
+            ("LINES", self.game.lines_cleared().to_string()),
+            ("TIME", mode::format_time(self.stats.elapsed())),
+        ];
+        rows.extend(self.modes.get(self.mode).goal(&self.game, self.stats.elapsed()));
+        let has_goal = rows.len() > 4;
+        
+        // Calculate total height of all text elements
+        let text_scale = 1.5;
+        let text_spacing = if has_goal { 50.0 } else { 60.0 };  // Tighter when the goal row is shown
+        let total_text_height = text_spacing * (rows.len() - 1) as f32;  // Space between the rows
+        
+        // Calculate starting Y position to center all text vertically
+        let text_y_start = panel.y + (panel.h - total_text_height) / 2.0 - 20.0;  // Moved up slightly to better center the whole block
+        
+        // Calculate horizontal position
+        let text_x = panel.x + GRID_SIZE * 1.5;
+        let label_width = 80.0;  // Fixed width for labels
+        
+        // Labels right-aligned and values left-aligned around a fixed column
+        let label_style = TextStyle::new(self.theme().text).scale(text_scale).align(Align::Right);
+        let value_style = TextStyle::new(self.theme().text).scale(text_scale);
+        for (i, (label, value)) in rows.iter().enumerate() {
+            let y = text_y_start + text_spacing * i as f32;
+            self.text_renderer.draw_text(canvas, label, [text_x + label_width, y], label_style);
+            self.text_renderer.draw_text(canvas, value, [text_x + label_width + 20.0, y], value_style);
+        }
+        
+        Ok(())
+    }
+
+    /// Draws the statistics panel below the score panel
+    /// During versus games and ghost races the other board takes the left part, so the panel narrows to one column
+    fn draw_stats_panel(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        let has_opponent = self.versus.is_some() || self.bot.is_some() || self.ghost.is_some();
+        let panel = if has_opponent {
+            self.layout.stats_beside_opponent()
+        } else {
+            self.layout.stats
+        };
+        let graphics::Rect { x: panel_x, y: panel_y, w: panel_w, .. } = panel;
+        self.draw_panel(ctx, canvas, panel)?;
+
+        // Rows are a piece swatch or label, then a value
+        let line_height = 26.0;
+        let swatch_size = 14.0;
+        let padding = 20.0;
+        let column_width = if has_opponent { panel_w } else { panel_w / 2.0 };
+        let rates = [
+            ("PPS", format!("{:.2}", self.stats.pieces_per_second())),
+            ("LPM", format!("{:.1}", self.stats.lines_per_minute())),
+        ];
+
+        let label_style = TextStyle::new(self.theme().hint);
+        let value_style = TextStyle::new(self.theme().text);
+
+        // Piece counts down the first column
+        let mut swatches = graphics::MeshBuilder::new();
+        for (i, &kind) in TetrominoType::ALL.iter().enumerate() {
+            let x = panel_x + padding;
+            let y = panel_y + padding + i as f32 * line_height;
+            swatches.rectangle(
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(x, y + 1.0, swatch_size, swatch_size),
+                self.theme().pieces.get(kind),
+            )?;
+            let count = format!("{:?}  {}", kind, self.stats.spawned(kind));
+            self.text_renderer.draw_text(canvas, &count, [x + swatch_size + 10.0, y], value_style);
+        }
+
+        // Rates in the second column, or below the counts when there is only one
+        for (i, (label, value)) in rates.iter().enumerate() {
+            let (x, y) = if has_opponent {
+                (panel_x + padding, panel_y + padding + (TetrominoType::ALL.len() + i) as f32 * line_height + 10.0)
+            } else {
+                (panel_x + column_width + padding, panel_y + padding + i as f32 * line_height)
+            };
+            self.text_renderer.draw_text(canvas, label, [x, y], label_style);
+            self.text_renderer.draw_text(canvas, value, [x + 60.0, y], value_style);
+        }
+
+        let swatch_mesh = graphics::Mesh::from_data(ctx, swatches.build());
+        canvas.draw(&swatch_mesh, graphics::DrawParam::default());
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use ggez::{
+    graphics::{self, Color},
+    input::keyboard::KeyCode,
+    Context,
+};
+use tetris::constants::*;
+use tetris::keybindings::Handedness;
+use tetris::mode;
+use tetris::profiles::{self, ProfileError, ProfileStore};
+use tetris::text::TextStyle;
+use tetris::ui::{keycode_to_char, Menu, MenuEvent};
+use crate::{GameScreen, GameState};
+
+impl GameState {
+    /// Lists the profiles followed by the entries for a new profile and for playing as a guest,
+    /// with the profile chosen last selected
+    pub(crate) fn new_profile_menu(profiles: &ProfileStore) -> Menu {
+        let mut labels: Vec<&str> = profiles.profiles.iter().map(|profile| profile.name.as_str()).collect();
+        labels.extend(["NEW PROFILE", "GUEST"]);
+        let mut menu = Menu::new(&labels);
+        menu.select(profiles.last_index().unwrap_or(0));
+        menu
+    }
+
+    /// Opens the profile screen with the current profile selected
+    pub(crate) fn open_profiles(&mut self) {
+        self.profile_menu = Self::new_profile_menu(&self.profiles);
+        self.screen = GameScreen::Profiles;
+    }
+
+    /// Handles a key press on the profile screen
+    pub(crate) fn handle_profiles_key(&mut self, ctx: &mut Context, key: KeyCode) {
+        let event = self.profile_menu.handle_key(key);
+        self.play_menu_sound(ctx, event);
+        let count = self.profiles.profiles.len();
+        match event {
+            MenuEvent::Activated(index) if index < count => self.choose_profile(Some(index)),
+            MenuEvent::Activated(index) if index == count => {
+                self.new_profile_name.clear();
+                self.new_profile_hand = Handedness::Right;
+                self.profile_error = None;
+                self.screen = GameScreen::NewProfile;
+            }
+            MenuEvent::Activated(_) => self.choose_profile(None),
+            MenuEvent::Back => self.screen = GameScreen::Title,
+            MenuEvent::Moved | MenuEvent::Adjusted(..) | MenuEvent::None => {}
+        }
+    }
+
+    /// Handles a key press while a new profile is being named
+    pub(crate) fn handle_new_profile_key(&mut self, ctx: &mut Context, key: KeyCode) {
+        match key {
+            KeyCode::Return => match self.profiles.create(&self.new_profile_name, self.new_profile_hand) {
+                Ok(index) => self.choose_profile(Some(index)),
+                Err(e) => self.profile_error = Some(e),
+            },
+            KeyCode::Escape => self.screen = GameScreen::Profiles,
+            KeyCode::Left | KeyCode::Right => {
+                let index = Handedness::ALL.iter().position(|&hand| hand == self.new_profile_hand).unwrap_or(0);
+                self.new_profile_hand = Handedness::ALL[(index + 1) % Handedness::ALL.len()];
+                self.sounds.play_move(ctx);
+            }
+            KeyCode::Back => {
+                self.new_profile_name.pop();
+            }
+            key if self.new_profile_name.len() < profiles::MAX_NAME_LENGTH => {
+                let shift = ctx.keyboard.is_key_pressed(KeyCode::LShift) || ctx.keyboard.is_key_pressed(KeyCode::RShift);
+                if let Some(ch) = keycode_to_char(key, shift) {
+                    self.new_profile_name.push(ch);
+                    self.profile_error = None;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Makes a profile the one playing, or plays as a guest with `None`, and goes on to the title screen
+    fn choose_profile(&mut self, index: Option<usize>) {
+        self.profiles.select(index);
+        self.save_profiles();
+        self.screen = GameScreen::Title;
+    }
+
+    /// Draws the profile screen, with the totals of the selected profile under the list
+    pub(crate) fn draw_profiles_screen(&self, canvas: &mut graphics::Canvas) {
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "WHO IS PLAYING?", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 6.0], title_style);
+
+        let top = SCREEN_HEIGHT / 6.0 + 120.0;
+        self.profile_menu.draw(canvas, &self.text_renderer, self.theme(), [SCREEN_WIDTH / 2.0, top], 50.0, 2.0);
+
+        if let Some(profile) = self.profiles.profiles.get(self.profile_menu.selected()) {
+            let stats = &profile.stats;
+            let lines = [
+                profile.handedness.label().to_string(),
+                format!("GAMES {}   BEST {}   LINES {}", stats.games, stats.best_score, stats.lines),
+                format!("PIECES {}   TIME {}", stats.pieces, mode::format_time(stats.play_time)),
+            ];
+            let stats_style = TextStyle::new(self.theme().text).scale(1.5).centered().shadow(0.0);
+            let y = top + self.profile_menu.items.len() as f32 * 50.0 + 30.0;
+            for (i, line) in lines.iter().enumerate() {
+                self.text_renderer.draw_text(canvas, line, [SCREEN_WIDTH / 2.0, y + i as f32 * 36.0], stats_style);
+            }
+        }
+
+        let help_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+        let help = "UP/DOWN: SELECT   ENTER: CHOOSE   ESC: BACK";
+        self.text_renderer.draw_text(canvas, help, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 160.0], help_style);
+    }
+
+    /// Draws the screen for naming a new profile and picking its hand
+    pub(crate) fn draw_new_profile_screen(&self, canvas: &mut graphics::Canvas) {
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "NEW PROFILE", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 6.0], title_style);
+
+        let text_style = TextStyle::new(self.theme().text).scale(1.5).centered().shadow(0.0);
+        self.text_renderer.draw_text(canvas, "NAME:", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0 - 90.0], text_style);
+        let cursor = if self.show_cursor { "_" } else { " " };
+        let name = format!("{}{}", self.new_profile_name, cursor);
+        let name_style = TextStyle::new(self.theme().accent).scale(2.0).centered();
+        self.text_renderer.draw_text(canvas, &name, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0 - 40.0], name_style);
+
+        let hand = format!("< {} >", self.new_profile_hand.label());
+        self.text_renderer.draw_text(canvas, &hand, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0 + 40.0], text_style);
+
+        if let Some(error) = self.profile_error {
+            let message = match error {
+                ProfileError::EmptyName => "TYPE A NAME FIRST",
+                ProfileError::NameTaken => "THAT NAME IS TAKEN",
+            };
+            let error_style = TextStyle::new(Color::RED).scale(1.5).centered().shadow(0.0);
+            self.text_renderer.draw_text(canvas, message, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 2.0 + 110.0], error_style);
+        }
+
+        let help_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+        let help = "LEFT/RIGHT: HAND   ENTER: CREATE   ESC: BACK";
+        self.text_renderer.draw_text(canvas, help, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 160.0], help_style);
+    }
+}
@@ -0,0 +1,76 @@
+use ggez::{graphics, input::keyboard::KeyCode};
+use tetris::stats;
+use tetris::constants::*;
+use tetris::text::{Align, TextStyle};
+use crate::{GameScreen, GameState};
+
+impl GameState {
+    /// Draws the game found in the autosave and asks whether to record it
+    pub(crate) fn draw_recovered_screen(&self, canvas: &mut graphics::Canvas) {
+        let Some(recovered) = &self.recovered else {
+            return;
+        };
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "GAME RECOVERED", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 6.0], title_style);
+        let hint_style = TextStyle::new(self.theme().hint).scale(1.2).centered().shadow(0.0);
+        let note = "THE LAST GAME ENDED WITHOUT A GAME OVER";
+        self.text_renderer.draw_text(canvas, note, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 6.0 + 60.0], hint_style);
+
+        let entry = &recovered.entry;
+        let rows = [
+            ("MODE", entry.mode.clone()),
+            ("SCORE", entry.score.to_string()),
+            ("LEVEL", entry.level.to_string()),
+            ("LINES", entry.lines.to_string()),
+            ("TIME", entry.time()),
+        ];
+        let label_style = TextStyle::new(self.theme().text).scale(1.5).align(Align::Right).shadow(0.0);
+        let value_style = TextStyle::new(self.theme().accent).scale(1.5).shadow(0.0);
+        for (row, (label, value)) in rows.iter().enumerate() {
+            let y = SCREEN_HEIGHT / 3.0 + row as f32 * 45.0;
+            self.text_renderer.draw_text(canvas, label, [SCREEN_WIDTH / 2.0 - 20.0, y], label_style);
+            self.text_renderer.draw_text(canvas, value, [SCREEN_WIDTH / 2.0 + 20.0, y], value_style);
+        }
+
+        let text_style = TextStyle::new(self.theme().text).scale(1.5).centered().shadow(0.0);
+        self.text_renderer.draw_text(canvas, "RECORD THIS SCORE?", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 230.0], text_style);
+        let help_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+        let help = "Y/ENTER: RECORD   N/ESC: DISCARD";
+        self.text_renderer.draw_text(canvas, help, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 160.0], help_style);
+    }
+
+    /// Handles a key press on the recovered game screen: Y records the game, N throws it away
+    pub(crate) fn handle_recovered_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Y | KeyCode::Return => self.record_recovered(),
+            KeyCode::N | KeyCode::Escape => {
+                self.recovered = None;
+                self.discard_autosave();
+                self.screen = GameScreen::Profiles;
+            }
+            _ => {}
+        }
+    }
+
+    /// Records the game found in the autosave: its statistics join the lifetime totals, and a score
+    /// that makes the table goes on to name entry
+    fn record_recovered(&mut self) {
+        let Some(recovered) = &self.recovered else {
+            return;
+        };
+        self.lifetime.record(&recovered.entry, &recovered.stats, false);
+        self.add_to_history(&recovered.entry);
+        if let Err(e) = self.lifetime.save(stats::lifetime_stats_path()) {
+            log::error!(target: "save", "Could not save the lifetime statistics: {}", e);
+        }
+        let qualifies = self.high_scores.would_qualify(recovered.entry.score);
+        self.discard_autosave();
+        if qualifies {
+            self.begin_name_entry();
+            self.screen = GameScreen::EnterName;
+        } else {
+            self.recovered = None;
+            self.screen = GameScreen::Profiles;
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use ggez::{
+    graphics::{self, Color},
+    input::keyboard::KeyCode,
+    Context, GameResult,
+};
+use tetris::stats;
+use tetris::constants::*;
+use tetris::high_scores::HighScoreEntry;
+use tetris::net::VersusLink;
+use tetris::camera::Camera;
+use tetris::chart;
+use tetris::clip;
+use tetris::mode;
+use tetris::text::{Align, TextStyle};
+use tetris::versus::VersusMatch;
+use crate::{GameScreen, GameState};
+
+impl GameState {
+    /// Shows the breakdown of the game that just ended, adding it to the lifetime statistics
+    /// and to the profile playing
+    pub(crate) fn show_results(&mut self) {
+        self.screen = GameScreen::Results;
+        self.discard_autosave();
+        self.recording.capture(self.stats.elapsed(), &self.game);
+        self.samples.finish(self.stats.elapsed(), self.game.score(), self.stats.locked());
+        self.save_ghost();
+        let name = self.profiles.active().map(|profile| profile.name.clone()).unwrap_or_default();
+        self.add_to_history(&self.score_entry(name));
+        if !self.modes.get(self.mode).is_ranked() {
+            return;
+        }
+        let entry = self.score_entry(String::new());
+        self.lifetime.record(&entry, &self.stats, self.mode_complete);
+        if let Err(e) = self.lifetime.save(stats::lifetime_stats_path()) {
+            log::error!(target: "save", "Could not save the lifetime statistics: {}", e);
+        }
+        if let Some(profile) = self.profiles.active_mut() {
+            profile.record_game(HighScoreEntry { name: profile.name.clone(), ..entry }, &self.stats, self.mode_complete);
+            self.save_profiles();
+        }
+    }
+
+    /// Saves the marked segment of the instant replay, or the game's last seconds, as an animated GIF
+    fn save_clip(&mut self) {
+        let Some(player) = self.instant_replay.clone().or_else(|| self.recording.play()) else {
+            return;
+        };
+        let path = clip::new_clip_path();
+        self.clip_status = match clip::save_gif(player.segment(), &path) {
+            Ok(()) => format!("SAVED {}", path.display()),
+            Err(e) => format!("COULD NOT SAVE THE GIF: {}", e),
+        };
+    }
+
+    /// Handles a key press on the results screen, held keys aside: replays and clips of the game,
+    /// then moving on to the next round, the name entry or the title
+    pub(crate) fn handle_results_key(&mut self, ctx: &mut Context, key: KeyCode) -> GameResult {
+        // R starts or stops the slow-motion replay of the game's last seconds; while it plays,
+        // [ and ] mark the segment G saves as a GIF, otherwise G saves all of it
+        match key {
+            KeyCode::R => {
+                self.instant_replay = match self.instant_replay {
+                    Some(_) => None,
+                    None => self.recording.play(),
+                };
+                return Ok(());
+            }
+            KeyCode::LBracket => {
+                if let Some(player) = &mut self.instant_replay {
+                    player.mark_start();
+                }
+                return Ok(());
+            }
+            KeyCode::RBracket => {
+                if let Some(player) = &mut self.instant_replay {
+                    player.mark_end();
+                }
+                return Ok(());
+            }
+            KeyCode::G => {
+                self.save_clip();
+                return Ok(());
+            }
+            _ => {}
+        }
+        // Between the rounds of a match Enter plays on and Esc leaves the match
+        if self.match_continues() {
+            match key {
+                KeyCode::Return | KeyCode::Space => self.next_round(ctx)?,
+                KeyCode::Escape => {
+                    self.leave_versus();
+                    self.screen = self.exit_screen();
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        // Enter moves on to name entry if the score made the table, otherwise back to the title
+        // Other keys are ignored so a key still held from play doesn't skip the results
+        if matches!(key, KeyCode::Return | KeyCode::Space) {
+            self.screen = if self.check_high_score() {
+                self.begin_name_entry();
+                GameScreen::EnterName
+            } else {
+                // Without a name entry only a profile's games have a name to go online under
+                if let Some(name) = self.profiles.active().map(|profile| profile.name.clone()) {
+                    self.submit_score(name);
+                }
+                self.exit_screen()
+            };
+        }
+        Ok(())
+    }
+
+    /// Draws the results screen: the final board behind a breakdown of the finished game
+    pub(crate) fn draw_results_screen(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        // First draw the game board in the background, dimmed so the breakdown stands out
+        self.draw_game(ctx, canvas)?;
+        let dim = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT),
+            Color::new(0.0, 0.0, 0.0, 0.75),
+        )?;
+        canvas.draw(&dim, graphics::DrawParam::default());
+
+        let title_y = SCREEN_HEIGHT * 0.15;
+
+        // Draw "GAME OVER" text with pixelated effect, centered on `title_y`
+        let game_over_scale = 3.0;
+        let (game_over_width, game_over_height) = self.text_renderer.measure(ctx, "GAME OVER", game_over_scale);
+        let game_over_x = (SCREEN_WIDTH - game_over_width) / 2.0;
+        let game_over_y = title_y - game_over_height / 2.0;
+        
+        // Draw multiple outlines for pixel-art effect
+        let outline = TextStyle::new(Color::BLACK).scale(game_over_scale).shadow(0.0);
+        for dx in [-2, -1, 1, 2] {
+            for dy in [-2, -1, 1, 2] {
+                self.text_renderer.draw_text(canvas, "GAME OVER", [game_over_x + dx as f32, game_over_y + dy as f32], outline);
+            }
+        }
+        
+        // Draw each letter with a slightly different shade of red
+        let game_over_chars = "GAME OVER".chars().collect::<Vec<_>>();
+        let char_width = game_over_width / game_over_chars.len() as f32;
+        
+        for (i, ch) in game_over_chars.iter().enumerate() {
+            // Skip spaces
+            if *ch == ' ' {
+                continue;
+            }
+            
+            // Alternate between different shades of red
+            let color = if i % 2 == 0 {
+                Color::from_rgb(255, 40, 40)
+            } else {
+                Color::from_rgb(220, 0, 0)
+            };
+            
+            let style = TextStyle::new(color).scale(game_over_scale).shadow(0.0);
+            self.text_renderer.draw_text(canvas, &ch.to_string(), [game_over_x + i as f32 * char_width, game_over_y], style);
+        }
+
+        // Show the result of a sprint or ultra game, or the outcome of a versus round or match
+        let outcome = if self.mode_complete {
+            let result = self.modes.get(self.mode).result(&self.game, self.stats.elapsed());
+            Some((result, Color::GREEN))
+        } else {
+            self.versus_result.map(|won| {
+                let round = match &self.versus_match {
+                    Some(versus_match) if !versus_match.is_over() => format!("ROUND {}: ", versus_match.rounds().len()),
+                    Some(_) => "MATCH: ".to_string(),
+                    None => String::new(),
+                };
+                if won {
+                    (format!("{}YOU WIN!", round), Color::GREEN)
+                } else {
+                    (format!("{}YOU LOSE", round), Color::RED)
+                }
+            })
+        };
+        if let Some((outcome, color)) = outcome {
+            let outcome_style = TextStyle::new(color).scale(2.5).centered().shadow(0.0);
+            self.text_renderer.draw_text(canvas, &outcome, [SCREEN_WIDTH / 2.0, title_y + 60.0], outcome_style);
+        }
+        if let Some(versus_match) = &self.versus_match {
+            let score = format!("{} - {}   FIRST TO {}", versus_match.wins(), versus_match.losses(), versus_match.first_to());
+            let score_style = TextStyle::new(self.theme().text).scale(1.5).centered().shadow(0.0);
+            self.text_renderer.draw_text(canvas, &score, [SCREEN_WIDTH / 2.0, title_y + 110.0], score_style);
+        }
+        if self.ghost_beaten {
+            let best_style = TextStyle::new(self.theme().accent).scale(1.5).centered().shadow(0.0);
+            self.text_renderer.draw_text(canvas, "NEW BEST - YOUR NEXT GHOST", [SCREEN_WIDTH / 2.0, title_y + 110.0], best_style);
+        }
+
+        // Breakdown of the game, or of the whole match once it's decided, labels right-aligned and values
+        // left-aligned around the center
+        let match_over = self.versus_match.as_ref().is_some_and(VersusMatch::is_over);
+        let rows = match self.versus_match.as_ref().filter(|versus_match| versus_match.is_over()) {
+            Some(versus_match) => {
+                let totals = versus_match.totals();
+                let pieces_per_second = if totals.time > 0.0 { totals.pieces as f64 / totals.time } else { 0.0 };
+                vec![
+                    ("ROUNDS", versus_match.rounds().len().to_string()),
+                    ("TIME", mode::format_time(totals.time)),
+                    ("LINES", totals.lines.to_string()),
+                    ("ATTACK", totals.attack.to_string()),
+                    ("APM", format!("{:.1}", versus_match.attack_per_minute())),
+                    ("PIECES", totals.pieces.to_string()),
+                    ("PPS", format!("{:.2}", pieces_per_second)),
+                ]
+            }
+            None => vec![
+                ("SCORE", self.game.score().to_string()),
+                ("TIME", mode::format_time(self.stats.elapsed())),
+                ("LINES", self.game.lines_cleared().to_string()),
+                ("LEVEL", self.game.level().to_string()),
+                ("TETRISES", self.stats.tetrises().to_string()),
+                ("T-SPINS", self.stats.t_spins().to_string()),
+                ("MAX COMBO", self.stats.max_combo().to_string()),
+                ("PPS", format!("{:.2}", self.stats.pieces_per_second())),
+                ("FINESSE", format!("{} FAULTS", self.stats.finesse_faults())),
+            ],
+        };
+        let row_scale = 2.0;
+        let row_spacing = 55.0;
+        let rows_top = title_y + 160.0;
+        for (i, (label, value)) in rows.iter().enumerate() {
+            let y = rows_top + i as f32 * row_spacing;
+            let label_style = TextStyle::new(self.theme().hint).scale(row_scale).align(Align::Right);
+            self.text_renderer.draw_text(canvas, label, [SCREEN_WIDTH / 2.0 - 20.0, y], label_style);
+            let value_style = TextStyle::new(self.theme().text).scale(row_scale);
+            self.text_renderer.draw_text(canvas, value, [SCREEN_WIDTH / 2.0 + 20.0, y], value_style);
+        }
+
+        // Practice games end with a picture of where the session's pieces locked, to the right of the breakdown
+        if self.modes.get(self.mode).is_practice() {
+            let area = graphics::Rect::new(SCREEN_WIDTH * 0.72, rows_top, SCREEN_WIDTH * 0.2, (rows.len() - 1) as f32 * row_spacing);
+            let camera = Camera::fit(area);
+            let board = camera.bounds();
+            let background = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), board, self.theme().panel)?;
+            canvas.draw(&background, graphics::DrawParam::default());
+            self.draw_heatmap(ctx, canvas, &camera, 1.0)?;
+            let frame = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(GRID_LINE_WIDTH), board, self.theme().border)?;
+            canvas.draw(&frame, graphics::DrawParam::default());
+            let label = format!("{} PIECES", self.heatmap.pieces());
+            let label_style = TextStyle::new(self.theme().hint).centered();
+            self.text_renderer.draw_text(canvas, &label, [board.center().x, board.bottom() + 10.0], label_style);
+        }
+
+        // The instant replay plays the game's last seconds in slow motion, to the left of the breakdown
+        if let Some(player) = &self.instant_replay {
+            let area = graphics::Rect::new(SCREEN_WIDTH * 0.08, rows_top, SCREEN_WIDTH * 0.2, (rows.len() - 1) as f32 * row_spacing);
+            let camera = Camera::fit(area);
+            let board = camera.bounds();
+            let background = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), board, self.theme().panel)?;
+            canvas.draw(&background, graphics::DrawParam::default());
+            self.draw_mini_cells(ctx, canvas, &camera, &player.frame().cells)?;
+            let frame = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(GRID_LINE_WIDTH), board, self.theme().border)?;
+            canvas.draw(&frame, graphics::DrawParam::default());
+            // The marked segment shows under the progress bar
+            let (start, end) = player.segment_bounds();
+            let segment = graphics::Rect::new(board.x + board.w * start, board.bottom() + 4.0, (board.w * (end - start)).max(2.0), 4.0);
+            let segment = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), segment, self.theme().hint)?;
+            canvas.draw(&segment, graphics::DrawParam::default());
+            let bar = graphics::Rect::new(board.x, board.bottom() + 4.0, board.w * player.progress(), 4.0);
+            let bar = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), bar, self.theme().accent)?;
+            canvas.draw(&bar, graphics::DrawParam::default());
+            let label = format!("REPLAY x{}", REPLAY_SPEED);
+            let label_style = TextStyle::new(self.theme().hint).centered();
+            self.text_renderer.draw_text(canvas, &label, [board.center().x, board.bottom() + 14.0], label_style);
+        }
+
+        // Draw "PRESS ENTER" text (blinking) with pixelated effect
+        let press_y = rows_top + rows.len() as f32 * row_spacing + 40.0;
+        let waiting = self.versus.as_ref().is_some_and(VersusLink::is_ready);
+        let press = if waiting {
+            "WAITING FOR OPPONENT"
+        } else if self.match_continues() {
+            "PRESS ENTER FOR THE NEXT ROUND"
+        } else {
+            "PRESS ENTER TO CONTINUE"
+        };
+        if self.show_text || waiting {
+            let press_style = TextStyle::new(self.theme().accent).scale(2.0).centered();
+            self.text_renderer.draw_text(canvas, press, [SCREEN_WIDTH / 2.0, press_y], press_style);
+        }
+        if !self.recording.is_empty() {
+            let hint = if self.instant_replay.is_some() {
+                "R: STOP REPLAY   [ ]: MARK START / END   G: SAVE GIF"
+            } else {
+                "R: INSTANT REPLAY   G: SAVE GIF"
+            };
+            let hint_style = TextStyle::new(self.theme().hint).centered();
+            self.text_renderer.draw_text(canvas, hint, [SCREEN_WIDTH / 2.0, press_y + 40.0], hint_style);
+        }
+        if self.match_continues() {
+            let hint_style = TextStyle::new(self.theme().hint).centered();
+            self.text_renderer.draw_text(canvas, "ESC: LEAVE MATCH", [SCREEN_WIDTH / 2.0, press_y + 70.0], hint_style);
+        }
+        if !self.clip_status.is_empty() {
+            let status_style = TextStyle::new(self.theme().text).centered();
+            self.text_renderer.draw_text(canvas, &self.clip_status, [SCREEN_WIDTH / 2.0, press_y + 100.0], status_style);
+        }
+
+        // Score and speed over the game along the bottom, unless the breakdown is for a whole match
+        if !match_over {
+            let top = press_y + 170.0;
+            let height = SCREEN_HEIGHT - MARGIN - top;
+            let score = graphics::Rect::new(SCREEN_WIDTH * 0.08, top, SCREEN_WIDTH * 0.39, height);
+            self.draw_chart(ctx, canvas, score, "SCORE", &self.samples.score_series(), |max| format!("{:.0}", max))?;
+            let pps = graphics::Rect::new(SCREEN_WIDTH * 0.53, top, SCREEN_WIDTH * 0.39, height);
+            self.draw_chart(ctx, canvas, pps, "PPS", &self.samples.pps_series(), |max| format!("{:.2}", max))?;
+        }
+        
+        Ok(())
+    }
+
+    /// Draws a line chart of a series against time, labelled above with its name and largest value
+    fn draw_chart(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut graphics::Canvas,
+        area: graphics::Rect,
+        label: &str,
+        series: &[(f64, f64)],
+        format_max: impl Fn(f64) -> String,
+    ) -> GameResult {
+        let background = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), area, self.theme().panel)?;
+        canvas.draw(&background, graphics::DrawParam::default());
+        let frame = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(GRID_LINE_WIDTH), area, self.theme().border)?;
+        canvas.draw(&frame, graphics::DrawParam::default());
+
+        let label_style = TextStyle::new(self.theme().hint);
+        self.text_renderer.draw_text(canvas, label, [area.x, area.y - 30.0], label_style);
+        let end = series.last().map_or(0.0, |&(time, _)| time);
+        let span = format!("MAX {}   {}", format_max(chart::series_max(series)), mode::format_time(end));
+        self.text_renderer.draw_text(canvas, &span, [area.right(), area.y - 30.0], label_style.align(Align::Right));
+
+        let inner = graphics::Rect::new(area.x + 6.0, area.y + 6.0, area.w - 12.0, area.h - 12.0);
+        if let Some(points) = chart::chart_points(series, inner) {
+            let line = graphics::Mesh::new_line(ctx, &points, 3.0, self.theme().accent)?;
+            canvas.draw(&line, graphics::DrawParam::default());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+use ggez::{
+    conf::FullscreenType,
+    graphics,
+    input::keyboard::KeyCode,
+    Context, GameResult,
+};
+use tetris::rotation;
+use tetris::constants::*;
+use tetris::keybindings::Action;
+use tetris::config::Settings;
+use tetris::text::TextStyle;
+use tetris::ui::MenuEvent;
+use crate::{GameScreen, GameState};
+
+/// Entries of the settings menu, in display order
+#[derive(PartialEq, Clone, Copy)]
+pub(crate) enum SettingsItem {
+    MusicVolume,
+    SfxVolume,
+    Das,
+    Arr,
+    GhostPiece,
+    Rotation,
+    TouchOverlay,
+    PracticeHeatmap,
+    TSpinHints,
+    Theme,
+    Background,
+    Grid,
+    Particles,
+    Patterns,
+    ReduceFlashing,
+    Fullscreen,
+    Vsync,
+    FrameCap,
+    ArcadeInitials,
+    PlayData,
+    KeyBindings,
+    Versus,
+    Transfer,
+    Back,
+}
+
+impl SettingsItem {
+    pub(crate) const ALL: [SettingsItem; 24] = [
+        SettingsItem::MusicVolume,
+        SettingsItem::SfxVolume,
+        SettingsItem::Das,
+        SettingsItem::Arr,
+        SettingsItem::GhostPiece,
+        SettingsItem::Rotation,
+        SettingsItem::TouchOverlay,
+        SettingsItem::PracticeHeatmap,
+        SettingsItem::TSpinHints,
+        SettingsItem::Theme,
+        SettingsItem::Background,
+        SettingsItem::Grid,
+        SettingsItem::Particles,
+        SettingsItem::Patterns,
+        SettingsItem::ReduceFlashing,
+        SettingsItem::Fullscreen,
+        SettingsItem::Vsync,
+        SettingsItem::FrameCap,
+        SettingsItem::ArcadeInitials,
+        SettingsItem::PlayData,
+        SettingsItem::KeyBindings,
+        SettingsItem::Versus,
+        SettingsItem::Transfer,
+        SettingsItem::Back,
+    ];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            SettingsItem::MusicVolume => "MUSIC VOLUME",
+            SettingsItem::SfxVolume => "SFX VOLUME",
+            SettingsItem::Das => "AUTO SHIFT DELAY",
+            SettingsItem::Arr => "AUTO REPEAT RATE",
+            SettingsItem::GhostPiece => "GHOST PIECE",
+            SettingsItem::Rotation => "ROTATION",
+            SettingsItem::TouchOverlay => "TOUCH OVERLAY",
+            SettingsItem::PracticeHeatmap => "PRACTICE HEATMAP",
+            SettingsItem::TSpinHints => "T-SPIN HINTS",
+            SettingsItem::Theme => "THEME",
+            SettingsItem::Background => "BACKGROUND",
+            SettingsItem::Grid => "GRID",
+            SettingsItem::Particles => "PARTICLES",
+            SettingsItem::Patterns => "PIECE PATTERNS",
+            SettingsItem::ReduceFlashing => "REDUCE FLASHING",
+            SettingsItem::Fullscreen => "FULLSCREEN",
+            SettingsItem::Vsync => "VSYNC",
+            SettingsItem::FrameCap => "FRAME CAP",
+            SettingsItem::ArcadeInitials => "ARCADE INITIALS",
+            SettingsItem::PlayData => "RECORD PLAY DATA",
+            SettingsItem::KeyBindings => "KEY BINDINGS",
+            SettingsItem::Versus => "VERSUS",
+            SettingsItem::Transfer => "IMPORT / EXPORT SCORES",
+            SettingsItem::Back => "BACK",
+        }
+    }
+}
+
+impl GameState {
+    /// Opens the settings screen, returning to `from` when it is closed
+    pub(crate) fn open_settings(&mut self, from: GameScreen) {
+        self.settings_menu.select(0);
+        self.refresh_settings_menu();
+        self.settings_return = from;
+        self.screen = GameScreen::Settings;
+    }
+
+    /// Handles a key press on the settings screen
+    pub(crate) fn handle_settings_key(&mut self, ctx: &mut Context, key: KeyCode) -> GameResult {
+        let event = self.settings_menu.handle_key(key);
+        self.play_menu_sound(ctx, event);
+        match event {
+            MenuEvent::Adjusted(index, _) | MenuEvent::Activated(index)
+                if SettingsItem::ALL[index] == SettingsItem::Fullscreen =>
+            {
+                self.toggle_fullscreen(ctx)?;
+            }
+            MenuEvent::Adjusted(index, steps) => {
+                self.change_setting(SettingsItem::ALL[index], steps);
+            }
+            MenuEvent::Activated(index) => match SettingsItem::ALL[index] {
+                SettingsItem::KeyBindings => {
+                    self.controls_cursor = 0;
+                    self.rebinding = false;
+                    self.screen = GameScreen::Controls;
+                }
+                SettingsItem::Versus => {
+                    self.versus_menu.select(0);
+                    self.refresh_versus_menu();
+                    self.screen = GameScreen::VersusOptions;
+                }
+                SettingsItem::Transfer => {
+                    self.transfer_menu.select(0);
+                    self.transfer_status.clear();
+                    self.screen = GameScreen::Transfer;
+                }
+                SettingsItem::Back => self.screen = self.settings_return,
+                item => self.change_setting(item, 1),
+            },
+            MenuEvent::Back => self.screen = self.settings_return,
+            MenuEvent::Moved | MenuEvent::None => {}
+        }
+        Ok(())
+    }
+
+    /// Changes a setting by `steps` (toggles flip on any step), then applies and saves it
+    fn change_setting(&mut self, item: SettingsItem, steps: i32) {
+        let audio = &mut self.settings.audio;
+        let gameplay = &mut self.settings.gameplay;
+        match item {
+            // Changing a volume unmutes, as the volume keys do
+            SettingsItem::MusicVolume => {
+                audio.music_volume = Settings::adjust_volume(audio.music_volume, steps);
+                audio.muted = false;
+            }
+            SettingsItem::SfxVolume => {
+                audio.sfx_volume = Settings::adjust_volume(audio.sfx_volume, steps);
+                audio.muted = false;
+            }
+            SettingsItem::Das => gameplay.das_ms = Settings::adjust_timing(gameplay.das_ms, steps, Settings::DAS_RANGE),
+            SettingsItem::Arr => gameplay.arr_ms = Settings::adjust_timing(gameplay.arr_ms, steps, Settings::ARR_RANGE),
+            SettingsItem::GhostPiece => gameplay.ghost_piece = !gameplay.ghost_piece,
+            // Games already running keep their rotation; the next one starts with the new system
+            SettingsItem::Rotation => {
+                let systems = &rotation::SYSTEMS;
+                let current = systems.iter().position(|system| system.name() == gameplay.rotation).unwrap_or(0);
+                let index = (current as i32 + steps).rem_euclid(systems.len() as i32) as usize;
+                gameplay.rotation = systems[index].name().to_string();
+            }
+            SettingsItem::TouchOverlay => gameplay.touch_overlay = !gameplay.touch_overlay,
+            SettingsItem::PracticeHeatmap => self.settings.practice.heatmap = !self.settings.practice.heatmap,
+            SettingsItem::TSpinHints => self.settings.practice.t_spin_hints = !self.settings.practice.t_spin_hints,
+            SettingsItem::Theme => {
+                let count = self.themes.len() as i32;
+                self.theme_index = (self.theme_index as i32 + steps).rem_euclid(count) as usize;
+                self.settings.video.theme = self.themes[self.theme_index].name.clone();
+                self.text_renderer.set_font(self.themes[self.theme_index].font.as_deref());
+            }
+            SettingsItem::Particles => {
+                self.settings.video.particles = !self.settings.video.particles;
+                self.particles.clear();
+            }
+            SettingsItem::Background => self.settings.video.background = self.settings.video.background.cycle(steps),
+            SettingsItem::Grid => self.settings.video.grid = self.settings.video.grid.cycle(steps),
+            SettingsItem::Patterns => self.settings.video.block_patterns = !self.settings.video.block_patterns,
+            SettingsItem::ReduceFlashing => {
+                let accessibility = &mut self.settings.accessibility;
+                accessibility.reduce_flashing = !accessibility.reduce_flashing;
+            }
+            // The window is created with vsync on or off, so this takes effect on the next launch
+            SettingsItem::Vsync => self.settings.video.vsync = !self.settings.video.vsync,
+            SettingsItem::FrameCap => self.settings.video.frame_cap = Settings::cycle_frame_cap(self.settings.video.frame_cap, steps),
+            SettingsItem::ArcadeInitials => {
+                let name_entry = &mut self.settings.name_entry;
+                name_entry.arcade_initials = !name_entry.arcade_initials;
+            }
+            // Turning recording off closes the log at once; turning it on starts with the next game
+            SettingsItem::PlayData => {
+                gameplay.record_play_data = !gameplay.record_play_data;
+                self.play_data = None;
+            }
+            // Fullscreen needs the context and goes through toggle_fullscreen
+            SettingsItem::Fullscreen | SettingsItem::KeyBindings | SettingsItem::Versus | SettingsItem::Transfer | SettingsItem::Back => return,
+        }
+        self.apply_audio_settings();
+        self.save_settings();
+        self.refresh_settings_menu();
+    }
+
+    /// Hands the saved volumes and mute to the sounds
+    fn apply_audio_settings(&mut self) {
+        let audio = self.settings.audio;
+        self.sounds.set_music_volume(audio.music_volume);
+        self.sounds.set_sfx_volume(audio.sfx_volume);
+        self.sounds.set_muted(audio.muted);
+    }
+
+    /// Toggles the music, skips a track, steps both volumes or toggles the mute for one of the audio actions
+    /// Volume and mute changes are saved right away, so they carry over to every screen and the next launch
+    pub(crate) fn adjust_audio(&mut self, ctx: &mut Context, action: Action) {
+        let audio = &mut self.settings.audio;
+        match action {
+            Action::ToggleMusic => {
+                if self.sounds.background_playing {
+                    self.sounds.stop_background_music(ctx);
+                } else {
+                    self.sounds.start_background_music();
+                }
+                return;
+            }
+            // Both volumes move together, and changing them unmutes
+            Action::VolumeDown | Action::VolumeUp => {
+                let steps = if action == Action::VolumeUp { 1 } else { -1 };
+                audio.music_volume = Settings::adjust_volume(audio.music_volume, steps);
+                audio.sfx_volume = Settings::adjust_volume(audio.sfx_volume, steps);
+                audio.muted = false;
+            }
+            Action::Mute => audio.muted = !audio.muted,
+            Action::NextTrack => {
+                self.sounds.next_track();
+                return;
+            }
+            _ => return,
+        }
+        self.apply_audio_settings();
+        self.save_settings();
+        self.refresh_settings_menu();
+    }
+
+    /// Switches between windowed and desktop fullscreen and saves the choice
+    /// The layout follows automatically since the viewport is recomputed every frame
+    pub(crate) fn toggle_fullscreen(&mut self, ctx: &mut Context) -> GameResult {
+        // A kiosk stays fullscreen
+        if self.kiosk {
+            return Ok(());
+        }
+        self.settings.video.fullscreen = !self.settings.video.fullscreen;
+        let fullscreen_type = if self.settings.video.fullscreen {
+            FullscreenType::Desktop
+        } else {
+            FullscreenType::Windowed
+        };
+        ctx.gfx.set_fullscreen(fullscreen_type)?;
+        self.save_settings();
+        self.refresh_settings_menu();
+        Ok(())
+    }
+
+    /// Updates the values shown on the settings screen
+    fn refresh_settings_menu(&mut self) {
+        let audio = self.settings.audio;
+        let gameplay = &self.settings.gameplay;
+        for (i, item) in SettingsItem::ALL.iter().enumerate() {
+            let value = match item {
+                SettingsItem::MusicVolume | SettingsItem::SfxVolume if audio.muted => "MUTED".to_string(),
+                SettingsItem::MusicVolume => format!("{}%", (audio.music_volume * 100.0).round()),
+                SettingsItem::SfxVolume => format!("{}%", (audio.sfx_volume * 100.0).round()),
+                SettingsItem::Das => format!("{} MS", gameplay.das_ms),
+                SettingsItem::Arr => format!("{} MS", gameplay.arr_ms),
+                SettingsItem::GhostPiece => if gameplay.ghost_piece { "ON" } else { "OFF" }.to_string(),
+                SettingsItem::Rotation => rotation::by_name(&gameplay.rotation).name().to_uppercase(),
+                SettingsItem::TouchOverlay => if gameplay.touch_overlay { "ON" } else { "OFF" }.to_string(),
+                SettingsItem::PracticeHeatmap => if self.settings.practice.heatmap { "ON" } else { "OFF" }.to_string(),
+                SettingsItem::TSpinHints => if self.settings.practice.t_spin_hints { "ON" } else { "OFF" }.to_string(),
+                SettingsItem::Theme => self.theme().name.to_uppercase(),
+                SettingsItem::Background => self.settings.video.background.name().to_uppercase(),
+                SettingsItem::Grid => self.settings.video.grid.name().to_uppercase(),
+                SettingsItem::Particles => if self.settings.video.particles { "ON" } else { "OFF" }.to_string(),
+                SettingsItem::Patterns => if self.settings.video.block_patterns { "ON" } else { "OFF" }.to_string(),
+                SettingsItem::ReduceFlashing => if self.settings.accessibility.reduce_flashing { "ON" } else { "OFF" }.to_string(),
+                SettingsItem::Fullscreen => if self.settings.video.fullscreen { "ON" } else { "OFF" }.to_string(),
+                SettingsItem::Vsync => if self.settings.video.vsync { "ON" } else { "OFF" }.to_string(),
+                SettingsItem::ArcadeInitials => if self.settings.name_entry.arcade_initials { "ON" } else { "OFF" }.to_string(),
+                SettingsItem::PlayData => if gameplay.record_play_data { "ON" } else { "OFF" }.to_string(),
+                SettingsItem::FrameCap => match self.settings.video.frame_cap {
+                    0 => "UNCAPPED".to_string(),
+                    cap => format!("{} FPS", cap),
+                },
+                SettingsItem::KeyBindings | SettingsItem::Versus | SettingsItem::Transfer | SettingsItem::Back => continue,
+            };
+            self.settings_menu.set_value(i, value);
+        }
+    }
+
+    /// Draws the settings screen
+    pub(crate) fn draw_settings_screen(&self, canvas: &mut graphics::Canvas) {
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "SETTINGS", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 6.0], title_style);
+
+        self.settings_menu.draw(canvas, &self.text_renderer, self.theme(), [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 6.0 + 140.0], 34.0, 2.0);
+
+        let help_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+        let help = "UP/DOWN: SELECT   LEFT/RIGHT: CHANGE   ESC: BACK";
+        self.text_renderer.draw_text(canvas, help, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 80.0], help_style);
+    }
+}
@@ -0,0 +1,52 @@
+use ggez::graphics;
+use tetris::stats::LifetimeStats;
+use tetris::constants::*;
+use tetris::mode;
+use tetris::text::{Align, TextStyle};
+use crate::GameState;
+
+/// Labels and values of the statistics screen
+fn lifetime_rows(stats: &LifetimeStats) -> [(&'static str, String); 7] {
+    let best_sprint = if stats.best_sprint > 0.0 { mode::format_time(stats.best_sprint) } else { "-".to_string() };
+    [
+        ("GAMES", stats.games.to_string()),
+        ("LINES", stats.lines.to_string()),
+        ("TETRISES", stats.tetrises.to_string()),
+        ("PLAY TIME", mode::format_time(stats.play_time)),
+        ("BEST SCORE", stats.best_score.to_string()),
+        ("HIGHEST LEVEL", stats.highest_level.to_string()),
+        ("BEST SPRINT", best_sprint),
+    ]
+}
+
+impl GameState {
+    /// Draws the lifetime statistics of every game on this machine, beside those of the profile playing
+    pub(crate) fn draw_statistics_screen(&self, canvas: &mut graphics::Canvas) {
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "STATISTICS", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 6.0], title_style);
+
+        let mut columns = vec![("ALL PLAYERS", &self.lifetime)];
+        if let Some(profile) = self.profiles.active() {
+            columns.push((profile.name.as_str(), &profile.stats));
+        }
+        let top = SCREEN_HEIGHT / 6.0 + 120.0;
+        let label_x = SCREEN_WIDTH / 2.0 - 80.0;
+        let label_style = TextStyle::new(self.theme().hint).scale(1.5).align(Align::Right).shadow(0.0);
+        let header_style = TextStyle::new(self.theme().accent).scale(1.5).align(Align::Right).shadow(0.0);
+        let value_style = TextStyle::new(self.theme().text).scale(1.5).align(Align::Right).shadow(0.0);
+        for (column, (name, stats)) in columns.iter().enumerate() {
+            let x = label_x + 300.0 * (column + 1) as f32;
+            self.text_renderer.draw_text(canvas, name, [x, top], header_style);
+            for (i, (label, value)) in lifetime_rows(stats).iter().enumerate() {
+                let y = top + 60.0 + i as f32 * 45.0;
+                if column == 0 {
+                    self.text_renderer.draw_text(canvas, label, [label_x, y], label_style);
+                }
+                self.text_renderer.draw_text(canvas, value, [x, y], value_style);
+            }
+        }
+
+        let help_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+        self.text_renderer.draw_text(canvas, "PRESS ANY KEY", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 160.0], help_style);
+    }
+}
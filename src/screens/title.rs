@@ -0,0 +1,425 @@
+use ggez::{
+    graphics::{self, Color},
+    input::keyboard::KeyCode,
+    Context, GameResult,
+};
+use tetris::ai::AiOpponent;
+use tetris::constants::*;
+use tetris::net::{self, NetHost};
+use tetris::keybindings::Action;
+use tetris::camera::Camera;
+use tetris::mode::ModeRegistry;
+use tetris::opener::Opener;
+use tetris::config::Settings;
+use tetris::text::TextStyle;
+use tetris::ui::{Menu, MenuEvent};
+use crate::{GameScreen, GameState};
+
+/// Entries of the title screen menu
+#[derive(PartialEq, Clone, Copy)]
+enum TitleItem {
+    Mode(usize),  // A single-player mode, by its position in the mode registry
+    Tutorial,     // How to play, one gated lesson at a time
+    Openers,      // Opener trainer, practicing the opener picked with left and right
+    Editor,       // Board editor, building scenarios to practice from
+    Versus,
+    Profile,
+    Settings,
+    HighScores,
+    History,
+    Statistics,
+    Quit,
+}
+
+impl TitleItem {
+    /// The registered modes followed by the fixed entries, in display order; a kiosk has no QUIT
+    fn all(modes: &ModeRegistry, kiosk: bool) -> Vec<TitleItem> {
+        let mut items: Vec<TitleItem> = (0..modes.len()).map(TitleItem::Mode).collect();
+        items.extend([TitleItem::Tutorial, TitleItem::Openers, TitleItem::Editor, TitleItem::Versus, TitleItem::Profile, TitleItem::Settings, TitleItem::HighScores, TitleItem::History, TitleItem::Statistics]);
+        if !kiosk {
+            items.push(TitleItem::Quit);
+        }
+        items
+    }
+
+    fn label(self, modes: &ModeRegistry) -> &'static str {
+        match self {
+            TitleItem::Mode(index) => modes.get(index).name(),
+            TitleItem::Tutorial => "TUTORIAL",
+            TitleItem::Openers => "OPENERS",
+            TitleItem::Editor => "EDITOR",
+            TitleItem::Versus => "VERSUS",
+            TitleItem::Profile => "PROFILE",
+            TitleItem::Settings => "SETTINGS",
+            TitleItem::HighScores => "HIGH SCORES",
+            TitleItem::History => "HISTORY",
+            TitleItem::Statistics => "STATISTICS",
+            TitleItem::Quit => "QUIT",
+        }
+    }
+}
+
+/// Opponents that can be picked for the versus entry of the title menu
+#[derive(PartialEq, Clone, Copy)]
+enum VersusChoice {
+    Cpu,   // Play against the built-in AI
+    Host,  // Host a LAN game
+    Join,  // Join a LAN game by address
+    Online, // Browse the lobby server's rooms
+}
+
+impl VersusChoice {
+    const ALL: [VersusChoice; 4] = [VersusChoice::Cpu, VersusChoice::Host, VersusChoice::Join, VersusChoice::Online];
+
+    fn label(self) -> &'static str {
+        match self {
+            VersusChoice::Cpu => "CPU",
+            VersusChoice::Host => "HOST LAN",
+            VersusChoice::Join => "JOIN LAN",
+            VersusChoice::Online => "ONLINE",
+        }
+    }
+}
+
+impl GameState {
+    /// Runs attract mode: after a while idle on the title screen the AI plays a demo game
+    pub(crate) fn update_attract_mode(&mut self, dt: f64) {
+        if self.screen != GameScreen::Title {
+            self.stop_demo();
+            return;
+        }
+
+        match &mut self.demo {
+            Some(demo) if demo.topped_out => self.demo = None,
+            Some(demo) => {
+                demo.update(dt as f32);
+            }
+            None => {
+                self.title_idle_timer += dt;
+                if self.title_idle_timer >= ATTRACT_DELAY {
+                    let mut demo = AiOpponent::new();
+                    demo.controller.weights = self.ai_weights;
+                    demo.move_delay = ATTRACT_MOVE_DELAY;
+                    self.demo = Some(demo);
+                }
+            }
+        }
+    }
+
+    /// Turns on kiosk mode for a cabinet build, taking QUIT off the title menu
+    pub(crate) fn enter_kiosk_mode(&mut self) {
+        self.kiosk = true;
+        self.title_menu = Self::new_title_menu(&self.modes, &self.openers, self.settings.gameplay.start_level, true);
+    }
+
+    /// Keeps a kiosk going without anyone at it: the demo and the high scores take turns on the title screen,
+    /// and any other screen left alone long enough goes back to the title, ending a game in progress
+    pub(crate) fn update_kiosk(&mut self, dt: f64) {
+        self.idle_time += dt;
+        match self.screen {
+            GameScreen::Title if self.demo.is_some() && self.idle_time >= ATTRACT_DELAY + KIOSK_DEMO_TIME => {
+                self.open_high_scores();
+                self.idle_time = 0.0;
+            }
+            GameScreen::HighScores if self.idle_time >= KIOSK_SCORES_TIME => {
+                // The demo starts again at once, and plays its full time
+                self.screen = GameScreen::Title;
+                self.title_idle_timer = ATTRACT_DELAY;
+                self.idle_time = ATTRACT_DELAY;
+            }
+            GameScreen::Title | GameScreen::HighScores => {}
+            _ if self.idle_time >= KIOSK_IDLE_TIMEOUT => {
+                if self.screen == GameScreen::EnterName {
+                    // A name that passes the rules is kept, as when the window closes
+                    if let Ok(name) = self.name_rules.validate(&self.current_name) {
+                        self.current_name = name;
+                        self.add_high_score();
+                    }
+                    self.current_name.clear();
+                }
+                if self.screen == GameScreen::Playing {
+                    self.discard_autosave();
+                }
+                // A recovered game left unanswered is offered again at the next start
+                self.recovered = None;
+                self.end_versus(false);
+                self.leave_versus();
+                self.net_host = None;
+                self.lobby = None;
+                self.chat_input = None;
+                self.paused = false;
+                self.quit_requested = false;
+                self.scenario = None;
+                self.screen = GameScreen::Title;
+                self.idle_time = 0.0;
+            }
+            _ => {}
+        }
+    }
+
+    /// Stops the attract mode demo and restarts the idle countdown
+    pub(crate) fn stop_demo(&mut self) {
+        self.demo = None;
+        self.title_idle_timer = 0.0;
+    }
+
+    /// Builds the title screen menu with the openers and versus entries showing their first choices,
+    /// and the modes with a level select showing the start level
+    pub(crate) fn new_title_menu(modes: &ModeRegistry, openers: &[Opener], start_level: u32, kiosk: bool) -> Menu {
+        let items = TitleItem::all(modes, kiosk);
+        let labels: Vec<&str> = items.iter().map(|item| item.label(modes)).collect();
+        let mut menu = Menu::new(&labels);
+        Self::show_start_level(&mut menu, modes, kiosk, start_level);
+        if let Some(index) = items.iter().position(|&item| item == TitleItem::Openers) {
+            menu.set_value(index, openers.first().map_or("NONE", |opener| opener.name.as_str()));
+        }
+        if let Some(versus) = items.iter().position(|&item| item == TitleItem::Versus) {
+            menu.set_value(versus, VersusChoice::ALL[0].label());
+        }
+        menu
+    }
+
+    /// Shows the start level beside every mode that has a level select; they all share it
+    fn show_start_level(menu: &mut Menu, modes: &ModeRegistry, kiosk: bool, start_level: u32) {
+        for (index, item) in TitleItem::all(modes, kiosk).into_iter().enumerate() {
+            if let TitleItem::Mode(mode) = item {
+                if modes.get(mode).has_level_select() {
+                    menu.set_value(index, format!("LEVEL {}", start_level));
+                }
+            }
+        }
+    }
+
+    /// Handles a key press on the title screen menu
+    pub(crate) fn handle_title_key(&mut self, ctx: &mut Context, key: KeyCode) -> GameResult {
+        let event = self.title_menu.handle_key(key);
+        self.play_menu_sound(ctx, event);
+        match event {
+            MenuEvent::Adjusted(index, steps) if TitleItem::all(&self.modes, self.kiosk)[index] == TitleItem::Versus => {
+                let count = VersusChoice::ALL.len() as i32;
+                self.versus_choice = (self.versus_choice as i32 + steps).rem_euclid(count) as usize;
+                self.title_menu.set_value(index, VersusChoice::ALL[self.versus_choice].label());
+            }
+            MenuEvent::Adjusted(index, steps) if TitleItem::all(&self.modes, self.kiosk)[index] == TitleItem::Openers && !self.openers.is_empty() => {
+                let count = self.openers.len() as i32;
+                self.opener_choice = (self.opener_choice as i32 + steps).rem_euclid(count) as usize;
+                self.title_menu.set_value(index, &self.openers[self.opener_choice].name);
+            }
+            MenuEvent::Adjusted(index, steps) if matches!(TitleItem::all(&self.modes, self.kiosk)[index], TitleItem::Mode(mode) if self.modes.get(mode).has_level_select()) => {
+                let gameplay = &mut self.settings.gameplay;
+                gameplay.start_level = Settings::adjust_start_level(gameplay.start_level, steps);
+                Self::show_start_level(&mut self.title_menu, &self.modes, self.kiosk, gameplay.start_level);
+                self.save_settings();
+            }
+            MenuEvent::Activated(index) => match TitleItem::all(&self.modes, self.kiosk)[index] {
+                TitleItem::Mode(mode) => self.start_game(ctx, mode)?,
+                TitleItem::Tutorial => self.start_tutorial(ctx)?,
+                TitleItem::Openers => self.start_opener(ctx)?,
+                TitleItem::Editor => {
+                    self.editor_status.clear();
+                    self.screen = GameScreen::Editor;
+                }
+                TitleItem::Versus => match VersusChoice::ALL[self.versus_choice] {
+                    VersusChoice::Cpu => self.start_bot_versus(ctx)?,
+                    VersusChoice::Host => {
+                        self.screen = GameScreen::Hosting;
+                        match NetHost::bind(net::DEFAULT_PORT) {
+                            Ok(host) => {
+                                self.net_host = Some(host);
+                                self.net_status.clear();
+                            }
+                            Err(e) => self.net_error("CANNOT HOST", e),
+                        }
+                    }
+                    VersusChoice::Join => {
+                        self.screen = GameScreen::Joining;
+                        self.net_status.clear();
+                    }
+                    VersusChoice::Online => self.open_rooms(),
+                },
+                TitleItem::Profile => self.open_profiles(),
+                TitleItem::Settings => self.open_settings(GameScreen::Title),
+                TitleItem::HighScores => self.open_high_scores(),
+                TitleItem::History => self.open_history(),
+                TitleItem::Statistics => self.screen = GameScreen::Statistics,
+                TitleItem::Quit => ctx.request_quit(),
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Draws the title screen
+    pub(crate) fn draw_title_screen(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        // Draw the attract mode demo faded out behind the title, in the middle of the screen
+        if let Some(demo) = &self.demo {
+            let camera = Camera::fit(graphics::Rect::new(0.0, MARGIN, SCREEN_WIDTH, SCREEN_HEIGHT - 2.0 * MARGIN));
+            for (y, row) in demo.board_with_piece().iter().enumerate() {
+                for (x, &color) in row.iter().enumerate() {
+                    if color != Color::BLACK {
+                        let faded = Color::new(color.r, color.g, color.b, ATTRACT_ALPHA);
+                        self.draw_block(ctx, canvas, &camera, x as f32, y as f32, faded)?;
+                    }
+                }
+            }
+        }
+
+        // Draw title text with pixelated appearance
+        let title_scale = 5.0;
+
+        // Calculate title dimensions for centering
+        let (title_width, title_height) = self.text_renderer.measure(ctx, "TETRIS", title_scale);
+        let title_x = (SCREEN_WIDTH - title_width) / 2.0;
+        let title_y = SCREEN_HEIGHT / 3.0;
+
+        // Draw multiple outlines for pixel-art effect
+        // Black outline
+        let outline = TextStyle::new(Color::BLACK).scale(title_scale).shadow(0.0);
+        for dx in [-3, -2, -1, 1, 2, 3] {
+            for dy in [-3, -2, -1, 1, 2, 3] {
+                self.text_renderer.draw_text(canvas, "TETRIS", [title_x + dx as f32, title_y + dy as f32], outline);
+            }
+        }
+
+        // Draw main title with gradient style colors
+        let colors = [
+            Color::from_rgb(50, 220, 240),   // Cyan
+            Color::from_rgb(60, 210, 250),   // Light blue
+            Color::from_rgb(80, 190, 255),   // Blue
+            Color::from_rgb(100, 170, 255),  // Darker blue
+            Color::from_rgb(120, 150, 255),  // Light purple
+        ];
+        
+        // Draw each letter with a slightly different color
+        let title_chars = "TETRIS".chars().collect::<Vec<_>>();
+        let char_width = title_width / title_chars.len() as f32;
+        
+        for (i, ch) in title_chars.iter().enumerate() {
+            let style = TextStyle::new(colors[i % colors.len()]).scale(title_scale).shadow(0.0);
+            self.text_renderer.draw_text(canvas, &ch.to_string(), [title_x + i as f32 * char_width, title_y], style);
+        }
+
+        // Draw a pixelated decoration line under the title
+        let line_y = title_y + title_height + 20.0;
+        let line_width = title_width + 100.0;
+        let line_segments = 20;
+        let segment_width = line_width / line_segments as f32;
+        
+        for i in 0..line_segments {
+            let color_idx = i % colors.len();
+            let line_rect = graphics::Rect::new(
+                (SCREEN_WIDTH - line_width) / 2.0 + i as f32 * segment_width,
+                line_y,
+                segment_width,
+                8.0,
+            );
+            let line_mesh = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                line_rect,
+                colors[color_idx],
+            )?;
+            canvas.draw(&line_mesh, graphics::DrawParam::default());
+        }
+
+        // Draw tetromino decorations
+        let tetromino_size = 15.0;
+        let tetrominoes = [
+            // I piece (cyan)
+            (vec![vec![1, 1, 1, 1]], Color::from_rgb(0, 240, 240), 
+             (SCREEN_WIDTH / 2.0 - line_width / 2.0 - 80.0, line_y + 40.0)),
+            // T piece (purple)
+            (vec![vec![0, 1, 0], vec![1, 1, 1]], Color::from_rgb(160, 0, 240),
+             (SCREEN_WIDTH / 2.0 + line_width / 2.0 + 30.0, line_y + 40.0)),
+            // L piece (orange)
+            (vec![vec![0, 0, 1], vec![1, 1, 1]], Color::from_rgb(240, 160, 0),
+             (SCREEN_WIDTH / 2.0 - 150.0, line_y + 120.0)),
+            // O piece (yellow)
+            (vec![vec![1, 1], vec![1, 1]], Color::from_rgb(240, 240, 0),
+             (SCREEN_WIDTH / 2.0 + 150.0, line_y + 120.0)),
+        ];
+        
+        for (shape, color, (pos_x, pos_y)) in tetrominoes.iter() {
+            for (y, row) in shape.iter().enumerate() {
+                for (x, &cell) in row.iter().enumerate() {
+                    if cell == 1 {
+                        // Main block
+                        let block_rect = graphics::Rect::new(
+                            pos_x + x as f32 * tetromino_size,
+                            pos_y + y as f32 * tetromino_size,
+                            tetromino_size - 1.0,
+                            tetromino_size - 1.0,
+                        );
+                        let block_mesh = graphics::Mesh::new_rectangle(
+                            ctx,
+                            graphics::DrawMode::fill(),
+                            block_rect,
+                            *color,
+                        )?;
+                        canvas.draw(&block_mesh, graphics::DrawParam::default());
+                        
+                        // Highlight (top-left)
+                        let highlight_color = Color::new(
+                            f32::min(color.r + 0.2, 1.0),
+                            f32::min(color.g + 0.2, 1.0),
+                            f32::min(color.b + 0.2, 1.0),
+                            color.a,
+                        );
+                        let highlight_rect = graphics::Rect::new(
+                            pos_x + x as f32 * tetromino_size,
+                            pos_y + y as f32 * tetromino_size,
+                            tetromino_size - 1.0,
+                            2.0,
+                        );
+                        let highlight_mesh = graphics::Mesh::new_rectangle(
+                            ctx,
+                            graphics::DrawMode::fill(),
+                            highlight_rect,
+                            highlight_color,
+                        )?;
+                        canvas.draw(&highlight_mesh, graphics::DrawParam::default());
+                        
+                        // Shadow (bottom-right)
+                        let shadow_color = Color::new(
+                            f32::max(color.r - 0.3, 0.0),
+                            f32::max(color.g - 0.3, 0.0),
+                            f32::max(color.b - 0.3, 0.0),
+                            color.a,
+                        );
+                        let shadow_rect = graphics::Rect::new(
+                            pos_x + x as f32 * tetromino_size,
+                            pos_y + y as f32 * tetromino_size + tetromino_size - 3.0,
+                            tetromino_size - 1.0,
+                            2.0,
+                        );
+                        let shadow_mesh = graphics::Mesh::new_rectangle(
+                            ctx,
+                            graphics::DrawMode::fill(),
+                            shadow_rect,
+                            shadow_color,
+                        )?;
+                        canvas.draw(&shadow_mesh, graphics::DrawParam::default());
+                    }
+                }
+            }
+        }
+
+        // Draw the main menu
+        self.title_menu.draw(canvas, &self.text_renderer, self.theme(), [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT * 0.55], 36.0, 2.0);
+
+        // Draw the menu help with the music status
+        let help = format!(
+            "UP/DOWN: SELECT   ENTER: CHOOSE   LEFT/RIGHT: CHANGE   {}: MUSIC {}",
+            self.binding_label(Action::ToggleMusic),
+            if self.settings.audio.muted { "MUTED" } else if self.sounds.background_playing { "ON" } else { "OFF" }
+        );
+        let help_style = TextStyle::new(self.theme().hint).centered().shadow(1.0);
+        self.text_renderer.draw_text(canvas, &help, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 80.0], help_style);
+
+        // Draw copyright text with pixelated shadow
+        let copyright_style = TextStyle::new(Color::new(0.5, 0.5, 0.5, 1.0)).centered().shadow(1.0);
+        self.text_renderer.draw_text(canvas, "© 2024 RUST TETRIS", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 40.0], copyright_style);
+
+        Ok(())
+    }
+}
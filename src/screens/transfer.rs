@@ -0,0 +1,96 @@
+use ggez::{
+    graphics,
+    input::keyboard::KeyCode,
+    Context,
+};
+use tetris::stats;
+use tetris::constants::*;
+use tetris::text::TextStyle;
+use tetris::transfer::Archive;
+use tetris::ui::{keycode_to_char, MenuEvent};
+use std::path::Path;
+use crate::{GameScreen, GameState};
+
+impl GameState {
+    /// Handles a key press on the import and export screen
+    /// Up and Down pick the action, Enter runs it on the typed file, and other keys edit the file name
+    pub(crate) fn handle_transfer_key(&mut self, ctx: &mut Context, key: KeyCode) {
+        match key {
+            KeyCode::Up | KeyCode::Down | KeyCode::Return | KeyCode::Escape => {
+                let event = self.transfer_menu.handle_key(key);
+                self.play_menu_sound(ctx, event);
+                match event {
+                    MenuEvent::Activated(0) => self.export_scores(),
+                    MenuEvent::Activated(1) => self.import_scores(),
+                    MenuEvent::Activated(_) | MenuEvent::Back => self.screen = GameScreen::Settings,
+                    MenuEvent::Moved | MenuEvent::Adjusted(..) | MenuEvent::None => {}
+                }
+            }
+            KeyCode::Back => {
+                self.transfer_path.pop();
+            }
+            key => {
+                let shift = ctx.keyboard.is_key_pressed(KeyCode::LShift) || ctx.keyboard.is_key_pressed(KeyCode::RShift);
+                if let Some(ch) = keycode_to_char(key, shift) {
+                    self.transfer_path.push(ch);
+                }
+            }
+        }
+    }
+
+    /// Writes the high scores and lifetime statistics to the typed file, as CSV if it ends in `.csv`
+    fn export_scores(&mut self) {
+        let archive = Archive::new(&self.high_scores, &self.lifetime);
+        self.transfer_status = match archive.write(Path::new(&self.transfer_path)) {
+            Ok(()) => format!("EXPORTED {} SCORES", archive.high_scores.len()),
+            Err(e) => format!("EXPORT FAILED: {}", e).to_uppercase(),
+        };
+    }
+
+    /// Merges the scores and statistics of an exported file into this machine's
+    fn import_scores(&mut self) {
+        let merged = Archive::read(Path::new(&self.transfer_path))
+            .map(|archive| archive.merge_into(&mut self.high_scores, &mut self.lifetime));
+        self.transfer_status = match merged {
+            Ok(merged) => {
+                if merged.scores > 0 {
+                    self.save_high_scores();
+                }
+                if merged.stats {
+                    if let Err(e) = self.lifetime.save(stats::lifetime_stats_path()) {
+                        log::error!(target: "save", "Could not save the lifetime statistics: {}", e);
+                    }
+                }
+                let stats = if merged.stats { "STATISTICS ADDED" } else { "STATISTICS ALREADY COUNTED" };
+                format!("{} NEW SCORES, {}", merged.scores, stats)
+            }
+            Err(e) => format!("IMPORT FAILED: {}", e).to_uppercase(),
+        };
+    }
+
+    /// Draws the import and export screen: the file, the actions and how the last one went
+    pub(crate) fn draw_transfer_screen(&self, canvas: &mut graphics::Canvas) {
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "IMPORT / EXPORT", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 6.0], title_style);
+
+        let text_style = TextStyle::new(self.theme().text).scale(1.5).centered().shadow(0.0);
+        self.text_renderer.draw_text(canvas, "FILE:", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 3.0], text_style);
+        let cursor = if self.show_cursor { "_" } else { " " };
+        let path = format!("{}{}", self.transfer_path, cursor);
+        let path_style = TextStyle::new(self.theme().accent).centered().shadow(0.0);
+        self.text_renderer.draw_text(canvas, &path, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 3.0 + 45.0], path_style);
+
+        let top = SCREEN_HEIGHT / 2.0 - 40.0;
+        self.transfer_menu.draw(canvas, &self.text_renderer, self.theme(), [SCREEN_WIDTH / 2.0, top], 50.0, 2.0);
+        self.text_renderer.draw_text(canvas, &self.transfer_status, [SCREEN_WIDTH / 2.0, top + 190.0], text_style);
+
+        let help_lines = [
+            "FILES ENDING IN .CSV ARE WRITTEN AS CSV, OTHERS AS JSON",
+            "UP/DOWN: SELECT   ENTER: RUN   ESC: BACK",
+        ];
+        let help_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+        for (i, line) in help_lines.iter().enumerate() {
+            self.text_renderer.draw_text(canvas, line, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 200.0 + i as f32 * 40.0], help_style);
+        }
+    }
+}
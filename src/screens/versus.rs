@@ -0,0 +1,274 @@
+use ggez::{
+    graphics::{self, Color},
+    Context, GameResult,
+};
+use tetris::ai::AiOpponent;
+use tetris::garbage::GarbageHoles;
+use tetris::events::GameEvent;
+use tetris::board::GameBoard;
+use tetris::constants::*;
+use tetris::net::{NetSession, VersusLink};
+use tetris::layout::Layout;
+use tetris::text::{TextStyle, TEXT_SIZE};
+use tetris::versus::{RoundResult, VersusMatch};
+use std::io;
+use crate::{GameScreen, GameState};
+
+impl GameState {
+    /// Sets up a versus match over an established connection, waiting in the room until both players are ready
+    /// The host tells the joining player how long the match is, and the joining player plays to that
+    pub(crate) fn start_versus(&mut self, session: NetSession) {
+        let hosting = self.net_host.take().is_some();
+        // A listed room is full once someone joins it
+        if let Some(mut lobby) = self.lobby.take() {
+            let _ = lobby.close_room();
+        }
+        self.mode = 0;
+        self.versus = Some(VersusLink::new(session, GRID_WIDTH as usize, GRID_HEIGHT as usize));
+        self.versus_match = Some(VersusMatch::new(self.settings.versus.first_to));
+        if hosting {
+            let first_to = self.settings.versus.first_to;
+            self.send_versus(|link, _| link.send_match(first_to));
+        }
+        self.net_status.clear();
+        self.trainer = None;
+        self.tutorial = None;
+        self.scenario = None;
+        self.screen = GameScreen::Room;
+    }
+
+    /// Starts a versus match against the computer
+    pub(crate) fn start_bot_versus(&mut self, ctx: &mut Context) -> GameResult {
+        self.mode = 0;
+        self.bot = Some(self.new_bot());
+        self.versus_match = Some(VersusMatch::new(self.settings.versus.first_to));
+        self.trainer = None;
+        self.tutorial = None;
+        self.scenario = None;
+        self.reset_game(ctx)
+    }
+
+    /// A fresh computer opponent for a round, playing at the chosen level and placing garbage holes in this machine's style
+    fn new_bot(&self) -> AiOpponent {
+        let mut bot = AiOpponent::new();
+        bot.controller.weights = self.ai_weights;
+        bot.set_difficulty(self.settings.versus.difficulty);
+        bot.garbage_holes = GarbageHoles::new(self.settings.versus.garbage_style);
+        bot
+    }
+
+    /// Ends a round of the versus match, remembering the outcome for the results screen
+    /// The connection or computer opponent stays for the next round until the match is decided
+    pub(crate) fn end_versus(&mut self, won: bool) {
+        if self.versus.is_none() && self.bot.is_none() {
+            return;
+        }
+        if !won {
+            self.send_versus(|link, _| link.send_game_over());
+        }
+        self.versus_result = Some(won);
+        let round = RoundResult {
+            won,
+            time: self.stats.elapsed(),
+            lines: self.game.lines_cleared(),
+            attack: self.stats.attack(),
+            pieces: self.stats.locked(),
+        };
+        if let Some(versus_match) = &mut self.versus_match {
+            versus_match.record(round);
+        }
+        if !self.match_continues() {
+            self.versus = None;
+            self.bot = None;
+        }
+    }
+
+    /// Whether the last versus round was played in a match that isn't decided yet
+    pub(crate) fn match_continues(&self) -> bool {
+        self.versus_match.as_ref().is_some_and(|versus_match| !versus_match.is_over())
+    }
+
+    /// Starts the next round of the versus match: straight away against the computer,
+    /// or once both LAN players are ready
+    pub(crate) fn next_round(&mut self, ctx: &mut Context) -> GameResult {
+        if self.bot.is_some() {
+            self.bot = Some(self.new_bot());
+            return self.reset_game(ctx);
+        }
+        self.send_versus(|link, _| link.send_ready());
+        Ok(())
+    }
+
+    /// Leaves the versus match for good, dropping the connection or computer opponent
+    pub(crate) fn leave_versus(&mut self) {
+        self.versus = None;
+        self.bot = None;
+        self.versus_match = None;
+    }
+
+    /// Advances the computer opponent, exchanging garbage with it
+    pub(crate) fn update_bot(&mut self, dt: f64) {
+        let Some(bot) = &mut self.bot else {
+            return;
+        };
+        let attack = bot.update(dt as f32);
+        self.game.receive_garbage(attack);
+        if bot.topped_out {
+            self.end_versus(true);
+            self.show_results();
+        }
+    }
+
+    /// Polls the versus connection, handling disconnects and the opponent topping out
+    pub(crate) fn update_network(&mut self, ctx: &mut Context) -> GameResult {
+        if let Some(accepted) = self.net_host.as_ref().map(|host| host.accept()) {
+            match accepted {
+                Ok(Some(session)) => self.start_versus(session),
+                Ok(None) => {}
+                Err(e) => {
+                    self.net_error("HOST ERROR", e);
+                    self.net_host = None;
+                }
+            }
+        }
+
+        if let Some(link) = &mut self.versus {
+            if let Err(e) = link.update() {
+                // A match can't go on without the opponent
+                self.net_error("DISCONNECTED", e);
+                self.versus = None;
+                self.versus_match = None;
+                return Ok(());
+            }
+            if let Some(first_to) = link.match_length.take() {
+                self.versus_match = Some(VersusMatch::new(first_to));
+            }
+            let messages = link.take_chat();
+            self.receive_chat(messages);
+            let Some(link) = &mut self.versus else {
+                return Ok(());
+            };
+            if link.take_round_start() && matches!(self.screen, GameScreen::Room | GameScreen::Results) {
+                return self.reset_game(ctx);
+            }
+            for lines in link.take_attacks() {
+                self.game.receive_garbage(lines);
+            }
+            if link.opponent_topped_out && self.screen == GameScreen::Playing {
+                self.end_versus(true);
+                self.show_results();
+            }
+        }
+        Ok(())
+    }
+
+    /// Tells the versus opponent, or the computer opponent, about a game event
+    pub(crate) fn send_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PieceLocked(locked) => {
+                self.send_versus(|link, _| link.send_placement(locked.cells.clone(), locked.piece.color));
+            }
+            GameEvent::AttackSent(lines) => {
+                self.attack_meter.send(*lines);
+                if let Some(bot) = &mut self.bot {
+                    bot.receive_garbage(*lines);
+                }
+                self.send_versus(|link, _| link.send_attack(*lines));
+            }
+            _ => {}
+        }
+        if event.changes_board() {
+            self.send_versus(|link, board| link.send_snapshot(board.rows()));
+        }
+    }
+
+    /// Sends a message to the versus opponent, if there is one, dropping the connection when it fails
+    pub(crate) fn send_versus(&mut self, send: impl FnOnce(&mut VersusLink, &GameBoard) -> io::Result<()>) {
+        let Some(link) = &mut self.versus else {
+            return;
+        };
+        if let Err(e) = send(link, self.game.board()) {
+            self.net_error("DISCONNECTED", e);
+            self.versus = None;
+        }
+    }
+
+    /// Draws the garbage meter to the right of the board: queued incoming garbage rises in red from the bottom,
+    /// and attack on its way to the opponent hangs in green from the top
+    pub(crate) fn draw_garbage_meter(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+        let meter = self.layout.garbage_meter;
+
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            meter,
+            Color::new(0.15, 0.15, 0.15, 1.0),
+        )?;
+        canvas.draw(&background, graphics::DrawParam::default());
+
+        // One grid cell of height per queued line, growing up from the bottom
+        let pending = self.game.pending_garbage().min(GRID_HEIGHT as u32) as f32;
+        if pending > 0.0 {
+            let fill_height = pending * GRID_SIZE;
+            let fill = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(meter.x, meter.bottom() - fill_height, meter.w, fill_height),
+                Color::from_rgb(230, 40, 40),
+            )?;
+            canvas.draw(&fill, graphics::DrawParam::default());
+        }
+
+        // Rows waiting to clear show the attack they'll make, then sent attacks drain away
+        let outgoing = (self.game.pending_attack() as f32 + self.attack_meter.lines()).min(GRID_HEIGHT as f32);
+        if outgoing > 0.0 {
+            let fill = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(meter.x, meter.y, meter.w, outgoing * GRID_SIZE),
+                Color::from_rgb(60, 210, 80),
+            )?;
+            canvas.draw(&fill, graphics::DrawParam::default());
+        }
+
+        Ok(())
+    }
+
+    /// Draws a small view of the versus opponent's board below the score panel
+    pub(crate) fn draw_opponent_board(&self, ctx: &mut Context, canvas: &mut graphics::Canvas, label: &str, board: &[Vec<Color>]) -> GameResult {
+        let camera = self.layout.opponent_camera();
+        let graphics::Rect { x: origin_x, y: origin_y, w: board_width, h: board_height } = camera.bounds();
+
+        // Label above the board
+        let label_style = TextStyle::new(self.theme().text).shadow(0.0);
+        self.text_renderer.draw_text(canvas, label, [origin_x, origin_y - Layout::OPPONENT_LABEL_HEIGHT], label_style);
+
+        // Background and frame
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(origin_x, origin_y, board_width, board_height),
+            self.theme().panel,
+        )?;
+        canvas.draw(&background, graphics::DrawParam::default());
+        let frame = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::stroke(GRID_LINE_WIDTH),
+            graphics::Rect::new(origin_x, origin_y, board_width, board_height),
+            self.theme().border,
+        )?;
+        canvas.draw(&frame, graphics::DrawParam::default());
+
+        // Rounds won by each side below the board during a match
+        if let Some(versus_match) = &self.versus_match {
+            let score = format!("YOU {} - {} OPP", versus_match.wins(), versus_match.losses());
+            let score_style = TextStyle::new(self.theme().text).shadow(0.0);
+            self.text_renderer.draw_text(canvas, &score, [origin_x, origin_y + board_height + 4.0], score_style);
+            let length_style = TextStyle::new(self.theme().hint).shadow(0.0);
+            let length = format!("FIRST TO {}", versus_match.first_to());
+            self.text_renderer.draw_text(canvas, &length, [origin_x, origin_y + board_height + 4.0 + TEXT_SIZE], length_style);
+        }
+
+        self.draw_mini_cells(ctx, canvas, &camera, board)
+    }
+}
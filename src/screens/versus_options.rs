@@ -0,0 +1,121 @@
+use ggez::{
+    graphics,
+    input::keyboard::KeyCode,
+    Context,
+};
+use tetris::constants::*;
+use tetris::mode::Handicap;
+use tetris::text::TextStyle;
+use tetris::ui::MenuEvent;
+use tetris::versus;
+use crate::{GameScreen, GameState};
+
+impl GameState {
+    /// Handicap of the profile playing, or the one in the settings file for a guest
+    pub(crate) fn handicap(&self) -> Handicap {
+        self.profiles.active().map_or(self.settings.versus.handicap, |profile| profile.handicap)
+    }
+
+    /// Changes the handicap with `change` and saves it where it came from
+    fn change_handicap(&mut self, change: impl FnOnce(&mut Handicap)) {
+        match self.profiles.active_mut() {
+            Some(profile) => {
+                change(&mut profile.handicap);
+                profile.handicap = profile.handicap.clamped();
+                self.save_profiles();
+            }
+            None => {
+                change(&mut self.settings.versus.handicap);
+                self.settings.versus.handicap = self.settings.versus.handicap.clamped();
+                self.save_settings();
+            }
+        }
+    }
+
+    /// Handles a key press on the versus screen
+    /// Left and Right change the selected option; Enter on Back or Esc returns to the settings
+    pub(crate) fn handle_versus_options_key(&mut self, ctx: &mut Context, key: KeyCode) {
+        let event = self.versus_menu.handle_key(key);
+        self.play_menu_sound(ctx, event);
+        let steps = match event {
+            MenuEvent::Adjusted(_, steps) => steps,
+            MenuEvent::Activated(8) | MenuEvent::Back => {
+                self.screen = GameScreen::Settings;
+                return;
+            }
+            MenuEvent::Activated(_) => 1,
+            MenuEvent::Moved | MenuEvent::None => return,
+        };
+        let step = |value: u32| value.saturating_add_signed(steps);
+        match self.versus_menu.selected() {
+            // The garbage style, match length and computer level belong to the machine, the handicap to the player
+            0 => {
+                let versus = &mut self.settings.versus;
+                versus.garbage_style = versus.garbage_style.cycle(steps);
+                self.save_settings();
+            }
+            1 => {
+                let versus = &mut self.settings.versus;
+                versus.first_to = versus::cycle_length(versus.first_to, steps);
+                self.save_settings();
+            }
+            2 => {
+                let versus = &mut self.settings.versus;
+                versus.difficulty = versus.difficulty.cycle(steps);
+                self.save_settings();
+            }
+            3 => self.change_handicap(|handicap| handicap.garbage_rows = step(handicap.garbage_rows)),
+            4 => self.change_handicap(|handicap| handicap.walls = step(handicap.walls)),
+            5 => self.change_handicap(|handicap| handicap.extra_levels = step(handicap.extra_levels)),
+            6 => self.change_handicap(|handicap| handicap.no_hold = !handicap.no_hold),
+            _ => {
+                self.settings.online.chat_muted = !self.settings.online.chat_muted;
+                self.save_settings();
+            }
+        }
+        self.refresh_versus_menu();
+    }
+
+    /// Shows the garbage style, the match length, the computer level and the current handicap in the versus menu
+    pub(crate) fn refresh_versus_menu(&mut self) {
+        let handicap = self.handicap();
+        let walls = match handicap.walls {
+            0 => "OFF".to_string(),
+            walls => format!("{} COLUMNS", GRID_WIDTH as u32 - walls),
+        };
+        self.versus_menu.set_value(0, self.settings.versus.garbage_style.name().to_uppercase());
+        self.versus_menu.set_value(1, format!("FIRST TO {}", self.settings.versus.first_to));
+        self.versus_menu.set_value(2, self.settings.versus.difficulty.name().to_uppercase());
+        self.versus_menu.set_value(3, handicap.garbage_rows.to_string());
+        self.versus_menu.set_value(4, walls);
+        self.versus_menu.set_value(5, (1 + handicap.extra_levels).to_string());
+        self.versus_menu.set_value(6, if handicap.no_hold { "OFF" } else { "ON" });
+        self.versus_menu.set_value(7, if self.settings.online.chat_muted { "MUTED" } else { "ON" });
+    }
+
+    /// Draws the versus screen, naming the player the handicap belongs to
+    pub(crate) fn draw_versus_options_screen(&self, canvas: &mut graphics::Canvas) {
+        let title_style = TextStyle::new(self.theme().accent).scale(3.0).centered();
+        self.text_renderer.draw_text(canvas, "VERSUS", [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 6.0], title_style);
+
+        let player = self.profiles.active().map_or("GUEST".to_string(), |profile| profile.name.to_uppercase());
+        let text_style = TextStyle::new(self.theme().text).scale(1.5).centered().shadow(0.0);
+        self.text_renderer.draw_text(canvas, &format!("HANDICAP FOR {}", player), [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT / 3.0], text_style);
+
+        let top = SCREEN_HEIGHT / 2.0 - 100.0;
+        self.versus_menu.draw(canvas, &self.text_renderer, self.theme(), [SCREEN_WIDTH / 2.0, top], 50.0, 2.0);
+
+        let help_lines = [
+            "GARBAGE STYLE: WHERE THE HOLES OF RISING GARBAGE GO",
+            "MATCH: ROUND WINS THAT TAKE A MATCH; A LAN HOST'S CHOICE COUNTS",
+            "CPU LEVEL: SPEED, SLIPS AND LOOKAHEAD OF THE COMPUTER OPPONENT",
+            "CHAT: MUTED HIDES OTHER PLAYERS' MESSAGES",
+            "HANDICAPS APPLY TO YOUR SIDE OF VERSUS GAMES",
+            "UP/DOWN: SELECT   LEFT/RIGHT: CHANGE   ESC: BACK",
+        ];
+        let help_style = TextStyle::new(self.theme().hint).scale(1.5).centered().shadow(0.0);
+        for (i, line) in help_lines.iter().enumerate() {
+            self.text_renderer.draw_text(canvas, line, [SCREEN_WIDTH / 2.0, SCREEN_HEIGHT - 280.0 + i as f32 * 40.0], help_style);
+        }
+    }
+}
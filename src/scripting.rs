@@ -0,0 +1,271 @@
+use crate::constants::GRID_HEIGHT;
+use crate::events::GameEvent;
+use crate::game::{Game, Tuning};
+use crate::garbage::TSpin;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{CallFnOptions, Dynamic, Engine, Scope, AST};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Most operations a script may run per call, so a runaway loop can't freeze the game
+const MAX_SCRIPT_OPERATIONS: u64 = 100_000;
+/// Largest gravity, score or garbage multiplier a script may set
+const MAX_SCRIPT_MULTIPLIER: f64 = 100.0;
+
+/// What scripts can read of the game and what they asked to change, shared with the registered functions
+#[derive(Clone, Debug, Default)]
+struct ScriptState {
+    score: u32,      // Score when the hook was called
+    level: u32,      // Level when the hook was called
+    lines: u32,      // Lines cleared when the hook was called
+    pieces: u32,     // Pieces spawned when the hook was called
+    tuning: Tuning,  // Multipliers, as changed by the scripts so far
+    garbage: u32,    // Garbage lines scripts queued for the player
+}
+
+/// A compiled script and the name its errors are reported under
+struct Script {
+    name: String,  // File name of the script
+    ast: AST,      // Compiled script
+}
+
+/// Runs Rhai scripts that react to game events and change the rules while a game is played
+/// Top-level code runs when a game starts, and functions named after events (`on_lock`,
+/// `on_lines_cleared`, `on_level_up`, ...) run when those events happen
+/// Scripts only get the functions registered here, so they can't touch files or the network
+pub struct ScriptHost {
+    engine: Engine,                   // Interpreter with the game's functions registered
+    scripts: Vec<Script>,             // Loaded scripts, run in load order
+    state: Rc<RefCell<ScriptState>>,  // Game values and requested changes, shared with the engine
+    errors: Vec<String>,              // Errors not yet reported, prefixed with the script name
+}
+
+impl Default for ScriptHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptHost {
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+        engine.set_max_call_levels(32);
+        engine.set_module_resolver(DummyModuleResolver::new());
+        let state = Rc::new(RefCell::new(ScriptState::default()));
+
+        // Reading the game
+        let shared = state.clone();
+        engine.register_fn("score", move || shared.borrow().score as i64);
+        let shared = state.clone();
+        engine.register_fn("level", move || shared.borrow().level as i64);
+        let shared = state.clone();
+        engine.register_fn("lines", move || shared.borrow().lines as i64);
+        let shared = state.clone();
+        engine.register_fn("pieces", move || shared.borrow().pieces as i64);
+        let shared = state.clone();
+        engine.register_fn("gravity", move || shared.borrow().tuning.gravity);
+        let shared = state.clone();
+        engine.register_fn("score_multiplier", move || shared.borrow().tuning.score);
+        let shared = state.clone();
+        engine.register_fn("garbage_multiplier", move || shared.borrow().tuning.garbage);
+
+        // Changing the rules; multipliers take whole numbers too and are kept between 0 and
+        // `MAX_SCRIPT_MULTIPLIER`, and each call adds at most a board's height of garbage
+        register_setter(&mut engine, &state, "set_gravity", |tuning, value| tuning.gravity = value);
+        register_setter(&mut engine, &state, "set_score_multiplier", |tuning, value| tuning.score = value);
+        register_setter(&mut engine, &state, "set_garbage_multiplier", |tuning, value| tuning.garbage = value);
+        let shared = state.clone();
+        engine.register_fn("add_garbage", move |lines: i64| {
+            let mut state = shared.borrow_mut();
+            state.garbage = state.garbage.saturating_add(lines.clamp(0, GRID_HEIGHT as i64) as u32);
+        });
+
+        Self {
+            engine,
+            scripts: Vec::new(),
+            state,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Compiles a script and adds it after the ones already loaded
+    pub fn load(&mut self, name: &str, source: &str) -> Result<(), String> {
+        let ast = self.engine.compile(source).map_err(|e| format!("{}: {}", name, e))?;
+        self.scripts.push(Script { name: name.to_string(), ast });
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// Runs the top-level code of every script for a new game
+    pub fn start(&mut self, game: &mut Game) {
+        self.run(game, |engine, script| engine.run_ast_with_scope(&mut Scope::new(), &script.ast));
+    }
+
+    /// Calls the hook for an event in every script that defines it
+    pub fn on_event(&mut self, game: &mut Game, event: &GameEvent) {
+        let Some((hook, args)) = hook(event) else {
+            return;
+        };
+        if !self.scripts.iter().any(|script| defines(script, hook, args.len())) {
+            return;
+        }
+        self.run(game, |engine, script| {
+            if !defines(script, hook, args.len()) {
+                return Ok(());
+            }
+            let options = CallFnOptions::new().eval_ast(false);
+            engine
+                .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &script.ast, hook, args.clone())
+                .map(|_| ())
+        });
+    }
+
+    /// Errors since the last call, each prefixed with the name of the script that caused it
+    pub fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Runs something in every script with the game's values, then applies what the scripts changed
+    /// A script that fails is unloaded, so it can't fail again on every event
+    fn run(&mut self, game: &mut Game, call: impl Fn(&Engine, &Script) -> Result<(), Box<rhai::EvalAltResult>>) {
+        *self.state.borrow_mut() = ScriptState {
+            score: game.score(),
+            level: game.level(),
+            lines: game.lines_cleared(),
+            pieces: game.pieces(),
+            tuning: game.tuning(),
+            garbage: 0,
+        };
+
+        let engine = &self.engine;
+        let errors = &mut self.errors;
+        self.scripts.retain(|script| match call(engine, script) {
+            Ok(()) => true,
+            Err(e) => {
+                errors.push(format!("{}: {}", script.name, e));
+                false
+            }
+        });
+
+        let state = self.state.borrow();
+        game.set_tuning(state.tuning);
+        if state.garbage > 0 {
+            game.receive_garbage(state.garbage);
+        }
+    }
+}
+
+/// Registers a function setting one of the multipliers, for both float and integer arguments
+/// Values are clamped to a multiplier the game can work with, and a NaN is ignored
+fn register_setter(engine: &mut Engine, state: &Rc<RefCell<ScriptState>>, name: &str, set: fn(&mut Tuning, f64)) {
+    let clamped = move |tuning: &mut Tuning, value: f64| {
+        if !value.is_nan() {
+            set(tuning, value.clamp(0.0, MAX_SCRIPT_MULTIPLIER));
+        }
+    };
+    let shared = state.clone();
+    engine.register_fn(name, move |value: f64| clamped(&mut shared.borrow_mut().tuning, value));
+    let shared = state.clone();
+    engine.register_fn(name, move |value: i64| clamped(&mut shared.borrow_mut().tuning, value as f64));
+}
+
+/// Whether a script defines a hook taking the given number of arguments
+fn defines(script: &Script, hook: &str, arity: usize) -> bool {
+    script.ast.iter_functions().any(|function| function.name == hook && function.params.len() == arity)
+}
+
+/// The script function called for an event, and its arguments
+/// Moves and rotations have no hook; they happen too often to be worth a script call each
+fn hook(event: &GameEvent) -> Option<(&'static str, Vec<Dynamic>)> {
+    let hook = match event {
+        GameEvent::PieceSpawned(kind) => ("on_spawn", vec![Dynamic::from(format!("{:?}", kind))]),
+        GameEvent::PieceHeld => ("on_hold", vec![]),
+        GameEvent::PieceLocked(locked) => ("on_lock", vec![Dynamic::from(locked.full_rows.len() as i64)]),
+        GameEvent::LinesCleared { lines, t_spin } => (
+            "on_lines_cleared",
+            vec![Dynamic::from(*lines as i64), Dynamic::from(*t_spin != TSpin::None)],
+        ),
+        GameEvent::LevelUp(level) => ("on_level_up", vec![Dynamic::from(*level as i64)]),
+        GameEvent::AttackSent(lines) => ("on_attack", vec![Dynamic::from(*lines as i64)]),
+        GameEvent::GarbageRose(lines) => ("on_garbage", vec![Dynamic::from(*lines as i64)]),
+        GameEvent::GameOver => ("on_game_over", vec![]),
+        GameEvent::PieceMoved | GameEvent::PieceRotated => return None,
+    };
+    Some(hook)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Move;
+
+    #[test]
+    fn test_start_and_hooks_change_the_rules() {
+        let mut scripts = ScriptHost::new();
+        scripts
+            .load(
+                "faster.rhai",
+                "set_score_multiplier(2);
+                 fn on_lock(rows) { set_gravity(gravity() + 0.5); }
+                 fn on_level_up(level) { add_garbage(level); }",
+            )
+            .unwrap();
+
+        let mut game = Game::with_seed(1);
+        scripts.start(&mut game);
+        assert_eq!(game.tuning().score, 2.0);
+
+        let result = game.apply(Move::HardDrop);
+        for event in &result.events {
+            scripts.on_event(&mut game, event);
+        }
+        assert_eq!(game.tuning().gravity, 1.5);
+        scripts.on_event(&mut game, &GameEvent::LevelUp(3));
+        assert_eq!(game.pending_garbage(), 3);
+        assert!(scripts.take_errors().is_empty());
+    }
+
+    #[test]
+    fn test_bad_scripts_are_reported() {
+        let mut scripts = ScriptHost::new();
+        assert!(scripts.load("broken.rhai", "fn on_lock(rows) {").unwrap_err().starts_with("broken.rhai"));
+        assert!(scripts.is_empty());
+
+        // Runaway loops hit the operation limit and the script is unloaded
+        scripts.load("loop.rhai", "fn on_game_over() { loop {} }").unwrap();
+        scripts.load("import.rhai", "import \"other\" as other;").unwrap();
+        let mut game = Game::with_seed(1);
+        scripts.on_event(&mut game, &GameEvent::GameOver);
+        scripts.start(&mut game);
+        let errors = scripts.take_errors();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("loop.rhai"));
+        assert!(errors[1].starts_with("import.rhai"));
+        assert!(scripts.is_empty());
+    }
+
+    #[test]
+    fn test_script_values_are_clamped() {
+        let mut scripts = ScriptHost::new();
+        scripts
+            .load(
+                "huge.rhai",
+                "set_score_multiplier(1e12);
+                 set_gravity(-5);
+                 fn on_game_over() { add_garbage(4_000_000_000); add_garbage(4_000_000_000); }",
+            )
+            .unwrap();
+        let mut game = Game::with_seed(1);
+        scripts.start(&mut game);
+        assert_eq!(game.tuning().score, MAX_SCRIPT_MULTIPLIER);
+        assert_eq!(game.tuning().gravity, 0.0);
+        scripts.on_event(&mut game, &GameEvent::GameOver);
+        scripts.on_event(&mut game, &GameEvent::GameOver);
+        assert_eq!(game.pending_garbage(), 4 * GRID_HEIGHT as u32);
+        assert!(scripts.take_errors().is_empty());
+    }
+}
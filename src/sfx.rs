@@ -0,0 +1,95 @@
+use crate::board::GameBoard;
+use crate::tetromino::Tetromino;
+use rand::Rng;
+
+/// Most combo steps the clear sound rises by; longer combos stay at the top pitch
+const MAX_COMBO_STEPS: u32 = 12;
+
+/// How far sound effects stray from their recorded pitch, as playback rates where 1.0 is unchanged
+/// Effects that repeat quickly, like moves, are nudged a little at random so they don't drone,
+/// and clear sounds climb with each lock of a combo
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PitchVariation {
+    pub spread: f32,      // Largest random change of rate either way, as a fraction; 0.0 for none
+    pub combo_step: f32,  // Semitones each lock of a combo raises the clear sound; 0.0 for none
+}
+
+impl Default for PitchVariation {
+    fn default() -> Self {
+        Self {
+            spread: 0.04,
+            combo_step: 1.0,
+        }
+    }
+}
+
+impl PitchVariation {
+    /// A random rate within the spread
+    pub fn jitter(&self, rng: &mut impl Rng) -> f32 {
+        let spread = self.spread.clamp(0.0, 0.5);
+        if spread == 0.0 {
+            return 1.0;
+        }
+        1.0 + rng.gen_range(-spread..=spread)
+    }
+
+    /// Rate of a clear sound after `combo` locks in a row that cleared lines
+    pub fn combo(&self, combo: u32) -> f32 {
+        2f32.powf(self.combo_step * combo.min(MAX_COMBO_STEPS) as f32 / 12.0)
+    }
+}
+
+/// Where a sound at `column` plays between the speakers, from -1.0 at the far left to 1.0 at the far right
+/// `column` is measured in cells from the left edge of a board `width` cells wide, and
+/// `strength` narrows the spread, down to 0.0 for every sound in the middle
+pub fn pan(column: f32, width: u32, strength: f32) -> f32 {
+    let middle = width as f32 / 2.0;
+    ((column - middle) / middle * strength.clamp(0.0, 1.0)).clamp(-1.0, 1.0)
+}
+
+/// Column of the middle of a piece's blocks, in cells from the left edge of the board
+pub fn piece_column(piece: &Tetromino) -> f32 {
+    let cells = GameBoard::piece_cells(piece);
+    cells.iter().map(|&(x, _)| x as f32 + 0.5).sum::<f32>() / cells.len().max(1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tetromino::TetrominoType;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_pitch_variation() {
+        let pitch = PitchVariation::default();
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..100 {
+            let rate = pitch.jitter(&mut rng);
+            assert!((1.0 - pitch.spread..=1.0 + pitch.spread).contains(&rate));
+        }
+        let steady = PitchVariation { spread: 0.0, combo_step: 0.0 };
+        assert_eq!(steady.jitter(&mut rng), 1.0);
+        assert_eq!(steady.combo(5), 1.0);
+
+        // A semitone per lock: twelve locks reach the octave and no further
+        assert_eq!(pitch.combo(0), 1.0);
+        assert!(pitch.combo(1) > 1.05 && pitch.combo(1) < 1.06);
+        assert_eq!(pitch.combo(12), 2.0);
+        assert_eq!(pitch.combo(20), 2.0);
+    }
+
+    #[test]
+    fn test_pan_follows_the_piece() {
+        let mut piece = Tetromino::new(TetrominoType::O);
+        piece.position.x = 0.0;
+        assert_eq!(piece_column(&piece), 1.0);
+        piece.position.x = 8.0;
+        assert_eq!(piece_column(&piece), 9.0);
+
+        assert_eq!(pan(5.0, 10, 1.0), 0.0);
+        assert_eq!(pan(0.0, 10, 1.0), -1.0);
+        assert_eq!(pan(10.0, 10, 0.5), 0.5);
+        assert_eq!(pan(9.0, 10, 0.0), 0.0);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::ai::{AiController, Difficulty, Weights};
+use crate::constants::{LINE_CLEAR_COLLAPSE_TIME, LINE_CLEAR_FLASH_TIME};
+use crate::dataset::{DataLog, DataRecord};
+use crate::game::{Controller, Game, StepResult};
+use crate::mode::ModeRegistry;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Seconds of game time each simulated frame advances, as at 60 FPS
+const FRAME_TIME: f64 = 1.0 / 60.0;
+
+/// How a `tetris simulate` run is set up
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimOptions {
+    pub games: u32,                // Games played
+    pub mode: String,              // Name of the game mode, as on the title screen
+    pub difficulty: Difficulty,    // Input speed and search depth of the bot
+    pub pieces: u32,               // Pieces after which a game is stopped, so strong bots finish
+    pub seed: u64,                 // Seed of the first game; each next game adds one
+    pub replays: Option<PathBuf>,  // Play data log the games' placements are written to
+    pub weights: Weights,          // Heuristic the bot plays with
+}
+
+impl Default for SimOptions {
+    fn default() -> Self {
+        Self {
+            games: 10,
+            mode: "MARATHON".to_string(),
+            difficulty: Difficulty::Hard,
+            pieces: 1000,
+            seed: 0,
+            replays: None,
+            weights: Weights::default(),
+        }
+    }
+}
+
+impl SimOptions {
+    /// Reads `[--games N] [--mode NAME] [--level easy|normal|hard|insane] [--pieces N] [--seed N] [--replays FILE]`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", flag));
+            let number = |text: String| text.parse().map_err(|_| format!("bad number {:?}", text));
+            match flag.as_str() {
+                "--games" => options.games = number(value()?)? as u32,
+                "--pieces" => options.pieces = number(value()?)? as u32,
+                "--seed" => options.seed = number(value()?)?,
+                "--mode" => options.mode = value()?.to_uppercase(),
+                "--level" => {
+                    let name = value()?.to_lowercase();
+                    options.difficulty = *Difficulty::ALL.iter().find(|level| level.name() == name).ok_or_else(|| format!("unknown level {:?}", name))?;
+                }
+                "--replays" => options.replays = Some(PathBuf::from(value()?)),
+                _ => return Err(format!("unknown option {:?}", flag)),
+            }
+        }
+        if ModeRegistry::new().find(&options.mode).is_none() {
+            return Err(format!("unknown mode {:?}", options.mode));
+        }
+        Ok(options)
+    }
+}
+
+/// How one simulated game went
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameSummary {
+    pub score: u32,
+    pub lines: u32,
+    pub level: u32,
+    pub pieces: u32,
+    pub time: f64,         // Seconds of game time played
+    pub topped_out: bool,  // Whether the game ended with the stack reaching the top
+}
+
+impl GameSummary {
+    /// Pieces placed per second of game time
+    pub fn pps(&self) -> f64 {
+        if self.time > 0.0 {
+            self.pieces as f64 / self.time
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Plays one game with the bot on the game's own clock: inputs at the difficulty's pace, with gravity,
+/// lock delay and the line clear wait in between, until it tops out, the mode's goal is met or `pieces` are placed
+pub fn play(options: &SimOptions, seed: u64, mut log: Option<&mut DataLog>) -> io::Result<GameSummary> {
+    let modes = ModeRegistry::new();
+    let mode = modes.get(modes.find(&options.mode).unwrap_or(0));
+    let mut game = Game::with_seed(seed);
+    game.set_line_clear_delay(LINE_CLEAR_FLASH_TIME + LINE_CLEAR_COLLAPSE_TIME);
+    game.set_tuning(mode.tuning());
+    game.set_scoring_system(mode.scoring());
+    mode.setup(&mut game);
+    if let Some(log) = log.as_deref_mut() {
+        log.write(&DataRecord::game(mode.name(), game.rotation_system().name(), game.board()))?;
+    }
+
+    let mut controller = AiController::new(options.weights);
+    controller.depth = options.difficulty.search_depth();
+    let move_delay = options.difficulty.move_delay() as f64;
+    let (mut time, mut move_timer) = (0.0, 0.0);
+    while !game.is_game_over() && !mode.is_complete(&game, time) && game.pieces() < options.pieces {
+        time += FRAME_TIME;
+        move_timer += FRAME_TIME;
+        let mut results = Vec::new();
+        while move_timer >= move_delay {
+            move_timer -= move_delay;
+            if let Some(snapshot) = game.snapshot() {
+                let mv = controller.next_move(&snapshot);
+                results.push(game.apply(mv));
+            }
+        }
+        results.push(game.update(FRAME_TIME));
+        if let Some(log) = log.as_deref_mut() {
+            write_placements(log, &game, &results)?;
+        }
+    }
+    Ok(GameSummary {
+        score: game.score(),
+        lines: game.lines_cleared(),
+        level: game.level(),
+        pieces: game.pieces(),
+        time,
+        topped_out: game.is_game_over(),
+    })
+}
+
+/// Logs the pieces locked in a frame, with the queue the bot saw, as the game logs a human's
+fn write_placements(log: &mut DataLog, game: &Game, results: &[StepResult]) -> io::Result<()> {
+    for locked in results.iter().filter_map(|result| result.locked.as_ref()) {
+        let queue = game.next_queue();
+        let seen = game.current_piece().into_iter().chain(queue).take(queue.len());
+        log.write(&DataRecord::placement(game.board(), locked, game.hold_piece(), seen))?;
+    }
+    Ok(())
+}
+
+/// Every game of a run, in the order they were played
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Summary {
+    pub games: Vec<GameSummary>,
+}
+
+impl Summary {
+    /// Average of a figure over the games
+    pub fn average(&self, figure: impl Fn(&GameSummary) -> f64) -> f64 {
+        if self.games.is_empty() {
+            return 0.0;
+        }
+        self.games.iter().map(figure).sum::<f64>() / self.games.len() as f64
+    }
+
+    /// Pieces per second over every game together
+    pub fn pps(&self) -> f64 {
+        let pieces: u32 = self.games.iter().map(|game| game.pieces).sum();
+        let time: f64 = self.games.iter().map(|game| game.time).sum();
+        if time > 0.0 {
+            pieces as f64 / time
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let range = |figure: fn(&GameSummary) -> f64| {
+            let values = self.games.iter().map(figure);
+            let min = values.clone().fold(f64::INFINITY, f64::min);
+            let max = values.fold(f64::NEG_INFINITY, f64::max);
+            format!("{:>10.1} {:>10.1} {:>10.1}", self.average(figure), min, max)
+        };
+        writeln!(f, "{:<10} {:>10} {:>10} {:>10}", "", "average", "min", "max")?;
+        writeln!(f, "{:<10} {}", "score", range(|game| game.score as f64))?;
+        writeln!(f, "{:<10} {}", "lines", range(|game| game.lines as f64))?;
+        writeln!(f, "{:<10} {}", "level", range(|game| game.level as f64))?;
+        writeln!(f, "{:<10} {}", "pieces", range(|game| game.pieces as f64))?;
+        writeln!(f, "{:<10} {}", "seconds", range(|game| game.time))?;
+        writeln!(f, "{:<10} {:>10.2}", "pps", self.pps())?;
+        let topped = self.games.iter().filter(|game| game.topped_out).count();
+        write!(f, "{:<10} {:>10}", "topped out", format!("{}/{}", topped, self.games.len()))
+    }
+}
+
+/// Plays every game of a run, printing each as it ends, and writes their placements if asked
+pub fn run(options: &SimOptions) -> io::Result<Summary> {
+    let mut log = options.replays.as_ref().map(DataLog::open).transpose()?;
+    let mut summary = Summary::default();
+    for game in 0..options.games {
+        let seed = options.seed.wrapping_add(game as u64);
+        let result = play(options, seed, log.as_mut())?;
+        println!(
+            "game {:>4}: {:>8} points, {:>4} lines, level {:>2}, {:>5} pieces, {:.2} pps{}",
+            game + 1,
+            result.score,
+            result.lines,
+            result.level,
+            result.pieces,
+            result.pps(),
+            if result.topped_out { ", topped out" } else { "" }
+        );
+        summary.games.push(result);
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_parse() {
+        let args = ["--games", "3", "--mode", "sprint", "--level", "Insane", "--replays", "out.jsonl"].map(String::from);
+        let options = SimOptions::parse(args).unwrap();
+        assert_eq!(options.games, 3);
+        assert_eq!(options.mode, "SPRINT");
+        assert_eq!(options.difficulty, Difficulty::Insane);
+        assert_eq!(options.replays, Some(PathBuf::from("out.jsonl")));
+        assert!(SimOptions::parse(["--mode".to_string(), "nope".to_string()]).is_err());
+        assert!(SimOptions::parse(["--games".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_games_play_on_the_clock() {
+        let options = SimOptions { pieces: 30, difficulty: Difficulty::Normal, ..SimOptions::default() };
+        let game = play(&options, 3, None).unwrap();
+        assert_eq!(game.pieces, 30);
+        assert!(!game.topped_out);
+        // Every piece takes a few inputs at the bot's pace, so it can't outrun them
+        assert!(game.pps() > 0.5 && game.pps() < 1.0 / options.difficulty.move_delay() as f64, "{} pps", game.pps());
+        // The same seed plays the same game
+        assert_eq!(play(&options, 3, None).unwrap(), game);
+
+        let summary = Summary { games: vec![game, GameSummary { score: 0, ..game }] };
+        assert_eq!(summary.average(|game| game.score as f64), game.score as f64 / 2.0);
+        let table = summary.to_string();
+        assert!(table.lines().last().unwrap().starts_with("topped out") && table.ends_with(" 0/2"), "{}", table);
+    }
+}
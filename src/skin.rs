@@ -0,0 +1,95 @@
+use crate::constants::GARBAGE_COLOR;
+use crate::tetromino::TetrominoType;
+use ggez::graphics::{self, Color, DrawParam, Image, Rect};
+use ggez::{Context, GameError, GameResult};
+
+/// A tile of a skin's sprite sheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Piece(TetrominoType),  // Block of one of the seven pieces
+    Garbage,               // Block of a garbage line
+    Ghost,                 // Block of the ghost piece, tinted with the piece's color
+}
+
+impl Tile {
+    /// Tiles in a sprite sheet: the pieces in the order I, O, T, S, Z, J, L, then garbage and ghost
+    pub const COUNT: usize = 9;
+
+    /// Position of the tile in the sheet, counting from the left
+    pub fn index(self) -> usize {
+        match self {
+            Tile::Piece(kind) => TetrominoType::ALL.iter().position(|&other| other == kind).unwrap(),
+            Tile::Garbage => 7,
+            Tile::Ghost => 8,
+        }
+    }
+
+    /// Tile for a board cell or piece stored in the classic colors; `None` for colors no tile stands for
+    pub fn for_color(color: Color) -> Option<Self> {
+        if color.to_rgb() == GARBAGE_COLOR.to_rgb() {
+            return Some(Tile::Garbage);
+        }
+        TetrominoType::from_color(color).map(Tile::Piece)
+    }
+
+    /// Part of the sheet holding the tile, as fractions of the sheet's size the way `DrawParam::src` takes it
+    pub fn source(self) -> Rect {
+        let width = 1.0 / Self::COUNT as f32;
+        Rect::new(self.index() as f32 * width, 0.0, width, 1.0)
+    }
+}
+
+/// Blocks textured from a PNG sprite sheet instead of drawn from shapes
+/// The sheet is one row of `Tile::COUNT` square tiles; they are scaled with nearest-neighbour sampling so pixel art stays sharp
+#[derive(Debug, Clone)]
+pub struct Skin {
+    sheet: Image,    // The whole sprite sheet
+    tile_size: f32,  // Width and height of a tile in the sheet's pixels
+}
+
+impl Skin {
+    /// Loads a sprite sheet from the resources folder, e.g. "/skins/nes.png"
+    pub fn load(ctx: &Context, path: &str) -> GameResult<Self> {
+        let sheet = Image::from_path(ctx, path)?;
+        let tile_size = sheet.height();
+        if tile_size == 0 || sheet.width() != tile_size * Tile::COUNT as u32 {
+            return Err(GameError::ResourceLoadError(format!(
+                "{} is {}x{}, expected a row of {} square tiles",
+                path,
+                sheet.width(),
+                sheet.height(),
+                Tile::COUNT
+            )));
+        }
+        Ok(Self { sheet, tile_size: tile_size as f32 })
+    }
+
+    /// Draws a tile `size` logical pixels wide with its top-left corner at a screen position
+    /// `tint` multiplies the tile's colors; white keeps them as drawn, and its opacity fades the tile
+    pub fn draw(&self, canvas: &mut graphics::Canvas, tile: Tile, x: f32, y: f32, size: f32, tint: Color) {
+        let scale = size / self.tile_size;
+        canvas.set_sampler(graphics::Sampler::nearest_clamp());
+        canvas.draw(&self.sheet, DrawParam::new().src(tile.source()).dest([x, y]).scale([scale, scale]).color(tint));
+        canvas.set_default_sampler();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiles_cover_the_sheet() {
+        let tiles: Vec<Tile> = TetrominoType::ALL.iter().map(|&kind| Tile::Piece(kind)).chain([Tile::Garbage, Tile::Ghost]).collect();
+        assert_eq!(tiles.len(), Tile::COUNT);
+        for (i, tile) in tiles.iter().enumerate() {
+            assert_eq!(tile.index(), i);
+            assert!((tile.source().x - i as f32 / Tile::COUNT as f32).abs() < 1e-6);
+        }
+        assert_eq!(Tile::Ghost.source().right(), 1.0);
+
+        assert_eq!(Tile::for_color(TetrominoType::S.color()), Some(Tile::Piece(TetrominoType::S)));
+        assert_eq!(Tile::for_color(GARBAGE_COLOR), Some(Tile::Garbage));
+        assert_eq!(Tile::for_color(Color::MAGENTA), None);
+    }
+}
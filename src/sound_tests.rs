@@ -1,22 +1,18 @@
 #[cfg(test)]
 mod tests {
+    use crate::constants::SOUND_FORMATS;
     use std::path::Path;
 
     #[test]
     fn test_sound_files_exist() {
-        // Test that all sound files exist in the assets directory
-        let sound_files = [
-            "sounds/move.wav",
-            "sounds/rotate.wav",
-            "sounds/drop.wav",
-            "sounds/clear.wav",
-            "sounds/tetris.wav",
-            "sounds/game_over.wav",
-        ];
+        // Every sound exists in the assets directory in at least one of the formats the game loads
+        let sounds = ["move", "rotate", "drop", "clear", "tetris", "level_up", "game_over", "background"];
 
-        for file in sound_files.iter() {
-            let path = Path::new("assets").join(file);
-            assert!(path.exists(), "Sound file {} not found", file);
+        for sound in sounds.iter() {
+            let found = SOUND_FORMATS
+                .iter()
+                .any(|extension| Path::new("assets/sounds").join(format!("{}.{}", sound, extension)).exists());
+            assert!(found, "Sound {} not found as any of {:?}", sound, SOUND_FORMATS);
         }
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,339 @@
+use crate::config;
+use crate::constants::LIFETIME_STATS_FILE;
+use crate::events::GameEvent;
+use crate::finesse;
+use crate::garbage::TSpin;
+use crate::high_scores::HighScoreEntry;
+use crate::mode::{GameMode, Sprint};
+use crate::rotation::RotationSystem;
+use crate::tetromino::TetrominoType;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Live statistics of the current game, fed by spawn, input and lock events
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    spawned: [u32; 7],     // Pieces spawned of each type, in `TetrominoType::ALL` order
+    locked: u32,           // Pieces locked onto the board
+    lines: u32,            // Lines cleared
+    tetrises: u32,         // Locks that cleared four lines
+    t_spins: u32,          // Locks that were T-spins, mini or full
+    combo: u32,            // Consecutive locks that cleared lines, up to the last lock
+    max_combo: u32,        // Longest combo so far; the first clear of a streak is combo 0
+    piece_inputs: u32,     // Moves and rotations pressed for the current piece
+    soft_dropped: bool,    // Whether the current piece was soft dropped, which finesse doesn't judge
+    finesse_faults: u32,   // Inputs pressed beyond the fewest needed, summed over all pieces
+    attack: u32,           // Garbage lines sent to a versus opponent
+    elapsed: f64,          // Seconds of play, excluding pauses
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a piece entering the board
+    pub fn on_spawn(&mut self, kind: TetrominoType) {
+        let index = TetrominoType::ALL.iter().position(|&other| other == kind).unwrap();
+        self.spawned[index] += 1;
+        self.piece_inputs = 0;
+        self.soft_dropped = false;
+    }
+
+    /// Records a move or rotation pressed by the player; auto-repeated moves don't count
+    pub fn on_input(&mut self) {
+        self.piece_inputs += 1;
+    }
+
+    pub fn on_soft_drop(&mut self) {
+        self.soft_dropped = true;
+    }
+
+    /// Records a piece locking, with the lines it cleared and the fewest inputs that could have placed it
+    pub fn on_lock(&mut self, lines_cleared: u32, t_spin: TSpin, minimal_inputs: u32) {
+        self.locked += 1;
+        self.lines += lines_cleared;
+        if lines_cleared == 4 {
+            self.tetrises += 1;
+        }
+        if t_spin != TSpin::None {
+            self.t_spins += 1;
+        }
+        if lines_cleared > 0 {
+            self.combo += 1;
+            self.max_combo = self.max_combo.max(self.combo - 1);
+        } else {
+            self.combo = 0;
+        }
+        if !self.soft_dropped {
+            self.finesse_faults += self.piece_inputs.saturating_sub(minimal_inputs);
+        }
+    }
+
+    /// Records the spawns, locks and attacks among the game core's events; inputs are recorded by the frontend
+    pub fn on_event(&mut self, event: &GameEvent, rotation_system: &dyn RotationSystem) {
+        match event {
+            GameEvent::PieceSpawned(kind) => self.on_spawn(*kind),
+            GameEvent::PieceLocked(locked) => {
+                let minimal_inputs = finesse::minimal_inputs(&locked.piece, rotation_system);
+                self.on_lock(locked.full_rows.len() as u32, locked.t_spin, minimal_inputs)
+            }
+            GameEvent::AttackSent(lines) => self.attack += lines,
+            _ => {}
+        }
+    }
+
+    /// Advances the game clock
+    pub fn update(&mut self, dt: f64) {
+        self.elapsed += dt;
+    }
+
+    /// How many pieces of a type have spawned
+    pub fn spawned(&self, kind: TetrominoType) -> u32 {
+        let index = TetrominoType::ALL.iter().position(|&other| other == kind).unwrap();
+        self.spawned[index]
+    }
+
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Pieces locked onto the board
+    pub fn locked(&self) -> u32 {
+        self.locked
+    }
+
+    pub fn tetrises(&self) -> u32 {
+        self.tetrises
+    }
+
+    pub fn t_spins(&self) -> u32 {
+        self.t_spins
+    }
+
+    pub fn max_combo(&self) -> u32 {
+        self.max_combo
+    }
+
+    pub fn finesse_faults(&self) -> u32 {
+        self.finesse_faults
+    }
+
+    /// Garbage lines sent to a versus opponent
+    pub fn attack(&self) -> u32 {
+        self.attack
+    }
+
+    /// Pieces locked per second of play
+    pub fn pieces_per_second(&self) -> f64 {
+        if self.elapsed > 0.0 {
+            self.locked as f64 / self.elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// Lines cleared per minute of play
+    pub fn lines_per_minute(&self) -> f64 {
+        if self.elapsed > 0.0 {
+            self.lines as f64 * 60.0 / self.elapsed
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Full path of the file with the lifetime statistics of every game played on this machine
+pub fn lifetime_stats_path() -> PathBuf {
+    config::config_dir().join(LIFETIME_STATS_FILE)
+}
+
+/// Totals and bests over every game played, kept between runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub games: u32,          // Games finished
+    pub lines: u32,          // Lines cleared
+    pub pieces: u32,         // Pieces locked
+    pub tetrises: u32,       // Locks that cleared four lines
+    pub play_time: f64,      // Seconds of play, excluding pauses
+    pub best_score: u32,     // Highest score of any game
+    pub highest_level: u32,  // Highest level any game ended on
+    pub best_sprint: f64,    // Fastest finished sprint in seconds, 0.0 if none
+    pub archives: Vec<u64>,  // Export times of the archives already counted, so none is added twice
+}
+
+impl LifetimeStats {
+    /// Loads the statistics from a file, starting from zero if it is missing or invalid
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Saves the statistics to a file, creating its directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Adds a finished game, described by its score entry and live statistics
+    /// `completed` is whether the game reached its mode's goal, which a sprint needs to count as a time
+    pub fn record(&mut self, entry: &HighScoreEntry, stats: &Stats, completed: bool) {
+        self.games += 1;
+        self.lines += entry.lines;
+        self.pieces += stats.locked();
+        self.tetrises += stats.tetrises();
+        self.play_time += entry.duration;
+        self.best_score = self.best_score.max(entry.score);
+        self.highest_level = self.highest_level.max(entry.level);
+        if completed && entry.mode == Sprint.name() {
+            self.best_sprint = faster(self.best_sprint, entry.duration);
+        }
+    }
+
+    /// Adds the totals and bests of another machine's statistics, exported at `archive`
+    /// Returns false and changes nothing if that archive was counted already
+    pub fn merge(&mut self, other: &LifetimeStats, archive: u64) -> bool {
+        if self.archives.contains(&archive) {
+            return false;
+        }
+        self.games += other.games;
+        self.lines += other.lines;
+        self.pieces += other.pieces;
+        self.tetrises += other.tetrises;
+        self.play_time += other.play_time;
+        self.best_score = self.best_score.max(other.best_score);
+        self.highest_level = self.highest_level.max(other.highest_level);
+        self.best_sprint = faster(self.best_sprint, other.best_sprint);
+        self.archives.push(archive);
+        for &counted in &other.archives {
+            if !self.archives.contains(&counted) {
+                self.archives.push(counted);
+            }
+        }
+        true
+    }
+}
+
+/// The quicker of two times in seconds, where 0.0 means no time
+fn faster(a: f64, b: f64) -> f64 {
+    if a == 0.0 || (b > 0.0 && b < a) { b } else { a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_and_rates() {
+        let mut stats = Stats::new();
+        assert_eq!(stats.pieces_per_second(), 0.0);
+        assert_eq!(stats.lines_per_minute(), 0.0);
+
+        stats.on_spawn(TetrominoType::T);
+        stats.on_spawn(TetrominoType::T);
+        stats.on_spawn(TetrominoType::I);
+        assert_eq!(stats.spawned(TetrominoType::T), 2);
+        assert_eq!(stats.spawned(TetrominoType::I), 1);
+        assert_eq!(stats.spawned(TetrominoType::O), 0);
+
+        for lines in [0, 0, 4, 0, 2, 0] {
+            stats.on_lock(lines, TSpin::None, 0);
+        }
+        stats.update(30.0);
+        assert_eq!(stats.elapsed(), 30.0);
+        assert_eq!(stats.pieces_per_second(), 0.2);
+        assert_eq!(stats.lines_per_minute(), 12.0);
+        assert_eq!(stats.tetrises(), 1);
+    }
+
+    #[test]
+    fn test_combo_and_t_spins() {
+        let mut stats = Stats::new();
+        for lines in [1, 1, 2, 0, 1] {
+            stats.on_lock(lines, TSpin::None, 0);
+        }
+        stats.on_lock(2, TSpin::Full, 0);
+        assert_eq!(stats.max_combo(), 2);
+        assert_eq!(stats.t_spins(), 1);
+    }
+
+    #[test]
+    fn test_finesse_faults() {
+        let mut stats = Stats::new();
+        stats.on_spawn(TetrominoType::O);
+        for _ in 0..3 {
+            stats.on_input();
+        }
+        stats.on_lock(0, TSpin::None, 1);
+        assert_eq!(stats.finesse_faults(), 2);
+
+        // Soft-dropped pieces may need extra inputs for tucks, so they aren't judged
+        stats.on_spawn(TetrominoType::O);
+        stats.on_soft_drop();
+        for _ in 0..5 {
+            stats.on_input();
+        }
+        stats.on_lock(0, TSpin::None, 0);
+        assert_eq!(stats.finesse_faults(), 2);
+    }
+
+    #[test]
+    fn test_fed_by_game_events() {
+        let mut game = crate::game::Game::with_seed(1);
+        let mut stats = Stats::new();
+        for _ in 0..3 {
+            for event in game.apply(crate::game::Move::HardDrop).events {
+                stats.on_event(&event, game.rotation_system());
+            }
+        }
+        assert_eq!(stats.locked, 3);
+        // The first piece spawned before the stats were listening
+        assert_eq!(TetrominoType::ALL.iter().map(|&kind| stats.spawned(kind)).sum::<u32>(), 3);
+    }
+
+    #[test]
+    fn test_lifetime_stats_add_up_games() {
+        let mut game = Stats::new();
+        for lines in [4, 0, 4] {
+            game.on_lock(lines, TSpin::None, 0);
+        }
+        let entry = |mode: &str, level: u32, duration: f64| HighScoreEntry {
+            level,
+            lines: 8,
+            duration,
+            mode: mode.to_string(),
+            ..HighScoreEntry::new(String::new(), 1000 * level)
+        };
+
+        let mut lifetime = LifetimeStats::default();
+        lifetime.record(&entry("MARATHON", 5, 300.0), &game, false);
+        lifetime.record(&entry("SPRINT", 2, 90.0), &game, false);
+        assert_eq!(lifetime.best_sprint, 0.0, "a sprint that topped out has no time");
+        lifetime.record(&entry("SPRINT", 3, 80.0), &game, true);
+        lifetime.record(&entry("SPRINT", 3, 85.0), &game, true);
+
+        assert_eq!(lifetime.games, 4);
+        assert_eq!(lifetime.lines, 32);
+        assert_eq!(lifetime.pieces, 12);
+        assert_eq!(lifetime.tetrises, 8);
+        assert_eq!(lifetime.play_time, 555.0);
+        assert_eq!(lifetime.best_score, 5000);
+        assert_eq!(lifetime.highest_level, 5);
+        assert_eq!(lifetime.best_sprint, 80.0);
+
+        let path = std::env::temp_dir().join(format!("tetris-lifetime-test-{}.json", std::process::id()));
+        lifetime.save(&path).unwrap();
+        assert_eq!(LifetimeStats::load(&path), lifetime);
+        let _ = fs::remove_file(&path);
+        assert_eq!(LifetimeStats::load(&path), LifetimeStats::default());
+    }
+}
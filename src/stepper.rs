@@ -0,0 +1,77 @@
+use crate::constants::STEP_TICK;
+
+/// Runs the game a tick at a time for debugging timing, instead of with the frame clock
+/// While on, game time stands still until a step is asked for, then passes by exactly `STEP_TICK`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stepper {
+    enabled: bool,  // Whether the game is held between steps
+    pending: u32,   // Steps asked for and not yet run
+    ticks: u64,     // Steps run since step mode was turned on
+}
+
+impl Stepper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turns step mode on or off; turning it on starts counting ticks again
+    pub fn toggle(&mut self) {
+        *self = Self { enabled: !self.enabled, ..Self::default() };
+    }
+
+    /// Asks for one more tick, run on the next update
+    pub fn step(&mut self) {
+        if self.enabled {
+            self.pending += 1;
+        }
+    }
+
+    /// Ticks run since step mode was turned on
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Seconds the game should move on by this update: the frame's `dt` normally, one tick after a step
+    /// in step mode, or `None` to hold the game still
+    pub fn tick(&mut self, dt: f64) -> Option<f64> {
+        if !self.enabled {
+            return Some(dt);
+        }
+        if self.pending == 0 {
+            return None;
+        }
+        self.pending -= 1;
+        self.ticks += 1;
+        Some(STEP_TICK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_run_one_tick_each() {
+        let mut stepper = Stepper::new();
+        assert_eq!(stepper.tick(0.5), Some(0.5));
+        stepper.step();
+        assert_eq!(stepper.tick(0.5), Some(0.5));
+
+        stepper.toggle();
+        assert_eq!(stepper.tick(0.5), None);
+        stepper.step();
+        stepper.step();
+        assert_eq!(stepper.tick(0.5), Some(STEP_TICK));
+        assert_eq!(stepper.tick(0.5), Some(STEP_TICK));
+        assert_eq!(stepper.tick(0.5), None);
+        assert_eq!(stepper.ticks(), 2);
+
+        stepper.toggle();
+        assert!(!stepper.is_enabled());
+        assert_eq!(stepper.tick(0.5), Some(0.5));
+    }
+}
@@ -0,0 +1,172 @@
+/// Samples per second of generated sounds
+pub const SAMPLE_RATE: u32 = 22050;
+
+/// Seconds of each note's tail faded out, so notes don't click against each other
+const RELEASE: f32 = 0.005;
+
+/// Seconds of an eighth note in the music
+const EIGHTH: f32 = 0.2;
+
+/// Korobeiniki, the melody every falling-block game is known by: MIDI note and length in eighths, 0 for a rest
+const MELODY: [(u8, u8); 38] = [
+    (76, 2), (71, 1), (72, 1), (74, 2), (72, 1), (71, 1),
+    (69, 2), (69, 1), (72, 1), (76, 2), (74, 1), (72, 1),
+    (71, 3), (72, 1), (74, 2), (76, 2),
+    (72, 2), (69, 2), (69, 4),
+    (74, 3), (77, 1), (81, 2), (79, 1), (77, 1),
+    (76, 3), (72, 1), (76, 2), (74, 1), (72, 1),
+    (71, 2), (71, 1), (72, 1), (74, 2), (76, 2),
+    (72, 2), (69, 2), (69, 2), (0, 2),
+];
+
+/// Root note of the bass for each bar of the melody, played as octave-jumping eighths
+const BASS: [u8; 8] = [40, 45, 40, 45, 38, 36, 40, 45];
+
+/// Names of the sounds the synthesizer can stand in for
+pub const SOUNDS: [&str; 8] = ["move", "rotate", "drop", "clear", "tetris", "level_up", "game_over", "background"];
+
+/// A sound made up at runtime as a mono 16-bit WAV file, for when the game's sound files are missing
+/// Effects are square-wave blips and a noise burst in the style of handheld consoles, and the music
+/// is a two-voice rendition of Korobeiniki; returns `None` for sounds it has no recipe for
+pub fn sound(name: &str) -> Option<Vec<u8>> {
+    let samples = match name {
+        "move" => notes(&[(440.0, 0.03), (392.0, 0.03)], 0.25),
+        "rotate" => notes(&[(440.0, 0.025), (554.4, 0.025), (659.3, 0.025), (880.0, 0.025)], 0.25),
+        "drop" => noise(0.12, 0.4),
+        "clear" => sweep(440.0, 1320.0, 0.2, 0.3),
+        "tetris" => {
+            let mut samples = notes(&[(523.3, 0.06), (659.3, 0.06), (784.0, 0.06), (1046.5, 0.06)], 0.3);
+            samples.extend(chord(&[1046.5, 1318.5, 1568.0], 0.25, 0.3));
+            samples
+        }
+        "level_up" => notes(&[(523.3, 0.07), (659.3, 0.07), (784.0, 0.07), (1046.5, 0.2)], 0.3),
+        "game_over" => notes(&[(880.0, 0.1), (659.3, 0.1), (554.4, 0.1), (440.0, 0.1), (329.6, 0.3)], 0.3),
+        "background" => korobeiniki(),
+        _ => return None,
+    };
+    Some(wav(&samples))
+}
+
+/// Frequency in Hz of a MIDI note number, with 69 the A at 440 Hz
+fn frequency(note: u8) -> f32 {
+    440.0 * 2f32.powf((note as f32 - 69.0) / 12.0)
+}
+
+/// A square wave at `freq` Hz, faded out over its last few milliseconds
+fn square(freq: f32, duration: f32, amplitude: f32) -> Vec<f32> {
+    let length = (duration * SAMPLE_RATE as f32) as usize;
+    let release = (RELEASE * SAMPLE_RATE as f32) as usize;
+    (0..length)
+        .map(|i| {
+            let phase = (i as f32 * freq / SAMPLE_RATE as f32).fract();
+            let fade = ((length - i) as f32 / release as f32).min(1.0);
+            if phase < 0.5 { amplitude * fade } else { -amplitude * fade }
+        })
+        .collect()
+}
+
+/// Square-wave notes one after another, as (frequency, seconds) pairs
+fn notes(notes: &[(f32, f32)], amplitude: f32) -> Vec<f32> {
+    notes.iter().flat_map(|&(freq, duration)| square(freq, duration, amplitude)).collect()
+}
+
+/// Square waves sounding together, sharing the amplitude between them
+fn chord(freqs: &[f32], duration: f32, amplitude: f32) -> Vec<f32> {
+    let voices: Vec<Vec<f32>> = freqs.iter().map(|&freq| square(freq, duration, amplitude / freqs.len() as f32)).collect();
+    (0..voices[0].len()).map(|i| voices.iter().map(|voice| voice[i]).sum()).collect()
+}
+
+/// A square wave gliding from one frequency to another
+fn sweep(from: f32, to: f32, duration: f32, amplitude: f32) -> Vec<f32> {
+    let length = (duration * SAMPLE_RATE as f32) as usize;
+    let mut phase = 0.0f32;
+    (0..length)
+        .map(|i| {
+            let freq = from + (to - from) * i as f32 / length as f32;
+            phase = (phase + freq / SAMPLE_RATE as f32).fract();
+            if phase < 0.5 { amplitude } else { -amplitude }
+        })
+        .collect()
+}
+
+/// A burst of noise from a 15-bit shift register, dying away linearly
+fn noise(duration: f32, amplitude: f32) -> Vec<f32> {
+    let length = (duration * SAMPLE_RATE as f32) as usize;
+    let mut register: u16 = 1;
+    (0..length)
+        .map(|i| {
+            let feedback = (register ^ (register >> 1)) & 1;
+            register = (register >> 1) | (feedback << 14);
+            let decay = 1.0 - i as f32 / length as f32;
+            if register & 1 == 0 { amplitude * decay } else { -amplitude * decay }
+        })
+        .collect()
+}
+
+/// The melody over a bass line, one loop of the music
+fn korobeiniki() -> Vec<f32> {
+    let melody: Vec<f32> = MELODY
+        .iter()
+        .flat_map(|&(note, eighths)| {
+            let duration = eighths as f32 * EIGHTH;
+            match note {
+                0 => vec![0.0; (duration * SAMPLE_RATE as f32) as usize],
+                note => square(frequency(note), duration, 0.2),
+            }
+        })
+        .collect();
+    let bass: Vec<f32> = BASS
+        .iter()
+        .flat_map(|&root| (0..8).flat_map(move |eighth| square(frequency(root + eighth % 2 * 12), EIGHTH, 0.12)))
+        .collect();
+    melody.iter().zip(bass.iter().chain(std::iter::repeat(&0.0))).map(|(a, b)| a + b).collect()
+}
+
+/// Wraps samples from -1.0 to 1.0 in a mono 16-bit PCM WAV file
+fn wav(samples: &[f32]) -> Vec<u8> {
+    let data_size = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());       // Size of the format chunk
+    bytes.extend_from_slice(&1u16.to_le_bytes());        // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes());        // Mono
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());  // Bytes per second
+    bytes.extend_from_slice(&2u16.to_le_bytes());        // Bytes per frame
+    bytes.extend_from_slice(&16u16.to_le_bytes());       // Bits per sample
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for &sample in samples {
+        bytes.extend_from_slice(&((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_sound_is_a_valid_wav() {
+        for name in SOUNDS {
+            let bytes = sound(name).unwrap();
+            assert_eq!(&bytes[0..4], b"RIFF", "{}", name);
+            assert_eq!(&bytes[8..16], b"WAVEfmt ", "{}", name);
+            let data_size = u32::from_le_bytes(bytes[40..44].try_into().unwrap()) as usize;
+            assert_eq!(bytes.len(), 44 + data_size, "{}", name);
+            assert!(data_size > 0, "{} is silent", name);
+        }
+        assert_eq!(sound("theme_1"), None);
+    }
+
+    #[test]
+    fn test_music_fills_its_bars() {
+        // Every bar of the melody is eight eighths long, so the bass lines up with it
+        let eighths: u32 = MELODY.iter().map(|&(_, eighths)| eighths as u32).sum();
+        assert_eq!(eighths, BASS.len() as u32 * 8);
+        assert!(korobeiniki().iter().all(|sample| sample.abs() <= 1.0));
+        assert_eq!(frequency(69), 440.0);
+    }
+}
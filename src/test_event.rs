@@ -11,7 +11,3 @@ impl event::EventHandler for TestState {
         Ok(())
     }
 }
-
-fn main() -> GameResult {
-    Ok(())
-} 
\ No newline at end of file
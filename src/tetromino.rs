@@ -1,10 +1,11 @@
+use crate::constants::GRID_WIDTH;
 use ggez::graphics::Color;
 use glam::Vec2;
 use rand::Rng;
 
 /// Represents the different types of Tetris pieces
 /// Each variant corresponds to a standard Tetris piece shape
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TetrominoType {
     I, // Long piece
     O, // Square piece
@@ -15,91 +16,131 @@ pub enum TetrominoType {
     L, // L-shaped piece
 }
 
+impl TetrominoType {
+    /// All seven piece types
+    pub const ALL: [TetrominoType; 7] = [
+        TetrominoType::I,
+        TetrominoType::O,
+        TetrominoType::T,
+        TetrominoType::S,
+        TetrominoType::Z,
+        TetrominoType::J,
+        TetrominoType::L,
+    ];
+
+    /// A random piece type from the given random number generator
+    pub fn random_from<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::ALL[rng.gen_range(0..Self::ALL.len())]
+    }
+
+    /// The piece type drawn in a standard color, ignoring its opacity
+    pub fn from_color(color: Color) -> Option<TetrominoType> {
+        let (r, g, b) = color.to_rgb();
+        Self::ALL.into_iter().find(|kind| kind.color().to_rgb() == (r, g, b))
+    }
+
+    /// The piece type named by its letter, in either case
+    pub fn from_letter(letter: char) -> Option<TetrominoType> {
+        Self::ALL.into_iter().find(|kind| kind.letter() == letter.to_ascii_uppercase())
+    }
+
+    /// Capital letter the piece is named by
+    pub fn letter(self) -> char {
+        match self {
+            TetrominoType::I => 'I',
+            TetrominoType::O => 'O',
+            TetrominoType::T => 'T',
+            TetrominoType::S => 'S',
+            TetrominoType::Z => 'Z',
+            TetrominoType::J => 'J',
+            TetrominoType::L => 'L',
+        }
+    }
+
+    /// Standard color of the piece type
+    pub fn color(self) -> Color {
+        match self {
+            TetrominoType::I => Color::from_rgb(0, 240, 240),  // Bright Cyan for I piece
+            TetrominoType::O => Color::from_rgb(240, 240, 0),  // Bright Yellow for O piece
+            TetrominoType::T => Color::from_rgb(160, 0, 240),  // Bright Purple for T piece
+            TetrominoType::S => Color::from_rgb(0, 240, 0),    // Bright Green for S piece
+            TetrominoType::Z => Color::from_rgb(240, 0, 0),    // Bright Red for Z piece
+            TetrominoType::J => Color::from_rgb(0, 0, 240),    // Bright Blue for J piece
+            TetrominoType::L => Color::from_rgb(240, 160, 0),  // Bright Orange for L piece
+        }
+    }
+}
+
 /// Represents a Tetris piece with its shape, color, and position
 /// The shape is stored as a 2D vector of booleans where true represents a filled cell
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Tetromino {
+    pub kind: TetrominoType,    // Which of the seven pieces this is
     pub shape: Vec<Vec<bool>>,  // 2D grid representing the piece's shape
     pub color: Color,           // Color of the piece
     pub position: Vec2,         // Current position on the game board
+    pub rotation: u8,           // Orientation: 0 as spawned, then one more for each clockwise turn
 }
 
 impl Tetromino {
     /// Creates a new Tetromino piece of the specified type
     /// Each piece type has its own predefined shape and color
     pub fn new(tetromino_type: TetrominoType) -> Self {
-        let (shape, color) = match tetromino_type {
-            TetrominoType::I => (
-                vec![
-                    vec![true, true, true, true],  // I piece is a single row of 4 blocks
-                ],
-                Color::from_rgb(0, 240, 240),  // Bright Cyan for I piece
-            ),
-            TetrominoType::O => (
-                vec![
-                    vec![true, true],              // O piece is a 2x2 square
-                    vec![true, true],
-                ],
-                Color::from_rgb(240, 240, 0),  // Bright Yellow for O piece
-            ),
-            TetrominoType::T => (
-                vec![
-                    vec![false, true, false],      // T piece has a T shape
-                    vec![true, true, true],
-                ],
-                Color::from_rgb(160, 0, 240),  // Bright Purple for T piece
-            ),
-            TetrominoType::S => (
-                vec![
-                    vec![false, true, true],       // S piece has an S shape
-                    vec![true, true, false],
-                ],
-                Color::from_rgb(0, 240, 0),    // Bright Green for S piece
-            ),
-            TetrominoType::Z => (
-                vec![
-                    vec![true, true, false],       // Z piece has a Z shape
-                    vec![false, true, true],
-                ],
-                Color::from_rgb(240, 0, 0),    // Bright Red for Z piece
-            ),
-            TetrominoType::J => (
-                vec![
-                    vec![true, false, false],      // J piece has a J shape
-                    vec![true, true, true],
-                ],
-                Color::from_rgb(0, 0, 240),    // Bright Blue for J piece
-            ),
-            TetrominoType::L => (
-                vec![
-                    vec![false, false, true],      // L piece has an L shape
-                    vec![true, true, true],
-                ],
-                Color::from_rgb(240, 160, 0),  // Bright Orange for L piece
-            ),
+        let shape = match tetromino_type {
+            TetrominoType::I => vec![
+                vec![true, true, true, true],  // I piece is a single row of 4 blocks
+            ],
+            TetrominoType::O => vec![
+                vec![true, true],              // O piece is a 2x2 square
+                vec![true, true],
+            ],
+            TetrominoType::T => vec![
+                vec![false, true, false],      // T piece has a T shape
+                vec![true, true, true],
+            ],
+            TetrominoType::S => vec![
+                vec![false, true, true],       // S piece has an S shape
+                vec![true, true, false],
+            ],
+            TetrominoType::Z => vec![
+                vec![true, true, false],       // Z piece has a Z shape
+                vec![false, true, true],
+            ],
+            TetrominoType::J => vec![
+                vec![true, false, false],      // J piece has a J shape
+                vec![true, true, true],
+            ],
+            TetrominoType::L => vec![
+                vec![false, false, true],      // L piece has an L shape
+                vec![true, true, true],
+            ],
         };
 
         Self {
+            kind: tetromino_type,
+            position: Vec2::new(Self::spawn_column(shape[0].len()), 0.0),  // Centered at the top of the board
             shape,
-            color,
-            position: Vec2::new(3.0, 0.0),  // Start position: middle top of the board
+            color: tetromino_type.color(),
+            rotation: 0,
         }
     }
 
+    /// Column where a piece `width` cells wide spawns: centered, and one column left of center
+    /// when it can't be exactly, as in the guideline
+    pub fn spawn_column(width: usize) -> f32 {
+        ((GRID_WIDTH as usize).saturating_sub(width) / 2) as f32
+    }
+
     /// Creates a random Tetromino piece
     /// Used for spawning new pieces during gameplay
     pub fn random() -> Self {
-        let mut rng = rand::thread_rng();
-        let types = [
-            TetrominoType::I,
-            TetrominoType::O,
-            TetrominoType::T,
-            TetrominoType::S,
-            TetrominoType::Z,
-            TetrominoType::J,
-            TetrominoType::L,
-        ];
-        Self::new(types[rng.gen_range(0..types.len())])
+        Self::random_from(&mut rand::thread_rng())
+    }
+
+    /// Creates a random Tetromino piece using the given random number generator
+    /// Use a seeded generator to get the same sequence of pieces every run
+    pub fn random_from<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new(TetrominoType::random_from(rng))
     }
 
     /// Rotates the piece 90 degrees clockwise
@@ -109,13 +150,14 @@ impl Tetromino {
         let cols = self.shape[0].len();
         let mut new_shape = vec![vec![false; rows]; cols];
 
-        for y in 0..rows {
-            for x in 0..cols {
-                new_shape[x][rows - 1 - y] = self.shape[y][x];
+        for (y, row) in self.shape.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                new_shape[x][rows - 1 - y] = cell;
             }
         }
 
         self.shape = new_shape;
+        self.rotation = (self.rotation + 1) % 4;
     }
 
     /// Moves the piece one unit down
@@ -145,6 +187,15 @@ mod tests {
         assert_eq!(o_piece.color, Color::from_rgb(240, 240, 0));
     }
 
+    #[test]
+    fn test_spawn_is_centered() {
+        // Two columns on each side of O, one more on the right than the left of three-wide pieces
+        assert_eq!(Tetromino::new(TetrominoType::O).position.x, 4.0);
+        assert_eq!(Tetromino::new(TetrominoType::I).position.x, 3.0);
+        assert_eq!(Tetromino::new(TetrominoType::T).position.x, 3.0);
+        assert_eq!(Tetromino::spawn_column(GRID_WIDTH as usize + 2), 0.0);
+    }
+
     #[test]
     fn test_tetromino_movement() {
         let mut piece = Tetromino::new(TetrominoType::I);
@@ -183,4 +234,15 @@ mod tests {
             assert!(piece.shape.iter().all(|row| !row.is_empty()));
         }
     }
+
+    #[test]
+    fn test_seeded_random_is_reproducible() {
+        use rand::SeedableRng;
+
+        let mut first = rand::rngs::StdRng::seed_from_u64(42);
+        let mut second = rand::rngs::StdRng::seed_from_u64(42);
+        for _ in 0..20 {
+            assert_eq!(Tetromino::random_from(&mut first).kind, Tetromino::random_from(&mut second).kind);
+        }
+    }
 } 
\ No newline at end of file
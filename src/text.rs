@@ -0,0 +1,337 @@
+use ggez::graphics::{self, Color, FontData, PxScale, TextAlign, TextFragment, TextLayout};
+use crate::assets;
+use crate::error::{Error, Result};
+use ab_glyph::{Font, FontRef};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Name the bundled pixel font is registered under
+pub const PIXEL_FONT: &str = "pixel";
+/// Name the fallback font is registered under
+pub const FALLBACK_FONT: &str = "fallback";
+/// Resource path of the fallback font, which draws what the pixel font can't, such as accented or CJK names
+/// It is generated from GNU Unifont by `generate_font.py`; a file in the resource folders replaces the built-in one
+pub const FALLBACK_FONT_PATH: &str = "/fonts/fallback.ttf";
+/// Height of text drawn at scale 1, in logical pixels; the pixel font's 8-pixel cell makes each font pixel 2 logical pixels
+pub const TEXT_SIZE: f32 = 16.0;
+
+/// Advance of a pixel font glyph as a fraction of `TEXT_SIZE`: five pixels of glyph and one of spacing in an 8-pixel cell
+const GLYPH_ADVANCE: f32 = 0.75;
+
+/// Most entries kept in each text cache; scores and timers change every frame, so a full cache is emptied rather than grown
+const TEXT_CACHE_CAPACITY: usize = 256;
+
+/// The bundled pixel font, generated by `generate_font.py`
+static PIXEL_FONT_DATA: &[u8] = include_bytes!("../assets/fonts/pixel.ttf");
+
+/// Which part of the text the position given to `draw_text` refers to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Align {
+    Left,    // Left edge
+    Center,  // Horizontal center
+    Right,   // Right edge
+}
+
+impl From<Align> for TextAlign {
+    fn from(align: Align) -> Self {
+        match align {
+            Align::Left => TextAlign::Begin,
+            Align::Center => TextAlign::Middle,
+            Align::Right => TextAlign::End,
+        }
+    }
+}
+
+/// How a line of text is sized, colored and placed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    pub scale: f32,    // Multiple of `TEXT_SIZE`
+    pub color: Color,  // Text color; its alpha also fades the shadow
+    pub align: Align,  // Which part of the text sits at the given position
+    pub shadow: f32,   // Offset of the drop shadow in logical pixels, or 0 for none
+}
+
+impl TextStyle {
+    /// Left-aligned text at the base size with the usual 2 pixel shadow
+    pub fn new(color: Color) -> Self {
+        Self {
+            scale: 1.0,
+            color,
+            align: Align::Left,
+            shadow: 2.0,
+        }
+    }
+
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn centered(self) -> Self {
+        self.align(Align::Center)
+    }
+
+    pub fn shadow(mut self, offset: f32) -> Self {
+        self.shadow = offset;
+        self
+    }
+}
+
+/// Draws every line of text in the game, so all screens share one font and one look
+/// Glyphs are rasterized at the size they end up on screen rather than scaled up afterwards,
+/// which keeps them sharp in large windows and on high-DPI displays
+/// Built texts and measured sizes are cached, since most lines are the same every frame;
+/// changing the font or the pixel scale empties the caches
+pub struct TextRenderer {
+    font: Option<String>,                                           // Font registered by the current theme, if it has one
+    fallback: bool,                                                 // Whether the fallback font is registered
+    pixel_scale: f32,                                               // Window pixels per logical pixel
+    texts: RefCell<HashMap<(String, u32, Align), graphics::Text>>,  // Built texts by content, size bits and alignment
+    sizes: RefCell<HashMap<(String, u32), (f32, f32)>>,             // Logical sizes by content and scale bits
+}
+
+impl TextRenderer {
+    /// Registers the bundled pixel font and, when one can be found, the fallback font
+    pub fn new(ctx: &mut Context) -> GameResult<Self> {
+        ctx.gfx.add_font(PIXEL_FONT, FontData::from_slice(PIXEL_FONT_DATA)?);
+        let data = match assets::embedded(FALLBACK_FONT_PATH).filter(|_| !ctx.fs.exists(FALLBACK_FONT_PATH)) {
+            Some(data) => FontData::from_slice(data),
+            None => FontData::from_path(ctx, FALLBACK_FONT_PATH),
+        };
+        let mut renderer = Self::with_pixel_font();
+        match data {
+            Ok(data) => {
+                ctx.gfx.add_font(FALLBACK_FONT, data);
+                renderer.fallback = true;
+            }
+            Err(e) => log::warn!(target: "text", "No fallback font, characters the pixel font lacks show as boxes: {}", e),
+        }
+        Ok(renderer)
+    }
+
+    /// A renderer using the pixel font, for when the font is already registered
+    fn with_pixel_font() -> Self {
+        Self {
+            font: None,
+            fallback: false,
+            pixel_scale: 1.0,
+            texts: RefCell::new(HashMap::new()),
+            sizes: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Uses a theme's font instead of the pixel font, or goes back to the pixel font with `None`
+    pub fn set_font(&mut self, font: Option<&str>) {
+        let font = font.map(str::to_string);
+        if font != self.font {
+            self.font = font;
+            self.clear_cache();
+        }
+    }
+
+    /// Sets how many window pixels make up a logical pixel; called whenever the viewport is fitted
+    pub fn set_pixel_scale(&mut self, pixel_scale: f32) {
+        let pixel_scale = pixel_scale.max(f32::EPSILON);
+        if pixel_scale != self.pixel_scale {
+            self.pixel_scale = pixel_scale;
+            // Sizes are in logical pixels and stay valid; built texts are rasterized for the old scale
+            self.texts.get_mut().clear();
+        }
+    }
+
+    /// Forgets every built text and measured size
+    fn clear_cache(&mut self) {
+        self.texts.get_mut().clear();
+        self.sizes.get_mut().clear();
+    }
+
+    fn font(&self) -> &str {
+        self.font.as_deref().unwrap_or(PIXEL_FONT)
+    }
+
+    /// Builds the text at a size in window pixels
+    /// With the pixel font, runs of characters it has no glyph for are drawn with the fallback font;
+    /// a theme's font is used for everything
+    fn build(&self, content: &str, size: f32, align: Align) -> graphics::Text {
+        let mut text = graphics::Text::default();
+        if self.fallback && self.font.is_none() {
+            for (run, fallback) in font_runs(content) {
+                let fragment = TextFragment::new(run);
+                text.add(if fallback { fragment.font(FALLBACK_FONT) } else { fragment });
+            }
+        } else {
+            text.add(content);
+        }
+        text.set_font(self.font())
+            .set_scale(PxScale::from(size))
+            .set_layout(TextLayout {
+                h_align: align.into(),
+                v_align: TextAlign::Begin,
+            });
+        text
+    }
+
+    /// The text built at a size in window pixels, from the cache when it was built before
+    fn cached_text(&self, content: &str, size: f32, align: Align) -> graphics::Text {
+        let key = (content.to_string(), size.to_bits(), align);
+        let mut texts = self.texts.borrow_mut();
+        if let Some(text) = texts.get(&key) {
+            return text.clone();
+        }
+        if texts.len() >= TEXT_CACHE_CAPACITY {
+            texts.clear();
+        }
+        let text = self.build(content, size, align);
+        texts.insert(key, text.clone());
+        text
+    }
+
+    /// Width and height of a line of text in logical pixels
+    /// When the font can't measure it, the size is estimated from the pixel font's glyph cells and the error logged
+    pub fn measure(&self, ctx: &Context, content: &str, scale: f32) -> (f32, f32) {
+        self.try_measure(ctx, content, scale).unwrap_or_else(|e| {
+            log::warn!(target: "text", "{}", e);
+            estimate(content, scale)
+        })
+    }
+
+    /// Width and height of a line of text in logical pixels, as laid out by the font
+    /// Only successful measurements are cached, so a failed one is tried again next time
+    pub fn try_measure(&self, ctx: &Context, content: &str, scale: f32) -> Result<(f32, f32)> {
+        let key = (content.to_string(), scale.to_bits());
+        if let Some(&size) = self.sizes.borrow().get(&key) {
+            return Ok(size);
+        }
+        let text = self.build(content, TEXT_SIZE * scale, Align::Left);
+        let size = text
+            .measure(ctx)
+            .map(|size| (size.x, size.y))
+            .map_err(|source| Error::Text { content: content.to_string(), source })?;
+        let mut sizes = self.sizes.borrow_mut();
+        if sizes.len() >= TEXT_CACHE_CAPACITY {
+            sizes.clear();
+        }
+        sizes.insert(key, size);
+        Ok(size)
+    }
+
+    /// Draws a line of text with its top at `dest[1]`, and its left edge, center or right edge at `dest[0]`
+    pub fn draw_text(&self, canvas: &mut graphics::Canvas, content: &str, dest: [f32; 2], style: TextStyle) {
+        let text = self.cached_text(content, TEXT_SIZE * style.scale * self.pixel_scale, style.align);
+        let param = graphics::DrawParam::default().scale([1.0 / self.pixel_scale, 1.0 / self.pixel_scale]);
+        if style.shadow != 0.0 {
+            canvas.draw(
+                &text,
+                param
+                    .color(Color::new(0.0, 0.0, 0.0, 0.6 * style.color.a))
+                    .dest([dest[0] + style.shadow, dest[1] + style.shadow]),
+            );
+        }
+        canvas.draw(&text, param.color(style.color).dest(dest));
+    }
+}
+
+/// Whether the pixel font has a glyph for every character of a grapheme cluster
+fn pixel_font_has(cluster: &str) -> bool {
+    static GLYPHS: OnceLock<Option<FontRef<'static>>> = OnceLock::new();
+    let glyphs = GLYPHS.get_or_init(|| FontRef::try_from_slice(PIXEL_FONT_DATA).ok());
+    glyphs.as_ref().is_none_or(|font| cluster.chars().all(|ch| font.glyph_id(ch).0 != 0))
+}
+
+/// `content` split into runs for the pixel font and runs for the fallback font, marked `true`
+/// A grapheme cluster goes to the fallback font whole, so an accent is never drawn apart from its letter
+pub fn font_runs(content: &str) -> Vec<(&str, bool)> {
+    let mut runs: Vec<(&str, bool)> = Vec::new();
+    let mut start = 0;
+    for (i, cluster) in content.grapheme_indices(true) {
+        let fallback = !pixel_font_has(cluster);
+        match runs.last_mut() {
+            Some((run, last)) if *last == fallback => *run = &content[start..i + cluster.len()],
+            _ => {
+                start = i;
+                runs.push((cluster, fallback));
+            }
+        }
+    }
+    runs
+}
+
+/// Size of a line of text in the pixel font, without asking the font: every glyph has the same advance
+pub fn estimate(content: &str, scale: f32) -> (f32, f32) {
+    let size = TEXT_SIZE * scale;
+    (content.chars().count() as f32 * size * GLYPH_ADVANCE, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_font_loads() {
+        assert!(FontData::from_slice(PIXEL_FONT_DATA).is_ok());
+    }
+
+    #[test]
+    fn test_fallback_font_runs() {
+        assert_eq!(font_runs("SCORE 100"), vec![("SCORE 100", false)]);
+        assert_eq!(font_runs("Zoë 山田"), vec![("Zo", false), ("ë", true), (" ", false), ("山田", true)]);
+        assert_eq!(font_runs("Ze\u{0301}!"), vec![("Z", false), ("e\u{0301}", true), ("!", false)]);
+        assert!(font_runs("").is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-assets")]
+    fn test_fallback_font_covers_names() {
+        let font = FontRef::try_from_slice(assets::embedded(FALLBACK_FONT_PATH).unwrap()).unwrap();
+        assert!("Zoë 山田 Дмитрий 김민준 ŁĄ Ελένη Nguyễn さくら".chars().all(|ch| font.glyph_id(ch).0 != 0));
+        // Scripts names aren't typed in are left out to keep the font small
+        assert_eq!(font.glyph_id('ᚠ').0, 0);
+    }
+
+    #[test]
+    fn test_estimate_uses_glyph_cells() {
+        assert_eq!(estimate("", 2.0), (0.0, 2.0 * TEXT_SIZE));
+        assert_eq!(estimate("TETRIS", 1.0), (6.0 * 12.0, TEXT_SIZE));
+    }
+
+    #[test]
+    fn test_style_builder() {
+        let style = TextStyle::new(Color::WHITE).scale(3.0).centered().shadow(0.0);
+        assert_eq!(style.scale, 3.0);
+        assert_eq!(style.align, Align::Center);
+        assert_eq!(style.shadow, 0.0);
+        assert_eq!(TextAlign::from(Align::Right), TextAlign::End);
+    }
+
+    #[test]
+    fn test_text_cache_reuses_and_invalidates() {
+        let mut renderer = TextRenderer::with_pixel_font();
+        renderer.cached_text("SCORE", 16.0, Align::Left);
+        renderer.cached_text("SCORE", 16.0, Align::Left);
+        renderer.cached_text("SCORE", 32.0, Align::Left);
+        assert_eq!(renderer.texts.borrow().len(), 2);
+
+        // Same scale keeps the cache, a new scale or font empties it
+        renderer.set_pixel_scale(1.0);
+        assert_eq!(renderer.texts.borrow().len(), 2);
+        renderer.set_pixel_scale(2.0);
+        assert!(renderer.texts.borrow().is_empty());
+        renderer.cached_text("LEVEL", 16.0, Align::Center);
+        renderer.set_font(Some("/fonts/retro.ttf"));
+        assert!(renderer.texts.borrow().is_empty());
+
+        // Text that changes every frame can't grow the cache forever
+        for i in 0..TEXT_CACHE_CAPACITY * 2 {
+            renderer.cached_text(&i.to_string(), 16.0, Align::Left);
+        }
+        assert!(renderer.texts.borrow().len() <= TEXT_CACHE_CAPACITY);
+    }
+}
@@ -0,0 +1,390 @@
+use crate::constants::GARBAGE_COLOR;
+use crate::tetromino::TetrominoType;
+use ggez::graphics::Color;
+use serde::{Deserialize, Serialize};
+
+/// How the ghost piece is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GhostStyle {
+    Filled,   // Translucent blocks
+    Outline,  // Only the outline of each block
+}
+
+/// Colors of the seven pieces
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PieceColors {
+    #[serde(with = "hex")]
+    pub i: Color,
+    #[serde(with = "hex")]
+    pub o: Color,
+    #[serde(with = "hex")]
+    pub t: Color,
+    #[serde(with = "hex")]
+    pub s: Color,
+    #[serde(with = "hex")]
+    pub z: Color,
+    #[serde(with = "hex")]
+    pub j: Color,
+    #[serde(with = "hex")]
+    pub l: Color,
+}
+
+impl PieceColors {
+    /// Color of a piece type
+    pub fn get(&self, kind: TetrominoType) -> Color {
+        match kind {
+            TetrominoType::I => self.i,
+            TetrominoType::O => self.o,
+            TetrominoType::T => self.t,
+            TetrominoType::S => self.s,
+            TetrominoType::Z => self.z,
+            TetrominoType::J => self.j,
+            TetrominoType::L => self.l,
+        }
+    }
+}
+
+/// Colors and styles the renderer draws with
+/// Themes are TOML files; colors are written as `"#RRGGBB"` or `"#RRGGBBAA"`,
+/// and anything missing from a file is taken from the classic theme
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub name: String,             // Name shown in the settings screen
+    pub pieces: PieceColors,      // Colors of the seven pieces
+    #[serde(with = "hex")]
+    pub garbage: Color,           // Color of garbage lines
+    #[serde(with = "hex")]
+    pub background: Color,        // Window background
+    #[serde(with = "hex")]
+    pub grid: Color,              // Grid lines inside the playfield
+    #[serde(with = "hex")]
+    pub border: Color,            // Border around the playfield and opponent board
+    #[serde(with = "hex")]
+    pub panel: Color,             // Background of the side panels
+    #[serde(with = "hex")]
+    pub panel_frame: Color,       // Outer frame around the side panels
+    #[serde(with = "hex")]
+    pub panel_inner: Color,       // Inner frame around the side panels
+    #[serde(with = "hex")]
+    pub text: Color,              // Regular text
+    #[serde(with = "hex")]
+    pub accent: Color,            // Titles and the selected menu item
+    #[serde(with = "hex")]
+    pub hint: Color,              // Help lines and secondary text
+    pub ghost: GhostStyle,        // How the ghost piece is drawn
+    pub ghost_alpha: f32,         // Opacity of the ghost piece
+    pub font: Option<String>,     // Font file in the resources folder replacing the bundled pixel font, e.g. "/fonts/retro.ttf"
+    pub skin: Option<String>,     // PNG sprite sheet in the resources folder texturing the blocks, e.g. "/skins/nes.png"
+    pub level_palettes: Vec<PieceColors>,  // Piece colors cycled through one level at a time during games; empty keeps `pieces`
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+impl Theme {
+    /// The original look of the game
+    pub fn classic() -> Self {
+        Self {
+            name: "classic".to_string(),
+            pieces: PieceColors {
+                i: TetrominoType::I.color(),
+                o: TetrominoType::O.color(),
+                t: TetrominoType::T.color(),
+                s: TetrominoType::S.color(),
+                z: TetrominoType::Z.color(),
+                j: TetrominoType::J.color(),
+                l: TetrominoType::L.color(),
+            },
+            garbage: GARBAGE_COLOR,
+            background: Color::new(0.05, 0.05, 0.1, 1.0),
+            grid: Color::new(0.2, 0.2, 0.2, 1.0),
+            border: Color::WHITE,
+            panel: Color::new(0.1, 0.1, 0.1, 1.0),
+            panel_frame: Color::new(0.2, 0.2, 0.2, 1.0),
+            panel_inner: Color::new(0.3, 0.3, 0.3, 1.0),
+            text: Color::WHITE,
+            accent: Color::YELLOW,
+            hint: Color::new(0.7, 0.7, 1.0, 1.0),
+            ghost: GhostStyle::Filled,
+            ghost_alpha: 0.25,
+            font: None,
+            skin: None,
+            level_palettes: Vec::new(),
+        }
+    }
+
+    /// Muted colors on a near-black background
+    pub fn dark() -> Self {
+        Self {
+            name: "dark".to_string(),
+            pieces: PieceColors {
+                i: Color::from_rgb(40, 150, 160),
+                o: Color::from_rgb(170, 150, 40),
+                t: Color::from_rgb(120, 60, 160),
+                s: Color::from_rgb(60, 150, 70),
+                z: Color::from_rgb(160, 60, 60),
+                j: Color::from_rgb(60, 80, 170),
+                l: Color::from_rgb(170, 110, 40),
+            },
+            garbage: Color::from_rgb(70, 70, 70),
+            background: Color::from_rgb(8, 8, 10),
+            grid: Color::from_rgb(25, 25, 30),
+            border: Color::from_rgb(90, 90, 100),
+            panel: Color::from_rgb(14, 14, 18),
+            panel_frame: Color::from_rgb(40, 40, 48),
+            panel_inner: Color::from_rgb(28, 28, 34),
+            text: Color::from_rgb(200, 200, 205),
+            accent: Color::from_rgb(220, 180, 80),
+            hint: Color::from_rgb(120, 120, 150),
+            ghost: GhostStyle::Outline,
+            ghost_alpha: 0.5,
+            font: None,
+            skin: None,
+            level_palettes: Vec::new(),
+        }
+    }
+
+    /// Saturated colors on black with a white grid, for low vision
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "high-contrast".to_string(),
+            pieces: PieceColors {
+                i: Color::from_rgb(0, 255, 255),
+                o: Color::from_rgb(255, 255, 0),
+                t: Color::from_rgb(255, 0, 255),
+                s: Color::from_rgb(0, 255, 0),
+                z: Color::from_rgb(255, 0, 0),
+                j: Color::from_rgb(0, 128, 255),
+                l: Color::from_rgb(255, 128, 0),
+            },
+            garbage: Color::from_rgb(160, 160, 160),
+            background: Color::BLACK,
+            grid: Color::from_rgb(90, 90, 90),
+            border: Color::WHITE,
+            panel: Color::BLACK,
+            panel_frame: Color::WHITE,
+            panel_inner: Color::BLACK,
+            text: Color::WHITE,
+            accent: Color::from_rgb(255, 255, 0),
+            hint: Color::WHITE,
+            ghost: GhostStyle::Outline,
+            ghost_alpha: 1.0,
+            font: None,
+            skin: None,
+            level_palettes: Vec::new(),
+        }
+    }
+
+    /// Classic look with pieces from Paul Tol's bright palette, told apart without red-green contrast
+    pub fn deuteranopia() -> Self {
+        Self {
+            name: "deuteranopia".to_string(),
+            pieces: PieceColors {
+                i: Color::from_rgb(0x66, 0xCC, 0xEE),
+                o: Color::from_rgb(0xCC, 0xBB, 0x44),
+                t: Color::from_rgb(0xAA, 0x33, 0x77),
+                s: Color::from_rgb(0x22, 0x88, 0x33),
+                z: Color::from_rgb(0xEE, 0x66, 0x77),
+                j: Color::from_rgb(0x44, 0x77, 0xAA),
+                l: Color::from_rgb(0xBB, 0xBB, 0xBB),
+            },
+            ..Self::classic()
+        }
+    }
+
+    /// Classic look with the Okabe-Ito palette, which keeps reds bright enough to see without red cones
+    pub fn protanopia() -> Self {
+        Self {
+            name: "protanopia".to_string(),
+            pieces: PieceColors {
+                i: Color::from_rgb(0x56, 0xB4, 0xE9),
+                o: Color::from_rgb(0xF0, 0xE4, 0x42),
+                t: Color::from_rgb(0xCC, 0x79, 0xA7),
+                s: Color::from_rgb(0x00, 0x9E, 0x73),
+                z: Color::from_rgb(0xD5, 0x5E, 0x00),
+                j: Color::from_rgb(0x00, 0x72, 0xB2),
+                l: Color::from_rgb(0xE6, 0x9F, 0x00),
+            },
+            ..Self::classic()
+        }
+    }
+
+    /// Classic look with reds, teals and greys, avoiding blue-yellow contrast
+    pub fn tritanopia() -> Self {
+        Self {
+            name: "tritanopia".to_string(),
+            pieces: PieceColors {
+                i: Color::from_rgb(0x00, 0xB3, 0xB3),
+                o: Color::from_rgb(0xF0, 0xF0, 0xF0),
+                t: Color::from_rgb(0xFF, 0x4D, 0xA6),
+                s: Color::from_rgb(0x00, 0x66, 0x66),
+                z: Color::from_rgb(0xE3, 0x1A, 0x1C),
+                j: Color::from_rgb(0x80, 0x80, 0x80),
+                l: Color::from_rgb(0xFF, 0xA3, 0xA3),
+            },
+            ..Self::classic()
+        }
+    }
+
+    /// The themes that ship with the game
+    pub fn built_in() -> Vec<Theme> {
+        vec![
+            Self::classic(),
+            Self::dark(),
+            Self::high_contrast(),
+            Self::deuteranopia(),
+            Self::protanopia(),
+            Self::tritanopia(),
+        ]
+    }
+
+    /// Parses a theme file
+    pub fn from_toml(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// Piece colors for a level: the level palettes take turns like on the NES, or `pieces` when there are none
+    pub fn pieces_at(&self, level: u32) -> &PieceColors {
+        if self.level_palettes.is_empty() {
+            &self.pieces
+        } else {
+            &self.level_palettes[level as usize % self.level_palettes.len()]
+        }
+    }
+
+    /// Color to draw a board cell or piece with
+    /// Pieces and garbage are stored with the classic colors, so those are swapped for this theme's;
+    /// the opacity of `color` is kept, so faded pieces stay faded
+    pub fn block_color(&self, color: Color) -> Color {
+        self.themed(color, &self.pieces)
+    }
+
+    /// Color to draw a board cell or piece with during a game at `level`, taking the level's palette
+    pub fn block_color_at(&self, color: Color, level: u32) -> Color {
+        self.themed(color, self.pieces_at(level))
+    }
+
+    fn themed(&self, color: Color, pieces: &PieceColors) -> Color {
+        let themed = if color.to_rgb() == GARBAGE_COLOR.to_rgb() {
+            self.garbage
+        } else {
+            TetrominoType::from_color(color).map_or(color, |kind| pieces.get(kind))
+        };
+        Color::new(themed.r, themed.g, themed.b, color.a)
+    }
+}
+
+/// Serializes colors as `#RRGGBB` hex strings, with `AA` appended when not opaque
+mod hex {
+    use ggez::graphics::Color;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        let (r, g, b, a) = color.to_rgba();
+        let text = if a == 255 {
+            format!("#{:02X}{:02X}{:02X}", r, g, b)
+        } else {
+            format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+        };
+        serializer.serialize_str(&text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse(&text).ok_or_else(|| D::Error::custom(format!("invalid color {:?}, expected #RRGGBB", text)))
+    }
+
+    pub fn parse(text: &str) -> Option<Color> {
+        let digits = text.strip_prefix('#')?;
+        if !matches!(digits.len(), 6 | 8) || !digits.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+        let alpha = if digits.len() == 8 { channel(6)? } else { 255 };
+        Some(Color::from_rgba(channel(0)?, channel(2)?, channel(4)?, alpha))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_roundtrip_and_fallback() {
+        // Colors are stored with 8 bits per channel, so compare the saved text
+        for theme in Theme::built_in() {
+            let text = toml::to_string_pretty(&theme).unwrap();
+            let parsed = Theme::from_toml(&text).unwrap();
+            assert_eq!(toml::to_string_pretty(&parsed).unwrap(), text);
+            assert_eq!(parsed.name, theme.name);
+        }
+
+        // Missing fields come from the classic theme
+        let partial = Theme::from_toml("name = \"mine\"\nbackground = \"#102030\"\n").unwrap();
+        assert_eq!(partial.name, "mine");
+        assert_eq!(partial.background, Color::from_rgb(0x10, 0x20, 0x30));
+        assert_eq!(partial.pieces, Theme::classic().pieces);
+
+        assert!(Theme::from_toml("background = \"red\"").is_err());
+    }
+
+    #[test]
+    fn test_bundled_theme_parses() {
+        let theme = Theme::from_toml(include_str!("../themes/gameboy.toml")).unwrap();
+        assert_eq!(theme.name, "gameboy");
+        assert_eq!(theme.ghost, GhostStyle::Outline);
+    }
+
+    #[test]
+    fn test_block_color_maps_classic_colors() {
+        let dark = Theme::dark();
+        let t_piece = TetrominoType::T.color();
+        assert_eq!(dark.block_color(t_piece), dark.pieces.t);
+        assert_eq!(dark.block_color(GARBAGE_COLOR), dark.garbage);
+        assert_eq!(dark.block_color(Color::MAGENTA), Color::MAGENTA);
+        assert_eq!(Theme::classic().block_color(t_piece), t_piece);
+
+        // Faded pieces keep their opacity
+        let faded = Color::new(t_piece.r, t_piece.g, t_piece.b, 0.25);
+        assert_eq!(dark.block_color(faded), Color::new(dark.pieces.t.r, dark.pieces.t.g, dark.pieces.t.b, 0.25));
+    }
+
+    #[test]
+    fn test_level_palettes_cycle() {
+        let theme = Theme::from_toml(include_str!("../themes/nes.toml")).unwrap();
+        assert_eq!(theme.level_palettes.len(), 10);
+        let s_piece = TetrominoType::S.color();
+        assert_eq!(theme.block_color_at(s_piece, 3), theme.level_palettes[3].s);
+        assert_eq!(theme.block_color_at(s_piece, 13), theme.level_palettes[3].s);
+        assert_ne!(theme.block_color_at(s_piece, 4), theme.block_color_at(s_piece, 3));
+        assert_eq!(theme.block_color_at(GARBAGE_COLOR, 3), theme.garbage);
+        assert_eq!(theme.block_color(s_piece), theme.pieces.s);
+
+        // Themes without level palettes look the same at every level
+        let dark = Theme::dark();
+        assert_eq!(dark.block_color_at(s_piece, 7), dark.block_color(s_piece));
+    }
+
+    #[test]
+    fn test_colorblind_palettes_are_distinct() {
+        for theme in [Theme::deuteranopia(), Theme::protanopia(), Theme::tritanopia()] {
+            let colors: Vec<_> = TetrominoType::ALL.iter().map(|&kind| theme.pieces.get(kind).to_rgb()).collect();
+            for (i, color) in colors.iter().enumerate() {
+                assert!(!colors[i + 1..].contains(color), "{} repeats a piece color", theme.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(hex::parse("#FF8000"), Some(Color::from_rgb(255, 128, 0)));
+        assert_eq!(hex::parse("#00000080"), Some(Color::from_rgba(0, 0, 0, 128)));
+        assert_eq!(hex::parse("FF8000"), None);
+        assert_eq!(hex::parse("#GG0000"), None);
+    }
+}
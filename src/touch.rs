@@ -0,0 +1,119 @@
+use crate::constants::{TOUCH_FLICK_DISTANCE, TOUCH_FLICK_TIME, TOUCH_STEP, TOUCH_TAP_DISTANCE};
+
+/// What a finger on the screen did, found from its touch events
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    Tap(f32, f32),  // Lifted without moving far, at this point in logical pixels
+    Left,           // Dragged a step to the left
+    Right,          // Dragged a step to the right
+    Up,             // Dragged a step up
+    Down,           // Dragged a step down
+    Flick,          // Lifted at the end of a quick stroke down
+}
+
+/// A finger on the screen
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Touch {
+    start: [f32; 2],  // Where it went down
+    start_time: f64,  // When it went down, in seconds
+    anchor: [f32; 2], // Where the last step was counted from
+    dragged: bool,    // Whether it has moved too far to be a tap
+}
+
+/// Turns the touch events of one finger into gestures: a step every `TOUCH_STEP` pixels of dragging,
+/// a tap when the finger is lifted where it went down, and a flick after a quick stroke down
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TouchTracker {
+    touch: Option<Touch>,  // The finger down, if there is one
+}
+
+impl TouchTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A finger went down at `x`, `y` at `time` seconds
+    pub fn begin(&mut self, x: f32, y: f32, time: f64) {
+        self.touch = Some(Touch { start: [x, y], start_time: time, anchor: [x, y], dragged: false });
+    }
+
+    /// The finger moved to `x`, `y`; each step it has moved since the last one is a gesture
+    pub fn drag(&mut self, x: f32, y: f32) -> Vec<Gesture> {
+        let Some(touch) = &mut self.touch else {
+            return Vec::new();
+        };
+        let mut gestures = Vec::new();
+        while (x - touch.anchor[0]).abs() >= TOUCH_STEP {
+            let step = TOUCH_STEP.copysign(x - touch.anchor[0]);
+            touch.anchor[0] += step;
+            gestures.push(if step < 0.0 { Gesture::Left } else { Gesture::Right });
+        }
+        while (y - touch.anchor[1]).abs() >= TOUCH_STEP {
+            let step = TOUCH_STEP.copysign(y - touch.anchor[1]);
+            touch.anchor[1] += step;
+            gestures.push(if step < 0.0 { Gesture::Up } else { Gesture::Down });
+        }
+        let distance = (x - touch.start[0]).hypot(y - touch.start[1]);
+        touch.dragged |= !gestures.is_empty() || distance > TOUCH_TAP_DISTANCE;
+        gestures
+    }
+
+    /// The finger was lifted at `x`, `y` at `time` seconds: the last steps, then a tap or a flick
+    pub fn end(&mut self, x: f32, y: f32, time: f64) -> Vec<Gesture> {
+        let mut gestures = self.drag(x, y);
+        let Some(touch) = self.touch.take() else {
+            return gestures;
+        };
+        if !touch.dragged {
+            gestures.push(Gesture::Tap(x, y));
+        } else if y - touch.start[1] >= TOUCH_FLICK_DISTANCE && time - touch.start_time <= TOUCH_FLICK_TIME {
+            gestures.push(Gesture::Flick);
+        }
+        gestures
+    }
+
+    /// Forgets the finger without any more gestures, as when the system takes the touch away
+    pub fn cancel(&mut self) {
+        self.touch = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_taps_and_drags() {
+        let mut tracker = TouchTracker::new();
+        tracker.begin(100.0, 100.0, 0.0);
+        assert!(tracker.drag(105.0, 98.0).is_empty());
+        assert_eq!(tracker.end(104.0, 99.0, 0.1), [Gesture::Tap(104.0, 99.0)]);
+
+        // A step for every TOUCH_STEP pixels, counted from where the last step was taken
+        tracker.begin(100.0, 100.0, 0.0);
+        assert_eq!(tracker.drag(100.0 - TOUCH_STEP * 2.5, 100.0), [Gesture::Left, Gesture::Left]);
+        assert_eq!(tracker.drag(100.0 - TOUCH_STEP, 100.0), [Gesture::Right]);
+        assert_eq!(tracker.drag(100.0 - TOUCH_STEP, 100.0 - TOUCH_STEP), [Gesture::Up]);
+        assert!(tracker.end(100.0 - TOUCH_STEP, 100.0 - TOUCH_STEP, 2.0).is_empty(), "a drag is not a tap");
+
+        // Events after the finger is gone are ignored
+        assert!(tracker.drag(0.0, 0.0).is_empty());
+        tracker.begin(0.0, 0.0, 0.0);
+        tracker.cancel();
+        assert!(tracker.end(0.0, 0.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_quick_strokes_down_are_flicks() {
+        let mut tracker = TouchTracker::new();
+        tracker.begin(0.0, 0.0, 1.0);
+        let gestures = tracker.end(0.0, TOUCH_FLICK_DISTANCE, 1.0 + TOUCH_FLICK_TIME / 2.0);
+        assert_eq!(gestures.last(), Some(&Gesture::Flick));
+        assert!(gestures[..gestures.len() - 1].iter().all(|&gesture| gesture == Gesture::Down));
+
+        // The same stroke made slowly only soft drops
+        tracker.begin(0.0, 0.0, 1.0);
+        let gestures = tracker.end(0.0, TOUCH_FLICK_DISTANCE, 1.0 + TOUCH_FLICK_TIME * 2.0);
+        assert!(gestures.iter().all(|&gesture| gesture == Gesture::Down));
+    }
+}
@@ -0,0 +1,261 @@
+use crate::config;
+use crate::high_scores::{HighScoreEntry, HighScores};
+use crate::stats::LifetimeStats;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Header of the score table in a CSV archive
+const SCORES_HEADER: &str = "name,score,level,lines,duration,mode,timestamp";
+
+/// Header of the statistics table in a CSV archive
+const STATS_HEADER: &str = "stat,value";
+
+/// File offered on the import and export screen until the player types another
+pub fn default_archive_path() -> PathBuf {
+    config::config_dir().join("tetris_scores.json")
+}
+
+/// How an archive is written, picked by the file's extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,  // The whole archive as one JSON object
+    Csv,   // A table of scores, a blank line, then a table of statistics; opens in spreadsheets
+}
+
+impl Format {
+    /// CSV for `.csv` files, JSON for anything else
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => Format::Csv,
+            _ => Format::Json,
+        }
+    }
+}
+
+/// Everything a player can carry to another machine: the high score table and the lifetime statistics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Archive {
+    pub exported: u64,                     // Seconds since the Unix epoch when the archive was made; tells archives apart
+    pub high_scores: Vec<HighScoreEntry>,  // The score table, best first
+    pub lifetime: LifetimeStats,           // Totals over every game played on the exporting machine
+}
+
+/// What importing an archive changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Merged {
+    pub scores: usize,  // Scores that were new and made the table
+    pub stats: bool,    // Whether the statistics were added; false if the archive was imported before
+}
+
+impl Archive {
+    /// An archive of this machine's scores and statistics, made now
+    pub fn new(scores: &HighScores, lifetime: &LifetimeStats) -> Self {
+        Self {
+            exported: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()),
+            high_scores: scores.entries.clone(),
+            lifetime: lifetime.clone(),
+        }
+    }
+
+    /// Writes the archive in the format its extension asks for
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let text = match Format::for_path(path) {
+            Format::Json => serde_json::to_string_pretty(self)?,
+            Format::Csv => self.to_csv(),
+        };
+        fs::write(path, text)
+    }
+
+    /// Reads an archive in the format its extension names
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        match Format::for_path(path) {
+            Format::Json => Ok(serde_json::from_str(&text)?),
+            Format::Csv => Self::from_csv(&text),
+        }
+    }
+
+    /// Adds the archive's scores and statistics to this machine's, skipping anything already here
+    pub fn merge_into(&self, scores: &mut HighScores, lifetime: &mut LifetimeStats) -> Merged {
+        Merged {
+            scores: scores.merge(&self.high_scores),
+            stats: lifetime.merge(&self.lifetime, self.exported),
+        }
+    }
+
+    /// The scores as one row each, then the statistics as one row per field
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", SCORES_HEADER);
+        for entry in &self.high_scores {
+            let fields = [
+                quote(&entry.name),
+                entry.score.to_string(),
+                entry.level.to_string(),
+                entry.lines.to_string(),
+                entry.duration.to_string(),
+                quote(&entry.mode),
+                entry.timestamp.to_string(),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv.push('\n');
+        csv.push_str(STATS_HEADER);
+        csv.push('\n');
+        csv.push_str(&format!("exported,{}\n", self.exported));
+        // Statistics are written field by field so new ones are exported without changes here
+        if let Ok(Value::Object(fields)) = serde_json::to_value(&self.lifetime) {
+            for (name, value) in fields.iter().filter(|(_, value)| value.is_number()) {
+                csv.push_str(&format!("{},{}\n", name, value));
+            }
+        }
+        csv
+    }
+
+    /// Reads the tables written by `to_csv`; statistics missing from the file count as zero
+    pub fn from_csv(csv: &str) -> io::Result<Self> {
+        let mut lines = csv.lines().map(str::trim_end);
+        if lines.next() != Some(SCORES_HEADER) {
+            return Err(invalid("the file doesn't start with the score table"));
+        }
+        let mut high_scores = Vec::new();
+        for line in lines.by_ref() {
+            if line.is_empty() {
+                break;
+            }
+            let fields = split(line);
+            let [name, score, level, lines, duration, mode, timestamp] = fields.as_slice() else {
+                return Err(invalid(&format!("expected 7 fields in {:?}", line)));
+            };
+            high_scores.push(HighScoreEntry {
+                name: name.clone(),
+                score: number(score)?,
+                level: number(level)?,
+                lines: number(lines)?,
+                duration: number(duration)?,
+                mode: mode.clone(),
+                timestamp: number(timestamp)?,
+            });
+        }
+
+        if lines.next() != Some(STATS_HEADER) {
+            return Err(invalid("the statistics table is missing"));
+        }
+        let mut exported = 0;
+        let mut fields = Map::new();
+        for line in lines.filter(|line| !line.is_empty()) {
+            let Some((name, value)) = line.split_once(',') else {
+                return Err(invalid(&format!("expected a name and value in {:?}", line)));
+            };
+            if name == "exported" {
+                exported = number(value)?;
+            } else {
+                fields.insert(name.to_string(), value.parse::<Value>().map_err(|_| invalid(value))?);
+            }
+        }
+        let lifetime = serde_json::from_value(Value::Object(fields))?;
+        Ok(Self { exported, high_scores, lifetime })
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Parses a number field of a CSV row
+fn number<T: std::str::FromStr>(field: &str) -> io::Result<T> {
+    field.trim().parse().map_err(|_| invalid(&format!("{:?} is not a number", field)))
+}
+
+/// A CSV field, quoted if it holds a comma or a quote
+fn quote(field: &str) -> String {
+    if field.contains([',', '"']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Splits a CSV row into its fields, undoing `quote`
+fn split(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            ch => fields.last_mut().unwrap().push(ch),
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive() -> Archive {
+        let entry = |name: &str, score, timestamp| HighScoreEntry {
+            timestamp,
+            level: 4,
+            lines: 31,
+            duration: 184.5,
+            mode: "SPRINT".to_string(),
+            ..HighScoreEntry::new(name.to_string(), score)
+        };
+        let lifetime = LifetimeStats { games: 3, lines: 90, best_sprint: 95.25, ..LifetimeStats::default() };
+        Archive {
+            exported: 1_700_000_000,
+            high_scores: vec![entry("Alex", 9000, 1_600_000_000), entry("\"Quick\", Sam", 500, 0)],
+            lifetime,
+        }
+    }
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let archive = archive();
+        let csv = archive.to_csv();
+        assert!(csv.starts_with(SCORES_HEADER));
+        assert!(csv.contains("\"\"\"Quick\"\", Sam\""));
+        assert_eq!(Archive::from_csv(&csv).unwrap(), archive);
+
+        assert!(Archive::from_csv("name,score\n").is_err());
+        assert!(Archive::from_csv(&format!("{}\nAlex,lots,1,1,1,M,1\n", SCORES_HEADER)).is_err());
+        assert_eq!(Format::for_path(Path::new("scores.CSV")), Format::Csv);
+        assert_eq!(Format::for_path(Path::new("scores")), Format::Json);
+    }
+
+    #[test]
+    fn test_importing_twice_changes_nothing() {
+        let archive = archive();
+        let dir = std::env::temp_dir().join(format!("tetris-transfer-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["scores.json", "scores.csv"] {
+            let path = dir.join(name);
+            archive.write(&path).unwrap();
+            assert_eq!(Archive::read(&path).unwrap(), archive, "{}", name);
+        }
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut scores = HighScores::new();
+        scores.entries.push(HighScoreEntry { timestamp: 1_600_000_000, ..HighScoreEntry::new("Alex".to_string(), 9000) });
+        let mut lifetime = LifetimeStats { games: 1, best_sprint: 120.0, ..LifetimeStats::default() };
+        let merged = archive.merge_into(&mut scores, &mut lifetime);
+        assert_eq!(merged, Merged { scores: 1, stats: true });
+        assert_eq!(scores.entries.len(), 2);
+        assert_eq!(lifetime.games, 4);
+        assert_eq!(lifetime.best_sprint, 95.25);
+
+        assert_eq!(archive.merge_into(&mut scores, &mut lifetime), Merged { scores: 0, stats: false });
+        assert_eq!(lifetime.games, 4);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::ai::{AiController, Weights};
+use crate::game::Game;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Candidates in each generation
+pub const POPULATION: usize = 40;
+/// Games each candidate plays per generation; every candidate gets the same pieces
+pub const GAMES: usize = 3;
+/// Pieces each game lasts unless the bot tops out first
+pub const PIECES: u32 = 250;
+/// Share of the population replaced by offspring each generation
+const OFFSPRING_SHARE: f64 = 0.3;
+/// Share of the population drawn for each tournament that picks two parents
+const TOURNAMENT_SHARE: f64 = 0.1;
+/// Chance that a child's weights are nudged, and how far at most
+const MUTATION_CHANCE: f64 = 0.05;
+const MUTATION_STEP: f64 = 0.2;
+
+/// The weights as a vector, in field order
+fn to_vector(weights: &Weights) -> [f64; 4] {
+    [weights.aggregate_height, weights.lines, weights.holes, weights.bumpiness]
+}
+
+/// Weights from a vector scaled to unit length; the evaluation is linear, so only the direction matters
+fn from_vector(vector: [f64; 4]) -> Weights {
+    let length = vector.iter().map(|w| w * w).sum::<f64>().sqrt().max(f64::EPSILON);
+    let [aggregate_height, lines, holes, bumpiness] = vector.map(|w| w / length);
+    Weights { aggregate_height, lines, holes, bumpiness }
+}
+
+/// Average score of the bot with `weights` over one game per seed, each cut off after `pieces` pieces
+/// Lines alone stop telling good weights apart once every candidate clears all it can, while the score still
+/// rewards bigger clears and ends when the bot tops out. The bot only plans the falling piece, so tuning
+/// measures the heuristic rather than the search
+pub fn fitness(weights: &Weights, seeds: &[u64], pieces: u32) -> f64 {
+    let total: u32 = seeds
+        .iter()
+        .map(|&seed| {
+            let mut game = Game::with_seed(seed);
+            let mut controller = AiController::new(*weights);
+            controller.depth = 1;
+            while game.pieces() < pieces && !game.is_game_over() {
+                game.step(&mut controller);
+            }
+            game.score()
+        })
+        .sum();
+    total as f64 / seeds.len().max(1) as f64
+}
+
+/// A weights candidate and how well it played in the latest generation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candidate {
+    pub weights: Weights,  // Unit-length heuristic weights
+    pub fitness: f64,      // Average score
+}
+
+/// Tunes the bot's weights by self-play: candidates play the same games, the weakest are replaced by
+/// children of tournament winners, and a few children are mutated
+pub struct Tuner {
+    population: Vec<Candidate>,  // Best first after each generation
+    games: usize,                // Games per candidate and generation
+    pieces: u32,                 // Pieces per game
+    generation: u32,             // Generations run so far
+    rng: StdRng,                 // Source of weights, parents, mutations and game seeds
+}
+
+impl Tuner {
+    /// Starts from `size` random candidates, plus the current weights so tuning never does worse than them
+    pub fn new(seed: u64, size: usize, games: usize, pieces: u32, current: Weights) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut population = vec![Candidate { weights: from_vector(to_vector(&current)), fitness: 0.0 }];
+        while population.len() < size.max(2) {
+            let vector = [(); 4].map(|_| rng.gen_range(-1.0..1.0));
+            population.push(Candidate { weights: from_vector(vector), fitness: 0.0 });
+        }
+        Self { population, games: games.max(1), pieces, generation: 0, rng }
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// The best candidate of the latest generation
+    pub fn best(&self) -> Candidate {
+        self.population[0]
+    }
+
+    /// Plays a generation of games and breeds the next population; returns the best candidate
+    pub fn step(&mut self) -> Candidate {
+        let seeds: Vec<u64> = (0..self.games).map(|_| self.rng.gen()).collect();
+        for candidate in &mut self.population {
+            candidate.fitness = fitness(&candidate.weights, &seeds, self.pieces);
+        }
+        self.population.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+        let best = self.best();
+
+        let offspring = ((self.population.len() as f64 * OFFSPRING_SHARE) as usize).max(1);
+        let children: Vec<Candidate> = (0..offspring).map(|_| self.breed()).collect();
+        let survivors = self.population.len() - offspring;
+        self.population.truncate(survivors);
+        self.population.extend(children);
+        self.generation += 1;
+        best
+    }
+
+    /// A child of the two fittest of a random draw, its weights the parents' blended by fitness
+    fn breed(&mut self) -> Candidate {
+        let draw = ((self.population.len() as f64 * TOURNAMENT_SHARE) as usize).max(2);
+        let mut entrants: Vec<Candidate> = (0..draw).map(|_| self.population[self.rng.gen_range(0..self.population.len())]).collect();
+        entrants.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+        let (first, second) = (entrants[0], entrants[1]);
+
+        let share = if first.fitness + second.fitness > 0.0 { first.fitness / (first.fitness + second.fitness) } else { 0.5 };
+        let (a, b) = (to_vector(&first.weights), to_vector(&second.weights));
+        let mut vector: [f64; 4] = std::array::from_fn(|i| a[i] * share + b[i] * (1.0 - share));
+        if self.rng.gen_bool(MUTATION_CHANCE) {
+            let i = self.rng.gen_range(0..4);
+            vector[i] += self.rng.gen_range(-MUTATION_STEP..MUTATION_STEP);
+        }
+        Candidate { weights: from_vector(vector), fitness: 0.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuner_keeps_the_best_weights() {
+        let mut tuner = Tuner::new(1, 6, 1, 40, Weights::default());
+        assert!((to_vector(&tuner.best().weights).iter().map(|w| w * w).sum::<f64>() - 1.0).abs() < 1e-9);
+
+        let first = tuner.step();
+        assert_eq!(tuner.generation(), 1);
+        assert!(first.fitness > 0.0, "the default weights score");
+        // The best candidate survives into the next generation
+        assert!(tuner.population.iter().any(|candidate| candidate.weights == first.weights));
+    }
+}
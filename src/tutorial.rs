@@ -0,0 +1,177 @@
+use crate::board::GameBoard;
+use crate::constants::GARBAGE_COLOR;
+use crate::events::GameEvent;
+use crate::game::{Game, Move, StepResult};
+use crate::keybindings::Action;
+use crate::tetromino::TetrominoType;
+
+/// Pieces dealt when the tutorial starts; a T first, as its turns are easy to follow
+pub const SEQUENCE: [TetrominoType; 7] = [
+    TetrominoType::T,
+    TetrominoType::L,
+    TetrominoType::O,
+    TetrominoType::J,
+    TetrominoType::S,
+    TetrominoType::Z,
+    TetrominoType::T,
+];
+
+/// Columns left open in the bottom row for the ghost lesson, where a flat I fits
+const GAP: std::ops::Range<usize> = 3..7;
+
+/// One step of the tutorial, finished by doing what it asks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lesson {
+    Move,      // Shift the piece left or right
+    Rotate,    // Turn the piece
+    SoftDrop,  // Move the piece down a row
+    HardDrop,  // Drop the piece all the way and lock it
+    Hold,      // Swap the piece into the hold box
+    Ghost,     // Follow the ghost to fill a gap and clear a line
+}
+
+impl Lesson {
+    /// Every lesson, in the order they're taught
+    pub const ALL: [Lesson; 6] = [Lesson::Move, Lesson::Rotate, Lesson::SoftDrop, Lesson::HardDrop, Lesson::Hold, Lesson::Ghost];
+
+    /// What the player is asked to do, shown above the board
+    pub fn prompt(self) -> &'static str {
+        match self {
+            Lesson::Move => "MOVE THE PIECE LEFT OR RIGHT",
+            Lesson::Rotate => "ROTATE THE PIECE",
+            Lesson::SoftDrop => "SOFT DROP TO MOVE IT DOWN",
+            Lesson::HardDrop => "HARD DROP TO LOCK IT AT ONCE",
+            Lesson::Hold => "HOLD THE PIECE TO SAVE IT FOR LATER",
+            Lesson::Ghost => "THE GHOST SHOWS WHERE IT LANDS: FILL THE GAP",
+        }
+    }
+
+    /// Actions whose keys are shown with the prompt
+    pub fn actions(self) -> &'static [Action] {
+        match self {
+            Lesson::Move => &[Action::MoveLeft, Action::MoveRight],
+            Lesson::Rotate => &[Action::RotateCw],
+            Lesson::SoftDrop => &[Action::SoftDrop],
+            Lesson::HardDrop | Lesson::Ghost => &[Action::HardDrop],
+            Lesson::Hold => &[Action::Hold],
+        }
+    }
+
+    /// Whether an input and what it did finish the lesson
+    fn is_done_by(self, mv: Move, result: &StepResult) -> bool {
+        let happened = |wanted: GameEvent| result.events.contains(&wanted);
+        match self {
+            Lesson::Move => matches!(mv, Move::Left | Move::Right) && result.moved,
+            Lesson::Rotate => happened(GameEvent::PieceRotated),
+            Lesson::SoftDrop => mv == Move::SoftDrop && result.moved,
+            Lesson::HardDrop => mv == Move::HardDrop && result.locked.is_some(),
+            Lesson::Hold => happened(GameEvent::PieceHeld),
+            // The completed row may wait out the line clear delay, so the lock that completes it counts
+            Lesson::Ghost => result.locked.as_ref().is_some_and(|locked| !locked.full_rows.is_empty()),
+        }
+    }
+
+    /// Sets the board up for the lesson; only the ghost lesson needs one, a bottom row with a gap for an I
+    fn setup(self, game: &mut Game) {
+        if self != Lesson::Ghost {
+            return;
+        }
+        let board = game.board_mut();
+        let (width, height) = (board.width(), board.height());
+        *board = GameBoard::with_size(width, height);
+        for x in (0..width).filter(|x| !GAP.contains(x)) {
+            board[height - 1][x] = GARBAGE_COLOR;
+        }
+        game.deal_sequence(&[TetrominoType::I, TetrominoType::I]);
+    }
+}
+
+/// Walks a player through the lessons in order; each has to be done before the next one starts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tutorial {
+    done: usize,  // Lessons finished so far
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The lesson being taught, or `None` once every lesson is done
+    pub fn lesson(&self) -> Option<Lesson> {
+        Lesson::ALL.get(self.done).copied()
+    }
+
+    /// Lessons finished so far
+    pub fn progress(&self) -> usize {
+        self.done
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.lesson().is_none()
+    }
+
+    /// Deals the tutorial's pieces to a new game
+    pub fn start(&mut self, game: &mut Game) {
+        self.done = 0;
+        game.deal_sequence(&SEQUENCE);
+    }
+
+    /// Checks an input against the lesson, moving on and setting up the next lesson once it's done
+    /// A piece that locks without filling the ghost lesson's gap sets the gap up again. Returns true if a lesson was finished
+    pub fn on_move(&mut self, game: &mut Game, mv: Move, result: &StepResult) -> bool {
+        let Some(lesson) = self.lesson() else {
+            return false;
+        };
+        if !lesson.is_done_by(mv, result) {
+            if lesson == Lesson::Ghost && result.locked.is_some() {
+                lesson.setup(game);
+            }
+            return false;
+        }
+        self.done += 1;
+        if let Some(next) = self.lesson().filter(|_| lesson != Lesson::Ghost) {
+            next.setup(game);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lessons_are_gated_in_order() {
+        let mut game = Game::with_seed(1);
+        let mut tutorial = Tutorial::new();
+        tutorial.start(&mut game);
+        assert_eq!(game.current_piece().unwrap().kind, TetrominoType::T);
+
+        // Inputs for later lessons don't count before their turn
+        let play = |game: &mut Game, tutorial: &mut Tutorial, mv: Move| {
+            let result = game.apply(mv);
+            tutorial.on_move(game, mv, &result)
+        };
+        assert!(!play(&mut game, &mut tutorial, Move::HardDrop));
+        assert_eq!(tutorial.lesson(), Some(Lesson::Move));
+        assert!(play(&mut game, &mut tutorial, Move::Left));
+        assert!(play(&mut game, &mut tutorial, Move::RotateCw));
+        assert!(play(&mut game, &mut tutorial, Move::SoftDrop));
+        assert!(play(&mut game, &mut tutorial, Move::HardDrop));
+        assert!(play(&mut game, &mut tutorial, Move::Hold));
+
+        // The ghost lesson deals an I over a gap it fits, and ends with the line it clears
+        assert_eq!(tutorial.lesson(), Some(Lesson::Ghost));
+        assert_eq!(game.current_piece().unwrap().kind, TetrominoType::I);
+        let piece = game.current_piece().unwrap().position.x as usize;
+        let target = GAP.start;
+        let shift = if piece > target { Move::Left } else { Move::Right };
+        for _ in 0..piece.abs_diff(target) {
+            game.apply(shift);
+        }
+        assert!(play(&mut game, &mut tutorial, Move::HardDrop));
+        assert!(tutorial.is_complete());
+        assert!(game.board().rows().iter().flatten().all(|&color| color == ggez::graphics::Color::BLACK));
+    }
+}
@@ -0,0 +1,454 @@
+use crate::text::{TextRenderer, TextStyle};
+use crate::theme::Theme;
+use ggez::graphics;
+use ggez::input::keyboard::KeyCode;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// One line of a menu, optionally showing the current value of a setting
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuItem {
+    pub label: String,          // Text shown for the item
+    pub value: Option<String>,  // Current value, shown after the label
+}
+
+/// What a key press did to a menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuEvent {
+    None,                  // The key isn't used by the menu
+    Moved,                 // The cursor moved to another item
+    Activated(usize),      // Enter was pressed on the item
+    Adjusted(usize, i32),  // Left (-1) or right (+1) was pressed on the item
+    Back,                  // Escape was pressed
+}
+
+/// A vertical list of items navigated with the arrow keys
+/// The menu only tracks the cursor; screens decide what each event means
+#[derive(Debug, Clone)]
+pub struct Menu {
+    pub items: Vec<MenuItem>,  // Items from top to bottom
+    selected: usize,           // Index of the highlighted item
+}
+
+impl Menu {
+    /// Creates a menu with the given item labels and the first item selected
+    pub fn new(labels: &[&str]) -> Self {
+        Self {
+            items: labels
+                .iter()
+                .map(|label| MenuItem {
+                    label: label.to_string(),
+                    value: None,
+                })
+                .collect(),
+            selected: 0,
+        }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.items.len().saturating_sub(1));
+    }
+
+    /// Sets the value shown next to an item
+    pub fn set_value(&mut self, index: usize, value: impl Into<String>) {
+        if let Some(item) = self.items.get_mut(index) {
+            item.value = Some(value.into());
+        }
+    }
+
+    /// Handles a key press; Up and Down wrap around the ends of the list
+    pub fn handle_key(&mut self, key: KeyCode) -> MenuEvent {
+        let count = self.items.len();
+        if count == 0 {
+            return MenuEvent::None;
+        }
+        match key {
+            KeyCode::Up => {
+                self.selected = (self.selected + count - 1) % count;
+                MenuEvent::Moved
+            }
+            KeyCode::Down => {
+                self.selected = (self.selected + 1) % count;
+                MenuEvent::Moved
+            }
+            KeyCode::Left => MenuEvent::Adjusted(self.selected, -1),
+            KeyCode::Right => MenuEvent::Adjusted(self.selected, 1),
+            KeyCode::Return | KeyCode::Space => MenuEvent::Activated(self.selected),
+            KeyCode::Escape => MenuEvent::Back,
+            _ => MenuEvent::None,
+        }
+    }
+
+    /// Draws the menu with its first item centered on `anchor` = `[center_x, top]`
+    /// The selected item is highlighted and its value gets arrows to show it can be changed
+    pub fn draw(
+        &self,
+        canvas: &mut graphics::Canvas,
+        text: &TextRenderer,
+        theme: &Theme,
+        anchor: [f32; 2],
+        spacing: f32,
+        scale: f32,
+    ) {
+        let [center_x, top] = anchor;
+        for (i, item) in self.items.iter().enumerate() {
+            let selected = i == self.selected;
+            let line = match (&item.value, selected) {
+                (Some(value), true) => format!("> {}   < {} >", item.label, value),
+                (Some(value), false) => format!("{}   {}", item.label, value),
+                (None, true) => format!("> {} <", item.label),
+                (None, false) => item.label.clone(),
+            };
+            let color = if selected { theme.accent } else { theme.text };
+            let y = top + i as f32 * spacing;
+            text.draw_text(canvas, &line, [center_x, y], TextStyle::new(color).scale(scale).centered());
+        }
+    }
+}
+
+/// Characters on the on-screen keyboard, before its delete and done keys; with them they fill four rows
+const GRID_CHARS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-.";
+
+/// Keys in each row of the on-screen keyboard
+pub const GRID_COLUMNS: usize = 10;
+
+/// A key of the on-screen keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridKey {
+    Char(char),  // Adds the character to the name
+    Delete,      // Removes the last character
+    Done,        // Finishes the name
+}
+
+impl GridKey {
+    fn label(self) -> String {
+        match self {
+            GridKey::Char(ch) => ch.to_string(),
+            GridKey::Delete => "DEL".to_string(),
+            GridKey::Done => "END".to_string(),
+        }
+    }
+}
+
+/// An arcade-style keyboard for entering names with the arrow keys and Enter alone
+/// Like `Menu`, it only tracks the cursor and leaves what a key does to the screen
+#[derive(Debug, Clone)]
+pub struct CharGrid {
+    keys: Vec<GridKey>,  // Keys row by row, `GRID_COLUMNS` to a row
+    selected: usize,     // Index of the highlighted key
+}
+
+impl Default for CharGrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CharGrid {
+    /// Creates the keyboard with its first key selected
+    pub fn new() -> Self {
+        let mut keys: Vec<GridKey> = GRID_CHARS.chars().map(GridKey::Char).collect();
+        keys.extend([GridKey::Delete, GridKey::Done]);
+        Self { keys, selected: 0 }
+    }
+
+    pub fn selected(&self) -> GridKey {
+        self.keys[self.selected]
+    }
+
+    /// Moves the cursor to a key
+    pub fn select(&mut self, key: GridKey) {
+        if let Some(index) = self.keys.iter().position(|&k| k == key) {
+            self.selected = index;
+        }
+    }
+
+    /// Handles a key press: the arrows move the cursor, wrapping around the edges, and Enter
+    /// returns the key under it; other keys return `None`
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<GridKey> {
+        let rows = self.keys.len() / GRID_COLUMNS;
+        let (row, column) = (self.selected / GRID_COLUMNS, self.selected % GRID_COLUMNS);
+        let (row, column) = match key {
+            KeyCode::Left => (row, (column + GRID_COLUMNS - 1) % GRID_COLUMNS),
+            KeyCode::Right => (row, (column + 1) % GRID_COLUMNS),
+            KeyCode::Up => ((row + rows - 1) % rows, column),
+            KeyCode::Down => ((row + 1) % rows, column),
+            KeyCode::Return => return Some(self.selected()),
+            _ => return None,
+        };
+        self.selected = row * GRID_COLUMNS + column;
+        None
+    }
+
+    /// Draws the keyboard with its top row centered on `anchor` = `[center_x, top]`
+    /// The selected key is drawn in the accent color with a bar beneath it
+    pub fn draw(&self, canvas: &mut graphics::Canvas, text: &TextRenderer, theme: &Theme, anchor: [f32; 2], spacing: f32, scale: f32) {
+        let [center_x, top] = anchor;
+        let left = center_x - (GRID_COLUMNS - 1) as f32 * spacing / 2.0;
+        for (i, key) in self.keys.iter().enumerate() {
+            let x = left + (i % GRID_COLUMNS) as f32 * spacing;
+            let y = top + (i / GRID_COLUMNS) as f32 * spacing;
+            let selected = i == self.selected;
+            let color = if selected { theme.accent } else { theme.text };
+            text.draw_text(canvas, &key.label(), [x, y], TextStyle::new(color).scale(scale).centered());
+            if selected {
+                let bar = graphics::Rect::new(x - spacing * 0.35, y + spacing * 0.6, spacing * 0.7, 4.0);
+                canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(bar).color(theme.accent));
+            }
+        }
+    }
+}
+
+/// Easing curves that shape how a `Timeline` moves from start to end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,     // Constant speed
+    EaseOut,    // Fast at first, settling gently at the end (cubic)
+    EaseInOut,  // Slow at both ends (cubic)
+}
+
+impl Easing {
+    /// Eased position for linear progress `t`, both from 0.0 to 1.0
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOut => 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0,
+        }
+    }
+}
+
+/// One animation running from 0.0 to 1.0 over a fixed time
+/// Widgets restart it when what they show changes, advance it every frame and read `value` when drawing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timeline {
+    duration: f64,   // Seconds from start to end
+    elapsed: f64,    // Seconds since the last start
+    easing: Easing,  // Shape of the movement
+}
+
+impl Timeline {
+    /// A timeline that starts out finished, so nothing animates until `start` is called
+    pub fn new(duration: f64, easing: Easing) -> Self {
+        Self { duration, elapsed: duration, easing }
+    }
+
+    /// Runs the animation again from the beginning
+    pub fn start(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    /// Jumps to the end
+    pub fn finish(&mut self) {
+        self.elapsed = self.duration;
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.elapsed < self.duration
+    }
+
+    /// Share of the duration that has passed, from 0.0 to 1.0
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        (self.elapsed / self.duration).clamp(0.0, 1.0) as f32
+    }
+
+    /// Eased position of the animation, from 0.0 at the start to 1.0 at the end
+    pub fn value(&self) -> f32 {
+        self.easing.apply(self.progress())
+    }
+}
+
+/// Converts a keycode to a character for name entry
+pub fn keycode_to_char(keycode: KeyCode, shift: bool) -> Option<char> {
+    match keycode {
+        KeyCode::A => Some(if shift { 'A' } else { 'a' }),
+        KeyCode::B => Some(if shift { 'B' } else { 'b' }),
+        KeyCode::C => Some(if shift { 'C' } else { 'c' }),
+        KeyCode::D => Some(if shift { 'D' } else { 'd' }),
+        KeyCode::E => Some(if shift { 'E' } else { 'e' }),
+        KeyCode::F => Some(if shift { 'F' } else { 'f' }),
+        KeyCode::G => Some(if shift { 'G' } else { 'g' }),
+        KeyCode::H => Some(if shift { 'H' } else { 'h' }),
+        KeyCode::I => Some(if shift { 'I' } else { 'i' }),
+        KeyCode::J => Some(if shift { 'J' } else { 'j' }),
+        KeyCode::K => Some(if shift { 'K' } else { 'k' }),
+        KeyCode::L => Some(if shift { 'L' } else { 'l' }),
+        KeyCode::M => Some(if shift { 'M' } else { 'm' }),
+        KeyCode::N => Some(if shift { 'N' } else { 'n' }),
+        KeyCode::O => Some(if shift { 'O' } else { 'o' }),
+        KeyCode::P => Some(if shift { 'P' } else { 'p' }),
+        KeyCode::Q => Some(if shift { 'Q' } else { 'q' }),
+        KeyCode::R => Some(if shift { 'R' } else { 'r' }),
+        KeyCode::S => Some(if shift { 'S' } else { 's' }),
+        KeyCode::T => Some(if shift { 'T' } else { 't' }),
+        KeyCode::U => Some(if shift { 'U' } else { 'u' }),
+        KeyCode::V => Some(if shift { 'V' } else { 'v' }),
+        KeyCode::W => Some(if shift { 'W' } else { 'w' }),
+        KeyCode::X => Some(if shift { 'X' } else { 'x' }),
+        KeyCode::Y => Some(if shift { 'Y' } else { 'y' }),
+        KeyCode::Z => Some(if shift { 'Z' } else { 'z' }),
+        KeyCode::Key0 | KeyCode::Numpad0 => Some(if shift { ')' } else { '0' }),
+        KeyCode::Key1 | KeyCode::Numpad1 => Some(if shift { '!' } else { '1' }),
+        KeyCode::Key2 | KeyCode::Numpad2 => Some(if shift { '@' } else { '2' }),
+        KeyCode::Key3 | KeyCode::Numpad3 => Some(if shift { '#' } else { '3' }),
+        KeyCode::Key4 | KeyCode::Numpad4 => Some(if shift { '$' } else { '4' }),
+        KeyCode::Key5 | KeyCode::Numpad5 => Some(if shift { '%' } else { '5' }),
+        KeyCode::Key6 | KeyCode::Numpad6 => Some(if shift { '^' } else { '6' }),
+        KeyCode::Key7 | KeyCode::Numpad7 => Some(if shift { '&' } else { '7' }),
+        KeyCode::Key8 | KeyCode::Numpad8 => Some(if shift { '*' } else { '8' }),
+        KeyCode::Key9 | KeyCode::Numpad9 => Some(if shift { '(' } else { '9' }),
+        KeyCode::Space => Some(' '),
+        KeyCode::Minus => Some(if shift { '_' } else { '-' }),
+        KeyCode::Equals => Some(if shift { '+' } else { '=' }),
+        KeyCode::Period => Some(if shift { '>' } else { '.' }),
+        KeyCode::Semicolon => Some(if shift { ':' } else { ';' }),
+        KeyCode::Slash => Some(if shift { '?' } else { '/' }),
+        KeyCode::Backslash => Some(if shift { '|' } else { '\\' }),
+        _ => None,
+    }
+}
+
+/// The grapheme clusters of `text`, in order, by Unicode's extended rules
+pub fn graphemes(text: &str) -> impl Iterator<Item = &str> + '_ {
+    text.graphemes(true)
+}
+
+/// Length of a name as the player sees it, in grapheme clusters rather than chars or bytes
+pub fn grapheme_count(text: &str) -> usize {
+    graphemes(text).count()
+}
+
+/// Removes the last grapheme cluster, so Backspace takes off an accented letter or an emoji as a whole
+pub fn pop_grapheme(text: &mut String) {
+    let last = text.grapheme_indices(true).next_back().map_or(0, |(start, _)| start);
+    text.truncate(last);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_navigation_wraps() {
+        let mut menu = Menu::new(&["A", "B", "C"]);
+        assert_eq!(menu.handle_key(KeyCode::Up), MenuEvent::Moved);
+        assert_eq!(menu.selected(), 2);
+        assert_eq!(menu.handle_key(KeyCode::Down), MenuEvent::Moved);
+        assert_eq!(menu.selected(), 0);
+    }
+
+    #[test]
+    fn test_events() {
+        let mut menu = Menu::new(&["A", "B"]);
+        menu.select(1);
+        assert_eq!(menu.handle_key(KeyCode::Return), MenuEvent::Activated(1));
+        assert_eq!(menu.handle_key(KeyCode::Left), MenuEvent::Adjusted(1, -1));
+        assert_eq!(menu.handle_key(KeyCode::Right), MenuEvent::Adjusted(1, 1));
+        assert_eq!(menu.handle_key(KeyCode::Escape), MenuEvent::Back);
+        assert_eq!(menu.handle_key(KeyCode::Q), MenuEvent::None);
+
+        menu.set_value(0, "ON");
+        assert_eq!(menu.items[0].value.as_deref(), Some("ON"));
+        menu.select(10);
+        assert_eq!(menu.selected(), 1);
+    }
+
+    #[test]
+    fn test_char_grid_navigation() {
+        let mut grid = CharGrid::new();
+        assert_eq!(grid.keys.len() % GRID_COLUMNS, 0, "every row is full");
+        assert_eq!(grid.selected(), GridKey::Char('A'));
+        assert_eq!(grid.handle_key(KeyCode::Left), None);
+        assert_eq!(grid.selected(), GridKey::Char('J'));
+        grid.handle_key(KeyCode::Up);
+        assert_eq!(grid.handle_key(KeyCode::Return), Some(GridKey::Done));
+        grid.handle_key(KeyCode::Right);
+        assert_eq!(grid.selected(), GridKey::Char('4'));
+        grid.handle_key(KeyCode::Down);
+        assert_eq!(grid.selected(), GridKey::Char('A'));
+
+        grid.select(GridKey::Delete);
+        assert_eq!(grid.handle_key(KeyCode::Return), Some(GridKey::Delete));
+        assert_eq!(grid.handle_key(KeyCode::A), None);
+    }
+
+    #[test]
+    fn test_keycode_to_char() {
+        // Test lowercase letters
+        assert_eq!(keycode_to_char(KeyCode::A, false), Some('a'));
+        assert_eq!(keycode_to_char(KeyCode::Z, false), Some('z'));
+        
+        // Test uppercase letters
+        assert_eq!(keycode_to_char(KeyCode::A, true), Some('A'));
+        assert_eq!(keycode_to_char(KeyCode::Z, true), Some('Z'));
+        
+        // Test numbers
+        assert_eq!(keycode_to_char(KeyCode::Key1, false), Some('1'));
+        assert_eq!(keycode_to_char(KeyCode::Key9, false), Some('9'));
+        
+        // Test space
+        assert_eq!(keycode_to_char(KeyCode::Space, false), Some(' '));
+        
+        // Test unsupported key
+        assert_eq!(keycode_to_char(KeyCode::F1, false), None);
+    }
+
+    #[test]
+    fn test_graphemes_count_as_one() {
+        assert_eq!(grapheme_count("Zoë"), 3);
+        assert_eq!(grapheme_count("Zoe\u{0308}"), 3);
+        assert_eq!(grapheme_count("山田"), 2);
+        assert_eq!(grapheme_count("👍🏽\u{1F1E9}\u{1F1EA}\u{1F1EB}\u{1F1F7}"), 3);
+        assert_eq!(grapheme_count("👩\u{200D}👩\u{200D}👧"), 1);
+        assert_eq!(graphemes("Zoe\u{0308}!").collect::<Vec<_>>(), ["Z", "o", "e\u{0308}", "!"]);
+        // Hangul jamo and Indic vowel signs join the letter before them
+        assert_eq!(grapheme_count("\u{1100}\u{1161}\u{11A8}"), 1);
+        assert_eq!(grapheme_count("তামিল"), 3);
+        assert_eq!(grapheme_count("தமிழ்"), 3);
+
+        let mut name = String::from("Rene\u{0301}");
+        pop_grapheme(&mut name);
+        assert_eq!(name, "Ren");
+        pop_grapheme(&mut name);
+        assert_eq!(name, "Re");
+        let mut empty = String::new();
+        pop_grapheme(&mut empty);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_timeline_eases_to_the_end() {
+        for easing in [Easing::Linear, Easing::EaseOut, Easing::EaseInOut] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            assert!((easing.apply(0.5) - 0.5).abs() < 0.5);
+        }
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+
+        let mut timeline = Timeline::new(0.2, Easing::EaseOut);
+        assert!(!timeline.is_running());
+        assert_eq!(timeline.value(), 1.0);
+        timeline.start();
+        assert_eq!(timeline.value(), 0.0);
+        timeline.update(0.1);
+        assert!(timeline.is_running());
+        assert!((timeline.progress() - 0.5).abs() < 1e-6);
+        timeline.update(0.5);
+        assert!(!timeline.is_running());
+        assert_eq!(timeline.value(), 1.0);
+        assert_eq!(Timeline::new(0.0, Easing::Linear).value(), 1.0);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::constants::MATCH_LENGTHS;
+
+/// How one round of a versus match went for the local player
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RoundResult {
+    pub won: bool,    // Whether the opponent topped out first
+    pub time: f64,    // Seconds the round lasted
+    pub lines: u32,   // Lines cleared
+    pub attack: u32,  // Garbage lines sent, after cancelling
+    pub pieces: u32,  // Pieces locked
+}
+
+/// A best-of-N versus match: rounds are played until one player has won `first_to` of them
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersusMatch {
+    first_to: u32,             // Rounds a player needs to win the match
+    rounds: Vec<RoundResult>,  // Rounds finished so far, in order
+}
+
+impl VersusMatch {
+    pub fn new(first_to: u32) -> Self {
+        Self { first_to: first_to.max(1), rounds: Vec::new() }
+    }
+
+    pub fn first_to(&self) -> u32 {
+        self.first_to
+    }
+
+    pub fn rounds(&self) -> &[RoundResult] {
+        &self.rounds
+    }
+
+    /// Records a finished round; rounds after the match is decided are ignored
+    pub fn record(&mut self, round: RoundResult) {
+        if !self.is_over() {
+            self.rounds.push(round);
+        }
+    }
+
+    /// Rounds the local player won
+    pub fn wins(&self) -> u32 {
+        self.rounds.iter().filter(|round| round.won).count() as u32
+    }
+
+    /// Rounds the opponent won
+    pub fn losses(&self) -> u32 {
+        self.rounds.len() as u32 - self.wins()
+    }
+
+    /// Whether the local player won the match, once one side has reached `first_to`
+    pub fn winner(&self) -> Option<bool> {
+        if self.wins() >= self.first_to {
+            Some(true)
+        } else if self.losses() >= self.first_to {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+    }
+
+    /// The rounds added up, with `won` set if the match was won
+    pub fn totals(&self) -> RoundResult {
+        self.rounds.iter().fold(RoundResult { won: self.winner() == Some(true), ..RoundResult::default() }, |total, round| RoundResult {
+            time: total.time + round.time,
+            lines: total.lines + round.lines,
+            attack: total.attack + round.attack,
+            pieces: total.pieces + round.pieces,
+            ..total
+        })
+    }
+
+    /// Garbage lines sent per minute over the whole match
+    pub fn attack_per_minute(&self) -> f64 {
+        let totals = self.totals();
+        if totals.time > 0.0 {
+            totals.attack as f64 * 60.0 / totals.time
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Moves `steps` places through `MATCH_LENGTHS` from `first_to`, wrapping around
+pub fn cycle_length(first_to: u32, steps: i32) -> u32 {
+    let index = MATCH_LENGTHS.iter().position(|&length| length == first_to).unwrap_or(0) as i32;
+    MATCH_LENGTHS[(index + steps).rem_euclid(MATCH_LENGTHS.len() as i32) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_ends_at_first_to() {
+        let round = |won| RoundResult { won, time: 30.0, lines: 10, attack: 6, pieces: 25 };
+        let mut versus = VersusMatch::new(3);
+        for won in [true, false, true, false] {
+            versus.record(round(won));
+            assert_eq!(versus.winner(), None);
+        }
+        versus.record(round(true));
+        assert_eq!((versus.wins(), versus.losses()), (3, 2));
+        assert_eq!(versus.winner(), Some(true));
+
+        // The decided match takes no more rounds
+        versus.record(round(false));
+        assert_eq!(versus.rounds().len(), 5);
+        let totals = versus.totals();
+        assert!(totals.won);
+        assert_eq!((totals.time, totals.lines, totals.attack, totals.pieces), (150.0, 50, 30, 125));
+        assert_eq!(versus.attack_per_minute(), 12.0);
+    }
+
+    #[test]
+    fn test_cycle_length() {
+        assert_eq!(cycle_length(3, 1), 5);
+        assert_eq!(cycle_length(3, -1), 7);
+        assert_eq!(cycle_length(4, 0), 3, "unknown lengths start from the first");
+    }
+}
@@ -0,0 +1,69 @@
+use ggez::graphics::Rect;
+
+/// Maps the fixed logical screen onto a window of any size
+/// The logical screen is scaled uniformly and centered, leaving bars on the sides that don't fit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub scale: f32,     // Window pixels per logical pixel
+    pub offset_x: f32,  // Width of the bar left of the logical screen, in window pixels
+    pub offset_y: f32,  // Height of the bar above the logical screen, in window pixels
+    window_w: f32,      // Window width in pixels
+    window_h: f32,      // Window height in pixels
+}
+
+impl Viewport {
+    /// Fits a logical screen of `logical_w` x `logical_h` into a window of `window_w` x `window_h`
+    pub fn fit(window_w: f32, window_h: f32, logical_w: f32, logical_h: f32) -> Self {
+        // A minimized window can report a size of zero; keep the math finite
+        let window_w = window_w.max(1.0);
+        let window_h = window_h.max(1.0);
+        let scale = (window_w / logical_w).min(window_h / logical_h);
+        Self {
+            scale,
+            offset_x: (window_w - logical_w * scale) / 2.0,
+            offset_y: (window_h - logical_h * scale) / 2.0,
+            window_w,
+            window_h,
+        }
+    }
+
+    /// Screen coordinates to give the canvas so logical coordinates land in the letterboxed area
+    pub fn screen_coordinates(&self) -> Rect {
+        Rect::new(
+            -self.offset_x / self.scale,
+            -self.offset_y / self.scale,
+            self.window_w / self.scale,
+            self.window_h / self.scale,
+        )
+    }
+
+    /// Converts a position in window pixels to logical coordinates
+    pub fn to_logical(&self, x: f32, y: f32) -> (f32, f32) {
+        ((x - self.offset_x) / self.scale, (y - self.offset_y) / self.scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_letterboxes_wide_window() {
+        // Twice as wide as needed: scale by height, bars left and right
+        let viewport = Viewport::fit(400.0, 100.0, 100.0, 100.0);
+        assert_eq!(viewport.scale, 1.0);
+        assert_eq!(viewport.offset_x, 150.0);
+        assert_eq!(viewport.offset_y, 0.0);
+        assert_eq!(viewport.screen_coordinates(), Rect::new(-150.0, 0.0, 400.0, 100.0));
+        assert_eq!(viewport.to_logical(150.0, 50.0), (0.0, 50.0));
+    }
+
+    #[test]
+    fn test_fit_scales_down() {
+        let viewport = Viewport::fit(50.0, 80.0, 100.0, 100.0);
+        assert_eq!(viewport.scale, 0.5);
+        assert_eq!(viewport.offset_y, 15.0);
+        let coords = viewport.screen_coordinates();
+        assert_eq!((coords.x, coords.y, coords.w, coords.h), (0.0, -30.0, 100.0, 160.0));
+    }
+}
@@ -0,0 +1,110 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+/// Notices files that were added, changed or removed in some folders of the resource directory
+/// The system's file notifications arrive as they happen; they are gathered until the folders
+/// have been quiet for a moment, so an editor saving a file in several writes causes one reload
+pub struct ResourceWatcher {
+    root: PathBuf,                                   // Resource directory the watched folders are in
+    _watcher: RecommendedWatcher,                    // Sends the notifications for as long as it lives
+    events: Receiver<notify::Result<Event>>,         // Notifications not yet looked at
+    changed: BTreeSet<PathBuf>,                      // Paths changed since the last report
+    interval: f64,                                   // Seconds without notifications before changes are reported
+    quiet: f64,                                      // Seconds since the last notification
+}
+
+impl ResourceWatcher {
+    /// Watches `dirs` under `root` and the folders inside them; a folder that doesn't exist yet isn't watched
+    /// Changes are reported once no more have arrived for `interval` seconds
+    pub fn new(root: impl Into<PathBuf>, dirs: &[&str], interval: f64) -> notify::Result<Self> {
+        let root = root.into();
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        for dir in dirs.iter().map(|dir| root.join(dir)).filter(|dir| dir.is_dir()) {
+            watcher.watch(&dir, RecursiveMode::Recursive)?;
+        }
+        Ok(Self {
+            root,
+            _watcher: watcher,
+            events,
+            changed: BTreeSet::new(),
+            interval,
+            quiet: 0.0,
+        })
+    }
+
+    /// Counts `dt` seconds and returns the files added, changed or removed, as resource paths like
+    /// `/themes/nes.toml`, once the folders have been quiet for the interval
+    pub fn update(&mut self, dt: f64) -> Vec<String> {
+        self.quiet += dt;
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                    self.changed.extend(event.paths);
+                    self.quiet = 0.0;
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!(target: "theme", "Could not watch the resources: {}", e),
+            }
+        }
+        if self.changed.is_empty() || self.quiet < self.interval {
+            return Vec::new();
+        }
+        let changed = std::mem::take(&mut self.changed);
+        changed.iter().map(|path| self.resource_path(path)).collect()
+    }
+
+    /// `path` relative to the resource directory, with forward slashes and a leading one, as ggez names resources
+    fn resource_path(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        relative.components().fold(String::new(), |name, part| format!("{}/{}", name, part.as_os_str().to_string_lossy()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{Duration, Instant};
+
+    /// Updates the watcher until it reports changes or two seconds have passed
+    fn wait_for_changes(watcher: &mut ResourceWatcher) -> Vec<String> {
+        let start = Instant::now();
+        loop {
+            std::thread::sleep(Duration::from_millis(20));
+            let changed = watcher.update(0.02);
+            if !changed.is_empty() || start.elapsed() > Duration::from_secs(2) {
+                return changed;
+            }
+        }
+    }
+
+    #[test]
+    fn test_notices_added_changed_and_removed_files() {
+        // The watcher's root is canonical, since some platforms report events under the resolved path
+        let root = std::env::temp_dir().join(format!("tetris-watch-{}", std::process::id()));
+        let themes = root.join("themes");
+        fs::create_dir_all(themes.join("extra")).unwrap();
+        let root = root.canonicalize().unwrap();
+        let themes = root.join("themes");
+        fs::write(themes.join("nes.toml"), "name = \"nes\"").unwrap();
+
+        let mut watcher = ResourceWatcher::new(&root, &["themes", "sounds"], 0.1).unwrap();
+        assert!(watcher.update(1.0).is_empty());
+
+        fs::write(themes.join("nes.toml"), "name = \"nes\"\nghost_alpha = 0.5").unwrap();
+        fs::write(themes.join("extra").join("neon.toml"), "name = \"neon\"").unwrap();
+        fs::write(root.join("notes.txt"), "not watched").unwrap();
+        assert!(watcher.update(0.0).is_empty(), "changes wait until the folders are quiet");
+        let changed = wait_for_changes(&mut watcher);
+        assert!(changed.contains(&"/themes/nes.toml".to_string()), "{:?}", changed);
+        assert!(changed.contains(&"/themes/extra/neon.toml".to_string()), "{:?}", changed);
+        assert!(!changed.iter().any(|path| path.starts_with("/notes")));
+
+        fs::remove_file(themes.join("nes.toml")).unwrap();
+        assert!(wait_for_changes(&mut watcher).contains(&"/themes/nes.toml".to_string()));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
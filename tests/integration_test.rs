@@ -1,32 +1,49 @@
 use ggez::graphics::Color;
 use ggez::input::keyboard::KeyCode;
-use tetris::{GameState, Tetromino, TetrominoType, keycode_to_char, HighScores, GameScreen};
-
-// Import constants from the tests_reexport module
-const GRID_WIDTH: i32 = 10;
-const GRID_HEIGHT: i32 = 20;
-const MAX_HIGH_SCORES: usize = 10;
-const GRID_SIZE: f32 = 20.0;
-const MARGIN: f32 = 20.0;
-const SCREEN_WIDTH: f32 = 800.0;
-const SCREEN_HEIGHT: f32 = 600.0;
-const PREVIEW_X: f32 = GRID_SIZE * (GRID_WIDTH as f32 + 3.0) + MARGIN;
-const PREVIEW_Y: f32 = GRID_SIZE * 2.0 + MARGIN;
-const PREVIEW_BOX_SIZE: f32 = 4.0;
-const BLINK_INTERVAL: f64 = 0.5;
-const CURSOR_BLINK_INTERVAL: f64 = 0.5;
+use tetris::board::{GameBoard, Playfield};
+use tetris::constants::{
+    DROP_TIME, GRID_HEIGHT, GRID_WIDTH, MAX_HIGH_SCORES, SCORE_COMBO, SCORE_DOUBLE, SCORE_HARD_DROP, SCORE_SINGLE,
+    SCORE_TETRIS, SCORE_TRIPLE,
+};
+use tetris::game::{Game, Move, StepResult};
+use tetris::high_scores::HighScores;
+use tetris::ui::keycode_to_char;
+use tetris::{Tetromino, TetrominoType};
+
+/// Fills the bottom `lines` rows except the first column and drops a vertical I piece into the gap
+/// Returns what the drop did and the points it scored for the cleared lines alone
+fn clear_with_i_piece(game: &mut Game, lines: usize) -> (StepResult, u32) {
+    for y in GRID_HEIGHT as usize - lines..GRID_HEIGHT as usize {
+        for x in 1..GRID_WIDTH as usize {
+            game.board_mut()[y][x] = Color::RED;
+        }
+    }
+    let mut piece = Tetromino::new(TetrominoType::I);
+    piece.rotate();
+    piece.position.x = 0.0;
+    piece.position.y = 0.0;
+    game.set_current_piece(piece);
+
+    let level = game.level();
+    let score = game.score();
+    let combo_points = SCORE_COMBO * game.combo() * level;
+    let result = game.apply(Move::HardDrop);
+    let line_points = game.score() - score - result.dropped * SCORE_HARD_DROP - combo_points;
+    (result, line_points)
+}
 
 #[test]
 fn test_game_state_properties() {
-    // Create a test game state
-    let game_state = GameState::new_test();
-    
+    // Create a test game
+    let game = Game::new();
+
     // Basic checks for initial game state
-    assert_eq!(game_state.score, 0);
-    assert_eq!(game_state.level, 1);
-    assert_eq!(game_state.lines_cleared, 0);
-    assert!(game_state.current_piece.is_some());
-    assert!(!game_state.paused);
+    assert_eq!(game.score(), 0);
+    assert_eq!(game.level(), 1);
+    assert_eq!(game.lines_cleared(), 0);
+    assert!(game.current_piece().is_some());
+    assert!(game.hold_piece().is_none());
+    assert!(!game.is_game_over());
 }
 
 #[test]
@@ -47,117 +64,61 @@ fn test_tetromino_properties() {
 
 #[test]
 fn test_collision_detection() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
-    // Create a test piece
+    let mut board = GameBoard::new();
+
+    // Create a test piece; the I piece starts horizontal, one row of four columns
     let mut test_piece = Tetromino::new(TetrominoType::I);
-    
-    // Debug the initial piece shape and position
-    println!("I piece dimensions: {}x{}", test_piece.shape.len(), test_piece.shape[0].len());
-    println!("I piece position: ({}, {})", test_piece.position.x, test_piece.position.y);
-    
-    // Looking at the debug output, I piece is 1x4 (one row, four columns)
-    // Let's fix our collision tests accordingly
-    
-    // For bottom boundary, move to the last row
-    test_piece.position.y = GRID_HEIGHT as f32; // This should be detected as collision
-    println!("Testing bottom collision at position: ({}, {})", test_piece.position.x, test_piece.position.y);
-    let collision = game_state.check_collision(&test_piece);
-    println!("Bottom collision detected: {}", collision);
-    assert!(collision, "Should collide with bottom boundary");
-    
+
+    // Below the last row is outside the board
+    test_piece.position.y = GRID_HEIGHT as f32;
+    assert!(board.collides(&test_piece), "Should collide with bottom boundary");
+
     // Test collision with left boundary
     test_piece.position.y = 5.0;
     test_piece.position.x = -1.0;
-    println!("Testing left collision at position: ({}, {})", test_piece.position.x, test_piece.position.y);
-    assert!(game_state.check_collision(&test_piece), "Should collide with left boundary");
-    
-    // Test collision with right boundary
-    // For I piece (width 4), it will be fully OOB at position 10
-    test_piece.position.x = 10.0 - 3.0; // Position where one cell is out of bounds
-    println!("Testing right collision at position: ({}, {})", test_piece.position.x, test_piece.position.y);
-    assert!(game_state.check_collision(&test_piece), "Should collide with right boundary");
-    
+    assert!(board.collides(&test_piece), "Should collide with left boundary");
+
+    // Test collision with right boundary; at x = 7 the last cell is off the board
+    test_piece.position.x = GRID_WIDTH as f32 - 3.0;
+    assert!(board.collides(&test_piece), "Should collide with right boundary");
+
     // Test no collision in valid position
     test_piece.position.x = 3.0;
     test_piece.position.y = 5.0;
-    assert!(!game_state.check_collision(&test_piece), "Should not collide in valid position");
-    
-    // Test collision with block on the board
-    game_state.board[10][3] = Color::RED; // Place a block on the board
-    test_piece.position.y = 10.0; // Position directly over the block
-    test_piece.position.x = 1.0;  // Position so one cell overlaps with the block at (3,10)
-    println!("Testing block collision with piece at ({}, {}) and block at (3, 10)", 
-             test_piece.position.x, test_piece.position.y);
-    println!("This should place cell 2 of the I piece over the block");
-    assert!(game_state.check_collision(&test_piece), "Should collide with block on board");
+    assert!(!board.collides(&test_piece), "Should not collide in valid position");
+
+    // Test collision with block on the board; the piece's third cell covers (3, 10)
+    board[10][3] = Color::RED;
+    test_piece.position.y = 10.0;
+    test_piece.position.x = 1.0;
+    assert!(board.collides(&test_piece), "Should collide with block on board");
 }
 
 #[test]
 fn test_line_clearing() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
-    // Create a complete line at the bottom
-    let bottom_row = GRID_HEIGHT as usize - 1; // Index 19 for a 20-height grid
-    println!("Creating complete line at row {}", bottom_row);
+    let mut board = GameBoard::new();
+
+    // Create a complete line at the bottom and a partial line above it
+    let bottom_row = GRID_HEIGHT as usize - 1;
+    let above_row = bottom_row - 1;
     for x in 0..GRID_WIDTH as usize {
-        game_state.board[bottom_row][x] = Color::RED;
+        board[bottom_row][x] = Color::RED;
     }
-    
-    // Create a partial line above it
-    let above_row = bottom_row - 1; // Index 18
-    println!("Creating partial line at row {}", above_row);
     for x in 0..8 {
-        game_state.board[above_row][x] = Color::GREEN;
-    }
-    
-    // Initial score
-    let initial_score = game_state.score;
-    
-    // Clear lines using the test version that doesn't need a context
-    let lines_cleared = game_state.clear_lines_test();
-    println!("Lines cleared: {}", lines_cleared);
-    
-    // Should have cleared 1 line
-    assert_eq!(lines_cleared, 1);
-    
-    // Score should have increased
-    assert!(game_state.score > initial_score);
-    
-    // Debug the bottom row after clearing
-    println!("Bottom row contents after clearing:");
-    for x in 0..GRID_WIDTH as usize {
-        println!("Cell {}: {:?}", x, game_state.board[bottom_row][x]);
+        board[above_row][x] = Color::GREEN;
     }
-    
-    // Based on the implementation of clear_lines_test, the GREEN cells should have moved down
-    // So we should expect the bottom row to contain GREEN cells (from the partial line above)
+
+    assert_eq!(board.full_rows(), vec![bottom_row]);
+    assert_eq!(board.clear_lines(), 1);
+
+    // The partial line moves down into the cleared row
     for x in 0..8 {
-        assert_eq!(game_state.board[bottom_row][x], Color::GREEN,
-                  "Cell at position ({}, {}) should be GREEN but was {:?}", 
-                  x, bottom_row, game_state.board[bottom_row][x]);
+        assert_eq!(board[bottom_row][x], Color::GREEN, "Cell ({}, {}) should be GREEN", x, bottom_row);
     }
-    
-    // The remaining cells in the bottom row should be BLACK
     for x in 8..GRID_WIDTH as usize {
-        assert_eq!(game_state.board[bottom_row][x], Color::BLACK,
-                  "Cell at position ({}, {}) should be BLACK but was {:?}",
-                  x, bottom_row, game_state.board[bottom_row][x]);
+        assert_eq!(board[bottom_row][x], Color::BLACK, "Cell ({}, {}) should be BLACK", x, bottom_row);
     }
+    assert!(board[above_row].iter().all(|&cell| cell == Color::BLACK), "Row above should be empty");
 }
 
 #[test]
@@ -183,21 +144,20 @@ fn test_keycode_to_char() {
 
 #[test]
 fn test_drop_speed() {
-    let mut game_state = GameState::new_test();
-    
-    // Test speeds at different levels
-    game_state.level = 1;
-    let speed_level_1 = game_state.drop_speed();
-    
-    game_state.level = 5;
-    let speed_level_5 = game_state.drop_speed();
-    
-    game_state.level = 10;
-    let speed_level_10 = game_state.drop_speed();
-    
+    let mut game = Game::with_seed(1);
+    let speed_level_1 = game.drop_speed();
+
+    // Ten lines make level 2
+    for _ in 0..2 {
+        clear_with_i_piece(&mut game, 4);
+    }
+    clear_with_i_piece(&mut game, 2);
+    assert_eq!(game.level(), 2);
+    let speed_level_2 = game.drop_speed();
+
     // Higher levels should have faster drop speeds (smaller time intervals)
-    assert!(speed_level_1 > speed_level_5, "Level 5 should be faster than level 1");
-    assert!(speed_level_5 > speed_level_10, "Level 10 should be faster than level 5");
+    assert!(speed_level_1 > speed_level_2, "Level 2 should be faster than level 1");
+    assert!(speed_level_1 <= DROP_TIME, "Gravity should never be slower than the base drop time");
 }
 
 // Test tetromino rotation logic
@@ -236,76 +196,49 @@ fn test_tetromino_rotation() {
 // Test scoring system
 #[test]
 fn test_scoring_system() {
-    let mut game_state = GameState::new_test();
-    
-    // Initialize game state
-    game_state.level = 1;
-    game_state.score = 0;
-    
-    // Test scoring for clearing 1 line at level 1
-    game_state.update_score(1);
-    assert_eq!(game_state.score, 40, "Clearing 1 line at level 1 should score 40 points");
-    
-    // Reset and test for 2 lines
-    game_state.score = 0;
-    game_state.update_score(2);
-    assert_eq!(game_state.score, 100, "Clearing 2 lines at level 1 should score 100 points");
-    
-    // Reset and test for 3 lines
-    game_state.score = 0;
-    game_state.update_score(3);
-    assert_eq!(game_state.score, 300, "Clearing 3 lines at level 1 should score 300 points");
-    
-    // Reset and test for 4 lines (Tetris)
-    game_state.score = 0;
-    game_state.update_score(4);
-    assert_eq!(game_state.score, 1200, "Clearing 4 lines at level 1 should score 1200 points");
-    
+    let mut game = Game::with_seed(2);
+
+    // Each clear scores its points times the level, on top of the hard drop points
+    let (result, points) = clear_with_i_piece(&mut game, 1);
+    assert_eq!(result.lines_cleared, 1);
+    assert_eq!(points, SCORE_SINGLE, "Clearing 1 line at level 1 should score {} points", SCORE_SINGLE);
+
+    let (_, points) = clear_with_i_piece(&mut game, 2);
+    assert_eq!(points, SCORE_DOUBLE, "Clearing 2 lines at level 1 should score {} points", SCORE_DOUBLE);
+
+    let (_, points) = clear_with_i_piece(&mut game, 3);
+    assert_eq!(points, SCORE_TRIPLE, "Clearing 3 lines at level 1 should score {} points", SCORE_TRIPLE);
+
+    let (_, points) = clear_with_i_piece(&mut game, 4);
+    assert_eq!(points, SCORE_TETRIS, "Clearing 4 lines at level 2 should score double points");
+    assert_eq!(game.level(), 2);
+
     // Test level multiplier
-    game_state.score = 0;
-    game_state.level = 2;
-    game_state.update_score(1);
-    assert_eq!(game_state.score, 80, "Clearing 1 line at level 2 should score 80 points");
-    
-    // Test level 3 multiplier with Tetris
-    game_state.score = 0;
-    game_state.level = 3;
-    game_state.update_score(4);
-    assert_eq!(game_state.score, 3600, "Clearing 4 lines at level 3 should score 3600 points");
+    let (_, points) = clear_with_i_piece(&mut game, 1);
+    assert_eq!(points, SCORE_SINGLE * 2, "Clearing 1 line at level 2 should score double points");
 }
 
 // Test level progression based on lines cleared
 #[test]
 fn test_level_progression() {
-    let mut game_state = GameState::new_test();
-    
-    // Start at level 1 with 0 lines cleared
-    game_state.level = 1;
-    game_state.lines_cleared = 0;
-    
+    let mut game = Game::with_seed(3);
+
     // Clear 9 lines - should still be level 1
     for _ in 0..9 {
-        // Mock clearing a line
-        game_state.lines_cleared += 1;
+        clear_with_i_piece(&mut game, 1);
     }
-    // Manually update level
-    game_state.level = (game_state.lines_cleared / 10) + 1;
-    
-    assert_eq!(game_state.level, 1, "Should still be level 1 after clearing 9 lines");
-    
+    assert_eq!(game.lines_cleared(), 9);
+    assert_eq!(game.level(), 1, "Should still be level 1 after clearing 9 lines");
+
     // Clear one more line - should advance to level 2
-    game_state.lines_cleared += 1;
-    game_state.level = (game_state.lines_cleared / 10) + 1;
-    
-    assert_eq!(game_state.level, 2, "Should advance to level 2 after clearing 10 lines");
-    
+    clear_with_i_piece(&mut game, 1);
+    assert_eq!(game.level(), 2, "Should advance to level 2 after clearing 10 lines");
+
     // Clear 10 more lines - should advance to level 3
-    for _ in 0..10 {
-        game_state.lines_cleared += 1;
+    for _ in 0..5 {
+        clear_with_i_piece(&mut game, 2);
     }
-    game_state.level = (game_state.lines_cleared / 10) + 1;
-    
-    assert_eq!(game_state.level, 3, "Should advance to level 3 after clearing 20 lines");
+    assert_eq!(game.level(), 3, "Should advance to level 3 after clearing 20 lines");
 }
 
 // Test high score tracking
@@ -360,1246 +293,452 @@ fn test_high_scores() {
     assert!(!added, "Score lower than minimum should not be added");
 }
 
-// Test piece spawning and positioning
+// Test that a full list only accepts scores that beat its lowest entry
 #[test]
-fn test_piece_spawn() {
-    let game_state = GameState::new_test();
-    
-    // Verify we have a current piece
-    assert!(game_state.current_piece.is_some(), "Current piece should exist");
-    
-    // Verify next piece is initialized
-    let next = &game_state.next_piece;
-    assert!(next.shape.len() > 0, "Next piece should have a valid shape");
-    
-    // Check if current piece is positioned at the top center
-    if let Some(piece) = &game_state.current_piece {
-        // For horizontal pieces like I, expect them centered
-        let piece_width = piece.shape[0].len() as f32;
-        let expected_x = (GRID_WIDTH as f32 - piece_width) / 2.0;
-        
-        // Position should be at the top (y = 0) and centered horizontally
-        assert_eq!(piece.position.y, 0.0, "Piece should spawn at the top");
-        assert!(
-            (piece.position.x - expected_x).abs() < 2.0, 
-            "Piece should spawn centered horizontally (expected around {}, got {})", 
-            expected_x, piece.position.x
-        );
+fn test_high_score_qualification() {
+    let mut high_scores = HighScores::new();
+    assert!(high_scores.would_qualify(0), "Any score should qualify for an empty list");
+
+    for i in 1..=MAX_HIGH_SCORES {
+        high_scores.add_score(format!("Player{}", i), i as u32 * 1000);
     }
-}
+    assert!(!high_scores.would_qualify(500), "500 points should not beat the lowest entry");
+    assert!(high_scores.would_qualify(1500), "1500 points should beat the lowest entry");
 
-// Test game screen states
-#[test]
-fn test_game_screen_states() {
-    let mut game_state = GameState::new_test();
-    
-    // Default screen should be Playing for our test version
-    assert_eq!(game_state.screen, GameScreen::Playing, "Default screen should be Playing");
-    
-    // Test transition to GameOver screen
-    game_state.screen = GameScreen::GameOver;
-    assert_eq!(game_state.screen, GameScreen::GameOver, "Screen should be GameOver");
-    
-    // Test transition to EnterName screen
-    game_state.screen = GameScreen::EnterName;
-    assert_eq!(game_state.screen, GameScreen::EnterName, "Screen should be EnterName");
-    
-    // Test transition to HighScores screen
-    game_state.screen = GameScreen::HighScores;
-    assert_eq!(game_state.screen, GameScreen::HighScores, "Screen should be HighScores");
-    
-    // Test transition to Title screen
-    game_state.screen = GameScreen::Title;
-    assert_eq!(game_state.screen, GameScreen::Title, "Screen should be Title");
+    // Entries are kept highest first
+    assert_eq!(high_scores.entries[0].score, MAX_HIGH_SCORES as u32 * 1000);
+    assert_eq!(high_scores.entries.last().unwrap().score, 1000);
 }
 
-// Test pause functionality
+// Test piece spawning and positioning
 #[test]
-fn test_pause_functionality() {
-    let mut game_state = GameState::new_test();
-    
-    // Game should start unpaused
-    assert!(!game_state.paused, "Game should start unpaused");
-    
-    // Test pausing
-    game_state.paused = true;
-    assert!(game_state.paused, "Game should be paused after setting paused=true");
-    
-    // Test unpausing
-    game_state.paused = false;
-    assert!(!game_state.paused, "Game should be unpaused after setting paused=false");
-}
+fn test_piece_spawn() {
+    let game = Game::new();
 
-// Test name input functionality
-#[test]
-fn test_name_input() {
-    let mut game_state = GameState::new_test();
-    
-    // Should start with empty name
-    assert_eq!(game_state.current_name, "", "Name should start empty");
-    
-    // Test adding a character
-    game_state.current_name.push('A');
-    assert_eq!(game_state.current_name, "A", "Name should contain 'A'");
-    
-    // Test adding multiple characters
-    game_state.current_name.push('B');
-    game_state.current_name.push('C');
-    assert_eq!(game_state.current_name, "ABC", "Name should contain 'ABC'");
-    
-    // Test removing a character
-    game_state.current_name.pop();
-    assert_eq!(game_state.current_name, "AB", "Name should be 'AB' after popping");
-    
-    // Test clearing the name
-    game_state.current_name.clear();
-    assert_eq!(game_state.current_name, "", "Name should be empty after clearing");
+    // Verify we have a current piece and a full queue
+    let piece = game.current_piece().expect("Current piece should exist");
+    assert!(!game.next_piece().shape.is_empty(), "Next piece should have a valid shape");
+    assert_eq!(game.next_queue().len(), tetris::constants::NEXT_QUEUE_SIZE);
+
+    // Position should be at the top (y = 0) and centered horizontally, rounding to the left
+    let piece_width = piece.shape[0].len() as i32;
+    let expected_x = ((GRID_WIDTH - piece_width) / 2) as f32;
+    assert_eq!(piece.position.y, 0.0, "Piece should spawn at the top");
+    assert_eq!(
+        piece.position.x, expected_x,
+        "Piece should spawn centered horizontally (expected {}, got {})",
+        expected_x, piece.position.x
+    );
 }
 
-// Test piece movement logic
+// Test piece movement through game inputs
 #[test]
 fn test_piece_movement() {
-    let mut game_state = GameState::new_test();
-    
-    // Ensure there's a current piece to work with
+    let mut game = Game::new();
     let mut current_piece = Tetromino::new(TetrominoType::I);
     current_piece.position.x = 3.0;
     current_piece.position.y = 3.0;
-    game_state.current_piece = Some(current_piece);
-    
-    // Get original position
-    let original_x = game_state.current_piece.as_ref().unwrap().position.x;
-    let original_y = game_state.current_piece.as_ref().unwrap().position.y;
-    
+    game.set_current_piece(current_piece);
+
     // Test moving left
-    if let Some(piece) = &mut game_state.current_piece {
-        piece.position.x -= 1.0; // Move left
-    }
-    
-    assert_eq!(
-        game_state.current_piece.as_ref().unwrap().position.x,
-        original_x - 1.0,
-        "Piece should move left by 1 unit"
-    );
-    
+    assert!(game.apply(Move::Left).moved);
+    assert_eq!(game.current_piece().unwrap().position.x, 2.0, "Piece should move left by 1 unit");
+
     // Test moving right
-    if let Some(piece) = &mut game_state.current_piece {
-        piece.position.x += 2.0; // Move right
-    }
-    
-    assert_eq!(
-        game_state.current_piece.as_ref().unwrap().position.x,
-        original_x + 1.0,
-        "Piece should move right by 2 units from previous position"
-    );
-    
+    game.apply(Move::Right);
+    game.apply(Move::Right);
+    assert_eq!(game.current_piece().unwrap().position.x, 4.0, "Piece should move right by 2 units from previous position");
+
     // Test moving down (soft drop)
-    if let Some(piece) = &mut game_state.current_piece {
-        piece.position.y += 1.0;
+    assert!(game.apply(Move::SoftDrop).moved);
+    assert_eq!(game.current_piece().unwrap().position.y, 4.0, "Piece should move down by 1 unit");
+
+    // Moves into a wall do nothing
+    for _ in 0..GRID_WIDTH {
+        game.apply(Move::Left);
     }
-    
-    assert_eq!(
-        game_state.current_piece.as_ref().unwrap().position.y,
-        original_y + 1.0,
-        "Piece should move down by 1 unit"
-    );
+    assert_eq!(game.current_piece().unwrap().position.x, 0.0);
+    assert!(!game.apply(Move::Left).moved, "Piece should not move through the left wall");
 }
 
 // Test piece landing and locking
 #[test]
 fn test_piece_landing() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
-    // Create a test piece at the bottom of the board
+    let mut game = Game::new();
     let mut test_piece = Tetromino::new(TetrominoType::I);
     test_piece.position.x = 3.0;
-    // For I piece, position at the bottom (this is special for I piece)
-    test_piece.position.y = GRID_HEIGHT as f32; // Position at bottom boundary
-    game_state.current_piece = Some(test_piece);
-    
-    // Verify collision is detected
-    if let Some(ref piece) = game_state.current_piece {
-        assert!(game_state.check_collision(piece), "Piece should collide with bottom boundary");
-    } else {
-        panic!("Current piece should exist");
-    }
-    
-    // Manual implementation of piece locking logic
-    let piece_color = game_state.current_piece.as_ref().unwrap().color;
-    let piece = game_state.current_piece.as_ref().unwrap();
-    let piece_x = piece.position.x.round() as i32;
-    let piece_y = piece.position.y.round() as i32 - 1; // Adjust to place piece just above bottom
-    
-    // Lock piece onto the board
-    for y in 0..piece.shape.len() {
-        for x in 0..piece.shape[y].len() {
-            if piece.shape[y][x] {
-                let board_x = piece_x + x as i32;
-                let board_y = piece_y + y as i32;
-                
-                // Only place on board if within bounds
-                if board_x >= 0 && board_x < GRID_WIDTH && board_y >= 0 && board_y < GRID_HEIGHT {
-                    game_state.board[board_y as usize][board_x as usize] = piece_color;
-                }
-            }
-        }
+    test_piece.position.y = GRID_HEIGHT as f32 - 1.0;
+    let piece_color = test_piece.color;
+    game.set_current_piece(test_piece);
+
+    // The piece rests on the floor, so gravity locks it
+    let locked = game.gravity().locked.expect("Piece should lock on the floor");
+    assert_eq!(locked.cells, vec![(3, 19), (4, 19), (5, 19), (6, 19)]);
+    for x in 3..7 {
+        assert_eq!(game.board()[19][x], piece_color, "Board cell ({},19) should have piece color", x);
     }
-    
-    // Verify piece was placed on board
-    // I piece at bottom should fill cells at x=3,4,5,6 in row 19
-    assert_eq!(game_state.board[19][3], piece_color, "Board cell (3,19) should have piece color");
-    assert_eq!(game_state.board[19][4], piece_color, "Board cell (4,19) should have piece color");
-    assert_eq!(game_state.board[19][5], piece_color, "Board cell (5,19) should have piece color");
-    assert_eq!(game_state.board[19][6], piece_color, "Board cell (6,19) should have piece color");
+    assert_eq!(game.pieces(), 2, "The next piece should spawn after the lock");
 }
 
 // Test scoring for Tetris (4 lines clear)
 #[test]
 fn test_tetris_scoring() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
-    // Fill 4 rows completely
-    for y in 16..20 {
-        for x in 0..GRID_WIDTH as usize {
-            game_state.board[y][x] = Color::RED;
-        }
-    }
-    
-    // Set level and record initial score
-    game_state.level = 1;
-    game_state.score = 0;
-    
-    // Manually handle score for Tetris (no need to count lines here)
-    
-    // Update score directly
-    game_state.update_score(4); // Update score for 4 lines at once
-    
-    // Should match the score for a Tetris
-    assert_eq!(game_state.score, 1200, "Should score 1200 points for a Tetris at level 1");
-    
-    // Now manually call clear_lines_test to verify our implementation
-    let cleared = game_state.clear_lines_test();
-    
-    // Should have cleared at most 2 lines in one call due to implementation
-    assert!(cleared <= 4, "Should have cleared at most 4 lines");
-    assert!(cleared >= 1, "Should have cleared at least 1 line");
-    
-    // Verify board state after clearing (lines should be moved down)
-    // Not testing for empty rows here since our clear_lines_test implementation 
-    // might not clear all 4 lines in one call
+    let mut game = Game::with_seed(4);
+    let (result, points) = clear_with_i_piece(&mut game, 4);
+
+    assert_eq!(points, SCORE_TETRIS, "Should score {} points for a Tetris at level 1", SCORE_TETRIS);
+    assert_eq!(result.lines_cleared, 4, "Should have cleared all 4 lines at once");
+    assert_eq!(result.locked.unwrap().full_rows, vec![16, 17, 18, 19]);
+    assert_eq!(game.board(), &GameBoard::new(), "The board should be empty after the Tetris");
 }
 
 // Test wall kick for I-piece
 #[test]
 fn test_i_piece_rotation_at_edge() {
-    // Create an I piece at the left edge
+    let board = GameBoard::new();
+
+    // In initial orientation, I piece is horizontal (1×4); place it against the left wall
     let mut i_piece = Tetromino::new(TetrominoType::I);
-    
-    // In initial orientation, I piece is horizontal (1×4)
     assert_eq!(i_piece.shape.len(), 1, "I piece should start as 1×4");
-    assert_eq!(i_piece.shape[0].len(), 4, "I piece should start as 1×4");
-    
-    // Position at the left edge
     i_piece.position.x = 0.0;
     i_piece.position.y = 5.0;
-    
-    // Create game state
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
-    game_state.current_piece = Some(i_piece);
-    
-    // Test manual wall kick using our own implementation rather than game's
-    // 1. Get the current piece
-    let mut piece = game_state.current_piece.take().unwrap();
-    
-    // 2. Rotate piece (no need to store original values for this test)
-    piece.rotate();
-    
-    // 3. Apply wall kick manually
-    piece.position.x = 2.0; // Move 2 units right
-    
-    // 4. Update game state
-    game_state.current_piece = Some(piece);
-    
-    // After wall kick, I piece should be rotated (now 4×1) and shifted right
-    if let Some(ref piece) = game_state.current_piece {
-        assert_eq!(piece.shape.len(), 4, "I piece should be 4×1 after rotation");
-        assert_eq!(piece.shape[0].len(), 1, "I piece should be 4×1 after rotation");
-        assert_eq!(piece.position.x, 2.0, "I piece should be shifted to x=2.0 after manual wall kick");
-    } else {
-        panic!("Current piece should exist");
-    }
+
+    let rotated = board.try_rotate(&i_piece).expect("I piece should rotate at the wall");
+    assert_eq!(rotated.shape.len(), 4, "I piece should be 4×1 after rotation");
+    assert_eq!(rotated.shape[0].len(), 1, "I piece should be 4×1 after rotation");
+    assert!(!board.collides(&rotated), "Rotated piece should stay inside the board");
 }
 
 // Test level progression after multiple line clears
 #[test]
 fn test_complex_level_progression() {
-    let mut game_state = GameState::new_test();
-    
-    // Set initial values
-    game_state.level = 1;
-    game_state.lines_cleared = 0;
-    game_state.score = 0;
-    
-    // First clear: Single line
-    game_state.lines_cleared += 1;
-    game_state.update_score(1);
-    game_state.level = (game_state.lines_cleared / 10) + 1;
-    assert_eq!(game_state.level, 1, "Should still be level 1");
-    assert_eq!(game_state.score, 40, "Score should be 40");
-    
-    // Second clear: Double line
-    game_state.lines_cleared += 2;
-    game_state.update_score(2);
-    game_state.level = (game_state.lines_cleared / 10) + 1;
-    assert_eq!(game_state.level, 1, "Should still be level 1");
-    assert_eq!(game_state.score, 140, "Score should be 40 + 100 = 140");
-    
-    // Third clear: Triple line
-    game_state.lines_cleared += 3;
-    game_state.update_score(3);
-    game_state.level = (game_state.lines_cleared / 10) + 1;
-    assert_eq!(game_state.level, 1, "Should still be level 1");
-    assert_eq!(game_state.score, 440, "Score should be 140 + 300 = 440");
-    
-    // Fourth clear: Tetris
-    game_state.lines_cleared += 4;
-    game_state.update_score(4);
-    // Total lines: 10, should level up
-    game_state.level = (game_state.lines_cleared / 10) + 1;
-    assert_eq!(game_state.level, 2, "Should advance to level 2");
-    assert_eq!(game_state.score, 1640, "Score should be 440 + 1200 = 1640");
-    
-    // Check drop speed increases
-    let speed_level_1 = 1.0; // Base drop interval
-    let speed_level_2 = 1.0 / (1.0 + 0.1); // Level 2 drop interval with factor 0.1
-    assert!(speed_level_2 < speed_level_1, "Level 2 should drop faster than level 1");
+    let mut game = Game::with_seed(5);
+
+    // Single, double, triple and tetris make ten lines
+    let expected = [SCORE_SINGLE, SCORE_DOUBLE, SCORE_TRIPLE, SCORE_TETRIS];
+    for (lines, &points) in (1..=4).zip(&expected) {
+        let (_, scored) = clear_with_i_piece(&mut game, lines);
+        assert_eq!(scored, points, "Clearing {} lines at level 1 should score {}", lines, points);
+    }
+    assert_eq!(game.lines_cleared(), 10);
+    assert_eq!(game.level(), 2, "Should advance to level 2");
+
+    // The speed follows the level
+    assert!(game.drop_speed() < Game::new().drop_speed(), "Level 2 should drop faster than level 1");
 }
 
 // Test game over condition (piece collision at spawn)
 #[test]
 fn test_game_over_condition() {
-    let mut game_state = GameState::new_test();
-    
-    // Fill the top rows of the board to cause collision at spawn
+    let mut game = Game::new();
+
+    // Fill the top rows of the board, leaving a gap so they aren't cleared
     for y in 0..4 {
-        for x in 0..GRID_WIDTH as usize {
-            game_state.board[y][x] = Color::RED;
+        for x in 1..GRID_WIDTH as usize {
+            game.board_mut()[y][x] = Color::RED;
         }
     }
-    
-    // Generate a new piece at the top
-    let new_piece = Tetromino::new(TetrominoType::I);
-    
-    // Verify collision at spawn position
-    assert!(game_state.check_collision(&new_piece), "New piece should collide at spawn");
-    
-    // In the actual game, this would trigger game over
-    game_state.screen = GameScreen::GameOver;
-    assert_eq!(game_state.screen, GameScreen::GameOver, "Game state should be GameOver");
-}
 
-// Test high score transition after game over
-#[test]
-fn test_high_score_transition_after_game_over() {
-    let mut game_state = GameState::new_test();
-    
-    // Ensure we have an empty high score list to start with
-    game_state.high_scores = HighScores::new();
-    
-    // Set a score that would qualify for high score
-    game_state.score = 1000;
-    
-    // Simulate game over
-    game_state.screen = GameScreen::GameOver;
-    
-    // Manually call what would happen in spawn_new_piece when game over occurs
-    let qualifies = game_state.check_high_score();
-    assert!(qualifies, "Score should qualify for high score");
-    
-    if qualifies {
-        game_state.screen = GameScreen::EnterName;
-    }
-    
-    // Verify screen has changed to EnterName
-    assert_eq!(game_state.screen, GameScreen::EnterName, "Screen should transition to EnterName for high score");
-    
-    // Test that after submitting a name, it goes to high scores screen
-    game_state.current_name = "TESTER".to_string();
-    let added = game_state.add_high_score();
-    assert!(added, "High score should be added successfully");
-    
-    // Verify score was added to high scores
-    assert_eq!(game_state.high_scores.entries.len(), 1, "Should have 1 high score entry");
-    assert_eq!(game_state.high_scores.entries[0].name, "TESTER", "High score entry should have correct name");
-    assert_eq!(game_state.high_scores.entries[0].score, 1000, "High score entry should have correct score");
+    // The next piece has nowhere to spawn
+    let result = game.apply(Move::HardDrop);
+    assert!(result.game_over, "The lock should end the game");
+    assert!(game.is_game_over());
+    assert_eq!(game.apply(Move::Left), StepResult { game_over: true, ..StepResult::default() });
 }
 
 // Test hard drop mechanics
 #[test]
 fn test_hard_drop() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
-    // Create an obstacle at the bottom of the board
-    for x in 0..GRID_WIDTH as usize {
-        game_state.board[15][x] = Color::RED;
+    let mut game = Game::new();
+
+    // Create an obstacle row with a gap, so it isn't cleared
+    for x in 1..GRID_WIDTH as usize {
+        game.board_mut()[15][x] = Color::RED;
     }
-    
-    // Create a test piece at the top
+
     let mut test_piece = Tetromino::new(TetrominoType::I);
     test_piece.position.x = 3.0;
     test_piece.position.y = 0.0;
-    game_state.current_piece = Some(test_piece);
-    
-    // Get original position
-    let original_y = game_state.current_piece.as_ref().unwrap().position.y;
-    
-    // Perform hard drop logic (manual implementation)
-    let mut final_y = original_y;
-    let piece = game_state.current_piece.as_ref().unwrap().clone();
-    
-    // Move down until collision
-    let mut test_piece = piece.clone();
-    let mut cells_dropped = 0;
-    loop {
-        test_piece.position.y += 1.0;
-        if game_state.check_collision(&test_piece) {
-            break;
-        }
-        final_y += 1.0;
-        cells_dropped += 1;
-    }
-    
-    // Update position
-    if let Some(piece) = &mut game_state.current_piece {
-        piece.position.y = final_y;
-    }
-    
-    // Verify piece was moved to just above the obstacle
-    assert_eq!(
-        game_state.current_piece.as_ref().unwrap().position.y,
-        14.0,
-        "Piece should be positioned just above the obstacle at y=15"
-    );
-    
-    // Verify cells_dropped is reasonable
-    assert!(cells_dropped > 0, "Hard drop should move the piece downward");
+    game.set_current_piece(test_piece);
+
+    let result = game.apply(Move::HardDrop);
+    let locked = result.locked.expect("Hard drop should lock the piece");
+    assert_eq!(locked.piece.position.y, 14.0, "Piece should land just above the obstacle at y=15");
+    assert_eq!(result.dropped, 14, "Hard drop should move the piece downward");
+    assert_eq!(game.score(), 14 * SCORE_HARD_DROP, "Hard drop should score points per row");
 }
 
-// Test L and J piece wall kick with left wall
+// Test L piece wall kick with left wall
 #[test]
 fn test_l_piece_wall_kick() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
-    // Create an L piece at the left edge
+    let board = GameBoard::new();
     let mut l_piece = Tetromino::new(TetrominoType::L);
-    l_piece.position.x = 0.0; // At the left edge
+    l_piece.position.x = 0.0;
     l_piece.position.y = 5.0;
-    
-    // Store original shape for comparison
-    let original_shape = l_piece.shape.clone();
-    
-    game_state.current_piece = Some(l_piece);
-    
-    // Manual wall kick implementation (rotate and move if needed)
-    let mut piece = game_state.current_piece.take().unwrap();
-    
-    // Rotate the piece
-    piece.rotate();
-    
-    // Check if rotation causes collision with left wall
-    if game_state.check_collision(&piece) {
-        // Apply wall kick by moving right (simplified to always move 1 unit right)
-        piece.position.x += 1.0;
-    }
-    
-    // Check if still colliding
-    if game_state.check_collision(&piece) {
-        // If still colliding, revert rotation but keep position change
-        // Clone original_shape to avoid moving it
-        piece.shape = original_shape.clone();
-    }
-    
-    // Update game state
-    game_state.current_piece = Some(piece);
-    
-    // Verify piece was rotated and moved 
-    if let Some(ref piece) = game_state.current_piece {
-        assert_ne!(piece.shape, original_shape, "L piece should have different shape after rotation");
-        // In our test implementation, the check_collision might not be triggering as expected
-        // So we're accepting the actual position
-        assert_eq!(piece.position.x, 0.0, "L piece position should remain at x=0.0 as it doesn't collide with the wall in test environment");
-    } else {
-        panic!("Current piece should exist");
-    }
+
+    // The rotated piece fits without a kick; it turns around the middle column of its box
+    let rotated = board.try_rotate(&l_piece).expect("L piece should rotate at the wall");
+    assert_ne!(rotated.shape, l_piece.shape, "L piece should have different shape after rotation");
+    assert_eq!(rotated.position.x, 1.0, "L piece should not need a kick at the left wall");
 }
 
 // Test successive line clears leading to level-up
 #[test]
 fn test_successive_line_clears() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
-    // Set initial game state
-    game_state.level = 1;
-    game_state.lines_cleared = 8; // Just need 2 more lines to level up
-    game_state.score = 0;
-    
-    // Create two complete lines at the bottom
-    for y in 18..20 {
-        for x in 0..GRID_WIDTH as usize {
-            game_state.board[y][x] = Color::RED;
-        }
-    }
-    
-    // Clear the lines - need to clear one at a time due to implementation
-    let first_clear = game_state.clear_lines_test(); // This will clear one line
-    let second_clear = game_state.clear_lines_test(); // This should clear the second line
-    
-    // Should have cleared 2 lines total
-    assert_eq!(first_clear + second_clear, 2, "Should have cleared 2 lines total");
-    
-    // Should level up from 1 to 2
-    assert_eq!(game_state.level, 2, "Should have leveled up to level 2");
-    
-    // Score should reflect clearing 2 lines at level 1, but as separate actions
-    // First clear: 40 points for a single line, then second clear: 40 points again
-    assert_eq!(game_state.score, 80, "Score should be 80 for 2 individual line clears at level 1");
-    
-    // Lines cleared should be updated
-    assert_eq!(game_state.lines_cleared, 10, "Total lines cleared should be 10");
-    
-    // Verify the board state after clearing (bottom two rows should be empty)
-    for y in 18..20 {
-        for x in 0..GRID_WIDTH as usize {
-            assert_eq!(game_state.board[y][x], Color::BLACK, 
-                      "Cell at position ({}, {}) should be BLACK after clearing", x, y);
-        }
+    let mut game = Game::with_seed(6);
+    for _ in 0..4 {
+        clear_with_i_piece(&mut game, 2);
     }
+    assert_eq!(game.lines_cleared(), 8);
+    assert_eq!(game.level(), 1);
+
+    // Two more lines level up
+    let (result, points) = clear_with_i_piece(&mut game, 2);
+    assert_eq!(result.lines_cleared, 2, "Should have cleared 2 lines");
+    assert_eq!(points, SCORE_DOUBLE, "The double is scored at the level it was made on");
+    assert_eq!(game.level(), 2, "Should have leveled up to level 2");
+    assert_eq!(game.lines_cleared(), 10, "Total lines cleared should be 10");
 }
 
-// Test game state after clearing multiple separate lines (not a Tetris)
+// Test clearing lines that aren't next to each other
 #[test]
 fn test_non_consecutive_line_clears() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
+    let mut game = Game::new();
+
     // Create two non-consecutive full lines
     for x in 0..GRID_WIDTH as usize {
-        game_state.board[10][x] = Color::RED;  // Line 10
-        game_state.board[15][x] = Color::BLUE; // Line 15
-    }
-    
-    // Initialize score and level
-    game_state.score = 0;
-    game_state.level = 1;
-    game_state.lines_cleared = 0;
-    
-    // Clear lines - this will need to be called twice since our implementation 
-    // only handles consecutive lines in one pass
-    let first_lines = game_state.clear_lines_test();
-    let second_lines = game_state.clear_lines_test();
-    
-    // Verify total cleared lines
-    let total_lines = first_lines + second_lines;
-    assert_eq!(total_lines, 2, "Should have cleared 2 non-consecutive lines");
-    
-    // Verify score update for 2 single line clears (40 points each at level 1)
-    assert_eq!(game_state.score, 100, "Score should be 100 for clearing 2 lines in this test implementation");
-    
-    // Verify line count
-    assert_eq!(game_state.lines_cleared, 2, "Total lines should be 2");
-    
-    // Verify both lines are now empty
-    for x in 0..GRID_WIDTH as usize {
-        assert_eq!(game_state.board[10][x], Color::BLACK, "Line 10 should be cleared");
-        assert_eq!(game_state.board[15][x], Color::BLACK, "Line 15 should be cleared");
+        game.board_mut()[10][x] = Color::RED;
+        game.board_mut()[15][x] = Color::BLUE;
     }
+
+    // Any lock clears every complete line
+    let result = game.apply(Move::HardDrop);
+    assert_eq!(result.locked.unwrap().full_rows, vec![10, 15]);
+    assert_eq!(result.lines_cleared, 2, "Should have cleared 2 non-consecutive lines");
+    assert_eq!(game.lines_cleared(), 2, "Total lines should be 2");
+    assert!(game.score() >= SCORE_DOUBLE, "Should score a double");
+
+    // Only the locked piece is left
+    let filled: usize = game.board().rows().iter().flatten().filter(|&&cell| cell != Color::BLACK).count();
+    assert_eq!(filled, 4);
 }
 
 // Test key input handling for piece movement and rotation
 #[test]
 fn test_key_input_effects() {
-    let mut game_state = GameState::new_test();
-    
-    // Create a known piece in a known position
+    let mut game = Game::new();
     let mut test_piece = Tetromino::new(TetrominoType::T);
     test_piece.position.x = 5.0;
     test_piece.position.y = 5.0;
-    game_state.current_piece = Some(test_piece);
-    
-    // Store original position and shape
-    let original_x = game_state.current_piece.as_ref().unwrap().position.x;
-    let original_y = game_state.current_piece.as_ref().unwrap().position.y;
-    let original_shape = game_state.current_piece.as_ref().unwrap().shape.clone();
-    
-    // Simulate left key press
-    if let Some(piece) = &mut game_state.current_piece {
-        piece.position.x -= 1.0;
-    }
-    
-    // Verify position changed
-    assert_eq!(
-        game_state.current_piece.as_ref().unwrap().position.x,
-        original_x - 1.0,
-        "Left key should move piece left by 1 unit"
-    );
-    
-    // Simulate right key press
-    if let Some(piece) = &mut game_state.current_piece {
-        piece.position.x += 1.0;
-    }
-    
-    // Verify position changed back
-    assert_eq!(
-        game_state.current_piece.as_ref().unwrap().position.x,
-        original_x,
-        "Right key should move piece right by 1 unit"
-    );
-    
-    // Simulate down key press
-    if let Some(piece) = &mut game_state.current_piece {
-        piece.position.y += 1.0;
-    }
-    
-    // Verify position changed
-    assert_eq!(
-        game_state.current_piece.as_ref().unwrap().position.y,
-        original_y + 1.0,
-        "Down key should move piece down by 1 unit"
-    );
-    
-    // Simulate rotation (up key press)
-    if let Some(piece) = &mut game_state.current_piece {
-        piece.rotate();
-    }
-    
-    // Verify shape changed
-    assert_ne!(
-        game_state.current_piece.as_ref().unwrap().shape,
-        original_shape,
-        "Up key should rotate the piece"
-    );
+    let original_shape = test_piece.shape.clone();
+    game.set_current_piece(test_piece);
+
+    game.apply(Move::Left);
+    assert_eq!(game.current_piece().unwrap().position.x, 4.0, "Left should move piece left by 1 unit");
+    game.apply(Move::Right);
+    assert_eq!(game.current_piece().unwrap().position.x, 5.0, "Right should move piece right by 1 unit");
+    game.apply(Move::SoftDrop);
+    assert_eq!(game.current_piece().unwrap().position.y, 6.0, "Soft drop should move piece down by 1 unit");
+    game.apply(Move::RotateCw);
+    assert_ne!(game.current_piece().unwrap().shape, original_shape, "Rotate should rotate the piece");
 }
 
-// Test T-spin detection (special rotation case)
+// Test a T piece rotating into a slot surrounded by blocks
 #[test]
 fn test_t_spin() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
-    // Create a T-spin setup (3 corners filled, leaving a T-shaped hole)
-    // Fill 3 corners around a position to force a T-spin
-    game_state.board[10][4] = Color::RED;  // Top-left
-    game_state.board[10][6] = Color::RED;  // Top-right
-    game_state.board[12][4] = Color::RED;  // Bottom-left
-    // Leave bottom-right empty
-    
-    // Create a T piece in position for a T-spin
+    let mut board = GameBoard::new();
+
+    // Fill 3 corners around the slot, leaving the bottom-right open
+    board[10][4] = Color::RED;
+    board[10][6] = Color::RED;
+    board[12][4] = Color::RED;
+
     let mut t_piece = Tetromino::new(TetrominoType::T);
     t_piece.position.x = 5.0;
     t_piece.position.y = 11.0;
-    
-    // Rotate T piece to point downward
     t_piece.rotate();
     t_piece.rotate();
-    
-    game_state.current_piece = Some(t_piece);
-    
-    // Verify T-piece can be rotated despite being surrounded by blocks
-    if let Some(ref mut piece) = game_state.current_piece {
-        let original_rotation = piece.shape.clone();
-        piece.rotate();
-        
-        // Check if rotation was successful
-        assert_ne!(piece.shape, original_rotation, "T piece should rotate in T-spin position");
-        
-        // Now check if the position is valid (no collision) using a clone to avoid borrow issues
-        let piece_clone = piece.clone();
-        assert!(!game_state.check_collision(&piece_clone), "T piece should be in valid position after T-spin");
-    } else {
-        panic!("Current piece should exist");
-    }
+    assert!(!board.collides(&t_piece));
+
+    let rotated = board.try_rotate(&t_piece).expect("T piece should rotate in T-spin position");
+    assert_ne!(rotated.shape, t_piece.shape, "T piece should rotate in T-spin position");
+    assert!(!board.collides(&rotated), "T piece should be in valid position after T-spin");
 }
 
 // Test boundary collisions on all four sides
 #[test]
 fn test_boundary_collisions() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
+    let mut board = GameBoard::new();
+
     // Test left boundary collision
     let mut left_piece = Tetromino::new(TetrominoType::I);
-    left_piece.position.x = -1.0;  // Partially off the left edge
+    left_piece.position.x = -1.0;
     left_piece.position.y = 5.0;
-    assert!(game_state.check_collision(&left_piece), "Piece should collide with left boundary");
-    
-    // Test right boundary collision
+    assert!(board.collides(&left_piece), "Piece should collide with left boundary");
+
+    // I piece has width 4, so placing at x=7 makes it partially off the right edge
     let mut right_piece = Tetromino::new(TetrominoType::I);
-    // I piece has width 4, so placing at x=7 will make it partially off the right edge
     right_piece.position.x = GRID_WIDTH as f32 - 3.0;
     right_piece.position.y = 5.0;
-    assert!(game_state.check_collision(&right_piece), "Piece should collide with right boundary");
-    
+    assert!(board.collides(&right_piece), "Piece should collide with right boundary");
+
     // Test bottom boundary collision
     let mut bottom_piece = Tetromino::new(TetrominoType::O);
     bottom_piece.position.x = 4.0;
-    bottom_piece.position.y = GRID_HEIGHT as f32;  // At the bottom
-    assert!(game_state.check_collision(&bottom_piece), "Piece should collide with bottom boundary");
-    
+    bottom_piece.position.y = GRID_HEIGHT as f32;
+    assert!(board.collides(&bottom_piece), "Piece should collide with bottom boundary");
+
+    // Above the top edge is open, so pieces can spawn partially hidden
+    let mut top_piece = Tetromino::new(TetrominoType::O);
+    top_piece.position.y = -1.0;
+    assert!(!board.collides(&top_piece), "Piece should not collide above the board");
+
     // Test collision with existing blocks
-    game_state.board[10][5] = Color::RED; // Place a block
+    board[10][5] = Color::RED;
     let mut colliding_piece = Tetromino::new(TetrominoType::T);
     colliding_piece.position.x = 4.0;
-    colliding_piece.position.y = 9.0;  // Just above the block
-    assert!(game_state.check_collision(&colliding_piece), "Piece should collide with existing block");
-    
+    colliding_piece.position.y = 9.0;
+    assert!(board.collides(&colliding_piece), "Piece should collide with existing block");
+
     // Test no collision with valid position
     let mut valid_piece = Tetromino::new(TetrominoType::I);
     valid_piece.position.x = 3.0;
     valid_piece.position.y = 3.0;
-    assert!(!game_state.check_collision(&valid_piece), "Piece should not collide in valid position");
+    assert!(!board.collides(&valid_piece), "Piece should not collide in valid position");
 }
 
 // Test rotation at right edge
 #[test]
 fn test_rotation_at_right_edge() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
-    // Create an I piece at the right edge
+    let board = GameBoard::new();
+
+    // A vertical I piece next to the right wall has to kick left to turn horizontal
     let mut i_piece = Tetromino::new(TetrominoType::I);
-    i_piece.position.x = GRID_WIDTH as f32 - 4.0; // Right at the edge
+    i_piece.rotate();
+    i_piece.position.x = GRID_WIDTH as f32 - 2.0;
     i_piece.position.y = 5.0;
-    
-    // Store original shape for comparison
-    let original_shape = i_piece.shape.clone();
-    
-    game_state.current_piece = Some(i_piece);
-    
-    // Manual wall kick implementation (rotate and move if needed)
-    let mut piece = game_state.current_piece.take().unwrap();
-    
-    // Rotate the piece
-    piece.rotate();
-    
-    // Check if rotation causes collision with right wall
-    if game_state.check_collision(&piece) {
-        // Apply wall kick by moving left
-        piece.position.x -= 1.0;
-    }
-    
-    // Update game state
-    game_state.current_piece = Some(piece);
-    
-    // Verify piece was rotated and ideally moved
-    if let Some(ref piece) = game_state.current_piece {
-        assert_ne!(piece.shape, original_shape, "I piece should have different shape after rotation");
-        // Due to the test environment, we're checking that piece position is valid after rotation
-        assert!(!game_state.check_collision(piece), "I piece should be in valid position after rotation");
-    } else {
-        panic!("Current piece should exist");
-    }
+    assert!(!board.collides(&i_piece));
+
+    let rotated = board.try_rotate(&i_piece).expect("I piece should kick off the right wall");
+    assert_ne!(rotated.shape, i_piece.shape, "I piece should have different shape after rotation");
+    assert!(rotated.position.x < i_piece.position.x, "I piece should be kicked to the left");
+    assert!(!board.collides(&rotated), "I piece should be in valid position after rotation");
 }
 
 // Test falling pattern at high level (faster drops)
 #[test]
 fn test_high_level_drop_pattern() {
-    let mut game_state = GameState::new_test();
-    
-    // Set a high level
-    game_state.level = 10;
-    
-    // Get drop speed
-    let drop_interval = game_state.drop_speed();
-    
-    // Higher level should have smaller drop interval (faster drops)
-    // Based on game formula: base_interval / (1 + level_factor * (level - 1))
-    // With base_interval = 1.0 and level_factor = 0.1, level 10 would have:
-    // 1.0 / (1 + 0.1 * 9) = 1.0 / 1.9 ≈ 0.526
-    assert!(drop_interval < 0.6, "Drop interval at level 10 should be less than 0.6 seconds");
-    
-    // Compare with level 1
-    game_state.level = 1;
-    let level1_drop_interval = game_state.drop_speed();
-    
+    let mut game = Game::with_seed(7);
+    let level1_drop_interval = game.drop_speed();
+
+    // 90 lines make level 10
+    while game.lines_cleared() < 88 {
+        clear_with_i_piece(&mut game, 4);
+    }
+    clear_with_i_piece(&mut game, 2);
+    assert_eq!(game.level(), 10);
+    let drop_interval = game.drop_speed();
+
     // Level 10 should drop faster than level 1
-    assert!(drop_interval < level1_drop_interval, 
-           "Level 10 should drop faster than level 1");
-           
-    // The ratio should match the formula
-    let expected_ratio = 1.0 / (1.0 + 0.1 * 9.0);
+    assert!(drop_interval < level1_drop_interval, "Level 10 should drop faster than level 1");
+
+    // The interval follows DROP_TIME / (1 + 0.1 * level)
+    let expected_ratio = 1.1 / 2.0;
     let actual_ratio = drop_interval / level1_drop_interval;
-    let tolerance = 0.01; // Allow 1% difference due to floating point precision
-    
-    assert!((actual_ratio - expected_ratio).abs() < tolerance, 
-            "Drop speed ratio should follow the formula: base_interval / (1 + level_factor * (level - 1))");
+    assert!(
+        (actual_ratio - expected_ratio).abs() < 0.01,
+        "Drop speed ratio should follow the formula: base_interval / (1 + level_factor * level)"
+    );
 }
 
-// Test locking delay mechanics (piece should lock after landing)
+// Test gravity over time: the piece falls at the level's speed and locks at the bottom
 #[test]
 fn test_locking_delay() {
-    let mut game_state = GameState::new_test();
-    
-    // Clear the board
-    for row in &mut game_state.board {
-        for cell in row.iter_mut() {
-            *cell = Color::BLACK;
-        }
-    }
-    
-    // Create a test piece
+    let mut game = Game::new();
     let mut test_piece = Tetromino::new(TetrominoType::O);
     test_piece.position.x = 4.0;
-    test_piece.position.y = GRID_HEIGHT as f32 - 2.0; // Just above the bottom
-    game_state.current_piece = Some(test_piece);
-    
-    // Get original color of the piece
-    let piece_color = game_state.current_piece.as_ref().unwrap().color;
-    
-    // Manual lock implementation
-    if let Some(piece) = &game_state.current_piece {
-        let piece_width = piece.shape[0].len() as i32;
-        let piece_height = piece.shape.len() as i32;
-        let piece_x = piece.position.x.round() as i32;
-        let piece_y = piece.position.y.round() as i32;
-        
-        // Place piece onto the board
-        for y in 0..piece_height {
-            for x in 0..piece_width {
-                if piece.shape[y as usize][x as usize] {
-                    let board_x = piece_x + x;
-                    let board_y = piece_y + y;
-                    
-                    // Only place on board if within bounds
-                    if board_x >= 0 && board_x < GRID_WIDTH && board_y >= 0 && board_y < GRID_HEIGHT {
-                        game_state.board[board_y as usize][board_x as usize] = piece_color;
-                    }
-                }
-            }
-        }
-    }
-    
-    // Verify the piece was placed onto the board
-    // O piece should fill a 2x2 area
-    let y = GRID_HEIGHT as usize - 2;
-    let x = 4 as usize;
-    assert_eq!(game_state.board[y][x], piece_color, "Board cell at bottom should have piece color");
-    assert_eq!(game_state.board[y][x+1], piece_color, "Board cell at bottom should have piece color");
-    assert_eq!(game_state.board[y+1][x], piece_color, "Board cell at bottom should have piece color");
-    assert_eq!(game_state.board[y+1][x+1], piece_color, "Board cell at bottom should have piece color");
-}
+    test_piece.position.y = GRID_HEIGHT as f32 - 3.0;
+    let piece_color = test_piece.color;
+    game.set_current_piece(test_piece);
 
-// Test complete game flow from title to playing to game over to high scores
-#[test]
-fn test_complete_game_flow() {
-    let mut game_state = GameState::new_test();
-    
-    // Start at title screen
-    game_state.screen = GameScreen::Title;
-    assert_eq!(game_state.screen, GameScreen::Title, "Should start on title screen");
-    
-    // Transition to Playing (simulating key press)
-    game_state.screen = GameScreen::Playing;
-    assert_eq!(game_state.screen, GameScreen::Playing, "Should transition to playing screen");
-    
-    // Trigger game over
-    game_state.screen = GameScreen::GameOver;
-    assert_eq!(game_state.screen, GameScreen::GameOver, "Should transition to game over screen");
-    
-    // Set up score to qualify for high score
-    game_state.score = 1000;
-    game_state.high_scores = HighScores::new();
-    
-    // Check high score and transition to name entry
-    let qualifies = game_state.check_high_score();
-    assert!(qualifies, "Score should qualify for high score");
-    
-    if qualifies {
-        game_state.screen = GameScreen::EnterName;
-    }
-    assert_eq!(game_state.screen, GameScreen::EnterName, "Should transition to name entry screen");
-    
-    // Enter name and submit
-    game_state.current_name = "TESTER".to_string();
-    let added = game_state.add_high_score();
-    assert!(added, "High score should be added successfully");
-    
-    // View high scores
-    game_state.screen = GameScreen::HighScores;
-    assert_eq!(game_state.screen, GameScreen::HighScores, "Should transition to high scores screen");
-    
-    // Return to title screen
-    game_state.screen = GameScreen::Title;
-    assert_eq!(game_state.screen, GameScreen::Title, "Should return to title screen");
-}
+    // Less than a drop interval doesn't move the piece
+    assert_eq!(game.update(game.drop_speed() / 2.0), StepResult::default());
+    assert_eq!(game.current_piece().unwrap().position.y, GRID_HEIGHT as f32 - 3.0);
 
-// Test pause and resume functionality with screen transitions
-#[test]
-fn test_pause_resume_transitions() {
-    let mut game_state = GameState::new_test();
-    
-    // Start in playing mode
-    game_state.screen = GameScreen::Playing;
-    assert_eq!(game_state.screen, GameScreen::Playing, "Should start in playing mode");
-    assert!(!game_state.paused, "Game should start unpaused");
-    
-    // Pause the game
-    game_state.paused = true;
-    assert!(game_state.paused, "Game should be paused");
-    
-    // Screen should remain in Playing mode even when paused
-    assert_eq!(game_state.screen, GameScreen::Playing, "Screen should remain in Playing mode when paused");
-    
-    // Verify game state is preserved during pause/resume
-    game_state.score = 500;
-    game_state.level = 2;
-    game_state.paused = true;
-    
-    // State should be preserved while paused
-    assert_eq!(game_state.score, 500, "Score should be preserved while paused");
-    assert_eq!(game_state.level, 2, "Level should be preserved while paused");
-    
-    // Resume and verify state is still correct
-    game_state.paused = false;
-    assert_eq!(game_state.score, 500, "Score should be preserved after resuming");
-    assert_eq!(game_state.level, 2, "Level should be preserved after resuming");
-}
+    // One interval moves it onto the floor, the next locks it
+    game.update(game.drop_speed());
+    assert_eq!(game.current_piece().unwrap().position.y, GRID_HEIGHT as f32 - 2.0);
+    let result = game.update(game.drop_speed());
+    assert!(result.locked.is_some(), "Piece should lock once it can't fall");
 
-// Test transition from game over to title if score doesn't qualify for high score
-#[test]
-fn test_game_over_to_title_transition() {
-    let mut game_state = GameState::new_test();
-    
-    // Setup high scores with high minimum score
-    game_state.high_scores = HighScores::new();
-    for i in 0..MAX_HIGH_SCORES {
-        game_state.high_scores.add_score(format!("Player{}", i), 5000 + i as u32);
-    }
-    
-    // Set a score that won't qualify
-    game_state.score = 100;
-    
-    // Trigger game over
-    game_state.screen = GameScreen::GameOver;
-    
-    // Check for high score qualification
-    let qualifies = game_state.check_high_score();
-    assert!(!qualifies, "Score should not qualify for high score");
-    
-    // This should go to title screen instead of enter name
-    if !qualifies {
-        game_state.screen = GameScreen::Title;
-    }
-    
-    assert_eq!(game_state.screen, GameScreen::Title, "Should transition directly to title screen when score doesn't qualify");
+    // O piece should fill a 2x2 area at the bottom
+    let y = GRID_HEIGHT as usize - 2;
+    let x = 4_usize;
+    assert_eq!(game.board()[y][x], piece_color, "Board cell at bottom should have piece color");
+    assert_eq!(game.board()[y][x + 1], piece_color, "Board cell at bottom should have piece color");
+    assert_eq!(game.board()[y + 1][x], piece_color, "Board cell at bottom should have piece color");
+    assert_eq!(game.board()[y + 1][x + 1], piece_color, "Board cell at bottom should have piece color");
 }
 
-// Test screen transitions using key inputs
+// Test that a frontend can hold completed rows on the board while it animates them
 #[test]
-fn test_key_triggered_transitions() {
-    let mut game_state = GameState::new_test();
-    
-    // Start at title screen
-    game_state.screen = GameScreen::Title;
-    
-    // Simulating pressing 'H' key on title screen to view high scores
-    // This is what would happen in the key_down_event handler for H key
-    game_state.screen = GameScreen::HighScores;
-    assert_eq!(game_state.screen, GameScreen::HighScores, "Should transition to high scores after pressing H");
-    
-    // Simulating pressing any key from high scores to return to title
-    game_state.screen = GameScreen::Title;
-    assert_eq!(game_state.screen, GameScreen::Title, "Should return to title screen after pressing any key");
-    
-    // Simulate game start (any key press on title screen)
-    game_state.screen = GameScreen::Playing;
-    assert_eq!(game_state.screen, GameScreen::Playing, "Should start game after pressing any key on title screen");
-    
-    // Simulate pause
-    game_state.paused = true;
-    assert!(game_state.paused, "Game should pause after pressing P");
-    
-    // Simulate unpause
-    game_state.paused = false;
-    assert!(!game_state.paused, "Game should unpause after pressing P again");
-}
+fn test_line_clear_delay() {
+    let mut game = Game::with_seed(8);
+    game.set_line_clear_delay(0.5);
 
-// Test name input screen interaction
-#[test]
-fn test_name_input_interaction() {
-    let mut game_state = GameState::new_test();
-    
-    // Set up a score that qualifies for high score
-    game_state.score = 1000;
-    game_state.high_scores = HighScores::new();
-    
-    // Navigate to name entry screen
-    game_state.screen = GameScreen::EnterName;
-    assert_eq!(game_state.screen, GameScreen::EnterName, "Should be on name entry screen");
-    
-    // Test typing characters (simulating key presses)
-    game_state.current_name.push('T');
-    game_state.current_name.push('E');
-    game_state.current_name.push('S');
-    game_state.current_name.push('T');
-    assert_eq!(game_state.current_name, "TEST", "Name should be updated as keys are pressed");
-    
-    // Test backspace (simulating backspace key)
-    game_state.current_name.pop();
-    assert_eq!(game_state.current_name, "TES", "Backspace should remove last character");
-    
-    // Test name input (add 15 characters)
-    game_state.current_name = "TESTTESTTEST123".to_string(); // 15 characters
-    
-    // The name length limit is enforced in the key_down_event handler in the actual game,
-    // but not directly in the current_name field itself, so we just check it can hold the name
-    assert_eq!(game_state.current_name.len(), 15, "Name should be 15 characters long");
-    
-    // Test submitting name (simulating Enter key)
-    let added = game_state.add_high_score();
-    assert!(added, "High score should be added");
-    
-    // This would transition to high scores screen
-    game_state.screen = GameScreen::HighScores;
-    assert_eq!(game_state.screen, GameScreen::HighScores, "Should transition to high scores after submitting name");
-    
-    // Verify the high score was added
-    assert!(game_state.high_scores.entries.len() > 0, "High score list should have entries");
-    assert_eq!(game_state.high_scores.entries[0].score, 1000, "Score should be added with correct value");
-}
+    let (result, _) = clear_with_i_piece(&mut game, 2);
+    assert!(result.locked.is_some());
+    assert!(!result.lock_finished, "The lock should wait for the rows to clear");
+    assert!(game.is_clearing());
+    assert_eq!(game.board().full_rows().len(), 2, "Completed rows should stay until the delay is over");
 
-// Test reset game state when starting a new game
-#[test]
-fn test_reset_game_state() {
-    let mut game_state = GameState::new_test();
-    
-    // Set some game state
-    game_state.score = 1000;
-    game_state.level = 5;
-    game_state.lines_cleared = 45;
-    
-    // Fill some of the board
-    for y in 10..GRID_HEIGHT as usize {
-        for x in 0..GRID_WIDTH as usize {
-            game_state.board[y][x] = Color::RED;
-        }
-    }
-    
-    // Reset game (simulating starting a new game from title screen)
-    game_state.board = vec![vec![Color::BLACK; GRID_WIDTH as usize]; GRID_HEIGHT as usize];
-    game_state.score = 0;
-    game_state.level = 1;
-    game_state.lines_cleared = 0;
-    game_state.screen = GameScreen::Playing;
-    
-    // Verify game state was reset
-    assert_eq!(game_state.score, 0, "Score should be reset to 0");
-    assert_eq!(game_state.level, 1, "Level should be reset to 1");
-    assert_eq!(game_state.lines_cleared, 0, "Lines cleared should be reset to 0");
-    
-    // Verify board was cleared
-    for y in 0..GRID_HEIGHT as usize {
-        for x in 0..GRID_WIDTH as usize {
-            assert_eq!(game_state.board[y][x], Color::BLACK, "Board should be cleared");
-        }
-    }
+    let result = game.update(0.5);
+    assert!(result.lock_finished);
+    assert_eq!(result.lines_cleared, 2);
+    assert!(game.current_piece().is_some(), "The next piece should spawn once the rows are cleared");
 }
 
-// Test UI component properties and rendering on different screens
+// Test hold during play
 #[test]
-fn test_ui_component_properties() {
-    let game_state = GameState::new_test();
-    
-    // Constants for rendering components should have valid values
-    assert!(GRID_SIZE > 0.0, "Grid size should be positive");
-    assert!(MARGIN > 0.0, "Margin should be positive");
-    assert!(PREVIEW_BOX_SIZE > 0.0, "Preview box size should be positive");
-    
-    // Preview box position should be valid
-    let preview_box_width = GRID_SIZE * PREVIEW_BOX_SIZE;
-    let preview_box_height = GRID_SIZE * PREVIEW_BOX_SIZE;
-    let game_field_right = MARGIN + GRID_SIZE * GRID_WIDTH as f32;
-    
-    // Preview box should be to the right of the game field
-    assert!(PREVIEW_X > game_field_right, "Preview box should be to the right of the game field");
-    // Preview box should fit within screen bounds
-    assert!(PREVIEW_X + preview_box_width <= SCREEN_WIDTH, "Preview box should fit within screen width");
-    assert!(PREVIEW_Y + preview_box_height <= SCREEN_HEIGHT, "Preview box should fit within screen height");
-}
+fn test_hold() {
+    let mut game = Game::new();
+    let first = game.current_piece().unwrap().kind;
 
-#[test]
-fn test_score_panel_positioning() {
-    let game_state = GameState::new_test();
-    
-    // Score panel should be positioned below the preview box
-    let panel_top = PREVIEW_Y + GRID_SIZE * 6.0 + 20.0;
-    let panel_width = GRID_SIZE * 6.0;
-    let panel_height = GRID_SIZE * 6.0;
-    
-    // Score panel should fit within screen bounds
-    assert!(PREVIEW_X - GRID_SIZE + panel_width <= SCREEN_WIDTH, "Score panel should fit within screen width");
-    assert!(panel_top + panel_height <= SCREEN_HEIGHT, "Score panel should fit within screen height");
-}
+    let result = game.apply(Move::Hold);
+    assert!(result.spawned.is_some(), "Holding into an empty box should spawn the next piece");
+    assert_eq!(game.hold_piece().unwrap().kind, first);
+    assert!(!game.apply(Move::Hold).moved, "Hold should only work once per piece");
 
-#[test]
-fn test_high_score_display_format() {
-    let mut game_state = GameState::new_test();
-    
-    // Populate high scores with test data
-    game_state.high_scores = HighScores::new();
-    game_state.high_scores.add_score("PLAYER1".to_string(), 1000);
-    game_state.high_scores.add_score("PLAYER2".to_string(), 2000);
-    game_state.high_scores.add_score("PLAYER3".to_string(), 3000);
-    
-    // Verify order - highest scores should come first
-    assert_eq!(game_state.high_scores.entries[0].name, "PLAYER3", "Highest score should be first");
-    assert_eq!(game_state.high_scores.entries[0].score, 3000, "Highest score value should be correct");
-    assert_eq!(game_state.high_scores.entries[1].name, "PLAYER2", "Second highest score should be second");
-    assert_eq!(game_state.high_scores.entries[2].name, "PLAYER1", "Lowest score should be last");
-    
-    // Verify column positions are properly spaced
-    let rank_x = SCREEN_WIDTH * 0.25;
-    let name_x = SCREEN_WIDTH * 0.45;
-    let score_x = SCREEN_WIDTH * 0.75;
-    
-    assert!(rank_x < name_x, "Rank column should be to the left of name column");
-    assert!(name_x < score_x, "Name column should be to the left of score column");
-    assert!(score_x < SCREEN_WIDTH, "Score column should be within screen bounds");
+    game.apply(Move::HardDrop);
+    assert!(game.can_hold(), "Hold should be available again for the next piece");
 }
 
+// Test starting over after a game
 #[test]
-fn test_title_screen_elements() {
-    let mut game_state = GameState::new_test();
-    
-    // Set screen to Title
-    game_state.screen = GameScreen::Title;
-    assert_eq!(game_state.screen, GameScreen::Title, "Game screen should be set to Title");
-    
-    // Title screen should have blinking text functionality
-    // We'll just verify the properties exist and are initialized correctly
-    assert!(game_state.blink_timer >= 0.0, "Blink timer should be initialized");
-    // The text visibility flag should be a boolean
-    assert!(game_state.show_text || !game_state.show_text, "Show text should be a boolean");
-}
+fn test_reset_game_state() {
+    let mut game = Game::new();
+    clear_with_i_piece(&mut game, 4);
+    game.apply(Move::HardDrop);
+    assert!(game.score() > 0);
 
-#[test]
-fn test_name_input_display() {
-    let mut game_state = GameState::new_test();
-    
-    // Set screen to EnterName
-    game_state.screen = GameScreen::EnterName;
-    assert_eq!(game_state.screen, GameScreen::EnterName, "Game screen should be set to EnterName");
-    
-    // Test cursor blink properties
-    assert!(game_state.cursor_blink_timer >= 0.0, "Cursor blink timer should be initialized");
-    // The cursor visibility flag should be a boolean
-    assert!(game_state.show_cursor || !game_state.show_cursor, "Show cursor should be a boolean");
-    
-    // Verify current_name display
-    game_state.current_name = "TEST".to_string();
-    assert_eq!(game_state.current_name, "TEST", "Name input should display correctly");
-    
-    // Testing name is displayed without modification
-    game_state.current_name = "PLAYER 1".to_string();
-    assert_eq!(game_state.current_name, "PLAYER 1", "Name with spaces should display correctly");
+    // A new game starts from scratch
+    let game = Game::new();
+    assert_eq!(game.score(), 0, "Score should be reset to 0");
+    assert_eq!(game.level(), 1, "Level should be reset to 1");
+    assert_eq!(game.lines_cleared(), 0, "Lines cleared should be reset to 0");
+    assert_eq!(game.board(), &GameBoard::new(), "Board should be cleared");
 }
 
 #[test]
-fn test_pause_screen_overlay() {
-    let mut game_state = GameState::new_test();
-    
-    // Set game to playing state
-    game_state.screen = GameScreen::Playing;
-    assert_eq!(game_state.screen, GameScreen::Playing, "Game screen should be set to Playing");
-    
-    // Test pause state
-    assert!(!game_state.paused, "Game should start unpaused");
-    
-    // Set paused state
-    game_state.paused = true;
-    assert!(game_state.paused, "Game should be paused");
-    
-    // Screen should remain in Playing mode even when paused
-    assert_eq!(game_state.screen, GameScreen::Playing, "Screen should remain in Playing mode when paused");
-}
+fn test_high_score_display_format() {
+    let mut high_scores = HighScores::new();
+    high_scores.add_score("PLAYER1".to_string(), 1000);
+    high_scores.add_score("PLAYER2".to_string(), 2000);
+    high_scores.add_score("PLAYER3".to_string(), 3000);
 
-#[test]
-fn test_game_over_screen_elements() {
-    let mut game_state = GameState::new_test();
-    
-    // Set screen to GameOver
-    game_state.screen = GameScreen::GameOver;
-    assert_eq!(game_state.screen, GameScreen::GameOver, "Game screen should be set to GameOver");
-    
-    // Test that the blinking properties exist
-    assert!(game_state.blink_timer >= 0.0, "Blink timer should be initialized");
-    // The text visibility flag should be a boolean
-    assert!(game_state.show_text || !game_state.show_text, "Show text should be a boolean");
+    // Verify order - highest scores should come first
+    assert_eq!(high_scores.entries[0].name, "PLAYER3", "Highest score should be first");
+    assert_eq!(high_scores.entries[0].score, 3000, "Highest score value should be correct");
+    assert_eq!(high_scores.entries[1].name, "PLAYER2", "Second highest score should be second");
+    assert_eq!(high_scores.entries[2].name, "PLAYER1", "Lowest score should be last");
 }
-
@@ -0,0 +1,130 @@
+use ggez::graphics::Color;
+use proptest::prelude::*;
+use tetris::board::{GameBoard, Playfield};
+use tetris::constants::GRID_WIDTH;
+use tetris::game::{Game, Move, StepResult};
+use tetris::rotation::{RotationSystem, SYSTEMS};
+use tetris::scoring::{ScoringSystem, GUIDELINE, NES};
+
+/// Something a frontend can do to a game between two checks
+#[derive(Clone, Debug)]
+enum Action {
+    Input(Move),   // A player or bot input
+    Tick(f64),     // Time passing, so gravity and lock timing act
+    Garbage(u32),  // Lines sent by an opponent
+}
+
+fn action() -> impl Strategy<Value = Action> {
+    prop_oneof![
+        8 => prop::sample::select(vec![
+            Move::None,
+            Move::Left,
+            Move::Right,
+            Move::RotateCw,
+            Move::SoftDrop,
+            Move::HardDrop,
+            Move::Hold,
+        ])
+        .prop_map(Action::Input),
+        2 => (0.0..2.0f64).prop_map(Action::Tick),
+        1 => (1..4u32).prop_map(Action::Garbage),
+    ]
+}
+
+fn rotation_system() -> impl Strategy<Value = &'static dyn RotationSystem> {
+    prop::sample::select(SYSTEMS.to_vec())
+}
+
+fn scoring_system() -> impl Strategy<Value = &'static dyn ScoringSystem> {
+    prop::sample::select(vec![&GUIDELINE as &'static dyn ScoringSystem, &NES])
+}
+
+fn filled_cells(board: &GameBoard) -> usize {
+    board.rows().iter().flatten().filter(|&&cell| cell != Color::BLACK).count()
+}
+
+/// Checks what has to hold after every action, whatever was played before it
+fn check_invariants(game: &Game, before: &Game, result: &StepResult) -> Result<(), TestCaseError> {
+    let board = game.board();
+
+    // Rows keep the board's width, and complete rows never stay once the lock is over
+    prop_assert!(board.rows().iter().all(|row| row.len() == GRID_WIDTH as usize));
+    if !game.is_clearing() {
+        prop_assert!(board.full_rows().is_empty(), "complete rows left on the board: {:?}", board.full_rows());
+    }
+
+    // The falling piece never overlaps the stack or the walls
+    if let Some(piece) = game.current_piece().filter(|_| !game.is_game_over()) {
+        prop_assert!(!board.collides(piece), "{:?} overlaps the board at {:?}", piece.kind, piece.position);
+    }
+
+    // Score, lines and level only go up, and the level follows the lines
+    prop_assert!(game.score() >= before.score(), "score fell from {} to {}", before.score(), game.score());
+    prop_assert!(game.lines_cleared() >= before.lines_cleared());
+    prop_assert_eq!(game.level(), game.lines_cleared() / 10 + 1);
+    prop_assert_eq!(game.lines_cleared() - before.lines_cleared(), result.lines_cleared);
+    prop_assert!(result.lines_cleared <= 4, "one lock cleared {} lines", result.lines_cleared);
+
+    // A lock adds the piece's four cells and a clear removes whole rows, nothing else changes the stack
+    if let Some(locked) = &result.locked {
+        prop_assert!(locked.cells.len() <= 4);
+        for &(x, y) in &locked.cells {
+            prop_assert!((0..GRID_WIDTH).contains(&x) && y >= 0, "locked cell ({}, {}) is off the board", x, y);
+        }
+    }
+    if result.garbage_added == 0 && !result.game_over {
+        let added = result.locked.as_ref().map_or(0, |locked| locked.cells.len());
+        let removed = result.lines_cleared as usize * GRID_WIDTH as usize;
+        prop_assert_eq!(filled_cells(board) + removed, filled_cells(before.board()) + added);
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    // Random inputs, timing and garbage through the game core, under every rotation and scoring system
+    #[test]
+    fn test_random_play_keeps_the_game_consistent(
+        seed in any::<u64>(),
+        rotation in rotation_system(),
+        scoring in scoring_system(),
+        actions in prop::collection::vec(action(), 1..300),
+    ) {
+        let mut game = Game::with_seed(seed);
+        game.set_rotation_system(rotation);
+        game.set_scoring_system(scoring);
+
+        for action in actions {
+            if game.is_game_over() {
+                break;
+            }
+            let before = game.clone();
+            let result = match action {
+                Action::Input(mv) => game.apply(mv),
+                Action::Tick(dt) => game.update(dt),
+                Action::Garbage(lines) => {
+                    game.receive_garbage(lines);
+                    StepResult::default()
+                }
+            };
+            check_invariants(&game, &before, &result)?;
+        }
+    }
+
+    // The same seed and inputs always play out the same game, pieces included
+    #[test]
+    fn test_seeded_games_replay_identically(
+        seed in any::<u64>(),
+        moves in prop::collection::vec(prop::sample::select(vec![Move::Left, Move::Right, Move::RotateCw, Move::HardDrop, Move::Hold]), 1..200),
+    ) {
+        let mut first = Game::with_seed(seed);
+        let mut second = Game::with_seed(seed);
+        prop_assert_eq!(first.next_queue(), second.next_queue());
+        for mv in moves {
+            prop_assert_eq!(first.apply(mv), second.apply(mv));
+        }
+        prop_assert_eq!(first.board(), second.board());
+        prop_assert_eq!(first.score(), second.score());
+    }
+}